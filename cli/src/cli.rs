@@ -17,6 +17,7 @@
 //! Polkadot CLI library.
 
 use clap::Parser;
+use std::path::PathBuf;
 
 #[allow(missing_docs)]
 #[derive(Debug, Parser)]
@@ -73,6 +74,37 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Check the consistency of the approval voting data in the parachains DB.
+	ApprovalDb(ApprovalDbCmd),
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Parser)]
+pub struct ApprovalDbCmd {
+	/// Remove orphaned entries found by the check.
+	///
+	/// Without this flag the database is only checked, not modified.
+	#[clap(long)]
+	pub prune: bool,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: sc_cli::DatabaseParams,
+}
+
+impl sc_cli::CliConfiguration for ApprovalDbCmd {
+	fn shared_params(&self) -> &sc_cli::SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&sc_cli::DatabaseParams> {
+		Some(&self.database_params)
+	}
 }
 
 #[allow(missing_docs)]
@@ -119,9 +151,44 @@ pub struct RunCmd {
 	///
 	/// Must be valid socket address, of format `IP:Port`
 	/// commonly `127.0.0.1:6831`.
-	#[clap(long)]
+	///
+	/// Deprecated, export spans using OTLP with `--otlp-endpoint` instead.
+	#[clap(long, conflicts_with = "otlp-endpoint")]
 	pub jaeger_agent: Option<String>,
 
+	/// Add the destination address of the OpenTelemetry collector to export jaeger spans to,
+	/// using OTLP over HTTP.
+	///
+	/// Must be valid socket address, of format `IP:Port`
+	/// commonly `127.0.0.1:4318`.
+	#[clap(long, value_name = "IP:PORT")]
+	pub otlp_endpoint: Option<String>,
+
+	/// The ratio of traces exported to the OpenTelemetry collector, between `0.0` and `1.0`.
+	///
+	/// Applies to all root spans without a ratio of their own, see `--otlp-span-sampling-ratio`.
+	#[clap(long, value_name = "RATIO")]
+	pub otlp_sampling_ratio: Option<f64>,
+
+	/// The ratio of traces exported for root spans of the given name, e.g.
+	/// `approval-voting=0.1`.
+	///
+	/// Can be passed multiple times.
+	#[clap(long, value_name = "SPAN=RATIO")]
+	pub otlp_span_sampling_ratio: Vec<String>,
+
+	/// The maximum number of spans sent to the OpenTelemetry collector in a single request.
+	#[clap(long, value_name = "COUNT")]
+	pub otlp_max_batch_size: Option<usize>,
+
+	/// Send incomplete batches of spans after this many milliseconds.
+	#[clap(long, value_name = "MILLIS")]
+	pub otlp_batch_interval: Option<u64>,
+
+	/// The maximum number of spans waiting to be sent, any spans beyond are dropped.
+	#[clap(long, value_name = "COUNT")]
+	pub otlp_max_queued_spans: Option<usize>,
+
 	/// Add the destination address to the `pyroscope` agent.
 	///
 	/// Must be valid socket address, of format `IP:Port`
@@ -144,6 +211,144 @@ pub struct RunCmd {
 	/// **Dangerous!** Do not touch unless explicitly adviced to.
 	#[clap(long)]
 	pub overseer_channel_capacity_override: Option<usize>,
+
+	/// How long to keep availability data of candidates not included in any block, in seconds.
+	#[clap(long)]
+	pub av_store_keep_unavailable_for: Option<u64>,
+
+	/// How long to keep availability data of candidates included in a finalized block, in seconds.
+	///
+	/// **Dangerous!** Lowering this below the dispute period breaks disputes.
+	#[clap(long)]
+	pub av_store_keep_finalized_for: Option<u64>,
+
+	/// How often to prune the availability store, in seconds.
+	#[clap(long)]
+	pub av_store_pruning_interval: Option<u64>,
+
+	/// Soft limit on the disk space used by availability data, in MiB.
+	///
+	/// When exceeded, the oldest finalized data is pruned ahead of time, but never within the
+	/// dispute period. Other data is kept, so the quota is not a hard limit.
+	#[clap(long)]
+	pub av_store_disk_quota: Option<u64>,
+
+	/// Archive the available data of finalized candidates for the given number of sessions.
+	///
+	/// Archived data is kept after the chunks are pruned and can be used to serve historic PoVs.
+	#[clap(long)]
+	pub av_store_archive_sessions: Option<u32>,
+
+	/// The maximum number of unfinalized block heights kept in the approval voting database.
+	///
+	/// Fully approved blocks further below the best block are pruned even if they are not
+	/// finalized yet, blocks which are still being approved are always kept. Unfinalized
+	/// blocks are not pruned by default.
+	#[clap(long, value_name = "HEIGHTS")]
+	pub approval_db_max_unfinalized_heights: Option<u32>,
+
+	/// Archive to recover availability data from if the network can't provide it.
+	///
+	/// Either an `http://` URL, queried with `GET <URL>/<candidate hash>`, or a local directory
+	/// with one file per candidate hash. Both serve the SCALE encoded available data.
+	#[clap(long)]
+	pub availability_archive: Option<String>,
+
+	/// Always keep a validation peer-set connection to the given authority.
+	///
+	/// Takes the SS58 encoded authority discovery key. Can be passed multiple times.
+	#[clap(long = "pin-authority", value_name = "AUTHORITY_ID")]
+	pub pinned_authorities: Vec<String>,
+
+	/// Never connect to the given authority, on any peer-set.
+	///
+	/// Takes the SS58 encoded authority discovery key. Can be passed multiple times.
+	#[clap(long = "ban-authority", value_name = "AUTHORITY_ID")]
+	pub banned_authorities: Vec<String>,
+
+	/// Rate limit the notifications every peer sends on the validation peer-set, allowing this
+	/// many in one go.
+	///
+	/// Notifications aren't rate limited by default.
+	#[clap(long, value_name = "COUNT", requires = "validation-rate-limit-per-second")]
+	pub validation_rate_limit_burst: Option<u32>,
+
+	/// The number of notifications per second a peer regains on the validation peer-set when
+	/// it is rate limited.
+	#[clap(long, value_name = "COUNT", requires = "validation-rate-limit-burst")]
+	pub validation_rate_limit_per_second: Option<u32>,
+
+	/// Rate limit the notifications every peer sends on the collation peer-set, allowing this
+	/// many in one go.
+	///
+	/// Notifications aren't rate limited by default.
+	#[clap(long, value_name = "COUNT", requires = "collation-rate-limit-per-second")]
+	pub collation_rate_limit_burst: Option<u32>,
+
+	/// The number of notifications per second a peer regains on the collation peer-set when
+	/// it is rate limited.
+	#[clap(long, value_name = "COUNT", requires = "collation-rate-limit-burst")]
+	pub collation_rate_limit_per_second: Option<u32>,
+
+	/// Report a subsystem as stalled once it left its messages waiting for this many seconds.
+	#[clap(long, value_name = "SECS")]
+	pub stall_message_threshold: Option<u64>,
+
+	/// Report a subsystem as stalled once it left its signals waiting for this many seconds.
+	#[clap(long, value_name = "SECS")]
+	pub stall_signal_threshold: Option<u64>,
+
+	/// Share of the messages between subsystems whose time spent in the channel is measured,
+	/// in parts per thousand.
+	#[clap(long, value_name = "PER_MILLE")]
+	pub message_tof_sample_per_mille: Option<u16>,
+
+	/// Log measured messages between subsystems that spent at least this many milliseconds
+	/// in the channel.
+	#[clap(long, value_name = "MILLIS")]
+	pub message_tof_trace_threshold: Option<u64>,
+
+	/// Log the inputs waiting for a subsystem once it stalls, per channel and by message kind.
+	///
+	/// Counting the waiting messages by kind has a small cost on every message sent.
+	#[clap(long)]
+	pub dump_stalled_subsystem_inputs: bool,
+
+	/// Periodically export the graph of subsystems and the messages they exchange to a file.
+	///
+	/// Written as JSON if the path ends in `.json`, in graphviz DOT format otherwise. Edges are
+	/// annotated with the number of messages sent, subsystems with their pending messages.
+	#[clap(long, value_name = "PATH")]
+	pub subsystem_graph_export: Option<PathBuf>,
+
+	/// How often to export the subsystem graph, in seconds.
+	#[clap(long, value_name = "SECS", default_value = "60")]
+	pub subsystem_graph_export_interval: u64,
+
+	/// Record the signals and messages received by the approval voting and dispute coordinator
+	/// subsystems, and the responses to their requests, into the given directory.
+	///
+	/// Each subsystem is recorded into its own file per start of the node,
+	/// `approval-voting-<millis>.rec` and `dispute-coordinator-<millis>.rec`, named after the
+	/// time of the start. Recordings can be replayed against the subsystems in tests.
+	#[clap(long, value_name = "DIR")]
+	pub record_subsystem_inputs: Option<PathBuf>,
+
+	/// Arrange validators into a random gossip topology with about `K` neighbors each, instead
+	/// of the 2D grid.
+	///
+	/// Meant for evaluating topologies on test networks: all validators of a network need to use
+	/// the same topology.
+	#[clap(long, value_name = "K")]
+	pub gossip_topology_random_k: Option<usize>,
+
+	/// Also write the log lines of the parachain subsystems to the given file as JSON, one
+	/// object per line.
+	///
+	/// The keys of the objects are stable, see the `tracing-gum` crate for the schema. The
+	/// regular log output is not affected.
+	#[clap(long, value_name = "PATH")]
+	pub log_json: Option<PathBuf>,
 }
 
 #[allow(missing_docs)]
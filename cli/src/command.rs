@@ -17,11 +17,11 @@
 use crate::cli::{Cli, Subcommand};
 use frame_benchmarking_cli::{BenchmarkCmd, SUBSTRATE_REFERENCE_HARDWARE};
 use futures::future::TryFutureExt;
-use log::info;
+use log::{info, warn};
 use sc_cli::{Role, RuntimeVersion, SubstrateCli};
 use service::{self, HeaderBackend, IdentifyVariant};
 use sp_core::crypto::Ss58AddressFormatRegistry;
-use std::net::ToSocketAddrs;
+use std::{net::ToSocketAddrs, sync::Arc, time::Duration};
 
 pub use crate::{error::Error, service::BlockId};
 pub use polkadot_performance_test::PerfCheckError;
@@ -274,6 +274,15 @@ where
 		.map_err(Error::from)?;
 	let chain_spec = &runner.config().chain_spec;
 
+	if let Some(ref path) = cli.run.log_json {
+		let output = std::fs::OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(path)
+			.map_err(Error::LogJsonOutput)?;
+		gum::enable_json_output(output).map_err(Error::LogJsonOutput)?;
+	}
+
 	// Disallow BEEFY on production networks.
 	if cli.run.beefy &&
 		(chain_spec.is_polkadot() || chain_spec.is_kusama() || chain_spec.is_westend())
@@ -297,18 +306,131 @@ where
 		info!("----------------------------");
 	}
 
-	let jaeger_agent = if let Some(ref jaeger_agent) = cli.run.jaeger_agent {
-		Some(
-			jaeger_agent
-				.to_socket_addrs()
-				.map_err(Error::AddressResolutionFailure)?
-				.next()
-				.ok_or_else(|| Error::AddressResolutionMissing)?,
-		)
+	if cli.run.jaeger_agent.is_some() {
+		warn!(
+			"`--jaeger-agent` is deprecated and will be removed, \
+			 export spans using OTLP with `--otlp-endpoint` instead, commonly `127.0.0.1:4318`"
+		);
+	}
+
+	let jaeger_destination = cli.run.otlp_endpoint.as_ref().or(cli.run.jaeger_agent.as_ref());
+	let jaeger_config = if let Some(destination) = jaeger_destination {
+		let addr = destination
+			.to_socket_addrs()
+			.map_err(Error::AddressResolutionFailure)?
+			.next()
+			.ok_or_else(|| Error::AddressResolutionMissing)?;
+
+		let mut builder = if cli.run.jaeger_agent.is_some() {
+			service::JaegerConfigBuilder::default().agent(addr)
+		} else {
+			service::JaegerConfigBuilder::default().collector(addr)
+		};
+		if let Some(ratio) = cli.run.otlp_sampling_ratio {
+			builder = builder.default_sampling_ratio(ratio);
+		}
+		for span_ratio in &cli.run.otlp_span_sampling_ratio {
+			let (span_name, ratio) = span_ratio
+				.split_once('=')
+				.and_then(|(span_name, ratio)| Some((span_name, ratio.parse().ok()?)))
+				.ok_or_else(|| Error::InvalidSpanSamplingRatio(span_ratio.clone()))?;
+			builder = builder.sampling_ratio(span_name, ratio);
+		}
+		if let Some(max_batch_size) = cli.run.otlp_max_batch_size {
+			builder = builder.max_batch_size(max_batch_size);
+		}
+		if let Some(batch_interval) = cli.run.otlp_batch_interval {
+			builder = builder.batch_interval(Duration::from_millis(batch_interval));
+		}
+		if let Some(max_queued_spans) = cli.run.otlp_max_queued_spans {
+			builder = builder.max_queued_spans(max_queued_spans);
+		}
+
+		Some(builder)
 	} else {
 		None
 	};
 
+	let availability_pruning_config = {
+		let defaults = service::AvailabilityPruningConfig::default();
+
+		service::AvailabilityPruningConfig {
+			keep_unavailable_for: cli
+				.run
+				.av_store_keep_unavailable_for
+				.map_or(defaults.keep_unavailable_for, Duration::from_secs),
+			keep_finalized_for: cli
+				.run
+				.av_store_keep_finalized_for
+				.map_or(defaults.keep_finalized_for, Duration::from_secs),
+			pruning_interval: cli
+				.run
+				.av_store_pruning_interval
+				.map_or(defaults.pruning_interval, Duration::from_secs),
+			disk_quota: cli.run.av_store_disk_quota.map(|mib| mib.saturating_mul(1024 * 1024)),
+			archive_sessions: cli.run.av_store_archive_sessions,
+		}
+	};
+
+	let network_rate_limits = service::NetworkRateLimitConfig {
+		validation: cli
+			.run
+			.validation_rate_limit_burst
+			.zip(cli.run.validation_rate_limit_per_second)
+			.map(|(burst, per_second)| service::NetworkRateLimit { burst, per_second }),
+		collation: cli
+			.run
+			.collation_rate_limit_burst
+			.zip(cli.run.collation_rate_limit_per_second)
+			.map(|(burst, per_second)| service::NetworkRateLimit { burst, per_second }),
+	};
+
+	let stall_watchdog_config = {
+		let defaults = service::StallWatchdogConfig::default();
+
+		service::StallWatchdogConfig {
+			message_threshold: cli
+				.run
+				.stall_message_threshold
+				.map_or(defaults.message_threshold, Duration::from_secs),
+			signal_threshold: cli
+				.run
+				.stall_signal_threshold
+				.map_or(defaults.signal_threshold, Duration::from_secs),
+			dump_pending: cli.run.dump_stalled_subsystem_inputs,
+		}
+	};
+
+	let message_tof_config = {
+		let defaults = service::MessageTofConfig::default();
+
+		service::MessageTofConfig {
+			sample_per_mille: cli
+				.run
+				.message_tof_sample_per_mille
+				.unwrap_or(defaults.sample_per_mille),
+			trace_threshold: cli
+				.run
+				.message_tof_trace_threshold
+				.map(service::CoarseDuration::from_millis)
+				.or(defaults.trace_threshold),
+		}
+	};
+
+	let gossip_support_config = {
+		let mut config = service::GossipSupportConfig::default();
+		if let Some(k) = cli.run.gossip_topology_random_k {
+			config.topology_strategy = Arc::new(service::RandomKTopology { k });
+		}
+		config
+	};
+
+	let graph_export_config =
+		cli.run.subsystem_graph_export.map(|path| service::GraphExportConfig {
+			path,
+			interval: Duration::from_secs(cli.run.subsystem_graph_export_interval),
+		});
+
 	runner.run_node_until_exit(move |config| async move {
 		let hwbench = if !cli.run.no_hardware_benchmarks {
 			config.database.path().map(|database_path| {
@@ -328,11 +450,26 @@ where
 				service::IsCollator::No,
 				grandpa_pause,
 				cli.run.beefy,
-				jaeger_agent,
+				jaeger_config,
 				None,
 				false,
 				overseer_gen,
 				cli.run.overseer_channel_capacity_override,
+				service::FullNodeOptions {
+					availability_pruning_config,
+					availability_archive: cli.run.availability_archive,
+					pinned_authorities: cli.run.pinned_authorities,
+					banned_authorities: cli.run.banned_authorities,
+					graph_export_config,
+					approval_db_max_unfinalized_heights: cli
+						.run
+						.approval_db_max_unfinalized_heights,
+					network_rate_limits,
+					subsystem_recording_dir: cli.run.record_subsystem_inputs,
+					stall_watchdog_config,
+					message_tof_config,
+					gossip_support_config,
+				},
 				hwbench,
 			)
 			.map(|full| full.task_manager)
@@ -642,6 +779,23 @@ pub fn run() -> Result<()> {
 			let runner = cli.create_runner(cmd)?;
 			Ok(runner.sync_run(|config| cmd.run::<service::Block>(&config))?)
 		},
+		Some(Subcommand::ApprovalDb(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			Ok(runner.sync_run(|config| {
+				let report = service::check_approval_db(&config.database, cmd.prune)?;
+				println!("{}", report);
+
+				if report.is_consistent() {
+					println!("The approval voting database is consistent.");
+				} else if cmd.prune {
+					println!("Orphaned entries and dangling references have been removed.");
+				} else {
+					println!("Run again with `--prune` to remove orphaned entries.");
+				}
+
+				Ok::<_, Error>(())
+			})?)
+		},
 	}?;
 
 	#[cfg(feature = "pyroscope")]
@@ -45,6 +45,12 @@ pub enum Error {
 	#[error("URL did not resolve to anything")]
 	AddressResolutionMissing,
 
+	#[error("Invalid span sampling ratio `{0}`, expected `SPAN=RATIO`")]
+	InvalidSpanSamplingRatio(String),
+
+	#[error("Failed to set up the JSON log output")]
+	LogJsonOutput(#[source] std::io::Error),
+
 	#[error("Command is not implemented")]
 	CommandNotImplemented,
 
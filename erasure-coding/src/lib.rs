@@ -96,6 +96,22 @@ pub const fn recovery_threshold(n_validators: usize) -> Result<usize, Error> {
 	Ok(needed + 1)
 }
 
+/// Obtain the number of systematic chunks, which hold the original data.
+///
+/// These are the first chunks returned by `obtain_chunks` and can be concatenated to recover
+/// the data without decoding, see `reconstruct_from_systematic`. This is never more than the
+/// `recovery_threshold`.
+pub fn systematic_recovery_threshold(n_validators: usize) -> Result<usize, Error> {
+	let threshold = recovery_threshold(n_validators)?;
+
+	// The number of data shards is rounded down to a power of two by the code parameters.
+	if threshold.is_power_of_two() {
+		Ok(threshold)
+	} else {
+		Ok(threshold.next_power_of_two() / 2)
+	}
+}
+
 fn code_params(n_validators: usize) -> Result<CodeParams, Error> {
 	// we need to be able to reconstruct from 1/3 - eps
 
@@ -204,6 +220,64 @@ where
 	Decode::decode(&mut &payload_bytes[..]).or_else(|_e| Err(Error::BadPayload))
 }
 
+/// Reconstruct the v1 available data from the systematic chunks.
+///
+/// Provide an iterator containing the data of the first `systematic_recovery_threshold`
+/// chunks, ordered by their index.
+///
+/// Works only up to 65536 validators, and `n_validators` must be non-zero.
+pub fn reconstruct_from_systematic_v1<'a, I: 'a>(
+	n_validators: usize,
+	chunks: I,
+) -> Result<AvailableData, Error>
+where
+	I: IntoIterator<Item = &'a [u8]>,
+{
+	reconstruct_from_systematic(n_validators, chunks)
+}
+
+/// Reconstruct decodable data from the systematic chunks.
+///
+/// Provide an iterator containing the data of the first `systematic_recovery_threshold`
+/// chunks, ordered by their index. As the code is systematic, these chunks contain the encoded
+/// data as is, so they are concatenated instead of being decoded.
+///
+/// Works only up to 65536 validators, and `n_validators` must be non-zero.
+pub fn reconstruct_from_systematic<'a, I: 'a, T: Decode>(
+	n_validators: usize,
+	chunks: I,
+) -> Result<T, Error>
+where
+	I: IntoIterator<Item = &'a [u8]>,
+{
+	let n_systematic = systematic_recovery_threshold(n_validators)?;
+	let chunks: Vec<&[u8]> = chunks.into_iter().take(n_systematic).collect();
+
+	if chunks.len() < n_systematic {
+		return Err(Error::NotEnoughChunks)
+	}
+
+	let shard_len = chunks[0].len();
+
+	if shard_len % 2 != 0 {
+		return Err(Error::UnevenLength)
+	}
+
+	if shard_len == 0 || chunks.iter().any(|chunk| chunk.len() != shard_len) {
+		return Err(Error::NonUniformChunks)
+	}
+
+	// Each encoding run spreads one symbol of two bytes over each of the shards.
+	let mut payload_bytes = Vec::with_capacity(shard_len * n_systematic);
+	for offset in (0..shard_len).step_by(2) {
+		for chunk in &chunks {
+			payload_bytes.extend_from_slice(&chunk[offset..offset + 2]);
+		}
+	}
+
+	Decode::decode(&mut &payload_bytes[..]).or_else(|_e| Err(Error::BadPayload))
+}
+
 /// An iterator that yields merkle branches and chunk data for all chunks to
 /// be sent to other validators.
 pub struct Branches<'a, I> {
@@ -382,6 +456,40 @@ mod tests {
 		assert_eq!(reconstructed, Err(Error::NotEnoughValidators));
 	}
 
+	#[test]
+	fn systematic_round_trip_works() {
+		let pov = PoV { block_data: BlockData((0..255).collect()) };
+
+		let available_data = AvailableData { pov: pov.into(), validation_data: Default::default() };
+
+		for n_validators in [2, 5, 10, 100, 1000] {
+			let chunks = obtain_chunks(n_validators, &available_data).unwrap();
+			let n_systematic = systematic_recovery_threshold(n_validators).unwrap();
+			assert!(n_systematic <= recovery_threshold(n_validators).unwrap());
+
+			let reconstructed: AvailableData = reconstruct_from_systematic(
+				n_validators,
+				chunks.iter().take(n_systematic).map(|chunk| &chunk[..]),
+			)
+			.unwrap();
+
+			assert_eq!(reconstructed, available_data);
+		}
+	}
+
+	#[test]
+	fn systematic_reconstruction_needs_all_systematic_chunks() {
+		let pov = PoV { block_data: BlockData((0..255).collect()) };
+
+		let available_data = AvailableData { pov: pov.into(), validation_data: Default::default() };
+		let chunks = obtain_chunks(10, &available_data).unwrap();
+
+		assert_eq!(systematic_recovery_threshold(10), Ok(4));
+		let reconstructed =
+			reconstruct_from_systematic_v1(10, chunks.iter().take(3).map(|c| &c[..]));
+		assert_eq!(reconstructed, Err(Error::NotEnoughChunks));
+	}
+
 	fn generate_trie_and_generate_proofs(magnitude: u32) {
 		let n_validators = 2_u32.pow(magnitude) as usize;
 		let pov = PoV { block_data: BlockData(vec![2; n_validators / KEY_INDEX_NIBBLE_SIZE]) };
@@ -35,6 +35,9 @@ use crate::{
 };
 
 const STORED_BLOCKS_KEY: &[u8] = b"Approvals_StoredBlocks";
+const BLOCK_ENTRY_PREFIX: [u8; 14] = *b"Approvals_blck";
+const CANDIDATE_ENTRY_PREFIX: [u8; 14] = *b"Approvals_cand";
+const BLOCKS_AT_HEIGHT_PREFIX: [u8; 12] = *b"Approvals_at";
 
 #[cfg(test)]
 pub mod tests;
@@ -80,6 +83,18 @@ impl Backend for DbBackend {
 		load_stored_blocks(&*self.inner, &self.config)
 	}
 
+	fn load_all_block_heights(&self) -> SubsystemResult<Vec<BlockNumber>> {
+		load_all_block_heights(&*self.inner, &self.config)
+	}
+
+	fn load_all_block_entry_hashes(&self) -> SubsystemResult<Vec<Hash>> {
+		load_all_block_entry_hashes(&*self.inner, &self.config)
+	}
+
+	fn load_all_candidate_entry_hashes(&self) -> SubsystemResult<Vec<CandidateHash>> {
+		load_all_candidate_entry_hashes(&*self.inner, &self.config)
+	}
+
 	/// Atomically write the list of operations, with later operations taking precedence over prior.
 	fn write<I>(&mut self, ops: I) -> SubsystemResult<()>
 	where
@@ -254,8 +269,6 @@ pub(crate) fn load_decode<D: Decode>(
 
 /// The key a given block entry is stored under.
 pub(crate) fn block_entry_key(block_hash: &Hash) -> [u8; 46] {
-	const BLOCK_ENTRY_PREFIX: [u8; 14] = *b"Approvals_blck";
-
 	let mut key = [0u8; 14 + 32];
 	key[0..14].copy_from_slice(&BLOCK_ENTRY_PREFIX);
 	key[14..][..32].copy_from_slice(block_hash.as_ref());
@@ -265,8 +278,6 @@ pub(crate) fn block_entry_key(block_hash: &Hash) -> [u8; 46] {
 
 /// The key a given candidate entry is stored under.
 pub(crate) fn candidate_entry_key(candidate_hash: &CandidateHash) -> [u8; 46] {
-	const CANDIDATE_ENTRY_PREFIX: [u8; 14] = *b"Approvals_cand";
-
 	let mut key = [0u8; 14 + 32];
 	key[0..14].copy_from_slice(&CANDIDATE_ENTRY_PREFIX);
 	key[14..][..32].copy_from_slice(candidate_hash.0.as_ref());
@@ -276,8 +287,6 @@ pub(crate) fn candidate_entry_key(candidate_hash: &CandidateHash) -> [u8; 46] {
 
 /// The key a set of block hashes corresponding to a block number is stored under.
 pub(crate) fn blocks_at_height_key(block_number: BlockNumber) -> [u8; 16] {
-	const BLOCKS_AT_HEIGHT_PREFIX: [u8; 12] = *b"Approvals_at";
-
 	let mut key = [0u8; 12 + 4];
 	key[0..12].copy_from_slice(&BLOCKS_AT_HEIGHT_PREFIX);
 	block_number.using_encoded(|s| key[12..16].copy_from_slice(s));
@@ -298,6 +307,50 @@ pub fn load_all_blocks(store: &dyn Database, config: &Config) -> SubsystemResult
 	Ok(hashes)
 }
 
+// Decode the hash a block or candidate entry with the given key prefix is stored under.
+fn decode_hash_key(key: &[u8], prefix: &[u8]) -> Option<Hash> {
+	key.strip_prefix(prefix).filter(|hash| hash.len() == 32).map(Hash::from_slice)
+}
+
+/// Return all heights with a blocks-at-height entry, including those outside of the stored
+/// block range.
+pub fn load_all_block_heights(
+	store: &dyn Database,
+	config: &Config,
+) -> SubsystemResult<Vec<BlockNumber>> {
+	Ok(store
+		.iter_with_prefix(config.col_data, &BLOCKS_AT_HEIGHT_PREFIX[..])
+		.filter_map(|(k, _)| {
+			BlockNumber::decode(&mut k.strip_prefix(&BLOCKS_AT_HEIGHT_PREFIX[..])?).ok()
+		})
+		.collect())
+}
+
+/// Return the hashes of all block entries, including those not reachable from the stored
+/// block range.
+pub fn load_all_block_entry_hashes(
+	store: &dyn Database,
+	config: &Config,
+) -> SubsystemResult<Vec<Hash>> {
+	Ok(store
+		.iter_with_prefix(config.col_data, &BLOCK_ENTRY_PREFIX[..])
+		.filter_map(|(k, _)| decode_hash_key(&k[..], &BLOCK_ENTRY_PREFIX[..]))
+		.collect())
+}
+
+/// Return the hashes of all candidate entries, including those not referenced by any block
+/// entry.
+pub fn load_all_candidate_entry_hashes(
+	store: &dyn Database,
+	config: &Config,
+) -> SubsystemResult<Vec<CandidateHash>> {
+	Ok(store
+		.iter_with_prefix(config.col_data, &CANDIDATE_ENTRY_PREFIX[..])
+		.filter_map(|(k, _)| decode_hash_key(&k[..], &CANDIDATE_ENTRY_PREFIX[..]))
+		.map(CandidateHash)
+		.collect())
+}
+
 /// Load the stored-blocks key from the state.
 pub fn load_stored_blocks(
 	store: &dyn Database,
@@ -19,7 +19,10 @@
 use super::{DbBackend, StoredBlockRange, *};
 use crate::{
 	backend::{Backend, OverlayedBackend},
-	ops::{add_block_entry, canonicalize, force_approve, NewCandidateInfo},
+	ops::{
+		add_block_entry, canonicalize, check_consistency, force_approve, prune_unfinalized,
+		ConsistencyReport, NewCandidateInfo,
+	},
 };
 use polkadot_node_subsystem_util::database::Database;
 use polkadot_primitives::v2::Id as ParaId;
@@ -422,7 +425,7 @@ fn canonicalize_works() {
 	]);
 
 	let mut overlay_db = OverlayedBackend::new(&db);
-	canonicalize(&mut overlay_db, 3, block_hash_c1).unwrap();
+	assert_eq!(canonicalize(&mut overlay_db, 3, block_hash_c1).unwrap(), 6);
 	let write_ops = overlay_db.into_write_ops();
 	db.write(write_ops).unwrap();
 
@@ -566,3 +569,205 @@ fn load_all_blocks_works() {
 		vec![block_hash_a, block_hash_b, block_hash_c],
 	)
 }
+
+#[test]
+fn prune_unfinalized_works() {
+	let (mut db, store) = make_db();
+
+	// A -> B -> C
+	//
+	// Only the top two heights are retained, so A should disappear once it is approved.
+	let parent_hash = Hash::repeat_byte(1);
+	let block_hash_a = Hash::repeat_byte(2);
+	let block_hash_b = Hash::repeat_byte(3);
+	let block_hash_c = Hash::repeat_byte(4);
+
+	let candidate_receipt_a = make_candidate(ParaId::from(1_u32), parent_hash);
+	let candidate_hash_a = candidate_receipt_a.hash();
+
+	let block_entry_a =
+		make_block_entry(block_hash_a, parent_hash, 1, vec![(CoreIndex(0), candidate_hash_a)]);
+	let block_entry_b =
+		make_block_entry(block_hash_b, block_hash_a, 2, vec![(CoreIndex(0), candidate_hash_a)]);
+	let block_entry_c = make_block_entry(block_hash_c, block_hash_b, 3, Vec::new());
+
+	let n_validators = 10;
+	let candidate_info = NewCandidateInfo::new(candidate_receipt_a, GroupIndex(0), None);
+
+	let mut overlay_db = OverlayedBackend::new(&db);
+	for block_entry in [block_entry_a, block_entry_b, block_entry_c] {
+		add_block_entry(&mut overlay_db, block_entry.into(), n_validators, |_| {
+			Some(candidate_info.clone())
+		})
+		.unwrap();
+	}
+	let write_ops = overlay_db.into_write_ops();
+	db.write(write_ops).unwrap();
+
+	// Nothing to prune yet.
+	let mut overlay_db = OverlayedBackend::new(&db);
+	assert_eq!(prune_unfinalized(&mut overlay_db, 3).unwrap(), None);
+	assert!(overlay_db.is_empty());
+
+	// A is still being approved.
+	assert_eq!(prune_unfinalized(&mut overlay_db, 2).unwrap(), None);
+	assert!(overlay_db.is_empty());
+
+	assert_eq!(force_approve(&mut overlay_db, block_hash_c, 1).unwrap(), vec![block_hash_a]);
+	assert_eq!(prune_unfinalized(&mut overlay_db, 2).unwrap(), Some((2, 1)));
+	let write_ops = overlay_db.into_write_ops();
+	db.write(write_ops).unwrap();
+
+	assert_eq!(
+		load_stored_blocks(store.as_ref(), &TEST_CONFIG).unwrap(),
+		Some(StoredBlockRange(2, 4))
+	);
+	assert!(load_blocks_at_height(store.as_ref(), &TEST_CONFIG, &1).unwrap().is_empty());
+	assert!(load_block_entry(store.as_ref(), &TEST_CONFIG, &block_hash_a).unwrap().is_none());
+	assert!(load_block_entry(store.as_ref(), &TEST_CONFIG, &block_hash_b).unwrap().is_some());
+
+	let candidate_entry_a = load_candidate_entry(store.as_ref(), &TEST_CONFIG, &candidate_hash_a)
+		.unwrap()
+		.unwrap();
+	assert_eq!(candidate_entry_a.block_assignments.keys().collect::<Vec<_>>(), vec![&block_hash_b]);
+}
+
+#[test]
+fn check_consistency_removes_orphans() {
+	let (mut db, store) = make_db();
+
+	// A -> B is stored normally. X is left behind below the stored range, W isn't listed at
+	// any height, B's height also lists a block without an entry and the candidate has an
+	// approval entry for an unknown block.
+	let parent_hash = Hash::repeat_byte(1);
+	let block_hash_a = Hash::repeat_byte(2);
+	let block_hash_b = Hash::repeat_byte(3);
+	let block_hash_x = Hash::repeat_byte(4);
+	let block_hash_y = Hash::repeat_byte(5);
+	let block_hash_z = Hash::repeat_byte(6);
+	let block_hash_w = Hash::repeat_byte(7);
+
+	let candidate_receipt_a = make_candidate(ParaId::from(1_u32), parent_hash);
+	let candidate_receipt_x = make_candidate(ParaId::from(2_u32), parent_hash);
+	let candidate_hash_a = candidate_receipt_a.hash();
+	let candidate_hash_x = candidate_receipt_x.hash();
+
+	let block_entry_a =
+		make_block_entry(block_hash_a, parent_hash, 1, vec![(CoreIndex(0), candidate_hash_a)]);
+	let block_entry_b =
+		make_block_entry(block_hash_b, block_hash_a, 2, vec![(CoreIndex(0), candidate_hash_a)]);
+	let block_entry_x =
+		make_block_entry(block_hash_x, parent_hash, 0, vec![(CoreIndex(0), candidate_hash_x)]);
+
+	let n_validators = 10;
+	let mut new_candidate_info = HashMap::new();
+	new_candidate_info
+		.insert(candidate_hash_a, NewCandidateInfo::new(candidate_receipt_a, GroupIndex(0), None));
+	new_candidate_info
+		.insert(candidate_hash_x, NewCandidateInfo::new(candidate_receipt_x, GroupIndex(0), None));
+
+	let mut overlay_db = OverlayedBackend::new(&db);
+	for block_entry in [block_entry_a, block_entry_b, block_entry_x] {
+		add_block_entry(&mut overlay_db, block_entry.into(), n_validators, |h| {
+			new_candidate_info.get(h).map(|x| x.clone())
+		})
+		.unwrap();
+	}
+
+	overlay_db.write_blocks_at_height(2, vec![block_hash_b, block_hash_y]);
+	overlay_db
+		.write_block_entry(make_block_entry(block_hash_w, block_hash_x, 7, Vec::new()).into());
+
+	let mut candidate_entry_a =
+		overlay_db.load_candidate_entry(&candidate_hash_a).unwrap().unwrap();
+	let approval_entry = candidate_entry_a.approval_entry(&block_hash_a).unwrap().clone();
+	candidate_entry_a.block_assignments.insert(block_hash_z, approval_entry);
+	overlay_db.write_candidate_entry(candidate_entry_a);
+
+	let write_ops = overlay_db.into_write_ops();
+	db.write(write_ops).unwrap();
+
+	let mut overlay_db = OverlayedBackend::new(&db);
+	let report = check_consistency(&mut overlay_db).unwrap();
+	assert_eq!(
+		report,
+		ConsistencyReport {
+			stored_range: Some((1, 3)),
+			block_entries: 2,
+			candidate_entries: 1,
+			missing_block_entries: 1,
+			missing_candidate_entries: 0,
+			orphaned_heights: 1,
+			orphaned_block_entries: 2,
+			orphaned_approval_entries: 1,
+			orphaned_candidate_entries: 1,
+		},
+	);
+	assert!(!report.is_consistent());
+
+	let write_ops = overlay_db.into_write_ops();
+	db.write(write_ops).unwrap();
+
+	assert!(load_blocks_at_height(store.as_ref(), &TEST_CONFIG, &0).unwrap().is_empty());
+	assert!(load_block_entry(store.as_ref(), &TEST_CONFIG, &block_hash_x).unwrap().is_none());
+	assert!(load_block_entry(store.as_ref(), &TEST_CONFIG, &block_hash_w).unwrap().is_none());
+	assert!(load_candidate_entry(store.as_ref(), &TEST_CONFIG, &candidate_hash_x)
+		.unwrap()
+		.is_none());
+	assert_eq!(
+		load_blocks_at_height(store.as_ref(), &TEST_CONFIG, &2).unwrap(),
+		vec![block_hash_b],
+	);
+
+	let candidate_entry_a = load_candidate_entry(store.as_ref(), &TEST_CONFIG, &candidate_hash_a)
+		.unwrap()
+		.unwrap();
+	assert_eq!(
+		candidate_entry_a.block_assignments.keys().collect::<Vec<_>>(),
+		vec![&block_hash_a, &block_hash_b],
+	);
+
+	// A second check finds nothing to repair.
+	let mut overlay_db = OverlayedBackend::new(&db);
+	assert!(check_consistency(&mut overlay_db).unwrap().is_consistent());
+	assert!(overlay_db.is_empty());
+}
+
+#[test]
+fn check_consistency_without_stored_range_removes_everything() {
+	let (mut db, store) = make_db();
+
+	let parent_hash = Hash::repeat_byte(1);
+	let block_hash_a = Hash::repeat_byte(2);
+	let candidate_receipt_a = make_candidate(ParaId::from(1_u32), parent_hash);
+	let candidate_hash_a = candidate_receipt_a.hash();
+	let block_entry_a =
+		make_block_entry(block_hash_a, parent_hash, 1, vec![(CoreIndex(0), candidate_hash_a)]);
+	let candidate_info = NewCandidateInfo::new(candidate_receipt_a, GroupIndex(0), None);
+
+	let mut overlay_db = OverlayedBackend::new(&db);
+	add_block_entry(&mut overlay_db, block_entry_a.into(), 10, |_| Some(candidate_info.clone()))
+		.unwrap();
+	overlay_db.delete_stored_block_range();
+	let write_ops = overlay_db.into_write_ops();
+	db.write(write_ops).unwrap();
+
+	let mut overlay_db = OverlayedBackend::new(&db);
+	assert_eq!(
+		check_consistency(&mut overlay_db).unwrap(),
+		ConsistencyReport {
+			orphaned_heights: 1,
+			orphaned_block_entries: 1,
+			orphaned_candidate_entries: 1,
+			..Default::default()
+		},
+	);
+	let write_ops = overlay_db.into_write_ops();
+	db.write(write_ops).unwrap();
+
+	assert!(load_all_block_heights(store.as_ref(), &TEST_CONFIG).unwrap().is_empty());
+	assert!(load_all_block_entry_hashes(store.as_ref(), &TEST_CONFIG).unwrap().is_empty());
+	assert!(load_all_candidate_entry_hashes(store.as_ref(), &TEST_CONFIG)
+		.unwrap()
+		.is_empty());
+}
@@ -24,7 +24,7 @@
 use polkadot_node_subsystem::SubsystemResult;
 use polkadot_primitives::v2::{BlockNumber, CandidateHash, Hash};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use super::{
 	approval_db::v1::StoredBlockRange,
@@ -58,6 +58,12 @@ pub trait Backend {
 	fn load_all_blocks(&self) -> SubsystemResult<Vec<Hash>>;
 	/// Load stored block range form the DB.
 	fn load_stored_blocks(&self) -> SubsystemResult<Option<StoredBlockRange>>;
+	/// Load all heights with a blocks-at-height entry, within the stored block range or not.
+	fn load_all_block_heights(&self) -> SubsystemResult<Vec<BlockNumber>>;
+	/// Load the hashes of all block entries, reachable from the stored block range or not.
+	fn load_all_block_entry_hashes(&self) -> SubsystemResult<Vec<Hash>>;
+	/// Load the hashes of all candidate entries, referenced by a block entry or not.
+	fn load_all_candidate_entry_hashes(&self) -> SubsystemResult<Vec<CandidateHash>>;
 	/// Atomically write the list of operations, with later operations taking precedence over prior.
 	fn write<I>(&mut self, ops: I) -> SubsystemResult<()>
 	where
@@ -121,6 +127,18 @@ impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
 		Ok(hashes)
 	}
 
+	pub fn load_all_block_heights(&self) -> SubsystemResult<Vec<BlockNumber>> {
+		Ok(merge_keys(self.inner.load_all_block_heights()?, &self.blocks_at_height))
+	}
+
+	pub fn load_all_block_entry_hashes(&self) -> SubsystemResult<Vec<Hash>> {
+		Ok(merge_keys(self.inner.load_all_block_entry_hashes()?, &self.block_entries))
+	}
+
+	pub fn load_all_candidate_entry_hashes(&self) -> SubsystemResult<Vec<CandidateHash>> {
+		Ok(merge_keys(self.inner.load_all_candidate_entry_hashes()?, &self.candidate_entries))
+	}
+
 	pub fn load_stored_blocks(&self) -> SubsystemResult<Option<StoredBlockRange>> {
 		match self.stored_block_range {
 			BlockRangeStatus::Inserted(ref value) => Ok(Some(value.clone())),
@@ -219,3 +237,20 @@ impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
 			.chain(candidate_entry_ops)
 	}
 }
+
+// Combine the keys stored in the inner backend with the ones written or deleted in the overlay.
+fn merge_keys<K: Copy + Eq + std::hash::Hash, V>(
+	inner: Vec<K>,
+	overlay: &HashMap<K, Option<V>>,
+) -> Vec<K> {
+	let mut keys: HashSet<K> = inner.into_iter().collect();
+	for (key, value) in overlay {
+		if value.is_some() {
+			keys.insert(*key);
+		} else {
+			keys.remove(key);
+		}
+	}
+
+	keys.into_iter().collect()
+}
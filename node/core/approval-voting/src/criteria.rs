@@ -181,6 +181,27 @@ pub(crate) trait AssignmentCriteria {
 		assignment: &AssignmentCert,
 		backing_group: GroupIndex,
 	) -> Result<DelayTranche, InvalidAssignment>;
+
+	/// Check the certs of a batch of assignments, like `check_assignment_cert` would for each of
+	/// them. The results are returned in the same order as the checks.
+	fn check_assignment_certs(
+		&self,
+		checks: &[AssignmentCertCheck],
+	) -> Vec<Result<DelayTranche, InvalidAssignment>> {
+		checks
+			.iter()
+			.map(|c| {
+				self.check_assignment_cert(
+					c.claimed_core_index,
+					c.validator_index,
+					&c.config,
+					c.relay_vrf_story.clone(),
+					&c.cert,
+					c.backing_group,
+				)
+			})
+			.collect()
+	}
 }
 
 pub(crate) struct RealAssignmentCriteria;
@@ -214,6 +235,13 @@ impl AssignmentCriteria for RealAssignmentCriteria {
 			backing_group,
 		)
 	}
+
+	fn check_assignment_certs(
+		&self,
+		checks: &[AssignmentCertCheck],
+	) -> Vec<Result<DelayTranche, InvalidAssignment>> {
+		check_assignment_certs(checks)
+	}
 }
 
 /// Compute the assignments for a given block. Returns a map containing all assignments to cores in
@@ -540,6 +568,64 @@ pub(crate) fn check_assignment_cert(
 	}
 }
 
+/// The inputs of [`check_assignment_cert`] for one assignment of a batch.
+pub(crate) struct AssignmentCertCheck {
+	pub(crate) claimed_core_index: CoreIndex,
+	pub(crate) validator_index: ValidatorIndex,
+	pub(crate) config: Config,
+	pub(crate) relay_vrf_story: RelayVRFStory,
+	pub(crate) cert: AssignmentCert,
+	pub(crate) backing_group: GroupIndex,
+}
+
+// The minimum number of certs worth a thread of their own.
+const MIN_CERTS_PER_THREAD: usize = 16;
+
+// The maximum number of threads checking the certs of a batch.
+const MAX_CERT_CHECK_THREADS: usize = 4;
+
+/// Checks the certs of a batch of assignments, like [`check_assignment_cert`].
+///
+/// The VRF proofs are gossiped as challenge and response, which can't be verified as a batch,
+/// so large batches are instead split across threads. Each cert gets a result of its own, so a
+/// bad cert doesn't affect the others of the batch.
+pub(crate) fn check_assignment_certs(
+	checks: &[AssignmentCertCheck],
+) -> Vec<Result<DelayTranche, InvalidAssignment>> {
+	let check = |c: &AssignmentCertCheck| {
+		check_assignment_cert(
+			c.claimed_core_index,
+			c.validator_index,
+			&c.config,
+			c.relay_vrf_story.clone(),
+			&c.cert,
+			c.backing_group,
+		)
+	};
+
+	let n_threads = std::thread::available_parallelism()
+		.map_or(1, |n| n.get())
+		.min(MAX_CERT_CHECK_THREADS)
+		.min(checks.len() / MIN_CERTS_PER_THREAD);
+
+	if n_threads <= 1 {
+		return checks.iter().map(check).collect()
+	}
+
+	let chunk_size = (checks.len() + n_threads - 1) / n_threads;
+	std::thread::scope(|scope| {
+		let handles: Vec<_> = checks
+			.chunks(chunk_size)
+			.map(|chunk| scope.spawn(move || chunk.iter().map(check).collect::<Vec<_>>()))
+			.collect();
+
+		handles
+			.into_iter()
+			.flat_map(|handle| handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+			.collect()
+	})
+}
+
 fn is_in_backing_group(
 	validator_groups: &[Vec<ValidatorIndex>],
 	validator: ValidatorIndex,
@@ -871,6 +957,73 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn batch_checks_match_individual_checks() {
+		let keystore = futures::executor::block_on(make_keystore(&[Sr25519Keyring::Alice]));
+		let n_cores = 100;
+		let config = Config {
+			assignment_keys: assignment_keys_plus_random(&[Sr25519Keyring::Alice], 199),
+			validator_groups: basic_groups(200, n_cores),
+			n_cores: n_cores as u32,
+			zeroth_delay_tranche_width: 10,
+			relay_vrf_modulo_samples: 3,
+			n_delay_tranches: 40,
+		};
+
+		let relay_vrf_story = RelayVRFStory([42u8; 32]);
+		let group_for_core = |i| GroupIndex(((i + 25) % n_cores) as _);
+		let assignments = compute_assignments(
+			&keystore,
+			relay_vrf_story.clone(),
+			&config,
+			(0..n_cores)
+				.map(|i| {
+					(
+						CandidateHash(Hash::repeat_byte(i as u8)),
+						CoreIndex(i as u32),
+						group_for_core(i),
+					)
+				})
+				.collect::<Vec<_>>(),
+		);
+
+		let checks: Vec<_> = assignments
+			.into_iter()
+			.enumerate()
+			.map(|(i, (core, assignment))| {
+				let mut cert = assignment.cert;
+				if i % 3 == 0 {
+					cert.vrf = garbage_vrf();
+				}
+
+				AssignmentCertCheck {
+					claimed_core_index: core,
+					validator_index: ValidatorIndex(0),
+					config: config.clone(),
+					relay_vrf_story: relay_vrf_story.clone(),
+					cert,
+					backing_group: group_for_core(core.0 as _),
+				}
+			})
+			.collect();
+		assert!(checks.len() > 2 * MIN_CERTS_PER_THREAD);
+
+		let results = check_assignment_certs(&checks);
+		assert_eq!(results.len(), checks.len());
+		for (i, (check, result)) in checks.iter().zip(results).enumerate() {
+			let expected = check_assignment_cert(
+				check.claimed_core_index,
+				check.validator_index,
+				&check.config,
+				check.relay_vrf_story.clone(),
+				&check.cert,
+				check.backing_group,
+			);
+			assert_eq!(result, expected);
+			assert_eq!(result.is_ok(), i % 3 != 0);
+		}
+	}
+
 	#[test]
 	fn check_rejects_modulo_core_wrong() {
 		check_mutated_assignments(200, 100, 25, |m| {
@@ -656,6 +656,8 @@ pub(crate) mod tests {
 			session_window: None,
 			keystore: Arc::new(LocalKeystore::in_memory()),
 			slot_duration_millis: 6_000,
+			max_unfinalized_heights: None,
+			db_block_entries: None,
 			clock: Box::new(MockClock::default()),
 			assignment_criteria: Box::new(MockAssignmentCriteria),
 		}
@@ -93,6 +93,8 @@ use crate::{
 	backend::{Backend, OverlayedBackend},
 };
 
+pub use ops::ConsistencyReport;
+
 #[cfg(test)]
 mod tests;
 
@@ -112,6 +114,11 @@ pub struct Config {
 	/// The slot duration of the consensus algorithm, in milliseconds. Should be evenly
 	/// divisible by 500.
 	pub slot_duration_millis: u64,
+	/// The maximum number of unfinalized block heights to keep in the DB. Entries of fully
+	/// approved blocks below that are pruned even if they haven't been finalized yet, up to the
+	/// lowest block which is still being approved. `None` means entries are only pruned on
+	/// finality.
+	pub max_unfinalized_heights: Option<BlockNumber>,
 }
 
 // The mode of the approval voting subsystem. It should start in a `Syncing` mode when it first
@@ -136,6 +143,7 @@ pub struct ApprovalVotingSubsystem {
 	keystore: Arc<LocalKeystore>,
 	db_config: DatabaseConfig,
 	slot_duration_millis: u64,
+	max_unfinalized_heights: Option<BlockNumber>,
 	db: Arc<dyn Database>,
 	mode: Mode,
 	metrics: Metrics,
@@ -152,6 +160,11 @@ struct MetricsInner {
 	block_approval_time_ticks: prometheus::Histogram,
 	time_db_transaction: prometheus::Histogram,
 	time_recover_and_approve: prometheus::Histogram,
+	approval_signature_batches_total: prometheus::CounterVec<prometheus::U64>,
+	approval_signature_batch_size: prometheus::Histogram,
+	approval_db_stored_heights: prometheus::Gauge<prometheus::U64>,
+	approval_db_block_entries: prometheus::Gauge<prometheus::U64>,
+	unfinalized_prunings_total: prometheus::Counter<prometheus::U64>,
 }
 
 /// Approval Voting metrics.
@@ -232,6 +245,31 @@ impl Metrics {
 	fn time_recover_and_approve(&self) -> Option<metrics::prometheus::prometheus::HistogramTimer> {
 		self.0.as_ref().map(|metrics| metrics.time_recover_and_approve.start_timer())
 	}
+
+	fn on_approval_batch_verified(&self, n_signatures: usize, verified: bool) {
+		if let Some(metrics) = &self.0 {
+			let status = if verified { "verified" } else { "fallback" };
+			metrics.approval_signature_batches_total.with_label_values(&[status]).inc();
+			metrics.approval_signature_batch_size.observe(n_signatures as f64);
+		}
+	}
+
+	fn is_enabled(&self) -> bool {
+		self.0.is_some()
+	}
+
+	fn on_db_size(&self, stored_heights: BlockNumber, block_entries: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.approval_db_stored_heights.set(stored_heights as u64);
+			metrics.approval_db_block_entries.set(block_entries as u64);
+		}
+	}
+
+	fn on_unfinalized_blocks_pruned(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.unfinalized_prunings_total.inc();
+		}
+	}
 }
 
 impl metrics::Metrics for Metrics {
@@ -315,6 +353,46 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			approval_signature_batches_total: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_approval_signature_batches_total",
+						"Number of approval signature batches verified, by whether individual checks were needed",
+					),
+					&["status"]
+				)?,
+				registry,
+			)?,
+			approval_signature_batch_size: prometheus::register(
+				prometheus::Histogram::with_opts(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_approval_signature_batch_size",
+						"Number of approval signatures verified together in one batch",
+					).buckets(vec![1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0, 256.0]),
+				)?,
+				registry,
+			)?,
+			approval_db_stored_heights: prometheus::register(
+				prometheus::Gauge::new(
+					"polkadot_parachain_approval_db_stored_heights",
+					"Number of block heights with entries in the approval voting DB",
+				)?,
+				registry,
+			)?,
+			approval_db_block_entries: prometheus::register(
+				prometheus::Gauge::new(
+					"polkadot_parachain_approval_db_block_entries",
+					"Number of block entries in the approval voting DB",
+				)?,
+				registry,
+			)?,
+			unfinalized_prunings_total: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_approval_db_unfinalized_prunings_total",
+					"Number of times unfinalized blocks were pruned from the approval voting DB",
+				)?,
+				registry,
+			)?,
 		};
 
 		Ok(Metrics(Some(metrics)))
@@ -333,6 +411,7 @@ impl ApprovalVotingSubsystem {
 		ApprovalVotingSubsystem {
 			keystore,
 			slot_duration_millis: config.slot_duration_millis,
+			max_unfinalized_heights: config.max_unfinalized_heights,
 			db,
 			db_config: DatabaseConfig { col_data: config.col_data },
 			mode: Mode::Syncing(sync_oracle),
@@ -352,6 +431,25 @@ impl ApprovalVotingSubsystem {
 		let ops = overlay.into_write_ops();
 		backend.write(ops)
 	}
+
+	/// Check the consistency of the approval DB, without requiring a resync.
+	///
+	/// Orphaned entries and dangling references which were found are removed if `prune` is
+	/// set, otherwise the DB is left untouched.
+	pub fn check_db(&self, prune: bool) -> Result<ConsistencyReport, SubsystemError> {
+		let config = approval_db::v1::Config { col_data: self.db_config.col_data };
+		let mut backend = approval_db::v1::DbBackend::new(self.db.clone(), config);
+		let mut overlay = OverlayedBackend::new(&backend);
+
+		let report = ops::check_consistency(&mut overlay)?;
+
+		if prune {
+			let ops = overlay.into_write_ops();
+			backend.write(ops)?;
+		}
+
+		Ok(report)
+	}
 }
 
 #[overseer::subsystem(ApprovalVoting, error = SubsystemError, prefix = self::overseer)]
@@ -589,6 +687,9 @@ struct State {
 	session_window: Option<RollingSessionWindow>,
 	keystore: Arc<LocalKeystore>,
 	slot_duration_millis: u64,
+	max_unfinalized_heights: Option<BlockNumber>,
+	// Running count of the block entries in the DB, only maintained while metrics are enabled.
+	db_block_entries: Option<usize>,
 	clock: Box<dyn Clock + Send + Sync>,
 	assignment_criteria: Box<dyn AssignmentCriteria + Send + Sync>,
 }
@@ -719,6 +820,8 @@ where
 		session_window: None,
 		keystore: subsystem.keystore,
 		slot_duration_millis: subsystem.slot_duration_millis,
+		max_unfinalized_heights: subsystem.max_unfinalized_heights,
+		db_block_entries: None,
 		clock,
 		assignment_criteria,
 	};
@@ -1086,6 +1189,33 @@ fn distribution_messages_for_activation(
 	Ok(messages)
 }
 
+// Update the metrics about the size of the DB, given the number of block entries
+// added and removed since the last update.
+//
+// The DB is scanned for block entries only once, after that the count is kept up to date.
+fn note_db_size(
+	db: &OverlayedBackend<'_, impl Backend>,
+	db_block_entries: &mut Option<usize>,
+	metrics: &Metrics,
+	added: usize,
+	removed: usize,
+) -> SubsystemResult<()> {
+	if !metrics.is_enabled() {
+		return Ok(())
+	}
+
+	let block_entries = match *db_block_entries {
+		Some(count) => (count + added).saturating_sub(removed),
+		None => db.load_all_blocks()?.len(),
+	};
+	*db_block_entries = Some(block_entries);
+
+	let stored_heights = db.load_stored_blocks()?.map_or(0, |range| range.1 - range.0);
+	metrics.on_db_size(stored_heights, block_entries);
+
+	Ok(())
+}
+
 // Handle an incoming signal from the overseer. Returns true if execution should conclude.
 #[overseer::contextbounds(ApprovalVoting, prefix = self::overseer)]
 async fn handle_from_overseer<Context>(
@@ -1100,12 +1230,16 @@ async fn handle_from_overseer<Context>(
 	let actions = match x {
 		FromOrchestra::Signal(OverseerSignal::ActiveLeaves(update)) => {
 			let mut actions = Vec::new();
+			let mut added_blocks = 0;
+			let mut removed_blocks = 0;
 
 			for activated in update.activated {
 				let head = activated.hash;
 				match import::handle_new_head(ctx, state, db, head, &*last_finalized_height).await {
 					Err(e) => return Err(SubsystemError::with_origin("db", e)),
 					Ok(block_imported_candidates) => {
+						added_blocks += block_imported_candidates.len();
+
 						// Schedule wakeups for all imported candidates.
 						for block_batch in block_imported_candidates {
 							gum::debug!(
@@ -1150,17 +1284,38 @@ async fn handle_from_overseer<Context>(
 				}
 			}
 
+			if let Some(max_heights) = state.max_unfinalized_heights {
+				let pruned_below = crate::ops::prune_unfinalized(db, max_heights)
+					.map_err(|e| SubsystemError::with_origin("db", e))?;
+
+				if let Some((pruned_below, pruned_blocks)) = pruned_below {
+					gum::warn!(
+						target: LOG_TARGET,
+						?pruned_below,
+						?last_finalized_height,
+						"Pruned approval entries of unfinalized blocks. Finality is lagging behind",
+					);
+					metrics.on_unfinalized_blocks_pruned();
+					wakeups.prune_finalized_wakeups(pruned_below.saturating_sub(1));
+					removed_blocks += pruned_blocks;
+				}
+			}
+
+			note_db_size(db, &mut state.db_block_entries, metrics, added_blocks, removed_blocks)?;
+
 			actions
 		},
 		FromOrchestra::Signal(OverseerSignal::BlockFinalized(block_hash, block_number)) => {
 			gum::debug!(target: LOG_TARGET, ?block_hash, ?block_number, "Block finalized");
 			*last_finalized_height = Some(block_number);
 
-			crate::ops::canonicalize(db, block_number, block_hash)
+			let removed_blocks = crate::ops::canonicalize(db, block_number, block_hash)
 				.map_err(|e| SubsystemError::with_origin("db", e))?;
 
 			wakeups.prune_finalized_wakeups(block_number);
 
+			note_db_size(db, &mut state.db_block_entries, metrics, 0, removed_blocks)?;
+
 			Vec::new()
 		},
 		FromOrchestra::Signal(OverseerSignal::Conclude) => {
@@ -1169,16 +1324,49 @@ async fn handle_from_overseer<Context>(
 		FromOrchestra::Communication { msg } => match msg {
 			ApprovalVotingMessage::CheckAndImportAssignment(a, claimed_core, res) => {
 				let (check_outcome, actions) =
-					check_and_import_assignment(state, db, a, claimed_core)?;
+					check_and_import_assignment(state, db, a, claimed_core, None)?;
 				let _ = res.send(check_outcome);
 
 				actions
 			},
 			ApprovalVotingMessage::CheckAndImportApproval(a, res) =>
-				check_and_import_approval(state, db, metrics, a, |r| {
+				check_and_import_approval(state, db, metrics, a, false, |r| {
 					let _ = res.send(r);
 				})?
 				.0,
+			ApprovalVotingMessage::CheckAndImportAssignments(assignments, res) => {
+				// Check all certs up front, then import the assignments one by one. Assignments
+				// which can't be imported anyway are left unchecked.
+				let mut cert_checks = Vec::with_capacity(assignments.len());
+				let mut checked = Vec::with_capacity(assignments.len());
+				for (a, claimed_core) in &assignments {
+					let check = assignment_cert_check(state, db, a, *claimed_core)?;
+					checked.push(check.is_some());
+					cert_checks.extend(check);
+				}
+				let mut cert_results =
+					state.assignment_criteria.check_assignment_certs(&cert_checks).into_iter();
+
+				let mut actions = Vec::new();
+				let mut check_outcomes = Vec::with_capacity(assignments.len());
+				for ((a, claimed_core), checked) in assignments.into_iter().zip(checked) {
+					let cert_result = if checked { cert_results.next() } else { None };
+					let (check_outcome, a_actions) =
+						check_and_import_assignment(state, db, a, claimed_core, cert_result)?;
+					check_outcomes.push(check_outcome);
+					actions.extend(a_actions);
+				}
+				let _ = res.send(check_outcomes);
+
+				actions
+			},
+			ApprovalVotingMessage::CheckAndImportApprovals(approvals, res) => {
+				let (actions, check_outcomes) =
+					check_and_import_approvals(state, db, metrics, approvals)?;
+				let _ = res.send(check_outcomes);
+
+				actions
+			},
 			ApprovalVotingMessage::ApprovedAncestor(target, lower_bound, res) => {
 				match handle_approved_ancestor(ctx, db, target, lower_bound, wakeups).await {
 					Ok(v) => {
@@ -1532,11 +1720,56 @@ fn schedule_wakeup_action(
 	maybe_action
 }
 
+// The inputs for checking the cert of an assignment, or `None` if the assignment can't be
+// imported, which `check_and_import_assignment` reports.
+fn assignment_cert_check(
+	state: &State,
+	db: &OverlayedBackend<'_, impl Backend>,
+	assignment: &IndirectAssignmentCert,
+	candidate_index: CandidateIndex,
+) -> SubsystemResult<Option<criteria::AssignmentCertCheck>> {
+	let block_entry = match db.load_block_entry(&assignment.block_hash)? {
+		Some(b) => b,
+		None => return Ok(None),
+	};
+
+	let session_info = match state.session_info(block_entry.session()) {
+		Some(s) => s,
+		None => return Ok(None),
+	};
+
+	let (claimed_core_index, assigned_candidate_hash) =
+		match block_entry.candidate(candidate_index as usize) {
+			Some((c, h)) => (*c, *h),
+			None => return Ok(None),
+		};
+
+	let backing_group = match db
+		.load_candidate_entry(&assigned_candidate_hash)?
+		.and_then(|c| c.approval_entry(&assignment.block_hash).map(|a| a.backing_group()))
+	{
+		Some(g) => g,
+		None => return Ok(None),
+	};
+
+	Ok(Some(criteria::AssignmentCertCheck {
+		claimed_core_index,
+		validator_index: assignment.validator,
+		config: criteria::Config::from(session_info),
+		relay_vrf_story: block_entry.relay_vrf_story(),
+		cert: assignment.cert.clone(),
+		backing_group,
+	}))
+}
+
+// Check and import an assignment. `cert_result` is the result of checking the cert, if that was
+// already done as part of a batch.
 fn check_and_import_assignment(
 	state: &State,
 	db: &mut OverlayedBackend<'_, impl Backend>,
 	assignment: IndirectAssignmentCert,
 	candidate_index: CandidateIndex,
+	cert_result: Option<Result<DelayTranche, criteria::InvalidAssignment>>,
 ) -> SubsystemResult<(AssignmentCheckResult, Vec<Action>)> {
 	let tick_now = state.clock.tick_now();
 
@@ -1600,14 +1833,16 @@ fn check_and_import_assignment(
 				)),
 		};
 
-		let res = state.assignment_criteria.check_assignment_cert(
-			claimed_core_index,
-			assignment.validator,
-			&criteria::Config::from(session_info),
-			block_entry.relay_vrf_story(),
-			&assignment.cert,
-			approval_entry.backing_group(),
-		);
+		let res = cert_result.unwrap_or_else(|| {
+			state.assignment_criteria.check_assignment_cert(
+				claimed_core_index,
+				assignment.validator,
+				&criteria::Config::from(session_info),
+				block_entry.relay_vrf_story(),
+				&assignment.cert,
+				approval_entry.backing_group(),
+			)
+		});
 
 		let tranche = match res {
 			Err(crate::criteria::InvalidAssignment(reason)) =>
@@ -1671,11 +1906,126 @@ fn check_and_import_assignment(
 	Ok((res, actions))
 }
 
+// Load the signing payload and the public key an approval vote has to be checked against.
+//
+// Returns `None` if any of the data is missing, in which case the vote is rejected
+// by `check_and_import_approval`.
+fn approval_signing_data(
+	state: &State,
+	db: &mut OverlayedBackend<'_, impl Backend>,
+	approval: &IndirectSignedApprovalVote,
+) -> SubsystemResult<Option<(Vec<u8>, ValidatorId)>> {
+	let block_entry = match db.load_block_entry(&approval.block_hash)? {
+		Some(b) => b,
+		None => return Ok(None),
+	};
+
+	let session_info = match state.session_info(block_entry.session()) {
+		Some(s) => s,
+		None => return Ok(None),
+	};
+
+	let approved_candidate_hash = match block_entry.candidate(approval.candidate_index as usize) {
+		Some((_, h)) => *h,
+		None => return Ok(None),
+	};
+
+	let pubkey = match session_info.validators.get(approval.validator.0 as usize) {
+		Some(k) => k.clone(),
+		None => return Ok(None),
+	};
+
+	let payload = ApprovalVote(approved_candidate_hash).signing_payload(block_entry.session());
+
+	Ok(Some((payload, pubkey)))
+}
+
+// Verify the signatures of all approval votes at once using sr25519 batch verification.
+//
+// Returns `false` if any of the signatures is invalid or can't be decoded. This doesn't tell
+// which signature is at fault, so the signatures have to be checked individually afterwards.
+fn verify_approval_signatures_batch(signed: &[(Vec<u8>, ValidatorId, ValidatorSignature)]) -> bool {
+	// The signing context used by `sp_core::sr25519` for all signatures.
+	const SIGNING_CTX: &[u8] = b"substrate";
+
+	let mut public_keys = Vec::with_capacity(signed.len());
+	let mut signatures = Vec::with_capacity(signed.len());
+	for (_, pubkey, signature) in signed {
+		match (
+			schnorrkel::PublicKey::from_bytes(pubkey.as_ref()),
+			schnorrkel::Signature::from_bytes(signature.as_ref()),
+		) {
+			(Ok(pubkey), Ok(signature)) => {
+				public_keys.push(pubkey);
+				signatures.push(signature);
+			},
+			_ => return false,
+		}
+	}
+
+	let ctx = schnorrkel::signing_context(SIGNING_CTX);
+	let transcripts = signed.iter().map(|(payload, _, _)| ctx.bytes(&payload[..]));
+
+	schnorrkel::verify_batch(transcripts, &signatures[..], &public_keys[..], false).is_ok()
+}
+
+fn check_and_import_approvals(
+	state: &State,
+	db: &mut OverlayedBackend<'_, impl Backend>,
+	metrics: &Metrics,
+	approvals: Vec<IndirectSignedApprovalVote>,
+) -> SubsystemResult<(Vec<Action>, Vec<ApprovalCheckResult>)> {
+	let mut signed = Vec::with_capacity(approvals.len());
+	let mut in_batch = Vec::with_capacity(approvals.len());
+	for approval in &approvals {
+		match approval_signing_data(state, db, approval)? {
+			Some((payload, pubkey)) => {
+				signed.push((payload, pubkey, approval.signature.clone()));
+				in_batch.push(true);
+			},
+			None => in_batch.push(false),
+		}
+	}
+
+	let batch_verified = verify_approval_signatures_batch(&signed);
+	metrics.on_approval_batch_verified(signed.len(), batch_verified);
+	if !batch_verified {
+		gum::debug!(
+			target: LOG_TARGET,
+			n_signatures = signed.len(),
+			"Batch verification of approval signatures failed, checking individually",
+		);
+	}
+
+	let mut actions = Vec::new();
+	let mut check_outcomes = Vec::with_capacity(approvals.len());
+	for (approval, in_batch) in approvals.into_iter().zip(in_batch) {
+		let (approval_actions, check_outcome) = check_and_import_approval(
+			state,
+			db,
+			metrics,
+			approval,
+			batch_verified && in_batch,
+			|r| r,
+		)?;
+
+		actions.extend(approval_actions);
+		check_outcomes.push(check_outcome);
+	}
+
+	Ok((actions, check_outcomes))
+}
+
+// Check and import an approval vote.
+//
+// The signature is only checked if `signature_verified` is `false`, otherwise it must have been
+// verified against the data returned by `approval_signing_data` already.
 fn check_and_import_approval<T>(
 	state: &State,
 	db: &mut OverlayedBackend<'_, impl Backend>,
 	metrics: &Metrics,
 	approval: IndirectSignedApprovalVote,
+	signature_verified: bool,
 	with_response: impl FnOnce(ApprovalCheckResult) -> T,
 ) -> SubsystemResult<(Vec<Action>, T)> {
 	macro_rules! respond_early {
@@ -1718,18 +2068,28 @@ fn check_and_import_approval<T>(
 	};
 
 	// Transform the approval vote into the wrapper used to import statements into disputes.
-	// This also does signature checking.
-	let signed_dispute_statement = match SignedDisputeStatement::new_checked(
-		DisputeStatement::Valid(ValidDisputeStatementKind::ApprovalChecking),
-		approved_candidate_hash,
-		block_entry.session(),
-		pubkey.clone(),
-		approval.signature.clone(),
-	) {
-		Err(_) => respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidSignature(
-			approval.validator
-		),)),
-		Ok(s) => s,
+	// This also does signature checking, unless the signature has been verified already.
+	let signed_dispute_statement = if signature_verified {
+		SignedDisputeStatement::new_unchecked_from_trusted_source(
+			DisputeStatement::Valid(ValidDisputeStatementKind::ApprovalChecking),
+			approved_candidate_hash,
+			block_entry.session(),
+			pubkey.clone(),
+			approval.signature.clone(),
+		)
+	} else {
+		match SignedDisputeStatement::new_checked(
+			DisputeStatement::Valid(ValidDisputeStatementKind::ApprovalChecking),
+			approved_candidate_hash,
+			block_entry.session(),
+			pubkey.clone(),
+			approval.signature.clone(),
+		) {
+			Err(_) => respond_early!(ApprovalCheckResult::Bad(
+				ApprovalCheckError::InvalidSignature(approval.validator),
+			)),
+			Ok(s) => s,
+		}
 	};
 
 	let candidate_entry = match db.load_candidate_entry(&approved_candidate_hash)? {
@@ -55,9 +55,9 @@ fn visit_and_remove_block_entry(
 	block_hash: Hash,
 	overlayed_db: &mut OverlayedBackend<'_, impl Backend>,
 	visited_candidates: &mut HashMap<CandidateHash, CandidateEntry>,
-) -> SubsystemResult<Vec<Hash>> {
+) -> SubsystemResult<Option<Vec<Hash>>> {
 	let block_entry = match overlayed_db.load_block_entry(&block_hash)? {
-		None => return Ok(Vec::new()),
+		None => return Ok(None),
 		Some(b) => b,
 	};
 
@@ -76,22 +76,45 @@ fn visit_and_remove_block_entry(
 		candidate.block_assignments.remove(&block_hash);
 	}
 
-	Ok(block_entry.children)
+	Ok(Some(block_entry.children))
+}
+
+// Write back all visited candidate entries, deleting those which now have empty
+// `block_assignments`. Returns the number of deleted candidate entries.
+fn write_visited_candidates(
+	overlay_db: &mut OverlayedBackend<'_, impl Backend>,
+	visited_candidates: HashMap<CandidateHash, CandidateEntry>,
+) -> usize {
+	let mut deleted = 0;
+	for (candidate_hash, candidate) in visited_candidates.into_iter() {
+		if candidate.block_assignments.is_empty() {
+			overlay_db.delete_candidate_entry(&candidate_hash);
+			deleted += 1;
+		} else {
+			overlay_db.write_candidate_entry(candidate);
+		}
+	}
+
+	deleted
 }
 
 /// Canonicalize some particular block, pruning everything before it and
 /// pruning any competing branches at the same height.
+///
+/// Returns the number of removed block entries.
 pub fn canonicalize(
 	overlay_db: &mut OverlayedBackend<'_, impl Backend>,
 	canon_number: BlockNumber,
 	canon_hash: Hash,
-) -> SubsystemResult<()> {
+) -> SubsystemResult<usize> {
 	let range = match overlay_db.load_stored_blocks()? {
-		None => return Ok(()),
-		Some(range) if range.0 >= canon_number => return Ok(()),
+		None => return Ok(0),
+		Some(range) if range.0 >= canon_number => return Ok(0),
 		Some(range) => range,
 	};
 
+	let mut removed = 0;
+
 	// Storing all candidates in memory is potentially heavy, but should be fine
 	// as long as finality doesn't stall for a long while. We could optimize this
 	// by keeping only the metadata about which blocks reference each candidate.
@@ -106,7 +129,9 @@ pub fn canonicalize(
 		overlay_db.delete_blocks_at_height(i);
 
 		for b in at_height {
-			let _ = visit_and_remove_block_entry(b, overlay_db, &mut visited_candidates)?;
+			if visit_and_remove_block_entry(b, overlay_db, &mut visited_candidates)?.is_some() {
+				removed += 1;
+			}
 		}
 	}
 
@@ -120,7 +145,12 @@ pub fn canonicalize(
 		let mut pruned_branches = Vec::new();
 
 		for b in at_height {
-			let children = visit_and_remove_block_entry(b, overlay_db, &mut visited_candidates)?;
+			let children =
+				match visit_and_remove_block_entry(b, overlay_db, &mut visited_candidates)? {
+					None => continue,
+					Some(children) => children,
+				};
+			removed += 1;
 
 			if b != canon_hash {
 				pruned_branches.extend(children);
@@ -137,6 +167,10 @@ pub fn canonicalize(
 		while let Some((height, next_child)) = frontier.pop() {
 			let children =
 				visit_and_remove_block_entry(next_child, overlay_db, &mut visited_candidates)?;
+			if children.is_some() {
+				removed += 1;
+			}
+			let children = children.unwrap_or_default();
 
 			// extend the frontier of branches to include the given height.
 			frontier.extend(children.into_iter().map(|h| (height + 1, h)));
@@ -153,13 +187,7 @@ pub fn canonicalize(
 	}
 
 	// Update all `CandidateEntry`s, deleting all those which now have empty `block_assignments`.
-	for (candidate_hash, candidate) in visited_candidates.into_iter() {
-		if candidate.block_assignments.is_empty() {
-			overlay_db.delete_candidate_entry(&candidate_hash);
-		} else {
-			overlay_db.write_candidate_entry(candidate);
-		}
-	}
+	let _ = write_visited_candidates(overlay_db, visited_candidates);
 
 	// Update all blocks-at-height keys, deleting all those which now have empty `block_assignments`.
 	for (h, at) in visited_heights.into_iter() {
@@ -177,7 +205,7 @@ pub fn canonicalize(
 
 	overlay_db.write_stored_block_range(new_range);
 
-	Ok(())
+	Ok(removed)
 }
 
 /// Record a new block entry.
@@ -423,3 +451,241 @@ pub fn revert_to(
 
 	Ok(())
 }
+
+/// Prune all blocks more than `max_heights` heights below the top of the stored block range,
+/// regardless of whether they have been finalized.
+///
+/// This bounds the size of the database when finality stalls. Pruning stops at the lowest
+/// height with a block which is not fully approved yet, so no approval state is discarded
+/// which might still be needed.
+///
+/// Returns the new lowest stored height and the number of removed block entries,
+/// if anything was pruned.
+pub fn prune_unfinalized(
+	overlay_db: &mut OverlayedBackend<'_, impl Backend>,
+	max_heights: BlockNumber,
+) -> SubsystemResult<Option<(BlockNumber, usize)>> {
+	let range = match overlay_db.load_stored_blocks()? {
+		Some(range) if range.1.saturating_sub(range.0) > max_heights => range,
+		_ => return Ok(None),
+	};
+
+	let max_start = range.1 - max_heights;
+	let mut new_start = range.0;
+	'heights: while new_start < max_start {
+		for block_hash in overlay_db.load_blocks_at_height(&new_start)? {
+			match overlay_db.load_block_entry(&block_hash)? {
+				Some(entry) if !entry.is_fully_approved() => break 'heights,
+				_ => {},
+			}
+		}
+
+		new_start += 1;
+	}
+
+	if new_start == range.0 {
+		return Ok(None)
+	}
+
+	let mut visited_candidates = HashMap::new();
+	let mut removed = 0;
+
+	for i in range.0..new_start {
+		let at_height = overlay_db.load_blocks_at_height(&i)?;
+		overlay_db.delete_blocks_at_height(i);
+
+		// Children at the new lowest height are kept, even though their parent is gone.
+		for b in at_height {
+			if visit_and_remove_block_entry(b, overlay_db, &mut visited_candidates)?.is_some() {
+				removed += 1;
+			}
+		}
+	}
+
+	let _ = write_visited_candidates(overlay_db, visited_candidates);
+	overlay_db.write_stored_block_range(StoredBlockRange(new_start, range.1));
+
+	Ok(Some((new_start, removed)))
+}
+
+/// The outcome of [`check_consistency`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ConsistencyReport {
+	/// The range of block heights stored in the database, if any.
+	pub stored_range: Option<(BlockNumber, BlockNumber)>,
+	/// The number of block entries reachable from the stored block range.
+	pub block_entries: usize,
+	/// The number of candidate entries referenced by reachable block entries.
+	pub candidate_entries: usize,
+	/// Block hashes listed at some height or as a child of another block, without a reachable
+	/// entry.
+	pub missing_block_entries: usize,
+	/// Candidate hashes referenced by a block entry, without an entry.
+	pub missing_candidate_entries: usize,
+	/// Blocks-at-height entries outside of the stored block range.
+	pub orphaned_heights: usize,
+	/// Block entries which are not reachable from the stored block range.
+	pub orphaned_block_entries: usize,
+	/// Approval entries of candidates which refer to a block that is not reachable.
+	pub orphaned_approval_entries: usize,
+	/// Candidate entries which are not referenced by any reachable block entry.
+	pub orphaned_candidate_entries: usize,
+}
+
+impl ConsistencyReport {
+	/// Whether no inconsistencies were found.
+	pub fn is_consistent(&self) -> bool {
+		self.missing_block_entries == 0 &&
+			self.missing_candidate_entries == 0 &&
+			self.orphaned_heights == 0 &&
+			self.orphaned_block_entries == 0 &&
+			self.orphaned_approval_entries == 0 &&
+			self.orphaned_candidate_entries == 0
+	}
+}
+
+impl std::fmt::Display for ConsistencyReport {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self.stored_range {
+			Some((start, end)) =>
+				writeln!(f, "stored block range: {}..{}", start, end)?,
+			None => writeln!(f, "stored block range: none")?,
+		}
+		writeln!(f, "block entries: {}", self.block_entries)?;
+		writeln!(f, "candidate entries: {}", self.candidate_entries)?;
+		writeln!(f, "missing block entries: {}", self.missing_block_entries)?;
+		writeln!(f, "missing candidate entries: {}", self.missing_candidate_entries)?;
+		writeln!(f, "orphaned heights: {}", self.orphaned_heights)?;
+		writeln!(f, "orphaned block entries: {}", self.orphaned_block_entries)?;
+		writeln!(f, "orphaned approval entries: {}", self.orphaned_approval_entries)?;
+		write!(f, "orphaned candidate entries: {}", self.orphaned_candidate_entries)
+	}
+}
+
+/// Check the consistency of everything reachable from the stored block range, removing
+/// orphaned entries and dangling references from the overlay.
+///
+/// Every blocks-at-height, block and candidate entry in the database is visited. Those which
+/// are not reachable from the stored block range are orphaned and removed, as are approval
+/// entries referring to blocks which are not reachable. Without a stored block range,
+/// everything is orphaned.
+///
+/// Missing candidate entries can't be repaired and are only reported.
+pub fn check_consistency(
+	overlay_db: &mut OverlayedBackend<'_, impl Backend>,
+) -> SubsystemResult<ConsistencyReport> {
+	let mut report = ConsistencyReport::default();
+
+	let range = overlay_db.load_stored_blocks()?;
+	report.stored_range = range.as_ref().map(|range| (range.0, range.1));
+	let heights = range.map_or(0..0, |range| range.0..range.1);
+
+	// Remove heights outside of the range, e.g. left behind by an interrupted pruning.
+	for height in overlay_db.load_all_block_heights()? {
+		if !heights.contains(&height) {
+			overlay_db.delete_blocks_at_height(height);
+			report.orphaned_heights += 1;
+		}
+	}
+
+	// Visit all blocks within the range, dropping references to missing block entries.
+	let mut reachable_blocks = HashMap::new();
+	for height in heights {
+		let listed = overlay_db.load_blocks_at_height(&height)?;
+		let mut at_height = Vec::with_capacity(listed.len());
+		for hash in &listed {
+			if let Some(entry) = overlay_db.load_block_entry(hash)? {
+				reachable_blocks.insert(*hash, entry);
+				at_height.push(*hash);
+			}
+		}
+
+		if at_height.len() != listed.len() {
+			report.missing_block_entries += listed.len() - at_height.len();
+			if at_height.is_empty() {
+				overlay_db.delete_blocks_at_height(height);
+			} else {
+				overlay_db.write_blocks_at_height(height, at_height);
+			}
+		}
+	}
+
+	let mut referenced_candidates = HashMap::new();
+	let mut modified_blocks = Vec::new();
+	for (block_hash, entry) in &reachable_blocks {
+		for (_, candidate_hash) in entry.candidates() {
+			referenced_candidates
+				.entry(*candidate_hash)
+				.or_insert_with(Vec::new)
+				.push(*block_hash);
+		}
+
+		let n_children = entry.children.len();
+		let children: Vec<_> = entry
+			.children
+			.iter()
+			.filter(|child| reachable_blocks.contains_key(child))
+			.cloned()
+			.collect();
+
+		if children.len() != n_children {
+			report.missing_block_entries += n_children - children.len();
+			let mut entry = entry.clone();
+			entry.children = children;
+			modified_blocks.push(entry);
+		}
+	}
+	report.block_entries = reachable_blocks.len();
+
+	for entry in modified_blocks {
+		overlay_db.write_block_entry(entry);
+	}
+
+	// Remove block entries which are not reachable and candidate entries which are not
+	// referenced by any reachable block.
+	for block_hash in overlay_db.load_all_block_entry_hashes()? {
+		if !reachable_blocks.contains_key(&block_hash) {
+			overlay_db.delete_block_entry(&block_hash);
+			report.orphaned_block_entries += 1;
+		}
+	}
+
+	for candidate_hash in overlay_db.load_all_candidate_entry_hashes()? {
+		if !referenced_candidates.contains_key(&candidate_hash) {
+			overlay_db.delete_candidate_entry(&candidate_hash);
+			report.orphaned_candidate_entries += 1;
+		}
+	}
+
+	// Visit all candidates referenced by reachable blocks, dropping approval entries which
+	// refer to blocks which are not reachable.
+	for candidate_hash in referenced_candidates.keys() {
+		let mut candidate_entry = match overlay_db.load_candidate_entry(candidate_hash)? {
+			Some(entry) => entry,
+			None => {
+				gum::warn!(
+					target: LOG_TARGET,
+					?candidate_hash,
+					blocks = ?referenced_candidates[candidate_hash],
+					"Missing candidate entry for block in approval DB",
+				);
+				report.missing_candidate_entries += 1;
+				continue
+			},
+		};
+		report.candidate_entries += 1;
+
+		let n_assignments = candidate_entry.block_assignments.len();
+		candidate_entry
+			.block_assignments
+			.retain(|block_hash, _| reachable_blocks.contains_key(block_hash));
+
+		if candidate_entry.block_assignments.len() != n_assignments {
+			report.orphaned_approval_entries +=
+				n_assignments - candidate_entry.block_assignments.len();
+			overlay_db.write_candidate_entry(candidate_entry);
+		}
+	}
+
+	Ok(report)
+}
@@ -297,6 +297,18 @@ impl Backend for TestStoreInner {
 		Ok(self.stored_block_range.clone())
 	}
 
+	fn load_all_block_heights(&self) -> SubsystemResult<Vec<BlockNumber>> {
+		Ok(self.blocks_at_height.keys().cloned().collect())
+	}
+
+	fn load_all_block_entry_hashes(&self) -> SubsystemResult<Vec<Hash>> {
+		Ok(self.block_entries.keys().cloned().collect())
+	}
+
+	fn load_all_candidate_entry_hashes(&self) -> SubsystemResult<Vec<CandidateHash>> {
+		Ok(self.candidate_entries.keys().cloned().collect())
+	}
+
 	fn write<I>(&mut self, ops: I) -> SubsystemResult<()>
 	where
 		I: IntoIterator<Item = BackendWriteOp>,
@@ -369,6 +381,21 @@ impl Backend for TestStore {
 		store.load_stored_blocks()
 	}
 
+	fn load_all_block_heights(&self) -> SubsystemResult<Vec<BlockNumber>> {
+		let store = self.store.lock();
+		store.load_all_block_heights()
+	}
+
+	fn load_all_block_entry_hashes(&self) -> SubsystemResult<Vec<Hash>> {
+		let store = self.store.lock();
+		store.load_all_block_entry_hashes()
+	}
+
+	fn load_all_candidate_entry_hashes(&self) -> SubsystemResult<Vec<CandidateHash>> {
+		let store = self.store.lock();
+		store.load_all_candidate_entry_hashes()
+	}
+
 	fn write<I>(&mut self, ops: I) -> SubsystemResult<()>
 	where
 		I: IntoIterator<Item = BackendWriteOp>,
@@ -487,6 +514,7 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(
 			Config {
 				col_data: test_constants::TEST_CONFIG.col_data,
 				slot_duration_millis: SLOT_DURATION_MILLIS,
+				max_unfinalized_heights: None,
 			},
 			Arc::new(db),
 			Arc::new(keystore),
@@ -1497,6 +1525,175 @@ fn subsystem_accepts_and_imports_approval_after_assignment() {
 	});
 }
 
+async fn check_and_import_approvals(
+	overseer: &mut VirtualOverseer,
+	approvals: Vec<IndirectSignedApprovalVote>,
+) -> oneshot::Receiver<Vec<ApprovalCheckResult>> {
+	let (tx, rx) = oneshot::channel();
+	overseer_send(
+		overseer,
+		FromOrchestra::Communication {
+			msg: ApprovalVotingMessage::CheckAndImportApprovals(approvals, tx),
+		},
+	)
+	.await;
+	rx
+}
+
+// Sets up a block with a single candidate and imports assignments for both
+// default validators, returning the block and candidate hash.
+async fn setup_block_with_two_assignments(
+	virtual_overseer: &mut VirtualOverseer,
+) -> (Hash, CandidateHash) {
+	assert_matches!(
+		overseer_recv(virtual_overseer).await,
+		AllMessages::ChainApi(ChainApiMessage::FinalizedBlockNumber(rx)) => {
+			rx.send(Ok(0)).unwrap();
+		}
+	);
+
+	let block_hash = Hash::repeat_byte(0x01);
+
+	let candidate_hash = {
+		let mut candidate_receipt =
+			dummy_candidate_receipt_bad_sig(block_hash, Some(Default::default()));
+		candidate_receipt.descriptor.para_id = ParaId::from(0_u32);
+		candidate_receipt.descriptor.relay_parent = block_hash;
+		candidate_receipt.hash()
+	};
+
+	ChainBuilder::new()
+		.add_block(
+			block_hash,
+			ChainBuilder::GENESIS_HASH,
+			1,
+			BlockConfig { slot: Slot::from(1), candidates: None, session_info: None },
+		)
+		.build(virtual_overseer)
+		.await;
+
+	for validator in [ValidatorIndex(0), ValidatorIndex(1)] {
+		let rx = check_and_import_assignment(virtual_overseer, block_hash, 0, validator).await;
+		assert_eq!(rx.await, Ok(AssignmentCheckResult::Accepted));
+	}
+
+	(block_hash, candidate_hash)
+}
+
+#[test]
+fn subsystem_accepts_batch_of_valid_approvals() {
+	test_harness(HarnessConfig::default(), |test_harness| async move {
+		let TestHarness { mut virtual_overseer, sync_oracle_handle: _sync_oracle_handle, .. } =
+			test_harness;
+
+		let (block_hash, candidate_hash) =
+			setup_block_with_two_assignments(&mut virtual_overseer).await;
+
+		let session_index = 1;
+		let approvals = vec![
+			IndirectSignedApprovalVote {
+				block_hash,
+				candidate_index: 0,
+				validator: ValidatorIndex(0),
+				signature: sign_approval(Sr25519Keyring::Alice, candidate_hash, session_index),
+			},
+			IndirectSignedApprovalVote {
+				block_hash,
+				candidate_index: 0,
+				validator: ValidatorIndex(1),
+				signature: sign_approval(Sr25519Keyring::Bob, candidate_hash, session_index),
+			},
+		];
+
+		let rx = check_and_import_approvals(&mut virtual_overseer, approvals).await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::ChainSelection(ChainSelectionMessage::Approved(b_hash)) => {
+				assert_eq!(b_hash, block_hash);
+			}
+		);
+
+		for _ in 0..2 {
+			assert_matches!(
+				overseer_recv(&mut virtual_overseer).await,
+				AllMessages::DisputeCoordinator(DisputeCoordinatorMessage::ImportStatements {
+					candidate_hash: c_hash,
+					pending_confirmation: None,
+					..
+				}) => {
+					assert_eq!(c_hash, candidate_hash);
+				}
+			);
+		}
+
+		assert_eq!(
+			rx.await,
+			Ok(vec![ApprovalCheckResult::Accepted, ApprovalCheckResult::Accepted])
+		);
+
+		virtual_overseer
+	});
+}
+
+#[test]
+fn subsystem_identifies_bad_signature_in_approval_batch() {
+	test_harness(HarnessConfig::default(), |test_harness| async move {
+		let TestHarness { mut virtual_overseer, sync_oracle_handle: _sync_oracle_handle, .. } =
+			test_harness;
+
+		let (block_hash, candidate_hash) =
+			setup_block_with_two_assignments(&mut virtual_overseer).await;
+
+		let session_index = 1;
+		let approvals = vec![
+			IndirectSignedApprovalVote {
+				block_hash,
+				candidate_index: 0,
+				validator: ValidatorIndex(0),
+				signature: sign_approval(Sr25519Keyring::Alice, candidate_hash, session_index),
+			},
+			// Signed by the wrong key.
+			IndirectSignedApprovalVote {
+				block_hash,
+				candidate_index: 0,
+				validator: ValidatorIndex(1),
+				signature: sign_approval(Sr25519Keyring::Alice, candidate_hash, session_index),
+			},
+		];
+
+		let rx = check_and_import_approvals(&mut virtual_overseer, approvals).await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::ChainSelection(ChainSelectionMessage::Approved(b_hash)) => {
+				assert_eq!(b_hash, block_hash);
+			}
+		);
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::DisputeCoordinator(DisputeCoordinatorMessage::ImportStatements {
+				candidate_hash: c_hash,
+				pending_confirmation: None,
+				..
+			}) => {
+				assert_eq!(c_hash, candidate_hash);
+			}
+		);
+
+		assert_eq!(
+			rx.await,
+			Ok(vec![
+				ApprovalCheckResult::Accepted,
+				ApprovalCheckResult::Bad(ApprovalCheckError::InvalidSignature(ValidatorIndex(1))),
+			]),
+		);
+
+		virtual_overseer
+	});
+}
+
 #[test]
 fn subsystem_second_approval_import_only_schedules_wakeups() {
 	test_harness(HarnessConfig::default(), |test_harness| async move {
@@ -40,7 +40,8 @@ use polkadot_node_subsystem::{
 };
 use polkadot_node_subsystem_util as util;
 use polkadot_primitives::v2::{
-	BlockNumber, CandidateEvent, CandidateHash, CandidateReceipt, Hash, Header, ValidatorIndex,
+	BlockNumber, CandidateEvent, CandidateHash, CandidateReceipt, Hash, Header, SessionIndex,
+	ValidatorIndex,
 };
 
 mod metrics;
@@ -58,6 +59,13 @@ const CHUNK_PREFIX: &[u8; 5] = b"chunk";
 const META_PREFIX: &[u8; 4] = b"meta";
 const UNFINALIZED_PREFIX: &[u8; 11] = b"unfinalized";
 const PRUNE_BY_TIME_PREFIX: &[u8; 13] = b"prune_by_time";
+const STORED_BYTES_PREFIX: &[u8; 12] = b"stored_bytes";
+const ARCHIVED_PREFIX: &[u8; 8] = b"archived";
+const ARCHIVE_BY_SESSION_PREFIX: &[u8; 18] = b"archive_by_session";
+
+// Marks that the stored bytes of candidates stored before the accounting was introduced
+// have been filled in.
+const STORED_BYTES_BACKFILLED_KEY: &[u8; 23] = b"backfilled_stored_bytes";
 
 // We have some keys we want to map to empty values because existence of the key is enough. We use this because
 // rocksdb doesn't support empty values.
@@ -126,7 +134,29 @@ impl Decode for BEBlockNumber {
 	}
 }
 
-#[derive(Debug, Encode, Decode)]
+/// [`SessionIndex`] wrapper with big-endian encoding.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord)]
+struct BESessionIndex(SessionIndex);
+
+impl Encode for BESessionIndex {
+	fn size_hint(&self) -> usize {
+		std::mem::size_of::<SessionIndex>()
+	}
+
+	fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
+		f(&self.0.to_be_bytes())
+	}
+}
+
+impl Decode for BESessionIndex {
+	fn decode<I: Input>(value: &mut I) -> Result<Self, CodecError> {
+		<[u8; std::mem::size_of::<SessionIndex>()]>::decode(value)
+			.map(SessionIndex::from_be_bytes)
+			.map(Self)
+	}
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
 enum State {
 	/// Candidate data was first observed at the given time but is not available in any block.
 	#[codec(index = 0)]
@@ -250,6 +280,57 @@ fn delete_meta(tx: &mut DBTransaction, config: &Config, hash: &CandidateHash) {
 	tx.delete(config.col_meta, &key[..])
 }
 
+fn load_stored_bytes(
+	db: &Arc<dyn Database>,
+	config: &Config,
+	hash: &CandidateHash,
+) -> Result<u64, Error> {
+	let key = (STORED_BYTES_PREFIX, hash).encode();
+
+	query_inner(db, config.col_meta, &key).map(|b| b.unwrap_or_default())
+}
+
+fn write_stored_bytes(tx: &mut DBTransaction, config: &Config, hash: &CandidateHash, bytes: u64) {
+	let key = (STORED_BYTES_PREFIX, hash).encode();
+
+	tx.put_vec(config.col_meta, &key, bytes.encode());
+}
+
+fn delete_stored_bytes(tx: &mut DBTransaction, config: &Config, hash: &CandidateHash) {
+	let key = (STORED_BYTES_PREFIX, hash).encode();
+
+	tx.delete(config.col_meta, &key)
+}
+
+fn load_archived_session(
+	db: &Arc<dyn Database>,
+	config: &Config,
+	hash: &CandidateHash,
+) -> Result<Option<SessionIndex>, Error> {
+	let key = (ARCHIVED_PREFIX, hash).encode();
+
+	query_inner(db, config.col_meta, &key)
+}
+
+fn write_archived(
+	tx: &mut DBTransaction,
+	config: &Config,
+	session: SessionIndex,
+	hash: &CandidateHash,
+) {
+	let key = (ARCHIVED_PREFIX, hash).encode();
+	tx.put_vec(config.col_meta, &key, session.encode());
+
+	let key = (ARCHIVE_BY_SESSION_PREFIX, BESessionIndex(session), hash).encode();
+	tx.put_vec(config.col_meta, &key, TOMBSTONE_VALUE.to_vec());
+}
+
+fn delete_archived(tx: &mut DBTransaction, config: &Config, hash: &CandidateHash) {
+	let key = (ARCHIVED_PREFIX, hash).encode();
+
+	tx.delete(config.col_meta, &key)
+}
+
 fn delete_unfinalized_height(tx: &mut DBTransaction, config: &Config, block_number: BlockNumber) {
 	let prefix = (UNFINALIZED_PREFIX, BEBlockNumber(block_number)).encode();
 	tx.delete_prefix(config.col_meta, &prefix);
@@ -315,6 +396,23 @@ fn pruning_range(now: impl Into<BETimestamp>) -> (Vec<u8>, Vec<u8>) {
 	(start, end)
 }
 
+fn archive_session_range(keep_from: SessionIndex) -> (Vec<u8>, Vec<u8>) {
+	// Session indices are encoded as big-endian so lexicographic order is ascending.
+	let start = ARCHIVE_BY_SESSION_PREFIX.encode();
+	let end = (ARCHIVE_BY_SESSION_PREFIX, BESessionIndex(keep_from)).encode();
+
+	(start, end)
+}
+
+fn decode_archive_key(s: &[u8]) -> Result<(SessionIndex, CandidateHash), CodecError> {
+	if !s.starts_with(ARCHIVE_BY_SESSION_PREFIX) {
+		return Err("missing magic string".into())
+	}
+
+	<(BESessionIndex, CandidateHash)>::decode(&mut &s[ARCHIVE_BY_SESSION_PREFIX.len()..])
+		.map(|(s, ch)| (s.0, ch))
+}
+
 fn decode_unfinalized_key(s: &[u8]) -> Result<(BlockNumber, Hash, CandidateHash), CodecError> {
 	if !s.starts_with(UNFINALIZED_PREFIX) {
 		return Err("missing magic string".into())
@@ -395,19 +493,28 @@ impl Error {
 	}
 }
 
-/// Struct holding pruning timing configuration.
-/// The only purpose of this structure is to use different timing
-/// configurations in production and in testing.
-#[derive(Clone)]
-struct PruningConfig {
+/// Struct holding the retention configuration of the availability store.
+#[derive(Debug, Clone, Copy)]
+pub struct PruningConfig {
 	/// How long unavailable data should be kept.
-	keep_unavailable_for: Duration,
+	pub keep_unavailable_for: Duration,
 
 	/// How long finalized data should be kept.
-	keep_finalized_for: Duration,
+	pub keep_finalized_for: Duration,
 
 	/// How often to perform data pruning.
-	pruning_interval: Duration,
+	pub pruning_interval: Duration,
+
+	/// Soft limit on the number of bytes of candidate data kept in the store.
+	///
+	/// When exceeded, the oldest finalized data is pruned ahead of schedule. Unavailable data,
+	/// data included in unfinalized blocks and data finalized within the dispute period is
+	/// never pruned early, so the quota might not be met. Archived data is not accounted for.
+	pub disk_quota: Option<u64>,
+
+	/// The number of sessions for which the available data of finalized candidates is
+	/// archived after the chunks have been pruned. Archiving is disabled if `None`.
+	pub archive_sessions: Option<SessionIndex>,
 }
 
 impl Default for PruningConfig {
@@ -416,6 +523,8 @@ impl Default for PruningConfig {
 			keep_unavailable_for: KEEP_UNAVAILABLE_FOR,
 			keep_finalized_for: KEEP_FINALIZED_FOR,
 			pruning_interval: PRUNING_INTERVAL,
+			disk_quota: None,
+			archive_sessions: None,
 		}
 	}
 }
@@ -427,6 +536,51 @@ pub struct Config {
 	pub col_data: u32,
 	/// The column family for availability store meta information.
 	pub col_meta: u32,
+	/// The retention configuration.
+	pub pruning: PruningConfig,
+}
+
+/// Number of bytes of candidate data held by the store, broken down by candidate state.
+///
+/// Only available data and erasure chunks are accounted for, not the meta information.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct StorageUsage {
+	/// Bytes held for candidates not included in any block.
+	unavailable: u64,
+	/// Bytes held for candidates included in unfinalized blocks.
+	unfinalized: u64,
+	/// Bytes held for candidates included in finalized blocks.
+	finalized: u64,
+}
+
+impl StorageUsage {
+	/// Total number of bytes held by the store.
+	fn total(&self) -> u64 {
+		self.unavailable.saturating_add(self.unfinalized).saturating_add(self.finalized)
+	}
+
+	fn state_bytes_mut(&mut self, state: &State) -> &mut u64 {
+		match state {
+			State::Unavailable(_) => &mut self.unavailable,
+			State::Unfinalized(_, _) => &mut self.unfinalized,
+			State::Finalized(_) => &mut self.finalized,
+		}
+	}
+
+	fn note_stored(&mut self, state: &State, bytes: u64) {
+		let state_bytes = self.state_bytes_mut(state);
+		*state_bytes = state_bytes.saturating_add(bytes);
+	}
+
+	fn note_removed(&mut self, state: &State, bytes: u64) {
+		let state_bytes = self.state_bytes_mut(state);
+		*state_bytes = state_bytes.saturating_sub(bytes);
+	}
+
+	fn note_transition(&mut self, from: &State, to: &State, bytes: u64) {
+		self.note_removed(from, bytes);
+		self.note_stored(to, bytes);
+	}
 }
 
 trait Clock: Send + Sync {
@@ -444,11 +598,13 @@ impl Clock for SystemClock {
 
 /// An implementation of the Availability Store subsystem.
 pub struct AvailabilityStoreSubsystem {
-	pruning_config: PruningConfig,
 	config: Config,
 	db: Arc<dyn Database>,
 	known_blocks: KnownUnfinalizedBlocks,
 	finalized_number: Option<BlockNumber>,
+	// Kept up to date as data is stored, changes state and is pruned, so it only needs to be
+	// read from the database on startup.
+	usage: StorageUsage,
 	metrics: Metrics,
 	clock: Box<dyn Clock>,
 }
@@ -456,31 +612,24 @@ pub struct AvailabilityStoreSubsystem {
 impl AvailabilityStoreSubsystem {
 	/// Create a new `AvailabilityStoreSubsystem` with a given config on disk.
 	pub fn new(db: Arc<dyn Database>, config: Config, metrics: Metrics) -> Self {
-		Self::with_pruning_config_and_clock(
-			db,
-			config,
-			PruningConfig::default(),
-			Box::new(SystemClock),
-			metrics,
-		)
+		Self::with_clock(db, config, Box::new(SystemClock), metrics)
 	}
 
 	/// Create a new `AvailabilityStoreSubsystem` with a given config on disk.
-	fn with_pruning_config_and_clock(
+	fn with_clock(
 		db: Arc<dyn Database>,
 		config: Config,
-		pruning_config: PruningConfig,
 		clock: Box<dyn Clock>,
 		metrics: Metrics,
 	) -> Self {
 		Self {
-			pruning_config,
 			config,
 			db,
 			metrics,
 			clock,
 			known_blocks: KnownUnfinalizedBlocks::default(),
 			finalized_number: None,
+			usage: StorageUsage::default(),
 		}
 	}
 }
@@ -529,7 +678,16 @@ impl<Context> AvailabilityStoreSubsystem {
 
 #[overseer::contextbounds(AvailabilityStore, prefix = self::overseer)]
 async fn run<Context>(mut subsystem: AvailabilityStoreSubsystem, mut ctx: Context) {
-	let mut next_pruning = Delay::new(subsystem.pruning_config.pruning_interval).fuse();
+	if let Err(e) = backfill_stored_bytes(&subsystem.db, &subsystem.config) {
+		e.trace();
+	}
+	match storage_usage(&subsystem.db, &subsystem.config) {
+		Ok(usage) => subsystem.usage = usage,
+		Err(e) => e.trace(),
+	}
+	subsystem.metrics.note_storage_usage(&subsystem.usage);
+
+	let mut next_pruning = Delay::new(subsystem.config.pruning.pruning_interval).fuse();
 
 	loop {
 		let res = run_iteration(&mut ctx, &mut subsystem, &mut next_pruning).await;
@@ -574,7 +732,7 @@ async fn run_iteration<Context>(
 					subsystem.known_blocks.prune_finalized(number);
 					process_block_finalized(
 						ctx,
-						&subsystem,
+						subsystem,
 						hash,
 						number,
 					).await?;
@@ -588,10 +746,24 @@ async fn run_iteration<Context>(
 		_ = next_pruning => {
 			// It's important to set the delay before calling `prune_all` because an error in `prune_all`
 			// could lead to the delay not being set again. Then we would never prune anything anymore.
-			*next_pruning = Delay::new(subsystem.pruning_config.pruning_interval).fuse();
+			*next_pruning = Delay::new(subsystem.config.pruning.pruning_interval).fuse();
 
 			let _timer = subsystem.metrics.time_pruning();
-			prune_all(&subsystem.db, &subsystem.config, &*subsystem.clock)?;
+			let now = subsystem.clock.now()?;
+			prune_all(&subsystem.db, &subsystem.config, now, &mut subsystem.usage)?;
+
+			match subsystem.config.pruning.disk_quota {
+				Some(quota) if subsystem.usage.total() > quota => prune_to_quota(
+					&subsystem.db,
+					&subsystem.config,
+					&subsystem.metrics,
+					now,
+					quota,
+					&mut subsystem.usage,
+				)?,
+				_ => {},
+			}
+			subsystem.metrics.note_storage_usage(&subsystem.usage);
 		}
 	}
 
@@ -632,19 +804,22 @@ async fn process_block_activated<Context>(
 		// it's important to commit the db transactions for a head before the next one is processed
 		// alternatively, we could utilize the OverlayBackend from approval-voting
 		let mut tx = DBTransaction::new();
+		let mut usage = subsystem.usage;
 		process_new_head(
 			ctx,
 			&subsystem.db,
 			&mut tx,
 			&subsystem.config,
-			&subsystem.pruning_config,
+			&subsystem.config.pruning,
 			now,
 			hash,
 			header,
+			&mut usage,
 		)
 		.await?;
 		subsystem.known_blocks.insert(hash, block_number);
 		subsystem.db.write(tx)?;
+		subsystem.usage = usage;
 	}
 
 	Ok(())
@@ -660,6 +835,7 @@ async fn process_new_head<Context>(
 	now: Duration,
 	hash: Hash,
 	header: Header,
+	usage: &mut StorageUsage,
 ) -> Result<(), Error> {
 	let candidate_events = util::request_candidate_events(hash, ctx.sender()).await.await??;
 
@@ -689,6 +865,7 @@ async fn process_new_head<Context>(
 					pruning_config,
 					(header.number, hash),
 					receipt,
+					usage,
 				)?;
 			},
 			_ => {},
@@ -734,6 +911,7 @@ fn note_block_included(
 	pruning_config: &PruningConfig,
 	block: (BlockNumber, Hash),
 	candidate: CandidateReceipt,
+	usage: &mut StorageUsage,
 ) -> Result<(), Error> {
 	let candidate_hash = candidate.hash();
 
@@ -752,6 +930,7 @@ fn note_block_included(
 
 			gum::debug!(target: LOG_TARGET, ?candidate_hash, "Candidate included");
 
+			let previous_state = meta.state.clone();
 			meta.state = match meta.state {
 				State::Unavailable(at) => {
 					let at_d: Duration = at.into();
@@ -783,6 +962,9 @@ fn note_block_included(
 				&candidate_hash,
 			);
 			write_meta(db_transaction, config, &candidate_hash, &meta);
+
+			let stored_bytes = load_stored_bytes(db, config, &candidate_hash)?;
+			usage.note_transition(&previous_state, &meta.state, stored_bytes);
 		},
 	}
 
@@ -801,12 +983,15 @@ macro_rules! peek_num {
 #[overseer::contextbounds(AvailabilityStore, prefix = self::overseer)]
 async fn process_block_finalized<Context>(
 	ctx: &mut Context,
-	subsystem: &AvailabilityStoreSubsystem,
+	subsystem: &mut AvailabilityStoreSubsystem,
 	finalized_hash: Hash,
 	finalized_number: BlockNumber,
 ) -> Result<(), Error> {
 	let now = subsystem.clock.now()?;
 
+	// The latest session any candidate was archived in, if archiving is enabled.
+	let mut archive_session = None;
+
 	let mut next_possible_batch = 0;
 	loop {
 		let mut db_transaction = DBTransaction::new();
@@ -864,6 +1049,28 @@ async fn process_block_finalized<Context>(
 			}
 		};
 
+		let batch_session = if subsystem.config.pruning.archive_sessions.is_some() {
+			match util::request_session_index_for_child(batch_finalized_hash, ctx.sender())
+				.await
+				.await?
+			{
+				Ok(session) => Some(session),
+				Err(err) => {
+					gum::warn!(
+						target: LOG_TARGET,
+						batch_num,
+						?err,
+						"Failed to retrieve session index, not archiving candidates.",
+					);
+
+					None
+				},
+			}
+		} else {
+			None
+		};
+		archive_session = archive_session.max(batch_session);
+
 		let iter = subsystem
 			.db
 			.iter_with_prefix(subsystem.config.col_meta, &start_prefix)
@@ -877,11 +1084,31 @@ async fn process_block_finalized<Context>(
 
 		delete_unfinalized_height(&mut db_transaction, &subsystem.config, batch_num);
 
-		update_blocks_at_finalized_height(&subsystem, &mut db_transaction, batch, batch_num, now)?;
+		let mut usage = subsystem.usage;
+		update_blocks_at_finalized_height(
+			&subsystem,
+			&mut db_transaction,
+			batch,
+			batch_num,
+			batch_session,
+			now,
+			&mut usage,
+		)?;
 
 		// We need to write at the end of the loop so the prefix iterator doesn't pick up the same values again
 		// in the next iteration. Another unfortunate effect of having to re-initialize the iterator.
 		subsystem.db.write(db_transaction)?;
+		subsystem.usage = usage;
+	}
+
+	if let (Some(session), Some(archive_sessions)) =
+		(archive_session, subsystem.config.pruning.archive_sessions)
+	{
+		prune_archive(
+			&subsystem.db,
+			&subsystem.config,
+			session.saturating_sub(archive_sessions.saturating_sub(1)),
+		)?;
 	}
 
 	Ok(())
@@ -924,7 +1151,9 @@ fn update_blocks_at_finalized_height(
 	db_transaction: &mut DBTransaction,
 	candidates: impl IntoIterator<Item = (CandidateHash, bool)>,
 	block_number: BlockNumber,
+	archive_session: Option<SessionIndex>,
 	now: Duration,
+	usage: &mut StorageUsage,
 ) -> Result<(), Error> {
 	for (candidate_hash, is_finalized) in candidates {
 		let mut meta = match load_meta(&subsystem.db, &subsystem.config, &candidate_hash)? {
@@ -939,6 +1168,7 @@ fn update_blocks_at_finalized_height(
 			},
 			Some(c) => c,
 		};
+		let previous_state = meta.state.clone();
 
 		if is_finalized {
 			// Clear everything else related to this block. We're finalized now!
@@ -968,12 +1198,17 @@ fn update_blocks_at_finalized_height(
 
 			meta.state = State::Finalized(now.into());
 
+			// Keep the available data around past regular pruning.
+			if let (Some(session), true) = (archive_session, meta.data_available) {
+				write_archived(db_transaction, &subsystem.config, session, &candidate_hash);
+			}
+
 			// Write the meta and a pruning record.
 			write_meta(db_transaction, &subsystem.config, &candidate_hash, &meta);
 			write_pruning_key(
 				db_transaction,
 				&subsystem.config,
-				now + subsystem.pruning_config.keep_finalized_for,
+				now + subsystem.config.pruning.keep_finalized_for,
 				&candidate_hash,
 			);
 		} else {
@@ -988,7 +1223,7 @@ fn update_blocks_at_finalized_height(
 					// aware of any blocks this is included in.
 					if blocks.is_empty() {
 						let at_d: Duration = at.into();
-						let prune_at = at_d + subsystem.pruning_config.keep_unavailable_for;
+						let prune_at = at_d + subsystem.config.pruning.keep_unavailable_for;
 						write_pruning_key(
 							db_transaction,
 							&subsystem.config,
@@ -1005,6 +1240,9 @@ fn update_blocks_at_finalized_height(
 			// Update the meta entry.
 			write_meta(db_transaction, &subsystem.config, &candidate_hash, &meta)
 		}
+
+		let stored_bytes = load_stored_bytes(&subsystem.db, &subsystem.config, &candidate_hash)?;
+		usage.note_transition(&previous_state, &meta.state, stored_bytes);
 	}
 
 	Ok(())
@@ -1018,6 +1256,13 @@ fn process_message(
 		AvailabilityStoreMessage::QueryAvailableData(candidate, tx) => {
 			let _ = tx.send(load_available_data(&subsystem.db, &subsystem.config, &candidate)?);
 		},
+		AvailabilityStoreMessage::QueryArchivedAvailableData(candidate, tx) => {
+			let data = match load_archived_session(&subsystem.db, &subsystem.config, &candidate)? {
+				Some(_) => load_available_data(&subsystem.db, &subsystem.config, &candidate)?,
+				None => None,
+			};
+			let _ = tx.send(data);
+		},
 		AvailabilityStoreMessage::QueryDataAvailability(candidate, tx) => {
 			let a = load_meta(&subsystem.db, &subsystem.config, &candidate)?
 				.map_or(false, |m| m.data_available);
@@ -1070,7 +1315,13 @@ fn process_message(
 			subsystem.metrics.on_chunks_received(1);
 			let _timer = subsystem.metrics.time_store_chunk();
 
-			match store_chunk(&subsystem.db, &subsystem.config, candidate_hash, chunk) {
+			match store_chunk(
+				&subsystem.db,
+				&subsystem.config,
+				candidate_hash,
+				chunk,
+				&mut subsystem.usage,
+			) {
 				Ok(true) => {
 					let _ = tx.send(Ok(()));
 				},
@@ -1094,7 +1345,7 @@ fn process_message(
 			let _timer = subsystem.metrics.time_store_available_data();
 
 			let res =
-				store_available_data(&subsystem, candidate_hash, n_validators as _, available_data);
+				store_available_data(subsystem, candidate_hash, n_validators as _, available_data);
 
 			match res {
 				Ok(()) => {
@@ -1117,8 +1368,10 @@ fn store_chunk(
 	config: &Config,
 	candidate_hash: CandidateHash,
 	chunk: ErasureChunk,
+	usage: &mut StorageUsage,
 ) -> Result<bool, Error> {
 	let mut tx = DBTransaction::new();
+	let chunk_bytes = chunk.encoded_size() as u64;
 
 	let mut meta = match load_meta(db, config, &candidate_hash)? {
 		Some(m) => m,
@@ -1130,8 +1383,12 @@ fn store_chunk(
 		Some(false) => {
 			meta.chunks_stored.set(chunk.index.0 as usize, true);
 
+			let stored_bytes =
+				load_stored_bytes(db, config, &candidate_hash)?.saturating_add(chunk_bytes);
+
 			write_chunk(&mut tx, config, &candidate_hash, chunk.index, &chunk);
 			write_meta(&mut tx, config, &candidate_hash, &meta);
+			write_stored_bytes(&mut tx, config, &candidate_hash, stored_bytes);
 		},
 		None => return Ok(false), // out of bounds.
 	}
@@ -1144,12 +1401,13 @@ fn store_chunk(
 	);
 
 	db.write(tx)?;
+	usage.note_stored(&meta.state, chunk_bytes);
 	Ok(true)
 }
 
 // Ok(true) on success, Ok(false) on failure, and Err on internal error.
 fn store_available_data(
-	subsystem: &AvailabilityStoreSubsystem,
+	subsystem: &mut AvailabilityStoreSubsystem,
 	candidate_hash: CandidateHash,
 	n_validators: usize,
 	available_data: AvailableData,
@@ -1168,7 +1426,7 @@ fn store_available_data(
 			let now = subsystem.clock.now()?;
 
 			// Write a pruning record.
-			let prune_at = now + subsystem.pruning_config.keep_unavailable_for;
+			let prune_at = now + subsystem.config.pruning.keep_unavailable_for;
 			write_pruning_key(&mut tx, &subsystem.config, prune_at, &candidate_hash);
 
 			CandidateMeta {
@@ -1190,7 +1448,11 @@ fn store_available_data(
 		},
 	);
 
+	// Any chunks stored previously are overwritten, so the size is accounted for from scratch.
+	let previous_bytes = load_stored_bytes(&subsystem.db, &subsystem.config, &candidate_hash)?;
+	let mut stored_bytes = available_data.encoded_size() as u64;
 	for chunk in erasure_chunks {
+		stored_bytes = stored_bytes.saturating_add(chunk.encoded_size() as u64);
 		write_chunk(&mut tx, &subsystem.config, &candidate_hash, chunk.index, &chunk);
 	}
 
@@ -1199,19 +1461,27 @@ fn store_available_data(
 
 	write_meta(&mut tx, &subsystem.config, &candidate_hash, &meta);
 	write_available_data(&mut tx, &subsystem.config, &candidate_hash, &available_data);
+	write_stored_bytes(&mut tx, &subsystem.config, &candidate_hash, stored_bytes);
 
 	subsystem.db.write(tx)?;
+	subsystem.usage.note_removed(&meta.state, previous_bytes);
+	subsystem.usage.note_stored(&meta.state, stored_bytes);
 
 	gum::debug!(target: LOG_TARGET, ?candidate_hash, "Stored data and chunks");
 
 	Ok(())
 }
 
-fn prune_all(db: &Arc<dyn Database>, config: &Config, clock: &dyn Clock) -> Result<(), Error> {
-	let now = clock.now()?;
+fn prune_all(
+	db: &Arc<dyn Database>,
+	config: &Config,
+	now: Duration,
+	usage: &mut StorageUsage,
+) -> Result<(), Error> {
 	let (range_start, range_end) = pruning_range(now);
 
 	let mut tx = DBTransaction::new();
+	let mut usage_after = *usage;
 	let iter = db
 		.iter_with_prefix(config.col_meta, &range_start[..])
 		.take_while(|(k, _)| &k[..] < &range_end[..]);
@@ -1224,36 +1494,234 @@ fn prune_all(db: &Arc<dyn Database>, config: &Config, clock: &dyn Clock) -> Resu
 			Err(_) => continue, // sanity
 		};
 
-		delete_meta(&mut tx, config, &candidate_hash);
+		prune_candidate(db, &mut tx, config, &candidate_hash, &mut usage_after)?;
+	}
 
-		// Clean up all attached data of the candidate.
-		if let Some(meta) = load_meta(db, config, &candidate_hash)? {
-			// delete available data.
-			if meta.data_available {
-				delete_available_data(&mut tx, config, &candidate_hash)
+	db.write(tx)?;
+	*usage = usage_after;
+	Ok(())
+}
+
+// Removes the meta information of a candidate along with all attached data, except for
+// archived available data, and accounts for it in `usage`. Pruning keys are left to the caller.
+fn prune_candidate(
+	db: &Arc<dyn Database>,
+	tx: &mut DBTransaction,
+	config: &Config,
+	candidate_hash: &CandidateHash,
+	usage: &mut StorageUsage,
+) -> Result<(), Error> {
+	delete_meta(tx, config, candidate_hash);
+	delete_stored_bytes(tx, config, candidate_hash);
+
+	// Clean up all attached data of the candidate.
+	if let Some(meta) = load_meta(db, config, candidate_hash)? {
+		usage.note_removed(&meta.state, load_stored_bytes(db, config, candidate_hash)?);
+
+		// delete available data, unless archived.
+		if meta.data_available && load_archived_session(db, config, candidate_hash)?.is_none() {
+			delete_available_data(tx, config, candidate_hash)
+		}
+
+		// delete chunks.
+		for (i, b) in meta.chunks_stored.iter().enumerate() {
+			if *b {
+				delete_chunk(tx, config, candidate_hash, ValidatorIndex(i as _));
 			}
+		}
 
-			// delete chunks.
-			for (i, b) in meta.chunks_stored.iter().enumerate() {
-				if *b {
-					delete_chunk(&mut tx, config, &candidate_hash, ValidatorIndex(i as _));
-				}
+		// delete unfinalized block references.
+		if let State::Unfinalized(_, blocks) = meta.state {
+			for (block_number, block_hash) in blocks {
+				delete_unfinalized_inclusion(
+					tx,
+					config,
+					block_number.0,
+					&block_hash,
+					candidate_hash,
+				);
 			}
+		}
+	}
 
-			// delete unfinalized block references. Pruning references don't need to be
-			// manually taken care of as we are deleting them as we go in the outer loop.
-			if let State::Unfinalized(_, blocks) = meta.state {
-				for (block_number, block_hash) in blocks {
-					delete_unfinalized_inclusion(
-						&mut tx,
-						config,
-						block_number.0,
-						&block_hash,
-						&candidate_hash,
-					);
+	Ok(())
+}
+
+// Sums up the stored bytes of all candidates. This reads the meta of every candidate, so it
+// is only done on startup and the usage is kept up to date from there on.
+fn storage_usage(db: &Arc<dyn Database>, config: &Config) -> Result<StorageUsage, Error> {
+	let mut usage = StorageUsage::default();
+
+	for (k, v) in db.iter_with_prefix(config.col_meta, &STORED_BYTES_PREFIX[..]) {
+		let candidate_hash = match CandidateHash::decode(&mut &k[STORED_BYTES_PREFIX.len()..]) {
+			Ok(h) => h,
+			Err(_) => continue, // sanity
+		};
+		let bytes = u64::decode(&mut &v[..])?;
+
+		if let Some(meta) = load_meta(db, config, &candidate_hash)? {
+			usage.note_stored(&meta.state, bytes);
+		}
+	}
+
+	Ok(usage)
+}
+
+// Fills in the stored bytes of candidates stored before they were accounted for, so they
+// count towards the disk quota. This only needs to happen once per database.
+fn backfill_stored_bytes(db: &Arc<dyn Database>, config: &Config) -> Result<(), Error> {
+	if db.get(config.col_meta, &STORED_BYTES_BACKFILLED_KEY[..])?.is_some() {
+		return Ok(())
+	}
+
+	let mut tx = DBTransaction::new();
+	let mut backfilled = 0;
+	for (k, v) in db.iter_with_prefix(config.col_meta, &META_PREFIX[..]) {
+		let candidate_hash = match CandidateHash::decode(&mut &k[META_PREFIX.len()..]) {
+			Ok(h) => h,
+			Err(_) => continue, // sanity
+		};
+
+		let stored_bytes_key = (STORED_BYTES_PREFIX, candidate_hash).encode();
+		if db.get(config.col_meta, &stored_bytes_key)?.is_some() {
+			continue
+		}
+
+		let meta = CandidateMeta::decode(&mut &v[..])?;
+		let mut stored_bytes = 0u64;
+		if meta.data_available {
+			let key = (AVAILABLE_PREFIX, candidate_hash).encode();
+			if let Some(raw) = db.get(config.col_data, &key)? {
+				stored_bytes = stored_bytes.saturating_add(raw.len() as u64);
+			}
+		}
+		for (i, b) in meta.chunks_stored.iter().enumerate() {
+			if *b {
+				let key = (CHUNK_PREFIX, candidate_hash, ValidatorIndex(i as _)).encode();
+				if let Some(raw) = db.get(config.col_data, &key)? {
+					stored_bytes = stored_bytes.saturating_add(raw.len() as u64);
 				}
 			}
 		}
+
+		write_stored_bytes(&mut tx, config, &candidate_hash, stored_bytes);
+		backfilled += 1;
+	}
+
+	tx.put(config.col_meta, &STORED_BYTES_BACKFILLED_KEY[..], TOMBSTONE_VALUE);
+	db.write(tx)?;
+
+	if backfilled > 0 {
+		gum::info!(target: LOG_TARGET, backfilled, "Accounted for the size of previously stored data");
+	}
+
+	Ok(())
+}
+
+// Prunes finalized candidates ahead of schedule until the usage drops to the quota, starting
+// with the oldest. Unavailable data is left to regular pruning, as it might still become
+// available, and data finalized within the dispute period is kept, as it might still be needed
+// to participate in disputes.
+fn prune_to_quota(
+	db: &Arc<dyn Database>,
+	config: &Config,
+	metrics: &Metrics,
+	now: Duration,
+	quota: u64,
+	usage: &mut StorageUsage,
+) -> Result<(), Error> {
+	gum::warn!(
+		target: LOG_TARGET,
+		used = usage.total(),
+		quota,
+		"Availability store exceeds its disk quota, pruning early",
+	);
+
+	// Pruning keys are ordered by the time the candidate is due to be pruned, so the oldest
+	// data comes first. Finalized data is due `keep_finalized_for` after finalization, so only
+	// keys up to the one of data finalized `KEEP_FINALIZED_FOR` ago need to be considered.
+	let (range_start, range_end) =
+		pruning_range((now + config.pruning.keep_finalized_for).saturating_sub(KEEP_FINALIZED_FOR));
+
+	let mut tx = DBTransaction::new();
+	let mut usage_after = *usage;
+	let mut pruned = 0;
+	let iter = db
+		.iter_with_prefix(config.col_meta, &range_start[..])
+		.take_while(|(k, _)| &k[..] < &range_end[..]);
+
+	for (k, _v) in iter {
+		if usage_after.total() <= quota {
+			break
+		}
+
+		let (_, candidate_hash) = match decode_pruning_key(&k[..]) {
+			Ok(m) => m,
+			Err(_) => continue, // sanity
+		};
+
+		let finalized_at: Duration = match load_meta(db, config, &candidate_hash)?.map(|m| m.state)
+		{
+			Some(State::Finalized(at)) => at.into(),
+			Some(State::Unavailable(_)) | Some(State::Unfinalized(_, _)) | None => continue,
+		};
+		// `keep_finalized_for` might have changed since the pruning key was written.
+		if finalized_at + KEEP_FINALIZED_FOR > now {
+			continue
+		}
+
+		tx.delete(config.col_meta, &k[..]);
+		prune_candidate(db, &mut tx, config, &candidate_hash, &mut usage_after)?;
+		pruned += 1;
+	}
+
+	db.write(tx)?;
+	*usage = usage_after;
+	metrics.on_quota_pruned(pruned);
+
+	if usage.total() > quota {
+		gum::warn!(
+			target: LOG_TARGET,
+			used = usage.total(),
+			quota,
+			unavailable = usage.unavailable,
+			unfinalized = usage.unfinalized,
+			finalized = usage.finalized,
+			"Availability store still exceeds its disk quota, the remaining data is unavailable, \
+			unfinalized or within the dispute period",
+		);
+	}
+
+	Ok(())
+}
+
+// Removes all candidates archived in sessions before `keep_from` from the archive. Their
+// available data is removed as well unless it is still within regular retention.
+fn prune_archive(
+	db: &Arc<dyn Database>,
+	config: &Config,
+	keep_from: SessionIndex,
+) -> Result<(), Error> {
+	let (range_start, range_end) = archive_session_range(keep_from);
+
+	let mut tx = DBTransaction::new();
+	let iter = db
+		.iter_with_prefix(config.col_meta, &range_start[..])
+		.take_while(|(k, _)| &k[..] < &range_end[..]);
+
+	for (k, _v) in iter {
+		tx.delete(config.col_meta, &k[..]);
+
+		let (_, candidate_hash) = match decode_archive_key(&k[..]) {
+			Ok(m) => m,
+			Err(_) => continue, // sanity
+		};
+
+		delete_archived(&mut tx, config, &candidate_hash);
+
+		if load_meta(db, config, &candidate_hash)?.is_none() {
+			delete_available_data(&mut tx, config, &candidate_hash);
+		}
 	}
 
 	db.write(tx)?;
@@ -16,6 +16,8 @@
 
 use polkadot_node_subsystem_util::metrics::{self, prometheus};
 
+use crate::StorageUsage;
+
 #[derive(Clone)]
 pub(crate) struct MetricsInner {
 	received_availability_chunks_total: prometheus::Counter<prometheus::U64>,
@@ -26,6 +28,8 @@ pub(crate) struct MetricsInner {
 	store_available_data: prometheus::Histogram,
 	store_chunk: prometheus::Histogram,
 	get_chunk: prometheus::Histogram,
+	stored_bytes: prometheus::GaugeVec<prometheus::U64>,
+	quota_pruned_total: prometheus::Counter<prometheus::U64>,
 }
 
 /// Availability metrics.
@@ -41,6 +45,22 @@ impl Metrics {
 		}
 	}
 
+	/// Record the number of bytes held by the store in each candidate state.
+	pub(crate) fn note_storage_usage(&self, usage: &StorageUsage) {
+		if let Some(metrics) = &self.0 {
+			metrics.stored_bytes.with_label_values(&["unavailable"]).set(usage.unavailable);
+			metrics.stored_bytes.with_label_values(&["unfinalized"]).set(usage.unfinalized);
+			metrics.stored_bytes.with_label_values(&["finalized"]).set(usage.finalized);
+		}
+	}
+
+	/// Increment the number of candidates pruned early to stay within the disk quota.
+	pub(crate) fn on_quota_pruned(&self, count: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.quota_pruned_total.inc_by(count as u64);
+		}
+	}
+
 	/// Provide a timer for `prune_povs` which observes on drop.
 	pub(crate) fn time_pruning(&self) -> Option<metrics::prometheus::prometheus::HistogramTimer> {
 		self.0.as_ref().map(|metrics| metrics.pruning.start_timer())
@@ -146,6 +166,23 @@ impl metrics::Metrics for Metrics {
 				))?,
 				registry,
 			)?,
+			stored_bytes: prometheus::register(
+				prometheus::GaugeVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_av_store_stored_bytes",
+						"Number of bytes of available data and chunks held by the availability store, by candidate state.",
+					),
+					&["state"],
+				)?,
+				registry,
+			)?,
+			quota_pruned_total: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_av_store_quota_pruned_total",
+					"Number of candidates pruned ahead of schedule to stay within the disk quota.",
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
@@ -32,7 +32,7 @@ use polkadot_node_subsystem_test_helpers as test_helpers;
 use polkadot_node_subsystem_util::{database::Database, TimeoutExt};
 use polkadot_primitives::v2::{
 	CandidateHash, CandidateReceipt, CoreIndex, GroupIndex, HeadData, Header,
-	PersistedValidationData, ValidatorId,
+	PersistedValidationData, SessionIndex, ValidatorId,
 };
 use sp_keyring::Sr25519Keyring;
 
@@ -42,7 +42,17 @@ mod columns {
 	pub const NUM_COLUMNS: u32 = 2;
 }
 
-const TEST_CONFIG: Config = Config { col_data: columns::DATA, col_meta: columns::META };
+const TEST_CONFIG: Config = Config {
+	col_data: columns::DATA,
+	col_meta: columns::META,
+	pruning: PruningConfig {
+		keep_unavailable_for: KEEP_UNAVAILABLE_FOR,
+		keep_finalized_for: KEEP_FINALIZED_FOR,
+		pruning_interval: PRUNING_INTERVAL,
+		disk_quota: None,
+		archive_sessions: None,
+	},
+};
 
 type VirtualOverseer = test_helpers::TestSubsystemContextHandle<AvailabilityStoreMessage>;
 
@@ -95,6 +105,8 @@ impl Default for TestState {
 			keep_unavailable_for: Duration::from_secs(1),
 			keep_finalized_for: Duration::from_secs(2),
 			pruning_interval: Duration::from_millis(250),
+			disk_quota: None,
+			archive_sessions: None,
 		};
 
 		let clock = TestClock { inner: Arc::new(Mutex::new(Duration::from_secs(0))) };
@@ -117,10 +129,9 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(
 	let pool = sp_core::testing::TaskExecutor::new();
 	let (context, virtual_overseer) = test_helpers::make_subsystem_context(pool.clone());
 
-	let subsystem = AvailabilityStoreSubsystem::with_pruning_config_and_clock(
+	let subsystem = AvailabilityStoreSubsystem::with_clock(
 		store,
-		TEST_CONFIG,
-		state.pruning_config.clone(),
+		Config { pruning: state.pruning_config.clone(), ..TEST_CONFIG },
 		Box::new(state.clock),
 		Metrics::default(),
 	);
@@ -726,6 +737,251 @@ fn stored_data_kept_until_finalized() {
 	});
 }
 
+#[test]
+fn only_finalized_data_past_the_dispute_period_is_pruned_when_over_quota() {
+	let store = test_store();
+	let mut test_state = TestState::default();
+	test_state.pruning_config.disk_quota = Some(100);
+	// Regular pruning doesn't kick in before the end of the dispute period.
+	test_state.pruning_config.keep_unavailable_for = KEEP_FINALIZED_FOR * 2;
+	test_state.pruning_config.keep_finalized_for = KEEP_FINALIZED_FOR * 2;
+
+	test_harness(test_state.clone(), store.clone(), |mut virtual_overseer| async move {
+		let n_validators = 10;
+
+		// Unavailable, exceeding the quota on its own as well.
+		let small_pov = PoV { block_data: BlockData(vec![1, 2, 3]) };
+		let small_candidate_hash = CandidateHash(Hash::repeat_byte(1));
+		let small_available_data = AvailableData {
+			pov: Arc::new(small_pov),
+			validation_data: test_state.persisted_validation_data.clone(),
+		};
+
+		// Finalized and exceeding the quota on its own.
+		let large_pov = PoV { block_data: BlockData(vec![4; 50_000]) };
+		let large_candidate =
+			TestCandidateBuilder { pov_hash: large_pov.hash(), ..Default::default() }.build();
+		let large_candidate_hash = large_candidate.hash();
+		let large_available_data = AvailableData {
+			pov: Arc::new(large_pov),
+			validation_data: test_state.persisted_validation_data.clone(),
+		};
+
+		for (candidate_hash, available_data) in [
+			(small_candidate_hash, small_available_data.clone()),
+			(large_candidate_hash, large_available_data.clone()),
+		] {
+			let (tx, rx) = oneshot::channel();
+			let block_msg = AvailabilityStoreMessage::StoreAvailableData {
+				candidate_hash,
+				n_validators,
+				available_data,
+				tx,
+			};
+
+			virtual_overseer.send(FromOrchestra::Communication { msg: block_msg }).await;
+			rx.await.unwrap().unwrap();
+		}
+
+		let block_number = 10;
+		let new_leaf = import_leaf(
+			&mut virtual_overseer,
+			Hash::repeat_byte(2),
+			block_number,
+			vec![candidate_included(large_candidate)],
+			(0..n_validators).map(|_| Sr25519Keyring::Alice.public().into()).collect(),
+		)
+		.await;
+
+		overseer_signal(
+			&mut virtual_overseer,
+			OverseerSignal::BlockFinalized(new_leaf, block_number),
+		)
+		.await;
+
+		// The finalized data is still needed for disputes, so the quota can't be met.
+		test_state.wait_for_pruning().await;
+		assert_eq!(
+			query_available_data(&mut virtual_overseer, large_candidate_hash).await.unwrap(),
+			large_available_data,
+		);
+		assert!(
+			has_all_chunks(&mut virtual_overseer, large_candidate_hash, n_validators, true).await
+		);
+
+		// Past the dispute period, the finalized data is pruned even though it is due later
+		// than the unavailable data, which is kept even though the quota can't be met.
+		test_state.clock.inc(KEEP_FINALIZED_FOR);
+		test_state.wait_for_pruning().await;
+
+		assert!(query_available_data(&mut virtual_overseer, large_candidate_hash)
+			.await
+			.is_none());
+		assert!(
+			has_all_chunks(&mut virtual_overseer, large_candidate_hash, n_validators, false).await
+		);
+
+		assert_eq!(
+			query_available_data(&mut virtual_overseer, small_candidate_hash).await.unwrap(),
+			small_available_data,
+		);
+		assert!(
+			has_all_chunks(&mut virtual_overseer, small_candidate_hash, n_validators, true).await
+		);
+
+		let usage = storage_usage(&store, &TEST_CONFIG).unwrap();
+		assert_eq!(usage.finalized, 0);
+		assert!(usage.unavailable > 100);
+		virtual_overseer
+	});
+}
+
+#[test]
+fn stored_bytes_are_backfilled_on_startup() {
+	let store = test_store();
+	let candidate_hash = CandidateHash(Hash::repeat_byte(1));
+	let n_validators = 10;
+
+	let chunk = ErasureChunk {
+		chunk: vec![1, 2, 3],
+		index: ValidatorIndex(5),
+		proof: Proof::try_from(vec![vec![3, 4, 5]]).unwrap(),
+	};
+	let available_data = AvailableData {
+		pov: Arc::new(PoV { block_data: BlockData(vec![4; 100]) }),
+		validation_data: TestState::default().persisted_validation_data,
+	};
+
+	// Stored without accounting for the size, as done by earlier versions.
+	let mut chunks_stored = bitvec::bitvec![u8, BitOrderLsb0; 0; n_validators];
+	chunks_stored.set(5, true);
+	with_tx(&store, |tx| {
+		super::write_meta(
+			tx,
+			&TEST_CONFIG,
+			&candidate_hash,
+			&CandidateMeta {
+				data_available: true,
+				chunks_stored,
+				state: State::Unavailable(BETimestamp(0)),
+			},
+		);
+		super::write_chunk(tx, &TEST_CONFIG, &candidate_hash, chunk.index, &chunk);
+		super::write_available_data(tx, &TEST_CONFIG, &candidate_hash, &available_data);
+	});
+	assert_eq!(storage_usage(&store, &TEST_CONFIG).unwrap().total(), 0);
+
+	test_harness(TestState::default(), store.clone(), |virtual_overseer| async move {
+		virtual_overseer
+	});
+
+	let expected = (available_data.encoded_size() + chunk.encoded_size()) as u64;
+	assert_eq!(storage_usage(&store, &TEST_CONFIG).unwrap().unavailable, expected);
+}
+
+#[test]
+fn archived_data_is_kept_for_configured_sessions() {
+	let store = test_store();
+	let mut test_state = TestState::default();
+	test_state.pruning_config.archive_sessions = Some(2);
+
+	test_harness(test_state.clone(), store.clone(), |mut virtual_overseer| async move {
+		let n_validators = 10;
+		let validators: Vec<ValidatorId> =
+			(0..n_validators).map(|_| Sr25519Keyring::Alice.public().into()).collect();
+
+		let (candidate_1, available_data_1) = candidate_with_data(&test_state, 1);
+		let (candidate_2, available_data_2) = candidate_with_data(&test_state, 2);
+		let candidate_hash_1 = candidate_1.hash();
+
+		// Finalize the first candidate in session 1.
+		store_data(&mut virtual_overseer, candidate_hash_1, n_validators, available_data_1.clone())
+			.await;
+
+		let block_number = 10;
+		let leaf_1 = import_leaf(
+			&mut virtual_overseer,
+			Hash::repeat_byte(2),
+			block_number,
+			vec![candidate_included(candidate_1)],
+			validators.clone(),
+		)
+		.await;
+
+		finalize_in_session(&mut virtual_overseer, leaf_1, block_number, 1).await;
+
+		// The candidate is served from the archive while still within regular retention.
+		assert_eq!(
+			query_archived_available_data(&mut virtual_overseer, candidate_hash_1)
+				.await
+				.unwrap(),
+			available_data_1,
+		);
+
+		test_state.clock.inc(test_state.pruning_config.keep_finalized_for * 2);
+		test_state.wait_for_pruning().await;
+
+		// The chunks are pruned but the available data is kept.
+		assert!(has_all_chunks(&mut virtual_overseer, candidate_hash_1, n_validators, false).await);
+		assert_eq!(
+			query_archived_available_data(&mut virtual_overseer, candidate_hash_1)
+				.await
+				.unwrap(),
+			available_data_1,
+		);
+
+		// Finalize the second candidate two sessions later.
+		store_data(&mut virtual_overseer, candidate_2.hash(), n_validators, available_data_2).await;
+
+		let leaf_2 = import_leaf(
+			&mut virtual_overseer,
+			leaf_1,
+			block_number + 1,
+			vec![candidate_included(candidate_2)],
+			validators,
+		)
+		.await;
+
+		finalize_in_session(&mut virtual_overseer, leaf_2, block_number + 1, 3).await;
+
+		// Session 1 dropped out of the archive.
+		assert!(query_archived_available_data(&mut virtual_overseer, candidate_hash_1)
+			.await
+			.is_none());
+		assert!(query_available_data(&mut virtual_overseer, candidate_hash_1).await.is_none());
+		virtual_overseer
+	});
+}
+
+fn candidate_with_data(test_state: &TestState, byte: u8) -> (CandidateReceipt, AvailableData) {
+	let pov = PoV { block_data: BlockData(vec![byte; 3]) };
+	let candidate = TestCandidateBuilder { pov_hash: pov.hash(), ..Default::default() }.build();
+	let available_data = AvailableData {
+		pov: Arc::new(pov),
+		validation_data: test_state.persisted_validation_data.clone(),
+	};
+
+	(candidate, available_data)
+}
+
+async fn store_data(
+	virtual_overseer: &mut VirtualOverseer,
+	candidate_hash: CandidateHash,
+	n_validators: u32,
+	available_data: AvailableData,
+) {
+	let (tx, rx) = oneshot::channel();
+	let block_msg = AvailabilityStoreMessage::StoreAvailableData {
+		candidate_hash,
+		n_validators,
+		available_data,
+		tx,
+	};
+
+	virtual_overseer.send(FromOrchestra::Communication { msg: block_msg }).await;
+	rx.await.unwrap().unwrap();
+}
+
 #[test]
 fn we_dont_miss_anything_if_import_notifications_are_missed() {
 	let store = test_store();
@@ -1008,6 +1264,38 @@ async fn query_available_data(
 	rx.await.unwrap()
 }
 
+async fn query_archived_available_data(
+	virtual_overseer: &mut VirtualOverseer,
+	candidate_hash: CandidateHash,
+) -> Option<AvailableData> {
+	let (tx, rx) = oneshot::channel();
+
+	let query = AvailabilityStoreMessage::QueryArchivedAvailableData(candidate_hash, tx);
+	virtual_overseer.send(FromOrchestra::Communication { msg: query }).await;
+
+	rx.await.unwrap()
+}
+
+async fn finalize_in_session(
+	virtual_overseer: &mut VirtualOverseer,
+	hash: Hash,
+	number: BlockNumber,
+	session: SessionIndex,
+) {
+	overseer_signal(virtual_overseer, OverseerSignal::BlockFinalized(hash, number)).await;
+
+	assert_matches!(
+		overseer_recv(virtual_overseer).await,
+		AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+			relay_parent,
+			RuntimeApiRequest::SessionIndexForChild(tx),
+		)) => {
+			assert_eq!(relay_parent, hash);
+			tx.send(Ok(session)).unwrap();
+		}
+	);
+}
+
 async fn query_chunk(
 	virtual_overseer: &mut VirtualOverseer,
 	candidate_hash: CandidateHash,
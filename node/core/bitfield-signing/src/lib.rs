@@ -180,6 +180,7 @@ async fn construct_availability_bitfield(
 }
 
 /// The bitfield signing subsystem.
+#[derive(Clone)]
 pub struct BitfieldSigningSubsystem {
 	keystore: SyncCryptoStorePtr,
 	metrics: Metrics,
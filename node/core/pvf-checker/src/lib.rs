@@ -48,6 +48,7 @@ use self::{
 };
 
 /// PVF pre-checking subsystem.
+#[derive(Clone)]
 pub struct PvfCheckerSubsystem {
 	enabled: bool,
 	keystore: SyncCryptoStorePtr,
@@ -90,6 +90,21 @@ pub(crate) fn impl_gum2(orig: TokenStream, level: Level) -> Result<TokenStream>
 
 	let Args { target, comma, mut values, fmt } = args;
 
+	// Normalize the names of well-known fields, each may only be given once.
+	let mut well_known_fields = Vec::new();
+	for value in values.iter_mut() {
+		value.normalize();
+		if let Some(well_known) = value.well_known() {
+			if well_known_fields.contains(&well_known) {
+				return Err(syn::Error::new(
+					value.as_ident().span(),
+					format!("Well-known field `{}` is given more than once.", well_known.name()),
+				))
+			}
+			well_known_fields.push(well_known);
+		}
+	}
+
 	// find a value or alias called `candidate_hash`.
 	let maybe_candidate_hash = values.iter_mut().find(|value| value.as_ident() == "candidate_hash");
 
@@ -117,6 +132,9 @@ pub(crate) fn impl_gum2(orig: TokenStream, level: Level) -> Result<TokenStream>
 			let _old = std::mem::replace(kv, replace_with);
 		};
 
+		let bindings = bind_values(&mut values);
+		let json = json_record(&krate, &target, level, &values, fmt.is_some(), true);
+
 		// Inject the addition `traceID = % trace_id` identifier
 		// while maintaining trailing comma semantics.
 		let had_trailing_comma = values.trailing_punct();
@@ -131,6 +149,7 @@ pub(crate) fn impl_gum2(orig: TokenStream, level: Level) -> Result<TokenStream>
 			values.push_punct(Token![,](span));
 		}
 
+		let emit = emit(&krate, &target, &comma, level, &values, &fmt, json);
 		Ok(quote! {
 			if #krate :: enabled!(#target #comma #level) {
 				use ::std::ops::Deref;
@@ -143,20 +162,137 @@ pub(crate) fn impl_gum2(orig: TokenStream, level: Level) -> Result<TokenStream>
 				// Do the `deref` to `Hash` and convert to a `TraceIdentifier`.
 				let #ident: #krate:: Hash = * value;
 				let trace_id = #krate:: hash_to_trace_identifier ( #ident );
-				#krate :: event!(
-					#target #comma #level, #values #fmt
-				)
+				#bindings
+				#emit
 			}
 		})
 	} else {
+		let bindings = bind_values(&mut values);
+		let json = json_record(&krate, &target, level, &values, fmt.is_some(), false);
+		let emit = emit(&krate, &target, &comma, level, &values, &fmt, json);
 		Ok(quote! {
-				#krate :: event!(
-					#target #comma #level, #values #fmt
-				)
+			if #krate :: enabled!(#target #comma #level) {
+				#bindings
+				#emit
+			}
 		})
 	}
 }
 
+/// Bind the expression of every aliased value to a local reference, so it is evaluated once
+/// for both the text and the JSON log line.
+fn bind_values(values: &mut Punctuated<Value, Token![,]>) -> TokenStream {
+	let mut bindings = TokenStream::new();
+	for (i, value) in values.iter_mut().enumerate() {
+		if let Value::Alias(alias) = value {
+			let binding = Ident::new(&format!("__gum_value_{}", i), Span::call_site());
+			let expr = &alias.expr;
+			bindings.extend(quote! {
+				let #binding = &(#expr);
+			});
+			alias.expr = parse_quote! { #binding };
+		}
+	}
+	bindings
+}
+
+/// Generate the code emitting the log line, also as JSON if enabled.
+///
+/// With JSON output enabled, the message is formatted once for both log lines.
+fn emit(
+	krate: &TokenStream,
+	target: &Option<Target>,
+	comma: &Option<Token![,]>,
+	level: Level,
+	values: &Punctuated<Value, Token![,]>,
+	fmt: &Option<FmtGroup>,
+	json: TokenStream,
+) -> TokenStream {
+	let text = quote! {
+		#krate :: event!(
+			#target #comma #level, #values #fmt
+		)
+	};
+	let json = match fmt {
+		Some(fmt) => quote! {
+			let message = format!(#fmt);
+			#krate :: event!(
+				#target #comma #level, #values "{}", message
+			);
+			#json
+		},
+		None => quote! {
+			#json
+			#text
+		},
+	};
+	quote! {
+		if #krate :: json::json_output_enabled() {
+			#json
+		} else {
+			#text
+		}
+	}
+}
+
+/// Generate the code writing a log line as JSON, see `tracing_gum::json`.
+///
+/// The values of well-known fields are converted via `tracing_gum::schema::WellKnown`,
+/// so any other type than the expected one fails to compile, in text mode as well. The
+/// formatted message is expected in a local `message`, if there is one.
+fn json_record(
+	krate: &TokenStream,
+	target: &Option<Target>,
+	level: Level,
+	values: &Punctuated<Value, Token![,]>,
+	with_message: bool,
+	with_trace_id: bool,
+) -> TokenStream {
+	let target = match target {
+		Some(target) => target.expr.to_token_stream(),
+		None => quote! { module_path!() },
+	};
+
+	let fields = values.iter().map(|value| {
+		let (name, expr) = value.name_and_expr();
+		if let Some(well_known) = value.well_known() {
+			return quote! {
+				json_record.well_known(
+					#name,
+					<_ as #krate :: schema::WellKnown<#well_known>>::to_json(&(#expr)),
+				);
+			}
+		}
+		let json_value = match value.marker() {
+			FormatMarker::Questionmark(_) => quote! { #krate :: schema::debug(&(#expr)) },
+			FormatMarker::Percentage(_) => quote! { #krate :: schema::display(&(#expr)) },
+			FormatMarker::None => quote! { #krate :: schema::Plain::to_json(&(#expr)) },
+		};
+		quote! {
+			json_record.field(#name, #json_value);
+		}
+	});
+
+	let trace_id = with_trace_id.then(|| {
+		quote! {
+			json_record.trace_id(trace_id);
+		}
+	});
+	let message = with_message.then(|| {
+		quote! {
+			json_record.message(message);
+		}
+	});
+
+	quote! {
+		let mut json_record = #krate :: json::Record::new(#level, #target);
+		#( #fields )*
+		#trace_id
+		#message
+		json_record.emit();
+	}
+}
+
 /// Extract the support crate path.
 fn support_crate() -> TokenStream {
 	let support_crate_name = if cfg!(test) {
@@ -204,4 +204,33 @@ mod roundtrip {
 			dbg!(x.to_string())
 		});
 	}
+	#[test]
+	fn well_known_aliases_are_normalized() {
+		let normalized = |value: TokenStream| {
+			let mut value = ::syn::parse2::<Value>(value).unwrap();
+			value.normalize();
+			value.into_token_stream().to_string()
+		};
+
+		assert_eq!(normalized(quote! {?peer}), quote! {peer_id = ?peer}.to_string());
+		assert_eq!(normalized(quote! {para = %x}), quote! {para_id = %x}.to_string());
+		assert_eq!(normalized(quote! {session_index = s.0}), quote! {session = s.0}.to_string());
+		assert_eq!(normalized(quote! {?relay_parent}), quote! {?relay_parent}.to_string());
+		assert_eq!(normalized(quote! {?peer.x}), quote! {?peer.x}.to_string());
+	}
+
+	#[test]
+	fn duplicate_well_known_field() {
+		assert_matches!(impl_gum2(
+			quote! {
+				target: "z",
+				?peer,
+				peer_id = ?x,
+				"xxx",
+			},
+			Level::Info
+		), Err(e) => {
+			assert!(e.to_string().contains("`peer_id`"))
+		});
+	}
 }
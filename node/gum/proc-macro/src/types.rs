@@ -29,7 +29,7 @@ pub(crate) mod kw {
 pub(crate) struct Target {
 	kw: kw::target,
 	colon: Token![:],
-	expr: syn::Expr,
+	pub expr: syn::Expr,
 }
 
 impl Parse for Target {
@@ -178,6 +178,111 @@ impl Value {
 			Self::Value(value) => &value.ident,
 		}
 	}
+
+	pub fn marker(&self) -> FormatMarker {
+		match self {
+			Self::Alias(alias) => alias.marker,
+			Self::Value(value) => value.marker,
+		}
+	}
+
+	/// The name of the field and the expression of its value.
+	pub fn name_and_expr(&self) -> (String, TokenStream) {
+		match self {
+			Self::Alias(alias) => (alias.alias.to_string(), alias.expr.to_token_stream()),
+			Self::Value(value) => {
+				let ValueWithFormatMarker { ident, dot, inner, .. } = value;
+				let expr = quote! { #ident #dot #inner };
+				(expr.to_string().replace(' ', ""), expr)
+			},
+		}
+	}
+
+	/// The well-known field this value is given for, if any.
+	///
+	/// Nested members, i.e. `?foo.bar`, are never well-known fields.
+	pub fn well_known(&self) -> Option<WellKnown> {
+		match self {
+			Self::Value(value) if value.dot.is_some() => None,
+			value => WellKnown::from_name(&value.as_ident().to_string()),
+		}
+	}
+
+	/// Rename a well-known field given by one of its aliases to its well-known name.
+	pub fn normalize(&mut self) {
+		let well_known = match self.well_known() {
+			Some(well_known) => well_known,
+			None => return,
+		};
+		let ident = self.as_ident().clone();
+		if ident == well_known.name() {
+			return
+		}
+		let name = Ident::new(well_known.name(), ident.span());
+		match self {
+			Self::Alias(alias) => alias.alias = name,
+			Self::Value(value) => {
+				let marker = value.marker;
+				*self = Self::Alias(ValueWithAliasIdent {
+					alias: name,
+					eq: Token![=](ident.span()),
+					marker,
+					expr: parse_quote! { #ident },
+				})
+			},
+		}
+	}
+}
+
+/// The fields with a fixed name and type, see `tracing_gum::schema`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WellKnown {
+	CandidateHash,
+	RelayParent,
+	ParaId,
+	Session,
+	PeerId,
+}
+
+impl WellKnown {
+	/// Lookup a well-known field by its name or one of its aliases.
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"candidate_hash" => Some(Self::CandidateHash),
+			"relay_parent" => Some(Self::RelayParent),
+			"para_id" | "para" => Some(Self::ParaId),
+			"session" | "session_index" => Some(Self::Session),
+			"peer_id" | "peer" => Some(Self::PeerId),
+			_ => None,
+		}
+	}
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::CandidateHash => "candidate_hash",
+			Self::RelayParent => "relay_parent",
+			Self::ParaId => "para_id",
+			Self::Session => "session",
+			Self::PeerId => "peer_id",
+		}
+	}
+}
+
+impl ToTokens for WellKnown {
+	fn to_tokens(&self, tokens: &mut TokenStream) {
+		let span = Span::call_site();
+		let marker = match self {
+			Self::CandidateHash => Ident::new("CandidateHash", span),
+			Self::RelayParent => Ident::new("RelayParent", span),
+			Self::ParaId => Ident::new("ParaId", span),
+			Self::Session => Ident::new("Session", span),
+			Self::PeerId => Ident::new("PeerId", span),
+		};
+		let krate = support_crate();
+		tokens.extend(quote! {
+			#krate :: schema :: field :: #marker
+		})
+	}
 }
 
 impl Parse for Value {
@@ -0,0 +1,167 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! JSON output of log lines.
+//!
+//! Once enabled, log lines of the `gum` macros are additionally written as one JSON object per
+//! line to a dedicated output, so they are never mixed with the lines formatted by the `tracing`
+//! subscriber. The subscriber still decides which log lines are enabled. Every object has the
+//! following keys:
+//!
+//! * `timestamp`: milliseconds since the UNIX epoch
+//! * `level`: one of `ERROR`, `WARN`, `INFO`, `DEBUG`, `TRACE`
+//! * `target`: the target of the log line, i.e. the subsystem
+//! * `message`: the formatted message, empty if there is none
+//! * `trace_id`: the hex encoded `traceID` if a `candidate_hash` was given, `null` otherwise
+//! * `candidate_hash`, `relay_parent`, `para_id`, `session`, `peer_id`: the well-known fields
+//!   described in [`crate::schema`], `null` if not given
+//! * `fields`: an object with all other fields
+//!
+//! Hashes are `0x` prefixed hex strings, `para_id` and `session` are numbers and `peer_id` is
+//! base58 encoded.
+//!
+//! Log lines are serialized on the logging thread and written by a dedicated thread. If that
+//! thread falls more than [`MAX_PENDING_LINES`] behind, log lines are dropped from the JSON
+//! output rather than blocking the logging threads.
+
+use parking_lot::Mutex;
+use serde_json::{Map, Value};
+use std::{
+	io::{self, BufWriter, Write},
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		mpsc,
+	},
+	thread,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::Level;
+
+/// The keys present in every JSON log line.
+pub const KEYS: &[&str] = &[
+	"timestamp",
+	"level",
+	"target",
+	"message",
+	"trace_id",
+	"candidate_hash",
+	"relay_parent",
+	"para_id",
+	"session",
+	"peer_id",
+	"fields",
+];
+
+/// The number of log lines waiting to be written, before further lines are dropped.
+pub const MAX_PENDING_LINES: usize = 8192;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static OUTPUT: Mutex<Option<mpsc::SyncSender<Vec<u8>>>> = parking_lot::const_mutex(None);
+
+/// Also write all following log lines as JSON to `output`, see the [module docs](self).
+///
+/// `output` should not be the output of the `tracing` subscriber. It is buffered and flushed
+/// whenever all pending log lines have been written. Fails if the writer thread can't be
+/// spawned.
+pub fn enable_json_output(output: impl Write + Send + 'static) -> io::Result<()> {
+	let (lines, pending) = mpsc::sync_channel(MAX_PENDING_LINES);
+	thread::Builder::new()
+		.name("gum-json-writer".into())
+		.spawn(move || write_lines(output, pending))?;
+
+	// Replacing an earlier output lets its writer thread exit once it wrote all of its lines.
+	*OUTPUT.lock() = Some(lines);
+	ENABLED.store(true, Ordering::Release);
+	Ok(())
+}
+
+fn write_lines(output: impl Write, pending: mpsc::Receiver<Vec<u8>>) {
+	let mut output = BufWriter::new(output);
+	while let Ok(line) = pending.recv() {
+		let _ = output.write_all(&line);
+		while let Ok(line) = pending.try_recv() {
+			let _ = output.write_all(&line);
+		}
+		let _ = output.flush();
+	}
+}
+
+/// Whether log lines are written as JSON.
+#[doc(hidden)]
+pub fn json_output_enabled() -> bool {
+	ENABLED.load(Ordering::Acquire)
+}
+
+/// A log line to be written as JSON.
+#[doc(hidden)]
+pub struct Record {
+	object: Map<String, Value>,
+	fields: Map<String, Value>,
+}
+
+impl Record {
+	/// Start a log line of the given level and target.
+	pub fn new(level: Level, target: &str) -> Self {
+		let timestamp = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map_or(0, |since| since.as_millis() as u64);
+
+		let mut object = Map::new();
+		for key in KEYS {
+			object.insert(key.to_string(), Value::Null);
+		}
+		object.insert("timestamp".to_owned(), timestamp.into());
+		object.insert("level".to_owned(), level.to_string().into());
+		object.insert("target".to_owned(), target.into());
+		object.insert("message".to_owned(), "".into());
+
+		Self { object, fields: Map::new() }
+	}
+
+	/// Set the formatted message.
+	pub fn message(&mut self, message: String) {
+		self.object.insert("message".to_owned(), message.into());
+	}
+
+	/// Set the `traceID` derived from the `candidate_hash`.
+	pub fn trace_id(&mut self, trace_id: u128) {
+		self.object.insert("trace_id".to_owned(), format!("{:032x}", trace_id).into());
+	}
+
+	/// Set one of the well-known fields.
+	pub fn well_known(&mut self, name: &'static str, value: Value) {
+		self.object.insert(name.to_owned(), value);
+	}
+
+	/// Add any other field.
+	pub fn field(&mut self, name: &'static str, value: Value) {
+		self.fields.insert(name.to_owned(), value);
+	}
+
+	/// Pass the log line on to the writer thread, dropping it if the thread is too far behind.
+	pub fn emit(mut self) {
+		self.object.insert("fields".to_owned(), Value::Object(self.fields));
+		let mut line = match serde_json::to_vec(&self.object) {
+			Ok(line) => line,
+			Err(_) => return,
+		};
+		line.push(b'\n');
+		if let Some(lines) = OUTPUT.lock().as_ref() {
+			let _ = lines.try_send(line);
+		}
+	}
+}
@@ -20,6 +20,9 @@
 
 //! A wrapper around `tracing` macros, to provide semi automatic
 //! `traceID` annotation without codebase turnover.
+//!
+//! Well-known fields are normalized to a common name and type checked, see [`schema`],
+//! which allows writing log lines as JSON with a stable schema, see [`json`].
 
 pub use tracing::{enabled, event, Level};
 
@@ -31,5 +34,10 @@ pub use polkadot_primitives::v2::{CandidateHash, Hash};
 
 pub use gum_proc_macro::{debug, error, info, trace, warn};
 
+pub mod json;
+pub mod schema;
+
+pub use self::json::enable_json_output;
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,190 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The well-known fields of log lines and the types they are required to have.
+//!
+//! Fields named like one of the well-known fields, or one of their aliases, are renamed to
+//! the well-known name by the `gum` macros:
+//!
+//! | field | aliases | type |
+//! |-------|---------|------|
+//! | `candidate_hash` | | anything that derefs to `Hash` |
+//! | `relay_parent` | | `Hash` |
+//! | `para_id` | `para` | `ParaId` |
+//! | `session` | `session_index` | `SessionIndex` |
+//! | `peer_id` | `peer` | `PeerId` |
+//!
+//! References to and `Option`s of these types are accepted as well. Using any other type is a
+//! compile time error.
+//!
+//! ```compile_fail
+//! let relay_parent = 7_u32;
+//! tracing_gum::info!(?relay_parent, "Not a hash");
+//! ```
+
+use jaeger::PeerId;
+use polkadot_primitives::v2::{Hash, Id as ParaId, SessionIndex};
+use serde_json::Value;
+use std::num::{NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize};
+
+/// Markers for the well-known fields, see [`WellKnown`].
+pub mod field {
+	/// The `candidate_hash` field.
+	pub enum CandidateHash {}
+	/// The `relay_parent` field.
+	pub enum RelayParent {}
+	/// The `para_id` field.
+	pub enum ParaId {}
+	/// The `session` field.
+	pub enum Session {}
+	/// The `peer_id` field.
+	pub enum PeerId {}
+}
+
+/// A type that is accepted as the value of the well-known field `F`.
+pub trait WellKnown<F> {
+	/// The value as it appears in JSON output.
+	fn to_json(&self) -> Value;
+}
+
+impl WellKnown<field::CandidateHash> for Hash {
+	fn to_json(&self) -> Value {
+		Value::String(format!("{:?}", self))
+	}
+}
+
+impl WellKnown<field::RelayParent> for Hash {
+	fn to_json(&self) -> Value {
+		Value::String(format!("{:?}", self))
+	}
+}
+
+impl WellKnown<field::ParaId> for ParaId {
+	fn to_json(&self) -> Value {
+		Value::from(u32::from(*self))
+	}
+}
+
+impl WellKnown<field::Session> for SessionIndex {
+	fn to_json(&self) -> Value {
+		Value::from(*self)
+	}
+}
+
+impl WellKnown<field::PeerId> for PeerId {
+	fn to_json(&self) -> Value {
+		Value::String(self.to_base58())
+	}
+}
+
+impl<F, T: WellKnown<F> + ?Sized> WellKnown<F> for &T {
+	fn to_json(&self) -> Value {
+		(**self).to_json()
+	}
+}
+
+impl<F, T: WellKnown<F>> WellKnown<F> for Option<T> {
+	fn to_json(&self) -> Value {
+		self.as_ref().map_or(Value::Null, WellKnown::to_json)
+	}
+}
+
+/// A field value passed without a `?` or `%` format marker.
+///
+/// Implemented for the types `tracing` accepts as such.
+pub trait Plain {
+	/// The value as it appears in JSON output.
+	fn to_json(&self) -> Value;
+}
+
+macro_rules! impl_plain_from {
+	($($ty:ty),+) => {
+		$(
+			impl Plain for $ty {
+				fn to_json(&self) -> Value {
+					Value::from(*self)
+				}
+			}
+		)+
+	};
+}
+
+macro_rules! impl_plain_to_string {
+	($($ty:ty),+) => {
+		$(
+			impl Plain for $ty {
+				fn to_json(&self) -> Value {
+					Value::String(self.to_string())
+				}
+			}
+		)+
+	};
+}
+
+impl_plain_from!(bool, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+impl_plain_to_string!(u128, i128, char, str, String, std::fmt::Arguments<'_>);
+impl_plain_to_string!(NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroUsize);
+impl_plain_to_string!(
+	dyn std::error::Error + 'static,
+	dyn std::error::Error + Send + 'static,
+	dyn std::error::Error + Send + Sync + 'static
+);
+
+impl<T: std::fmt::Display> Plain for tracing::field::DisplayValue<T> {
+	fn to_json(&self) -> Value {
+		Value::String(format!("{:?}", self))
+	}
+}
+
+impl<T: std::fmt::Debug> Plain for tracing::field::DebugValue<T> {
+	fn to_json(&self) -> Value {
+		Value::String(format!("{:?}", self))
+	}
+}
+
+impl Plain for tracing::field::Empty {
+	fn to_json(&self) -> Value {
+		Value::Null
+	}
+}
+
+impl<T: Plain + ?Sized> Plain for &T {
+	fn to_json(&self) -> Value {
+		(**self).to_json()
+	}
+}
+
+impl<T: Plain + ?Sized> Plain for Box<T> {
+	fn to_json(&self) -> Value {
+		(**self).to_json()
+	}
+}
+
+impl<T: Plain> Plain for Option<T> {
+	fn to_json(&self) -> Value {
+		self.as_ref().map_or(Value::Null, Plain::to_json)
+	}
+}
+
+/// A field value passed with the `?` format marker.
+pub fn debug<T: std::fmt::Debug + ?Sized>(value: &T) -> Value {
+	Value::String(format!("{:?}", value))
+}
+
+/// A field value passed with the `%` format marker.
+pub fn display<T: std::fmt::Display + ?Sized>(value: &T) -> Value {
+	Value::String(value.to_string())
+}
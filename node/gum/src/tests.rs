@@ -15,7 +15,15 @@
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::*;
-pub use polkadot_primitives::v2::{CandidateHash, Hash};
+use jaeger::PeerId;
+pub use polkadot_primitives::v2::{CandidateHash, Hash, Id as ParaId};
+use std::{
+	io::Write,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc, Mutex,
+	},
+};
 
 #[derive(Default, Debug)]
 struct Y {
@@ -32,7 +40,7 @@ fn plain() {
 fn wo_alias() {
 	let a: i32 = 7;
 	error!(target: "foo",
-		"Something something {}, {:?}, or maybe {}",
+		"Something something {}, {b:?}, or maybe {c}",
 		a,
 		b = Y::default(),
 		c = a
@@ -102,3 +110,102 @@ fn w_candidate_hash_aliased_unnecessary() {
 		"xxx",
 	);
 }
+
+/// Enables all log lines, only counting the events.
+#[derive(Clone, Default)]
+struct EnableAll(Arc<AtomicUsize>);
+
+impl tracing::Subscriber for EnableAll {
+	fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+		true
+	}
+	fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+		tracing::span::Id::from_u64(1)
+	}
+	fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+	fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+	fn event(&self, _: &tracing::Event<'_>) {
+		self.0.fetch_add(1, Ordering::Relaxed);
+	}
+	fn enter(&self, _: &tracing::span::Id) {}
+	fn exit(&self, _: &tracing::span::Id) {}
+}
+
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Output {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		self.0.lock().unwrap().write(buf)
+	}
+	fn flush(&mut self) -> std::io::Result<()> {
+		Ok(())
+	}
+}
+
+#[test]
+fn json_output_normalizes_well_known_fields() {
+	let output = Output::default();
+	enable_json_output(output.clone()).unwrap();
+	let subscriber = EnableAll::default();
+
+	let candidate_hash = CandidateHash(Hash::repeat_byte(0xF1));
+	let peer = PeerId::random();
+	tracing::subscriber::with_default(subscriber.clone(), || {
+		info!(
+			target: "bar",
+			?candidate_hash,
+			relay_parent = ?Hash::repeat_byte(0xAA),
+			para = %ParaId::from(2000),
+			session_index = Some(7),
+			?peer,
+			n = 3usize,
+			y = ?Y::default(),
+			"Hello {}",
+			"json",
+		);
+	});
+
+	// The log line still reaches the subscriber.
+	assert_eq!(subscriber.0.load(Ordering::Relaxed), 1);
+
+	// Lines are written by a dedicated thread.
+	let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+	while output.0.lock().unwrap().is_empty() && std::time::Instant::now() < deadline {
+		std::thread::sleep(std::time::Duration::from_millis(10));
+	}
+	let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+	let lines = output.lines().collect::<Vec<_>>();
+	assert_eq!(lines.len(), 1);
+	let line: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+
+	let keys = line.as_object().unwrap().keys().map(|key| key.as_str()).collect::<Vec<_>>();
+	let mut expected_keys = json::KEYS.to_vec();
+	expected_keys.sort();
+	assert_eq!(keys, expected_keys);
+
+	assert_eq!(line["level"], "INFO");
+	assert_eq!(line["target"], "bar");
+	assert_eq!(line["message"], "Hello json");
+	assert_eq!(
+		line["trace_id"],
+		format!("{:032x}", hash_to_trace_identifier(Hash::repeat_byte(0xF1)))
+	);
+	assert_eq!(line["candidate_hash"], format!("{:?}", Hash::repeat_byte(0xF1)));
+	assert_eq!(line["relay_parent"], format!("{:?}", Hash::repeat_byte(0xAA)));
+	assert_eq!(line["para_id"], 2000);
+	assert_eq!(line["session"], 7);
+	assert_eq!(line["peer_id"], peer.to_base58());
+	assert_eq!(line["fields"], serde_json::json!({ "n": 3, "y": "Y { x: 0 }" }));
+
+	// Values are evaluated once for both log lines.
+	let evaluated = AtomicUsize::new(0);
+	tracing::subscriber::with_default(subscriber.clone(), || {
+		info!(
+			n = evaluated.fetch_add(1, Ordering::Relaxed),
+			"{}",
+			evaluated.load(Ordering::Relaxed)
+		);
+	});
+	assert_eq!(evaluated.load(Ordering::Relaxed), 1);
+}
@@ -0,0 +1,324 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Deprecated export of spans to a jaeger agent, using thrift over UDP.
+//!
+//! Each batch is sent as `emitBatch` calls of the compact thrift protocol, split across as
+//! many packets as needed to stay below the maximum packet size of the agent.
+
+use std::{
+	net::SocketAddr,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::otlp::{AttributeValue, FinishedSpan};
+
+const LOG_TARGET: &str = "jaeger";
+
+/// The default maximum packet size of the agent.
+const MAX_PACKET_SIZE: usize = 65_000;
+
+// Upper bound of the bytes following the spans of a packet: the list header and the ends
+// of the batch and argument structs.
+const PACKET_TRAILER_SIZE: usize = 8;
+
+// Types of the compact thrift protocol.
+const TYPE_I32: u8 = 5;
+const TYPE_I64: u8 = 6;
+const TYPE_BINARY: u8 = 8;
+const TYPE_LIST: u8 = 9;
+const TYPE_STRUCT: u8 = 12;
+
+// Values of the jaeger thrift enums.
+const TAG_TYPE_STRING: i32 = 0;
+const TAG_TYPE_LONG: i32 = 3;
+const SPAN_REF_TYPE_FOLLOWS_FROM: i32 = 1;
+const SPAN_FLAG_SAMPLED: i32 = 1;
+
+/// Send a batch of spans to the agent at `addr`.
+pub(crate) async fn export(
+	socket: &async_std::net::UdpSocket,
+	addr: SocketAddr,
+	resource: &[(&'static str, AttributeValue)],
+	spans: &[FinishedSpan],
+) {
+	for packet in encode_packets(resource, spans) {
+		// UDP sending errors happen only either if the API is misused or in case of missing privilege.
+		if let Err(e) = socket.send_to(&packet, addr).await {
+			log::debug!(target: LOG_TARGET, "UDP send error: {}", e);
+		}
+	}
+}
+
+/// Encode spans as `emitBatch` packets, dropping spans which don't fit into a packet alone.
+fn encode_packets(
+	resource: &[(&'static str, AttributeValue)],
+	spans: &[FinishedSpan],
+) -> Vec<Vec<u8>> {
+	let header = encode_packet_header(resource);
+	let mut packets = Vec::new();
+	let mut pending: Vec<Vec<u8>> = Vec::new();
+	let mut pending_size = header.len() + PACKET_TRAILER_SIZE;
+
+	for span in spans.iter().map(encode_span) {
+		if header.len() + PACKET_TRAILER_SIZE + span.len() > MAX_PACKET_SIZE {
+			log::debug!(target: LOG_TARGET, "Dropping span of {} bytes", span.len());
+			continue
+		}
+
+		if pending_size + span.len() > MAX_PACKET_SIZE {
+			packets.push(encode_packet(&header, &pending));
+			pending.clear();
+			pending_size = header.len() + PACKET_TRAILER_SIZE;
+		}
+
+		pending_size += span.len();
+		pending.push(span);
+	}
+
+	if !pending.is_empty() {
+		packets.push(encode_packet(&header, &pending));
+	}
+
+	packets
+}
+
+/// Everything of a packet before the list of spans: the message header, the start of the
+/// `emitBatch` arguments and the process of the batch.
+fn encode_packet_header(resource: &[(&'static str, AttributeValue)]) -> Vec<u8> {
+	let service_name = resource
+		.iter()
+		.find_map(|(key, value)| match (key, value) {
+			(&"service.name", AttributeValue::String(name)) => Some(name.as_str()),
+			_ => None,
+		})
+		.unwrap_or("polkadot");
+
+	let mut encoder = Encoder::default();
+	// The protocol identifier, followed by the version and the message type `ONEWAY`.
+	encoder.buf.extend_from_slice(&[0x82, 0x81]);
+	encoder.varint(0);
+	encoder.string("emitBatch");
+
+	// The arguments.
+	encoder.struct_begin();
+	encoder.field(1, TYPE_STRUCT);
+	// The batch.
+	encoder.struct_begin();
+	encoder.field(1, TYPE_STRUCT);
+	// The process.
+	encoder.struct_begin();
+	encoder.string_field(1, service_name);
+	encode_tags(&mut encoder, 2, resource);
+	encoder.struct_end();
+	encoder.field(2, TYPE_LIST);
+
+	encoder.buf
+}
+
+fn encode_packet(header: &[u8], spans: &[Vec<u8>]) -> Vec<u8> {
+	let mut encoder = Encoder::default();
+	encoder.buf.extend_from_slice(header);
+	encoder.list_begin(TYPE_STRUCT, spans.len());
+	spans.iter().for_each(|span| encoder.buf.extend_from_slice(span));
+	// The ends of the batch and the arguments.
+	encoder.buf.extend_from_slice(&[0, 0]);
+	encoder.buf
+}
+
+fn encode_span(span: &FinishedSpan) -> Vec<u8> {
+	let mut encoder = Encoder::default();
+	encoder.struct_begin();
+	encoder.i64_field(1, span.trace_id.get() as u64 as i64);
+	encoder.i64_field(2, (span.trace_id.get() >> 64) as u64 as i64);
+	encoder.i64_field(3, span.span_id.get() as i64);
+	encoder.i64_field(4, span.parent_span_id.map_or(0, |id| id.get() as i64));
+	encoder.string_field(5, span.name);
+
+	encoder.field(6, TYPE_LIST);
+	encoder.list_begin(TYPE_STRUCT, span.links.len());
+	for (trace_id, span_id) in &span.links {
+		encoder.struct_begin();
+		encoder.i32_field(1, SPAN_REF_TYPE_FOLLOWS_FROM);
+		encoder.i64_field(2, trace_id.get() as u64 as i64);
+		encoder.i64_field(3, (trace_id.get() >> 64) as u64 as i64);
+		encoder.i64_field(4, span_id.get() as i64);
+		encoder.struct_end();
+	}
+
+	let start = unix_micros(span.start);
+	encoder.i32_field(7, SPAN_FLAG_SAMPLED);
+	encoder.i64_field(8, start);
+	encoder.i64_field(9, unix_micros(span.end).saturating_sub(start));
+	encode_tags(&mut encoder, 10, &span.attributes);
+	encoder.struct_end();
+
+	encoder.buf
+}
+
+fn encode_tags(encoder: &mut Encoder, field_id: i16, tags: &[(&'static str, AttributeValue)]) {
+	encoder.field(field_id, TYPE_LIST);
+	encoder.list_begin(TYPE_STRUCT, tags.len());
+	for (key, value) in tags {
+		encoder.struct_begin();
+		encoder.string_field(1, key);
+		match value {
+			AttributeValue::String(s) => {
+				encoder.i32_field(2, TAG_TYPE_STRING);
+				encoder.string_field(3, s);
+			},
+			AttributeValue::Int(i) => {
+				encoder.i32_field(2, TAG_TYPE_LONG);
+				encoder.i64_field(6, *i);
+			},
+		}
+		encoder.struct_end();
+	}
+}
+
+fn unix_micros(time: SystemTime) -> i64 {
+	time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_micros() as i64)
+}
+
+/// A writer of the compact thrift protocol, just as much as the jaeger types need.
+#[derive(Default)]
+struct Encoder {
+	buf: Vec<u8>,
+	last_field_id: i16,
+	outer_field_ids: Vec<i16>,
+}
+
+impl Encoder {
+	fn varint(&mut self, mut value: u64) {
+		while value >= 0x80 {
+			self.buf.push(value as u8 | 0x80);
+			value >>= 7;
+		}
+		self.buf.push(value as u8);
+	}
+
+	fn i32(&mut self, value: i32) {
+		self.varint(((value << 1) ^ (value >> 31)) as u32 as u64);
+	}
+
+	fn i64(&mut self, value: i64) {
+		self.varint(((value << 1) ^ (value >> 63)) as u64);
+	}
+
+	fn string(&mut self, value: &str) {
+		self.varint(value.len() as u64);
+		self.buf.extend_from_slice(value.as_bytes());
+	}
+
+	fn field(&mut self, id: i16, ty: u8) {
+		match id - self.last_field_id {
+			delta @ 1..=15 => self.buf.push((delta as u8) << 4 | ty),
+			_ => {
+				self.buf.push(ty);
+				self.i32(id as i32);
+			},
+		}
+		self.last_field_id = id;
+	}
+
+	fn i32_field(&mut self, id: i16, value: i32) {
+		self.field(id, TYPE_I32);
+		self.i32(value);
+	}
+
+	fn i64_field(&mut self, id: i16, value: i64) {
+		self.field(id, TYPE_I64);
+		self.i64(value);
+	}
+
+	fn string_field(&mut self, id: i16, value: &str) {
+		self.field(id, TYPE_BINARY);
+		self.string(value);
+	}
+
+	fn list_begin(&mut self, element_ty: u8, len: usize) {
+		if len < 15 {
+			self.buf.push((len as u8) << 4 | element_ty);
+		} else {
+			self.buf.push(0xF0 | element_ty);
+			self.varint(len as u64);
+		}
+	}
+
+	fn struct_begin(&mut self) {
+		self.outer_field_ids.push(self.last_field_id);
+		self.last_field_id = 0;
+	}
+
+	fn struct_end(&mut self) {
+		self.buf.push(0);
+		self.last_field_id = self.outer_field_ids.pop().unwrap_or(0);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::otlp::channel;
+	use futures::{FutureExt as _, StreamExt as _};
+	use std::num::NonZeroU128;
+
+	#[test]
+	fn spans_are_split_across_packets() {
+		let (traces_in, mut traces_out) = channel(64);
+		let trace_id = NonZeroU128::new(1).unwrap();
+		let tag = "x".repeat(MAX_PACKET_SIZE / 4);
+		for _ in 0..10 {
+			traces_in.span(trace_id, "span").add_string_tag("tag", &tag);
+		}
+		// Too large for a packet of its own.
+		traces_in.span(trace_id, "span").add_string_tag("tag", &tag.repeat(5));
+
+		let spans: Vec<_> =
+			std::iter::from_fn(|| traces_out.next().now_or_never().flatten()).collect();
+		assert_eq!(spans.len(), 11);
+
+		let resource = vec![("service.name", AttributeValue::String("polkadot-test".to_owned()))];
+		let packets = encode_packets(&resource, &spans);
+
+		// Three spans fit into a packet.
+		assert_eq!(packets.len(), 4);
+		for packet in &packets {
+			assert!(packet.len() <= MAX_PACKET_SIZE);
+			assert_eq!(&packet[..3], &[0x82, 0x81, 0]);
+			assert_eq!(&packet[3..13], b"\x09emitBatch");
+			assert_eq!(&packet[packet.len() - 2..], &[0, 0]);
+		}
+	}
+
+	#[test]
+	fn fields_are_encoded_compactly() {
+		let mut encoder = Encoder::default();
+		encoder.struct_begin();
+		encoder.i32_field(1, -1);
+		encoder.i64_field(3, 300);
+		encoder.i32_field(20, 1);
+		encoder.struct_begin();
+		encoder.struct_end();
+		encoder.list_begin(TYPE_STRUCT, 20);
+		encoder.struct_end();
+
+		assert_eq!(
+			encoder.buf,
+			vec![0x15, 0x01, 0x26, 0xD8, 0x04, 0x05, 0x28, 0x02, 0x00, 0xFC, 0x14, 0x00],
+		);
+	}
+}
@@ -16,20 +16,39 @@
 
 //! Polkadot Jaeger configuration.
 
+use std::{collections::HashMap, time::Duration};
+
 /// Configuration for the jaeger tracing.
+///
+/// Spans are exported to an OpenTelemetry collector, using OTLP over HTTP, unless a jaeger
+/// agent is configured.
 #[derive(Clone)]
 pub struct JaegerConfig {
 	pub(crate) node_name: String,
-	pub(crate) agent_addr: std::net::SocketAddr,
+	pub(crate) node_role: String,
+	pub(crate) collector_addr: std::net::SocketAddr,
+	pub(crate) agent_addr: Option<std::net::SocketAddr>,
+	pub(crate) max_batch_size: usize,
+	pub(crate) batch_interval: Duration,
+	pub(crate) max_queued_spans: usize,
+	pub(crate) default_sampling_ratio: f64,
+	pub(crate) sampling_ratios: HashMap<String, f64>,
 }
 
 impl std::default::Default for JaegerConfig {
 	fn default() -> Self {
 		Self {
 			node_name: "unknown_".to_owned(),
-			agent_addr: "127.0.0.1:6831"
+			node_role: "unknown".to_owned(),
+			collector_addr: "127.0.0.1:4318"
 				.parse()
-				.expect(r#"Static "127.0.0.1:6831" is a valid socket address string. qed"#),
+				.expect(r#"Static "127.0.0.1:4318" is a valid socket address string. qed"#),
+			agent_addr: None,
+			max_batch_size: 512,
+			batch_interval: Duration::from_secs(5),
+			max_queued_spans: 8192,
+			default_sampling_ratio: 1.0,
+			sampling_ratios: HashMap::new(),
 		}
 	}
 }
@@ -57,12 +76,72 @@ impl JaegerConfigBuilder {
 		self
 	}
 
-	/// Set the agent address to send the collected spans to.
+	/// Set the role of this node, i.e. `authority` or `full`.
+	pub fn role<S>(mut self, role: S) -> Self
+	where
+		S: AsRef<str>,
+	{
+		self.inner.node_role = role.as_ref().to_owned();
+		self
+	}
+
+	/// Set the address of the OTLP/HTTP collector to send the collected spans to.
+	pub fn collector<U>(mut self, addr: U) -> Self
+	where
+		U: Into<std::net::SocketAddr>,
+	{
+		self.inner.collector_addr = addr.into();
+		self
+	}
+
+	/// Send the collected spans to the jaeger agent at the given address, using thrift over
+	/// UDP, instead of the OTLP/HTTP collector.
+	///
+	/// Deprecated in favour of OTLP, the agent is deprecated by jaeger itself.
 	pub fn agent<U>(mut self, addr: U) -> Self
 	where
 		U: Into<std::net::SocketAddr>,
 	{
-		self.inner.agent_addr = addr.into();
+		self.inner.agent_addr = Some(addr.into());
+		self
+	}
+
+	/// Set the maximum number of spans sent to the collector in a single request.
+	pub fn max_batch_size(mut self, max_batch_size: usize) -> Self {
+		self.inner.max_batch_size = max_batch_size.max(1);
+		self
+	}
+
+	/// Set the interval after which an incomplete batch of spans is sent regardless.
+	pub fn batch_interval(mut self, interval: Duration) -> Self {
+		self.inner.batch_interval = interval;
+		self
+	}
+
+	/// Set the maximum number of finished spans waiting to be sent,
+	/// any spans beyond are dropped.
+	pub fn max_queued_spans(mut self, max_queued_spans: usize) -> Self {
+		self.inner.max_queued_spans = max_queued_spans;
+		self
+	}
+
+	/// Set the ratio of traces recorded for root spans without a dedicated ratio.
+	///
+	/// The ratio is clamped to `0.0..=1.0`.
+	pub fn default_sampling_ratio(mut self, ratio: f64) -> Self {
+		self.inner.default_sampling_ratio = ratio.clamp(0.0, 1.0);
+		self
+	}
+
+	/// Set the ratio of traces recorded for root spans of the given name.
+	///
+	/// Child spans are recorded whenever their root span is. The ratio is
+	/// clamped to `0.0..=1.0`.
+	pub fn sampling_ratio<S>(mut self, span_name: S, ratio: f64) -> Self
+	where
+		S: Into<String>,
+	{
+		self.inner.sampling_ratios.insert(span_name.into(), ratio.clamp(0.0, 1.0));
 		self
 	}
 
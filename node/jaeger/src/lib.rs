@@ -20,7 +20,9 @@
 //!
 //! # Integration
 //!
-//! See <https://www.jaegertracing.io/> for an introduction.
+//! Spans are exported to an OpenTelemetry collector, using OTLP over HTTP, so any backend
+//! ingesting OTLP can be used. Exporting to a jaeger agent over UDP is still supported, but
+//! deprecated. See <https://www.jaegertracing.io/> for an introduction.
 //!
 //! The easiest way to try Jaeger is:
 //!
@@ -32,22 +34,20 @@
 //! ```not_rust
 //! podman login docker.io
 //! podman run -d --name jaeger \
-//!  -e COLLECTOR_ZIPKIN_HTTP_PORT=9411 \
-//!  -p 5775:5775/udp \
-//!  -p 6831:6831/udp \
-//!  -p 6832:6832/udp \
-//!  -p 5778:5778 \
+//!  -e COLLECTOR_OTLP_ENABLED=true \
 //!  -p 16686:16686 \
-//!  -p 14268:14268 \
-//!  -p 14250:14250 \
-//!  -p 9411:9411 \
-//!  docker.io/jaegertracing/all-in-one:1.21
+//!  -p 4318:4318 \
+//!  docker.io/jaegertracing/all-in-one:1.35
 //! ```
+//!
+//! and the node pointed at it with `--otlp-endpoint 127.0.0.1:4318`.
 
 #![forbid(unused_imports)]
 
+mod agent;
 mod config;
 mod errors;
+mod otlp;
 mod spans;
 
 pub use self::{
@@ -55,8 +55,13 @@ pub use self::{
 	errors::JaegerError,
 	spans::{hash_to_trace_identifier, PerLeafSpan, Span, Stage},
 };
+/// The peer identifier of [`Span::with_peer_id`].
+pub use sc_network::PeerId;
 
-use self::spans::TraceIdentifier;
+use self::{
+	otlp::{Sampler, TracesIn},
+	spans::TraceIdentifier,
+};
 
 use sp_core::traits::SpawnNamed;
 
@@ -67,12 +72,14 @@ lazy_static::lazy_static! {
 	static ref INSTANCE: RwLock<Jaeger> = RwLock::new(Jaeger::None);
 }
 
-/// Stateful convenience wrapper around [`mick_jaeger`].
+/// Stateful convenience wrapper around the span exporter.
 pub enum Jaeger {
 	/// Launched and operational state.
 	Launched {
-		/// [`mick_jaeger`] provided API to record spans to.
-		traces_in: Arc<mick_jaeger::TracesIn>,
+		/// The exporter to record spans to.
+		traces_in: Arc<TracesIn>,
+		/// Decides which traces are recorded.
+		sampler: Sampler,
 	},
 	/// Preparation state with the necessary config to launch the collector.
 	Prep(JaegerConfig),
@@ -86,7 +93,7 @@ impl Jaeger {
 		Jaeger::Prep(cfg)
 	}
 
-	/// Spawn the background task in order to send the tracing information out via UDP
+	/// Spawn the background task in order to send the tracing information out via OTLP.
 	#[cfg(target_os = "unknown")]
 	pub fn launch<S: SpawnNamed>(self, _spawner: S) -> result::Result<(), JaegerError> {
 		Ok(())
@@ -99,15 +106,14 @@ impl Jaeger {
 		match *instance {
 			Self::Launched { .. } => {},
 			_ => {
-				let (traces_in, _traces_out) = mick_jaeger::init(mick_jaeger::Config {
-					service_name: "polkadot-jaeger-test".to_owned(),
-				});
-				*instance = Self::Launched { traces_in };
+				let (traces_in, _traces_out) = otlp::channel(0);
+				*instance = Self::Launched { traces_in, sampler: Sampler::always() };
 			},
 		}
 	}
 
-	/// Spawn the background task in order to send the tracing information out via UDP
+	/// Spawn the background task in order to send the tracing information out via OTLP,
+	/// or via UDP to a jaeger agent.
 	#[cfg(not(target_os = "unknown"))]
 	pub fn launch<S: SpawnNamed>(self, spawner: S) -> result::Result<(), JaegerError> {
 		let cfg = match self {
@@ -116,35 +122,21 @@ impl Jaeger {
 			Self::None => Err(JaegerError::MissingConfiguration),
 		}?;
 
-		let jaeger_agent = cfg.agent_addr;
-
-		log::info!("🐹 Collecting jaeger spans for {:?}", &jaeger_agent);
-
-		let (traces_in, mut traces_out) = mick_jaeger::init(mick_jaeger::Config {
-			service_name: format!("polkadot-{}", cfg.node_name),
-		});
-
-		// Spawn a background task that pulls span information and sends them on the network.
-		spawner.spawn(
-			"jaeger-collector",
-			Some("jaeger"),
-			Box::pin(async move {
-				match async_std::net::UdpSocket::bind("0.0.0.0:0").await {
-					Ok(udp_socket) => loop {
-						let buf = traces_out.next().await;
-						// UDP sending errors happen only either if the API is misused or in case of missing privilege.
-						if let Err(e) = udp_socket.send_to(&buf, jaeger_agent).await {
-							log::debug!(target: "jaeger", "UDP send error: {}", e);
-						}
-					},
-					Err(e) => {
-						log::warn!(target: "jaeger", "UDP socket open error: {}", e);
-					},
-				}
-			}),
-		);
-
-		*INSTANCE.write() = Self::Launched { traces_in };
+		match cfg.agent_addr {
+			Some(agent_addr) => log::info!("🐹 Collecting jaeger spans for {:?}", &agent_addr),
+			None => log::info!(
+				"🐹 Exporting jaeger spans to the OTLP collector at {:?}",
+				&cfg.collector_addr
+			),
+		}
+
+		let (traces_in, traces_out) = otlp::channel(cfg.max_queued_spans);
+		let exporter = otlp::Exporter::new(&cfg);
+
+		// Spawn a background task that batches span information and sends them on the network.
+		spawner.spawn("jaeger-collector", Some("jaeger"), Box::pin(exporter.run(traces_out)));
+
+		*INSTANCE.write() = Self::Launched { traces_in, sampler: Sampler::new(&cfg) };
 		Ok(())
 	}
 
@@ -152,13 +144,18 @@ impl Jaeger {
 	///
 	/// The deferral allows to avoid the additional CPU runtime cost in case of
 	/// items that are not a pre-computed hash by themselves.
-	pub(crate) fn span<F>(&self, lazy_hash: F, span_name: &'static str) -> Option<mick_jaeger::Span>
+	///
+	/// Returns `None` unless the trace started by the span is sampled.
+	pub(crate) fn span<F>(&self, lazy_hash: F, span_name: &'static str) -> Option<otlp::Span>
 	where
 		F: Fn() -> TraceIdentifier,
 	{
-		if let Self::Launched { traces_in, .. } = self {
+		if let Self::Launched { traces_in, sampler } = self {
 			let ident = lazy_hash();
 			let trace_id = std::num::NonZeroU128::new(ident)?;
+			if !sampler.is_sampled(trace_id, span_name) {
+				return None
+			}
 			Some(traces_in.span(trace_id, span_name))
 		} else {
 			None
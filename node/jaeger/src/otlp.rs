@@ -0,0 +1,506 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Export of spans to an OpenTelemetry collector, using OTLP over HTTP with JSON encoding.
+//!
+//! Spans are handed to the exporter once dropped. The exporter batches them up and posts
+//! each batch to the `/v1/traces` endpoint of the collector, or sends it to a jaeger agent
+//! if one is configured instead.
+
+use futures::{channel::mpsc, stream::FusedStream, FutureExt as _, Stream, StreamExt as _};
+use futures_timer::Delay;
+use serde_json::{json, Value};
+
+use std::{
+	collections::HashMap,
+	net::SocketAddr,
+	num::{NonZeroU128, NonZeroU64},
+	pin::Pin,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
+	task::{Context, Poll},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{agent, JaegerConfig};
+
+const LOG_TARGET: &str = "jaeger";
+
+/// How long to wait for the collector to accept a batch, before dropping it.
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The value of a span or resource attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum AttributeValue {
+	String(String),
+	Int(i64),
+}
+
+/// A span which ended, ready to be exported.
+#[derive(Debug)]
+pub(crate) struct FinishedSpan {
+	pub(crate) trace_id: NonZeroU128,
+	pub(crate) span_id: NonZeroU64,
+	pub(crate) parent_span_id: Option<NonZeroU64>,
+	pub(crate) name: &'static str,
+	pub(crate) start: SystemTime,
+	pub(crate) end: SystemTime,
+	pub(crate) attributes: Vec<(&'static str, AttributeValue)>,
+	pub(crate) links: Vec<(NonZeroU128, NonZeroU64)>,
+}
+
+/// Create the channel between recorded spans and the exporter.
+///
+/// At most `max_queued` finished spans wait to be exported, any spans beyond are dropped.
+pub(crate) fn channel(max_queued: usize) -> (Arc<TracesIn>, TracesOut) {
+	let (sender, receiver) = mpsc::unbounded();
+	let queued = Arc::new(AtomicUsize::new(0));
+	(
+		Arc::new(TracesIn { sender, queued: queued.clone(), max_queued }),
+		TracesOut { receiver, queued },
+	)
+}
+
+/// The sending side of the exporter, shared by all spans.
+pub struct TracesIn {
+	sender: mpsc::UnboundedSender<FinishedSpan>,
+	queued: Arc<AtomicUsize>,
+	max_queued: usize,
+}
+
+impl TracesIn {
+	/// Start a root span of the given trace.
+	pub(crate) fn span(self: &Arc<Self>, trace_id: NonZeroU128, name: &'static str) -> Span {
+		Span::new(self.clone(), trace_id, None, name)
+	}
+
+	fn submit(&self, span: FinishedSpan) {
+		if self.queued.fetch_add(1, Ordering::Relaxed) >= self.max_queued {
+			self.queued.fetch_sub(1, Ordering::Relaxed);
+			return
+		}
+
+		if self.sender.unbounded_send(span).is_err() {
+			self.queued.fetch_sub(1, Ordering::Relaxed);
+		}
+	}
+}
+
+/// The receiving side of the exporter.
+pub(crate) struct TracesOut {
+	receiver: mpsc::UnboundedReceiver<FinishedSpan>,
+	queued: Arc<AtomicUsize>,
+}
+
+impl Stream for TracesOut {
+	type Item = FinishedSpan;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let polled = self.receiver.poll_next_unpin(cx);
+		if let Poll::Ready(Some(_)) = polled {
+			self.queued.fetch_sub(1, Ordering::Relaxed);
+		}
+		polled
+	}
+}
+
+impl FusedStream for TracesOut {
+	fn is_terminated(&self) -> bool {
+		self.receiver.is_terminated()
+	}
+}
+
+/// A span being recorded, handed to the exporter once dropped.
+pub struct Span {
+	traces_in: Arc<TracesIn>,
+	trace_id: NonZeroU128,
+	span_id: NonZeroU64,
+	parent_span_id: Option<NonZeroU64>,
+	name: &'static str,
+	start: SystemTime,
+	attributes: Vec<(&'static str, AttributeValue)>,
+	links: Vec<(NonZeroU128, NonZeroU64)>,
+}
+
+impl Span {
+	fn new(
+		traces_in: Arc<TracesIn>,
+		trace_id: NonZeroU128,
+		parent_span_id: Option<NonZeroU64>,
+		name: &'static str,
+	) -> Self {
+		Span {
+			traces_in,
+			trace_id,
+			span_id: new_span_id(),
+			parent_span_id,
+			name,
+			start: SystemTime::now(),
+			attributes: Vec::new(),
+			links: Vec::new(),
+		}
+	}
+
+	/// Start a child span of `self`, part of the same trace.
+	pub(crate) fn child(&self, name: &'static str) -> Self {
+		Span::new(self.traces_in.clone(), self.trace_id, Some(self.span_id), name)
+	}
+
+	pub(crate) fn add_string_tag(&mut self, key: &'static str, value: &str) {
+		self.attributes.push((key, AttributeValue::String(value.to_owned())));
+	}
+
+	pub(crate) fn add_int_tag(&mut self, key: &'static str, value: i64) {
+		self.attributes.push((key, AttributeValue::Int(value)));
+	}
+
+	/// Link `self` to `other`, which it follows from.
+	pub(crate) fn add_follows_from(&mut self, other: &Self) {
+		self.links.push((other.trace_id, other.span_id));
+	}
+
+	pub(crate) fn trace_id(&self) -> NonZeroU128 {
+		self.trace_id
+	}
+}
+
+impl Drop for Span {
+	fn drop(&mut self) {
+		self.traces_in.submit(FinishedSpan {
+			trace_id: self.trace_id,
+			span_id: self.span_id,
+			parent_span_id: self.parent_span_id,
+			name: self.name,
+			start: self.start,
+			end: SystemTime::now(),
+			attributes: std::mem::take(&mut self.attributes),
+			links: std::mem::take(&mut self.links),
+		});
+	}
+}
+
+fn new_span_id() -> NonZeroU64 {
+	loop {
+		if let Some(span_id) = NonZeroU64::new(rand::random()) {
+			return span_id
+		}
+	}
+}
+
+/// Decides which traces are recorded, based on the name of their root span.
+///
+/// The decision is derived from the trace identifier, the same way as the ratio based
+/// sampler of OpenTelemetry does it. All nodes thus record the same candidates.
+pub struct Sampler {
+	default_ratio: f64,
+	ratios: HashMap<String, f64>,
+}
+
+impl Sampler {
+	pub(crate) fn new(cfg: &JaegerConfig) -> Self {
+		Sampler { default_ratio: cfg.default_sampling_ratio, ratios: cfg.sampling_ratios.clone() }
+	}
+
+	/// Record every trace.
+	#[cfg(test)]
+	pub(crate) fn always() -> Self {
+		Sampler { default_ratio: 1.0, ratios: HashMap::new() }
+	}
+
+	/// Whether to record the trace started by a root span of the given name.
+	pub(crate) fn is_sampled(&self, trace_id: NonZeroU128, span_name: &str) -> bool {
+		let ratio = self.ratios.get(span_name).copied().unwrap_or(self.default_ratio);
+		if ratio >= 1.0 {
+			return true
+		}
+		if ratio <= 0.0 {
+			return false
+		}
+
+		// Compare the lower 64 bits of the trace identifier without their last one,
+		// so the bound fits into a `u64`.
+		let bound = (ratio * (1u64 << 63) as f64) as u64;
+		(trace_id.get() as u64 >> 1) < bound
+	}
+}
+
+/// Where the exporter sends batches of spans to.
+enum Destination {
+	/// The traces endpoint of an OTLP/HTTP collector.
+	Collector(hyper::Uri),
+	/// A jaeger agent, deprecated.
+	Agent(SocketAddr),
+}
+
+/// Batches finished spans and sends them to the collector.
+pub(crate) struct Exporter {
+	destination: Destination,
+	resource: Vec<(&'static str, AttributeValue)>,
+	max_batch_size: usize,
+	batch_interval: Duration,
+}
+
+impl Exporter {
+	pub(crate) fn new(cfg: &JaegerConfig) -> Self {
+		let destination = match cfg.agent_addr {
+			Some(agent_addr) => Destination::Agent(agent_addr),
+			None => Destination::Collector(
+				format!("http://{}/v1/traces", cfg.collector_addr)
+					.parse()
+					.expect("A socket address is a valid URI authority. qed"),
+			),
+		};
+
+		Exporter {
+			destination,
+			resource: resource_attributes(cfg),
+			max_batch_size: cfg.max_batch_size,
+			batch_interval: cfg.batch_interval,
+		}
+	}
+
+	/// Export spans until all their senders are gone.
+	pub(crate) async fn run(self, mut traces_out: TracesOut) {
+		let client = hyper::Client::new();
+		let udp_socket = match self.destination {
+			Destination::Agent(_) => match async_std::net::UdpSocket::bind("0.0.0.0:0").await {
+				Ok(udp_socket) => Some(udp_socket),
+				Err(e) => {
+					log::warn!(target: LOG_TARGET, "UDP socket open error: {}", e);
+					return
+				},
+			},
+			Destination::Collector(_) => None,
+		};
+		let mut batch = Vec::with_capacity(self.max_batch_size);
+		let mut flush_timer = Delay::new(self.batch_interval).fuse();
+
+		loop {
+			let (flush, closed) = futures::select! {
+				span = traces_out.next() => match span {
+					Some(span) => {
+						batch.push(span);
+						(batch.len() >= self.max_batch_size, false)
+					},
+					None => (true, true),
+				},
+				_ = flush_timer => (true, false),
+			};
+
+			if !flush {
+				continue
+			}
+
+			flush_timer = Delay::new(self.batch_interval).fuse();
+
+			if !batch.is_empty() {
+				match (&self.destination, &udp_socket) {
+					(Destination::Agent(agent_addr), Some(udp_socket)) =>
+						agent::export(udp_socket, *agent_addr, &self.resource, &batch).await,
+					(Destination::Collector(endpoint), _) => {
+						let body = encode_batch(&self.resource, &batch).to_string();
+						self.export(&client, endpoint, body).await;
+					},
+					(Destination::Agent(_), None) => {},
+				}
+				batch.clear();
+			}
+
+			if closed {
+				return
+			}
+		}
+	}
+
+	async fn export(
+		&self,
+		client: &hyper::Client<hyper::client::HttpConnector>,
+		endpoint: &hyper::Uri,
+		body: String,
+	) {
+		let request = match hyper::Request::post(endpoint.clone())
+			.header(hyper::header::CONTENT_TYPE, "application/json")
+			.body(hyper::Body::from(body))
+		{
+			Ok(request) => request,
+			Err(e) => {
+				log::warn!(target: LOG_TARGET, "Failed to build OTLP request: {}", e);
+				return
+			},
+		};
+
+		let response = futures::select! {
+			response = client.request(request).fuse() => response,
+			_ = Delay::new(EXPORT_TIMEOUT).fuse() => {
+				log::debug!(target: LOG_TARGET, "OTLP export timed out");
+				return
+			},
+		};
+
+		match response {
+			Ok(response) if !response.status().is_success() => {
+				log::debug!(target: LOG_TARGET, "Collector rejected spans: {}", response.status())
+			},
+			Ok(_) => {},
+			Err(e) => log::debug!(target: LOG_TARGET, "OTLP export error: {}", e),
+		}
+	}
+}
+
+/// The attributes describing this node, attached to every exported batch.
+fn resource_attributes(cfg: &JaegerConfig) -> Vec<(&'static str, AttributeValue)> {
+	vec![
+		("service.name", AttributeValue::String(format!("polkadot-{}", cfg.node_name))),
+		("node.name", AttributeValue::String(cfg.node_name.clone())),
+		("node.role", AttributeValue::String(cfg.node_role.clone())),
+	]
+}
+
+/// Encode a batch of spans as an OTLP `ExportTraceServiceRequest`.
+fn encode_batch(resource: &[(&'static str, AttributeValue)], spans: &[FinishedSpan]) -> Value {
+	json!({
+		"resourceSpans": [{
+			"resource": { "attributes": encode_attributes(resource) },
+			"scopeSpans": [{
+				"scope": {
+					"name": env!("CARGO_PKG_NAME"),
+					"version": env!("CARGO_PKG_VERSION"),
+				},
+				"spans": spans.iter().map(encode_span).collect::<Vec<_>>(),
+			}],
+		}],
+	})
+}
+
+fn encode_span(span: &FinishedSpan) -> Value {
+	let mut encoded = json!({
+		"traceId": format!("{:032x}", span.trace_id.get()),
+		"spanId": format!("{:016x}", span.span_id.get()),
+		"name": span.name,
+		// `SPAN_KIND_INTERNAL`
+		"kind": 1,
+		"startTimeUnixNano": unix_nanos(span.start).to_string(),
+		"endTimeUnixNano": unix_nanos(span.end).to_string(),
+		"attributes": encode_attributes(&span.attributes),
+		"links": span.links.iter().map(|(trace_id, span_id)| json!({
+			"traceId": format!("{:032x}", trace_id.get()),
+			"spanId": format!("{:016x}", span_id.get()),
+		})).collect::<Vec<_>>(),
+	});
+
+	if let Some(parent_span_id) = span.parent_span_id {
+		encoded["parentSpanId"] = json!(format!("{:016x}", parent_span_id.get()));
+	}
+
+	encoded
+}
+
+fn encode_attributes(attributes: &[(&'static str, AttributeValue)]) -> Vec<Value> {
+	attributes
+		.iter()
+		.map(|(key, value)| {
+			let value = match value {
+				AttributeValue::String(s) => json!({ "stringValue": s }),
+				// 64 bit integers are encoded as strings in OTLP/JSON.
+				AttributeValue::Int(i) => json!({ "intValue": i.to_string() }),
+			};
+			json!({ "key": key, "value": value })
+		})
+		.collect()
+}
+
+fn unix_nanos(time: SystemTime) -> u64 {
+	time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TRACE_ID: u128 = 0xFFAA_1122_3344_5566_7889_9AAB_BCCD_DEEF;
+
+	#[test]
+	fn spans_are_encoded_with_their_trace_and_parent() {
+		let (traces_in, mut traces_out) = channel(16);
+		let trace_id = NonZeroU128::new(TRACE_ID).unwrap();
+
+		let mut root = traces_in.span(trace_id, "root");
+		root.add_int_tag("para-id", 100);
+		let mut child = root.child("child");
+		child.add_string_tag("candidate-stage", "2");
+		child.add_follows_from(&root);
+		let (root_span_id, child_span_id) = (root.span_id, child.span_id);
+		drop(child);
+		drop(root);
+
+		let spans: Vec<_> =
+			std::iter::from_fn(|| traces_out.next().now_or_never().flatten()).collect();
+		assert_eq!(spans.len(), 2);
+		assert_eq!(traces_out.queued.load(Ordering::Relaxed), 0);
+
+		let resource = vec![("node.role", AttributeValue::String("authority".to_owned()))];
+		let encoded = encode_batch(&resource, &spans);
+		let batch = &encoded["resourceSpans"][0];
+		assert_eq!(
+			batch["resource"]["attributes"][0],
+			json!({ "key": "node.role", "value": { "stringValue": "authority" } }),
+		);
+
+		let child = &batch["scopeSpans"][0]["spans"][0];
+		assert_eq!(child["name"], "child");
+		assert_eq!(child["traceId"], format!("{:032x}", TRACE_ID));
+		assert_eq!(child["parentSpanId"], format!("{:016x}", root_span_id));
+		assert_eq!(child["spanId"], format!("{:016x}", child_span_id));
+		assert_eq!(child["links"][0]["spanId"], format!("{:016x}", root_span_id));
+
+		let root = &batch["scopeSpans"][0]["spans"][1];
+		assert_eq!(root["name"], "root");
+		assert_eq!(root["traceId"], child["traceId"]);
+		assert!(root.get("parentSpanId").is_none());
+		assert_eq!(root["attributes"][0]["value"], json!({ "intValue": "100" }));
+	}
+
+	#[test]
+	fn spans_beyond_the_queue_limit_are_dropped() {
+		let (traces_in, mut traces_out) = channel(2);
+		let trace_id = NonZeroU128::new(TRACE_ID).unwrap();
+
+		(0..4).for_each(|_| drop(traces_in.span(trace_id, "span")));
+		drop(traces_in);
+
+		assert_eq!(futures::executor::block_on_stream(&mut traces_out).count(), 2);
+	}
+
+	#[test]
+	fn sampling_is_keyed_on_the_trace_identifier() {
+		let cfg = JaegerConfig::builder()
+			.default_sampling_ratio(0.0)
+			.sampling_ratio("sampled", 0.5)
+			.build();
+		let sampler = Sampler::new(&cfg);
+
+		// The decision only depends on the lower 64 bits, without the least significant one.
+		let low = NonZeroU128::new(1 << 64 | 0x0000_0000_0000_0001).unwrap();
+		let high = NonZeroU128::new(0xC000_0000_0000_0000).unwrap();
+
+		assert!(sampler.is_sampled(low, "sampled"));
+		assert!(!sampler.is_sampled(high, "sampled"));
+		assert!(!sampler.is_sampled(low, "unsampled"));
+		assert!(Sampler::always().is_sampled(high, "unsampled"));
+	}
+}
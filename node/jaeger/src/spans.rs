@@ -92,7 +92,7 @@ use sc_network::PeerId;
 
 use std::{fmt, sync::Arc};
 
-use super::INSTANCE;
+use super::{otlp, INSTANCE};
 
 /// A special "per leaf span".
 ///
@@ -163,7 +163,7 @@ pub enum Stage {
 /// Handles running with and without jaeger.
 pub enum Span {
 	/// Running with jaeger being enabled.
-	Enabled(mick_jaeger::Span),
+	Enabled(otlp::Span),
 	/// Running with jaeger disabled.
 	Disabled,
 }
@@ -441,8 +441,8 @@ impl std::fmt::Debug for Span {
 	}
 }
 
-impl From<Option<mick_jaeger::Span>> for Span {
-	fn from(src: Option<mick_jaeger::Span>) -> Self {
+impl From<Option<otlp::Span>> for Span {
+	fn from(src: Option<otlp::Span>) -> Self {
 		if let Some(span) = src {
 			Self::Enabled(span)
 		} else {
@@ -451,8 +451,8 @@ impl From<Option<mick_jaeger::Span>> for Span {
 	}
 }
 
-impl From<mick_jaeger::Span> for Span {
-	fn from(src: mick_jaeger::Span) -> Self {
+impl From<otlp::Span> for Span {
+	fn from(src: otlp::Span) -> Self {
 		Self::Enabled(src)
 	}
 }
@@ -25,12 +25,20 @@ use futures::{
 
 use std::{pin::Pin, result};
 
-use super::{measure_tof_check, CoarseInstant, MaybeTimeOfFlight, Meter};
+use super::{CoarseInstant, MaybeTimeOfFlight, Meter, TofConfig};
 
 /// Create a wrapped `mpsc::channel` pair of `MeteredSender` and `MeteredReceiver`.
 pub fn channel<T>(capacity: usize) -> (MeteredSender<T>, MeteredReceiver<T>) {
+	channel_with_tof_config(capacity, TofConfig::default())
+}
+
+/// Create a wrapped `mpsc::channel` pair, measuring the time of flight of messages as configured.
+pub fn channel_with_tof_config<T>(
+	capacity: usize,
+	tof_config: TofConfig,
+) -> (MeteredSender<T>, MeteredReceiver<T>) {
 	let (tx, rx) = mpsc::channel::<MaybeTimeOfFlight<T>>(capacity);
-	let shared_meter = Meter::default();
+	let shared_meter = Meter::with_tof_config(tof_config);
 	let tx = MeteredSender { meter: shared_meter.clone(), inner: tx };
 	let rx = MeteredReceiver { meter: shared_meter, inner: rx };
 	(tx, rx)
@@ -82,6 +90,7 @@ impl<T> MeteredReceiver<T> {
 					// `coarsetime` does a saturating sub for all `CoarseInstant` substractions
 					let duration = tof_start.elapsed();
 					self.meter.note_time_of_flight(duration);
+					self.meter.trace_time_of_flight::<T>(duration);
 					value
 				},
 				MaybeTimeOfFlight::<T>::Bare(value) => value,
@@ -140,7 +149,7 @@ impl<T> std::ops::DerefMut for MeteredSender<T> {
 impl<T> MeteredSender<T> {
 	fn prepare_with_tof(&self, item: T) -> MaybeTimeOfFlight<T> {
 		let previous = self.meter.note_sent();
-		let item = if measure_tof_check(previous) {
+		let item = if self.meter.measure_tof_check(previous) {
 			MaybeTimeOfFlight::WithTimeOfFlight(item, CoarseInstant::now())
 		} else {
 			MaybeTimeOfFlight::Bare(item)
@@ -17,7 +17,7 @@
 //! Metered variant of mpsc channels to be able to extract metrics.
 
 use std::sync::{
-	atomic::{AtomicUsize, Ordering},
+	atomic::{AtomicU64, AtomicUsize, Ordering},
 	Arc,
 };
 
@@ -35,6 +35,59 @@ use coarsetime::Instant as CoarseInstant;
 #[cfg(test)]
 mod tests;
 
+/// Upper bounds, in seconds, of the time of flight histogram buckets.
+///
+/// Measurements exceeding the last bound are only accounted for in the total count.
+pub const TOF_BUCKETS: [f64; 12] =
+	[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Configuration of the time of flight measurements of a channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TofConfig {
+	/// Share of the sent messages whose time of flight is measured, in parts per thousand.
+	pub sample_per_mille: u16,
+	/// Measured messages that spent at least this long in the channel are logged.
+	pub trace_threshold: Option<CoarseDuration>,
+}
+
+impl TofConfig {
+	/// Do not measure the time of flight at all.
+	pub const DISABLED: Self = Self { sample_per_mille: 0, trace_threshold: None };
+	/// Measure the time of flight of every message.
+	pub const ALL: Self = Self { sample_per_mille: 1000, trace_threshold: None };
+}
+
+impl std::default::Default for TofConfig {
+	fn default() -> Self {
+		// measure 5.3%
+		Self { sample_per_mille: 53, trace_threshold: None }
+	}
+}
+
+/// Time of flight histogram buckets, shared between sender and receiver.
+#[derive(Debug, Default)]
+struct TofBuckets {
+	// Non cumulative number of measurements per bucket, the last one catches all exceeding
+	// the largest bound.
+	buckets: [AtomicU64; TOF_BUCKETS.len() + 1],
+	// Sum of all measurements in micro seconds.
+	sum_micros: AtomicU64,
+}
+
+/// A readout of the time of flight histogram of a channel.
+///
+/// In contrast to the sampled values in [`Readout::tof`], the histogram is never reset.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TofHistogram {
+	/// Upper bound in seconds and cumulative number of measurements for each bucket of
+	/// [`TOF_BUCKETS`].
+	pub buckets: Vec<(f64, u64)>,
+	/// Total number of measurements.
+	pub count: u64,
+	/// Sum of all measurements in seconds.
+	pub sum: f64,
+}
+
 /// A peek into the inner state of a meter.
 #[derive(Debug, Clone)]
 pub struct Meter {
@@ -46,16 +99,14 @@ pub struct Meter {
 	blocked: Arc<AtomicUsize>,
 	// Atomic ringbuffer of the last 50 time of flight values
 	tof: Arc<crossbeam_queue::ArrayQueue<CoarseDuration>>,
+	// Histogram of all time of flight values
+	tof_buckets: Arc<TofBuckets>,
+	tof_config: TofConfig,
 }
 
 impl std::default::Default for Meter {
 	fn default() -> Self {
-		Self {
-			sent: Arc::new(AtomicUsize::new(0)),
-			received: Arc::new(AtomicUsize::new(0)),
-			blocked: Arc::new(AtomicUsize::new(0)),
-			tof: Arc::new(crossbeam_queue::ArrayQueue::new(100)),
-		}
+		Self::with_tof_config(TofConfig::default())
 	}
 }
 
@@ -72,9 +123,27 @@ pub struct Readout {
 	pub blocked: usize,
 	/// Time of flight in micro seconds (us)
 	pub tof: Vec<CoarseDuration>,
+	/// Histogram of all time of flight measurements.
+	pub tof_histogram: TofHistogram,
 }
 
 impl Meter {
+	fn with_tof_config(tof_config: TofConfig) -> Self {
+		Self {
+			sent: Arc::new(AtomicUsize::new(0)),
+			received: Arc::new(AtomicUsize::new(0)),
+			blocked: Arc::new(AtomicUsize::new(0)),
+			tof: Arc::new(crossbeam_queue::ArrayQueue::new(100)),
+			tof_buckets: Arc::new(TofBuckets::default()),
+			tof_config,
+		}
+	}
+
+	/// The time of flight configuration of the channel.
+	pub fn tof_config(&self) -> &TofConfig {
+		&self.tof_config
+	}
+
 	/// Count the number of items queued up inside the channel.
 	pub fn read(&self) -> Readout {
 		// when obtaining we don't care much about off by one
@@ -90,9 +159,25 @@ impl Meter {
 				}
 				acc
 			},
+			tof_histogram: self.read_tof_histogram(),
 		}
 	}
 
+	fn read_tof_histogram(&self) -> TofHistogram {
+		let mut count = 0;
+		let buckets = TOF_BUCKETS
+			.iter()
+			.zip(self.tof_buckets.buckets.iter())
+			.map(|(upper_bound, bucket)| {
+				count += bucket.load(Ordering::Relaxed);
+				(*upper_bound, count)
+			})
+			.collect();
+		count += self.tof_buckets.buckets[TOF_BUCKETS.len()].load(Ordering::Relaxed);
+		let sum = self.tof_buckets.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.;
+		TofHistogram { buckets, count, sum }
+	}
+
 	fn note_sent(&self) -> usize {
 		self.sent.fetch_add(1, Ordering::Relaxed)
 	}
@@ -111,21 +196,46 @@ impl Meter {
 
 	fn note_time_of_flight(&self, tof: CoarseDuration) {
 		let _ = self.tof.force_push(tof);
+
+		let secs = tof.as_f64();
+		let idx = TOF_BUCKETS
+			.iter()
+			.position(|upper_bound| secs <= *upper_bound)
+			.unwrap_or(TOF_BUCKETS.len());
+		self.tof_buckets.buckets[idx].fetch_add(1, Ordering::Relaxed);
+		self.tof_buckets.sum_micros.fetch_add(tof.as_micros(), Ordering::Relaxed);
 	}
-}
 
-/// Determine if this instance shall be measured
-#[inline(always)]
-fn measure_tof_check(nth: usize) -> bool {
-	if cfg!(test) {
-		// for tests, be deterministic and pick every second
-		nth & 0x01 == 0
-	} else {
-		use nanorand::Rng;
-		let mut rng = nanorand::WyRand::new_seed(nth as u64);
-		let pick = rng.generate_range(1_usize..=1000);
-		// measure 5.3%
-		pick <= 53
+	/// Determine if the `nth` sent message shall be measured
+	#[inline(always)]
+	fn measure_tof_check(&self, nth: usize) -> bool {
+		match self.tof_config.sample_per_mille {
+			0 => false,
+			per_mille if per_mille >= 1000 => true,
+			// for tests, be deterministic and pick every second
+			_ if cfg!(test) => nth & 0x01 == 0,
+			per_mille => {
+				use nanorand::Rng;
+				let mut rng = nanorand::WyRand::new_seed(nth as u64);
+				let pick = rng.generate_range(1_u16..=1000);
+				pick <= per_mille
+			},
+		}
+	}
+
+	/// Log the time of flight of a message if it exceeds the configured threshold.
+	fn trace_time_of_flight<T>(&self, tof: CoarseDuration) {
+		match self.tof_config.trace_threshold {
+			Some(threshold) if tof >= threshold => {
+				tracing::warn!(
+					target: "metered",
+					tof_ms = tof.as_millis(),
+					message_type = std::any::type_name::<T>(),
+					"Message spent longer than the threshold in the channel",
+				);
+			},
+			_ => {},
+		}
 	}
 }
 
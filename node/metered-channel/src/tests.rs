@@ -39,12 +39,12 @@ fn try_send_try_next() {
 		assert_matches!(rx.meter().read(), Readout { sent: 4, received: 1, .. });
 		rx.try_next().unwrap();
 		rx.try_next().unwrap();
-		assert_matches!(tx.meter().read(), Readout { sent: 4, received: 3, blocked: 0, tof } => {
+		assert_matches!(tx.meter().read(), Readout { sent: 4, received: 3, blocked: 0, tof, .. } => {
 			// every second in test, consumed before
 			assert_eq!(dbg!(tof).len(), 1);
 		});
 		rx.try_next().unwrap();
-		assert_matches!(rx.meter().read(), Readout { sent: 4, received: 4, blocked: 0, tof } => {
+		assert_matches!(rx.meter().read(), Readout { sent: 4, received: 4, blocked: 0, tof, .. } => {
 			// every second in test, consumed before
 			assert_eq!(dbg!(tof).len(), 0);
 		});
@@ -148,3 +148,58 @@ fn blocked_send_is_metered() {
 		);
 	});
 }
+
+#[test]
+fn tof_sampling_is_configurable() {
+	let (mut none_tx, mut none_rx) = channel_with_tof_config::<Msg>(5, TofConfig::DISABLED);
+	let (all_tx, mut all_rx) = unbounded_with_tof_config::<Msg>(TofConfig::ALL);
+
+	for _ in 0..4 {
+		none_tx.try_send(Msg::default()).unwrap();
+		all_tx.unbounded_send(Msg::default()).unwrap();
+	}
+	for _ in 0..4 {
+		none_rx.try_next().unwrap();
+		all_rx.try_next().unwrap();
+	}
+
+	assert_matches!(none_rx.meter().read(), Readout { received: 4, tof, tof_histogram, .. } => {
+		assert!(tof.is_empty());
+		assert_eq!(tof_histogram.count, 0);
+	});
+	assert_matches!(all_rx.meter().read(), Readout { received: 4, tof, tof_histogram, .. } => {
+		assert_eq!(tof.len(), 4);
+		assert_eq!(tof_histogram.count, 4);
+	});
+}
+
+#[test]
+fn tof_histogram_is_cumulative_and_not_drained() {
+	let (tx, _rx) = unbounded::<Msg>();
+	let meter = tx.meter();
+	meter.note_time_of_flight(CoarseDuration::from_millis(0));
+	meter.note_time_of_flight(CoarseDuration::from_millis(20));
+	meter.note_time_of_flight(CoarseDuration::from_millis(700));
+	meter.note_time_of_flight(CoarseDuration::from_secs(30));
+
+	let histogram = meter.read().tof_histogram;
+	assert_eq!(histogram.buckets.len(), TOF_BUCKETS.len());
+	assert_eq!(histogram.count, 4);
+	// coarse durations are not exact
+	assert!((histogram.sum - 30.72).abs() < 0.1);
+
+	let cumulative = |upper_bound: f64| {
+		histogram.buckets.iter().find(|(bound, _)| *bound == upper_bound).unwrap().1
+	};
+	assert_eq!(cumulative(0.001), 1);
+	assert_eq!(cumulative(0.025), 2);
+	assert_eq!(cumulative(0.5), 2);
+	assert_eq!(cumulative(1.0), 3);
+	assert_eq!(cumulative(10.0), 3);
+
+	// unlike the sampled values, the histogram survives a read
+	assert_matches!(meter.read(), Readout { tof, tof_histogram, .. } => {
+		assert!(tof.is_empty());
+		assert_eq!(tof_histogram, histogram);
+	});
+}
@@ -24,12 +24,19 @@ use futures::{
 
 use std::{pin::Pin, result};
 
-use super::{measure_tof_check, CoarseInstant, MaybeTimeOfFlight, Meter};
+use super::{CoarseInstant, MaybeTimeOfFlight, Meter, TofConfig};
 
 /// Create a wrapped `mpsc::channel` pair of `MeteredSender` and `MeteredReceiver`.
 pub fn unbounded<T>() -> (UnboundedMeteredSender<T>, UnboundedMeteredReceiver<T>) {
+	unbounded_with_tof_config(TofConfig::default())
+}
+
+/// Create a wrapped `mpsc::unbounded` pair, measuring the time of flight of messages as configured.
+pub fn unbounded_with_tof_config<T>(
+	tof_config: TofConfig,
+) -> (UnboundedMeteredSender<T>, UnboundedMeteredReceiver<T>) {
 	let (tx, rx) = mpsc::unbounded::<MaybeTimeOfFlight<T>>();
-	let shared_meter = Meter::default();
+	let shared_meter = Meter::with_tof_config(tof_config);
 	let tx = UnboundedMeteredSender { meter: shared_meter.clone(), inner: tx };
 	let rx = UnboundedMeteredReceiver { meter: shared_meter, inner: rx };
 	(tx, rx)
@@ -81,6 +88,7 @@ impl<T> UnboundedMeteredReceiver<T> {
 					// `coarsetime` does a saturating substractio for all `CoarseInstant`s
 					let duration = tof_start.elapsed();
 					self.meter.note_time_of_flight(duration);
+					self.meter.trace_time_of_flight::<T>(duration);
 					value
 				},
 				MaybeTimeOfFlight::<T>::Bare(value) => value,
@@ -139,7 +147,7 @@ impl<T> std::ops::DerefMut for UnboundedMeteredSender<T> {
 impl<T> UnboundedMeteredSender<T> {
 	fn prepare_with_tof(&self, item: T) -> MaybeTimeOfFlight<T> {
 		let previous = self.meter.note_sent();
-		let item = if measure_tof_check(previous) {
+		let item = if self.meter.measure_tof_check(previous) {
 			MaybeTimeOfFlight::WithTimeOfFlight(item, CoarseInstant::now())
 		} else {
 			MaybeTimeOfFlight::Bare(item)
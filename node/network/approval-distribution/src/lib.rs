@@ -20,7 +20,12 @@
 
 #![warn(missing_docs)]
 
-use futures::{channel::oneshot, FutureExt as _};
+use futures::{
+	channel::oneshot,
+	future::{self, Either},
+	FutureExt as _,
+};
+use futures_timer::Delay;
 use polkadot_node_network_protocol::{
 	self as net_protocol,
 	grid_topology::{RandomRouting, RequiredRouting, SessionGridTopologies, SessionGridTopology},
@@ -40,7 +45,10 @@ use polkadot_primitives::v2::{
 	BlockNumber, CandidateIndex, Hash, SessionIndex, ValidatorIndex, ValidatorSignature,
 };
 use rand::{CryptoRng, Rng, SeedableRng};
-use std::collections::{hash_map, BTreeMap, HashMap, HashSet, VecDeque};
+use std::{
+	collections::{hash_map, BTreeMap, HashMap, HashSet, VecDeque},
+	time::Duration,
+};
 
 use self::metrics::Metrics;
 
@@ -51,6 +59,13 @@ mod tests;
 
 const LOG_TARGET: &str = "parachain::approval-distribution";
 
+/// The maximum number of assignments and approvals received from peers which are buffered
+/// before checking them as a batch.
+const MAX_BATCH_SIZE: usize = 256;
+/// The maximum time assignments and approvals received from peers are buffered for
+/// before checking them as a batch.
+const BATCH_FLUSH_INTERVAL: Duration = Duration::from_millis(20);
+
 const COST_UNEXPECTED_MESSAGE: Rep =
 	Rep::CostMinor("Peer sent an out-of-view assignment or approval");
 const COST_DUPLICATE_MESSAGE: Rep = Rep::CostMinorRepeated("Peer sent identical messages");
@@ -168,6 +183,13 @@ struct State {
 	/// also a race that occurs typically on local networks.
 	pending_known: HashMap<Hash, Vec<(PeerId, PendingMessage)>>,
 
+	/// Assignments and approvals received from peers, in the order they were received,
+	/// which are waiting to be checked together by the approval voting subsystem.
+	///
+	/// This is flushed once it reaches `MAX_BATCH_SIZE` or after `BATCH_FLUSH_INTERVAL`,
+	/// and always before handling any other message or signal.
+	incoming_batch: Vec<(PeerId, PendingMessage)>,
+
 	/// Peer data is partially stored here, and partially inline within the [`BlockEntry`]s
 	peer_views: HashMap<PeerId, View>,
 
@@ -370,7 +392,7 @@ impl State {
 					live
 				});
 			},
-			NetworkBridgeEvent::PeerMessage(peer_id, Versioned::V1(msg)) => {
+			NetworkBridgeEvent::PeerMessage(peer_id, Versioned::V1(msg) | Versioned::V2(msg)) => {
 				self.process_incoming_peer_message(ctx, metrics, peer_id, msg, rng).await;
 			},
 		}
@@ -465,30 +487,8 @@ impl State {
 
 				let _timer = metrics.time_import_pending_now_known();
 
-				for (peer_id, message) in to_import {
-					match message {
-						PendingMessage::Assignment(assignment, claimed_index) => {
-							self.import_and_circulate_assignment(
-								ctx,
-								metrics,
-								MessageSource::Peer(peer_id),
-								assignment,
-								claimed_index,
-								rng,
-							)
-							.await;
-						},
-						PendingMessage::Approval(approval_vote) => {
-							self.import_and_circulate_approval(
-								ctx,
-								metrics,
-								MessageSource::Peer(peer_id),
-								approval_vote,
-							)
-							.await;
-						},
-					}
-				}
+				self.incoming_batch.extend(to_import);
+				self.flush_incoming_batch(ctx, metrics, rng).await;
 			}
 		}
 
@@ -559,15 +559,10 @@ impl State {
 						continue
 					}
 
-					self.import_and_circulate_assignment(
-						ctx,
-						metrics,
-						MessageSource::Peer(peer_id.clone()),
-						assignment,
-						claimed_index,
-						rng,
-					)
-					.await;
+					self.incoming_batch.push((
+						peer_id.clone(),
+						PendingMessage::Assignment(assignment, claimed_index),
+					));
 				}
 			},
 			protocol_v1::ApprovalDistributionMessage::Approvals(approvals) => {
@@ -597,16 +592,15 @@ impl State {
 						continue
 					}
 
-					self.import_and_circulate_approval(
-						ctx,
-						metrics,
-						MessageSource::Peer(peer_id.clone()),
-						approval_vote,
-					)
-					.await;
+					self.incoming_batch
+						.push((peer_id.clone(), PendingMessage::Approval(approval_vote)));
 				}
 			},
 		}
+
+		if self.incoming_batch.len() >= MAX_BATCH_SIZE {
+			self.flush_incoming_batch(ctx, metrics, rng).await;
+		}
 	}
 
 	// handle a peer view change: requires that the peer is already connected
@@ -697,25 +691,98 @@ impl State {
 	) where
 		R: CryptoRng + Rng,
 	{
-		let block_hash = assignment.block_hash.clone();
+		if let Some(peer_id) = source.peer_id() {
+			if !self
+				.pre_check_peer_assignment(ctx, &peer_id, &assignment, claimed_candidate_index)
+				.await
+			{
+				return
+			}
+
+			let result = match check_assignments(
+				ctx,
+				metrics,
+				vec![(assignment.clone(), claimed_candidate_index)],
+			)
+			.await
+			{
+				Some(mut results) if results.len() == 1 => results.remove(0),
+				_ => return,
+			};
+
+			if !self
+				.on_peer_assignment_checked(
+					ctx,
+					peer_id,
+					&assignment,
+					claimed_candidate_index,
+					result,
+				)
+				.await
+			{
+				return
+			}
+		} else {
+			let entry = match self.blocks.get_mut(&assignment.block_hash) {
+				Some(entry) => entry,
+				None => return,
+			};
+
+			let message_subject = MessageSubject(
+				assignment.block_hash,
+				claimed_candidate_index,
+				assignment.validator,
+			);
+
+			if !entry.knowledge.insert(message_subject.clone(), MessageKind::Assignment) {
+				// if we already imported an assignment, there is no need to distribute it again
+				gum::warn!(
+					target: LOG_TARGET,
+					?message_subject,
+					"Importing locally an already known assignment",
+				);
+				return
+			} else {
+				gum::debug!(
+					target: LOG_TARGET,
+					?message_subject,
+					"Importing locally a new assignment",
+				);
+			}
+		}
+
+		self.circulate_assignment(ctx, metrics, source, assignment, claimed_candidate_index, rng)
+			.await;
+	}
+
+	// Checks whether an assignment received from a peer needs to be checked by approval voting.
+	//
+	// This handles out-of-view, duplicate and already known assignments, adjusting the reputation
+	// and knowledge of the peer accordingly.
+	async fn pre_check_peer_assignment<Context>(
+		&mut self,
+		ctx: &mut Context,
+		peer_id: &PeerId,
+		assignment: &IndirectAssignmentCert,
+		claimed_candidate_index: CandidateIndex,
+	) -> bool {
+		let block_hash = assignment.block_hash;
 		let validator_index = assignment.validator;
 
 		let entry = match self.blocks.get_mut(&block_hash) {
 			Some(entry) => entry,
 			None => {
-				if let Some(peer_id) = source.peer_id() {
-					gum::trace!(
-						target: LOG_TARGET,
-						?peer_id,
-						hash = ?block_hash,
-						?validator_index,
-						"Unexpected assignment",
-					);
-					if !self.recent_outdated_blocks.is_recent_outdated(&block_hash) {
-						modify_reputation(ctx.sender(), peer_id, COST_UNEXPECTED_MESSAGE).await;
-					}
+				gum::trace!(
+					target: LOG_TARGET,
+					?peer_id,
+					hash = ?block_hash,
+					?validator_index,
+					"Unexpected assignment",
+				);
+				if !self.recent_outdated_blocks.is_recent_outdated(&block_hash) {
+					modify_reputation(ctx.sender(), peer_id.clone(), COST_UNEXPECTED_MESSAGE).await;
 				}
-				return
+				return false
 			},
 		};
 
@@ -723,138 +790,155 @@ impl State {
 		let message_subject = MessageSubject(block_hash, claimed_candidate_index, validator_index);
 		let message_kind = MessageKind::Assignment;
 
-		if let Some(peer_id) = source.peer_id() {
-			// check if our knowledge of the peer already contains this assignment
-			match entry.known_by.entry(peer_id.clone()) {
-				hash_map::Entry::Occupied(mut peer_knowledge) => {
-					let peer_knowledge = peer_knowledge.get_mut();
-					if peer_knowledge.contains(&message_subject, message_kind) {
-						// wasn't included before
-						if !peer_knowledge.received.insert(message_subject.clone(), message_kind) {
-							gum::debug!(
-								target: LOG_TARGET,
-								?peer_id,
-								?message_subject,
-								"Duplicate assignment",
-							);
-							modify_reputation(ctx.sender(), peer_id, COST_DUPLICATE_MESSAGE).await;
-						}
-						return
+		// check if our knowledge of the peer already contains this assignment
+		match entry.known_by.entry(peer_id.clone()) {
+			hash_map::Entry::Occupied(mut peer_knowledge) => {
+				let peer_knowledge = peer_knowledge.get_mut();
+				if peer_knowledge.contains(&message_subject, message_kind) {
+					// wasn't included before
+					if !peer_knowledge.received.insert(message_subject.clone(), message_kind) {
+						gum::debug!(
+							target: LOG_TARGET,
+							?peer_id,
+							?message_subject,
+							"Duplicate assignment",
+						);
+						modify_reputation(ctx.sender(), peer_id.clone(), COST_DUPLICATE_MESSAGE)
+							.await;
 					}
-				},
-				hash_map::Entry::Vacant(_) => {
-					gum::debug!(
-						target: LOG_TARGET,
-						?peer_id,
-						?message_subject,
-						"Assignment from a peer is out of view",
-					);
-					modify_reputation(ctx.sender(), peer_id.clone(), COST_UNEXPECTED_MESSAGE).await;
-				},
-			}
-
-			// if the assignment is known to be valid, reward the peer
-			if entry.knowledge.contains(&message_subject, message_kind) {
-				modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE).await;
-				if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-					gum::trace!(target: LOG_TARGET, ?peer_id, ?message_subject, "Known assignment");
-					peer_knowledge.received.insert(message_subject, message_kind);
+					return false
 				}
-				return
+			},
+			hash_map::Entry::Vacant(_) => {
+				gum::debug!(
+					target: LOG_TARGET,
+					?peer_id,
+					?message_subject,
+					"Assignment from a peer is out of view",
+				);
+				modify_reputation(ctx.sender(), peer_id.clone(), COST_UNEXPECTED_MESSAGE).await;
+			},
+		}
+
+		// if the assignment is known to be valid, reward the peer
+		if entry.knowledge.contains(&message_subject, message_kind) {
+			modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE).await;
+			if let Some(peer_knowledge) = entry.known_by.get_mut(peer_id) {
+				gum::trace!(target: LOG_TARGET, ?peer_id, ?message_subject, "Known assignment");
+				peer_knowledge.received.insert(message_subject, message_kind);
 			}
+			return false
+		}
 
-			let (tx, rx) = oneshot::channel();
+		true
+	}
 
-			ctx.send_message(ApprovalVotingMessage::CheckAndImportAssignment(
-				assignment.clone(),
-				claimed_candidate_index,
-				tx,
-			))
-			.await;
+	// Handles the result of checking an assignment received from a peer.
+	//
+	// Returns `true` if the assignment was accepted and should be circulated.
+	async fn on_peer_assignment_checked<Context>(
+		&mut self,
+		ctx: &mut Context,
+		peer_id: PeerId,
+		assignment: &IndirectAssignmentCert,
+		claimed_candidate_index: CandidateIndex,
+		result: AssignmentCheckResult,
+	) -> bool {
+		let block_hash = assignment.block_hash;
+		let message_subject =
+			MessageSubject(block_hash, claimed_candidate_index, assignment.validator);
+		let message_kind = MessageKind::Assignment;
 
-			let timer = metrics.time_awaiting_approval_voting();
-			let result = match rx.await {
-				Ok(result) => result,
-				Err(_) => {
-					gum::debug!(target: LOG_TARGET, "The approval voting subsystem is down");
-					return
-				},
-			};
-			drop(timer);
+		gum::trace!(
+			target: LOG_TARGET,
+			?peer_id,
+			?message_subject,
+			?result,
+			"Checked assignment",
+		);
 
-			gum::trace!(
-				target: LOG_TARGET,
-				?source,
-				?message_subject,
-				?result,
-				"Checked assignment",
-			);
-			match result {
-				AssignmentCheckResult::Accepted => {
-					modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE_FIRST)
-						.await;
+		match result {
+			AssignmentCheckResult::Accepted => {
+				modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE_FIRST).await;
+				if let Some(entry) = self.blocks.get_mut(&block_hash) {
 					entry.knowledge.known_messages.insert(message_subject.clone(), message_kind);
 					if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-						peer_knowledge.received.insert(message_subject.clone(), message_kind);
+						peer_knowledge.received.insert(message_subject, message_kind);
 					}
-				},
-				AssignmentCheckResult::AcceptedDuplicate => {
-					// "duplicate" assignments aren't necessarily equal.
-					// There is more than one way each validator can be assigned to each core.
-					// cf. https://github.com/paritytech/polkadot/pull/2160#discussion_r557628699
-					if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-						peer_knowledge.received.insert(message_subject.clone(), message_kind);
-					}
-					gum::debug!(
-						target: LOG_TARGET,
-						hash = ?block_hash,
-						?peer_id,
-						"Got an `AcceptedDuplicate` assignment",
-					);
-					return
-				},
-				AssignmentCheckResult::TooFarInFuture => {
-					gum::debug!(
-						target: LOG_TARGET,
-						hash = ?block_hash,
-						?peer_id,
-						"Got an assignment too far in the future",
-					);
-					modify_reputation(ctx.sender(), peer_id, COST_ASSIGNMENT_TOO_FAR_IN_THE_FUTURE)
-						.await;
-					return
-				},
-				AssignmentCheckResult::Bad(error) => {
-					gum::info!(
-						target: LOG_TARGET,
-						hash = ?block_hash,
-						?peer_id,
-						%error,
-						"Got a bad assignment from peer",
-					);
-					modify_reputation(ctx.sender(), peer_id, COST_INVALID_MESSAGE).await;
-					return
-				},
-			}
-		} else {
-			if !entry.knowledge.insert(message_subject.clone(), message_kind) {
-				// if we already imported an assignment, there is no need to distribute it again
-				gum::warn!(
+					true
+				} else {
+					false
+				}
+			},
+			AssignmentCheckResult::AcceptedDuplicate => {
+				// "duplicate" assignments aren't necessarily equal.
+				// There is more than one way each validator can be assigned to each core.
+				// cf. https://github.com/paritytech/polkadot/pull/2160#discussion_r557628699
+				if let Some(peer_knowledge) = self
+					.blocks
+					.get_mut(&block_hash)
+					.and_then(|entry| entry.known_by.get_mut(&peer_id))
+				{
+					peer_knowledge.received.insert(message_subject, message_kind);
+				}
+				gum::debug!(
 					target: LOG_TARGET,
-					?message_subject,
-					"Importing locally an already known assignment",
+					hash = ?block_hash,
+					?peer_id,
+					"Got an `AcceptedDuplicate` assignment",
 				);
-				return
-			} else {
+				false
+			},
+			AssignmentCheckResult::TooFarInFuture => {
 				gum::debug!(
 					target: LOG_TARGET,
-					?message_subject,
-					"Importing locally a new assignment",
+					hash = ?block_hash,
+					?peer_id,
+					"Got an assignment too far in the future",
 				);
-			}
+				modify_reputation(ctx.sender(), peer_id, COST_ASSIGNMENT_TOO_FAR_IN_THE_FUTURE)
+					.await;
+				false
+			},
+			AssignmentCheckResult::Bad(error) => {
+				gum::info!(
+					target: LOG_TARGET,
+					hash = ?block_hash,
+					?peer_id,
+					%error,
+					"Got a bad assignment from peer",
+				);
+				modify_reputation(ctx.sender(), peer_id, COST_INVALID_MESSAGE).await;
+				false
+			},
 		}
+	}
+
+	// Dispatches a newly imported assignment to all peers in its routing set.
+	//
+	// Invariant: to our knowledge, none of the peers except for the `source` know about the assignment.
+	async fn circulate_assignment<Context, R>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		source: MessageSource,
+		assignment: IndirectAssignmentCert,
+		claimed_candidate_index: CandidateIndex,
+		rng: &mut R,
+	) where
+		R: CryptoRng + Rng,
+	{
+		let block_hash = assignment.block_hash.clone();
+		let validator_index = assignment.validator;
+
+		let entry = match self.blocks.get_mut(&block_hash) {
+			Some(entry) => entry,
+			None => return,
+		};
+
+		let message_subject = MessageSubject(block_hash, claimed_candidate_index, validator_index);
+		let message_kind = MessageKind::Assignment;
 
-		// Invariant: to our knowledge, none of the peers except for the `source` know about the assignment.
 		metrics.on_assignment_imported();
 
 		let topology = self.topologies.get_topology(entry.session);
@@ -955,19 +1039,70 @@ impl State {
 		source: MessageSource,
 		vote: IndirectSignedApprovalVote,
 	) {
-		let block_hash = vote.block_hash.clone();
+		if let Some(peer_id) = source.peer_id() {
+			if !self.pre_check_peer_approval(ctx, &peer_id, &vote).await {
+				return
+			}
+
+			let result = match check_approvals(ctx, metrics, vec![vote.clone()]).await {
+				Some(mut results) if results.len() == 1 => results.remove(0),
+				_ => return,
+			};
+
+			if !self.on_peer_approval_checked(ctx, peer_id, &vote, result).await {
+				return
+			}
+		} else {
+			let entry = match self.blocks.get_mut(&vote.block_hash) {
+				Some(entry) if entry.candidates.get(vote.candidate_index as usize).is_some() =>
+					entry,
+				_ => return,
+			};
+
+			let message_subject =
+				MessageSubject(vote.block_hash, vote.candidate_index, vote.validator);
+
+			if !entry.knowledge.insert(message_subject.clone(), MessageKind::Approval) {
+				// if we already imported an approval, there is no need to distribute it again
+				gum::warn!(
+					target: LOG_TARGET,
+					?message_subject,
+					"Importing locally an already known approval",
+				);
+				return
+			} else {
+				gum::debug!(
+					target: LOG_TARGET,
+					?message_subject,
+					"Importing locally a new approval",
+				);
+			}
+		}
+
+		self.circulate_approval(ctx, metrics, source, vote).await;
+	}
+
+	// Checks whether an approval received from a peer needs to be checked by approval voting.
+	//
+	// This handles out-of-view, duplicate, already known approvals and approvals we don't know
+	// the assignment for, adjusting the reputation and knowledge of the peer accordingly.
+	async fn pre_check_peer_approval<Context>(
+		&mut self,
+		ctx: &mut Context,
+		peer_id: &PeerId,
+		vote: &IndirectSignedApprovalVote,
+	) -> bool {
+		let block_hash = vote.block_hash;
 		let validator_index = vote.validator;
 		let candidate_index = vote.candidate_index;
 
 		let entry = match self.blocks.get_mut(&block_hash) {
 			Some(entry) if entry.candidates.get(candidate_index as usize).is_some() => entry,
 			_ => {
-				if let Some(peer_id) = source.peer_id() {
-					if !self.recent_outdated_blocks.is_recent_outdated(&block_hash) {
-						modify_reputation(ctx.sender(), peer_id, COST_UNEXPECTED_MESSAGE).await;
-					}
+				if !self.recent_outdated_blocks.is_recent_outdated(&block_hash) {
+					modify_reputation(ctx.sender(), peer_id.clone(), COST_UNEXPECTED_MESSAGE).await;
 				}
-				return
+				return false
 			},
 		};
 
@@ -975,119 +1110,130 @@ impl State {
 		let message_subject = MessageSubject(block_hash, candidate_index, validator_index);
 		let message_kind = MessageKind::Approval;
 
-		if let Some(peer_id) = source.peer_id() {
-			if !entry.knowledge.contains(&message_subject, MessageKind::Assignment) {
+		if !entry.knowledge.contains(&message_subject, MessageKind::Assignment) {
+			gum::debug!(
+				target: LOG_TARGET,
+				?peer_id,
+				?message_subject,
+				"Unknown approval assignment",
+			);
+			modify_reputation(ctx.sender(), peer_id.clone(), COST_UNEXPECTED_MESSAGE).await;
+			return false
+		}
+
+		// check if our knowledge of the peer already contains this approval
+		match entry.known_by.entry(peer_id.clone()) {
+			hash_map::Entry::Occupied(mut knowledge) => {
+				let peer_knowledge = knowledge.get_mut();
+				if peer_knowledge.contains(&message_subject, message_kind) {
+					if !peer_knowledge.received.insert(message_subject.clone(), message_kind) {
+						gum::debug!(
+							target: LOG_TARGET,
+							?peer_id,
+							?message_subject,
+							"Duplicate approval",
+						);
+
+						modify_reputation(ctx.sender(), peer_id.clone(), COST_DUPLICATE_MESSAGE)
+							.await;
+					}
+					return false
+				}
+			},
+			hash_map::Entry::Vacant(_) => {
 				gum::debug!(
 					target: LOG_TARGET,
 					?peer_id,
 					?message_subject,
-					"Unknown approval assignment",
+					"Approval from a peer is out of view",
 				);
-				modify_reputation(ctx.sender(), peer_id, COST_UNEXPECTED_MESSAGE).await;
-				return
-			}
-
-			// check if our knowledge of the peer already contains this approval
-			match entry.known_by.entry(peer_id.clone()) {
-				hash_map::Entry::Occupied(mut knowledge) => {
-					let peer_knowledge = knowledge.get_mut();
-					if peer_knowledge.contains(&message_subject, message_kind) {
-						if !peer_knowledge.received.insert(message_subject.clone(), message_kind) {
-							gum::debug!(
-								target: LOG_TARGET,
-								?peer_id,
-								?message_subject,
-								"Duplicate approval",
-							);
-
-							modify_reputation(ctx.sender(), peer_id, COST_DUPLICATE_MESSAGE).await;
-						}
-						return
-					}
-				},
-				hash_map::Entry::Vacant(_) => {
-					gum::debug!(
-						target: LOG_TARGET,
-						?peer_id,
-						?message_subject,
-						"Approval from a peer is out of view",
-					);
-					modify_reputation(ctx.sender(), peer_id.clone(), COST_UNEXPECTED_MESSAGE).await;
-				},
-			}
+				modify_reputation(ctx.sender(), peer_id.clone(), COST_UNEXPECTED_MESSAGE).await;
+			},
+		}
 
-			// if the approval is known to be valid, reward the peer
-			if entry.knowledge.contains(&message_subject, message_kind) {
-				gum::trace!(target: LOG_TARGET, ?peer_id, ?message_subject, "Known approval");
-				modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE).await;
-				if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-					peer_knowledge.received.insert(message_subject.clone(), message_kind);
-				}
-				return
+		// if the approval is known to be valid, reward the peer
+		if entry.knowledge.contains(&message_subject, message_kind) {
+			gum::trace!(target: LOG_TARGET, ?peer_id, ?message_subject, "Known approval");
+			modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE).await;
+			if let Some(peer_knowledge) = entry.known_by.get_mut(peer_id) {
+				peer_knowledge.received.insert(message_subject.clone(), message_kind);
 			}
+			return false
+		}
 
-			let (tx, rx) = oneshot::channel();
+		true
+	}
 
-			ctx.send_message(ApprovalVotingMessage::CheckAndImportApproval(vote.clone(), tx))
-				.await;
+	// Handles the result of checking an approval received from a peer.
+	//
+	// Returns `true` if the approval was accepted and should be circulated.
+	async fn on_peer_approval_checked<Context>(
+		&mut self,
+		ctx: &mut Context,
+		peer_id: PeerId,
+		vote: &IndirectSignedApprovalVote,
+		result: ApprovalCheckResult,
+	) -> bool {
+		let message_subject = MessageSubject(vote.block_hash, vote.candidate_index, vote.validator);
+		let message_kind = MessageKind::Approval;
 
-			let timer = metrics.time_awaiting_approval_voting();
-			let result = match rx.await {
-				Ok(result) => result,
-				Err(_) => {
-					gum::debug!(target: LOG_TARGET, "The approval voting subsystem is down");
-					return
-				},
-			};
-			drop(timer);
+		gum::trace!(
+			target: LOG_TARGET,
+			?peer_id,
+			?message_subject,
+			?result,
+			"Checked approval",
+		);
 
-			gum::trace!(
-				target: LOG_TARGET,
-				?peer_id,
-				?message_subject,
-				?result,
-				"Checked approval",
-			);
-			match result {
-				ApprovalCheckResult::Accepted => {
-					modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE_FIRST)
-						.await;
+		match result {
+			ApprovalCheckResult::Accepted => {
+				modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE_FIRST).await;
 
+				if let Some(entry) = self.blocks.get_mut(&vote.block_hash) {
 					entry.knowledge.insert(message_subject.clone(), message_kind);
 					if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-						peer_knowledge.received.insert(message_subject.clone(), message_kind);
+						peer_knowledge.received.insert(message_subject, message_kind);
 					}
-				},
-				ApprovalCheckResult::Bad(error) => {
-					modify_reputation(ctx.sender(), peer_id, COST_INVALID_MESSAGE).await;
-					gum::info!(
-						target: LOG_TARGET,
-						?peer_id,
-						%error,
-						"Got a bad approval from peer",
-					);
-					return
-				},
-			}
-		} else {
-			if !entry.knowledge.insert(message_subject.clone(), message_kind) {
-				// if we already imported an approval, there is no need to distribute it again
-				gum::warn!(
-					target: LOG_TARGET,
-					?message_subject,
-					"Importing locally an already known approval",
-				);
-				return
-			} else {
-				gum::debug!(
+					true
+				} else {
+					false
+				}
+			},
+			ApprovalCheckResult::Bad(error) => {
+				modify_reputation(ctx.sender(), peer_id, COST_INVALID_MESSAGE).await;
+				gum::info!(
 					target: LOG_TARGET,
-					?message_subject,
-					"Importing locally a new approval",
+					?peer_id,
+					%error,
+					"Got a bad approval from peer",
 				);
-			}
+				false
+			},
 		}
+	}
+
+	// Dispatches a newly imported approval to all peers in its routing set.
+	//
+	// Invariant: to our knowledge, none of the peers except for the `source` know about the approval.
+	async fn circulate_approval<Context>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		source: MessageSource,
+		vote: IndirectSignedApprovalVote,
+	) {
+		let block_hash = vote.block_hash.clone();
+		let validator_index = vote.validator;
+		let candidate_index = vote.candidate_index;
+
+		let entry = match self.blocks.get_mut(&block_hash) {
+			Some(entry) => entry,
+			None => return,
+		};
+
+		let message_subject = MessageSubject(block_hash, candidate_index, validator_index);
+		let message_kind = MessageKind::Approval;
 
-		// Invariant: to our knowledge, none of the peers except for the `source` know about the approval.
 		metrics.on_approval_imported();
 
 		let required_routing = match entry.candidates.get_mut(candidate_index as usize) {
@@ -1210,6 +1356,175 @@ impl State {
 		}
 	}
 
+	// Checks and imports all assignments and approvals received from peers since the last flush.
+	//
+	// All assignments are handled before all approvals, so approvals whose assignments
+	// arrived in the same batch are not rejected.
+	async fn flush_incoming_batch<Context, R>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		rng: &mut R,
+	) where
+		R: CryptoRng + Rng,
+	{
+		if self.incoming_batch.is_empty() {
+			return
+		}
+
+		let mut assignments = Vec::new();
+		let mut approvals = Vec::new();
+		for (peer_id, message) in std::mem::take(&mut self.incoming_batch) {
+			match message {
+				PendingMessage::Assignment(assignment, claimed_index) =>
+					assignments.push((peer_id, assignment, claimed_index)),
+				PendingMessage::Approval(vote) => approvals.push((peer_id, vote)),
+			}
+		}
+
+		gum::trace!(
+			target: LOG_TARGET,
+			num_assignments = assignments.len(),
+			num_approvals = approvals.len(),
+			"Flushing batch of peer messages",
+		);
+
+		self.check_and_circulate_assignments(ctx, metrics, assignments, rng).await;
+		self.check_and_circulate_approvals(ctx, metrics, approvals).await;
+	}
+
+	async fn check_and_circulate_assignments<Context, R>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		assignments: Vec<(PeerId, IndirectAssignmentCert, CandidateIndex)>,
+		rng: &mut R,
+	) where
+		R: CryptoRng + Rng,
+	{
+		let mut to_check = Vec::with_capacity(assignments.len());
+		let mut to_check_subjects = HashSet::new();
+		let mut deferred = Vec::new();
+
+		for (peer_id, assignment, claimed_index) in assignments {
+			let message_subject =
+				MessageSubject(assignment.block_hash, claimed_index, assignment.validator);
+
+			// The same assignment from multiple peers is only checked once, the others are
+			// handled like any other known assignment after the check.
+			if to_check_subjects.contains(&message_subject) {
+				deferred.push((peer_id, assignment, claimed_index));
+				continue
+			}
+
+			if self.pre_check_peer_assignment(ctx, &peer_id, &assignment, claimed_index).await {
+				to_check_subjects.insert(message_subject);
+				to_check.push((peer_id, assignment, claimed_index));
+			}
+		}
+
+		if !to_check.is_empty() {
+			let results = match check_assignments(
+				ctx,
+				metrics,
+				to_check.iter().map(|(_, a, c)| (a.clone(), *c)).collect(),
+			)
+			.await
+			{
+				Some(results) => results,
+				None => return,
+			};
+
+			for ((peer_id, assignment, claimed_index), result) in to_check.into_iter().zip(results)
+			{
+				if self
+					.on_peer_assignment_checked(
+						ctx,
+						peer_id.clone(),
+						&assignment,
+						claimed_index,
+						result,
+					)
+					.await
+				{
+					self.circulate_assignment(
+						ctx,
+						metrics,
+						MessageSource::Peer(peer_id),
+						assignment,
+						claimed_index,
+						rng,
+					)
+					.await;
+				}
+			}
+		}
+
+		for (peer_id, assignment, claimed_index) in deferred {
+			self.import_and_circulate_assignment(
+				ctx,
+				metrics,
+				MessageSource::Peer(peer_id),
+				assignment,
+				claimed_index,
+				rng,
+			)
+			.await;
+		}
+	}
+
+	async fn check_and_circulate_approvals<Context>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		approvals: Vec<(PeerId, IndirectSignedApprovalVote)>,
+	) {
+		let mut to_check = Vec::with_capacity(approvals.len());
+		let mut to_check_subjects = HashSet::new();
+		let mut deferred = Vec::new();
+
+		for (peer_id, vote) in approvals {
+			let message_subject =
+				MessageSubject(vote.block_hash, vote.candidate_index, vote.validator);
+
+			// The same approval from multiple peers is only checked once, the others are
+			// handled like any other known approval after the check.
+			if to_check_subjects.contains(&message_subject) {
+				deferred.push((peer_id, vote));
+				continue
+			}
+
+			if self.pre_check_peer_approval(ctx, &peer_id, &vote).await {
+				to_check_subjects.insert(message_subject);
+				to_check.push((peer_id, vote));
+			}
+		}
+
+		if !to_check.is_empty() {
+			let results = match check_approvals(
+				ctx,
+				metrics,
+				to_check.iter().map(|(_, vote)| vote.clone()).collect(),
+			)
+			.await
+			{
+				Some(results) => results,
+				None => return,
+			};
+
+			for ((peer_id, vote), result) in to_check.into_iter().zip(results) {
+				if self.on_peer_approval_checked(ctx, peer_id.clone(), &vote, result).await {
+					self.circulate_approval(ctx, metrics, MessageSource::Peer(peer_id), vote).await;
+				}
+			}
+		}
+
+		for (peer_id, vote) in deferred {
+			self.import_and_circulate_approval(ctx, metrics, MessageSource::Peer(peer_id), vote)
+				.await;
+		}
+	}
+
 	async fn unify_with_peer(
 		sender: &mut impl overseer::ApprovalDistributionSenderTrait,
 		metrics: &Metrics,
@@ -1569,6 +1884,89 @@ async fn adjust_required_routing_and_propagate<Context, BlockFilter, RoutingModi
 	}
 }
 
+// Ask the approval voting subsystem to check the given assignments received from peers.
+//
+// Uses the batched request unless there is just a single assignment.
+// Returns `None` if the approval voting subsystem is down.
+#[overseer::contextbounds(ApprovalDistribution, prefix = self::overseer)]
+async fn check_assignments<Context>(
+	ctx: &mut Context,
+	metrics: &Metrics,
+	mut assignments: Vec<(IndirectAssignmentCert, CandidateIndex)>,
+) -> Option<Vec<AssignmentCheckResult>> {
+	metrics.on_check_batch("assignment", assignments.len());
+
+	let results = if assignments.len() == 1 {
+		let (assignment, claimed_candidate_index) = assignments.remove(0);
+		let (tx, rx) = oneshot::channel();
+
+		ctx.send_message(ApprovalVotingMessage::CheckAndImportAssignment(
+			assignment,
+			claimed_candidate_index,
+			tx,
+		))
+		.await;
+
+		let _timer = metrics.time_awaiting_approval_voting();
+		rx.await.map(|result| vec![result])
+	} else {
+		let (tx, rx) = oneshot::channel();
+
+		ctx.send_message(ApprovalVotingMessage::CheckAndImportAssignments(assignments, tx))
+			.await;
+
+		let _timer = metrics.time_awaiting_approval_voting();
+		rx.await
+	};
+
+	match results {
+		Ok(results) => Some(results),
+		Err(_) => {
+			gum::debug!(target: LOG_TARGET, "The approval voting subsystem is down");
+			None
+		},
+	}
+}
+
+// Ask the approval voting subsystem to check the given approvals received from peers.
+//
+// Uses the batched request unless there is just a single approval, so that the signatures
+// can be batch-verified. Returns `None` if the approval voting subsystem is down.
+#[overseer::contextbounds(ApprovalDistribution, prefix = self::overseer)]
+async fn check_approvals<Context>(
+	ctx: &mut Context,
+	metrics: &Metrics,
+	mut votes: Vec<IndirectSignedApprovalVote>,
+) -> Option<Vec<ApprovalCheckResult>> {
+	metrics.on_check_batch("approval", votes.len());
+
+	let results = if votes.len() == 1 {
+		let (tx, rx) = oneshot::channel();
+
+		ctx.send_message(ApprovalVotingMessage::CheckAndImportApproval(votes.remove(0), tx))
+			.await;
+
+		let _timer = metrics.time_awaiting_approval_voting();
+		rx.await.map(|result| vec![result])
+	} else {
+		let (tx, rx) = oneshot::channel();
+
+		ctx.send_message(ApprovalVotingMessage::CheckAndImportApprovals(votes, tx))
+			.await;
+
+		let _timer = metrics.time_awaiting_approval_voting();
+		rx.await
+	};
+
+	match results {
+		Ok(results) => Some(results),
+		Err(_) => {
+			gum::debug!(target: LOG_TARGET, "The approval voting subsystem is down");
+			None
+		},
+	}
+}
+
 /// Modify the reputation of a peer based on its behavior.
 async fn modify_reputation(
 	sender: &mut impl overseer::ApprovalDistributionSenderTrait,
@@ -1608,14 +2006,51 @@ impl ApprovalDistribution {
 		state: &mut State,
 		rng: &mut (impl CryptoRng + Rng),
 	) {
+		let mut batch_timer = None;
 		loop {
-			let message = match ctx.recv().await {
+			if state.incoming_batch.is_empty() {
+				batch_timer = None;
+			}
+
+			let message = if state.incoming_batch.is_empty() {
+				ctx.recv().await
+			} else {
+				let timer = batch_timer.get_or_insert_with(|| Delay::new(BATCH_FLUSH_INTERVAL));
+				let message = match future::select(ctx.recv(), timer).await {
+					Either::Left((message, _)) => Some(message),
+					Either::Right(_) => None,
+				};
+
+				match message {
+					Some(message) => message,
+					None => {
+						state.flush_incoming_batch(&mut ctx, &self.metrics, rng).await;
+						continue
+					},
+				}
+			};
+
+			let message = match message {
 				Ok(message) => message,
 				Err(e) => {
 					gum::debug!(target: LOG_TARGET, err = ?e, "Failed to receive a message from Overseer, exiting");
 					return
 				},
 			};
+
+			// Assignments and approvals from peers are added to the batch, everything else
+			// is handled only after the batch has been checked to preserve ordering.
+			if !matches!(
+				message,
+				FromOrchestra::Communication {
+					msg: ApprovalDistributionMessage::NetworkBridgeUpdate(
+						NetworkBridgeEvent::PeerMessage(..)
+					),
+				}
+			) {
+				state.flush_incoming_batch(&mut ctx, &self.metrics, rng).await;
+			}
+
 			match message {
 				FromOrchestra::Communication { msg } =>
 					Self::handle_incoming(&mut ctx, state, msg, &self.metrics, rng).await,
@@ -31,6 +31,7 @@ struct MetricsInner {
 	time_unify_with_peer: prometheus::Histogram,
 	time_import_pending_now_known: prometheus::Histogram,
 	time_awaiting_approval_voting: prometheus::Histogram,
+	check_batch_size: prometheus::HistogramVec,
 }
 
 impl Metrics {
@@ -72,6 +73,12 @@ impl Metrics {
 			.map(|metrics| metrics.time_awaiting_approval_voting.start_timer())
 	}
 
+	pub(crate) fn on_check_batch(&self, kind: &'static str, size: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.check_batch_size.with_label_values(&[kind]).observe(size as f64);
+		}
+	}
+
 	pub(crate) fn on_aggression_l1(&self) {
 		if let Some(metrics) = &self.0 {
 			metrics.aggression_l1_messages_total.inc();
@@ -144,6 +151,17 @@ impl MetricsTrait for Metrics {
 				))?,
 				registry,
 			)?,
+			check_batch_size: prometheus::register(
+				prometheus::HistogramVec::new(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_approval_distribution_check_batch_size",
+						"Number of peer messages sent to the Approval Voting Subsystem to be checked at once.",
+					)
+					.buckets(vec![1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0, 256.0]),
+					&["kind"],
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
@@ -21,7 +21,9 @@ use polkadot_node_network_protocol::{our_view, view, ObservedRole};
 use polkadot_node_primitives::approval::{
 	AssignmentCertKind, VRFOutput, VRFProof, RELAY_VRF_MODULO_CONTEXT,
 };
-use polkadot_node_subsystem::messages::{network_bridge_event, AllMessages, ApprovalCheckError};
+use polkadot_node_subsystem::messages::{
+	network_bridge_event, AllMessages, ApprovalCheckError, AssignmentCheckError,
+};
 use polkadot_node_subsystem_test_helpers as test_helpers;
 use polkadot_node_subsystem_util::TimeoutExt as _;
 use polkadot_primitives::v2::{AuthorityDiscoveryId, BlakeTwo256, HashT};
@@ -369,22 +371,23 @@ fn spam_attack_results_in_negative_reputation_change() {
 		let msg = protocol_v1::ApprovalDistributionMessage::Assignments(assignments.clone());
 		send_message_from_peer(overseer, peer, msg.clone()).await;
 
-		for i in 0..candidates_count {
+		for _ in 0..candidates_count {
 			expect_reputation_change(overseer, peer, COST_UNEXPECTED_MESSAGE).await;
+		}
 
-			assert_matches!(
-				overseer_recv(overseer).await,
-				AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
-					assignment,
-					claimed_candidate_index,
-					tx,
-				)) => {
-					assert_eq!(assignment, assignments[i].0);
-					assert_eq!(claimed_candidate_index, assignments[i].1);
-					tx.send(AssignmentCheckResult::Accepted).unwrap();
-				}
-			);
+		// all assignments are checked at once
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignments(
+				batch,
+				tx,
+			)) => {
+				assert_eq!(batch, assignments);
+				tx.send(vec![AssignmentCheckResult::Accepted; candidates_count]).unwrap();
+			}
+		);
 
+		for _ in 0..candidates_count {
 			expect_reputation_change(overseer, peer, BENEFIT_VALID_MESSAGE_FIRST).await;
 		}
 
@@ -410,6 +413,80 @@ fn spam_attack_results_in_negative_reputation_change() {
 	});
 }
 
+/// Assignments received from different peers in quick succession are checked in a single batch,
+/// and each peer is rewarded or punished according to the result for its own assignment.
+#[test]
+fn assignments_from_multiple_peers_are_checked_in_one_batch() {
+	let parent_hash = Hash::repeat_byte(0xFF);
+	let hash = Hash::repeat_byte(0xAA);
+	let peer_a = PeerId::random();
+	let peer_b = PeerId::random();
+
+	let _ = test_harness(State::default(), |mut virtual_overseer| async move {
+		let overseer = &mut virtual_overseer;
+		setup_peer_with_view(overseer, &peer_a, view![hash]).await;
+		setup_peer_with_view(overseer, &peer_b, view![hash]).await;
+
+		// new block `hash` with 2 candidates
+		let meta = BlockApprovalMeta {
+			hash,
+			parent_hash,
+			number: 1,
+			candidates: vec![Default::default(); 2],
+			slot: 1.into(),
+			session: 1,
+		};
+		overseer_send(overseer, ApprovalDistributionMessage::NewBlocks(vec![meta])).await;
+
+		let cert_a = fake_assignment_cert(hash, ValidatorIndex(0));
+		let cert_b = fake_assignment_cert(hash, ValidatorIndex(1));
+
+		let msg = protocol_v1::ApprovalDistributionMessage::Assignments(vec![(cert_a.clone(), 0)]);
+		send_message_from_peer(overseer, &peer_a, msg).await;
+		let msg = protocol_v1::ApprovalDistributionMessage::Assignments(vec![(cert_b.clone(), 1)]);
+		send_message_from_peer(overseer, &peer_b, msg).await;
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignments(
+				batch,
+				tx,
+			)) => {
+				assert_eq!(batch, vec![(cert_a, 0), (cert_b, 1)]);
+				tx.send(vec![
+					AssignmentCheckResult::Accepted,
+					AssignmentCheckResult::Bad(AssignmentCheckError::InvalidCert(
+						ValidatorIndex(1),
+						String::from("bad cert"),
+					)),
+				])
+				.unwrap();
+			}
+		);
+
+		expect_reputation_change(overseer, &peer_a, BENEFIT_VALID_MESSAGE_FIRST).await;
+
+		// the accepted assignment is circulated to the other peer
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::NetworkBridge(NetworkBridgeMessage::SendValidationMessage(
+				peers,
+				Versioned::V1(protocol_v1::ValidationProtocol::ApprovalDistribution(
+					protocol_v1::ApprovalDistributionMessage::Assignments(assignments)
+				))
+			)) => {
+				assert_eq!(peers, vec![peer_b.clone()]);
+				assert_eq!(assignments.len(), 1);
+			}
+		);
+
+		expect_reputation_change(overseer, &peer_b, COST_INVALID_MESSAGE).await;
+
+		assert!(overseer.recv().timeout(TIMEOUT).await.is_none(), "no message should be sent");
+		virtual_overseer
+	});
+}
+
 /// Imagine we send a message to peer A and peer B.
 /// Upon receiving them, they both will try to send the message each other.
 /// This test makes sure they will not punish each other for such duplicate messages.
@@ -1109,23 +1186,22 @@ fn race_condition_in_local_vs_remote_view_update() {
 		let msg = ApprovalDistributionMessage::NewBlocks(vec![meta]);
 		overseer_send(overseer, msg).await;
 
-		for i in 0..candidates_count {
-			// Previously, this has caused out-of-view assignments/approvals
-			//expect_reputation_change(overseer, peer, COST_UNEXPECTED_MESSAGE).await;
+		// Previously, this has caused out-of-view assignments/approvals
+		//expect_reputation_change(overseer, peer, COST_UNEXPECTED_MESSAGE).await;
 
-			assert_matches!(
-				overseer_recv(overseer).await,
-				AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
-					assignment,
-					claimed_candidate_index,
-					tx,
-				)) => {
-					assert_eq!(assignment, assignments[i].0);
-					assert_eq!(claimed_candidate_index, assignments[i].1);
-					tx.send(AssignmentCheckResult::Accepted).unwrap();
-				}
-			);
+		// All pending assignments are checked at once
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignments(
+				batch,
+				tx,
+			)) => {
+				assert_eq!(batch, assignments);
+				tx.send(vec![AssignmentCheckResult::Accepted; candidates_count]).unwrap();
+			}
+		);
 
+		for _ in 0..candidates_count {
 			// Since we have a valid statement pending, this should always occur
 			expect_reputation_change(overseer, peer, BENEFIT_VALID_MESSAGE_FIRST).await;
 		}
@@ -0,0 +1,203 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Archives serving full `AvailableData` outside of the live network.
+//!
+//! An archive is the last resort of a recovery, tried once neither backers nor validators could
+//! provide the data. This allows nodes to recover PoVs past the validator retention window.
+//! Archives are not trusted: data obtained from them is checked against the erasure root of
+//! the candidate like any other recovered data.
+//!
+//! Archives are addressed by candidate hash, encoded as `0x` prefixed hex, and serve the SCALE
+//! encoded `AvailableData`:
+//! - A local archive is a directory with one file per candidate.
+//! - An HTTP archive serves `GET <base>/<candidate hash>`, responding with `404 Not Found` for
+//!   unknown candidates.
+
+use std::{io, path::PathBuf, sync::Arc, time::Duration};
+
+use futures::{future::BoxFuture, FutureExt};
+use hyper::body::HttpBody;
+use parity_scale_codec::Decode;
+use thiserror::Error;
+
+use polkadot_node_primitives::AvailableData;
+use polkadot_node_subsystem_util::TimeoutExt;
+use polkadot_primitives::v2::{CandidateHash, MAX_POV_SIZE};
+
+/// The maximum size of a response of an HTTP archive.
+///
+/// This is the bound applied to fetching available data over the network as well, leaving room
+/// for the validation data next to the PoV.
+const MAX_RESPONSE_SIZE: usize = MAX_POV_SIZE as usize + 10_000;
+
+/// How long to wait for an HTTP archive to respond with the full data.
+const HTTP_FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Errors of fetching data from an archive.
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+	#[error("archive endpoint {0:?} is neither an HTTP URL nor an existing directory")]
+	InvalidEndpoint(String),
+
+	#[error(transparent)]
+	Io(#[from] io::Error),
+
+	#[error(transparent)]
+	InvalidUri(#[from] hyper::http::uri::InvalidUri),
+
+	#[error(transparent)]
+	Http(#[from] hyper::Error),
+
+	#[error("archive responded with unexpected status {0}")]
+	UnexpectedStatus(hyper::StatusCode),
+
+	#[error("archive response exceeds the maximum size")]
+	ResponseTooLarge,
+
+	#[error("archive did not respond in time")]
+	Timeout,
+
+	#[error(transparent)]
+	Decode(#[from] parity_scale_codec::Error),
+}
+
+/// A source of full `AvailableData`, which is not part of the live network.
+pub trait ArchiveSource: Send + Sync {
+	/// Fetch the `AvailableData` of a candidate, `None` if the archive doesn't have it.
+	fn fetch(
+		&self,
+		candidate_hash: CandidateHash,
+	) -> BoxFuture<'static, Result<Option<AvailableData>, ArchiveError>>;
+}
+
+/// Create an archive from the given endpoint, either an `http://` URL or a local directory.
+pub fn from_endpoint(endpoint: &str) -> Result<Arc<dyn ArchiveSource>, ArchiveError> {
+	if endpoint.starts_with("http://") {
+		return Ok(Arc::new(HttpArchive::new(endpoint)?))
+	}
+
+	let dir = PathBuf::from(endpoint);
+	if !dir.is_dir() {
+		return Err(ArchiveError::InvalidEndpoint(endpoint.to_owned()))
+	}
+
+	Ok(Arc::new(LocalArchive { dir }))
+}
+
+fn archive_key(candidate_hash: &CandidateHash) -> String {
+	format!("{:?}", candidate_hash.0)
+}
+
+/// An archive in a local directory.
+pub struct LocalArchive {
+	dir: PathBuf,
+}
+
+impl ArchiveSource for LocalArchive {
+	fn fetch(
+		&self,
+		candidate_hash: CandidateHash,
+	) -> BoxFuture<'static, Result<Option<AvailableData>, ArchiveError>> {
+		let path = self.dir.join(archive_key(&candidate_hash));
+
+		async move {
+			// Reading a whole PoV from disk is too slow to block the executor with.
+			let raw = tokio::task::spawn_blocking(move || std::fs::read(path))
+				.await
+				.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+			let raw = match raw {
+				Ok(raw) => raw,
+				Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+				Err(e) => return Err(e.into()),
+			};
+
+			Ok(Some(AvailableData::decode(&mut &raw[..])?))
+		}
+		.boxed()
+	}
+}
+
+/// An archive served over HTTP.
+pub struct HttpArchive {
+	base: String,
+	client: hyper::Client<hyper::client::HttpConnector>,
+}
+
+impl HttpArchive {
+	fn new(base: &str) -> Result<Self, ArchiveError> {
+		let base = base.trim_end_matches('/').to_owned();
+		// Fail early on malformed URLs.
+		let _: hyper::Uri = base.parse()?;
+
+		Ok(HttpArchive { base, client: hyper::Client::new() })
+	}
+}
+
+impl ArchiveSource for HttpArchive {
+	fn fetch(
+		&self,
+		candidate_hash: CandidateHash,
+	) -> BoxFuture<'static, Result<Option<AvailableData>, ArchiveError>> {
+		let uri = format!("{}/{}", self.base, archive_key(&candidate_hash)).parse::<hyper::Uri>();
+		let client = self.client.clone();
+
+		async move {
+			let raw = match fetch_bounded(client, uri?)
+				.timeout(HTTP_FETCH_TIMEOUT)
+				.await
+				.ok_or(ArchiveError::Timeout)??
+			{
+				Some(raw) => raw,
+				None => return Ok(None),
+			};
+
+			Ok(Some(AvailableData::decode(&mut &raw[..])?))
+		}
+		.boxed()
+	}
+}
+
+// Fetch the body of the given URI, `None` if it is not found. Fails as soon as the body is
+// known to exceed `MAX_RESPONSE_SIZE`.
+async fn fetch_bounded(
+	client: hyper::Client<hyper::client::HttpConnector>,
+	uri: hyper::Uri,
+) -> Result<Option<Vec<u8>>, ArchiveError> {
+	let response = client.get(uri).await?;
+
+	match response.status() {
+		hyper::StatusCode::OK => {},
+		hyper::StatusCode::NOT_FOUND => return Ok(None),
+		status => return Err(ArchiveError::UnexpectedStatus(status)),
+	}
+
+	let mut body = response.into_body();
+	if body.size_hint().lower() > MAX_RESPONSE_SIZE as u64 {
+		return Err(ArchiveError::ResponseTooLarge)
+	}
+
+	let mut raw = Vec::new();
+	while let Some(chunk) = body.data().await {
+		let chunk = chunk?;
+		if raw.len() + chunk.len() > MAX_RESPONSE_SIZE {
+			return Err(ArchiveError::ResponseTooLarge)
+		}
+		raw.extend_from_slice(&chunk);
+	}
+
+	Ok(Some(raw))
+}
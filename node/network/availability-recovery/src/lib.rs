@@ -21,6 +21,7 @@
 use std::{
 	collections::{HashMap, VecDeque},
 	pin::Pin,
+	sync::Arc,
 	time::Duration,
 };
 
@@ -36,7 +37,10 @@ use lru::LruCache;
 use rand::seq::SliceRandom;
 
 use fatality::Nested;
-use polkadot_erasure_coding::{branch_hash, branches, obtain_chunks_v1, recovery_threshold};
+use polkadot_erasure_coding::{
+	branch_hash, branches, obtain_chunks_v1, reconstruct_from_systematic_v1, recovery_threshold,
+	systematic_recovery_threshold,
+};
 #[cfg(not(test))]
 use polkadot_node_network_protocol::request_response::CHUNK_REQUEST_TIMEOUT;
 use polkadot_node_network_protocol::{
@@ -60,11 +64,14 @@ use polkadot_primitives::v2::{
 	Hash, HashT, SessionIndex, SessionInfo, ValidatorId, ValidatorIndex,
 };
 
+pub mod archive;
 mod error;
 mod futures_undead;
 mod metrics;
 use metrics::Metrics;
 
+use archive::ArchiveSource;
+
 use futures_undead::FuturesUndead;
 use sc_network::{OutboundFailure, RequestFailure};
 
@@ -100,6 +107,8 @@ pub struct AvailabilityRecoverySubsystem {
 	fast_path: bool,
 	/// Receiver for available data requests.
 	req_receiver: IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
+	/// Archive to fall back to if the data cannot be recovered from the network.
+	archive: Option<Arc<dyn ArchiveSource>>,
 	/// Metrics for this subsystem.
 	metrics: Metrics,
 }
@@ -110,6 +119,10 @@ struct RequestFromBackers {
 	shuffled_backers: Vec<ValidatorIndex>,
 }
 
+struct FetchFromArchive {
+	archive: Arc<dyn ArchiveSource>,
+}
+
 struct RequestChunksFromValidators {
 	/// How many request have been unsuccessful so far.
 	error_count: usize,
@@ -119,6 +132,8 @@ struct RequestChunksFromValidators {
 	total_received_responses: usize,
 	/// a random shuffling of the validators which indicates the order in which we connect to the validators and
 	/// request the chunk from them.
+	///
+	/// Validators holding systematic chunks are at the back, so they are requested first.
 	shuffling: VecDeque<ValidatorIndex>,
 	received_chunks: HashMap<ValidatorIndex, ErasureChunk>,
 	/// Pending chunk requests with soft timeout.
@@ -135,6 +150,9 @@ struct RecoveryParams {
 	/// The number of pieces needed.
 	threshold: usize,
 
+	/// The number of systematic chunks, which suffice to recover the data without decoding.
+	systematic_threshold: usize,
+
 	/// A hash of the relevant candidate.
 	candidate_hash: CandidateHash,
 
@@ -147,10 +165,12 @@ struct RecoveryParams {
 
 /// Source the availability data either by means
 /// of direct request response protocol to
-/// backers (a.k.a. fast-path), or recover from chunks.
+/// backers (a.k.a. fast-path), recover from chunks,
+/// or, as a last resort, fetch it from an archive.
 enum Source {
 	RequestFromBackers(RequestFromBackers),
 	RequestChunks(RequestChunksFromValidators),
+	FetchFromArchive(FetchFromArchive),
 }
 
 /// A stateful reconstruction of availability data in reference to
@@ -163,6 +183,9 @@ struct RecoveryTask<Sender> {
 
 	/// The source to obtain the availability data from.
 	source: Source,
+
+	/// The archive to fall back to once the network is exhausted.
+	archive: Option<Arc<dyn ArchiveSource>>,
 }
 
 impl RequestFromBackers {
@@ -242,10 +265,80 @@ impl RequestFromBackers {
 	}
 }
 
+impl FetchFromArchive {
+	// Run this phase to completion.
+	async fn run(&mut self, params: &RecoveryParams) -> Result<AvailableData, RecoveryError> {
+		gum::trace!(
+			target: LOG_TARGET,
+			candidate_hash = ?params.candidate_hash,
+			erasure_root = ?params.erasure_root,
+			"Fetching from archive",
+		);
+
+		match self.archive.fetch(params.candidate_hash).await {
+			Ok(Some(data)) => {
+				if reconstructed_data_matches_root(
+					params.validators.len(),
+					&params.erasure_root,
+					&data,
+				) {
+					gum::debug!(
+						target: LOG_TARGET,
+						candidate_hash = ?params.candidate_hash,
+						"Recovered data from archive",
+					);
+					params.metrics.on_archive_fetch_succeeded();
+
+					Ok(data)
+				} else {
+					// The archive is not trusted, so this doesn't make the data invalid.
+					gum::warn!(
+						target: LOG_TARGET,
+						candidate_hash = ?params.candidate_hash,
+						erasure_root = ?params.erasure_root,
+						"Archive served data not matching the erasure root",
+					);
+					params.metrics.on_archive_fetch_invalid();
+
+					Err(RecoveryError::Unavailable)
+				}
+			},
+			Ok(None) => {
+				gum::debug!(
+					target: LOG_TARGET,
+					candidate_hash = ?params.candidate_hash,
+					"Data not found in archive",
+				);
+				params.metrics.on_archive_fetch_not_found();
+
+				Err(RecoveryError::Unavailable)
+			},
+			Err(err) => {
+				gum::debug!(
+					target: LOG_TARGET,
+					candidate_hash = ?params.candidate_hash,
+					?err,
+					"Error fetching data from archive",
+				);
+				params.metrics.on_archive_fetch_error();
+
+				Err(RecoveryError::Unavailable)
+			},
+		}
+	}
+}
+
 impl RequestChunksFromValidators {
-	fn new(n_validators: u32) -> Self {
-		let mut shuffling: Vec<_> = (0..n_validators).map(ValidatorIndex).collect();
+	fn new(n_validators: u32, systematic_threshold: usize) -> Self {
+		let systematic_threshold = systematic_threshold as u32;
+
+		let mut systematic: Vec<_> = (0..systematic_threshold).map(ValidatorIndex).collect();
+		systematic.shuffle(&mut rand::thread_rng());
+
+		let mut shuffling: Vec<_> =
+			(systematic_threshold..n_validators).map(ValidatorIndex).collect();
 		shuffling.shuffle(&mut rand::thread_rng());
+		shuffling.extend(systematic);
 
 		RequestChunksFromValidators {
 			error_count: 0,
@@ -265,8 +358,17 @@ impl RequestChunksFromValidators {
 		)
 	}
 
+	/// Whether all systematic chunks have been received, so the data can be recovered without
+	/// decoding.
+	fn has_systematic_chunks(&self, params: &RecoveryParams) -> bool {
+		(0..params.systematic_threshold)
+			.all(|i| self.received_chunks.contains_key(&ValidatorIndex(i as _)))
+	}
+
 	fn can_conclude(&self, params: &RecoveryParams) -> bool {
-		self.received_chunks.len() >= params.threshold || self.is_unavailable(params)
+		self.received_chunks.len() >= params.threshold ||
+			self.has_systematic_chunks(params) ||
+			self.is_unavailable(params)
 	}
 
 	/// Desired number of parallel requests.
@@ -543,16 +645,32 @@ impl RequestChunksFromValidators {
 			self.launch_parallel_requests(params, sender).await;
 			self.wait_for_chunks(params).await;
 
-			// If received_chunks has more than threshold entries, attempt to recover the data.
+			// If received_chunks has all systematic chunks, concatenate them, or if it has more
+			// than threshold entries, attempt to decode the data.
 			// If that fails, or a re-encoding of it doesn't match the expected erasure root,
 			// return Err(RecoveryError::Invalid)
-			if self.received_chunks.len() >= params.threshold {
+			let has_systematic_chunks = self.has_systematic_chunks(params);
+			if has_systematic_chunks || self.received_chunks.len() >= params.threshold {
 				let recovery_duration = metrics.time_erasure_recovery();
 
-				return match polkadot_erasure_coding::reconstruct_v1(
-					params.validators.len(),
-					self.received_chunks.values().map(|c| (&c.chunk[..], c.index.0 as usize)),
-				) {
+				let recovered = if has_systematic_chunks {
+					metrics.on_systematic_reconstruction();
+
+					reconstruct_from_systematic_v1(
+						params.validators.len(),
+						(0..params.systematic_threshold)
+							.map(|i| &self.received_chunks[&ValidatorIndex(i as _)].chunk[..]),
+					)
+				} else {
+					metrics.on_regular_reconstruction();
+
+					polkadot_erasure_coding::reconstruct_v1(
+						params.validators.len(),
+						self.received_chunks.values().map(|c| (&c.chunk[..], c.index.0 as usize)),
+					)
+				};
+
+				return match recovered {
 					Ok(data) => {
 						if reconstructed_data_matches_root(
 							params.validators.len(),
@@ -687,11 +805,22 @@ where
 						Err(RecoveryError::Unavailable) =>
 							self.source = Source::RequestChunks(RequestChunksFromValidators::new(
 								self.params.validators.len() as _,
+								self.params.systematic_threshold,
 							)),
 					}
 				},
-				Source::RequestChunks(ref mut from_all) =>
-					break from_all.run(&self.params, &mut self.sender).await,
+				Source::RequestChunks(ref mut from_all) => {
+					match from_all.run(&self.params, &mut self.sender).await {
+						Err(RecoveryError::Unavailable) => match self.archive.take() {
+							Some(archive) =>
+								self.source = Source::FetchFromArchive(FetchFromArchive { archive }),
+							None => break Err(RecoveryError::Unavailable),
+						},
+						res => break res,
+					}
+				},
+				Source::FetchFromArchive(ref mut from_archive) =>
+					break from_archive.run(&self.params).await,
 			}
 		}
 	}
@@ -790,6 +919,9 @@ struct State {
 
 	/// An LRU cache of recently recovered data.
 	availability_lru: LruCache<CandidateHash, CachedRecovery>,
+
+	/// Archive to fall back to if the data cannot be recovered from the network.
+	archive: Option<Arc<dyn ArchiveSource>>,
 }
 
 impl Default for State {
@@ -798,6 +930,7 @@ impl Default for State {
 			ongoing_recoveries: FuturesUnordered::new(),
 			live_block: (0, Hash::default()),
 			availability_lru: LruCache::new(LRU_SIZE),
+			archive: None,
 		}
 	}
 }
@@ -848,6 +981,7 @@ async fn launch_recovery_task<Context>(
 		validator_authority_keys: session_info.discovery_keys.clone(),
 		validators: session_info.validators.clone(),
 		threshold: recovery_threshold(session_info.validators.len())?,
+		systematic_threshold: systematic_recovery_threshold(session_info.validators.len())?,
 		candidate_hash,
 		erasure_root: receipt.descriptor.erasure_root,
 		metrics: metrics.clone(),
@@ -857,10 +991,18 @@ async fn launch_recovery_task<Context>(
 		.and_then(|g| session_info.validator_groups.get(g.0 as usize))
 		.map(|group| Source::RequestFromBackers(RequestFromBackers::new(group.clone())))
 		.unwrap_or_else(|| {
-			Source::RequestChunks(RequestChunksFromValidators::new(params.validators.len() as _))
+			Source::RequestChunks(RequestChunksFromValidators::new(
+				params.validators.len() as _,
+				params.systematic_threshold,
+			))
 		});
 
-	let recovery_task = RecoveryTask { sender: ctx.sender().clone(), params, source: phase };
+	let recovery_task = RecoveryTask {
+		sender: ctx.sender().clone(),
+		params,
+		source: phase,
+		archive: state.archive.clone(),
+	};
 
 	let (remote, remote_handle) = recovery_task.run().remote_handle();
 
@@ -963,24 +1105,30 @@ async fn query_full_data<Context>(
 impl AvailabilityRecoverySubsystem {
 	/// Create a new instance of `AvailabilityRecoverySubsystem` which starts with a fast path to
 	/// request data from backers.
+	///
+	/// The `archive` is tried if the data cannot be recovered from the network.
 	pub fn with_fast_path(
 		req_receiver: IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
+		archive: Option<Arc<dyn ArchiveSource>>,
 		metrics: Metrics,
 	) -> Self {
-		Self { fast_path: true, req_receiver, metrics }
+		Self { fast_path: true, req_receiver, archive, metrics }
 	}
 
 	/// Create a new instance of `AvailabilityRecoverySubsystem` which requests only chunks
+	///
+	/// The `archive` is tried if the data cannot be recovered from the network.
 	pub fn with_chunks_only(
 		req_receiver: IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
+		archive: Option<Arc<dyn ArchiveSource>>,
 		metrics: Metrics,
 	) -> Self {
-		Self { fast_path: false, req_receiver, metrics }
+		Self { fast_path: false, req_receiver, archive, metrics }
 	}
 
 	async fn run<Context>(self, mut ctx: Context) -> SubsystemResult<()> {
-		let mut state = State::default();
-		let Self { fast_path, mut req_receiver, metrics } = self;
+		let Self { fast_path, mut req_receiver, archive, metrics } = self;
+		let mut state = State { archive, ..Default::default() };
 
 		loop {
 			let recv_req = req_receiver.recv(|| vec![COST_INVALID_REQUEST]).fuse();
@@ -53,11 +53,21 @@ struct MetricsInner {
 	/// Number of full recoveries that have been finished one way or the other.
 	full_recoveries_finished: CounterVec<U64>,
 
+	/// Number of attempts to reconstruct the data from chunks.
+	///
+	/// Split by method:
+	/// - `systematic` ... systematic chunks were concatenated without decoding.
+	/// - `regular` ... chunks were decoded.
+	reconstructions: CounterVec<U64>,
+
 	/// Number of full recoveries that have been started on this subsystem.
 	///
 	/// Note: Those are only recoveries which could not get served locally already - so in other
 	/// words: Only real recoveries.
 	full_recoveries_started: Counter<U64>,
+
+	/// Number of archive fetches that have been finished one way or the other.
+	archive_fetches_finished: CounterVec<U64>,
 }
 
 impl Metrics {
@@ -144,6 +154,48 @@ impl Metrics {
 		}
 	}
 
+	/// The data is reconstructed from the systematic chunks.
+	pub fn on_systematic_reconstruction(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.reconstructions.with_label_values(&["systematic"]).inc()
+		}
+	}
+
+	/// The data is reconstructed by decoding the chunks.
+	pub fn on_regular_reconstruction(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.reconstructions.with_label_values(&["regular"]).inc()
+		}
+	}
+
+	/// Data was fetched from the archive successfully.
+	pub fn on_archive_fetch_succeeded(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.archive_fetches_finished.with_label_values(&["success"]).inc()
+		}
+	}
+
+	/// The archive did not have the data.
+	pub fn on_archive_fetch_not_found(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.archive_fetches_finished.with_label_values(&["not_found"]).inc()
+		}
+	}
+
+	/// The archive served data not matching the erasure root.
+	pub fn on_archive_fetch_invalid(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.archive_fetches_finished.with_label_values(&["invalid"]).inc()
+		}
+	}
+
+	/// Fetching from the archive failed.
+	pub fn on_archive_fetch_error(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.archive_fetches_finished.with_label_values(&["error"]).inc()
+		}
+	}
+
 	/// A recover was started.
 	pub fn on_recovery_started(&self) {
 		if let Some(metrics) = &self.0 {
@@ -203,6 +255,16 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			reconstructions: prometheus::register(
+				CounterVec::new(
+					Opts::new(
+						"polkadot_parachain_availability_recovery_reconstructions",
+						"Total number of attempts to reconstruct the data from chunks.",
+					),
+					&["method"],
+				)?,
+				registry,
+			)?,
 			full_recoveries_started: prometheus::register(
 				Counter::new(
 					"polkadot_parachain_availability_recovery_recovieries_started",
@@ -210,6 +272,16 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			archive_fetches_finished: prometheus::register(
+				CounterVec::new(
+					Opts::new(
+						"polkadot_parachain_availability_recovery_archive_fetches_finished",
+						"Total number of fetches from the archive that finished.",
+					),
+					&["result"],
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
@@ -54,8 +54,11 @@ fn test_harness_fast_path<T: Future<Output = (VirtualOverseer, RequestResponseCo
 	let (context, virtual_overseer) = make_subsystem_context(pool.clone());
 
 	let (collation_req_receiver, req_cfg) = IncomingRequest::get_config_receiver();
-	let subsystem =
-		AvailabilityRecoverySubsystem::with_fast_path(collation_req_receiver, Metrics::new_dummy());
+	let subsystem = AvailabilityRecoverySubsystem::with_fast_path(
+		collation_req_receiver,
+		None,
+		Metrics::new_dummy(),
+	);
 	let subsystem = async {
 		subsystem.run(context).await.unwrap();
 	};
@@ -77,6 +80,15 @@ fn test_harness_fast_path<T: Future<Output = (VirtualOverseer, RequestResponseCo
 
 fn test_harness_chunks_only<T: Future<Output = (VirtualOverseer, RequestResponseConfig)>>(
 	test: impl FnOnce(VirtualOverseer, RequestResponseConfig) -> T,
+) {
+	test_harness_chunks_only_with_archive(None, test)
+}
+
+fn test_harness_chunks_only_with_archive<
+	T: Future<Output = (VirtualOverseer, RequestResponseConfig)>,
+>(
+	archive: Option<Arc<dyn ArchiveSource>>,
+	test: impl FnOnce(VirtualOverseer, RequestResponseConfig) -> T,
 ) {
 	let _ = env_logger::builder()
 		.is_test(true)
@@ -90,6 +102,7 @@ fn test_harness_chunks_only<T: Future<Output = (VirtualOverseer, RequestResponse
 	let (collation_req_receiver, req_cfg) = IncomingRequest::get_config_receiver();
 	let subsystem = AvailabilityRecoverySubsystem::with_chunks_only(
 		collation_req_receiver,
+		archive,
 		Metrics::new_dummy(),
 	);
 	let subsystem = subsystem.run(context);
@@ -153,6 +166,18 @@ async fn overseer_recv(
 
 use sp_keyring::Sr25519Keyring;
 
+struct TestArchive(Option<AvailableData>);
+
+impl ArchiveSource for TestArchive {
+	fn fetch(
+		&self,
+		_candidate_hash: CandidateHash,
+	) -> future::BoxFuture<'static, std::result::Result<Option<AvailableData>, archive::ArchiveError>>
+	{
+		future::ready(Ok(self.0.clone())).boxed()
+	}
+}
+
 #[derive(Debug)]
 enum Has {
 	No,
@@ -1034,6 +1059,88 @@ fn chunks_retry_until_all_nodes_respond() {
 	});
 }
 
+fn archive_is_tried_if_chunks_are_unavailable(archive_has_valid_data: bool) {
+	let test_state = TestState::default();
+
+	let mut archived_data = test_state.available_data.clone();
+	if !archive_has_valid_data {
+		archived_data.pov = Arc::new(PoV { block_data: BlockData(vec![1; 64]) });
+	}
+
+	let archive = Arc::new(TestArchive(Some(archived_data)));
+	test_harness_chunks_only_with_archive(
+		Some(archive),
+		|mut virtual_overseer, req_cfg| async move {
+			overseer_signal(
+				&mut virtual_overseer,
+				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(ActivatedLeaf {
+					hash: test_state.current.clone(),
+					number: 1,
+					status: LeafStatus::Fresh,
+					span: Arc::new(jaeger::Span::Disabled),
+				})),
+			)
+			.await;
+
+			let (tx, rx) = oneshot::channel();
+
+			overseer_send(
+				&mut virtual_overseer,
+				AvailabilityRecoveryMessage::RecoverAvailableData(
+					test_state.candidate.clone(),
+					test_state.session_index,
+					None,
+					tx,
+				),
+			)
+			.await;
+
+			test_state.test_runtime_api(&mut virtual_overseer).await;
+
+			let candidate_hash = test_state.candidate.hash();
+
+			test_state.respond_to_available_data_query(&mut virtual_overseer, false).await;
+			test_state.respond_to_query_all_request(&mut virtual_overseer, |_| false).await;
+
+			test_state
+				.test_chunk_requests(
+					candidate_hash,
+					&mut virtual_overseer,
+					test_state.validators.len() - test_state.threshold(),
+					|_| Has::timeout(),
+				)
+				.await;
+
+			test_state
+				.test_chunk_requests(
+					candidate_hash,
+					&mut virtual_overseer,
+					test_state.impossibility_threshold(),
+					|_| Has::No,
+				)
+				.await;
+
+			// The archive is used, but only trusted if the data matches the erasure root.
+			if archive_has_valid_data {
+				assert_eq!(rx.await.unwrap().unwrap(), test_state.available_data);
+			} else {
+				assert_eq!(rx.await.unwrap().unwrap_err(), RecoveryError::Unavailable);
+			}
+			(virtual_overseer, req_cfg)
+		},
+	);
+}
+
+#[test]
+fn recovers_from_archive_if_chunks_are_unavailable() {
+	archive_is_tried_if_chunks_are_unavailable(true);
+}
+
+#[test]
+fn invalid_archive_data_is_not_used() {
+	archive_is_tried_if_chunks_are_unavailable(false);
+}
+
 #[test]
 fn not_returning_requests_wont_stall_retrieval() {
 	let test_state = TestState::default();
@@ -1279,11 +1386,84 @@ fn does_not_query_local_validator() {
 	});
 }
 
+fn recovers_from_chunks_with_systematic_chunk(systematic_chunk_available: bool) {
+	let test_state = TestState::default();
+	let systematic_threshold = systematic_recovery_threshold(test_state.validators.len()).unwrap();
+
+	test_harness_chunks_only(|mut virtual_overseer, req_cfg| async move {
+		overseer_signal(
+			&mut virtual_overseer,
+			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(ActivatedLeaf {
+				hash: test_state.current.clone(),
+				number: 1,
+				status: LeafStatus::Fresh,
+				span: Arc::new(jaeger::Span::Disabled),
+			})),
+		)
+		.await;
+
+		let (tx, rx) = oneshot::channel();
+
+		overseer_send(
+			&mut virtual_overseer,
+			AvailabilityRecoveryMessage::RecoverAvailableData(
+				test_state.candidate.clone(),
+				test_state.session_index,
+				None,
+				tx,
+			),
+		)
+		.await;
+
+		test_state.test_runtime_api(&mut virtual_overseer).await;
+		test_state.respond_to_available_data_query(&mut virtual_overseer, false).await;
+		test_state.respond_to_query_all_request(&mut virtual_overseer, |_| false).await;
+
+		let candidate_hash = test_state.candidate.hash();
+
+		// Systematic chunks are requested first.
+		test_state
+			.test_chunk_requests(candidate_hash, &mut virtual_overseer, systematic_threshold, |i| {
+				assert!(i < systematic_threshold, "requested non-systematic chunk first");
+				if i == 0 && !systematic_chunk_available {
+					Has::No
+				} else {
+					Has::Yes
+				}
+			})
+			.await;
+
+		if !systematic_chunk_available {
+			// Fall back to decoding with another chunk.
+			test_state
+				.test_chunk_requests(candidate_hash, &mut virtual_overseer, 1, |i| {
+					assert!(i >= systematic_threshold, "requested systematic chunk again");
+					Has::Yes
+				})
+				.await;
+		}
+
+		assert_eq!(rx.await.unwrap().unwrap(), test_state.available_data);
+		(virtual_overseer, req_cfg)
+	});
+}
+
+#[test]
+fn recovers_from_systematic_chunks_first() {
+	recovers_from_chunks_with_systematic_chunk(true);
+}
+
+#[test]
+fn decodes_chunks_if_systematic_chunk_is_missing() {
+	recovers_from_chunks_with_systematic_chunk(false);
+}
+
 #[test]
 fn parallel_request_calculation_works_as_expected() {
 	let num_validators = 100;
 	let threshold = recovery_threshold(num_validators).unwrap();
-	let mut phase = RequestChunksFromValidators::new(100);
+	let systematic_threshold = systematic_recovery_threshold(num_validators).unwrap();
+	let mut phase = RequestChunksFromValidators::new(100, systematic_threshold);
 	assert_eq!(phase.get_desired_request_count(threshold), threshold);
 	phase.error_count = 1;
 	phase.total_received_responses = 1;
@@ -29,7 +29,9 @@ use polkadot_node_network_protocol::{
 	grid_topology::{
 		RandomRouting, RequiredRouting, SessionBoundGridTopologyStorage, SessionGridTopology,
 	},
-	v1 as protocol_v1, OurView, PeerId, UnifiedReputationChange as Rep, Versioned, View,
+	peer_set::ValidationVersion,
+	v1 as protocol_v1, v2 as protocol_v2, OurView, PeerId, ProtocolVersion,
+	UnifiedReputationChange as Rep, Versioned, View,
 };
 use polkadot_node_subsystem::{
 	jaeger, messages::*, overseer, ActiveLeavesUpdate, FromOrchestra, OverseerSignal, PerLeafSpan,
@@ -50,6 +52,7 @@ mod tests;
 
 const COST_SIGNATURE_INVALID: Rep = Rep::CostMajor("Bitfield signature invalid");
 const COST_VALIDATOR_INDEX_INVALID: Rep = Rep::CostMajor("Bitfield validator index invalid");
+const COST_BATCH_TOO_LARGE: Rep = Rep::CostMajor("Bitfield batch larger than validator set");
 const COST_MISSING_PEER_SESSION_KEY: Rep = Rep::CostMinor("Missing peer session key");
 const COST_NOT_IN_VIEW: Rep = Rep::CostMinor("Not interested in that parent hash");
 const COST_PEER_DUPLICATE_MESSAGE: Rep =
@@ -58,6 +61,9 @@ const BENEFIT_VALID_MESSAGE_FIRST: Rep =
 	Rep::BenefitMinorFirst("Valid message with new information");
 const BENEFIT_VALID_MESSAGE: Rep = Rep::BenefitMinor("Valid message");
 
+/// The first validation protocol version supporting batched bitfield messages.
+const BATCHED_BITFIELDS_VERSION: ProtocolVersion = ValidationVersion::V2 as ProtocolVersion;
+
 /// Checked signed availability bitfield that is distributed
 /// to other peers.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -81,6 +87,23 @@ impl BitfieldGossipMessage {
 	}
 }
 
+/// Batch bitfield messages of the same relay parent into a single v2 message.
+fn into_batched_validation_protocol(
+	relay_parent: Hash,
+	messages: Vec<BitfieldGossipMessage>,
+) -> net_protocol::VersionedValidationProtocol {
+	let bitfields = messages.into_iter().map(|m| m.signed_availability.into()).collect();
+	let message: net_protocol::BitfieldDistributionMessage =
+		Versioned::V2(protocol_v2::BitfieldDistributionMessage::Bitfields(relay_parent, bitfields));
+
+	message.into()
+}
+
+/// Whether a peer connected on the given protocol version understands batched bitfields.
+fn supports_batches(version: Option<&ProtocolVersion>) -> bool {
+	version.map_or(false, |v| *v >= BATCHED_BITFIELDS_VERSION)
+}
+
 /// Data used to track information of peers and relay parents the
 /// overseer ordered us to work on.
 #[derive(Default, Debug)]
@@ -89,6 +112,9 @@ struct ProtocolState {
 	/// to determine what is relevant to them.
 	peer_views: HashMap<PeerId, View>,
 
+	/// The validation protocol version each peer is connected on.
+	peer_versions: HashMap<PeerId, ProtocolVersion>,
+
 	/// The current and previous gossip topologies
 	topologies: SessionBoundGridTopologyStorage,
 
@@ -330,14 +356,14 @@ async fn handle_bitfield_distribution<Context>(
 	let topology = state.topologies.get_topology_or_fallback(session_idx);
 	let required_routing = topology.required_routing_by_index(validator_index, true);
 
-	relay_message(
+	relay_messages(
 		ctx,
 		job_data,
 		topology,
 		&mut state.peer_views,
-		validator,
-		msg,
-		required_routing,
+		&state.peer_versions,
+		metrics,
+		vec![(validator, msg, required_routing)],
 		rng,
 	)
 	.await;
@@ -345,111 +371,173 @@ async fn handle_bitfield_distribution<Context>(
 	metrics.on_own_bitfield_sent();
 }
 
-/// Distribute a given valid and signature checked bitfield message.
+/// Distribute the given valid and signature checked bitfield messages, all of which must be
+/// for the relay parent of `job_data`.
 ///
 /// Can be originated by another subsystem or received via network from another peer.
+/// Peers supporting it receive all messages relayed to them as a single batch.
 #[overseer::contextbounds(BitfieldDistribution, prefix=self::overseer)]
-async fn relay_message<Context>(
+async fn relay_messages<Context>(
 	ctx: &mut Context,
 	job_data: &mut PerRelayParentData,
 	topology: &SessionGridTopology,
 	peer_views: &mut HashMap<PeerId, View>,
-	validator: ValidatorId,
-	message: BitfieldGossipMessage,
-	required_routing: RequiredRouting,
+	peer_versions: &HashMap<PeerId, ProtocolVersion>,
+	metrics: &Metrics,
+	messages: Vec<(ValidatorId, BitfieldGossipMessage, RequiredRouting)>,
 	rng: &mut (impl CryptoRng + Rng),
 ) {
-	let relay_parent = message.relay_parent;
 	let span = job_data.span.child("relay-msg");
+	let total_peers = peer_views.len();
 
-	let _span = span.child("provisionable");
-	// notify the overseer about a new and valid signed bitfield
-	ctx.send_message(ProvisionerMessage::ProvisionableData(
-		relay_parent,
-		ProvisionableData::Bitfield(relay_parent, message.signed_availability.clone()),
-	))
-	.await;
+	// The peers to send each of the messages to on its own.
+	let mut sends = Vec::with_capacity(messages.len());
+	// The messages to send to each peer supporting batches, by index into `sends`.
+	let mut batches: HashMap<PeerId, Vec<usize>> = HashMap::new();
 
-	drop(_span);
-	let total_peers = peer_views.len();
-	let mut random_routing: RandomRouting = Default::default();
-
-	let _span = span.child("interested-peers");
-	// pass on the bitfield distribution to all interested peers
-	let interested_peers = peer_views
-		.iter()
-		.filter_map(|(peer, view)| {
-			// check interest in the peer in this message's relay parent
-			if view.contains(&message.relay_parent) {
-				let message_needed =
-					job_data.message_from_validator_needed_by_peer(&peer, &validator);
-				if message_needed {
-					let in_topology = topology.route_to_peer(required_routing, &peer);
-					let need_routing = in_topology || {
-						let route_random = random_routing.sample(total_peers, rng);
-						if route_random {
-							random_routing.inc_sent();
-						}
+	for (validator, message, required_routing) in messages {
+		let relay_parent = message.relay_parent;
 
-						route_random
-					};
+		let _span = span.child("provisionable");
+		// notify the overseer about a new and valid signed bitfield
+		ctx.send_message(ProvisionerMessage::ProvisionableData(
+			relay_parent,
+			ProvisionableData::Bitfield(relay_parent, message.signed_availability.clone()),
+		))
+		.await;
 
-					if need_routing {
-						Some(peer.clone())
+		drop(_span);
+		let mut random_routing: RandomRouting = Default::default();
+
+		let _span = span.child("interested-peers");
+		// pass on the bitfield distribution to all interested peers
+		let interested_peers = peer_views
+			.iter()
+			.filter_map(|(peer, view)| {
+				// check interest in the peer in this message's relay parent
+				if view.contains(&message.relay_parent) {
+					let message_needed =
+						job_data.message_from_validator_needed_by_peer(&peer, &validator);
+					if message_needed {
+						let in_topology = topology.route_to_peer(required_routing, &peer);
+						let need_routing = in_topology || {
+							let route_random = random_routing.sample(total_peers, rng);
+							if route_random {
+								random_routing.inc_sent();
+							}
+
+							route_random
+						};
+
+						if need_routing {
+							Some(peer.clone())
+						} else {
+							None
+						}
 					} else {
 						None
 					}
 				} else {
 					None
 				}
-			} else {
-				None
-			}
-		})
-		.collect::<Vec<PeerId>>();
+			})
+			.collect::<Vec<PeerId>>();
+
+		interested_peers.iter().for_each(|peer| {
+			// track the message as sent for this peer
+			job_data
+				.message_sent_to_peer
+				.entry(peer.clone())
+				.or_default()
+				.insert(validator.clone());
+		});
+
+		drop(_span);
+
+		if interested_peers.is_empty() {
+			gum::trace!(
+				target: LOG_TARGET,
+				?relay_parent,
+				"no peers are interested in gossip for relay parent",
+			);
+		}
 
-	interested_peers.iter().for_each(|peer| {
-		// track the message as sent for this peer
-		job_data
-			.message_sent_to_peer
-			.entry(peer.clone())
-			.or_default()
-			.insert(validator.clone());
-	});
+		let (batch_peers, single_peers): (Vec<_>, Vec<_>) = interested_peers
+			.into_iter()
+			.partition(|peer| supports_batches(peer_versions.get(peer)));
 
-	drop(_span);
+		for peer in batch_peers {
+			batches.entry(peer).or_default().push(sends.len());
+		}
+		sends.push((single_peers, message));
+	}
 
-	if interested_peers.is_empty() {
-		gum::trace!(
-			target: LOG_TARGET,
-			?relay_parent,
-			"no peers are interested in gossip for relay parent",
-		);
-	} else {
-		let _span = span.child("gossip");
+	let _span = span.child("gossip");
+
+	// Peers getting only a single message receive it along with all others.
+	let mut batched = HashMap::new();
+	for (peer, indices) in batches {
+		if let [index] = indices[..] {
+			sends[index].0.push(peer);
+		} else {
+			batched.insert(peer, indices);
+		}
+	}
+
+	for (peer, indices) in batched {
+		let messages = indices.into_iter().map(|i| sends[i].1.clone()).collect::<Vec<_>>();
+		let relay_parent = messages[0].relay_parent;
+
+		metrics.on_batch_sent(messages.len());
 		ctx.send_message(NetworkBridgeMessage::SendValidationMessage(
-			interested_peers,
-			message.into_validation_protocol(),
+			vec![peer],
+			into_batched_validation_protocol(relay_parent, messages),
 		))
 		.await;
 	}
+
+	for (peers, message) in sends {
+		if !peers.is_empty() {
+			ctx.send_message(NetworkBridgeMessage::SendValidationMessage(
+				peers,
+				message.into_validation_protocol(),
+			))
+			.await;
+		}
+	}
 }
 
 /// Handle an incoming message from a peer.
+///
+/// All bitfields of a batch are checked individually.
 #[overseer::contextbounds(BitfieldDistribution, prefix=self::overseer)]
 async fn process_incoming_peer_message<Context>(
 	ctx: &mut Context,
 	state: &mut ProtocolState,
 	metrics: &Metrics,
 	origin: PeerId,
-	message: protocol_v1::BitfieldDistributionMessage,
+	message: net_protocol::BitfieldDistributionMessage,
 	rng: &mut (impl CryptoRng + Rng),
 ) {
-	let protocol_v1::BitfieldDistributionMessage::Bitfield(relay_parent, bitfield) = message;
+	let (relay_parent, bitfields) = match message {
+		Versioned::V1(protocol_v1::BitfieldDistributionMessage::Bitfield(
+			relay_parent,
+			bitfield,
+		)) |
+		Versioned::V2(protocol_v2::BitfieldDistributionMessage::Bitfield(
+			relay_parent,
+			bitfield,
+		)) => (relay_parent, vec![bitfield]),
+		Versioned::V2(protocol_v2::BitfieldDistributionMessage::Bitfields(
+			relay_parent,
+			bitfields,
+		)) => (relay_parent, bitfields),
+	};
 	gum::trace!(
 		target: LOG_TARGET,
 		peer = %origin,
 		?relay_parent,
+		num_bitfields = bitfields.len(),
 		"received bitfield gossip from peer"
 	);
 	// we don't care about this, not part of our view.
@@ -467,16 +555,6 @@ async fn process_incoming_peer_message<Context>(
 		return
 	};
 
-	let validator_index = bitfield.unchecked_validator_index();
-
-	let mut _span = job_data
-		.span
-		.child("msg-received")
-		.with_peer_id(&origin)
-		.with_relay_parent(relay_parent)
-		.with_claimed_validator_index(validator_index)
-		.with_stage(jaeger::Stage::BitfieldDistribution);
-
 	let validator_set = &job_data.validator_set;
 	if validator_set.is_empty() {
 		gum::trace!(target: LOG_TARGET, ?relay_parent, ?origin, "Validator set is empty",);
@@ -484,74 +562,110 @@ async fn process_incoming_peer_message<Context>(
 		return
 	}
 
-	// Use the (untrusted) validator index provided by the signed payload
-	// and see if that one actually signed the availability bitset.
-	let signing_context = job_data.signing_context.clone();
-	let validator = if let Some(validator) = validator_set.get(validator_index.0 as usize) {
-		validator.clone()
-	} else {
-		modify_reputation(ctx.sender(), relay_parent, origin, COST_VALIDATOR_INDEX_INVALID).await;
-		return
-	};
-
-	// Check if the peer already sent us a message for the validator denoted in the message earlier.
-	// Must be done after validator index verification, in order to avoid storing an unbounded
-	// number of set entries.
-	let received_set = job_data.message_received_from_peer.entry(origin.clone()).or_default();
-
-	if !received_set.contains(&validator) {
-		received_set.insert(validator.clone());
-	} else {
-		gum::trace!(target: LOG_TARGET, ?validator_index, ?origin, "Duplicate message");
-		modify_reputation(ctx.sender(), relay_parent, origin, COST_PEER_DUPLICATE_MESSAGE).await;
-		return
-	};
-
-	let one_per_validator = &mut (job_data.one_per_validator);
-
-	// relay a message received from a validator at most _once_
-	if let Some(old_message) = one_per_validator.get(&validator) {
+	// A batch can't contain more than one bitfield per validator.
+	if bitfields.len() > validator_set.len() {
 		gum::trace!(
 			target: LOG_TARGET,
-			?validator_index,
-			"already received a message for validator",
+			?relay_parent,
+			?origin,
+			num_bitfields = bitfields.len(),
+			"Bitfield batch larger than validator set",
 		);
-		if old_message.signed_availability.as_unchecked() == &bitfield {
-			modify_reputation(ctx.sender(), relay_parent, origin, BENEFIT_VALID_MESSAGE).await;
-		}
+		modify_reputation(ctx.sender(), relay_parent, origin, COST_BATCH_TOO_LARGE).await;
 		return
 	}
-	let signed_availability = match bitfield.try_into_checked(&signing_context, &validator) {
-		Err(_) => {
-			modify_reputation(ctx.sender(), relay_parent, origin, COST_SIGNATURE_INVALID).await;
-			return
-		},
-		Ok(bitfield) => bitfield,
-	};
 
-	let message = BitfieldGossipMessage { relay_parent, signed_availability };
+	let signing_context = job_data.signing_context.clone();
+	let topology = state.topologies.get_topology_or_fallback(signing_context.session_index);
+	let mut accepted = Vec::with_capacity(bitfields.len());
+
+	for bitfield in bitfields {
+		let validator_index = bitfield.unchecked_validator_index();
+
+		let mut _span = job_data
+			.span
+			.child("msg-received")
+			.with_peer_id(&origin)
+			.with_relay_parent(relay_parent)
+			.with_claimed_validator_index(validator_index)
+			.with_stage(jaeger::Stage::BitfieldDistribution);
+
+		// Use the (untrusted) validator index provided by the signed payload
+		// and see if that one actually signed the availability bitset.
+		let validator =
+			if let Some(validator) = job_data.validator_set.get(validator_index.0 as usize) {
+				validator.clone()
+			} else {
+				modify_reputation(ctx.sender(), relay_parent, origin, COST_VALIDATOR_INDEX_INVALID)
+					.await;
+				continue
+			};
 
-	let topology = state
-		.topologies
-		.get_topology_or_fallback(job_data.signing_context.session_index);
-	let required_routing = topology.required_routing_by_index(validator_index, false);
+		// Check if the peer already sent us a message for the validator denoted in the message earlier.
+		// Must be done after validator index verification, in order to avoid storing an unbounded
+		// number of set entries.
+		let received_set = job_data.message_received_from_peer.entry(origin.clone()).or_default();
+
+		if !received_set.contains(&validator) {
+			received_set.insert(validator.clone());
+		} else {
+			gum::trace!(target: LOG_TARGET, ?validator_index, ?origin, "Duplicate message");
+			modify_reputation(ctx.sender(), relay_parent, origin, COST_PEER_DUPLICATE_MESSAGE)
+				.await;
+			continue
+		};
+
+		let one_per_validator = &mut (job_data.one_per_validator);
+
+		// relay a message received from a validator at most _once_
+		if let Some(old_message) = one_per_validator.get(&validator) {
+			gum::trace!(
+				target: LOG_TARGET,
+				?validator_index,
+				"already received a message for validator",
+			);
+			if old_message.signed_availability.as_unchecked() == &bitfield {
+				modify_reputation(ctx.sender(), relay_parent, origin, BENEFIT_VALID_MESSAGE).await;
+			}
+			continue
+		}
+		let signed_availability = match bitfield.try_into_checked(&signing_context, &validator) {
+			Err(_) => {
+				modify_reputation(ctx.sender(), relay_parent, origin, COST_SIGNATURE_INVALID).await;
+				continue
+			},
+			Ok(bitfield) => bitfield,
+		};
+
+		let message = BitfieldGossipMessage { relay_parent, signed_availability };
+		let required_routing = topology.required_routing_by_index(validator_index, false);
+
+		metrics.on_bitfield_received();
+		one_per_validator.insert(validator.clone(), message.clone());
+
+		accepted.push((validator, message, required_routing));
+	}
 
-	metrics.on_bitfield_received();
-	one_per_validator.insert(validator.clone(), message.clone());
+	if accepted.is_empty() {
+		return
+	}
 
-	relay_message(
+	let num_accepted = accepted.len();
+	relay_messages(
 		ctx,
 		job_data,
 		topology,
 		&mut state.peer_views,
-		validator,
-		message,
-		required_routing,
+		&state.peer_versions,
+		metrics,
+		accepted,
 		rng,
 	)
 	.await;
 
-	modify_reputation(ctx.sender(), relay_parent, origin, BENEFIT_VALID_MESSAGE_FIRST).await
+	for _ in 0..num_accepted {
+		modify_reputation(ctx.sender(), relay_parent, origin, BENEFIT_VALID_MESSAGE_FIRST).await
+	}
 }
 
 /// Deal with network bridge updates and track what needs to be tracked
@@ -567,15 +681,17 @@ async fn handle_network_msg<Context>(
 	let _timer = metrics.time_handle_network_msg();
 
 	match bridge_message {
-		NetworkBridgeEvent::PeerConnected(peer, role, _, _) => {
-			gum::trace!(target: LOG_TARGET, ?peer, ?role, "Peer connected");
+		NetworkBridgeEvent::PeerConnected(peer, role, version, _) => {
+			gum::trace!(target: LOG_TARGET, ?peer, ?role, version, "Peer connected");
 			// insert if none already present
 			state.peer_views.entry(peer).or_default();
+			state.peer_versions.insert(peer, version);
 		},
 		NetworkBridgeEvent::PeerDisconnected(peer) => {
 			gum::trace!(target: LOG_TARGET, ?peer, "Peer disconnected");
 			// get rid of superfluous data
 			state.peer_views.remove(&peer);
+			state.peer_versions.remove(&peer);
 		},
 		NetworkBridgeEvent::NewGossipTopology(gossip_topology) => {
 			let session_index = gossip_topology.session;
@@ -594,19 +710,19 @@ async fn handle_network_msg<Context>(
 				// it might have had an existing view, we use to initialize
 				// and minimize the delta on `PeerViewChange` to be sent
 				if let Some(old_view) = state.peer_views.remove(&new_peer) {
-					handle_peer_view_change(ctx, state, new_peer, old_view, rng).await;
+					handle_peer_view_change(ctx, state, metrics, new_peer, old_view, rng).await;
 				}
 			}
 		},
 		NetworkBridgeEvent::PeerViewChange(peerid, new_view) => {
 			gum::trace!(target: LOG_TARGET, ?peerid, ?new_view, "Peer view change");
-			handle_peer_view_change(ctx, state, peerid, new_view, rng).await;
+			handle_peer_view_change(ctx, state, metrics, peerid, new_view, rng).await;
 		},
 		NetworkBridgeEvent::OurViewChange(new_view) => {
 			gum::trace!(target: LOG_TARGET, ?new_view, "Our view change");
 			handle_our_view_change(state, new_view);
 		},
-		NetworkBridgeEvent::PeerMessage(remote, Versioned::V1(message)) =>
+		NetworkBridgeEvent::PeerMessage(remote, message) =>
 			process_incoming_peer_message(ctx, state, metrics, remote, message, rng).await,
 	}
 }
@@ -639,6 +755,7 @@ fn handle_our_view_change(state: &mut ProtocolState, view: OurView) {
 async fn handle_peer_view_change<Context>(
 	ctx: &mut Context,
 	state: &mut ProtocolState,
+	metrics: &Metrics,
 	origin: PeerId,
 	view: View,
 	rng: &mut (impl CryptoRng + Rng),
@@ -687,46 +804,68 @@ async fn handle_peer_view_change<Context>(
 		.flatten()
 		.collect();
 
-	for (validator, message) in delta_set.into_iter() {
-		send_tracked_gossip_message(ctx, state, origin.clone(), validator, message).await;
-	}
+	send_tracked_gossip_messages(ctx, state, metrics, origin, delta_set).await;
 }
 
-/// Send a gossip message and track it in the per relay parent data.
+/// Send gossip messages to a peer and track them in the per relay parent data.
+///
+/// If the peer supports it, messages of the same relay parent are sent as a single batch.
 #[overseer::contextbounds(BitfieldDistribution, prefix=self::overseer)]
-async fn send_tracked_gossip_message<Context>(
+async fn send_tracked_gossip_messages<Context>(
 	ctx: &mut Context,
 	state: &mut ProtocolState,
+	metrics: &Metrics,
 	dest: PeerId,
-	validator: ValidatorId,
-	message: BitfieldGossipMessage,
+	messages: Vec<(ValidatorId, BitfieldGossipMessage)>,
 ) {
-	let job_data = if let Some(job_data) = state.per_relay_parent.get_mut(&message.relay_parent) {
+	let batch = supports_batches(state.peer_versions.get(&dest));
+	let mut batches: HashMap<Hash, Vec<BitfieldGossipMessage>> = HashMap::new();
+
+	for (validator, message) in messages {
+		let job_data = if let Some(job_data) = state.per_relay_parent.get_mut(&message.relay_parent)
+		{
+			job_data
+		} else {
+			continue
+		};
+
+		let _span = job_data.span.child("gossip");
+		gum::trace!(
+			target: LOG_TARGET,
+			?dest,
+			?validator,
+			relay_parent = ?message.relay_parent,
+			"Sending gossip message"
+		);
+
 		job_data
-	} else {
-		return
-	};
+			.message_sent_to_peer
+			.entry(dest.clone())
+			.or_default()
+			.insert(validator.clone());
 
-	let _span = job_data.span.child("gossip");
-	gum::trace!(
-		target: LOG_TARGET,
-		?dest,
-		?validator,
-		relay_parent = ?message.relay_parent,
-		"Sending gossip message"
-	);
+		if batch {
+			batches.entry(message.relay_parent).or_default().push(message);
+		} else {
+			ctx.send_message(NetworkBridgeMessage::SendValidationMessage(
+				vec![dest],
+				message.into_validation_protocol(),
+			))
+			.await;
+		}
+	}
 
-	job_data
-		.message_sent_to_peer
-		.entry(dest.clone())
-		.or_default()
-		.insert(validator.clone());
+	for (relay_parent, mut messages) in batches {
+		let message = if messages.len() == 1 {
+			messages.remove(0).into_validation_protocol()
+		} else {
+			metrics.on_batch_sent(messages.len());
+			into_batched_validation_protocol(relay_parent, messages)
+		};
 
-	ctx.send_message(NetworkBridgeMessage::SendValidationMessage(
-		vec![dest],
-		message.into_validation_protocol(),
-	))
-	.await;
+		ctx.send_message(NetworkBridgeMessage::SendValidationMessage(vec![dest], message))
+			.await;
+	}
 }
 
 #[overseer::subsystem(BitfieldDistribution, error=SubsystemError, prefix=self::overseer)]
@@ -20,6 +20,8 @@ use polkadot_node_subsystem_util::metrics::{prometheus, Metrics as MetricsTrait}
 struct MetricsInner {
 	sent_own_availability_bitfields: prometheus::Counter<prometheus::U64>,
 	received_availability_bitfields: prometheus::Counter<prometheus::U64>,
+	sent_bitfield_batches: prometheus::Counter<prometheus::U64>,
+	sent_batched_bitfields: prometheus::Counter<prometheus::U64>,
 	active_leaves_update: prometheus::Histogram,
 	handle_bitfield_distribution: prometheus::Histogram,
 	handle_network_msg: prometheus::Histogram,
@@ -42,6 +44,13 @@ impl Metrics {
 		}
 	}
 
+	pub(crate) fn on_batch_sent(&self, bitfields: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.sent_bitfield_batches.inc();
+			metrics.sent_batched_bitfields.inc_by(bitfields as u64);
+		}
+	}
+
 	/// Provide a timer for `active_leaves_update` which observes on drop.
 	pub(crate) fn time_active_leaves_update(
 		&self,
@@ -81,6 +90,20 @@ impl MetricsTrait for Metrics {
 				)?,
 				registry,
 			)?,
+			sent_bitfield_batches: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_bitfield_distribution_sent_batches_total",
+					"Number of batched bitfield messages sent to v2 peers.",
+				)?,
+				registry,
+			)?,
+			sent_batched_bitfields: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_bitfield_distribution_sent_batched_bitfields_total",
+					"Number of availability bitfields sent as part of a batch.",
+				)?,
+				registry,
+			)?,
 			active_leaves_update: prometheus::register(
 				prometheus::Histogram::with_opts(prometheus::HistogramOpts::new(
 					"polkadot_parachain_bitfield_distribution_active_leaves_update",
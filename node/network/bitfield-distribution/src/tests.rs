@@ -79,6 +79,7 @@ fn prewarmed_state(
 				},
 		},
 		peer_views: peers.iter().cloned().map(|peer| (peer, view!(relay_parent))).collect(),
+		peer_versions: HashMap::new(),
 		topologies,
 		view: our_view!(relay_parent),
 	}
@@ -459,14 +460,14 @@ fn do_not_relay_message_twice() {
 			peers_x: HashSet::from_iter(vec![peer_a.clone(), peer_b.clone()].into_iter()),
 			..Default::default()
 		};
-		relay_message(
+		relay_messages(
 			&mut ctx,
 			state.per_relay_parent.get_mut(&hash).unwrap(),
 			&gossip_peers,
 			&mut state.peer_views,
-			validator.clone(),
-			msg.clone(),
-			RequiredRouting::GridXY,
+			&state.peer_versions,
+			&Default::default(),
+			vec![(validator.clone(), msg.clone(), RequiredRouting::GridXY)],
 			&mut rng,
 		)
 		.await;
@@ -495,14 +496,14 @@ fn do_not_relay_message_twice() {
 		);
 
 		// Relaying the message a second time shouldn't work.
-		relay_message(
+		relay_messages(
 			&mut ctx,
 			state.per_relay_parent.get_mut(&hash).unwrap(),
 			&gossip_peers,
 			&mut state.peer_views,
-			validator.clone(),
-			msg.clone(),
-			RequiredRouting::GridXY,
+			&state.peer_versions,
+			&Default::default(),
+			vec![(validator.clone(), msg.clone(), RequiredRouting::GridXY)],
 			&mut rng,
 		)
 		.await;
@@ -523,6 +524,167 @@ fn do_not_relay_message_twice() {
 	});
 }
 
+#[test]
+fn receive_and_relay_batched_bitfields() {
+	let _ = env_logger::builder()
+		.filter(None, log::LevelFilter::Trace)
+		.is_test(true)
+		.try_init();
+
+	let hash: Hash = [0; 32].into();
+
+	let peer_a = PeerId::random();
+	let peer_b = PeerId::random();
+	let peer_c = PeerId::random();
+
+	// validator 0 key pair
+	let (mut state, signing_context, keystore, validator_0) =
+		state_with_view(our_view![hash], hash.clone());
+
+	// validator 1 key pair
+	let validator_1: ValidatorId =
+		SyncCryptoStore::sr25519_generate_new(&*keystore, ValidatorId::ID, None)
+			.expect("generating sr25519 key not to fail")
+			.into();
+	state
+		.per_relay_parent
+		.get_mut(&hash)
+		.unwrap()
+		.validator_set
+		.push(validator_1.clone());
+
+	// messages of both validators are to be routed to peers B and C.
+	let mut topology: SessionGridTopology = Default::default();
+	topology.peers_y = HashSet::from_iter(vec![peer_b.clone(), peer_c.clone()].into_iter());
+	topology.validator_indices_x =
+		HashSet::from_iter(vec![ValidatorIndex(0), ValidatorIndex(1)].into_iter());
+	state.topologies.update_topology(1_u32, topology);
+
+	// peers A and B support batches, peer C does not.
+	for (peer, version) in [(peer_a, 2), (peer_b, 2), (peer_c, 1)] {
+		state.peer_views.insert(peer.clone(), view![hash]);
+		state.peer_versions.insert(peer, version);
+	}
+
+	let sign = |validator_index: u32, validator: &ValidatorId| {
+		let payload = AvailabilityBitfield(bitvec![u8, bitvec::order::Lsb0; 1u8; 32]);
+		executor::block_on(Signed::<AvailabilityBitfield>::sign(
+			&keystore,
+			payload,
+			&signing_context,
+			ValidatorIndex(validator_index),
+			validator,
+		))
+		.ok()
+		.flatten()
+		.expect("should be signed")
+	};
+	let signed_0 = sign(0, &validator_0);
+	let signed_1 = sign(1, &validator_1);
+
+	let msg_0 =
+		BitfieldGossipMessage { relay_parent: hash.clone(), signed_availability: signed_0.clone() };
+	let msg_1 =
+		BitfieldGossipMessage { relay_parent: hash.clone(), signed_availability: signed_1.clone() };
+
+	let pool = sp_core::testing::TaskExecutor::new();
+	let (mut ctx, mut handle) = make_subsystem_context::<BitfieldDistributionMessage, _>(pool);
+	let mut rng = dummy_rng();
+
+	executor::block_on(async move {
+		// a batch with more bitfields than validators gets rejected as a whole.
+		launch!(handle_network_msg(
+			&mut ctx,
+			&mut state,
+			&Default::default(),
+			NetworkBridgeEvent::PeerMessage(
+				peer_a.clone(),
+				Versioned::V2(protocol_v2::BitfieldDistributionMessage::Bitfields(
+					hash.clone(),
+					vec![signed_0.clone().into(), signed_1.clone().into(), signed_0.clone().into()],
+				)),
+			),
+			&mut rng,
+		));
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::NetworkBridge(
+				NetworkBridgeMessage::ReportPeer(peer, rep)
+			) => {
+				assert_eq!(peer, peer_a);
+				assert_eq!(rep, COST_BATCH_TOO_LARGE)
+			}
+		);
+
+		launch!(handle_network_msg(
+			&mut ctx,
+			&mut state,
+			&Default::default(),
+			NetworkBridgeEvent::PeerMessage(
+				peer_a.clone(),
+				Versioned::V2(protocol_v2::BitfieldDistributionMessage::Bitfields(
+					hash.clone(),
+					vec![signed_0.clone().into(), signed_1.clone().into()],
+				)),
+			),
+			&mut rng,
+		));
+
+		for signed_bitfield in [&signed_0, &signed_1] {
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::Provisioner(ProvisionerMessage::ProvisionableData(
+					_,
+					ProvisionableData::Bitfield(h, signed)
+				)) => {
+					assert_eq!(h, hash);
+					assert_eq!(&signed, signed_bitfield)
+				}
+			);
+		}
+
+		// peer B gets both bitfields in a single batch...
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::NetworkBridge(
+				NetworkBridgeMessage::SendValidationMessage(peers, send_msg),
+			) => {
+				assert_eq!(peers, vec![peer_b]);
+				assert_eq!(
+					send_msg,
+					into_batched_validation_protocol(hash, vec![msg_0.clone(), msg_1.clone()]),
+				);
+			}
+		);
+
+		// ...while peer C gets them one by one.
+		for msg in [&msg_0, &msg_1] {
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::NetworkBridge(
+					NetworkBridgeMessage::SendValidationMessage(peers, send_msg),
+				) => {
+					assert_eq!(peers, vec![peer_c]);
+					assert_eq!(send_msg, msg.clone().into_validation_protocol());
+				}
+			);
+		}
+
+		for _ in 0..2 {
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::NetworkBridge(
+					NetworkBridgeMessage::ReportPeer(peer, rep)
+				) => {
+					assert_eq!(peer, peer_a);
+					assert_eq!(rep, BENEFIT_VALID_MESSAGE_FIRST)
+				}
+			);
+		}
+	});
+}
+
 #[test]
 fn changing_view() {
 	let _ = env_logger::builder()
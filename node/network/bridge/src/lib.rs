@@ -21,7 +21,11 @@
 
 use always_assert::never;
 use bytes::Bytes;
-use futures::{prelude::*, stream::BoxStream};
+use futures::{
+	future::BoxFuture,
+	prelude::*,
+	stream::{BoxStream, FuturesUnordered},
+};
 use parity_scale_codec::{Decode, DecodeAll, Encode};
 use parking_lot::Mutex;
 use sc_network::Event as NetworkEvent;
@@ -30,7 +34,8 @@ use sp_consensus::SyncOracle;
 use polkadot_node_network_protocol::{
 	self as net_protocol,
 	peer_set::{PeerSet, PerPeerSet},
-	v1 as protocol_v1, ObservedRole, OurView, PeerId, ProtocolVersion,
+	request_response::Recipient,
+	v1 as protocol_v1, v2 as protocol_v2, ObservedRole, OurView, PeerId, ProtocolVersion,
 	UnifiedReputationChange as Rep, Versioned, View,
 };
 
@@ -39,8 +44,8 @@ use polkadot_node_subsystem::{
 	messages::{
 		network_bridge_event::{NewGossipTopology, TopologyPeerInfo},
 		ApprovalDistributionMessage, BitfieldDistributionMessage, CollatorProtocolMessage,
-		GossipSupportMessage, NetworkBridgeEvent, NetworkBridgeMessage,
-		StatementDistributionMessage,
+		GossipSupportMessage, NetworkBridgeEvent, NetworkBridgeMessage, PeerInfo, PeerPolicy,
+		PeerReport, StatementDistributionMessage,
 	},
 	overseer, ActivatedLeaf, ActiveLeavesUpdate, FromOrchestra, OverseerSignal, SpawnedSubsystem,
 };
@@ -53,9 +58,10 @@ use polkadot_primitives::v2::{AuthorityDiscoveryId, BlockNumber, Hash, Validator
 pub use polkadot_node_network_protocol::peer_set::{peer_sets_info, IsAuthority};
 
 use std::{
-	collections::{hash_map, HashMap},
+	collections::{hash_map, BTreeMap, HashMap, HashSet},
 	iter::ExactSizeIterator,
 	sync::Arc,
+	time::Instant,
 };
 
 mod validator_discovery;
@@ -64,13 +70,17 @@ mod validator_discovery;
 ///
 /// Defines the `Network` trait with an implementation for an `Arc<NetworkService>`.
 mod network;
-use network::{send_message, Network};
+use network::{send_message, with_fallback, Fallback, Network};
 
 use crate::network::get_peer_id_by_authority_id;
 
 mod metrics;
 use self::metrics::Metrics;
 
+mod rate_limit;
+use self::rate_limit::TokenBucket;
+pub use self::rate_limit::{RateLimit, RateLimitConfig};
+
 #[cfg(test)]
 mod tests;
 
@@ -83,6 +93,7 @@ const MALFORMED_MESSAGE_COST: Rep = Rep::CostMajor("Malformed Network-bridge mes
 const UNCONNECTED_PEERSET_COST: Rep = Rep::CostMinor("Message sent to un-connected peer-set");
 const MALFORMED_VIEW_COST: Rep = Rep::CostMajor("Malformed view");
 const EMPTY_VIEW_COST: Rep = Rep::CostMajor("Peer sent us an empty view");
+const RATE_LIMIT_COST: Rep = Rep::CostMinorRepeated("Peer exceeded its notification rate limit");
 
 // network bridge log target
 const LOG_TARGET: &'static str = "parachain::network-bridge";
@@ -107,6 +118,10 @@ pub struct NetworkBridge<N, AD> {
 	authority_discovery_service: AD,
 	sync_oracle: Box<dyn SyncOracle + Send>,
 	metrics: Metrics,
+	/// Initial connection policies for authorities.
+	peer_policies: HashMap<AuthorityDiscoveryId, PeerPolicy>,
+	/// Budgets of incoming notifications per peer.
+	rate_limits: RateLimitConfig,
 }
 
 impl<N, AD> NetworkBridge<N, AD> {
@@ -119,8 +134,17 @@ impl<N, AD> NetworkBridge<N, AD> {
 		authority_discovery_service: AD,
 		sync_oracle: Box<dyn SyncOracle + Send>,
 		metrics: Metrics,
+		peer_policies: HashMap<AuthorityDiscoveryId, PeerPolicy>,
+		rate_limits: RateLimitConfig,
 	) -> Self {
-		NetworkBridge { network_service, authority_discovery_service, sync_oracle, metrics }
+		NetworkBridge {
+			network_service,
+			authority_discovery_service,
+			sync_oracle,
+			metrics,
+			peer_policies,
+			rate_limits,
+		}
 	}
 }
 
@@ -148,6 +172,10 @@ struct PeerData {
 	/// The Latest view sent by the peer.
 	view: View,
 	version: ProtocolVersion,
+	/// The authority ids of the peer, if it is an authority.
+	authority_ids: Option<HashSet<AuthorityDiscoveryId>>,
+	/// The remaining budget of notifications of the peer, if rate limited.
+	rate_limiter: Option<TokenBucket>,
 }
 
 #[derive(Debug)]
@@ -178,6 +206,96 @@ struct SharedInner {
 	local_view: Option<View>,
 	validation_peers: HashMap<PeerId, PeerData>,
 	collation_peers: HashMap<PeerId, PeerData>,
+	/// Reputation changes reported for connected peers, accumulated by reason.
+	reputation_changes: HashMap<PeerId, HashMap<&'static str, i64>>,
+	/// Connection policies for authorities.
+	peer_policies: HashMap<AuthorityDiscoveryId, PeerPolicy>,
+	/// Budgets of incoming notifications per peer.
+	rate_limits: RateLimitConfig,
+	/// Connected peers which don't support the compressed variants of request/response protocols.
+	uncompressed_peers: HashSet<Recipient>,
+}
+
+impl Shared {
+	/// Note a reputation change reported for a peer, if the peer is connected.
+	fn note_reputation_change(&self, peer: PeerId, rep: Rep) {
+		let mut shared = self.0.lock();
+		if !shared.validation_peers.contains_key(&peer) &&
+			!shared.collation_peers.contains_key(&peer)
+		{
+			return
+		}
+
+		let change = rep.into_base_rep();
+		*shared
+			.reputation_changes
+			.entry(peer)
+			.or_default()
+			.entry(change.reason)
+			.or_default() += i64::from(change.value);
+	}
+}
+
+impl SharedInner {
+	/// Whether the recipient of a request is connected on any peer-set.
+	fn is_connected(&self, recipient: &Recipient) -> bool {
+		match recipient {
+			Recipient::Peer(peer) =>
+				self.validation_peers.contains_key(peer) || self.collation_peers.contains_key(peer),
+			Recipient::Authority(id) => self
+				.validation_peers
+				.values()
+				.chain(self.collation_peers.values())
+				.any(|data| data.authority_ids.as_ref().map_or(false, |ids| ids.contains(id))),
+		}
+	}
+
+	/// Whether any of the given authority ids is banned.
+	fn is_banned(&self, authority_ids: Option<&HashSet<AuthorityDiscoveryId>>) -> bool {
+		authority_ids.map_or(false, |ids| {
+			ids.iter().any(|id| self.peer_policies.get(id) == Some(&PeerPolicy::Banned))
+		})
+	}
+
+	/// Group the given peers by the protocol version they negotiated on the given peer-set.
+	///
+	/// Peers we are not connected to are assumed to be on the oldest supported version.
+	fn peers_by_version(
+		&self,
+		peer_set: PeerSet,
+		peers: Vec<PeerId>,
+	) -> BTreeMap<ProtocolVersion, Vec<PeerId>> {
+		let connected = match peer_set {
+			PeerSet::Validation => &self.validation_peers,
+			PeerSet::Collation => &self.collation_peers,
+		};
+		let oldest_version = peer_set.get_supported_versions().last().copied().unwrap_or(1);
+
+		let mut by_version = BTreeMap::<_, Vec<_>>::new();
+		for peer in peers {
+			let version = connected.get(&peer).map_or(oldest_version, |data| data.version);
+			by_version.entry(version).or_default().push(peer);
+		}
+
+		by_version
+	}
+
+	/// All connected peers of the given peer-set.
+	fn peers_info(&self, peer_set: PeerSet) -> impl Iterator<Item = PeerInfo> + '_ {
+		let peers = match peer_set {
+			PeerSet::Validation => &self.validation_peers,
+			PeerSet::Collation => &self.collation_peers,
+		};
+
+		peers.iter().map(move |(peer_id, data)| PeerInfo {
+			peer_id: *peer_id,
+			peer_set,
+			version: data.version,
+			view: data.view.clone(),
+			authority_ids: data.authority_ids.clone(),
+			reputation_changes: self.reputation_changes.get(peer_id).cloned().unwrap_or_default(),
+		})
+	}
 }
 
 enum Mode {
@@ -205,8 +323,43 @@ where
 
 	let mut mode = Mode::Syncing(sync_oracle);
 
+	// Make sure we connect to pinned authorities right away.
+	let peer_policies = shared.0.lock().peer_policies.clone();
+	if !peer_policies.is_empty() {
+		let (ns, ads) = validator_discovery
+			.on_policy_change(peer_policies, network_service, authority_discovery_service)
+			.await;
+		network_service = ns;
+		authority_discovery_service = ads;
+	}
+
+	// Requests sent on compressed protocols, which might need to fall back to uncompressed ones.
+	let mut compressed_requests: FuturesUnordered<BoxFuture<'static, Option<Fallback>>> =
+		FuturesUnordered::new();
+
 	loop {
 		futures::select! {
+			fallback = compressed_requests.select_next_some() => {
+				if let Some(Fallback { protocol, req, if_disconnected }) = fallback {
+					gum::debug!(
+						target: LOG_TARGET,
+						?protocol,
+						peer = ?req.peer,
+						"Peer doesn't support compressed responses, falling back",
+					);
+
+					{
+						// Only remembered while connected, so the entry can be dropped on disconnect.
+						let mut shared = shared.0.lock();
+						if shared.is_connected(&req.peer) {
+							shared.uncompressed_peers.insert(req.peer.clone());
+						}
+					}
+					network_service
+						.start_request(&mut authority_discovery_service, protocol, req, if_disconnected)
+						.await;
+				}
+			},
 			msg = ctx.recv().fuse() => match msg {
 				Ok(FromOrchestra::Signal(OverseerSignal::ActiveLeaves(active_leaves))) => {
 					let ActiveLeavesUpdate { activated, deactivated } = active_leaves;
@@ -276,6 +429,7 @@ where
 						}
 
 						metrics.on_report_event();
+						shared.note_reputation_change(peer, rep);
 						network_service.report_peer(peer, rep);
 					}
 					NetworkBridgeMessage::DisconnectPeer(peer, peer_set) => {
@@ -295,14 +449,13 @@ where
 							num_messages = 1usize,
 						);
 
-						match msg {
-							Versioned::V1(msg) => send_validation_message_v1(
-								&mut network_service,
-								peers,
-								WireMessage::ProtocolMessage(msg),
-								&metrics,
-							),
-						}
+						send_validation_message(
+							&mut network_service,
+							&shared,
+							peers,
+							msg,
+							&metrics,
+						);
 					}
 					NetworkBridgeMessage::SendValidationMessages(msgs) => {
 						gum::trace!(
@@ -312,14 +465,13 @@ where
 						);
 
 						for (peers, msg) in msgs {
-							match msg {
-								Versioned::V1(msg) => send_validation_message_v1(
-									&mut network_service,
-									peers,
-									WireMessage::ProtocolMessage(msg),
-									&metrics,
-								),
-							}
+							send_validation_message(
+								&mut network_service,
+								&shared,
+								peers,
+								msg,
+								&metrics,
+							);
 						}
 					}
 					NetworkBridgeMessage::SendCollationMessage(peers, msg) => {
@@ -329,14 +481,13 @@ where
 							num_messages = 1usize,
 						);
 
-						match msg {
-							Versioned::V1(msg) => send_collation_message_v1(
-								&mut network_service,
-								peers,
-								WireMessage::ProtocolMessage(msg),
-								&metrics,
-							),
-						}
+						send_collation_message(
+							&mut network_service,
+							&shared,
+							peers,
+							msg,
+							&metrics,
+						);
 					}
 					NetworkBridgeMessage::SendCollationMessages(msgs) => {
 						gum::trace!(
@@ -346,14 +497,13 @@ where
 						);
 
 						for (peers, msg) in msgs {
-							match msg {
-								Versioned::V1(msg) => send_collation_message_v1(
-									&mut network_service,
-									peers,
-									WireMessage::ProtocolMessage(msg),
-									&metrics,
-								),
-							}
+							send_collation_message(
+								&mut network_service,
+								&shared,
+								peers,
+								msg,
+								&metrics,
+							);
 						}
 					}
 					NetworkBridgeMessage::SendRequests(reqs, if_disconnected) => {
@@ -364,9 +514,20 @@ where
 						);
 
 						for req in reqs {
-							network_service
-								.start_request(&mut authority_discovery_service, req, if_disconnected)
-								.await;
+							let (protocol, req) = req.encode_request();
+							let uncompressed = shared.0.lock().uncompressed_peers.contains(&req.peer);
+							match protocol.compressed() {
+								Some(compressed) if !uncompressed => {
+									let (req, forward) = with_fallback(protocol, req, if_disconnected);
+									network_service
+										.start_request(&mut authority_discovery_service, compressed, req, if_disconnected)
+										.await;
+									compressed_requests.push(forward);
+								}
+								_ => network_service
+									.start_request(&mut authority_discovery_service, protocol, req, if_disconnected)
+									.await,
+							}
 						}
 					}
 					NetworkBridgeMessage::ConnectToValidators {
@@ -416,6 +577,83 @@ where
 							network_service,
 						).await;
 					}
+					NetworkBridgeMessage::GetPeerReport(tx) => {
+						gum::trace!(
+							target: LOG_TARGET,
+							action = "GetPeerReport",
+						);
+
+						let shared = shared.0.lock();
+						let mut report = PeerReport::default();
+						for peer_set in [PeerSet::Validation, PeerSet::Collation] {
+							let peers: Vec<_> = shared.peers_info(peer_set).collect();
+							let connected: HashSet<_> = peers
+								.iter()
+								.filter_map(|p| p.authority_ids.as_ref())
+								.flatten()
+								.collect();
+
+							report.unreachable_validators.extend(
+								validator_discovery
+									.wanted_validators(peer_set)
+									.into_iter()
+									.filter(|id| !connected.contains(id))
+									.map(|id| (peer_set, id)),
+							);
+							report.peers.extend(peers);
+						}
+
+						let _ = tx.send(report);
+					}
+					NetworkBridgeMessage::SetPeerPolicy(authority, policy) => {
+						gum::info!(
+							target: LOG_TARGET,
+							action = "SetPeerPolicy",
+							?authority,
+							?policy,
+							"Changing connection policy of authority",
+						);
+
+						let (peer_policies, to_disconnect) = {
+							let mut shared = shared.0.lock();
+							match policy {
+								Some(policy) =>
+									shared.peer_policies.insert(authority.clone(), policy),
+								None => shared.peer_policies.remove(&authority),
+							};
+
+							let mut to_disconnect = Vec::new();
+							if policy == Some(PeerPolicy::Banned) {
+								for peer_set in [PeerSet::Validation, PeerSet::Collation] {
+									to_disconnect.extend(
+										shared
+											.peers_info(peer_set)
+											.filter(|p| {
+												p.authority_ids
+													.as_ref()
+													.map_or(false, |ids| ids.contains(&authority))
+											})
+											.map(|p| (p.peer_id, peer_set)),
+									);
+								}
+							}
+
+							(shared.peer_policies.clone(), to_disconnect)
+						};
+
+						for (peer, peer_set) in to_disconnect {
+							network_service.disconnect_peer(peer, peer_set);
+						}
+
+						let (ns, ads) = validator_discovery.on_policy_change(
+							peer_policies,
+							network_service,
+							authority_discovery_service,
+						).await;
+
+						network_service = ns;
+						authority_discovery_service = ads;
+					}
 					NetworkBridgeMessage::NewGossipTopology {
 						session,
 						our_neighbors_x,
@@ -551,8 +789,27 @@ async fn handle_network_messages<AD: validator_discovery::AuthorityDiscovery>(
 					role = ?role
 				);
 
+				let maybe_authority =
+					authority_discovery_service.get_authority_ids_by_peer_id(peer).await;
+
 				let local_view = {
 					let mut shared = shared.0.lock();
+					if shared.is_banned(maybe_authority.as_ref()) {
+						gum::debug!(
+							target: LOG_TARGET,
+							?peer,
+							?peer_set,
+							"Disconnecting banned authority",
+						);
+
+						network_service.disconnect_peer(peer, peer_set);
+						continue
+					}
+
+					let rate_limiter = shared
+						.rate_limits
+						.limit(peer_set)
+						.map(|limit| TokenBucket::new(limit, Instant::now()));
 					let peer_map = match peer_set {
 						PeerSet::Validation => &mut shared.validation_peers,
 						PeerSet::Collation => &mut shared.collation_peers,
@@ -561,7 +818,12 @@ async fn handle_network_messages<AD: validator_discovery::AuthorityDiscovery>(
 					match peer_map.entry(peer.clone()) {
 						hash_map::Entry::Occupied(_) => continue,
 						hash_map::Entry::Vacant(vacant) => {
-							vacant.insert(PeerData { view: View::default(), version });
+							vacant.insert(PeerData {
+								view: View::default(),
+								version,
+								authority_ids: maybe_authority.clone(),
+								rate_limiter,
+							});
 						},
 					}
 
@@ -571,9 +833,6 @@ async fn handle_network_messages<AD: validator_discovery::AuthorityDiscovery>(
 					shared.local_view.clone().unwrap_or(View::default())
 				};
 
-				let maybe_authority =
-					authority_discovery_service.get_authority_ids_by_peer_id(peer).await;
-
 				match peer_set {
 					PeerSet::Validation => {
 						dispatch_validation_events_to_all(
@@ -581,7 +840,7 @@ async fn handle_network_messages<AD: validator_discovery::AuthorityDiscovery>(
 								NetworkBridgeEvent::PeerConnected(
 									peer.clone(),
 									role,
-									1,
+									version,
 									maybe_authority,
 								),
 								NetworkBridgeEvent::PeerViewChange(peer.clone(), View::default()),
@@ -605,7 +864,7 @@ async fn handle_network_messages<AD: validator_discovery::AuthorityDiscovery>(
 								NetworkBridgeEvent::PeerConnected(
 									peer.clone(),
 									role,
-									1,
+									version,
 									maybe_authority,
 								),
 								NetworkBridgeEvent::PeerViewChange(peer.clone(), View::default()),
@@ -645,11 +904,26 @@ async fn handle_network_messages<AD: validator_discovery::AuthorityDiscovery>(
 						PeerSet::Collation => &mut shared.collation_peers,
 					};
 
-					let w = peer_map.remove(&peer).is_some();
+					let removed = peer_map.remove(&peer);
+					let w = removed.is_some();
 
 					metrics.on_peer_disconnected(peer_set, version);
 					metrics.note_peer_count(peer_set, version, peer_map.len());
 
+					if !shared.validation_peers.contains_key(&peer) &&
+						!shared.collation_peers.contains_key(&peer)
+					{
+						shared.reputation_changes.remove(&peer);
+						shared.uncompressed_peers.remove(&Recipient::Peer(peer));
+					}
+
+					for id in removed.and_then(|data| data.authority_ids).into_iter().flatten() {
+						let recipient = Recipient::Authority(id);
+						if !shared.is_connected(&recipient) {
+							shared.uncompressed_peers.remove(&recipient);
+						}
+					}
+
 					w
 				};
 
@@ -707,6 +981,7 @@ async fn handle_network_messages<AD: validator_discovery::AuthorityDiscovery>(
 				let v_messages = match v_messages {
 					Err(rep) => {
 						gum::debug!(target: LOG_TARGET, action = "ReportPeer");
+						shared.note_reputation_change(remote, rep);
 						network_service.report_peer(remote, rep);
 
 						continue
@@ -737,6 +1012,7 @@ async fn handle_network_messages<AD: validator_discovery::AuthorityDiscovery>(
 				let c_messages = match c_messages {
 					Err(rep) => {
 						gum::debug!(target: LOG_TARGET, action = "ReportPeer");
+						shared.note_reputation_change(remote, rep);
 						network_service.report_peer(remote, rep);
 
 						continue
@@ -757,30 +1033,38 @@ async fn handle_network_messages<AD: validator_discovery::AuthorityDiscovery>(
 				);
 
 				if !v_messages.is_empty() {
-					let (events, reports) =
-						if expected_versions[PeerSet::Validation] == Some(1) {
-							handle_v1_peer_messages::<protocol_v1::ValidationProtocol, _>(
-								remote.clone(),
-								PeerSet::Validation,
-								&mut shared.0.lock().validation_peers,
-								v_messages,
-								&metrics,
-							)
-						} else {
+					let (events, reports) = match expected_versions[PeerSet::Validation] {
+						Some(1) => handle_peer_messages::<protocol_v1::ValidationProtocol, _>(
+							remote.clone(),
+							PeerSet::Validation,
+							&mut shared.0.lock().validation_peers,
+							v_messages,
+							&metrics,
+						),
+						Some(2) => handle_peer_messages::<protocol_v2::ValidationProtocol, _>(
+							remote.clone(),
+							PeerSet::Validation,
+							&mut shared.0.lock().validation_peers,
+							v_messages,
+							&metrics,
+						),
+						version => {
 							gum::warn!(
 								target: LOG_TARGET,
-								version = ?expected_versions[PeerSet::Validation],
+								?version,
 								"Major logic bug. Peer somehow has unsupported validation protocol version."
 							);
 
-							never!("Only version 1 is supported; peer set connection checked above; qed");
+							never!("Only versions 1 and 2 are supported; peer set connection checked above; qed");
 
 							// If a peer somehow triggers this, we'll disconnect them
 							// eventually.
 							(Vec::new(), vec![UNCONNECTED_PEERSET_COST])
-						};
+						},
+					};
 
 					for report in reports {
+						shared.note_reputation_change(remote, report);
 						network_service.report_peer(remote.clone(), report);
 					}
 
@@ -790,7 +1074,7 @@ async fn handle_network_messages<AD: validator_discovery::AuthorityDiscovery>(
 				if !c_messages.is_empty() {
 					let (events, reports) =
 						if expected_versions[PeerSet::Collation] == Some(1) {
-							handle_v1_peer_messages::<protocol_v1::CollationProtocol, _>(
+							handle_peer_messages::<protocol_v1::CollationProtocol, _>(
 								remote.clone(),
 								PeerSet::Collation,
 								&mut shared.0.lock().collation_peers,
@@ -812,6 +1096,7 @@ async fn handle_network_messages<AD: validator_discovery::AuthorityDiscovery>(
 						};
 
 					for report in reports {
+						shared.note_reputation_change(remote, report);
 						network_service.report_peer(remote.clone(), report);
 					}
 
@@ -843,10 +1128,21 @@ where
 	N: Network,
 	AD: validator_discovery::AuthorityDiscovery + Clone,
 {
-	let shared = Shared::default();
+	let NetworkBridge {
+		network_service,
+		authority_discovery_service,
+		metrics,
+		sync_oracle,
+		peer_policies,
+		rate_limits,
+	} = bridge;
 
-	let NetworkBridge { network_service, authority_discovery_service, metrics, sync_oracle } =
-		bridge;
+	let shared = Shared::default();
+	{
+		let mut shared = shared.0.lock();
+		shared.peer_policies = peer_policies;
+		shared.rate_limits = rate_limits;
+	}
 
 	let (remote, network_event_handler) = handle_network_messages(
 		ctx.sender().clone(),
@@ -938,20 +1234,36 @@ fn update_our_view<Net, Context>(
 			},
 		}
 
+		let validation_peers = shared.validation_peers.keys().cloned().collect();
+		let collation_peers = shared.collation_peers.keys().cloned().collect();
 		(
-			shared.validation_peers.keys().cloned().collect::<Vec<_>>(),
-			shared.collation_peers.keys().cloned().collect::<Vec<_>>(),
+			shared.peers_by_version(PeerSet::Validation, validation_peers),
+			shared.peers_by_version(PeerSet::Collation, collation_peers),
 		)
 	};
 
-	send_validation_message_v1(
-		net,
-		validation_peers,
-		WireMessage::ViewUpdate(new_view.clone()),
-		metrics,
-	);
+	// View updates are encoded the same way on all versions.
+	for (version, peers) in validation_peers {
+		send_message(
+			net,
+			peers,
+			PeerSet::Validation,
+			version,
+			WireMessage::<protocol_v1::ValidationProtocol>::ViewUpdate(new_view.clone()),
+			metrics,
+		);
+	}
 
-	send_collation_message_v1(net, collation_peers, WireMessage::ViewUpdate(new_view), metrics);
+	for (version, peers) in collation_peers {
+		send_message(
+			net,
+			peers,
+			PeerSet::Collation,
+			version,
+			WireMessage::<protocol_v1::CollationProtocol>::ViewUpdate(new_view.clone()),
+			metrics,
+		);
+	}
 
 	let our_view = OurView::new(
 		live_heads.iter().take(MAX_VIEW_HEADS).cloned().map(|a| (a.hash, a.span)),
@@ -969,9 +1281,12 @@ fn update_our_view<Net, Context>(
 	);
 }
 
-// Handle messages on a specific v1 peer-set. The peer is expected to be connected on that
-// peer-set.
-fn handle_v1_peer_messages<RawMessage: Decode, OutMessage: From<RawMessage>>(
+// Handle messages on a specific peer-set, decoded as `RawMessage` of the version the peer
+// negotiated. The peer is expected to be connected on that peer-set.
+//
+// Protocol messages exceeding the peer's rate limit are dropped and reported at most once per
+// batch. View updates are exempt, so peers' views stay accurate.
+fn handle_peer_messages<RawMessage: Decode, OutMessage: From<RawMessage>>(
 	peer: PeerId,
 	peer_set: PeerSet,
 	peers: &mut HashMap<PeerId, PeerData>,
@@ -985,9 +1300,12 @@ fn handle_v1_peer_messages<RawMessage: Decode, OutMessage: From<RawMessage>>(
 
 	let mut outgoing_events = Vec::with_capacity(messages.len());
 	let mut reports = Vec::new();
+	let mut rate_limited = 0;
+	let now = Instant::now();
 
 	for message in messages {
 		metrics.on_notification_received(peer_set, peer_data.version, message.len());
+
 		let message = match WireMessage::<RawMessage>::decode_all(&mut message.as_ref()) {
 			Err(_) => {
 				reports.push(MALFORMED_MESSAGE_COST);
@@ -1014,11 +1332,32 @@ fn handle_v1_peer_messages<RawMessage: Decode, OutMessage: From<RawMessage>>(
 					NetworkBridgeEvent::PeerViewChange(peer.clone(), peer_data.view.clone())
 				}
 			},
-			WireMessage::ProtocolMessage(message) =>
-				NetworkBridgeEvent::PeerMessage(peer.clone(), message.into()),
+			WireMessage::ProtocolMessage(message) => {
+				if let Some(ref mut rate_limiter) = peer_data.rate_limiter {
+					if !rate_limiter.try_acquire(now) {
+						rate_limited += 1;
+						continue
+					}
+				}
+
+				NetworkBridgeEvent::PeerMessage(peer.clone(), message.into())
+			},
 		})
 	}
 
+	if rate_limited > 0 {
+		gum::debug!(
+			target: LOG_TARGET,
+			?peer,
+			?peer_set,
+			rate_limited,
+			"Dropped notifications exceeding the peer's rate limit",
+		);
+
+		metrics.on_notifications_rate_limited(peer_set, peer_data.version, rate_limited);
+		reports.push(RATE_LIMIT_COST);
+	}
+
 	(outgoing_events, reports)
 }
 
@@ -1031,6 +1370,71 @@ fn send_validation_message_v1(
 	send_message(net, peers, PeerSet::Validation, 1, message, metrics);
 }
 
+fn send_validation_message_v2(
+	net: &mut impl Network,
+	peers: Vec<PeerId>,
+	message: WireMessage<protocol_v2::ValidationProtocol>,
+	metrics: &Metrics,
+) {
+	send_message(net, peers, PeerSet::Validation, 2, message, metrics);
+}
+
+/// Send a versioned validation message to the given peers, each on the version it negotiated.
+///
+/// Peers on a version the message can't be expressed in are skipped.
+fn send_validation_message(
+	net: &mut impl Network,
+	shared: &Shared,
+	peers: Vec<PeerId>,
+	message: net_protocol::VersionedValidationProtocol,
+	metrics: &Metrics,
+) {
+	let peers_by_version = shared.0.lock().peers_by_version(PeerSet::Validation, peers);
+
+	for (version, peers) in peers_by_version {
+		match message.clone().into_version(version) {
+			Some(Versioned::V1(msg)) =>
+				send_validation_message_v1(net, peers, WireMessage::ProtocolMessage(msg), metrics),
+			Some(Versioned::V2(msg)) =>
+				send_validation_message_v2(net, peers, WireMessage::ProtocolMessage(msg), metrics),
+			None => gum::debug!(
+				target: LOG_TARGET,
+				version,
+				message_version = message.version(),
+				num_peers = peers.len(),
+				"Validation message can't be sent on the peers' protocol version",
+			),
+		}
+	}
+}
+
+/// Send a versioned collation message to the given peers, each on the version it negotiated.
+///
+/// Peers on a version the message can't be expressed in are skipped.
+fn send_collation_message(
+	net: &mut impl Network,
+	shared: &Shared,
+	peers: Vec<PeerId>,
+	message: net_protocol::VersionedCollationProtocol,
+	metrics: &Metrics,
+) {
+	let peers_by_version = shared.0.lock().peers_by_version(PeerSet::Collation, peers);
+
+	for (version, peers) in peers_by_version {
+		match message.clone().into_version(version) {
+			Some(Versioned::V1(msg)) =>
+				send_collation_message_v1(net, peers, WireMessage::ProtocolMessage(msg), metrics),
+			Some(Versioned::V2(_)) | None => gum::debug!(
+				target: LOG_TARGET,
+				version,
+				message_version = message.version(),
+				num_peers = peers.len(),
+				"Collation message can't be sent on the peers' protocol version",
+			),
+		}
+	}
+}
+
 fn send_collation_message_v1(
 	net: &mut impl Network,
 	peers: Vec<PeerId>,
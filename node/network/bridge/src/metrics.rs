@@ -93,6 +93,20 @@ impl Metrics {
 		}
 	}
 
+	pub fn on_notifications_rate_limited(
+		&self,
+		peer_set: PeerSet,
+		version: ProtocolVersion,
+		count: usize,
+	) {
+		self.0.as_ref().map(|metrics| {
+			metrics
+				.notifications_rate_limited
+				.with_label_values(&[peer_set_label(peer_set, version)])
+				.inc_by(count as u64)
+		});
+	}
+
 	pub fn note_desired_peer_count(&self, peer_set: PeerSet, size: usize) {
 		self.0.as_ref().map(|metrics| {
 			metrics
@@ -119,6 +133,7 @@ pub(crate) struct MetricsInner {
 
 	notifications_received: prometheus::CounterVec<prometheus::U64>,
 	notifications_sent: prometheus::CounterVec<prometheus::U64>,
+	notifications_rate_limited: prometheus::CounterVec<prometheus::U64>,
 
 	bytes_received: prometheus::CounterVec<prometheus::U64>,
 	bytes_sent: prometheus::CounterVec<prometheus::U64>,
@@ -196,6 +211,16 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			notifications_rate_limited: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_notifications_rate_limited_total",
+						"The number of notifications dropped for exceeding the sending peer's rate limit",
+					),
+					&["protocol"]
+				)?,
+				registry,
+			)?,
 			bytes_received: prometheus::register(
 				prometheus::CounterVec::new(
 					prometheus::Opts::new(
@@ -17,7 +17,7 @@
 use std::{borrow::Cow, collections::HashSet, sync::Arc};
 
 use async_trait::async_trait;
-use futures::{prelude::*, stream::BoxStream};
+use futures::{channel::oneshot, future::BoxFuture, prelude::*, stream::BoxStream};
 
 use parity_scale_codec::Encode;
 
@@ -28,7 +28,7 @@ use sc_network::{
 
 use polkadot_node_network_protocol::{
 	peer_set::PeerSet,
-	request_response::{OutgoingRequest, Recipient, Requests},
+	request_response::{decompress_response, OutgoingRequest, Protocol, Recipient},
 	PeerId, ProtocolVersion, UnifiedReputationChange as Rep,
 };
 use polkadot_primitives::v2::{AuthorityDiscoveryId, Block, Hash};
@@ -91,11 +91,12 @@ pub trait Network: Clone + Send + 'static {
 	/// Removes the peers for the protocol's peer set (both reserved and non-reserved).
 	async fn remove_from_peers_set(&mut self, protocol: Cow<'static, str>, peers: Vec<PeerId>);
 
-	/// Send a request to a remote peer.
+	/// Send an encoded request to a remote peer on the given protocol.
 	async fn start_request<AD: AuthorityDiscovery>(
 		&self,
 		authority_discovery: &mut AD,
-		req: Requests,
+		protocol: Protocol,
+		req: OutgoingRequest<Vec<u8>>,
 		if_disconnected: IfDisconnected,
 	);
 
@@ -151,10 +152,11 @@ impl Network for Arc<NetworkService<Block, Hash>> {
 	async fn start_request<AD: AuthorityDiscovery>(
 		&self,
 		authority_discovery: &mut AD,
-		req: Requests,
+		protocol: Protocol,
+		req: OutgoingRequest<Vec<u8>>,
 		if_disconnected: IfDisconnected,
 	) {
-		let (protocol, OutgoingRequest { peer, payload, pending_response }) = req.encode_request();
+		let OutgoingRequest { peer, payload, pending_response } = req;
 
 		let peer_id = match peer {
 			Recipient::Peer(peer_id) => Some(peer_id),
@@ -205,6 +207,61 @@ impl Network for Arc<NetworkService<Block, Hash>> {
 	}
 }
 
+/// A request to be sent again on the uncompressed variant of its protocol.
+pub(crate) struct Fallback {
+	/// The uncompressed protocol.
+	pub protocol: Protocol,
+	/// The request, still waiting for a response.
+	pub req: OutgoingRequest<Vec<u8>>,
+	/// How to deal with the peer not being connected.
+	pub if_disconnected: IfDisconnected,
+}
+
+/// Prepare a request encoded for `protocol` to be sent on the compressed variant of it instead.
+///
+/// Returns the request to send on the compressed protocol and a future forwarding its decompressed
+/// response to the requester. If the peer doesn't support the compressed protocol, the future
+/// resolves to the request to be sent on `protocol` instead.
+///
+/// Responses which fail to decompress are forwarded as they are, so they fail to decode at the
+/// requester.
+pub(crate) fn with_fallback(
+	protocol: Protocol,
+	req: OutgoingRequest<Vec<u8>>,
+	if_disconnected: IfDisconnected,
+) -> (OutgoingRequest<Vec<u8>>, BoxFuture<'static, Option<Fallback>>) {
+	let OutgoingRequest { peer, payload, pending_response } = req;
+	let (tx, rx) = oneshot::channel();
+	let compressed_req =
+		OutgoingRequest { peer: peer.clone(), payload: payload.clone(), pending_response: tx };
+
+	let forward = async move {
+		match rx.await {
+			Ok(Err(RequestFailure::Network(OutboundFailure::UnsupportedProtocols))) => {
+				let req = OutgoingRequest { peer, payload, pending_response };
+				Some(Fallback { protocol, req, if_disconnected })
+			},
+			Ok(Ok(response)) => {
+				let decompressed = match decompress_response(protocol, &response) {
+					Ok(Cow::Owned(decompressed)) => Some(decompressed),
+					// Not compressed or invalid.
+					Ok(Cow::Borrowed(_)) | Err(_) => None,
+				};
+				let _ = pending_response.send(Ok(decompressed.unwrap_or(response)));
+				None
+			},
+			Ok(Err(err)) => {
+				let _ = pending_response.send(Err(err));
+				None
+			},
+			// Dropping `pending_response` lets the requester know.
+			Err(oneshot::Canceled) => None,
+		}
+	};
+
+	(compressed_req, forward.boxed())
+}
+
 /// We assume one `peer_id` per `authority_id`.
 pub async fn get_peer_id_by_authority_id<AD: AuthorityDiscovery>(
 	authority_discovery: &mut AD,
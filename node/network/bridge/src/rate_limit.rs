@@ -0,0 +1,107 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-peer rate limiting of incoming notifications.
+
+use std::time::Instant;
+
+use super::PeerSet;
+
+/// The budget of notifications a single peer may send us on a peer-set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+	/// The number of notifications a peer may send in one go.
+	pub burst: u32,
+	/// The number of notifications per second a peer regains, up to `burst`.
+	pub per_second: u32,
+}
+
+/// Rate limits of the network bridge, per peer-set.
+///
+/// `None` disables rate limiting on the peer-set, which is the default: there are no measured
+/// budgets yet which legitimate traffic on large validator sets is known to stay within.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitConfig {
+	/// The budget of every peer on the validation peer-set.
+	pub validation: Option<RateLimit>,
+	/// The budget of every peer on the collation peer-set.
+	pub collation: Option<RateLimit>,
+}
+
+impl RateLimitConfig {
+	/// The budget of a peer on the given peer-set.
+	pub fn limit(&self, peer_set: PeerSet) -> Option<RateLimit> {
+		match peer_set {
+			PeerSet::Validation => self.validation,
+			PeerSet::Collation => self.collation,
+		}
+	}
+}
+
+/// A token bucket tracking the remaining budget of a single peer.
+#[derive(Debug, Clone)]
+pub(crate) struct TokenBucket {
+	limit: RateLimit,
+	tokens: f64,
+	last_refill: Instant,
+}
+
+impl TokenBucket {
+	/// A full bucket for the given budget.
+	pub(crate) fn new(limit: RateLimit, now: Instant) -> Self {
+		TokenBucket { limit, tokens: limit.burst as f64, last_refill: now }
+	}
+
+	/// Take a token for a single notification, returning `false` if the budget is exhausted.
+	pub(crate) fn try_acquire(&mut self, now: Instant) -> bool {
+		let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+		self.tokens =
+			(self.tokens + elapsed * self.limit.per_second as f64).min(self.limit.burst as f64);
+		self.last_refill = now;
+
+		if self.tokens >= 1.0 {
+			self.tokens -= 1.0;
+			true
+		} else {
+			false
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+
+	#[test]
+	fn bucket_allows_burst_then_refills() {
+		let now = Instant::now();
+		let mut bucket = TokenBucket::new(RateLimit { burst: 3, per_second: 2 }, now);
+
+		assert!((0..3).all(|_| bucket.try_acquire(now)));
+		assert!(!bucket.try_acquire(now));
+
+		// Half a second regains a single token.
+		let now = now + Duration::from_millis(500);
+		assert!(bucket.try_acquire(now));
+		assert!(!bucket.try_acquire(now));
+
+		// Refills never exceed the burst.
+		let now = now + Duration::from_secs(60);
+		assert!((0..3).all(|_| bucket.try_acquire(now)));
+		assert!(!bucket.try_acquire(now));
+	}
+}
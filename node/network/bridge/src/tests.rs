@@ -29,7 +29,8 @@ use std::{
 use sc_network::{Event as NetworkEvent, IfDisconnected};
 
 use polkadot_node_network_protocol::{
-	request_response::outgoing::Requests, view, ObservedRole, Versioned,
+	request_response::{compress_response, OutgoingRequest, Protocol, Recipient},
+	view, ObservedRole, Versioned,
 };
 use polkadot_node_subsystem::{
 	jaeger,
@@ -114,7 +115,8 @@ impl Network for TestNetwork {
 	async fn start_request<AD: AuthorityDiscovery>(
 		&self,
 		_: &mut AD,
-		_: Requests,
+		_: Protocol,
+		_: OutgoingRequest<Vec<u8>>,
 		_: IfDisconnected,
 	) {
 	}
@@ -174,11 +176,28 @@ impl TestNetworkHandle {
 		v
 	}
 
+	/// Connect a peer on version 1 of the given peer set.
 	async fn connect_peer(&mut self, peer: PeerId, peer_set: PeerSet, role: ObservedRole) {
+		self.connect_peer_with_version(peer, peer_set, 1, role).await
+	}
+
+	async fn connect_peer_with_version(
+		&mut self,
+		peer: PeerId,
+		peer_set: PeerSet,
+		version: ProtocolVersion,
+		role: ObservedRole,
+	) {
+		let negotiated_fallback = if version == peer_set.get_default_version() {
+			None
+		} else {
+			peer_set.into_protocol_name(version)
+		};
+
 		self.send_network_event(NetworkEvent::NotificationStreamOpened {
 			remote: peer,
 			protocol: peer_set.into_default_protocol_name(),
-			negotiated_fallback: None,
+			negotiated_fallback,
 			role: role.into(),
 		})
 		.await;
@@ -277,6 +296,14 @@ struct TestHarness {
 fn test_harness<T: Future<Output = VirtualOverseer>>(
 	sync_oracle: Box<dyn SyncOracle + Send>,
 	test: impl FnOnce(TestHarness) -> T,
+) {
+	test_harness_with_rate_limits(sync_oracle, RateLimitConfig::default(), test)
+}
+
+fn test_harness_with_rate_limits<T: Future<Output = VirtualOverseer>>(
+	sync_oracle: Box<dyn SyncOracle + Send>,
+	rate_limits: RateLimitConfig,
+	test: impl FnOnce(TestHarness) -> T,
 ) {
 	let pool = sp_core::testing::TaskExecutor::new();
 	let (mut network, network_handle, discovery) = new_test_network();
@@ -289,6 +316,8 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(
 		authority_discovery_service: discovery,
 		metrics: Metrics(None),
 		sync_oracle,
+		peer_policies: HashMap::new(),
+		rate_limits,
 	};
 
 	let network_bridge = run_network(bridge, context, network_stream)
@@ -743,6 +772,164 @@ fn peer_messages_sent_via_overseer() {
 	});
 }
 
+#[test]
+fn peer_messages_exceeding_rate_limit_are_dropped() {
+	let rate_limits = RateLimitConfig {
+		validation: Some(RateLimit { burst: 2, per_second: 0 }),
+		collation: None,
+	};
+
+	test_harness_with_rate_limits(done_syncing_oracle(), rate_limits, |test_harness| async move {
+		let TestHarness { mut network_handle, mut virtual_overseer } = test_harness;
+
+		let peer = PeerId::random();
+
+		network_handle
+			.connect_peer(peer.clone(), PeerSet::Validation, ObservedRole::Full)
+			.await;
+
+		// bridge will inform about all connected peers.
+		{
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerConnected(peer.clone(), ObservedRole::Full, 1, None),
+				&mut virtual_overseer,
+			)
+			.await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerViewChange(peer.clone(), View::default()),
+				&mut virtual_overseer,
+			)
+			.await;
+		}
+
+		let approval_distribution_message =
+			protocol_v1::ApprovalDistributionMessage::Approvals(Vec::new());
+
+		let message =
+			WireMessage::ProtocolMessage(protocol_v1::ValidationProtocol::ApprovalDistribution(
+				approval_distribution_message.clone(),
+			))
+			.encode();
+
+		// View updates don't count towards the budget.
+		let view = view![Hash::repeat_byte(1)];
+		let view_update =
+			WireMessage::<protocol_v1::ValidationProtocol>::ViewUpdate(view.clone()).encode();
+
+		network_handle
+			.send_network_event(NetworkEvent::NotificationsReceived {
+				remote: peer.clone(),
+				messages: [message.clone(), message.clone(), message, view_update]
+					.into_iter()
+					.map(|m| (PeerSet::Validation.into_default_protocol_name(), m.into()))
+					.collect(),
+			})
+			.await;
+
+		let actions = network_handle.next_network_actions(2).await;
+		assert_network_actions_contains(
+			&actions,
+			&NetworkAction::ReputationChange(peer.clone(), RATE_LIMIT_COST),
+		);
+
+		network_handle.disconnect_peer(peer.clone(), PeerSet::Validation).await;
+
+		// Only the messages within the budget are forwarded.
+		for _ in 0..2 {
+			assert_matches!(
+				virtual_overseer.recv().await,
+				AllMessages::ApprovalDistribution(
+					ApprovalDistributionMessage::NetworkBridgeUpdate(
+						NetworkBridgeEvent::PeerMessage(p, Versioned::V1(m))
+					)
+				) => {
+					assert_eq!(p, peer);
+					assert_eq!(m, approval_distribution_message);
+				}
+			);
+		}
+
+		assert_sends_validation_event_to_all(
+			NetworkBridgeEvent::PeerViewChange(peer.clone(), view),
+			&mut virtual_overseer,
+		)
+		.await;
+
+		assert_sends_validation_event_to_all(
+			NetworkBridgeEvent::PeerDisconnected(peer),
+			&mut virtual_overseer,
+		)
+		.await;
+		virtual_overseer
+	});
+}
+
+#[test]
+fn peer_report_includes_reputation_changes() {
+	test_harness(done_syncing_oracle(), |test_harness| async move {
+		let TestHarness { mut network_handle, mut virtual_overseer } = test_harness;
+
+		let peer = PeerId::random();
+
+		network_handle
+			.connect_peer(peer.clone(), PeerSet::Validation, ObservedRole::Full)
+			.await;
+
+		// bridge will inform about all connected peers.
+		{
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerConnected(peer.clone(), ObservedRole::Full, 1, None),
+				&mut virtual_overseer,
+			)
+			.await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerViewChange(peer.clone(), View::default()),
+				&mut virtual_overseer,
+			)
+			.await;
+		}
+
+		network_handle.peer_message(peer.clone(), PeerSet::Validation, vec![0xff]).await;
+
+		let actions = network_handle.next_network_actions(2).await;
+		assert_network_actions_contains(
+			&actions,
+			&NetworkAction::ReputationChange(peer.clone(), MALFORMED_MESSAGE_COST),
+		);
+
+		let cost = Rep::CostMinor("Reported by a subsystem");
+		virtual_overseer
+			.send(FromOrchestra::Communication {
+				msg: NetworkBridgeMessage::ReportPeer(peer, cost),
+			})
+			.await;
+
+		let (tx, rx) = oneshot::channel();
+		virtual_overseer
+			.send(FromOrchestra::Communication { msg: NetworkBridgeMessage::GetPeerReport(tx) })
+			.await;
+
+		let report = rx.await.unwrap();
+		assert!(report.unreachable_validators.is_empty());
+		assert_matches!(&report.peers[..], [info] => {
+			assert_eq!(info.peer_id, peer);
+			assert_eq!(info.peer_set, PeerSet::Validation);
+			assert_eq!(info.version, 1);
+			assert_eq!(
+				info.reputation_changes,
+				HashMap::from([
+					(MALFORMED_MESSAGE_COST.description(), i64::from(MALFORMED_MESSAGE_COST.into_base_rep().value)),
+					(cost.description(), i64::from(cost.into_base_rep().value)),
+				]),
+			);
+		});
+
+		virtual_overseer
+	});
+}
+
 #[test]
 fn peer_disconnect_from_just_one_peerset() {
 	test_harness(done_syncing_oracle(), |test_harness| async move {
@@ -1193,6 +1380,155 @@ fn send_messages_to_peers() {
 	});
 }
 
+#[test]
+fn validation_messages_routed_by_peer_version() {
+	test_harness(done_syncing_oracle(), |test_harness| async move {
+		let TestHarness { mut network_handle, mut virtual_overseer } = test_harness;
+
+		let peer_v1 = PeerId::random();
+		let peer_v2 = PeerId::random();
+
+		network_handle
+			.connect_peer_with_version(peer_v1.clone(), PeerSet::Validation, 1, ObservedRole::Full)
+			.await;
+		network_handle
+			.connect_peer_with_version(peer_v2.clone(), PeerSet::Validation, 2, ObservedRole::Full)
+			.await;
+
+		// bridge will inform about all connected peers, along with their versions.
+		for (peer, version) in [(peer_v1, 1), (peer_v2, 2)] {
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerConnected(peer.clone(), ObservedRole::Full, version, None),
+				&mut virtual_overseer,
+			)
+			.await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerViewChange(peer.clone(), View::default()),
+				&mut virtual_overseer,
+			)
+			.await;
+		}
+
+		// consume peer view changes
+		{
+			let _peer_view_changes = network_handle.next_network_actions(2).await;
+		}
+
+		// v1 messages reach peers of both versions.
+		let message_v1 = protocol_v1::ValidationProtocol::ApprovalDistribution(
+			protocol_v1::ApprovalDistributionMessage::Approvals(Vec::new()),
+		);
+
+		virtual_overseer
+			.send(FromOrchestra::Communication {
+				msg: NetworkBridgeMessage::SendValidationMessage(
+					vec![peer_v1.clone(), peer_v2.clone()],
+					Versioned::V1(message_v1.clone()),
+				),
+			})
+			.await;
+
+		let actions = network_handle.next_network_actions(2).await;
+		assert_network_actions_contains(
+			&actions,
+			&NetworkAction::WriteNotification(
+				peer_v1.clone(),
+				PeerSet::Validation,
+				WireMessage::ProtocolMessage(message_v1.clone()).encode(),
+			),
+		);
+		assert_network_actions_contains(
+			&actions,
+			&NetworkAction::WriteNotification(
+				peer_v2.clone(),
+				PeerSet::Validation,
+				WireMessage::<protocol_v2::ValidationProtocol>::ProtocolMessage(
+					message_v1.clone().into(),
+				)
+				.encode(),
+			),
+		);
+
+		// v2 messages only reach v2 peers.
+		let message_v2 = protocol_v2::ValidationProtocol::BitfieldDistribution(
+			protocol_v2::BitfieldDistributionMessage::Bitfields(Hash::repeat_byte(1), Vec::new()),
+		);
+
+		virtual_overseer
+			.send(FromOrchestra::Communication {
+				msg: NetworkBridgeMessage::SendValidationMessages(vec![
+					(vec![peer_v1.clone(), peer_v2.clone()], Versioned::V2(message_v2.clone())),
+					(vec![peer_v1.clone()], Versioned::V1(message_v1.clone())),
+				]),
+			})
+			.await;
+
+		assert_eq!(
+			network_handle.next_network_actions(2).await,
+			vec![
+				NetworkAction::WriteNotification(
+					peer_v2.clone(),
+					PeerSet::Validation,
+					WireMessage::ProtocolMessage(message_v2.clone()).encode(),
+				),
+				NetworkAction::WriteNotification(
+					peer_v1.clone(),
+					PeerSet::Validation,
+					WireMessage::ProtocolMessage(message_v1.clone()).encode(),
+				),
+			],
+		);
+
+		// v2 messages which exist in v1 are downgraded for v1 peers.
+		virtual_overseer
+			.send(FromOrchestra::Communication {
+				msg: NetworkBridgeMessage::SendValidationMessage(
+					vec![peer_v1.clone()],
+					Versioned::V2(message_v1.clone().into()),
+				),
+			})
+			.await;
+
+		assert_eq!(
+			network_handle.next_network_actions(1).await,
+			vec![NetworkAction::WriteNotification(
+				peer_v1.clone(),
+				PeerSet::Validation,
+				WireMessage::ProtocolMessage(message_v1).encode(),
+			)],
+		);
+
+		// messages of v2 peers are decoded as v2.
+		network_handle
+			.peer_message(
+				peer_v2.clone(),
+				PeerSet::Validation,
+				WireMessage::ProtocolMessage(message_v2).encode(),
+			)
+			.await;
+
+		assert_matches!(
+			virtual_overseer.recv().await,
+			AllMessages::BitfieldDistribution(
+				BitfieldDistributionMessage::NetworkBridgeUpdate(
+					NetworkBridgeEvent::PeerMessage(p, Versioned::V2(m))
+				)
+			) => {
+				assert_eq!(p, peer_v2);
+				assert_eq!(
+					m,
+					protocol_v2::BitfieldDistributionMessage::Bitfields(
+						Hash::repeat_byte(1),
+						Vec::new(),
+					),
+				);
+			}
+		);
+		virtual_overseer
+	});
+}
+
 #[test]
 fn our_view_updates_decreasing_order_and_limited_to_max() {
 	test_harness(done_syncing_oracle(), |test_harness| async move {
@@ -1243,3 +1579,51 @@ fn our_view_updates_decreasing_order_and_limited_to_max() {
 		virtual_overseer
 	});
 }
+
+#[test]
+fn compressed_requests_fall_back_if_unsupported() {
+	let send_compressed = |response| {
+		let (pending_response, response_rx) = oneshot::channel();
+		let req = OutgoingRequest {
+			peer: Recipient::Peer(PeerId::random()),
+			payload: vec![1, 2, 3],
+			pending_response,
+		};
+
+		let (compressed_req, forward) =
+			with_fallback(Protocol::ChunkFetchingV1, req, IfDisconnected::ImmediateError);
+		assert_eq!(compressed_req.payload, vec![1, 2, 3]);
+		compressed_req.pending_response.send(response).unwrap();
+
+		(executor::block_on(forward), response_rx)
+	};
+
+	// Responses are forwarded to the requester.
+	let (fallback, mut response_rx) = send_compressed(Ok(vec![4, 5, 6]));
+	assert!(fallback.is_none());
+	assert_matches!(response_rx.try_recv(), Ok(Some(Ok(response))) => {
+		assert_eq!(response, vec![4, 5, 6]);
+	});
+
+	// Compressed responses are decompressed before forwarding them.
+	let response = vec![42u8; 10_000];
+	let compressed = compress_response(Protocol::ChunkFetchingV2, response.clone());
+	assert_ne!(compressed, response);
+	let (fallback, mut response_rx) = send_compressed(Ok(compressed));
+	assert!(fallback.is_none());
+	assert_matches!(response_rx.try_recv(), Ok(Some(Ok(decompressed))) => {
+		assert_eq!(decompressed, response);
+	});
+
+	// Requests to peers not supporting the protocol are sent again.
+	let (fallback, mut response_rx) = send_compressed(Err(sc_network::RequestFailure::Network(
+		sc_network::OutboundFailure::UnsupportedProtocols,
+	)));
+	assert_matches!(response_rx.try_recv(), Ok(None));
+	assert_matches!(
+		fallback,
+		Some(Fallback { protocol: Protocol::ChunkFetchingV1, req, .. }) => {
+			assert_eq!(req.payload, vec![1, 2, 3]);
+		}
+	);
+}
@@ -19,7 +19,7 @@
 use crate::Network;
 
 use core::marker::PhantomData;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use futures::channel::oneshot;
 
@@ -30,12 +30,15 @@ use polkadot_node_network_protocol::{
 	peer_set::{PeerSet, PerPeerSet},
 	PeerId,
 };
+use polkadot_node_subsystem::messages::PeerPolicy;
 use polkadot_primitives::v2::AuthorityDiscoveryId;
 
 const LOG_TARGET: &str = "parachain::validator-discovery";
 
 pub(super) struct Service<N, AD> {
 	state: PerPeerSet<StatePerPeerSet>,
+	/// Connection policies configured by the operator.
+	peer_policies: HashMap<AuthorityDiscoveryId, PeerPolicy>,
 	// PhantomData used to make the struct generic instead of having generic methods
 	_phantom: PhantomData<(N, AD)>,
 }
@@ -43,11 +46,74 @@ pub(super) struct Service<N, AD> {
 #[derive(Default)]
 struct StatePerPeerSet {
 	previously_requested: HashSet<PeerId>,
+	/// Validators of the last `ConnectToValidators` request, if there was any.
+	requested_validators: Option<Vec<AuthorityDiscoveryId>>,
 }
 
 impl<N: Network, AD: AuthorityDiscovery> Service<N, AD> {
 	pub fn new() -> Self {
-		Self { state: Default::default(), _phantom: PhantomData }
+		Self { state: Default::default(), peer_policies: HashMap::new(), _phantom: PhantomData }
+	}
+
+	/// The validators we want to be connected to on the given peer-set.
+	///
+	/// These are the validators of the last request without any banned ones. On the validation
+	/// peer-set, pinned validators are always included.
+	pub fn wanted_validators(&self, peer_set: PeerSet) -> Vec<AuthorityDiscoveryId> {
+		let mut wanted: Vec<_> = self.state[peer_set]
+			.requested_validators
+			.iter()
+			.flatten()
+			.filter(|id| self.peer_policies.get(id) != Some(&PeerPolicy::Banned))
+			.cloned()
+			.collect();
+
+		if peer_set == PeerSet::Validation {
+			for (id, policy) in &self.peer_policies {
+				if *policy == PeerPolicy::Pinned && !wanted.contains(id) {
+					wanted.push(id.clone());
+				}
+			}
+		}
+
+		wanted
+	}
+
+	/// Update the connection policies and re-issue the connection requests accordingly.
+	///
+	/// Takes `network_service` and `authority_discovery_service` by value for the same reason as
+	/// `on_request`.
+	pub async fn on_policy_change(
+		&mut self,
+		peer_policies: HashMap<AuthorityDiscoveryId, PeerPolicy>,
+		mut network_service: N,
+		mut authority_discovery_service: AD,
+	) -> (N, AD) {
+		self.peer_policies = peer_policies;
+
+		for peer_set in [PeerSet::Validation, PeerSet::Collation] {
+			let has_pinned = peer_set == PeerSet::Validation &&
+				self.peer_policies.values().any(|p| *p == PeerPolicy::Pinned);
+			if self.state[peer_set].requested_validators.is_none() && !has_pinned {
+				continue
+			}
+
+			let validator_ids = self.wanted_validators(peer_set);
+			let (failed, _) = oneshot::channel();
+			let (ns, ads) = self
+				.connect(
+					validator_ids,
+					peer_set,
+					failed,
+					network_service,
+					authority_discovery_service,
+				)
+				.await;
+			network_service = ns;
+			authority_discovery_service = ads;
+		}
+
+		(network_service, authority_discovery_service)
 	}
 
 	/// Connect to already resolved addresses.
@@ -102,7 +168,24 @@ impl<N: Network, AD: AuthorityDiscovery> Service<N, AD> {
 	/// This method will also disconnect from previously connected validators not in the `validator_ids` set.
 	/// it takes `network_service` and `authority_discovery_service` by value
 	/// and returns them as a workaround for the Future: Send requirement imposed by async function implementation.
+	///
+	/// Banned validators are left out, pinned ones are added on the validation peer-set.
 	pub async fn on_request(
+		&mut self,
+		validator_ids: Vec<AuthorityDiscoveryId>,
+		peer_set: PeerSet,
+		failed: oneshot::Sender<usize>,
+		network_service: N,
+		authority_discovery_service: AD,
+	) -> (N, AD) {
+		self.state[peer_set].requested_validators = Some(validator_ids);
+		let validator_ids = self.wanted_validators(peer_set);
+
+		self.connect(validator_ids, peer_set, failed, network_service, authority_discovery_service)
+			.await
+	}
+
+	async fn connect(
 		&mut self,
 		validator_ids: Vec<AuthorityDiscoveryId>,
 		peer_set: PeerSet,
@@ -162,7 +245,10 @@ mod tests {
 
 	use async_trait::async_trait;
 	use futures::stream::BoxStream;
-	use polkadot_node_network_protocol::{request_response::outgoing::Requests, PeerId};
+	use polkadot_node_network_protocol::{
+		request_response::{OutgoingRequest, Protocol},
+		PeerId,
+	};
 	use sc_network::{Event as NetworkEvent, IfDisconnected};
 	use sp_keyring::Sr25519Keyring;
 	use std::{
@@ -235,7 +321,8 @@ mod tests {
 		async fn start_request<AD: AuthorityDiscovery>(
 			&self,
 			_: &mut AD,
-			_: Requests,
+			_: Protocol,
+			_: OutgoingRequest<Vec<u8>>,
 			_: IfDisconnected,
 		) {
 		}
@@ -39,7 +39,8 @@ use polkadot_node_primitives::{CollationSecondedSignal, PoV, Statement};
 use polkadot_node_subsystem::{
 	jaeger,
 	messages::{
-		CollatorProtocolMessage, NetworkBridgeEvent, NetworkBridgeMessage, RuntimeApiMessage,
+		CollationDistributionInfo, CollationDistributionStatus, CollatorProtocolMessage,
+		NetworkBridgeEvent, NetworkBridgeMessage, RuntimeApiMessage,
 	},
 	overseer, FromOrchestra, OverseerSignal, PerLeafSpan,
 };
@@ -95,6 +96,13 @@ impl Metrics {
 		}
 	}
 
+	/// Note the final status of a collation, once its relay parent left our view.
+	fn on_collation_outcome(&self, status: &CollationStatus) {
+		if let Some(metrics) = &self.0 {
+			metrics.collation_outcomes.with_label_values(&[status.outcome_label()]).inc();
+		}
+	}
+
 	/// Provide a timer for `process_msg` which observes on drop.
 	fn time_process_msg(&self) -> Option<prometheus::prometheus::HistogramTimer> {
 		self.0.as_ref().map(|metrics| metrics.process_msg.start_timer())
@@ -116,6 +124,7 @@ struct MetricsInner {
 	advertisements_made: prometheus::Counter<prometheus::U64>,
 	collations_sent: prometheus::Counter<prometheus::U64>,
 	collations_send_requested: prometheus::Counter<prometheus::U64>,
+	collation_outcomes: prometheus::CounterVec<prometheus::U64>,
 	process_msg: prometheus::Histogram,
 	collation_distribution_time: prometheus::HistogramVec,
 }
@@ -146,6 +155,16 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			collation_outcomes: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_collator_protocol_collation_outcomes_total",
+						"Final distribution status of our collations per relay parent.",
+					),
+					&["outcome"],
+				)?,
+				registry,
+			)?,
 			process_msg: prometheus::register(
 				prometheus::Histogram::with_opts(
 					prometheus::HistogramOpts::new(
@@ -184,6 +203,8 @@ impl metrics::Metrics for Metrics {
 /// It keeps track to which validators we advertised our collation.
 #[derive(Debug)]
 struct ValidatorGroup {
+	/// All validators of the group assigned to our para.
+	validators: Vec<AuthorityDiscoveryId>,
 	/// All [`ValidatorId`]'s of the current group to that we advertised our collation.
 	advertised_to: HashSet<AuthorityDiscoveryId>,
 }
@@ -192,8 +213,19 @@ impl ValidatorGroup {
 	/// Create a new `ValidatorGroup`
 	///
 	/// without any advertisements.
-	fn new() -> Self {
-		Self { advertised_to: HashSet::new() }
+	fn new(validators: Vec<AuthorityDiscoveryId>) -> Self {
+		Self { validators, advertised_to: HashSet::new() }
+	}
+
+	/// Returns the number of validators of this group we are connected to.
+	fn connected_validators(
+		&self,
+		peer_ids: &HashMap<PeerId, HashSet<AuthorityDiscoveryId>>,
+	) -> usize {
+		self.validators
+			.iter()
+			.filter(|v| peer_ids.values().any(|authority_ids| authority_ids.contains(v)))
+			.count()
 	}
 
 	/// Returns `true` if we should advertise our collation to the given peer.
@@ -230,21 +262,53 @@ enum CollationStatus {
 	Advertised,
 	/// The collation was requested by at least one validator.
 	Requested,
+	/// The collation was seconded by at least one validator.
+	Seconded,
 }
 
 impl CollationStatus {
 	/// Advance to the [`Self::Advertised`] status.
 	///
-	/// This ensures that `self` isn't already [`Self::Requested`].
+	/// This ensures that `self` is still [`Self::Created`].
 	fn advance_to_advertised(&mut self) {
-		if !matches!(self, Self::Requested) {
+		if matches!(self, Self::Created) {
 			*self = Self::Advertised;
 		}
 	}
 
 	/// Advance to the [`Self::Requested`] status.
+	///
+	/// This ensures that `self` isn't already [`Self::Seconded`].
 	fn advance_to_requested(&mut self) {
-		*self = Self::Requested;
+		if !matches!(self, Self::Seconded) {
+			*self = Self::Requested;
+		}
+	}
+
+	/// Advance to the [`Self::Seconded`] status.
+	fn advance_to_seconded(&mut self) {
+		*self = Self::Seconded;
+	}
+
+	/// The label used for the outcome metric.
+	fn outcome_label(&self) -> &'static str {
+		match self {
+			Self::Created => "not_advertised",
+			Self::Advertised => "not_requested",
+			Self::Requested => "not_seconded",
+			Self::Seconded => "seconded",
+		}
+	}
+}
+
+impl From<&CollationStatus> for CollationDistributionStatus {
+	fn from(status: &CollationStatus) -> Self {
+		match status {
+			CollationStatus::Created => Self::Created,
+			CollationStatus::Advertised => Self::Advertised,
+			CollationStatus::Requested => Self::Requested,
+			CollationStatus::Seconded => Self::Seconded,
+		}
 	}
 }
 
@@ -253,6 +317,8 @@ struct Collation {
 	receipt: CandidateReceipt,
 	pov: PoV,
 	status: CollationStatus,
+	/// Validators that requested this collation.
+	requested_by: HashSet<PeerId>,
 }
 
 /// Stores the state for waiting collation fetches.
@@ -344,6 +410,26 @@ impl State {
 		}
 	}
 
+	/// Get the distribution status of all collations we are currently holding.
+	fn collation_status(&self) -> Vec<CollationDistributionInfo> {
+		self.collations
+			.iter()
+			.map(|(relay_parent, collation)| {
+				let group = self.our_validators_groups.get(relay_parent);
+				CollationDistributionInfo {
+					relay_parent: *relay_parent,
+					candidate_hash: collation.receipt.hash(),
+					status: (&collation.status).into(),
+					group_size: group.map_or(0, |g| g.validators.len()),
+					connected_validators: group
+						.map_or(0, |g| g.connected_validators(&self.peer_ids)),
+					advertised_to: group.map_or(0, |g| g.advertised_to.len()),
+					requested_by: collation.requested_by.len(),
+				}
+			})
+			.collect()
+	}
+
 	/// Get all peers which have the given relay parent in their view.
 	fn peers_interested_in_leaf(&self, relay_parent: &Hash) -> Vec<PeerId> {
 		self.peer_views
@@ -437,17 +523,20 @@ async fn distribute_collation<Context>(
 	);
 
 	// Issue a discovery request for the validators of the current group:
-	connect_to_validators(ctx, current_validators.validators.into_iter().collect()).await;
+	connect_to_validators(ctx, current_validators.validators.clone()).await;
 
-	state.our_validators_groups.insert(relay_parent, ValidatorGroup::new());
+	state
+		.our_validators_groups
+		.insert(relay_parent, ValidatorGroup::new(current_validators.validators));
 
 	if let Some(result_sender) = result_sender {
 		state.collation_result_senders.insert(receipt.hash(), result_sender);
 	}
 
-	state
-		.collations
-		.insert(relay_parent, Collation { receipt, pov, status: CollationStatus::Created });
+	state.collations.insert(
+		relay_parent,
+		Collation { receipt, pov, status: CollationStatus::Created, requested_by: HashSet::new() },
+	);
 
 	let interested = state.peers_interested_in_leaf(&relay_parent);
 	// Make sure already connected peers get collations:
@@ -672,6 +761,9 @@ async fn process_msg<Context>(
 				"ReportCollator message is not expected on the collator side of the protocol",
 			);
 		},
+		QueryCollationStatus(tx) => {
+			let _ = tx.send(state.collation_status());
+		},
 		NetworkBridgeUpdate(event) => {
 			// We should count only this shoulder in the histogram, as other shoulders are just introducing noise
 			let _ = state.metrics.time_process_msg();
@@ -785,8 +877,14 @@ async fn handle_incoming_peer_message<Context>(
 					.await?
 					.map_err(Error::InvalidStatementSignature)?;
 
-				let removed =
-					state.collation_result_senders.remove(&statement.payload().candidate_hash());
+				let candidate_hash = statement.payload().candidate_hash();
+				if let Some(collation) = state.collations.get_mut(&relay_parent) {
+					if collation.receipt.hash() == candidate_hash {
+						collation.status.advance_to_seconded();
+					}
+				}
+
+				let removed = state.collation_result_senders.remove(&candidate_hash);
 
 				if let Some(sender) = removed {
 					gum::trace!(
@@ -828,6 +926,7 @@ async fn handle_incoming_request<Context>(
 			let (receipt, pov) =
 				if let Some(collation) = state.collations.get_mut(&req.payload.relay_parent) {
 					collation.status.advance_to_requested();
+					collation.requested_by.insert(req.peer);
 					(collation.receipt.clone(), collation.pov.clone())
 				} else {
 					gum::warn!(
@@ -943,7 +1042,7 @@ async fn handle_network_msg<Context>(
 			gum::trace!(target: LOG_TARGET, ?view, "Own view change");
 			handle_our_view_change(state, view).await?;
 		},
-		PeerMessage(remote, Versioned::V1(msg)) => {
+		PeerMessage(remote, Versioned::V1(msg) | Versioned::V2(msg)) => {
 			handle_incoming_peer_message(ctx, runtime, state, remote, msg).await?;
 		},
 		NewGossipTopology { .. } => {
@@ -961,6 +1060,7 @@ async fn handle_our_view_change(state: &mut State, view: OurView) -> Result<()>
 
 		if let Some(collation) = state.collations.remove(removed) {
 			state.collation_result_senders.remove(&collation.receipt.hash());
+			state.metrics.on_collation_outcome(&collation.status);
 
 			match collation.status {
 				CollationStatus::Created => gum::warn!(
@@ -979,7 +1079,13 @@ async fn handle_our_view_change(state: &mut State, view: OurView) -> Result<()>
 					target: LOG_TARGET,
 					candidate_hash = ?collation.receipt.hash(),
 					pov_hash = ?collation.pov.hash(),
-					"Collation was requested.",
+					"Collation was requested, but not seconded.",
+				),
+				CollationStatus::Seconded => gum::debug!(
+					target: LOG_TARGET,
+					candidate_hash = ?collation.receipt.hash(),
+					pov_hash = ?collation.pov.hash(),
+					"Collation was seconded.",
 				),
 			}
 		}
@@ -493,6 +493,15 @@ async fn send_peer_view_change(
 	.await;
 }
 
+/// Query the distribution status of our collations.
+async fn query_collation_status(
+	virtual_overseer: &mut VirtualOverseer,
+) -> Vec<CollationDistributionInfo> {
+	let (tx, rx) = oneshot::channel();
+	overseer_send(virtual_overseer, CollatorProtocolMessage::QueryCollationStatus(tx)).await;
+	rx.await.expect("Collator protocol answers status queries")
+}
+
 #[test]
 fn advertise_and_send_collation() {
 	let mut test_state = TestState::default();
@@ -724,6 +733,52 @@ fn collations_are_only_advertised_to_validators_with_correct_view() {
 	})
 }
 
+#[test]
+fn collation_status_query_reports_progress() {
+	let test_state = TestState::default();
+	let local_peer_id = test_state.local_peer_id.clone();
+	let collator_pair = test_state.collator_pair.clone();
+
+	test_harness(local_peer_id, collator_pair, |mut test_harness| async move {
+		let virtual_overseer = &mut test_harness.virtual_overseer;
+
+		let peer = test_state.current_group_validator_peer_ids()[0].clone();
+		let validator_id = test_state.current_group_validator_authority_ids()[0].clone();
+
+		setup_system(virtual_overseer, &test_state).await;
+
+		connect_peer(virtual_overseer, peer.clone(), Some(validator_id)).await;
+		expect_declare_msg(virtual_overseer, &test_state, &peer).await;
+
+		let DistributeCollation { candidate, .. } =
+			distribute_collation(virtual_overseer, &test_state, true).await;
+
+		let expected = CollationDistributionInfo {
+			relay_parent: test_state.relay_parent,
+			candidate_hash: candidate.hash(),
+			status: CollationDistributionStatus::Created,
+			group_size: test_state.current_group_validator_authority_ids().len(),
+			connected_validators: 1,
+			advertised_to: 0,
+			requested_by: 0,
+		};
+		assert_eq!(query_collation_status(virtual_overseer).await, vec![expected.clone()]);
+
+		send_peer_view_change(virtual_overseer, &peer, vec![test_state.relay_parent]).await;
+		expect_advertise_collation_msg(virtual_overseer, &peer, test_state.relay_parent).await;
+
+		assert_eq!(
+			query_collation_status(virtual_overseer).await,
+			vec![CollationDistributionInfo {
+				status: CollationDistributionStatus::Advertised,
+				advertised_to: 1,
+				..expected
+			}],
+		);
+		test_harness
+	})
+}
+
 #[test]
 fn collate_on_two_different_relay_chain_blocks() {
 	let mut test_state = TestState::default();
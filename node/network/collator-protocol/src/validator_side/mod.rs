@@ -23,8 +23,9 @@ use futures::{
 	FutureExt, StreamExt,
 };
 use futures_timer::Delay;
+use lru::LruCache;
 use std::{
-	collections::{hash_map::Entry, HashMap, HashSet},
+	collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
 	sync::Arc,
 	task::Poll,
 	time::{Duration, Instant},
@@ -71,6 +72,10 @@ const COST_INVALID_SIGNATURE: Rep = Rep::Malicious("Invalid network message sign
 const COST_REPORT_BAD: Rep = Rep::Malicious("A collator was reported by another subsystem");
 const COST_WRONG_PARA: Rep = Rep::Malicious("A collator provided a collation for the wrong para");
 const COST_UNNEEDED_COLLATOR: Rep = Rep::CostMinor("An unneeded collator connected");
+const COST_ADVERTISEMENT_RATE_LIMITED: Rep =
+	Rep::CostMinor("A collator sent advertisements faster than allowed");
+const COST_UNRELIABLE_COLLATOR: Rep =
+	Rep::CostMinor("A collator repeatedly failed to deliver advertised collations");
 const BENEFIT_NOTIFY_GOOD: Rep =
 	Rep::BenefitMinor("A collator was noted good by another subsystem");
 
@@ -87,6 +92,23 @@ const BENEFIT_NOTIFY_GOOD: Rep =
 /// There is debug logging output, so we can adjust this value based on production results.
 const MAX_UNSHARED_DOWNLOAD_TIME: Duration = Duration::from_millis(400);
 
+/// Maximum number of advertisements we accept from a single peer within
+/// `ADVERTISEMENT_RATE_WINDOW`.
+///
+/// A well-behaved collator advertises at most once per relay parent, so this leaves room for a
+/// couple of forks while bounding the work a single peer can cause us.
+const MAX_ADVERTISEMENTS_PER_WINDOW: usize = 8;
+
+/// The sliding window used for rate limiting advertisements.
+const ADVERTISEMENT_RATE_WINDOW: Duration = Duration::from_secs(6);
+
+/// Number of consecutive failed deliveries after which a collator gets its reputation reduced on
+/// every further failure.
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// The number of collators to keep delivery statistics for, including disconnected ones.
+const MAX_TRACKED_COLLATORS: usize = 1024;
+
 // How often to check all peers with activity.
 #[cfg(not(test))]
 const ACTIVITY_POLL: Duration = Duration::from_secs(1);
@@ -222,9 +244,34 @@ struct CollatingPeerState {
 	para_id: ParaId,
 	// Advertised relay parents.
 	advertisements: HashSet<Hash>,
+	// Instants of advertisements received within the current `ADVERTISEMENT_RATE_WINDOW`.
+	recent_advertisements: VecDeque<Instant>,
 	last_active: Instant,
 }
 
+impl CollatingPeerState {
+	/// Note an incoming advertisement for rate limiting purposes.
+	///
+	/// Returns `false` if the peer already exhausted its advertisement budget for the current
+	/// window.
+	fn note_advertisement_attempt(&mut self, now: Instant) -> bool {
+		while self
+			.recent_advertisements
+			.front()
+			.map_or(false, |t| now.duration_since(*t) >= ADVERTISEMENT_RATE_WINDOW)
+		{
+			self.recent_advertisements.pop_front();
+		}
+
+		if self.recent_advertisements.len() >= MAX_ADVERTISEMENTS_PER_WINDOW {
+			return false
+		}
+
+		self.recent_advertisements.push_back(now);
+		true
+	}
+}
+
 #[derive(Debug)]
 enum PeerState {
 	// The peer has connected at the given instant.
@@ -238,6 +285,7 @@ enum AdvertisementError {
 	Duplicate,
 	OutOfOurView,
 	UndeclaredCollator,
+	RateLimited,
 }
 
 #[derive(Debug)]
@@ -270,8 +318,8 @@ impl PeerData {
 	}
 
 	/// Note an advertisement by the collator. Returns `true` if the advertisement was imported
-	/// successfully. Fails if the advertisement is duplicate, out of view, or the peer has not
-	/// declared itself a collator.
+	/// successfully. Fails if the peer has not declared itself a collator, exceeded its
+	/// advertisement rate, or the advertisement is out of view or a duplicate.
 	fn insert_advertisement(
 		&mut self,
 		on_relay_parent: Hash,
@@ -279,14 +327,19 @@ impl PeerData {
 	) -> std::result::Result<(CollatorId, ParaId), AdvertisementError> {
 		match self.state {
 			PeerState::Connected(_) => Err(AdvertisementError::UndeclaredCollator),
-			_ if !our_view.contains(&on_relay_parent) => Err(AdvertisementError::OutOfOurView),
-			PeerState::Collating(ref mut state) =>
-				if state.advertisements.insert(on_relay_parent) {
-					state.last_active = Instant::now();
+			PeerState::Collating(ref mut state) => {
+				let now = Instant::now();
+				if !state.note_advertisement_attempt(now) {
+					Err(AdvertisementError::RateLimited)
+				} else if !our_view.contains(&on_relay_parent) {
+					Err(AdvertisementError::OutOfOurView)
+				} else if state.advertisements.insert(on_relay_parent) {
+					state.last_active = now;
 					Ok((state.collator_id.clone(), state.para_id.clone()))
 				} else {
 					Err(AdvertisementError::Duplicate)
-				},
+				}
+			},
 		}
 	}
 
@@ -307,6 +360,7 @@ impl PeerData {
 			collator_id,
 			para_id,
 			advertisements: HashSet::new(),
+			recent_advertisements: VecDeque::new(),
 			last_active: Instant::now(),
 		});
 	}
@@ -525,7 +579,61 @@ impl CollationStatus {
 	}
 }
 
+/// Delivery statistics of a collator, used for picking the next collation to fetch and for
+/// adjusting reputation.
+#[derive(Debug, Default, Clone, Copy)]
+struct CollatorStats {
+	/// Number of collations fetched successfully.
+	delivered: u32,
+	/// Number of collations the collator failed to deliver in time or at all.
+	failed: u32,
+	/// Number of failed deliveries since the last successful one.
+	consecutive_failures: u32,
+}
+
+/// Delivery statistics of recently connected collators.
+///
+/// These are kept after a collator disconnects, so reconnecting doesn't reset them.
+struct CollatorStatsCache(LruCache<CollatorId, CollatorStats>);
+
+impl Default for CollatorStatsCache {
+	fn default() -> Self {
+		CollatorStatsCache(LruCache::new(MAX_TRACKED_COLLATORS))
+	}
+}
+
+impl CollatorStatsCache {
+	fn get(&self, collator_id: &CollatorId) -> Option<&CollatorStats> {
+		self.0.peek(collator_id)
+	}
+
+	fn get_or_insert_mut(&mut self, collator_id: &CollatorId) -> &mut CollatorStats {
+		if !self.0.contains(collator_id) {
+			self.0.put(collator_id.clone(), CollatorStats::default());
+		}
+
+		self.0.get_mut(collator_id).expect("inserted above; qed")
+	}
+}
+
+impl CollatorStats {
+	fn note_delivered(&mut self) {
+		self.delivered = self.delivered.saturating_add(1);
+		self.consecutive_failures = 0;
+	}
+
+	/// Note a failed delivery. Returns `true` if the collator should be punished for it.
+	fn note_failed(&mut self) -> bool {
+		self.failed = self.failed.saturating_add(1);
+		self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+		self.consecutive_failures >= MAX_CONSECUTIVE_FAILURES
+	}
+}
+
 /// Information about collations per relay parent.
+///
+/// As we are assigned to a single para per relay parent, this is also the queue of collations
+/// advertised for that para.
 #[derive(Default)]
 struct CollationsPerRelayParent {
 	/// What is the current status in regards to a collation for this relay parent?
@@ -535,7 +643,7 @@ struct CollationsPerRelayParent {
 	/// This is the currently last started fetch, which did not exceed `MAX_UNSHARED_DOWNLOAD_TIME`
 	/// yet.
 	waiting_collation: Option<CollatorId>,
-	/// Collation that were advertised to us, but we did not yet fetch.
+	/// Collations that were advertised to us, but we did not yet fetch, in order of arrival.
 	unfetched_collations: Vec<(PendingCollation, CollatorId)>,
 }
 
@@ -546,9 +654,13 @@ impl CollationsPerRelayParent {
 	///
 	/// Returns `Some(_)` if there is any collation to fetch, the `status` is not `Seconded` and
 	/// the passed in `finished_one` is the currently `waiting_collation`.
+	///
+	/// Collators which recently failed to deliver are only picked once all other collators
+	/// have had their chance, otherwise collations are fetched in order of advertisement.
 	pub fn get_next_collation_to_fetch(
 		&mut self,
 		finished_one: Option<&CollatorId>,
+		collator_stats: &CollatorStatsCache,
 	) -> Option<(PendingCollation, CollatorId)> {
 		// If finished one does not match waiting_collation, then we already dequeued another fetch
 		// to replace it.
@@ -567,7 +679,18 @@ impl CollationsPerRelayParent {
 			// We don't need to fetch any other collation when we already have seconded one.
 			CollationStatus::Seconded => None,
 			CollationStatus::Waiting => {
-				let next = self.unfetched_collations.pop();
+				let next = self
+					.unfetched_collations
+					.iter()
+					.enumerate()
+					.min_by_key(|(index, (_, collator_id))| {
+						let failures = collator_stats
+							.get(collator_id)
+							.map_or(0, |stats| stats.consecutive_failures);
+						(failures, *index)
+					})
+					.map(|(index, _)| index)
+					.map(|index| self.unfetched_collations.remove(index));
 				self.waiting_collation = next.as_ref().map(|(_, collator_id)| collator_id.clone());
 				next
 			},
@@ -617,6 +740,9 @@ struct State {
 
 	/// Keep track of all pending candidate collations
 	pending_candidates: HashMap<Hash, CollationEvent>,
+
+	/// Delivery statistics of collators.
+	collator_stats: CollatorStatsCache,
 }
 
 // O(n) search for collator ID by iterating through the peers map. This should be fast enough
@@ -702,6 +828,35 @@ async fn note_good_collation(
 	}
 }
 
+/// A collator failed to deliver a requested collation: the request failed, timed out or the
+/// response was invalid. This is noted once per request, the collation fetch timeout of
+/// `MAX_UNSHARED_DOWNLOAD_TIME` doesn't count as a failure.
+///
+/// Collators failing repeatedly get their reputation reduced.
+async fn note_failed_delivery(
+	sender: &mut impl overseer::CollatorProtocolSenderTrait,
+	state: &mut State,
+	peer_id: PeerId,
+) {
+	// Stats are only updated for connected collators.
+	let id = match state.peer_data.get(&peer_id).and_then(|data| data.collator_id()) {
+		Some(id) => id.clone(),
+		None => return,
+	};
+
+	let stats = state.collator_stats.get_or_insert_mut(&id);
+	if stats.note_failed() {
+		gum::debug!(
+			target: LOG_TARGET,
+			?peer_id,
+			collator_id = ?id,
+			?stats,
+			"Collator repeatedly failed to deliver collations",
+		);
+		modify_reputation(sender, peer_id, COST_UNRELIABLE_COLLATOR).await;
+	}
+}
+
 /// Notify a collator that its collation got seconded.
 async fn notify_collation_seconded(
 	sender: &mut impl overseer::CollatorProtocolSenderTrait,
@@ -968,7 +1123,11 @@ async fn process_incoming_peer_message<Context>(
 						"Invalid advertisement",
 					);
 
-					modify_reputation(ctx.sender(), origin, COST_UNEXPECTED_MESSAGE).await;
+					let rep = match error {
+						AdvertisementError::RateLimited => COST_ADVERTISEMENT_RATE_LIMITED,
+						_ => COST_UNEXPECTED_MESSAGE,
+					};
+					modify_reputation(ctx.sender(), origin, rep).await;
 				},
 			}
 		},
@@ -1078,7 +1237,7 @@ async fn handle_network_msg<Context>(
 		OurViewChange(view) => {
 			handle_our_view_change(ctx, state, keystore, view).await?;
 		},
-		PeerMessage(remote, Versioned::V1(msg)) => {
+		PeerMessage(remote, Versioned::V1(msg) | Versioned::V2(msg)) => {
 			process_incoming_peer_message(ctx, state, remote, msg).await;
 		},
 	}
@@ -1112,6 +1271,12 @@ async fn process_msg<Context>(
 				"DistributeCollation message is not expected on the validator side of the protocol",
 			);
 		},
+		QueryCollationStatus(_) => {
+			gum::warn!(
+				target: LOG_TARGET,
+				"QueryCollationStatus message is not expected on the validator side of the protocol",
+			);
+		},
 		ReportCollator(id) => {
 			report_collator(ctx.sender(), &state.peer_data, id).await;
 		},
@@ -1236,14 +1401,17 @@ pub(crate) async fn run<Context>(
 				dequeue_next_collation_and_fetch(&mut ctx, &mut state, relay_parent, collator_id).await;
 			}
 			_ = check_collations_stream.next() => {
-				let reputation_changes = poll_requests(
+				let failed_fetches = poll_requests(
 					&mut state.requested_collations,
 					&state.metrics,
 					&state.span_per_relay_parent,
 				).await;
 
-				for (peer_id, rep) in reputation_changes {
-					modify_reputation(ctx.sender(), peer_id, rep).await;
+				for (peer_id, rep) in failed_fetches {
+					if let Some(rep) = rep {
+						modify_reputation(ctx.sender(), peer_id.clone(), rep).await;
+					}
+					note_failed_delivery(ctx.sender(), &mut state, peer_id).await;
 				}
 			},
 		}
@@ -1256,9 +1424,9 @@ async fn poll_requests(
 	requested_collations: &mut HashMap<PendingCollation, PerRequest>,
 	metrics: &Metrics,
 	span_per_relay_parent: &HashMap<Hash, PerLeafSpan>,
-) -> Vec<(PeerId, Rep)> {
+) -> Vec<(PeerId, Option<Rep>)> {
 	let mut retained_requested = HashSet::new();
-	let mut reputation_changes = Vec::new();
+	let mut failed_fetches = Vec::new();
 	for (pending_collation, per_req) in requested_collations.iter_mut() {
 		// Despite the await, this won't block on the response itself.
 		let result =
@@ -1268,12 +1436,12 @@ async fn poll_requests(
 		if !result.is_ready() {
 			retained_requested.insert(pending_collation.clone());
 		}
-		if let CollationFetchResult::Error(Some(rep)) = result {
-			reputation_changes.push((pending_collation.peer_id.clone(), rep));
+		if let CollationFetchResult::Error(rep) = result {
+			failed_fetches.push((pending_collation.peer_id.clone(), rep));
 		}
 	}
 	requested_collations.retain(|k, _| retained_requested.contains(k));
-	reputation_changes
+	failed_fetches
 }
 
 /// Dequeue another collation and fetch.
@@ -1288,7 +1456,7 @@ async fn dequeue_next_collation_and_fetch<Context>(
 	if let Some((next, id)) = state
 		.collations_per_relay_parent
 		.get_mut(&relay_parent)
-		.and_then(|c| c.get_next_collation_to_fetch(Some(&previous_fetch)))
+		.and_then(|c| c.get_next_collation_to_fetch(Some(&previous_fetch), &state.collator_stats))
 	{
 		gum::debug!(
 			target: LOG_TARGET,
@@ -1337,6 +1505,10 @@ async fn handle_collation_fetched_result<Context>(
 		},
 	};
 
+	if collator_peer_id(&state.peer_data, &collation_event.0).is_some() {
+		state.collator_stats.get_or_insert_mut(&collation_event.0).note_delivered();
+	}
+
 	if let Some(collations) = state.collations_per_relay_parent.get_mut(&relay_parent) {
 		if let CollationStatus::Seconded = collations.status {
 			gum::debug!(
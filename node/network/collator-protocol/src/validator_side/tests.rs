@@ -668,6 +668,290 @@ fn fetch_collations_works() {
 	});
 }
 
+// Ensure that collators exceeding `MAX_UNSHARED_DOWNLOAD_TIME` aren't counted as failing.
+#[test]
+fn slow_collators_are_not_counted_as_failing() {
+	let test_state = TestState::default();
+
+	test_harness(|test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		let second = Hash::random();
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::OurViewChange(
+				our_view![test_state.relay_parent, second],
+			)),
+		)
+		.await;
+
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+
+		let peer_b = PeerId::random();
+		let peer_c = PeerId::random();
+		let peer_d = PeerId::random();
+
+		for (peer, collator) in
+			[&peer_b, &peer_c, &peer_d].into_iter().zip(test_state.collators.iter())
+		{
+			connect_and_declare_collator(
+				&mut virtual_overseer,
+				peer.clone(),
+				collator.clone(),
+				test_state.chain_ids[0].clone(),
+			)
+			.await;
+		}
+
+		advertise_collation(&mut virtual_overseer, peer_b.clone(), test_state.relay_parent).await;
+		advertise_collation(&mut virtual_overseer, peer_c.clone(), test_state.relay_parent).await;
+
+		let response_channel = assert_fetch_collation_request(
+			&mut virtual_overseer,
+			test_state.relay_parent,
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		// The collation of `peer_c` is fetched in parallel once `peer_b` is too slow.
+		Delay::new(MAX_UNSHARED_DOWNLOAD_TIME + Duration::from_millis(50)).await;
+
+		let _response_channel_c = assert_fetch_collation_request(
+			&mut virtual_overseer,
+			test_state.relay_parent,
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		// `peer_b` still delivers.
+		let pov = PoV { block_data: BlockData(vec![]) };
+		let mut candidate_a =
+			dummy_candidate_receipt_bad_sig(dummy_hash(), Some(Default::default()));
+		candidate_a.descriptor.para_id = test_state.chain_ids[0];
+		candidate_a.descriptor.relay_parent = test_state.relay_parent;
+		response_channel
+			.send(Ok(
+				CollationFetchingResponse::Collation(candidate_a.clone(), pov.clone()).encode()
+			))
+			.expect("Sending response should succeed");
+
+		assert_candidate_backing_second(
+			&mut virtual_overseer,
+			test_state.relay_parent,
+			test_state.chain_ids[0],
+			&pov,
+		)
+		.await;
+
+		advertise_collation(&mut virtual_overseer, peer_b.clone(), second).await;
+		advertise_collation(&mut virtual_overseer, peer_d.clone(), second).await;
+
+		// `peer_b` advertised first and has no failed deliveries, so it is fetched from first.
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridge(NetworkBridgeMessage::SendRequests(reqs, IfDisconnected::ImmediateError)
+		) => {
+			assert_matches!(
+				reqs.into_iter().next(),
+				Some(Requests::CollationFetchingV1(req)) => {
+					assert_eq!(req.peer, Recipient::Peer(peer_b));
+					assert_eq!(req.payload.relay_parent, second);
+				}
+			);
+		});
+
+		virtual_overseer
+	});
+}
+
+// Ensure that collators which failed to deliver are tried last.
+#[test]
+fn unreliable_collators_are_fetched_from_last() {
+	let test_state = TestState::default();
+
+	test_harness(|test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		let second = Hash::random();
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::OurViewChange(
+				our_view![test_state.relay_parent, second],
+			)),
+		)
+		.await;
+
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+
+		let peer_b = PeerId::random();
+		let peer_c = PeerId::random();
+		let peer_d = PeerId::random();
+		let peer_e = PeerId::random();
+
+		for (peer, collator) in [&peer_b, &peer_c, &peer_d, &peer_e]
+			.into_iter()
+			.zip(test_state.collators.iter())
+		{
+			connect_and_declare_collator(
+				&mut virtual_overseer,
+				peer.clone(),
+				collator.clone(),
+				test_state.chain_ids[0].clone(),
+			)
+			.await;
+		}
+
+		advertise_collation(&mut virtual_overseer, peer_b.clone(), test_state.relay_parent).await;
+		advertise_collation(&mut virtual_overseer, peer_c.clone(), test_state.relay_parent).await;
+
+		// Dropping the response channel makes the first collator fail its delivery.
+		assert_fetch_collation_request(
+			&mut virtual_overseer,
+			test_state.relay_parent,
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		let response_channel = assert_fetch_collation_request(
+			&mut virtual_overseer,
+			test_state.relay_parent,
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		let pov = PoV { block_data: BlockData(vec![]) };
+		let mut candidate_a =
+			dummy_candidate_receipt_bad_sig(dummy_hash(), Some(Default::default()));
+		candidate_a.descriptor.para_id = test_state.chain_ids[0];
+		candidate_a.descriptor.relay_parent = test_state.relay_parent;
+		response_channel
+			.send(Ok(
+				CollationFetchingResponse::Collation(candidate_a.clone(), pov.clone()).encode()
+			))
+			.expect("Sending response should succeed");
+
+		assert_candidate_backing_second(
+			&mut virtual_overseer,
+			test_state.relay_parent,
+			test_state.chain_ids[0],
+			&pov,
+		)
+		.await;
+
+		// Reconnecting doesn't reset the statistics of the collator.
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::PeerDisconnected(
+				peer_b.clone(),
+			)),
+		)
+		.await;
+
+		let peer_b = PeerId::random();
+		connect_and_declare_collator(
+			&mut virtual_overseer,
+			peer_b.clone(),
+			test_state.collators[0].clone(),
+			test_state.chain_ids[0].clone(),
+		)
+		.await;
+
+		advertise_collation(&mut virtual_overseer, peer_c.clone(), second).await;
+		advertise_collation(&mut virtual_overseer, peer_b.clone(), second).await;
+		advertise_collation(&mut virtual_overseer, peer_d.clone(), second).await;
+		advertise_collation(&mut virtual_overseer, peer_e.clone(), second).await;
+
+		let _response_channel =
+			assert_fetch_collation_request(&mut virtual_overseer, second, test_state.chain_ids[0])
+				.await;
+
+		// `peer_b` advertised first, but failed to deliver before, so `peer_d` is next in line.
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridge(NetworkBridgeMessage::SendRequests(reqs, IfDisconnected::ImmediateError)
+		) => {
+			assert_matches!(
+				reqs.into_iter().next(),
+				Some(Requests::CollationFetchingV1(req)) => {
+					assert_eq!(req.peer, Recipient::Peer(peer_d));
+					assert_eq!(req.payload.relay_parent, second);
+				}
+			);
+		});
+
+		virtual_overseer
+	});
+}
+
+// Ensure that a peer sending too many advertisements gets reported.
+#[test]
+fn advertisements_are_rate_limited() {
+	let test_state = TestState::default();
+
+	test_harness(|test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::OurViewChange(
+				our_view![test_state.relay_parent],
+			)),
+		)
+		.await;
+
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+
+		let peer_b = PeerId::random();
+
+		connect_and_declare_collator(
+			&mut virtual_overseer,
+			peer_b.clone(),
+			test_state.collators[0].clone(),
+			test_state.chain_ids[0].clone(),
+		)
+		.await;
+
+		advertise_collation(&mut virtual_overseer, peer_b.clone(), test_state.relay_parent).await;
+
+		let _response_channel = assert_fetch_collation_request(
+			&mut virtual_overseer,
+			test_state.relay_parent,
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		// Duplicates count towards the limit as well.
+		for _ in 1..MAX_ADVERTISEMENTS_PER_WINDOW {
+			advertise_collation(&mut virtual_overseer, peer_b.clone(), test_state.relay_parent)
+				.await;
+
+			assert_matches!(
+				overseer_recv(&mut virtual_overseer).await,
+				AllMessages::NetworkBridge(NetworkBridgeMessage::ReportPeer(peer, rep)) => {
+					assert_eq!(peer, peer_b);
+					assert_eq!(rep, COST_UNEXPECTED_MESSAGE);
+				}
+			);
+		}
+
+		advertise_collation(&mut virtual_overseer, peer_b.clone(), test_state.relay_parent).await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridge(NetworkBridgeMessage::ReportPeer(peer, rep)) => {
+				assert_eq!(peer, peer_b);
+				assert_eq!(rep, COST_ADVERTISEMENT_RATE_LIMITED);
+			}
+		);
+
+		virtual_overseer
+	});
+}
+
 #[test]
 fn reject_connection_to_next_group() {
 	let test_state = TestState::default();
@@ -27,7 +27,8 @@
 use std::{
 	collections::{HashMap, HashSet},
 	fmt,
-	time::{Duration, Instant},
+	sync::Arc,
+	time::{Duration, Instant, SystemTime},
 };
 
 use futures::{channel::oneshot, select, FutureExt as _};
@@ -40,13 +41,15 @@ use sp_application_crypto::{AppKey, ByteArray};
 use sp_keystore::{CryptoStore, SyncCryptoStorePtr};
 
 use polkadot_node_network_protocol::{
-	authority_discovery::AuthorityDiscovery, peer_set::PeerSet, GossipSupportNetworkMessage,
-	PeerId, Versioned,
+	authority_discovery::AuthorityDiscovery,
+	grid_topology::{Matrix, TopologyStrategy},
+	peer_set::PeerSet,
+	GossipSupportNetworkMessage, PeerId, Versioned,
 };
 use polkadot_node_subsystem::{
 	messages::{
-		GossipSupportMessage, NetworkBridgeEvent, NetworkBridgeMessage, RuntimeApiMessage,
-		RuntimeApiRequest,
+		AuthorityConnectivity, ConnectivityReport, GossipSupportMessage, NetworkBridgeEvent,
+		NetworkBridgeMessage, RuntimeApiMessage, RuntimeApiRequest,
 	},
 	overseer, ActiveLeavesUpdate, FromOrchestra, OverseerSignal, SpawnedSubsystem, SubsystemError,
 };
@@ -79,7 +82,17 @@ const LOW_CONNECTIVITY_WARN_DELAY: Duration = Duration::from_secs(600);
 /// If connectivity is lower than this in percent, issue warning in logs.
 const LOW_CONNECTIVITY_WARN_THRESHOLD: usize = 90;
 
+/// Connectivity statistics of a single authority.
+#[derive(Debug, Default, Clone)]
+struct ConnectivityStats {
+	/// The last time we saw the authority connected.
+	last_connected: Option<SystemTime>,
+	/// Failures to resolve or connect to the authority since it was last connected.
+	failures: u32,
+}
+
 /// The Gossip Support subsystem.
+#[derive(Clone)]
 pub struct GossipSupport<AD> {
 	keystore: SyncCryptoStorePtr,
 
@@ -107,20 +120,45 @@ pub struct GossipSupport<AD> {
 	///
 	/// Needed for efficient handling of disconnect events.
 	connected_authorities_by_peer_id: HashMap<PeerId, HashSet<AuthorityDiscoveryId>>,
+	/// Connectivity statistics of all authorities of the last connection request.
+	authority_connectivity: HashMap<AuthorityDiscoveryId, ConnectivityStats>,
 	/// Authority discovery service.
 	authority_discovery: AD,
 
+	/// How validators are arranged into the gossip topology.
+	topology_strategy: Arc<dyn TopologyStrategy>,
+
 	/// Subsystem metrics.
 	metrics: Metrics,
 }
 
+/// Configuration of the gossip support subsystem.
+#[derive(Clone)]
+pub struct Config {
+	/// How validators are arranged into the gossip topology.
+	///
+	/// All validators need to use the same strategy.
+	pub topology_strategy: Arc<dyn TopologyStrategy>,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Config { topology_strategy: Arc::new(Matrix) }
+	}
+}
+
 #[overseer::contextbounds(GossipSupport, prefix = self::overseer)]
 impl<AD> GossipSupport<AD>
 where
 	AD: AuthorityDiscovery,
 {
 	/// Create a new instance of the [`GossipSupport`] subsystem.
-	pub fn new(keystore: SyncCryptoStorePtr, authority_discovery: AD, metrics: Metrics) -> Self {
+	pub fn new(
+		keystore: SyncCryptoStorePtr,
+		authority_discovery: AD,
+		config: Config,
+		metrics: Metrics,
+	) -> Self {
 		// Initialize metrics to `0`.
 		metrics.on_is_not_authority();
 		metrics.on_is_not_parachain_validator();
@@ -133,7 +171,9 @@ where
 			resolved_authorities: HashMap::new(),
 			connected_authorities: HashMap::new(),
 			connected_authorities_by_peer_id: HashMap::new(),
+			authority_connectivity: HashMap::new(),
 			authority_discovery,
+			topology_strategy: config.topology_strategy,
 			metrics,
 		}
 	}
@@ -167,6 +207,11 @@ where
 				FromOrchestra::Communication {
 					msg: GossipSupportMessage::NetworkBridgeUpdate(ev),
 				} => self.handle_connect_disconnect(ev),
+				FromOrchestra::Communication {
+					msg: GossipSupportMessage::GetConnectivityReport(tx),
+				} => {
+					let _ = tx.send(self.connectivity_report());
+				},
 				FromOrchestra::Signal(OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
 					activated,
 					..
@@ -265,6 +310,7 @@ where
 
 					update_gossip_topology(
 						sender,
+						&*self.topology_strategy,
 						our_index,
 						session_info.discovery_keys,
 						relay_parent,
@@ -319,22 +365,29 @@ where
 		let mut validator_addrs = Vec::with_capacity(authorities.len());
 		let mut failures = 0;
 		let mut resolved = HashMap::with_capacity(authorities.len());
+		let mut connectivity = HashMap::with_capacity(authorities.len());
 		for authority in authorities {
+			let mut stats = self.authority_connectivity.remove(&authority).unwrap_or_default();
 			if let Some(addrs) =
 				self.authority_discovery.get_addresses_by_authority_id(authority.clone()).await
 			{
 				validator_addrs.push(addrs.clone());
-				resolved.insert(authority, addrs);
+				resolved.insert(authority.clone(), addrs);
 			} else {
 				failures += 1;
+				if !self.connected_authorities.contains_key(&authority) {
+					stats.failures = stats.failures.saturating_add(1);
+				}
 				gum::debug!(
 					target: LOG_TARGET,
 					"Couldn't resolve addresses of authority: {:?}",
 					authority
 				);
 			}
+			connectivity.insert(authority, stats);
 		}
 		self.resolved_authorities = resolved;
+		self.authority_connectivity = connectivity;
 		gum::debug!(target: LOG_TARGET, %num, "Issuing a connection request");
 
 		sender
@@ -378,8 +431,13 @@ where
 		match ev {
 			NetworkBridgeEvent::PeerConnected(peer_id, _, _, o_authority) => {
 				if let Some(authority_ids) = o_authority {
+					let now = SystemTime::now();
 					authority_ids.iter().for_each(|a| {
 						self.connected_authorities.insert(a.clone(), peer_id);
+						if let Some(stats) = self.authority_connectivity.get_mut(a) {
+							stats.last_connected = Some(now);
+							stats.failures = 0;
+						}
 					});
 					self.connected_authorities_by_peer_id.insert(peer_id, authority_ids);
 				}
@@ -387,24 +445,47 @@ where
 			NetworkBridgeEvent::PeerDisconnected(peer_id) => {
 				if let Some(authority_ids) = self.connected_authorities_by_peer_id.remove(&peer_id)
 				{
+					let now = SystemTime::now();
 					authority_ids.into_iter().for_each(|a| {
 						self.connected_authorities.remove(&a);
+						if let Some(stats) = self.authority_connectivity.get_mut(&a) {
+							stats.last_connected = Some(now);
+						}
 					});
 				}
 			},
 			NetworkBridgeEvent::OurViewChange(_) => {},
 			NetworkBridgeEvent::PeerViewChange(_, _) => {},
 			NetworkBridgeEvent::NewGossipTopology { .. } => {},
-			NetworkBridgeEvent::PeerMessage(_, Versioned::V1(v)) => {
+			NetworkBridgeEvent::PeerMessage(_, Versioned::V1(v) | Versioned::V2(v)) => {
 				match v {};
 			},
 		}
 	}
 
-	/// Check connectivity and report on it in logs.
+	/// Check connectivity and report on it in logs and metrics.
 	fn check_connectivity(&mut self) {
+		let now = SystemTime::now();
+		let mut wanted_connected = 0;
+		for (authority, stats) in &mut self.authority_connectivity {
+			if self.connected_authorities.contains_key(authority) {
+				stats.last_connected = Some(now);
+				wanted_connected += 1;
+			} else if self.resolved_authorities.contains_key(authority) {
+				stats.failures = stats.failures.saturating_add(1);
+			}
+		}
+
 		let absolute_connected = self.connected_authorities.len();
 		let absolute_resolved = self.resolved_authorities.len();
+		if let Some(session) = self.last_session_index {
+			self.metrics.on_connectivity_check(
+				session,
+				self.authority_connectivity.len(),
+				absolute_resolved,
+				wanted_connected,
+			);
+		}
 		let connected_ratio =
 			(100 * absolute_connected).checked_div(absolute_resolved).unwrap_or(100);
 		let unconnected_authorities = self
@@ -430,6 +511,23 @@ where
 			"Connectivity Report"
 		);
 	}
+
+	/// A report on our connectivity to the authorities of the last connection request.
+	fn connectivity_report(&self) -> ConnectivityReport {
+		let authorities = self
+			.authority_connectivity
+			.iter()
+			.map(|(authority, stats)| AuthorityConnectivity {
+				authority: authority.clone(),
+				addresses: self.resolved_authorities.get(authority).cloned().unwrap_or_default(),
+				connected: self.connected_authorities.contains_key(authority),
+				last_connected: stats.last_connected,
+				failures: stats.failures,
+			})
+			.collect();
+
+		ConnectivityReport { session: self.last_session_index, authorities }
+	}
 }
 
 // Get the authorities of the past, present, and future.
@@ -479,16 +577,20 @@ async fn remove_all_controlled(
 	to_remove.len()
 }
 
-/// We partition the list of all sorted `authorities` into `sqrt(len)` groups of `sqrt(len)` size
-/// and form a matrix where each validator is connected to all validators in its row and column.
-/// This is similar to `[web3]` research proposed topology, except for the groups are not parachain
-/// groups (because not all validators are parachain validators and the group size is small),
-/// but formed randomly via BABE randomness from two epochs ago.
-/// This limits the amount of gossip peers to 2 * `sqrt(len)` and ensures the diameter of 2.
+/// We shuffle the list of all sorted `authorities` via BABE randomness from two epochs ago and
+/// arrange them into a topology with the given `strategy`.
+///
+/// With the default [`Matrix`] strategy, we partition the shuffled list into `sqrt(len)` groups of
+/// `sqrt(len)` size and form a matrix where each validator is connected to all validators in its
+/// row and column. This is similar to `[web3]` research proposed topology, except for the groups
+/// are not parachain groups (because not all validators are parachain validators and the group
+/// size is small). This limits the amount of gossip peers to 2 * `sqrt(len)` and ensures the
+/// diameter of 2.
 ///
 /// [web3]: https://research.web3.foundation/en/latest/polkadot/networking/3-avail-valid.html#topology
 async fn update_gossip_topology(
 	sender: &mut impl overseer::GossipSupportSenderTrait,
+	strategy: &dyn TopologyStrategy,
 	our_index: usize,
 	authorities: Vec<AuthorityDiscoveryId>,
 	relay_parent: Hash,
@@ -524,15 +626,17 @@ async fn update_gossip_topology(
 		.position(|i| *i == our_index)
 		.expect("our_index < len; indices contains it; qed");
 
-	let neighbors = matrix_neighbors(our_shuffled_position, len);
+	let neighbors = strategy.neighbors(our_shuffled_position, len, random_seed);
 	let row_neighbors = neighbors
-		.row_neighbors
+		.x
+		.into_iter()
 		.map(|i| indices[i])
 		.map(|i| (authorities[i].clone(), ValidatorIndex::from(i as u32)))
 		.collect();
 
 	let column_neighbors = neighbors
-		.column_neighbors
+		.y
+		.into_iter()
 		.map(|i| indices[i])
 		.map(|i| (authorities[i].clone(), ValidatorIndex::from(i as u32)))
 		.collect();
@@ -548,39 +652,6 @@ async fn update_gossip_topology(
 	Ok(())
 }
 
-struct MatrixNeighbors<R, C> {
-	row_neighbors: R,
-	column_neighbors: C,
-}
-
-/// Compute our row and column neighbors in a matrix
-fn matrix_neighbors(
-	our_index: usize,
-	len: usize,
-) -> MatrixNeighbors<impl Iterator<Item = usize>, impl Iterator<Item = usize>> {
-	assert!(our_index < len, "our_index is computed using `enumerate`; qed");
-
-	// e.g. for size 11 the matrix would be
-	//
-	// 0  1  2
-	// 3  4  5
-	// 6  7  8
-	// 9 10
-	//
-	// and for index 10, the neighbors would be 1, 4, 7, 9
-
-	let sqrt = (len as f64).sqrt() as usize;
-	let our_row = our_index / sqrt;
-	let our_column = our_index % sqrt;
-	let row_neighbors = our_row * sqrt..std::cmp::min(our_row * sqrt + sqrt, len);
-	let column_neighbors = (our_column..len).step_by(sqrt);
-
-	MatrixNeighbors {
-		row_neighbors: row_neighbors.filter(move |i| *i != our_index),
-		column_neighbors: column_neighbors.filter(move |i| *i != our_index),
-	}
-}
-
 #[overseer::subsystem(GossipSupport, error = SubsystemError, prefix = self::overseer)]
 impl<Context, AD> GossipSupport<AD>
 where
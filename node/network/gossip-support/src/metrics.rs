@@ -14,11 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
+use polkadot_primitives::v2::SessionIndex;
+
 use polkadot_node_subsystem_util::{
 	metrics,
 	metrics::{
 		prometheus,
-		prometheus::{Gauge, PrometheusError, Registry, U64},
+		prometheus::{Gauge, GaugeVec, PrometheusError, Registry, U64},
 	},
 };
 
@@ -32,6 +34,8 @@ struct MetricsInner {
 	is_authority: Gauge<U64>,
 	/// Tracks authority status for parachain approval checking.
 	is_parachain_validator: Gauge<U64>,
+	/// Number of authorities we want to be connected to, resolved and connected to.
+	authorities: GaugeVec<U64>,
 }
 
 impl Metrics {
@@ -68,6 +72,30 @@ impl Metrics {
 			metrics.is_parachain_validator.set(0);
 		}
 	}
+
+	/// Record the result of a connectivity check in the given session.
+	///
+	/// Only the latest session is kept, to bound the number of time series.
+	pub fn on_connectivity_check(
+		&self,
+		session: SessionIndex,
+		wanted: usize,
+		resolved: usize,
+		connected: usize,
+	) {
+		if let Some(metrics) = &self.0 {
+			let session = session.to_string();
+			metrics.authorities.reset();
+			for (state, count) in
+				[("wanted", wanted), ("resolved", resolved), ("connected", connected)]
+			{
+				metrics
+					.authorities
+					.with_label_values(&[session.as_str(), state])
+					.set(count as u64);
+			}
+		}
+	}
 }
 
 impl metrics::Metrics for Metrics {
@@ -84,6 +112,16 @@ impl metrics::Metrics for Metrics {
 				subset of authorities that perform approval checking of all parachain candidates in a session.")?,
 				registry,
 			)?,
+			authorities: prometheus::register(
+				GaugeVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_gossip_support_authorities",
+						"Number of authorities we want to be connected to, resolved and actually connected to in the latest session.",
+					),
+					&["session", "state"],
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
@@ -29,6 +29,7 @@ use sp_consensus_babe::{AllowedSlots, BabeEpochConfiguration, Epoch as BabeEpoch
 use sp_core::crypto::Pair as PairT;
 use sp_keyring::Sr25519Keyring;
 
+use polkadot_node_network_protocol::ObservedRole;
 use polkadot_node_subsystem::{
 	jaeger,
 	messages::{AllMessages, RuntimeApiMessage, RuntimeApiRequest},
@@ -157,6 +158,7 @@ fn make_subsystem() -> GossipSupport<MockAuthorityDiscovery> {
 	GossipSupport::new(
 		make_ferdie_keystore(),
 		MOCK_AUTHORITY_DISCOVERY.clone(),
+		Config::default(),
 		Metrics::new_dummy(),
 	)
 }
@@ -637,6 +639,92 @@ fn issues_a_connection_request_when_last_request_was_mostly_unresolved() {
 	assert!(state.last_failure.is_none());
 }
 
+#[test]
+fn connectivity_report_tracks_unreachable_authorities() {
+	let hash = Hash::repeat_byte(0xAA);
+	let mut state = make_subsystem();
+	let alice: AuthorityDiscoveryId = Sr25519Keyring::Alice.public().into();
+	let bob: AuthorityDiscoveryId = Sr25519Keyring::Bob.public().into();
+	state.authority_discovery.addrs.remove(&alice);
+
+	test_harness(state, |mut virtual_overseer| async move {
+		let overseer = &mut virtual_overseer;
+		overseer_signal_active_leaves(overseer, hash).await;
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+				_,
+				RuntimeApiRequest::SessionIndexForChild(tx),
+			)) => {
+				tx.send(Ok(1)).unwrap();
+			}
+		);
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+				_,
+				RuntimeApiRequest::SessionInfo(_, tx),
+			)) => {
+				tx.send(Ok(Some(make_session_info()))).unwrap();
+			}
+		);
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+				_,
+				RuntimeApiRequest::Authorities(tx),
+			)) => {
+				tx.send(Ok(AUTHORITIES.clone())).unwrap();
+			}
+		);
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::NetworkBridge(NetworkBridgeMessage::ConnectToResolvedValidators { .. })
+		);
+
+		test_neighbors(overseer, 1).await;
+
+		overseer
+			.send(FromOrchestra::Communication {
+				msg: GossipSupportMessage::NetworkBridgeUpdate(NetworkBridgeEvent::PeerConnected(
+					PeerId::random(),
+					ObservedRole::Authority,
+					1,
+					Some(HashSet::from([bob.clone()])),
+				)),
+			})
+			.await;
+
+		let (tx, rx) = oneshot::channel();
+		overseer
+			.send(FromOrchestra::Communication {
+				msg: GossipSupportMessage::GetConnectivityReport(tx),
+			})
+			.await;
+		let report = rx.await.unwrap();
+
+		assert_eq!(report.session, Some(1));
+		assert_eq!(report.authorities.len(), AUTHORITIES_WITHOUT_US.len());
+
+		let alice_report = report.authorities.iter().find(|a| a.authority == alice).unwrap();
+		assert!(alice_report.addresses.is_empty());
+		assert!(!alice_report.connected);
+		assert_eq!(alice_report.last_connected, None);
+		assert_eq!(alice_report.failures, 1);
+
+		let bob_report = report.authorities.iter().find(|a| a.authority == bob).unwrap();
+		assert!(!bob_report.addresses.is_empty());
+		assert!(bob_report.connected);
+		assert!(bob_report.last_connected.is_some());
+		assert_eq!(bob_report.failures, 0);
+
+		virtual_overseer
+	});
+}
+
 #[test]
 fn test_matrix_neighbors() {
 	for (our_index, len, expected_row, expected_column) in vec![
@@ -649,9 +737,9 @@ fn test_matrix_neighbors() {
 	]
 	.into_iter()
 	{
-		let matrix = matrix_neighbors(our_index, len);
-		let mut row_result: Vec<_> = matrix.row_neighbors.collect();
-		let mut column_result: Vec<_> = matrix.column_neighbors.collect();
+		let matrix = Matrix.neighbors(our_index, len, [0; 32]);
+		let mut row_result = matrix.x;
+		let mut column_result = matrix.y;
 		row_result.sort();
 		column_result.sort();
 
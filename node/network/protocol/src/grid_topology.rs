@@ -28,12 +28,16 @@
 //! an adversary doesn't know which peers a validator will send to.
 //! This is combined with the property that the adversary doesn't know which validators will elect to check a block.
 //!
+//! How validators are arranged into neighbors is determined by a [`TopologyStrategy`]: the 2D grid
+//! described above is the [`Matrix`] strategy, [`RandomK`] is an alternative for evaluation.
+//!
 
 use crate::PeerId;
 use polkadot_primitives::v2::{SessionIndex, ValidatorIndex};
-use rand::{CryptoRng, Rng};
+use rand::{CryptoRng, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use std::{
-	collections::{hash_map, HashMap, HashSet},
+	collections::{hash_map, BTreeSet, HashMap, HashSet},
 	fmt::Debug,
 };
 
@@ -99,13 +103,13 @@ impl SessionGridTopology {
 			(true, false) => RequiredRouting::GridY, // messages from X go to Y
 			(false, true) => RequiredRouting::GridX, // messages from Y go to X
 			(true, true) => {
-				gum::debug!(
+				gum::trace!(
 					target: LOG_TARGET,
 					?originator,
-					"Grid topology is unexpected, play it safe and send to X AND Y"
+					"Originator is a neighbor in both dimensions, sending to X AND Y"
 				);
 				RequiredRouting::GridXY
-			}, // if the grid works as expected, this shouldn't happen.
+			}, // doesn't happen with the matrix topology, but does with e.g. random-k.
 		}
 	}
 
@@ -134,7 +138,7 @@ impl SessionGridTopology {
 
 	/// A convenience method that returns total number of peers in the topology
 	pub fn len(&self) -> usize {
-		self.peers_x.len().saturating_add(self.peers_y.len())
+		self.peers_x.union(&self.peers_y).count()
 	}
 }
 
@@ -295,6 +299,120 @@ impl RequiredRouting {
 	}
 }
 
+/// Our neighbors in a gossip topology, as indices into the shuffled list of validators.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TopologyNeighbors {
+	/// Neighbors in the X dimension.
+	pub x: Vec<usize>,
+	/// Neighbors in the Y dimension.
+	pub y: Vec<usize>,
+}
+
+/// A strategy arranging the validators of a session into a gossip topology.
+///
+/// Messages are routed based on where their originator is in our topology: messages from X
+/// neighbors are forwarded to Y neighbors and the other way around, see
+/// [`SessionGridTopology::required_routing_by_index`]. Messages from validators which are not
+/// our neighbors are not forwarded along the topology at all.
+///
+/// All validators of a session need to use the same strategy, as the topology only works if the
+/// neighbor relation is consistent.
+pub trait TopologyStrategy: Send + Sync {
+	/// Compute the neighbors of the validator at `our_index` among `len` shuffled validators.
+	///
+	/// `random_seed` is the same for all validators of a session.
+	fn neighbors(&self, our_index: usize, len: usize, random_seed: [u8; 32]) -> TopologyNeighbors;
+}
+
+/// The 2D grid topology.
+///
+/// Validators are laid out in a matrix with `sqrt(len)` columns. Row neighbors are X neighbors,
+/// column neighbors are Y neighbors. Every validator reaches every other validator in at most
+/// 2 hops, via 2 distinct paths.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Matrix;
+
+impl TopologyStrategy for Matrix {
+	fn neighbors(&self, our_index: usize, len: usize, _random_seed: [u8; 32]) -> TopologyNeighbors {
+		assert!(our_index < len, "our_index is computed using `enumerate`; qed");
+
+		// e.g. for size 11 the matrix would be
+		//
+		// 0  1  2
+		// 3  4  5
+		// 6  7  8
+		// 9 10
+		//
+		// and for index 10, the neighbors would be 1, 4, 7, 9
+
+		let sqrt = (len as f64).sqrt() as usize;
+		let our_row = our_index / sqrt;
+		let our_column = our_index % sqrt;
+		let row_neighbors = our_row * sqrt..std::cmp::min(our_row * sqrt + sqrt, len);
+		let column_neighbors = (our_column..len).step_by(sqrt);
+
+		TopologyNeighbors {
+			x: row_neighbors.filter(|i| *i != our_index).collect(),
+			y: column_neighbors.filter(|i| *i != our_index).collect(),
+		}
+	}
+}
+
+/// A random topology where every validator has about `k` neighbors.
+///
+/// Every validator picks `k / 2` random other validators, from a random generator seeded with
+/// the session randomness and its index. Its neighbors are the validators it picked and the
+/// validators which picked it, so the neighbor relation is symmetric. All neighbors are both X
+/// and Y neighbors, so messages from neighbors are forwarded to all other neighbors. Unlike the
+/// matrix, this does not guarantee that every validator is reached in 2 hops.
+#[derive(Debug, Clone, Copy)]
+pub struct RandomK {
+	/// The number of neighbors of each validator, on average.
+	pub k: usize,
+}
+
+impl RandomK {
+	/// The validators picked by the validator at `index`.
+	fn picks(&self, index: usize, len: usize, random_seed: [u8; 32]) -> Vec<usize> {
+		let mut rng: ChaCha20Rng = SeedableRng::from_seed(random_seed);
+		// The first stream is used for shuffling the validators.
+		rng.set_stream(index as u64 + 1);
+
+		let n_picks = std::cmp::min(self.k / 2, len - 1);
+		rand::seq::index::sample(&mut rng, len - 1, n_picks)
+			.into_iter()
+			.map(|i| if i < index { i } else { i + 1 })
+			.collect()
+	}
+
+	/// The neighbors of all `len` validators.
+	fn all_neighbors(&self, len: usize, random_seed: [u8; 32]) -> Vec<TopologyNeighbors> {
+		let mut neighbors = vec![BTreeSet::new(); len];
+		for index in 0..len {
+			for pick in self.picks(index, len, random_seed) {
+				neighbors[index].insert(pick);
+				neighbors[pick].insert(index);
+			}
+		}
+
+		neighbors
+			.into_iter()
+			.map(|neighbors| {
+				let neighbors: Vec<_> = neighbors.into_iter().collect();
+				TopologyNeighbors { x: neighbors.clone(), y: neighbors }
+			})
+			.collect()
+	}
+}
+
+impl TopologyStrategy for RandomK {
+	fn neighbors(&self, our_index: usize, len: usize, random_seed: [u8; 32]) -> TopologyNeighbors {
+		assert!(our_index < len, "our_index is computed using `enumerate`; qed");
+
+		self.all_neighbors(len, random_seed).swap_remove(our_index)
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -365,4 +483,144 @@ mod tests {
 		let mut random_routing = RandomRouting { target: 10, sent: 0, sample_rate: 10 };
 		assert_eq!(run_random_routing(&mut random_routing, &mut rng, 10, 100), 10);
 	}
+
+	/// The topology of every validator, as computed by `strategy`.
+	///
+	/// Only validator indices are filled in, which are the positions in the shuffled list of
+	/// validators: the shuffle itself doesn't matter for propagation.
+	fn build_topologies(strategy: &dyn TopologyStrategy, len: usize) -> Vec<SessionGridTopology> {
+		to_topologies((0..len).map(|our_index| strategy.neighbors(our_index, len, [7; 32])))
+	}
+
+	fn to_topologies(
+		neighbors: impl Iterator<Item = TopologyNeighbors>,
+	) -> Vec<SessionGridTopology> {
+		neighbors
+			.map(|neighbors| {
+				let to_indices =
+					|v: Vec<usize>| v.into_iter().map(|i| ValidatorIndex(i as u32)).collect();
+				SessionGridTopology {
+					validator_indices_x: to_indices(neighbors.x),
+					validator_indices_y: to_indices(neighbors.y),
+					..Default::default()
+				}
+			})
+			.collect()
+	}
+
+	fn routing_targets(topology: &SessionGridTopology, routing: RequiredRouting) -> Vec<usize> {
+		let indices: Vec<_> = match routing {
+			RequiredRouting::GridX => topology.validator_indices_x.iter().collect(),
+			RequiredRouting::GridY => topology.validator_indices_y.iter().collect(),
+			RequiredRouting::GridXY =>
+				topology.validator_indices_x.union(&topology.validator_indices_y).collect(),
+			_ => Vec::new(),
+		};
+
+		indices.into_iter().map(|i| i.0 as usize).collect()
+	}
+
+	struct Propagation {
+		/// Fraction of the other validators which received the message.
+		coverage: f64,
+		/// Hops until the last validator received the message.
+		max_hops: usize,
+		/// Messages sent per other validator.
+		redundancy: f64,
+	}
+
+	/// Gossip a message from `originator` along the given topologies, following the routing
+	/// rules of `SessionGridTopology`. Validators forward a message when they first receive it.
+	fn simulate_propagation(topologies: &[SessionGridTopology], originator: usize) -> Propagation {
+		let len = topologies.len();
+		let mut received = vec![false; len];
+		received[originator] = true;
+
+		let mut sent = 0;
+		let mut max_hops = 0;
+		let mut frontier = vec![originator];
+		while !frontier.is_empty() {
+			let mut next = Vec::new();
+			for validator in frontier {
+				let topology = &topologies[validator];
+				let routing = topology.required_routing_by_index(
+					ValidatorIndex(originator as u32),
+					validator == originator,
+				);
+
+				for target in routing_targets(topology, routing) {
+					sent += 1;
+					if !received[target] {
+						received[target] = true;
+						next.push(target);
+					}
+				}
+			}
+
+			if !next.is_empty() {
+				max_hops += 1;
+			}
+			frontier = next;
+		}
+
+		let reached = received.iter().filter(|r| **r).count() - 1;
+		Propagation {
+			coverage: reached as f64 / (len - 1) as f64,
+			max_hops,
+			redundancy: sent as f64 / (len - 1) as f64,
+		}
+	}
+
+	#[test]
+	fn matrix_topology_propagation() {
+		for len in [1000, 1024, 1500, 2000] {
+			let topologies = build_topologies(&Matrix, len);
+
+			for originator in (0..len).step_by(37) {
+				let propagation = simulate_propagation(&topologies, originator);
+
+				assert_eq!(propagation.coverage, 1.0);
+				assert_eq!(propagation.max_hops, 2);
+				// Every validator receives the message about twice.
+				assert!(propagation.redundancy < 2.0, "{}", propagation.redundancy);
+			}
+		}
+	}
+
+	#[test]
+	fn random_k_topology_propagation() {
+		let len = 1000;
+		let k = 64;
+		// Computing the neighbors of a single validator already computes those of all others.
+		let all_neighbors = RandomK { k }.all_neighbors(len, [7; 32]);
+		for our_index in (0..len).step_by(97) {
+			assert_eq!(RandomK { k }.neighbors(our_index, len, [7; 32]), all_neighbors[our_index]);
+		}
+		let topologies = to_topologies(all_neighbors.into_iter());
+
+		let mut n_neighbors = 0;
+		for (index, topology) in topologies.iter().enumerate() {
+			// Every validator picks `k / 2` neighbors on its own.
+			assert!(topology.validator_indices_x.len() >= k / 2);
+			n_neighbors += topology.validator_indices_x.len();
+			// The neighbor relation is symmetric.
+			for neighbor in &topology.validator_indices_x {
+				assert!(topologies[neighbor.0 as usize]
+					.validator_indices_x
+					.contains(&ValidatorIndex(index as u32)));
+			}
+		}
+		// Picks of two validators rarely coincide.
+		let average = n_neighbors as f64 / len as f64;
+		assert!(average > 0.95 * k as f64 && average <= k as f64, "{}", average);
+
+		for originator in (0..len).step_by(37) {
+			let propagation = simulate_propagation(&topologies, originator);
+
+			// Two hops don't reach everyone, random propagation needs to make up for the rest.
+			assert!(propagation.coverage > 0.8, "{}", propagation.coverage);
+			assert_eq!(propagation.max_hops, 2);
+			assert!(propagation.redundancy < 5.0, "{}", propagation.redundancy);
+		}
+	}
 }
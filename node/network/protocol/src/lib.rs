@@ -253,22 +253,35 @@ impl View {
 
 /// A protocol-versioned type.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Versioned<V1> {
+pub enum Versioned<V1, V2> {
 	/// V1 type.
 	V1(V1),
+	/// V2 type.
+	V2(V2),
 }
 
-impl<V1: Clone> Versioned<&'_ V1> {
+impl<V1, V2> Versioned<V1, V2> {
+	/// The protocol version of the message.
+	pub fn version(&self) -> ProtocolVersion {
+		match self {
+			Versioned::V1(_) => 1,
+			Versioned::V2(_) => 2,
+		}
+	}
+}
+
+impl<V1: Clone, V2: Clone> Versioned<&'_ V1, &'_ V2> {
 	/// Convert to a fully-owned version of the message.
-	pub fn clone_inner(&self) -> Versioned<V1> {
+	pub fn clone_inner(&self) -> Versioned<V1, V2> {
 		match *self {
 			Versioned::V1(inner) => Versioned::V1(inner.clone()),
+			Versioned::V2(inner) => Versioned::V2(inner.clone()),
 		}
 	}
 }
 
 /// All supported versions of the validation protocol message.
-pub type VersionedValidationProtocol = Versioned<v1::ValidationProtocol>;
+pub type VersionedValidationProtocol = Versioned<v1::ValidationProtocol, v2::ValidationProtocol>;
 
 impl From<v1::ValidationProtocol> for VersionedValidationProtocol {
 	fn from(v1: v1::ValidationProtocol) -> Self {
@@ -276,8 +289,44 @@ impl From<v1::ValidationProtocol> for VersionedValidationProtocol {
 	}
 }
 
+impl From<v2::ValidationProtocol> for VersionedValidationProtocol {
+	fn from(v2: v2::ValidationProtocol) -> Self {
+		VersionedValidationProtocol::V2(v2)
+	}
+}
+
+impl VersionedValidationProtocol {
+	/// Convert the message for sending to peers connected on the given protocol version.
+	///
+	/// Returns `None` if the message can't be expressed in that version: Any v1 message is a valid
+	/// v2 message, but not every v2 message exists in v1.
+	pub fn into_version(self, version: ProtocolVersion) -> Option<Self> {
+		match (self, version) {
+			(Versioned::V1(m), 1) => Some(Versioned::V1(m)),
+			(Versioned::V1(m), 2) => Some(Versioned::V2(m.into())),
+			(Versioned::V2(m), 1) => v1::ValidationProtocol::try_from(m).ok().map(Versioned::V1),
+			(Versioned::V2(m), 2) => Some(Versioned::V2(m)),
+			_ => None,
+		}
+	}
+}
+
 /// All supported versions of the collation protocol message.
-pub type VersionedCollationProtocol = Versioned<v1::CollationProtocol>;
+///
+/// The collation protocol did not change in v2, so only `V1` messages are ever sent or received.
+pub type VersionedCollationProtocol = Versioned<v1::CollationProtocol, v2::CollationProtocol>;
+
+impl VersionedCollationProtocol {
+	/// Convert the message for sending to peers connected on the given protocol version.
+	///
+	/// Returns `None` if the message can't be expressed in that version.
+	pub fn into_version(self, version: ProtocolVersion) -> Option<Self> {
+		match (self, version) {
+			(Versioned::V1(m) | Versioned::V2(m), 1) => Some(Versioned::V1(m)),
+			_ => None,
+		}
+	}
+}
 
 impl From<v1::CollationProtocol> for VersionedCollationProtocol {
 	fn from(v1: v1::CollationProtocol) -> Self {
@@ -291,6 +340,7 @@ macro_rules! impl_versioned_full_protocol_from {
 			fn from(versioned_from: $from) -> $out {
 				match versioned_from {
 					Versioned::V1(x) => Versioned::V1(x.into()),
+					Versioned::V2(x) => Versioned::V2(x.into()),
 				}
 			}
 		}
@@ -300,7 +350,7 @@ macro_rules! impl_versioned_full_protocol_from {
 /// Implement `TryFrom` for one versioned enum variant into the inner type.
 /// `$m_ty::$variant(inner) -> Ok(inner)`
 macro_rules! impl_versioned_try_from {
-	($from:ty, $out:ty, $v1_pat:pat => $v1_out:expr) => {
+	($from:ty, $out:ty, $v1_pat:pat => $v1_out:expr, $v2_pat:pat => $v2_out:expr) => {
 		impl TryFrom<$from> for $out {
 			type Error = crate::WrongVariant;
 
@@ -308,6 +358,7 @@ macro_rules! impl_versioned_try_from {
 				#[allow(unreachable_patterns)] // when there is only one variant
 				match x {
 					Versioned::V1($v1_pat) => Ok(Versioned::V1($v1_out)),
+					Versioned::V2($v2_pat) => Ok(Versioned::V2($v2_out)),
 					_ => Err(crate::WrongVariant),
 				}
 			}
@@ -320,6 +371,7 @@ macro_rules! impl_versioned_try_from {
 				#[allow(unreachable_patterns)] // when there is only one variant
 				match x {
 					Versioned::V1($v1_pat) => Ok(Versioned::V1($v1_out.clone())),
+					Versioned::V2($v2_pat) => Ok(Versioned::V2($v2_out.clone())),
 					_ => Err(crate::WrongVariant),
 				}
 			}
@@ -328,7 +380,8 @@ macro_rules! impl_versioned_try_from {
 }
 
 /// Version-annotated messages used by the bitfield distribution subsystem.
-pub type BitfieldDistributionMessage = Versioned<v1::BitfieldDistributionMessage>;
+pub type BitfieldDistributionMessage =
+	Versioned<v1::BitfieldDistributionMessage, v2::BitfieldDistributionMessage>;
 impl_versioned_full_protocol_from!(
 	BitfieldDistributionMessage,
 	VersionedValidationProtocol,
@@ -337,11 +390,13 @@ impl_versioned_full_protocol_from!(
 impl_versioned_try_from!(
 	VersionedValidationProtocol,
 	BitfieldDistributionMessage,
-	v1::ValidationProtocol::BitfieldDistribution(x) => x
+	v1::ValidationProtocol::BitfieldDistribution(x) => x,
+	v2::ValidationProtocol::BitfieldDistribution(x) => x
 );
 
 /// Version-annotated messages used by the statement distribution subsystem.
-pub type StatementDistributionMessage = Versioned<v1::StatementDistributionMessage>;
+pub type StatementDistributionMessage =
+	Versioned<v1::StatementDistributionMessage, v2::StatementDistributionMessage>;
 impl_versioned_full_protocol_from!(
 	StatementDistributionMessage,
 	VersionedValidationProtocol,
@@ -350,11 +405,13 @@ impl_versioned_full_protocol_from!(
 impl_versioned_try_from!(
 	VersionedValidationProtocol,
 	StatementDistributionMessage,
-	v1::ValidationProtocol::StatementDistribution(x) => x
+	v1::ValidationProtocol::StatementDistribution(x) => x,
+	v2::ValidationProtocol::StatementDistribution(x) => x
 );
 
 /// Version-annotated messages used by the approval distribution subsystem.
-pub type ApprovalDistributionMessage = Versioned<v1::ApprovalDistributionMessage>;
+pub type ApprovalDistributionMessage =
+	Versioned<v1::ApprovalDistributionMessage, v2::ApprovalDistributionMessage>;
 impl_versioned_full_protocol_from!(
 	ApprovalDistributionMessage,
 	VersionedValidationProtocol,
@@ -363,11 +420,13 @@ impl_versioned_full_protocol_from!(
 impl_versioned_try_from!(
 	VersionedValidationProtocol,
 	ApprovalDistributionMessage,
-	v1::ValidationProtocol::ApprovalDistribution(x) => x
+	v1::ValidationProtocol::ApprovalDistribution(x) => x,
+	v2::ValidationProtocol::ApprovalDistribution(x) => x
 );
 
 /// Version-annotated messages used by the gossip-support subsystem (this is void).
-pub type GossipSupportNetworkMessage = Versioned<v1::GossipSupportNetworkMessage>;
+pub type GossipSupportNetworkMessage =
+	Versioned<v1::GossipSupportNetworkMessage, v2::GossipSupportNetworkMessage>;
 // This is a void enum placeholder, so never gets sent over the wire.
 impl TryFrom<VersionedValidationProtocol> for GossipSupportNetworkMessage {
 	type Error = WrongVariant;
@@ -384,7 +443,8 @@ impl<'a> TryFrom<&'a VersionedValidationProtocol> for GossipSupportNetworkMessag
 }
 
 /// Version-annotated messages used by the bitfield distribution subsystem.
-pub type CollatorProtocolMessage = Versioned<v1::CollatorProtocolMessage>;
+pub type CollatorProtocolMessage =
+	Versioned<v1::CollatorProtocolMessage, v2::CollatorProtocolMessage>;
 impl_versioned_full_protocol_from!(
 	CollatorProtocolMessage,
 	VersionedCollationProtocol,
@@ -393,7 +453,8 @@ impl_versioned_full_protocol_from!(
 impl_versioned_try_from!(
 	VersionedCollationProtocol,
 	CollatorProtocolMessage,
-	v1::CollationProtocol::CollatorProtocol(x) => x
+	v1::CollationProtocol::CollatorProtocol(x) => x,
+	v2::CollationProtocol::CollatorProtocol(x) => x
 );
 
 /// v1 notification protocol types.
@@ -553,3 +614,99 @@ pub mod v1 {
 		payload
 	}
 }
+
+/// v2 notification protocol types.
+///
+/// Only the bitfield distribution messages changed compared to v1, all other messages are
+/// re-exported from [`v1`]. Any v1 message has the same encoding in v2, see
+/// [`crate::VersionedValidationProtocol::into_version`] for converting between the two.
+pub mod v2 {
+	use parity_scale_codec::{Decode, Encode};
+
+	use polkadot_primitives::v2::{Hash, UncheckedSignedAvailabilityBitfield};
+
+	pub use super::v1::{
+		declare_signature_payload, ApprovalDistributionMessage, CollationProtocol,
+		CollatorProtocolMessage, GossipSupportNetworkMessage, StatementDistributionMessage,
+		StatementMetadata,
+	};
+
+	/// Network messages used by the bitfield distribution subsystem.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+	pub enum BitfieldDistributionMessage {
+		/// A signed availability bitfield for a given relay-parent hash.
+		#[codec(index = 0)]
+		Bitfield(Hash, UncheckedSignedAvailabilityBitfield),
+		/// Signed availability bitfields of multiple validators for a given relay-parent hash.
+		///
+		/// Each bitfield carries its own signature and is checked on its own.
+		#[codec(index = 1)]
+		Bitfields(Hash, Vec<UncheckedSignedAvailabilityBitfield>),
+	}
+
+	impl From<super::v1::BitfieldDistributionMessage> for BitfieldDistributionMessage {
+		fn from(message: super::v1::BitfieldDistributionMessage) -> Self {
+			match message {
+				super::v1::BitfieldDistributionMessage::Bitfield(relay_parent, bitfield) =>
+					BitfieldDistributionMessage::Bitfield(relay_parent, bitfield),
+			}
+		}
+	}
+
+	impl TryFrom<BitfieldDistributionMessage> for super::v1::BitfieldDistributionMessage {
+		type Error = crate::WrongVariant;
+
+		fn try_from(message: BitfieldDistributionMessage) -> Result<Self, Self::Error> {
+			match message {
+				BitfieldDistributionMessage::Bitfield(relay_parent, bitfield) =>
+					Ok(super::v1::BitfieldDistributionMessage::Bitfield(relay_parent, bitfield)),
+				BitfieldDistributionMessage::Bitfields(..) => Err(crate::WrongVariant),
+			}
+		}
+	}
+
+	/// All network messages on the validation peer-set.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq, derive_more::From)]
+	pub enum ValidationProtocol {
+		/// Bitfield distribution messages
+		#[codec(index = 1)]
+		#[from]
+		BitfieldDistribution(BitfieldDistributionMessage),
+		/// Statement distribution messages
+		#[codec(index = 3)]
+		#[from]
+		StatementDistribution(StatementDistributionMessage),
+		/// Approval distribution messages
+		#[codec(index = 4)]
+		#[from]
+		ApprovalDistribution(ApprovalDistributionMessage),
+	}
+
+	impl From<super::v1::ValidationProtocol> for ValidationProtocol {
+		fn from(message: super::v1::ValidationProtocol) -> Self {
+			match message {
+				super::v1::ValidationProtocol::BitfieldDistribution(m) =>
+					ValidationProtocol::BitfieldDistribution(m.into()),
+				super::v1::ValidationProtocol::StatementDistribution(m) =>
+					ValidationProtocol::StatementDistribution(m),
+				super::v1::ValidationProtocol::ApprovalDistribution(m) =>
+					ValidationProtocol::ApprovalDistribution(m),
+			}
+		}
+	}
+
+	impl TryFrom<ValidationProtocol> for super::v1::ValidationProtocol {
+		type Error = crate::WrongVariant;
+
+		fn try_from(message: ValidationProtocol) -> Result<Self, Self::Error> {
+			Ok(match message {
+				ValidationProtocol::BitfieldDistribution(m) =>
+					super::v1::ValidationProtocol::BitfieldDistribution(m.try_into()?),
+				ValidationProtocol::StatementDistribution(m) =>
+					super::v1::ValidationProtocol::StatementDistribution(m),
+				ValidationProtocol::ApprovalDistribution(m) =>
+					super::v1::ValidationProtocol::ApprovalDistribution(m),
+			})
+		}
+	}
+}
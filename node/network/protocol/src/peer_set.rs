@@ -26,13 +26,46 @@ use strum::{EnumIter, IntoEnumIterator};
 
 // Only supported protocol versions should be defined here.
 const VALIDATION_PROTOCOL_V1: &str = "/polkadot/validation/1";
+const VALIDATION_PROTOCOL_V2: &str = "/polkadot/validation/2";
 const COLLATION_PROTOCOL_V1: &str = "/polkadot/collation/1";
 
 /// The default validation protocol version.
-pub const DEFAULT_VALIDATION_PROTOCOL_VERSION: ProtocolVersion = 1;
+pub const DEFAULT_VALIDATION_PROTOCOL_VERSION: ProtocolVersion = ValidationVersion::V2 as u32;
 
 /// The default collation protocol version.
-pub const DEFAULT_COLLATION_PROTOCOL_VERSION: ProtocolVersion = 1;
+pub const DEFAULT_COLLATION_PROTOCOL_VERSION: ProtocolVersion = CollationVersion::V1 as u32;
+
+/// Supported versions of the validation protocol.
+///
+/// Peers negotiate the most recent version both sides support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum ValidationVersion {
+	/// The initial version.
+	V1 = 1,
+	/// Adds batched bitfield distribution messages.
+	V2 = 2,
+}
+
+/// Supported versions of the collation protocol.
+///
+/// Peers negotiate the most recent version both sides support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum CollationVersion {
+	/// The initial version.
+	V1 = 1,
+}
+
+impl From<ValidationVersion> for ProtocolVersion {
+	fn from(version: ValidationVersion) -> ProtocolVersion {
+		version as ProtocolVersion
+	}
+}
+
+impl From<CollationVersion> for ProtocolVersion {
+	fn from(version: CollationVersion) -> ProtocolVersion {
+		version as ProtocolVersion
+	}
+}
 
 /// The peer-sets and thus the protocols which are used for the network.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
@@ -70,7 +103,7 @@ impl PeerSet {
 		match self {
 			PeerSet::Validation => NonDefaultSetConfig {
 				notifications_protocol: protocol,
-				fallback_names: Vec::new(),
+				fallback_names: self.get_fallback_protocol_names(),
 				max_notification_size,
 				set_config: sc_network::config::SetConfig {
 					// we allow full nodes to connect to validators for gossip
@@ -85,7 +118,7 @@ impl PeerSet {
 			},
 			PeerSet::Collation => NonDefaultSetConfig {
 				notifications_protocol: protocol,
-				fallback_names: Vec::new(),
+				fallback_names: self.get_fallback_protocol_names(),
 				max_notification_size,
 				set_config: SetConfig {
 					// Non-authority nodes don't need to accept incoming connections on this peer set:
@@ -110,10 +143,34 @@ impl PeerSet {
 		}
 	}
 
+	/// Get all protocol versions supported for this peer set, the most recent first.
+	pub fn get_supported_versions(self) -> Vec<ProtocolVersion> {
+		let mut versions: Vec<ProtocolVersion> = match self {
+			PeerSet::Validation => ValidationVersion::iter().map(Into::into).collect(),
+			PeerSet::Collation => CollationVersion::iter().map(Into::into).collect(),
+		};
+
+		versions.sort_unstable_by(|a, b| b.cmp(a));
+		versions
+	}
+
+	/// Get the protocol names of older versions still supported by this peer set, the most recent
+	/// first.
+	///
+	/// Peers not supporting the default version will negotiate one of those instead.
+	pub fn get_fallback_protocol_names(self) -> Vec<Cow<'static, str>> {
+		let default_version = self.get_default_version();
+		self.get_supported_versions()
+			.into_iter()
+			.filter(|version| *version != default_version)
+			.filter_map(|version| self.into_protocol_name(version))
+			.collect()
+	}
+
 	/// Get the default protocol name as a static str.
 	pub const fn get_default_protocol_name(self) -> &'static str {
 		match self {
-			PeerSet::Validation => VALIDATION_PROTOCOL_V1,
+			PeerSet::Validation => VALIDATION_PROTOCOL_V2,
 			PeerSet::Collation => COLLATION_PROTOCOL_V1,
 		}
 	}
@@ -123,6 +180,7 @@ impl PeerSet {
 	pub const fn get_protocol_name_static(self, version: ProtocolVersion) -> Option<&'static str> {
 		match (self, version) {
 			(PeerSet::Validation, 1) => Some(VALIDATION_PROTOCOL_V1),
+			(PeerSet::Validation, 2) => Some(VALIDATION_PROTOCOL_V2),
 			(PeerSet::Collation, 1) => Some(COLLATION_PROTOCOL_V1),
 			_ => None,
 		}
@@ -143,11 +201,13 @@ impl PeerSet {
 	///
 	/// This only succeeds on supported versions.
 	pub fn try_from_protocol_name(name: &Cow<'static, str>) -> Option<(PeerSet, ProtocolVersion)> {
-		match name {
-			n if n == VALIDATION_PROTOCOL_V1 => Some((PeerSet::Validation, 1)),
-			n if n == COLLATION_PROTOCOL_V1 => Some((PeerSet::Collation, 1)),
-			_ => None,
-		}
+		PeerSet::iter().find_map(|peer_set| {
+			peer_set
+				.get_supported_versions()
+				.into_iter()
+				.find(|version| peer_set.get_protocol_name_static(*version) == Some(&**name))
+				.map(|version| (peer_set, version))
+		})
 	}
 }
 
@@ -184,3 +244,38 @@ impl<T> IndexMut<PeerSet> for PerPeerSet<T> {
 pub fn peer_sets_info(is_authority: IsAuthority) -> Vec<sc_network::config::NonDefaultSetConfig> {
 	PeerSet::iter().map(|s| s.get_info(is_authority)).collect()
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn protocol_names_round_trip() {
+		for peer_set in PeerSet::iter() {
+			for version in peer_set.get_supported_versions() {
+				let name =
+					peer_set.into_protocol_name(version).expect("supported version has a name");
+				assert_eq!(PeerSet::try_from_protocol_name(&name), Some((peer_set, version)));
+			}
+		}
+
+		assert_eq!(PeerSet::try_from_protocol_name(&"/polkadot/validation/0".into()), None);
+	}
+
+	#[test]
+	fn fallbacks_are_older_versions() {
+		assert_eq!(PeerSet::Validation.get_supported_versions(), vec![2, 1]);
+		assert_eq!(
+			PeerSet::Validation.get_fallback_protocol_names(),
+			vec![Cow::Borrowed(VALIDATION_PROTOCOL_V1)],
+		);
+		assert!(PeerSet::Collation.get_fallback_protocol_names().is_empty());
+
+		for peer_set in PeerSet::iter() {
+			assert_eq!(
+				peer_set.get_supported_versions().first(),
+				Some(&peer_set.get_default_version()),
+			);
+		}
+	}
+}
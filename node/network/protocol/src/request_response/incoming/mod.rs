@@ -18,14 +18,14 @@ use std::marker::PhantomData;
 
 use futures::{
 	channel::{mpsc, oneshot},
-	StreamExt,
+	select, StreamExt,
 };
 
 use parity_scale_codec::{Decode, Encode};
 
 use sc_network::{config as netconfig, config::RequestResponseConfig, PeerId};
 
-use super::IsRequest;
+use super::{compress_response, IsRequest};
 use crate::UnifiedReputationChange;
 
 mod error;
@@ -57,7 +57,31 @@ where
 	/// returned `IncomingRequestReceiver`.
 	pub fn get_config_receiver() -> (IncomingRequestReceiver<Req>, RequestResponseConfig) {
 		let (raw, cfg) = Req::PROTOCOL.get_config();
-		(IncomingRequestReceiver { raw, phantom: PhantomData {} }, cfg)
+		(IncomingRequestReceiver { raw, raw_compressed: None, phantom: PhantomData {} }, cfg)
+	}
+
+	/// Create configurations for all variants of the protocol and a corresponding typed receiver.
+	///
+	/// Like `get_config_receiver`, but if the protocol has a variant with compressed responses,
+	/// requests on that variant are received as well and answered with compressed responses.
+	pub fn get_config_receivers() -> (IncomingRequestReceiver<Req>, Vec<RequestResponseConfig>) {
+		let (raw, cfg) = Req::PROTOCOL.get_config();
+		match Req::PROTOCOL.compressed() {
+			Some(compressed) => {
+				let (raw_compressed, compressed_cfg) = compressed.get_config();
+				let receiver = IncomingRequestReceiver {
+					raw,
+					raw_compressed: Some(raw_compressed),
+					phantom: PhantomData {},
+				};
+				(receiver, vec![cfg, compressed_cfg])
+			},
+			None => {
+				let receiver =
+					IncomingRequestReceiver { raw, raw_compressed: None, phantom: PhantomData {} };
+				(receiver, vec![cfg])
+			},
+		}
 	}
 
 	/// Create new `IncomingRequest`.
@@ -69,7 +93,11 @@ where
 		Self {
 			peer,
 			payload,
-			pending_response: OutgoingResponseSender { pending_response, phantom: PhantomData {} },
+			pending_response: OutgoingResponseSender {
+				pending_response,
+				compressed: false,
+				phantom: PhantomData {},
+			},
 		}
 	}
 
@@ -81,9 +109,11 @@ where
 	/// Params:
 	///		- The raw request to decode
 	///		- Reputation changes to apply for the peer in case decoding fails.
+	///		- Whether the request was received on the compressed variant of the protocol.
 	fn try_from_raw(
 		raw: sc_network::config::IncomingRequest,
 		reputation_changes: Vec<UnifiedReputationChange>,
+		compressed: bool,
 	) -> std::result::Result<Self, JfyiError> {
 		let sc_network::config::IncomingRequest { payload, peer, pending_response } = raw;
 		let payload = match Req::decode(&mut payload.as_ref()) {
@@ -103,7 +133,9 @@ where
 				return Err(JfyiError::DecodingError(peer, err))
 			},
 		};
-		Ok(Self::new(peer, payload, pending_response))
+		let mut req = Self::new(peer, payload, pending_response);
+		req.pending_response.compressed = compressed;
+		Ok(req)
 	}
 
 	/// Convert into raw untyped substrate `IncomingRequest`.
@@ -139,6 +171,8 @@ where
 #[derive(Debug)]
 pub struct OutgoingResponseSender<Req> {
 	pending_response: oneshot::Sender<netconfig::OutgoingResponse>,
+	/// Whether responses are sent on the compressed variant of the protocol.
+	compressed: bool,
 	phantom: PhantomData<Req>,
 }
 
@@ -154,9 +188,10 @@ where
 	/// `netconfig::OutgoingResponse` exposes a way of modifying the peer's reputation. If needed we
 	/// can change this function to expose this feature as well.
 	pub fn send_response(self, resp: Req::Response) -> std::result::Result<(), Req::Response> {
+		let encoded = self.encode_response(&resp);
 		self.pending_response
 			.send(netconfig::OutgoingResponse {
-				result: Ok(resp.encode()),
+				result: Ok(encoded),
 				reputation_changes: Vec::new(),
 				sent_feedback: None,
 			})
@@ -175,13 +210,21 @@ where
 		let OutgoingResponse { result, reputation_changes, sent_feedback } = resp;
 
 		let response = netconfig::OutgoingResponse {
-			result: result.map(|v| v.encode()),
+			result: result.map(|v| self.encode_response(&v)),
 			reputation_changes: reputation_changes.into_iter().map(|c| c.into_base_rep()).collect(),
 			sent_feedback,
 		};
 
 		self.pending_response.send(response).map_err(|_| ())
 	}
+
+	fn encode_response(&self, resp: &Req::Response) -> Vec<u8> {
+		if self.compressed {
+			compress_response(Req::PROTOCOL, resp.encode())
+		} else {
+			resp.encode()
+		}
+	}
 }
 
 /// Typed variant of [`netconfig::OutgoingResponse`].
@@ -207,6 +250,8 @@ pub struct OutgoingResponse<Response> {
 /// Takes care of decoding and handling of invalid encoded requests.
 pub struct IncomingRequestReceiver<Req> {
 	raw: mpsc::Receiver<netconfig::IncomingRequest>,
+	/// Requests received on the variant of the protocol with compressed responses.
+	raw_compressed: Option<mpsc::Receiver<netconfig::IncomingRequest>>,
 	phantom: PhantomData<Req>,
 }
 
@@ -223,9 +268,17 @@ where
 	where
 		F: FnOnce() -> Vec<UnifiedReputationChange>,
 	{
-		let req = match self.raw.next().await {
+		let (raw, compressed) = match self.raw_compressed.as_mut() {
+			Some(raw_compressed) => select! {
+				raw = self.raw.next() => (raw, false),
+				raw = raw_compressed.next() => (raw, true),
+			},
+			None => (self.raw.next().await, false),
+		};
+		let req = match raw {
 			None => return Err(FatalError::RequestChannelExhausted.into()),
-			Some(raw) => IncomingRequest::<Req>::try_from_raw(raw, reputation_changes())?,
+			Some(raw) =>
+				IncomingRequest::<Req>::try_from_raw(raw, reputation_changes(), compressed)?,
 		};
 		Ok(req)
 	}
@@ -31,10 +31,15 @@
 //! data, like what is the corresponding response type.
 //!
 //!  Versioned (v1 module): The actual requests and responses as sent over the network.
+//!
+//! Protocols transferring PoV like data have a V2 variant, which uses the same requests and
+//! responses as V1, but responses may be compressed. See `compress_response` and
+//! `decompress_response`.
 
 use std::{borrow::Cow, time::Duration, u64};
 
 use futures::channel::mpsc;
+use parity_scale_codec::Error as DecodingError;
 use polkadot_primitives::v2::{MAX_CODE_SIZE, MAX_POV_SIZE};
 use strum::EnumIter;
 
@@ -71,6 +76,14 @@ pub enum Protocol {
 	StatementFetchingV1,
 	/// Sending of dispute statements with application level confirmations.
 	DisputeSendingV1,
+	/// Protocol for chunk fetching, with compressed responses.
+	ChunkFetchingV2,
+	/// Protocol for fetching collations from collators, with compressed responses.
+	CollationFetchingV2,
+	/// Protocol for fetching seconded PoVs, with compressed responses.
+	PoVFetchingV2,
+	/// Protocol for fetching available data, with compressed responses.
+	AvailableDataFetchingV2,
 }
 
 /// Minimum bandwidth we expect for validators - 500Mbit/s is the recommendation, so approximately
@@ -116,6 +129,9 @@ pub const MAX_PARALLEL_STATEMENT_REQUESTS: u32 = 3;
 /// might have more severe effects.
 const POV_RESPONSE_SIZE: u64 = MAX_POV_SIZE as u64 + 10_000;
 
+/// Responses smaller than this are not worth compressing.
+const MIN_COMPRESSED_RESPONSE_SIZE: usize = 1024;
+
 /// Maximum response sizes for `StatementFetchingV1`.
 ///
 /// This is `MAX_CODE_SIZE` plus some additional space for protocol overhead.
@@ -130,7 +146,7 @@ impl Protocol {
 		let p_name = self.into_protocol_name();
 		let (tx, rx) = mpsc::channel(self.get_channel_size());
 		let cfg = match self {
-			Protocol::ChunkFetchingV1 => RequestResponseConfig {
+			Protocol::ChunkFetchingV1 | Protocol::ChunkFetchingV2 => RequestResponseConfig {
 				name: p_name,
 				max_request_size: 1_000,
 				max_response_size: POV_RESPONSE_SIZE as u64 * 3,
@@ -138,29 +154,31 @@ impl Protocol {
 				request_timeout: CHUNK_REQUEST_TIMEOUT,
 				inbound_queue: Some(tx),
 			},
-			Protocol::CollationFetchingV1 => RequestResponseConfig {
-				name: p_name,
-				max_request_size: 1_000,
-				max_response_size: POV_RESPONSE_SIZE,
-				// Taken from initial implementation in collator protocol:
-				request_timeout: POV_REQUEST_TIMEOUT_CONNECTED,
-				inbound_queue: Some(tx),
-			},
-			Protocol::PoVFetchingV1 => RequestResponseConfig {
+			Protocol::CollationFetchingV1 | Protocol::CollationFetchingV2 =>
+				RequestResponseConfig {
+					name: p_name,
+					max_request_size: 1_000,
+					max_response_size: POV_RESPONSE_SIZE,
+					// Taken from initial implementation in collator protocol:
+					request_timeout: POV_REQUEST_TIMEOUT_CONNECTED,
+					inbound_queue: Some(tx),
+				},
+			Protocol::PoVFetchingV1 | Protocol::PoVFetchingV2 => RequestResponseConfig {
 				name: p_name,
 				max_request_size: 1_000,
 				max_response_size: POV_RESPONSE_SIZE,
 				request_timeout: POV_REQUEST_TIMEOUT_CONNECTED,
 				inbound_queue: Some(tx),
 			},
-			Protocol::AvailableDataFetchingV1 => RequestResponseConfig {
-				name: p_name,
-				max_request_size: 1_000,
-				// Available data size is dominated by the PoV size.
-				max_response_size: POV_RESPONSE_SIZE,
-				request_timeout: POV_REQUEST_TIMEOUT_CONNECTED,
-				inbound_queue: Some(tx),
-			},
+			Protocol::AvailableDataFetchingV1 | Protocol::AvailableDataFetchingV2 =>
+				RequestResponseConfig {
+					name: p_name,
+					max_request_size: 1_000,
+					// Available data size is dominated by the PoV size.
+					max_response_size: POV_RESPONSE_SIZE,
+					request_timeout: POV_REQUEST_TIMEOUT_CONNECTED,
+					inbound_queue: Some(tx),
+				},
 			Protocol::StatementFetchingV1 => RequestResponseConfig {
 				name: p_name,
 				max_request_size: 1_000,
@@ -201,14 +219,14 @@ impl Protocol {
 			// times (due to network delays), 100 seems big enough to accomodate for "bursts",
 			// assuming we can service requests relatively quickly, which would need to be measured
 			// as well.
-			Protocol::ChunkFetchingV1 => 100,
+			Protocol::ChunkFetchingV1 | Protocol::ChunkFetchingV2 => 100,
 			// 10 seems reasonable, considering group sizes of max 10 validators.
-			Protocol::CollationFetchingV1 => 10,
+			Protocol::CollationFetchingV1 | Protocol::CollationFetchingV2 => 10,
 			// 10 seems reasonable, considering group sizes of max 10 validators.
-			Protocol::PoVFetchingV1 => 10,
+			Protocol::PoVFetchingV1 | Protocol::PoVFetchingV2 => 10,
 			// Validators are constantly self-selecting to request available data which may lead
 			// to constant load and occasional burstiness.
-			Protocol::AvailableDataFetchingV1 => 100,
+			Protocol::AvailableDataFetchingV1 | Protocol::AvailableDataFetchingV2 => 100,
 			// Our queue size approximation is how many blocks of the size of
 			// a runtime we can transfer within a statements timeout, minus the requests we handle
 			// in parallel.
@@ -251,8 +269,74 @@ impl Protocol {
 			Protocol::AvailableDataFetchingV1 => "/polkadot/req_available_data/1",
 			Protocol::StatementFetchingV1 => "/polkadot/req_statement/1",
 			Protocol::DisputeSendingV1 => "/polkadot/send_dispute/1",
+			Protocol::ChunkFetchingV2 => "/polkadot/req_chunk/2",
+			Protocol::CollationFetchingV2 => "/polkadot/req_collation/2",
+			Protocol::PoVFetchingV2 => "/polkadot/req_pov/2",
+			Protocol::AvailableDataFetchingV2 => "/polkadot/req_available_data/2",
 		}
 	}
+
+	/// Get the variant of this protocol with compressed responses, if there is one.
+	///
+	/// Requesters should fall back to this protocol, if a peer does not support the compressed
+	/// variant.
+	pub const fn compressed(self) -> Option<Protocol> {
+		match self {
+			Protocol::ChunkFetchingV1 => Some(Protocol::ChunkFetchingV2),
+			Protocol::CollationFetchingV1 => Some(Protocol::CollationFetchingV2),
+			Protocol::PoVFetchingV1 => Some(Protocol::PoVFetchingV2),
+			Protocol::AvailableDataFetchingV1 => Some(Protocol::AvailableDataFetchingV2),
+			Protocol::StatementFetchingV1 |
+			Protocol::DisputeSendingV1 |
+			Protocol::ChunkFetchingV2 |
+			Protocol::CollationFetchingV2 |
+			Protocol::PoVFetchingV2 |
+			Protocol::AvailableDataFetchingV2 => None,
+		}
+	}
+
+	/// The maximum size of a response, after decompression.
+	///
+	/// This is the response size limit of the uncompressed variant of the protocol.
+	fn max_decompressed_response_size(self) -> usize {
+		match self {
+			Protocol::ChunkFetchingV1 | Protocol::ChunkFetchingV2 => POV_RESPONSE_SIZE as usize * 3,
+			Protocol::CollationFetchingV1 |
+			Protocol::CollationFetchingV2 |
+			Protocol::PoVFetchingV1 |
+			Protocol::PoVFetchingV2 |
+			Protocol::AvailableDataFetchingV1 |
+			Protocol::AvailableDataFetchingV2 => POV_RESPONSE_SIZE as usize,
+			Protocol::StatementFetchingV1 => STATEMENT_RESPONSE_SIZE as usize,
+			Protocol::DisputeSendingV1 => 100,
+		}
+	}
+}
+
+/// Compress an encoded response to be sent on the compressed variant of `protocol`.
+///
+/// The response is only compressed if that makes it smaller, otherwise it is sent as is.
+pub fn compress_response(protocol: Protocol, response: Vec<u8>) -> Vec<u8> {
+	if response.len() < MIN_COMPRESSED_RESPONSE_SIZE {
+		return response
+	}
+
+	match sp_maybe_compressed_blob::compress(&response, protocol.max_decompressed_response_size()) {
+		Some(compressed) if compressed.len() < response.len() => compressed,
+		_ => response,
+	}
+}
+
+/// Decompress an encoded response received on `protocol`.
+///
+/// Responses which are not compressed are returned as is. Fails if the decompressed response
+/// exceeds the response size limit of the protocol.
+pub fn decompress_response(
+	protocol: Protocol,
+	response: &[u8],
+) -> Result<Cow<[u8]>, DecodingError> {
+	sp_maybe_compressed_blob::decompress(response, protocol.max_decompressed_response_size())
+		.map_err(|_| DecodingError::from("Response could not be decompressed"))
 }
 
 /// Common properties of any `Request`.
@@ -263,3 +347,41 @@ pub trait IsRequest {
 	/// What protocol this `Request` implements.
 	const PROTOCOL: Protocol;
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn compressed_responses_round_trip() {
+		let response = vec![42u8; 10_000];
+
+		let compressed = compress_response(Protocol::PoVFetchingV2, response.clone());
+		assert!(compressed.len() < response.len());
+		assert_eq!(
+			decompress_response(Protocol::PoVFetchingV2, &compressed).unwrap().as_ref(),
+			&response[..],
+		);
+	}
+
+	#[test]
+	fn small_responses_are_not_compressed() {
+		let response = vec![42u8; 100];
+
+		let compressed = compress_response(Protocol::ChunkFetchingV2, response.clone());
+		assert_eq!(compressed, response);
+		assert_eq!(
+			decompress_response(Protocol::ChunkFetchingV2, &compressed).unwrap().as_ref(),
+			&response[..],
+		);
+	}
+
+	#[test]
+	fn decompressed_size_is_limited() {
+		let limit = Protocol::AvailableDataFetchingV2.max_decompressed_response_size();
+		let bomb = sp_maybe_compressed_blob::compress(&vec![0u8; limit + 1], limit + 1).unwrap();
+
+		assert!(bomb.len() < limit);
+		assert!(decompress_response(Protocol::AvailableDataFetchingV2, &bomb).is_err());
+	}
+}
@@ -73,7 +73,7 @@ use requester::{fetch, RequesterMessage};
 
 /// Background task logic for responding for large statements.
 mod responder;
-use responder::{respond, ResponderMessage};
+use responder::{respond, RequestedStatement, ResponderMessage};
 
 /// Metrics for the statement distribution
 pub(crate) mod metrics;
@@ -670,6 +670,8 @@ struct ActiveHeadData {
 	session_index: sp_staking::SessionIndex,
 	/// How many `Seconded` statements we've seen per validator.
 	seconded_counts: HashMap<ValidatorIndex, usize>,
+	/// The other validators of our backing group at the head's child, if we are in one.
+	backing_group: HashSet<AuthorityDiscoveryId>,
 	/// A Jaeger span for this head, so we can attach data to it.
 	span: PerLeafSpan,
 }
@@ -678,6 +680,7 @@ impl ActiveHeadData {
 	fn new(
 		validators: Vec<ValidatorId>,
 		session_index: sp_staking::SessionIndex,
+		backing_group: HashSet<AuthorityDiscoveryId>,
 		span: PerLeafSpan,
 	) -> Self {
 		ActiveHeadData {
@@ -687,6 +690,7 @@ impl ActiveHeadData {
 			validators,
 			session_index,
 			seconded_counts: Default::default(),
+			backing_group,
 			span,
 		}
 	}
@@ -1688,7 +1692,7 @@ async fn handle_network_update<Context, R>(
 				}
 			}
 		},
-		NetworkBridgeEvent::PeerMessage(peer, Versioned::V1(message)) => {
+		NetworkBridgeEvent::PeerMessage(peer, Versioned::V1(message) | Versioned::V2(message)) => {
 			handle_incoming_message_and_circulate(
 				peer,
 				topology_storage,
@@ -1760,6 +1764,7 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 			respond(
 				self.req_receiver.take().expect("Mandatory argument to new. qed"),
 				res_sender.clone(),
+				self.metrics.clone(),
 			)
 			.boxed(),
 		)
@@ -1852,7 +1857,15 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 						)),
 				};
 
-				tx.send(committed).map_err(|_| JfyiError::ResponderGetDataCanceled)?;
+				let from_backing_group = peers
+					.get(&requesting_peer)
+					.and_then(|peer| peer.maybe_authority.as_ref())
+					.map_or(false, |ids| {
+						ids.iter().any(|id| active_head.backing_group.contains(id))
+					});
+
+				tx.send(RequestedStatement { committed, from_backing_group })
+					.map_err(|_| JfyiError::ResponderGetDataCanceled)?;
 			},
 		}
 		Ok(())
@@ -2017,10 +2030,23 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 						.get_session_info_by_index(ctx.sender(), relay_parent, session_index)
 						.await?;
 					let session_info = &info.session_info;
+					let validator_info = &info.validator_info;
+
+					let backing_group = validator_info
+						.our_group
+						.map(|our_group| {
+							session_info.validator_groups[our_group.0 as usize]
+								.iter()
+								.filter(|i| Some(**i) != validator_info.our_index)
+								.map(|i| session_info.discovery_keys[i.0 as usize].clone())
+								.collect()
+						})
+						.unwrap_or_default();
 
 					active_heads.entry(relay_parent).or_insert(ActiveHeadData::new(
 						session_info.validators.clone(),
 						session_index,
+						backing_group,
 						span,
 					));
 				}
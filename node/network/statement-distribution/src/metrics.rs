@@ -30,6 +30,8 @@ struct MetricsInner {
 	network_bridge_update_v1: prometheus::HistogramVec,
 	statements_unexpected: prometheus::CounterVec<prometheus::U64>,
 	created_message_size: prometheus::Gauge<prometheus::U64>,
+	served_requests: prometheus::CounterVec<prometheus::U64>,
+	dropped_requests: prometheus::CounterVec<prometheus::U64>,
 }
 
 /// Statement Distribution metrics.
@@ -115,6 +117,22 @@ impl Metrics {
 			metrics.created_message_size.set(size as u64);
 		}
 	}
+
+	/// Update the served requests counter, labeled by whether the requester is in our backing
+	/// group.
+	pub fn on_served_request(&self, from_backing_group: bool) {
+		if let Some(metrics) = &self.0 {
+			let label = if from_backing_group { "backing_group" } else { "other" };
+			metrics.served_requests.with_label_values(&[label]).inc();
+		}
+	}
+
+	/// Update the dropped requests counter with the reason the request was not served.
+	pub fn on_dropped_request(&self, reason: &'static str) {
+		if let Some(metrics) = &self.0 {
+			metrics.dropped_requests.with_label_values(&[reason]).inc();
+		}
+	}
 }
 
 impl metrics::Metrics for Metrics {
@@ -194,6 +212,26 @@ impl metrics::Metrics for Metrics {
 				))?,
 				registry,
 			)?,
+			served_requests: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_statement_distribution_served_requests_total",
+						"Number of large statement requests we sent the data for.",
+					),
+					&["requester"],
+				)?,
+				registry,
+			)?,
+			dropped_requests: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_statement_distribution_dropped_requests_total",
+						"Number of large statement requests we rejected without sending the data.",
+					),
+					&["reason"],
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
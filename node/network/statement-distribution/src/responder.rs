@@ -14,10 +14,12 @@
 
 //! Large statement responding background task logic.
 
+use std::collections::{HashMap, VecDeque};
+
 use futures::{
 	channel::{mpsc, oneshot},
 	stream::FuturesUnordered,
-	SinkExt, StreamExt,
+	FutureExt, SinkExt, StreamExt,
 };
 
 use fatality::Nested;
@@ -25,16 +27,28 @@ use polkadot_node_network_protocol::{
 	request_response::{
 		incoming::OutgoingResponse,
 		v1::{StatementFetchingRequest, StatementFetchingResponse},
-		IncomingRequestReceiver, MAX_PARALLEL_STATEMENT_REQUESTS,
+		IncomingRequest, IncomingRequestReceiver, MAX_PARALLEL_STATEMENT_REQUESTS,
 	},
 	PeerId, UnifiedReputationChange as Rep,
 };
 use polkadot_primitives::v2::{CandidateHash, CommittedCandidateReceipt, Hash};
 
-use crate::LOG_TARGET;
+use crate::{metrics::Metrics, LOG_TARGET};
 
 const COST_INVALID_REQUEST: Rep = Rep::CostMajor("Peer sent unparsable request");
 
+/// How many responses we send to a single peer in parallel, including queued ones.
+///
+/// A peer requests each candidate separately, so this allows fetching the statements of two
+/// candidates at once. At the minimum bandwidth we can send about 11 responses of maximum size
+/// within `STATEMENTS_TIMEOUT`, so two of them still leave most of it to other peers.
+///
+/// Further requests of that peer are rejected right away, so it can try another peer.
+pub(crate) const MAX_PARALLEL_RESPONSES_PER_PEER: usize = 2;
+
+/// How many responses we queue at most, while `MAX_PARALLEL_STATEMENT_REQUESTS` are being sent.
+const MAX_QUEUED_RESPONSES: usize = MAX_PARALLEL_STATEMENT_REQUESTS as usize;
+
 /// Messages coming from a background task.
 pub enum ResponderMessage {
 	/// Get an update of available peers to try for fetching a given statement.
@@ -42,10 +56,72 @@ pub enum ResponderMessage {
 		requesting_peer: PeerId,
 		relay_parent: Hash,
 		candidate_hash: CandidateHash,
-		tx: oneshot::Sender<CommittedCandidateReceipt>,
+		tx: oneshot::Sender<RequestedStatement>,
 	},
 }
 
+/// The data of a requested large statement.
+pub struct RequestedStatement {
+	/// The candidate receipt to respond with.
+	pub committed: CommittedCandidateReceipt,
+	/// Whether the requesting peer is in our backing group.
+	pub from_backing_group: bool,
+}
+
+/// A response waiting to be sent.
+struct QueuedResponse {
+	req: IncomingRequest<StatementFetchingRequest>,
+	data: RequestedStatement,
+}
+
+/// Responses waiting to be sent, the ones for our backing group first.
+#[derive(Default)]
+struct ResponseQueue {
+	backing_group: VecDeque<QueuedResponse>,
+	other: VecDeque<QueuedResponse>,
+}
+
+impl ResponseQueue {
+	fn len(&self) -> usize {
+		self.backing_group.len() + self.other.len()
+	}
+
+	/// Number of queued responses to the given peer.
+	fn queued_for(&self, peer: &PeerId) -> usize {
+		self.backing_group
+			.iter()
+			.chain(self.other.iter())
+			.filter(|r| &r.req.peer == peer)
+			.count()
+	}
+
+	/// Queue a response.
+	///
+	/// If the queue is full, this returns the response which got dropped instead: the newest one
+	/// not for our backing group if `response` is for our backing group, `response` itself
+	/// otherwise.
+	fn push(&mut self, response: QueuedResponse) -> Option<QueuedResponse> {
+		let dropped = if self.len() < MAX_QUEUED_RESPONSES {
+			None
+		} else if response.data.from_backing_group && !self.other.is_empty() {
+			self.other.pop_back()
+		} else {
+			return Some(response)
+		};
+
+		if response.data.from_backing_group {
+			self.backing_group.push_back(response);
+		} else {
+			self.other.push_back(response);
+		}
+		dropped
+	}
+
+	fn pop(&mut self) -> Option<QueuedResponse> {
+		self.backing_group.pop_front().or_else(|| self.other.pop_front())
+	}
+}
+
 /// A fetching task, taking care of fetching large statements via request/response.
 ///
 /// A fetch task does not know about a particular `Statement` instead it just tries fetching a
@@ -54,8 +130,12 @@ pub enum ResponderMessage {
 pub async fn respond(
 	mut receiver: IncomingRequestReceiver<StatementFetchingRequest>,
 	mut sender: mpsc::Sender<ResponderMessage>,
+	metrics: Metrics,
 ) {
 	let mut pending_out = FuturesUnordered::new();
+	// Responses being sent, per peer.
+	let mut in_flight: HashMap<PeerId, usize> = HashMap::new();
+	let mut queue = ResponseQueue::default();
 	loop {
 		// Ensure we are not handling too many requests in parallel.
 		// We do this for three reasons:
@@ -70,12 +150,36 @@ pub async fn respond(
 		// From this perspective we would not want parallel response sending at all, but we don't
 		// want a single slow requester slowing everyone down, so we want some parallelism for that
 		// reason.
-		if pending_out.len() >= MAX_PARALLEL_STATEMENT_REQUESTS as usize {
-			// Wait for one to finish:
-			pending_out.next().await;
+		while pending_out.len() < MAX_PARALLEL_STATEMENT_REQUESTS as usize {
+			let QueuedResponse { req, data } = match queue.pop() {
+				Some(queued) => queued,
+				None => break,
+			};
+
+			let peer = req.peer;
+			let (pending_sent_tx, pending_sent_rx) = oneshot::channel();
+			let response = OutgoingResponse {
+				result: Ok(StatementFetchingResponse::Statement(data.committed)),
+				reputation_changes: Vec::new(),
+				sent_feedback: Some(pending_sent_tx),
+			};
+			*in_flight.entry(peer).or_default() += 1;
+			pending_out.push(pending_sent_rx.map(move |_| peer));
+			metrics.on_served_request(data.from_backing_group);
+			if let Err(_) = req.send_outgoing_response(response) {
+				gum::debug!(target: LOG_TARGET, "Sending response failed");
+			}
 		}
 
-		let req = match receiver.recv(|| vec![COST_INVALID_REQUEST]).await.into_nested() {
+		let req = futures::select! {
+			peer = pending_out.select_next_some() => {
+				note_response_sent(&mut in_flight, peer);
+				continue
+			},
+			req = receiver.recv(|| vec![COST_INVALID_REQUEST]).fuse() => req,
+		};
+
+		let req = match req.into_nested() {
 			Ok(Ok(v)) => v,
 			Err(fatal) => {
 				gum::debug!(target: LOG_TARGET, error = ?fatal, "Shutting down request responder");
@@ -87,6 +191,24 @@ pub async fn respond(
 			},
 		};
 
+		// Account for responses which have been sent in the meantime.
+		while let Some(Some(peer)) = pending_out.next().now_or_never() {
+			note_response_sent(&mut in_flight, peer);
+		}
+
+		let parallel_responses =
+			in_flight.get(&req.peer).copied().unwrap_or_default() + queue.queued_for(&req.peer);
+		if parallel_responses >= MAX_PARALLEL_RESPONSES_PER_PEER {
+			gum::trace!(
+				target: LOG_TARGET,
+				peer = ?req.peer,
+				"Rejecting request, too many parallel responses to peer",
+			);
+			metrics.on_dropped_request("peer_limit");
+			send_error(req);
+			continue
+		}
+
 		let (tx, rx) = oneshot::channel();
 		if let Err(err) = sender
 			.feed(ResponderMessage::GetData {
@@ -100,22 +222,42 @@ pub async fn respond(
 			gum::debug!(target: LOG_TARGET, ?err, "Shutting down responder");
 			return
 		}
-		let response = match rx.await {
+		let data = match rx.await {
 			Err(err) => {
 				gum::debug!(target: LOG_TARGET, ?err, "Requested data not found.");
-				Err(())
+				metrics.on_dropped_request("not_found");
+				send_error(req);
+				continue
 			},
-			Ok(v) => Ok(StatementFetchingResponse::Statement(v)),
-		};
-		let (pending_sent_tx, pending_sent_rx) = oneshot::channel();
-		let response = OutgoingResponse {
-			result: response,
-			reputation_changes: Vec::new(),
-			sent_feedback: Some(pending_sent_tx),
+			Ok(v) => v,
 		};
-		pending_out.push(pending_sent_rx);
-		if let Err(_) = req.send_outgoing_response(response) {
-			gum::debug!(target: LOG_TARGET, "Sending response failed");
+
+		if let Some(dropped) = queue.push(QueuedResponse { req, data }) {
+			gum::trace!(
+				target: LOG_TARGET,
+				peer = ?dropped.req.peer,
+				"Rejecting request, response queue is full",
+			);
+			metrics.on_dropped_request("queue_full");
+			send_error(dropped.req);
+		}
+	}
+}
+
+fn note_response_sent(in_flight: &mut HashMap<PeerId, usize>, peer: PeerId) {
+	if let Some(count) = in_flight.get_mut(&peer) {
+		*count = count.saturating_sub(1);
+		if *count == 0 {
+			in_flight.remove(&peer);
 		}
 	}
 }
+
+/// Reject a request without sending any data.
+fn send_error(req: IncomingRequest<StatementFetchingRequest>) {
+	let response =
+		OutgoingResponse { result: Err(()), reputation_changes: Vec::new(), sent_feedback: None };
+	if let Err(_) = req.send_outgoing_response(response) {
+		gum::debug!(target: LOG_TARGET, "Sending response failed");
+	}
+}
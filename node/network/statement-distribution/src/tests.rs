@@ -80,6 +80,7 @@ fn active_head_accepts_only_2_seconded_per_validator() {
 	let mut head_data = ActiveHeadData::new(
 		validators,
 		session_index,
+		HashSet::new(),
 		PerLeafSpan::new(Arc::new(jaeger::Span::Disabled), "test"),
 	);
 
@@ -426,6 +427,7 @@ fn peer_view_update_sends_messages() {
 		let mut data = ActiveHeadData::new(
 			validators,
 			session_index,
+			HashSet::new(),
 			PerLeafSpan::new(Arc::new(jaeger::Span::Disabled), "test"),
 		);
 
@@ -1365,6 +1367,48 @@ fn receiving_large_statement_from_one_sends_to_another_and_to_candidate_backing(
 			pending_response,
 		};
 		req_cfg.inbound_queue.as_mut().unwrap().send(req).await.unwrap();
+		let response = response_rx.await.unwrap();
+		let StatementFetchingResponse::Statement(committed) =
+			Decode::decode(&mut response.result.as_ref().unwrap().as_ref()).unwrap();
+		assert_eq!(committed, candidate);
+
+		// Requests from peer_b are served in parallel, up to a limit:
+		let mut responses = vec![response];
+		for _ in 1..responder::MAX_PARALLEL_RESPONSES_PER_PEER {
+			let (pending_response, response_rx) = oneshot::channel();
+			let req = sc_network::config::IncomingRequest {
+				peer: peer_b,
+				payload: inner_req.encode(),
+				pending_response,
+			};
+			req_cfg.inbound_queue.as_mut().unwrap().send(req).await.unwrap();
+			let response = response_rx.await.unwrap();
+			assert!(response.result.is_ok());
+			responses.push(response);
+		}
+
+		// As long as those responses are being sent, further requests from peer_b are rejected:
+		let (pending_response, response_rx) = oneshot::channel();
+		let req = sc_network::config::IncomingRequest {
+			peer: peer_b,
+			payload: inner_req.encode(),
+			pending_response,
+		};
+		req_cfg.inbound_queue.as_mut().unwrap().send(req).await.unwrap();
+		assert_matches!(
+			response_rx.await.unwrap().result,
+			Err(()) => {}
+		);
+
+		// Once one has been sent, peer_b can request again:
+		responses.pop();
+		let (pending_response, response_rx) = oneshot::channel();
+		let req = sc_network::config::IncomingRequest {
+			peer: peer_b,
+			payload: inner_req.encode(),
+			pending_response,
+		};
+		req_cfg.inbound_queue.as_mut().unwrap().send(req).await.unwrap();
 		let StatementFetchingResponse::Statement(committed) =
 			Decode::decode(&mut response_rx.await.unwrap().result.unwrap().as_ref()).unwrap();
 		assert_eq!(committed, candidate);
@@ -2261,6 +2305,63 @@ fn handle_multiple_seconded_statements() {
 	executor::block_on(future::join(test_fut, virtual_overseer_fut));
 }
 
+#[test]
+fn concurrent_fetches_for_different_candidates_succeed() {
+	let (statement_req_receiver, mut req_cfg) = IncomingRequest::get_config_receiver();
+	let (responder_tx, mut responder_rx) = mpsc::channel(1);
+	let peer = PeerId::random();
+
+	let candidates: Vec<_> = (0..responder::MAX_PARALLEL_RESPONSES_PER_PEER)
+		.map(|i| {
+			let mut candidate = dummy_committed_candidate_receipt(dummy_hash());
+			candidate.descriptor.para_id = (i as u32).into();
+			candidate
+		})
+		.collect();
+
+	let test_fut = async move {
+		// None of the responses has been sent yet while the next candidate is requested.
+		let mut responses = Vec::new();
+		for candidate in &candidates {
+			let (pending_response, response_rx) = oneshot::channel();
+			let req = sc_network::config::IncomingRequest {
+				peer,
+				payload: StatementFetchingRequest {
+					relay_parent: candidate.descriptor.relay_parent,
+					candidate_hash: candidate.hash(),
+				}
+				.encode(),
+				pending_response,
+			};
+			req_cfg.inbound_queue.as_mut().unwrap().send(req).await.unwrap();
+
+			assert_matches!(
+				responder_rx.next().await,
+				Some(ResponderMessage::GetData { requesting_peer, candidate_hash, tx, .. }) => {
+					assert_eq!(requesting_peer, peer);
+					assert_eq!(candidate_hash, candidate.hash());
+					let data =
+						RequestedStatement { committed: candidate.clone(), from_backing_group: false };
+					assert!(tx.send(data).is_ok());
+				}
+			);
+
+			responses.push(response_rx.await.unwrap());
+		}
+
+		for (response, candidate) in responses.iter().zip(&candidates) {
+			let StatementFetchingResponse::Statement(committed) =
+				Decode::decode(&mut response.result.as_ref().unwrap().as_ref()).unwrap();
+			assert_eq!(&committed, candidate);
+		}
+	};
+
+	executor::block_on(future::join(
+		respond(statement_req_receiver, responder_tx, Metrics::default()),
+		test_fut,
+	));
+}
+
 fn make_session_info(validators: Vec<Pair>, groups: Vec<Vec<u32>>) -> SessionInfo {
 	let validator_groups: Vec<Vec<ValidatorIndex>> = groups
 		.iter()
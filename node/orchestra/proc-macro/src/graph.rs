@@ -30,15 +30,12 @@ pub(crate) struct ConnectionGraph<'a> {
 	/// the receiver of the message.
 	pub(crate) graph: Graph<Ident, Path>,
 	/// Cycles within the graph
-	#[cfg_attr(not(feature = "graph"), allow(dead_code))]
 	pub(crate) sccs: Vec<Vec<NodeIndex>>,
 	/// Messages that are never being sent (and by which subsystem), but are consumed
 	/// Maps the message `Path` to the subsystem `Ident` represented by `NodeIndex`.
-	#[cfg_attr(not(feature = "graph"), allow(dead_code))]
 	pub(crate) unsent_messages: HashMap<&'a Path, (&'a Ident, NodeIndex)>,
 	/// Messages being sent (and by which subsystem), but not consumed by any subsystem
 	/// Maps the message `Path` to the subsystem `Ident` represented by `NodeIndex`.
-	#[cfg_attr(not(feature = "graph"), allow(dead_code))]
 	pub(crate) unconsumed_messages: HashMap<&'a Path, Vec<(&'a Ident, NodeIndex)>>,
 }
 
@@ -42,12 +42,17 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 	let consumes = &info.consumes_without_wip();
 	let channel_name = &info.channel_names_without_wip("");
 	let channel_name_unbounded = &info.channel_names_without_wip("_unbounded");
+	let channel_name_priority = &info.channel_names_without_wip("_priority");
 
 	let channel_name_tx = &info.channel_names_without_wip("_tx");
 	let channel_name_unbounded_tx = &info.channel_names_without_wip("_unbounded_tx");
+	let channel_name_priority_tx = &info.channel_names_without_wip("_priority_tx");
 
 	let channel_name_rx = &info.channel_names_without_wip("_rx");
 	let channel_name_unbounded_rx = &info.channel_names_without_wip("_unbounded_rx");
+	let channel_name_priority_rx = &info.channel_names_without_wip("_priority_rx");
+
+	let channel_name_pending_kinds = &info.channel_names_without_wip("_pending_kinds");
 
 	let baggage_name = &info.baggage_names();
 	let baggage_generic_ty = &info.baggage_generic_types();
@@ -80,6 +85,47 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 		})
 		.collect::<Vec<_>>();
 
+	let signal = &info.extern_signal_ty;
+
+	let supervision = info
+		.subsystems()
+		.iter()
+		.filter(|ssf| !ssf.wip)
+		.map(|ssf| match ssf.supervision {
+			Supervision::FailFast => quote! { #support_crate ::supervision::Supervision::FailFast },
+			Supervision::Restart => quote! { #support_crate ::supervision::Supervision::Restart },
+			Supervision::Degrade => quote! { #support_crate ::supervision::Supervision::Degrade },
+		})
+		.collect::<Vec<_>>();
+
+	// Restarted subsystems are started from a clone of the initial instance.
+	let restart = info
+		.subsystems()
+		.iter()
+		.filter(|ssf| !ssf.wip)
+		.map(|ssf| {
+			if ssf.supervision != Supervision::Restart {
+				return quote! { None }
+			}
+			let subsystem_name = &ssf.name;
+			let consumes = &ssf.message_to_consume;
+			quote! {
+				{
+					let template = ::std::clone::Clone::clone(& #subsystem_name);
+					let make_ctx = ::std::clone::Clone::clone(&make_ctx);
+					let restart: #support_crate ::supervision::RestartFn< #signal, #consumes, #error_ty > =
+						Box::new(move |incoming| {
+							#support_crate ::Subsystem::start(
+								::std::clone::Clone::clone(&template),
+								make_ctx(incoming),
+							)
+						});
+					Some(restart)
+				}
+			}
+		})
+		.collect::<Vec<_>>();
+
 	// Helpers to use within quote! macros
 	let spawner_where_clause: syn::TypeParam = parse_quote! {
 			S: #support_crate ::Spawner
@@ -161,6 +207,10 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 
 							channel_capacity: self.channel_capacity,
 							signal_capacity: self.signal_capacity,
+							tof_config: self.tof_config,
+							restart_backoff: self.restart_backoff,
+							count_pending_kinds: self.count_pending_kinds,
+							restart_signals: self.restart_signals,
 						}
 					}
 					/// Specify the the initialization function for a subsystem
@@ -186,6 +236,10 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 
 							channel_capacity: self.channel_capacity,
 							signal_capacity: self.signal_capacity,
+							tof_config: self.tof_config,
+							restart_backoff: self.restart_backoff,
+							count_pending_kinds: self.count_pending_kinds,
+							restart_signals: self.restart_signals,
 						}
 					}
 				}
@@ -225,6 +279,10 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 
 							channel_capacity: self.channel_capacity,
 							signal_capacity: self.signal_capacity,
+							tof_config: self.tof_config,
+							restart_backoff: self.restart_backoff,
+							count_pending_kinds: self.count_pending_kinds,
+							restart_signals: self.restart_signals,
 						}
 					}
 				}
@@ -275,6 +333,10 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 
 						channel_capacity: self.channel_capacity,
 						signal_capacity: self.signal_capacity,
+						tof_config: self.tof_config,
+						restart_backoff: self.restart_backoff,
+						count_pending_kinds: self.count_pending_kinds,
+						restart_signals: self.restart_signals,
 					}
 				}
 			}
@@ -296,6 +358,10 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 
 						channel_capacity: self.channel_capacity,
 						signal_capacity: self.signal_capacity,
+						tof_config: self.tof_config,
+						restart_backoff: self.restart_backoff,
+						count_pending_kinds: self.count_pending_kinds,
+						restart_signals: self.restart_signals,
 					}
 				}
 			}
@@ -317,9 +383,14 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 			let consumes = &ssf.message_to_consume;
 			let subsystem_sender_trait = format_ident!("{}SenderTrait", ssf.generic);
 			let subsystem_ctx_trait = format_ident!("{}ContextTrait", ssf.generic);
+			let restartable = if ssf.supervision == Supervision::Restart {
+				quote! { + ::std::clone::Clone + Send + 'static }
+			} else {
+				TokenStream::new()
+			};
 			quote! {
 				#field_type:
-					#support_crate::Subsystem< #subsystem_ctx_name < #consumes>, #error_ty>,
+					#support_crate::Subsystem< #subsystem_ctx_name < #consumes>, #error_ty> #restartable,
 				<#subsystem_ctx_name< #consumes > as #subsystem_ctx_trait>::Sender:
 					#subsystem_sender_trait,
 				#subsystem_ctx_name< #consumes >:
@@ -443,6 +514,13 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 			// or the default value.
 			channel_capacity: Option<usize>,
 			signal_capacity: Option<usize>,
+			// if `None`, the default time of flight sampling is used
+			tof_config: Option<#support_crate ::metered::TofConfig>,
+			// if `None`, the default backoff is used
+			restart_backoff: Option<#support_crate ::supervision::RestartBackoff>,
+			count_pending_kinds: bool,
+			// if `None`, restarted subsystems only receive the signals sent after their restart
+			restart_signals: Option<#support_crate ::supervision::RestartSignalsFn< #signal >>,
 		}
 	});
 
@@ -467,6 +545,10 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 
 					channel_capacity: None,
 					signal_capacity: None,
+					tof_config: None,
+					restart_backoff: None,
+					count_pending_kinds: false,
+					restart_signals: None,
 				}
 			}
 		}
@@ -494,6 +576,10 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 
 					channel_capacity: self.channel_capacity,
 					signal_capacity: self.signal_capacity,
+					tof_config: self.tof_config,
+					restart_backoff: self.restart_backoff,
+					count_pending_kinds: self.count_pending_kinds,
+					restart_signals: self.restart_signals,
 				}
 			}
 		}
@@ -519,6 +605,40 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 				self.channel_capacity = Some(capacity);
 				self
 			}
+
+			/// Set how the time of flight of messages in the interconnecting message channels
+			/// is measured.
+			pub fn message_tof_config(mut self, config: #support_crate ::metered::TofConfig) -> Self
+			{
+				self.tof_config = Some(config);
+				self
+			}
+
+			/// Set the backoff between restarts of subsystems supervised with `supervision: Restart`.
+			pub fn restart_backoff(mut self, backoff: #support_crate ::supervision::RestartBackoff) -> Self
+			{
+				self.restart_backoff = Some(backoff);
+				self
+			}
+
+			/// Count the messages waiting for each subsystem by their kind.
+			///
+			/// The counts are available from the meters of the subsystems.
+			pub fn count_pending_message_kinds(mut self, enabled: bool) -> Self
+			{
+				self.count_pending_kinds = enabled;
+				self
+			}
+
+			/// Set the signals a subsystem supervised with `supervision: Restart` receives first
+			/// once restarted, to catch up with the signals it missed while it was down.
+			pub fn restart_signals<F>(mut self, restart_signals: F) -> Self
+			where
+				F: Fn() -> Vec< #signal > + Send + Sync + 'static,
+			{
+				self.restart_signals = Some(::std::sync::Arc::new(restart_signals));
+				self
+			}
 		}
 	});
 
@@ -528,6 +648,20 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 		.map(|ident| proc_macro2::Literal::string(ident.to_string().replace("_", "-").as_str()))
 		.collect::<Vec<_>>();
 
+	// The running instance of every subsystem in the connection graph, in order.
+	let connection_graph_instance_name = info
+		.subsystems()
+		.iter()
+		.map(|ssf| {
+			if ssf.wip {
+				quote! { ::std::option::Option::None }
+			} else {
+				let subsystem_name = &ssf.name;
+				quote! { #subsystem_name .instance.as_ref().map(|instance| instance.name) }
+			}
+		})
+		.collect::<Vec<_>>();
+
 	ts.extend(quote! {
 		/// Type used to represent a builder where all fields are initialized and the orchestra could be constructed.
 		pub type #initialized_builder<#initialized_builder_generics> = #builder<Init<S>, #( Init<#field_type>, )*>;
@@ -562,23 +696,46 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 					ToOrchestra
 				>();
 
+				let tof_config = self.tof_config.unwrap_or_default();
+				let restart_backoff = self.restart_backoff.unwrap_or_default();
+				let restart_signals = self.restart_signals;
+
 				#(
 					let (#channel_name_tx, #channel_name_rx)
 					=
-						#support_crate ::metered::channel::<
+						#support_crate ::metered::channel_with_tof_config::<
 							MessagePacket< #consumes >
 						>(
-							self.channel_capacity.unwrap_or(CHANNEL_CAPACITY)
+							self.channel_capacity.unwrap_or(CHANNEL_CAPACITY),
+							tof_config,
 						);
 				)*
 
 				#(
 					let (#channel_name_unbounded_tx, #channel_name_unbounded_rx) =
-						#support_crate ::metered::unbounded::<
+						#support_crate ::metered::unbounded_with_tof_config::<
+							MessagePacket< #consumes >
+						>(tof_config);
+				)*
+
+				#(
+					let (#channel_name_priority_tx, #channel_name_priority_rx)
+					=
+						#support_crate ::metered::channel_with_tof_config::<
 							MessagePacket< #consumes >
-						>();
+						>(
+							self.channel_capacity.unwrap_or(CHANNEL_CAPACITY),
+							tof_config,
+						);
 				)*
 
+				#(
+					let #channel_name_pending_kinds =
+						#support_crate ::PendingMessageKinds::new(self.count_pending_kinds);
+				)*
+
+				let edge_counters = #support_crate ::graph::EdgeCounters::new(&CONNECTION_GRAPH);
+
 				let channels_out =
 					ChannelsOut {
 						#(
@@ -587,6 +744,12 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 						#(
 							#channel_name_unbounded: #channel_name_unbounded_tx,
 						)*
+						#(
+							#channel_name_priority: #channel_name_priority_tx .clone(),
+						)*
+						#(
+							#channel_name_pending_kinds: #channel_name_pending_kinds .clone(),
+						)*
 					};
 
 				let mut spawner = match self.spawner {
@@ -605,36 +768,54 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 					};
 
 					let unbounded_meter = #channel_name_unbounded_rx.meter().clone();
-					// Prefer unbounded channel when selecting
-					let message_rx: SubsystemIncomingMessages< #consumes > = #support_crate ::select_with_strategy(
-						#channel_name_rx, #channel_name_unbounded_rx,
-						#support_crate ::select_message_channel_strategy
-					);
+					// Prefer the high priority lane, then the unbounded channel when selecting
+					let message_rx = SubsystemIncomingMessages::< #consumes >::new(
+						#channel_name_priority_rx, #channel_name_rx, #channel_name_unbounded_rx,
+					).with_pending_kinds(#channel_name_pending_kinds .clone());
 					let (signal_tx, signal_rx) = #support_crate ::metered::channel(
 						self.signal_capacity.unwrap_or(SIGNAL_CHANNEL_CAPACITY)
 					);
 
-					let ctx = #subsystem_ctx_name::< #consumes >::new(
-						signal_rx,
-						message_rx,
-						channels_out.clone(),
-						to_orchestra_tx.clone(),
-						#subsystem_name_str_literal
-					);
+					let make_ctx = {
+						let channels_out = channels_out.clone();
+						let to_orchestra_tx = to_orchestra_tx.clone();
+						let edge_counters = edge_counters.clone();
+						move |incoming| #subsystem_ctx_name::< #consumes >::new(
+							incoming,
+							channels_out.clone(),
+							to_orchestra_tx.clone(),
+							edge_counters.clone(),
+							#subsystem_name_str_literal
+						)
+					};
+					let restart = #restart;
 
 					let #subsystem_name: OrchestratedSubsystem< #consumes > =
-						spawn::<_,_, #blocking, _, _, _>(
+						spawn::<_,_, #blocking, _, _, _, _>(
 							&mut spawner,
 							#channel_name_tx,
+							#channel_name_priority_tx,
 							signal_tx,
 							unbounded_meter,
-							ctx,
+							#channel_name_pending_kinds,
+							#support_crate ::supervision::SubsystemIncoming::new(signal_rx, message_rx),
+							make_ctx,
 							#subsystem_name,
+							restart,
+							restart_signals.clone(),
+							#supervision,
+							restart_backoff,
 							#subsystem_name_str_literal,
 							&mut running_subsystems,
 						)?;
 				)*
 
+				let connection_graph = #support_crate ::graph::ConnectionGraphTracker::new(
+					&CONNECTION_GRAPH,
+					edge_counters,
+					vec![ #( #connection_graph_instance_name ),* ],
+				);
+
 				use #support_crate ::StreamExt;
 
 				let to_orchestra_rx = to_orchestra_rx.fuse();
@@ -654,6 +835,7 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 					running_subsystems,
 					events_rx,
 					to_orchestra_rx,
+					connection_graph,
 				};
 
 				Ok((orchestra, handle))
@@ -696,14 +878,21 @@ pub(crate) fn impl_task_kind(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 		}
 
 		/// Spawn task of kind `self` using spawner `S`.
-		pub fn spawn<S, M, TK, Ctx, E, SubSys>(
+		pub fn spawn<S, M, TK, Ctx, E, SubSys, MakeCtx>(
 			spawner: &mut S,
 			message_tx: #support_crate ::metered::MeteredSender<MessagePacket<M>>,
+			priority_tx: #support_crate ::metered::MeteredSender<MessagePacket<M>>,
 			signal_tx: #support_crate ::metered::MeteredSender< #signal >,
 			// meter for the unbounded channel
 			unbounded_meter: #support_crate ::metered::Meter,
-			ctx: Ctx,
+			pending_kinds: #support_crate ::PendingMessageKinds,
+			incoming: #support_crate ::supervision::SubsystemIncoming< #signal, M >,
+			make_ctx: MakeCtx,
 			s: SubSys,
+			restart: Option<#support_crate ::supervision::RestartFn< #signal, M, E >>,
+			restart_signals: Option<#support_crate ::supervision::RestartSignalsFn< #signal >>,
+			supervision: #support_crate ::supervision::Supervision,
+			restart_backoff: #support_crate ::supervision::RestartBackoff,
 			subsystem_name: &'static str,
 			futures: &mut #support_crate ::FuturesUnordered<BoxFuture<'static, ::std::result::Result<(), #error_ty> >>,
 		) -> ::std::result::Result<OrchestratedSubsystem<M>, #error_ty >
@@ -714,18 +903,29 @@ pub(crate) fn impl_task_kind(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 			Ctx: #support_crate ::SubsystemContext<Message=M>,
 			E: ::std::error::Error + Send + Sync + 'static + ::std::convert::From<#support_crate ::OrchestraError>,
 			SubSys: #support_crate ::Subsystem<Ctx, E>,
+			MakeCtx: FnOnce(#support_crate ::supervision::IncomingLease< #signal, M >) -> Ctx,
 		{
-			let #support_crate ::SpawnedSubsystem::<E> { future, name } = s.start(ctx);
+			let (lease, reclaim) = incoming.lend();
+			let #support_crate ::SpawnedSubsystem::<E> { future, name } = s.start(make_ctx(lease));
 
 			let (tx, rx) = #support_crate ::oneshot::channel();
 
-			let fut = Box::pin(async move {
-				if let Err(e) = future.await {
-					#support_crate ::tracing::error!(subsystem=name, err = ?e, "subsystem exited with error");
-				} else {
-					#support_crate ::tracing::debug!(subsystem=name, "subsystem exited without an error");
+			let supervision_meter = #support_crate ::supervision::SupervisionMeter::default();
+			let fut = Box::pin({
+				let supervision_meter = supervision_meter.clone();
+				async move {
+					#support_crate ::supervision::supervise(
+						name,
+						future,
+						reclaim,
+						supervision,
+						restart,
+						restart_signals,
+						restart_backoff,
+						supervision_meter,
+					).await;
+					let _ = tx.send(());
 				}
-				let _ = tx.send(());
 			});
 
 			<TK as TaskKind>::launch_task(spawner, name, subsystem_name, fut);
@@ -742,9 +942,13 @@ pub(crate) fn impl_task_kind(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 					unbounded: unbounded_meter,
 					bounded: message_tx.meter().clone(),
 					signals: signal_tx.meter().clone(),
+					priority: priority_tx.meter().clone(),
+					supervision: supervision_meter,
+					pending_kinds,
 				},
 				tx_signal: signal_tx,
 				tx_bounded: message_tx,
+				tx_priority: priority_tx,
 				signals_received: 0,
 				name,
 			});
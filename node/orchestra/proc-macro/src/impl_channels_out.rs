@@ -24,6 +24,8 @@ pub(crate) fn impl_channels_out_struct(info: &OrchestraInfo) -> Result<proc_macr
 
 	let channel_name = &info.channel_names_without_wip("");
 	let channel_name_unbounded = &info.channel_names_without_wip("_unbounded");
+	let channel_name_priority = &info.channel_names_without_wip("_priority");
+	let channel_name_pending_kinds = &info.channel_names_without_wip("_pending_kinds");
 
 	let consumes = &info.consumes_without_wip();
 
@@ -32,7 +34,11 @@ pub(crate) fn impl_channels_out_struct(info: &OrchestraInfo) -> Result<proc_macr
 
 	let support_crate = info.support_crate_name();
 
+	let message_priority = impl_message_priority(info);
+
 	let ts = quote! {
+		#message_priority
+
 		/// Collection of channels to the individual subsystems.
 		///
 		/// Naming is from the point of view of the orchestra.
@@ -53,12 +59,27 @@ pub(crate) fn impl_channels_out_struct(info: &OrchestraInfo) -> Result<proc_macr
 						MessagePacket< #consumes >
 					>,
 			)*
+
+			#(
+				/// Bounded high priority channel sender, connected to a subsystem.
+				pub #channel_name_priority:
+					#support_crate ::metered::MeteredSender<
+						MessagePacket< #consumes >
+					>,
+			)*
+
+			#(
+				/// Counts the messages waiting for a subsystem by kind.
+				pub #channel_name_pending_kinds: #support_crate ::PendingMessageKinds,
+			)*
 		}
 
 		#[allow(unreachable_code)]
 		// when no defined messages in enum
 		impl ChannelsOut {
 			/// Send a message via a bounded channel.
+			///
+			/// Messages with a high priority are sent on the high priority lane.
 			pub async fn send_and_log_error(
 				&mut self,
 				signals_received: usize,
@@ -68,9 +89,14 @@ pub(crate) fn impl_channels_out_struct(info: &OrchestraInfo) -> Result<proc_macr
 				let res: ::std::result::Result<_, _> = match message {
 				#(
 					#message_wrapper :: #consumes_variant ( inner ) => {
-						self. #channel_name .send(
-							#support_crate ::make_packet(signals_received, inner)
-						).await.map_err(|_| stringify!( #channel_name ))
+						self. #channel_name_pending_kinds .note_sent(&inner);
+						let packet = #support_crate ::make_packet(signals_received, inner);
+						match packet.message.priority() {
+							#support_crate ::Priority::High =>
+								self. #channel_name_priority .send(packet).await,
+							#support_crate ::Priority::Normal =>
+								self. #channel_name .send(packet).await,
+						}.map_err(|_| stringify!( #channel_name ))
 					}
 				)*
 					// subsystems that are wip
@@ -106,6 +132,7 @@ pub(crate) fn impl_channels_out_struct(info: &OrchestraInfo) -> Result<proc_macr
 				let res: ::std::result::Result<_, _> = match message {
 				#(
 					#message_wrapper :: #consumes_variant (inner) => {
+						self. #channel_name_pending_kinds .note_sent(&inner);
 						self. #channel_name_unbounded .unbounded_send(
 							#support_crate ::make_packet(signals_received, inner)
 						)
@@ -140,3 +167,42 @@ pub(crate) fn impl_channels_out_struct(info: &OrchestraInfo) -> Result<proc_macr
 	};
 	Ok(ts)
 }
+
+/// Define the `trait MessagePriority` and implement it for all consumed messages.
+///
+/// Variants listed in `priority: [..]` are high priority, all others normal.
+pub(crate) fn impl_message_priority(info: &OrchestraInfo) -> proc_macro2::TokenStream {
+	let support_crate = info.support_crate_name();
+
+	let mut ts = quote! {
+		/// The lane a message consumed by a subsystem is queued on.
+		///
+		/// Note: Implemented per consumed message, since there is a 1:1 relation
+		/// between consumed messages and subsystems.
+		pub trait MessagePriority {
+			/// The lane to queue the message on.
+			fn priority(&self) -> #support_crate ::Priority;
+		}
+	};
+
+	for ssf in info.subsystems() {
+		let consumes = &ssf.message_to_consume;
+		let priority_variant = &ssf.priority_variants;
+
+		ts.extend(quote! {
+			impl MessagePriority for #consumes {
+				fn priority(&self) -> #support_crate ::Priority {
+					match self {
+					#(
+						#consumes :: #priority_variant { .. } => #support_crate ::Priority::High,
+					)*
+						#[allow(unreachable_patterns)]
+						_ => #support_crate ::Priority::Normal,
+					}
+				}
+			}
+		});
+	}
+
+	ts
+}
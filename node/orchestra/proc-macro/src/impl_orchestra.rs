@@ -93,6 +93,9 @@ pub(crate) fn impl_orchestra_struct(info: &OrchestraInfo) -> proc_macro2::TokenS
 
 			/// Events that are sent to the orchestra from the outside world.
 			events_rx: #support_crate ::metered::MeteredReceiver< #event_ty >,
+
+			/// Tracks the messages sent between subsystems.
+			connection_graph: #support_crate ::graph::ConnectionGraphTracker,
 		}
 
 		impl #generics #orchestra_name #generics #where_clause {
@@ -172,6 +175,13 @@ pub(crate) fn impl_orchestra_struct(info: &OrchestraInfo) -> proc_macro2::TokenS
 				]
 			}
 
+			/// The connections between subsystems, with the number of messages sent along each.
+			///
+			/// Take a snapshot of it to inspect or export the graph.
+			pub fn connection_graph(&self) -> &#support_crate ::graph::ConnectionGraphTracker {
+				&self.connection_graph
+			}
+
 			/// Get access to internal task spawner.
 			pub fn spawner<'a> (&'a mut self) -> &'a mut S {
 				&mut self.spawner
@@ -206,12 +216,21 @@ pub(crate) fn impl_orchestrated_subsystem(info: &OrchestraInfo) -> proc_macro2::
 		impl<M> OrchestratedSubsystem<M> {
 			/// Send a message to the wrapped subsystem.
 			///
+			/// Messages with a high priority are sent on the high priority lane.
 			/// If the inner `instance` is `None`, nothing is happening.
-			pub async fn send_message2(&mut self, message: M, origin: &'static str) -> ::std::result::Result<(), #error_ty > {
+			pub async fn send_message2(&mut self, message: M, origin: &'static str) -> ::std::result::Result<(), #error_ty >
+			where
+				M: MessagePriority + ::std::fmt::Debug,
+			{
 				const MESSAGE_TIMEOUT: Duration = Duration::from_secs(10);
 
 				if let Some(ref mut instance) = self.instance {
-					match instance.tx_bounded.send(MessagePacket {
+					instance.meters.pending_kinds.note_sent(&message);
+					let tx = match message.priority() {
+						#support_crate ::Priority::High => &mut instance.tx_priority,
+						#support_crate ::Priority::Normal => &mut instance.tx_bounded,
+					};
+					match tx.send(MessagePacket {
 						signals_received: instance.signals_received,
 						message: message.into(),
 					}).timeout(MESSAGE_TIMEOUT).await
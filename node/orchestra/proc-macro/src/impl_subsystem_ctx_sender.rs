@@ -17,7 +17,8 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{Ident, Path, Result, Type};
 
-use petgraph::{visit::EdgeRef, Direction};
+use petgraph::{graph::EdgeIndex, visit::EdgeRef, Direction};
+use std::collections::HashMap;
 
 use super::*;
 
@@ -35,6 +36,21 @@ pub(crate) fn impl_subsystem_types_all(info: &OrchestraInfo) -> Result<TokenStre
 	let cg = graph::ConnectionGraph::construct(info.subsystems());
 	let graph = &cg.graph;
 
+	// Sort the edges, so their runtime indices are stable across builds.
+	let mut edges = graph
+		.edge_references()
+		.map(|edge| {
+			let message = to_variant(edge.weight(), span)?.to_string();
+			Ok((edge.source().index(), edge.target().index(), message, edge.id()))
+		})
+		.collect::<Result<Vec<(usize, usize, String, EdgeIndex)>>>()?;
+	edges.sort();
+	let edge_position = HashMap::<EdgeIndex, usize>::from_iter(
+		edges.iter().enumerate().map(|(position, edge)| (edge.3, position)),
+	);
+
+	ts.extend(impl_connection_graph_description(&cg, &edges, support_crate, span)?);
+
 	// All outgoing edges are now usable to derive everything we need
 	for node_index in graph.node_indices() {
 		let subsystem_name = graph[node_index].to_string();
@@ -47,12 +63,17 @@ pub(crate) fn impl_subsystem_types_all(info: &OrchestraInfo) -> Result<TokenStre
 			.map(|edge| {
 				let message_ty = edge.weight();
 				let subsystem_generic_consumer = graph[edge.target()].clone();
-				Ok((to_variant(message_ty, span.clone())?, subsystem_generic_consumer))
+				Ok((
+					to_variant(message_ty, span.clone())?,
+					subsystem_generic_consumer,
+					edge_position[&edge.id()],
+				))
 			})
-			.collect::<Result<Vec<(Ident, Ident)>>>()?;
+			.collect::<Result<Vec<(Ident, Ident, usize)>>>()?;
 
 		// Split it for usage with quote
 		let outgoing_variant = outgoing_to_consumer.iter().map(|x| x.0.clone()).collect::<Vec<_>>();
+		let edge_index = outgoing_to_consumer.iter().map(|x| x.2).collect::<Vec<_>>();
 		let subsystem_generic = outgoing_to_consumer.into_iter().map(|x| x.1).collect::<Vec<_>>();
 
 		ts.extend(quote! {
@@ -72,6 +93,19 @@ pub(crate) fn impl_subsystem_types_all(info: &OrchestraInfo) -> Result<TokenStre
 					}
 				}
 			}
+
+			impl #support_crate ::graph::CountedEdge for #outgoing_wrapper {
+				fn edge_index(&self) -> ::std::option::Option<usize> {
+					match self {
+					#(
+						#outgoing_wrapper :: #outgoing_variant ( _ ) => ::std::option::Option::Some( #edge_index ),
+					)*
+						// Messages no subsystem consumes
+						#[allow(unreachable_patterns)]
+						_ => ::std::option::Option::None,
+					}
+				}
+			}
 		})
 	}
 
@@ -151,6 +185,60 @@ pub(crate) fn impl_subsystem_types_all(info: &OrchestraInfo) -> Result<TokenStre
 	Ok(ts)
 }
 
+/// Generates the runtime description of the connection graph, see `orchestra::graph`.
+///
+/// The `edges` are sorted, their positions are the indices of the edges at runtime.
+fn impl_connection_graph_description(
+	cg: &graph::ConnectionGraph,
+	edges: &[(usize, usize, String, EdgeIndex)],
+	support_crate: &Path,
+	span: Span,
+) -> Result<TokenStream> {
+	let graph = &cg.graph;
+
+	let subsystem = graph.node_indices().map(|idx| graph[idx].to_string());
+
+	let edge_sender = edges.iter().map(|edge| edge.0);
+	let edge_receiver = edges.iter().map(|edge| edge.1);
+	let edge_message = edges.iter().map(|edge| &edge.2);
+
+	let mut cycles = Vec::from_iter(cg.sccs.iter().map(|scc| {
+		let mut scc = Vec::from_iter(scc.iter().map(|idx| idx.index()));
+		scc.sort();
+		scc
+	}));
+	cycles.sort();
+	let cycles = cycles.into_iter().map(|cycle| quote! { &[ #( #cycle ),* ] });
+
+	let mut unsent = cg
+		.unsent_messages
+		.iter()
+		.map(|(message, (_, idx))| Ok((to_variant(message, span)?.to_string(), idx.index())))
+		.collect::<Result<Vec<_>>>()?;
+	unsent.sort();
+	let unsent = unsent.into_iter().map(|(message, idx)| quote! { (#message, #idx) });
+
+	let mut unconsumed = Vec::new();
+	for (message, senders) in cg.unconsumed_messages.iter() {
+		let message = to_variant(message, span)?.to_string();
+		unconsumed.extend(senders.iter().map(|(_, idx)| (message.clone(), idx.index())));
+	}
+	unconsumed.sort();
+	let unconsumed = unconsumed.into_iter().map(|(message, idx)| quote! { (#message, #idx) });
+
+	Ok(quote! {
+		/// The connections between the subsystems of the orchestra.
+		static CONNECTION_GRAPH: #support_crate ::graph::ConnectionGraphDescription =
+			#support_crate ::graph::ConnectionGraphDescription {
+				subsystems: &[ #( #subsystem ),* ],
+				edges: &[ #( (#edge_sender, #edge_receiver, #edge_message) ),* ],
+				cycles: &[ #( #cycles ),* ],
+				unsent_messages: &[ #( #unsent ),* ],
+				unconsumed_messages: &[ #( #unconsumed ),* ],
+			};
+	})
+}
+
 /// Extract the final component of the message type path as used in the `#[subsystem(consumes: path::to::Foo)]` annotation.
 fn to_variant(path: &Path, span: Span) -> Result<Ident> {
 	let ident = path
@@ -222,6 +310,8 @@ pub(crate) fn impl_subsystem_sender(
 			channels: ChannelsOut,
 			/// Systemwide tick for which signals were received by all subsystems.
 			signals_received: SignalsReceived,
+			/// Counts of the messages sent between subsystems.
+			edge_counters: #support_crate ::graph::EdgeCounters,
 			/// Keep that marker around.
 			_phantom: ::core::marker::PhantomData< OutgoingWrapper >,
 		}
@@ -233,6 +323,7 @@ pub(crate) fn impl_subsystem_sender(
 				Self {
 					channels: self.channels.clone(),
 					signals_received: self.signals_received.clone(),
+					edge_counters: self.edge_counters.clone(),
 					_phantom: ::core::marker::PhantomData::default(),
 				}
 			}
@@ -254,11 +345,11 @@ pub(crate) fn impl_subsystem_sender(
 			{
 				async fn send_message(&mut self, msg: OutgoingMessage)
 				{
+					let msg = <#outgoing_wrapper as ::std::convert::From<_>> :: from ( msg );
+					self.edge_counters.count(&msg);
 					self.channels.send_and_log_error(
 						self.signals_received.load(),
-						<#all_messages_wrapper as ::std::convert::From<_>> ::from (
-							<#outgoing_wrapper as ::std::convert::From<_>> :: from ( msg )
-						)
+						<#all_messages_wrapper as ::std::convert::From<_>> ::from ( msg )
 					).await;
 				}
 
@@ -274,11 +365,11 @@ pub(crate) fn impl_subsystem_sender(
 
 				fn send_unbounded_message(&mut self, msg: OutgoingMessage)
 				{
+					let msg = <#outgoing_wrapper as ::std::convert::From<_>> :: from ( msg );
+					self.edge_counters.count(&msg);
 					self.channels.send_unbounded_and_log_error(
 						self.signals_received.load(),
-						<#all_messages_wrapper as ::std::convert::From<_>> ::from (
-							<#outgoing_wrapper as ::std::convert::From<_>> :: from ( msg )
-						)
+						<#all_messages_wrapper as ::std::convert::From<_>> ::from ( msg )
 					);
 				}
 			}
@@ -386,6 +477,12 @@ pub(crate) fn impl_subsystem_context_trait_for(
 			}
 
 			async fn recv(&mut self) -> ::std::result::Result<FromOrchestra<Self::Message, #signal>, #error_ty> {
+				// Signals to catch up after a restart precede everything else, they were not
+				// sent through the signal channel and are hence not counted.
+				if let Some(signal) = self.restart_signals.pop_front() {
+					return Ok( #support_crate ::FromOrchestra::Signal(signal))
+				}
+
 				loop {
 					// If we have a message pending an orchestra signal, we only poll for signals
 					// in the meantime.
@@ -605,14 +702,17 @@ pub(crate) fn impl_subsystem_context(
 		#[derive(Debug)]
 		#[allow(missing_docs)]
 		pub struct #subsystem_ctx_name<M: AssociateOutgoing + Send + 'static> {
-			signals: #support_crate ::metered::MeteredReceiver< #signal_ty >,
-			messages: SubsystemIncomingMessages< M >,
+			signals: #support_crate ::supervision::Reclaimable<
+				#support_crate ::metered::MeteredReceiver< #signal_ty >
+			>,
+			messages: #support_crate ::supervision::Reclaimable< SubsystemIncomingMessages< M > >,
 			to_subsystems: #subsystem_sender_name < <M as AssociateOutgoing>::OutgoingMessages >,
 			to_orchestra: #support_crate ::metered::UnboundedMeteredSender<
 				#support_crate ::ToOrchestra
 				>,
 			signals_received: SignalsReceived,
 			pending_incoming: Option<(usize, M)>,
+			restart_signals: ::std::collections::VecDeque< #signal_ty >,
 			name: &'static str
 		}
 
@@ -620,26 +720,28 @@ pub(crate) fn impl_subsystem_context(
 		where
 			M: AssociateOutgoing + Send + 'static,
 		{
-			/// Create a new context.
+			/// Create a new context on the channels lent by the supervisor of the subsystem.
 			fn new(
-				signals: #support_crate ::metered::MeteredReceiver< #signal_ty >,
-				messages: SubsystemIncomingMessages< M >,
+				incoming: #support_crate ::supervision::IncomingLease< #signal_ty, M >,
 				to_subsystems: ChannelsOut,
 				to_orchestra: #support_crate ::metered::UnboundedMeteredSender<#support_crate:: ToOrchestra>,
+				edge_counters: #support_crate ::graph::EdgeCounters,
 				name: &'static str
 			) -> Self {
-				let signals_received = SignalsReceived::default();
+				let (signals, messages, signals_received, restart_signals) = incoming.into_parts();
 				#subsystem_ctx_name :: <M> {
 					signals,
 					messages,
 					to_subsystems: #subsystem_sender_name :: < <M as AssociateOutgoing>::OutgoingMessages > {
 						channels: to_subsystems,
 						signals_received: signals_received.clone(),
+						edge_counters,
 						_phantom: ::core::marker::PhantomData::default(),
 					},
 					to_orchestra,
 					signals_received,
 					pending_incoming: None,
+					restart_signals,
 					name
 				}
 			}
@@ -32,6 +32,8 @@ mod kw {
 	syn::custom_keyword!(blocking);
 	syn::custom_keyword!(consumes);
 	syn::custom_keyword!(sends);
+	syn::custom_keyword!(priority);
+	syn::custom_keyword!(supervision);
 }
 
 #[derive(Clone, Debug)]
@@ -46,6 +48,10 @@ pub(crate) enum SubSysAttrItem {
 	Sends(Sends),
 	/// Message to be consumed by this subsystem.
 	Consumes(Consumes),
+	/// Variants of the consumed message to be queued on the high priority lane.
+	Priority(PriorityVariants),
+	/// What happens when the subsystem fails.
+	Supervision(SupervisionPolicy),
 }
 
 impl Parse for SubSysAttrItem {
@@ -57,6 +63,10 @@ impl Parse for SubSysAttrItem {
 			Self::Blocking(input.parse::<kw::blocking>()?)
 		} else if lookahead.peek(kw::sends) {
 			Self::Sends(input.parse::<Sends>()?)
+		} else if lookahead.peek(kw::priority) {
+			Self::Priority(input.parse::<PriorityVariants>()?)
+		} else if lookahead.peek(kw::supervision) {
+			Self::Supervision(input.parse::<SupervisionPolicy>()?)
 		} else {
 			Self::Consumes(input.parse::<Consumes>()?)
 		})
@@ -78,6 +88,12 @@ impl ToTokens for SubSysAttrItem {
 			Self::Consumes(_) => {
 				quote! {}
 			},
+			Self::Priority(_) => {
+				quote! {}
+			},
+			Self::Supervision(_) => {
+				quote! {}
+			},
 		};
 		tokens.extend(ts.into_iter());
 	}
@@ -97,6 +113,10 @@ pub(crate) struct SubSysField {
 	pub(crate) message_to_consume: Path,
 	/// Types of messages to be sent by the subsystem.
 	pub(crate) messages_to_send: Vec<Path>,
+	/// Variants of the consumed message which are queued on the high priority lane.
+	pub(crate) priority_variants: Vec<Ident>,
+	/// What happens when the subsystem fails.
+	pub(crate) supervision: Supervision,
 	/// If the subsystem implementation is blocking execution and hence
 	/// has to be spawned on a separate thread or thread pool.
 	pub(crate) blocking: bool,
@@ -169,6 +189,72 @@ impl Parse for Sends {
 	}
 }
 
+/// Parses `priority: [Foo, Bar]`, naming variants of the consumed message.
+#[derive(Debug, Clone)]
+pub(crate) struct PriorityVariants {
+	pub(crate) keyword_priority: kw::priority,
+	#[allow(dead_code)]
+	pub(crate) colon: Token![:],
+	#[allow(dead_code)]
+	pub(crate) bracket: Option<Bracket>,
+	pub(crate) variants: Punctuated<Ident, Token![,]>,
+}
+
+impl Parse for PriorityVariants {
+	fn parse(input: syn::parse::ParseStream) -> Result<Self> {
+		let content;
+		let keyword_priority = input.parse()?;
+		let colon = input.parse()?;
+		let (bracket, variants) = if !input.peek(syn::token::Bracket) {
+			let mut variants = Punctuated::new();
+			variants.push_value(input.parse::<Ident>()?);
+			(None, variants)
+		} else {
+			let bracket = Some(syn::bracketed!(content in input));
+			let variants = Punctuated::parse_terminated(&content)?;
+			(bracket, variants)
+		};
+		Ok(Self { keyword_priority, colon, bracket, variants })
+	}
+}
+
+/// What happens when a subsystem fails, mirrors `Supervision` of the support crate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Supervision {
+	#[default]
+	FailFast,
+	Restart,
+	Degrade,
+}
+
+/// Parses `supervision: Restart`, naming the policy applied when the subsystem fails.
+#[derive(Debug, Clone)]
+pub(crate) struct SupervisionPolicy {
+	#[allow(dead_code)]
+	pub(crate) keyword_supervision: kw::supervision,
+	#[allow(dead_code)]
+	pub(crate) colon: Token![:],
+	pub(crate) policy: Supervision,
+}
+
+impl Parse for SupervisionPolicy {
+	fn parse(input: syn::parse::ParseStream) -> Result<Self> {
+		let keyword_supervision = input.parse()?;
+		let colon = input.parse()?;
+		let ident = input.parse::<Ident>()?;
+		let policy = match ident.to_string().as_str() {
+			"FailFast" => Supervision::FailFast,
+			"Restart" => Supervision::Restart,
+			"Degrade" => Supervision::Degrade,
+			_ => return Err(Error::new(
+				ident.span(),
+				"Unknown supervision policy, expected one of `FailFast`, `Restart` or `Degrade`.",
+			)),
+		};
+		Ok(Self { keyword_supervision, colon, policy })
+	}
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct Consumes {
 	#[allow(dead_code)]
@@ -206,6 +292,10 @@ pub(crate) struct SubSystemAttrItems {
 	/// The message type being consumed by the subsystem.
 	pub(crate) consumes: Option<Consumes>,
 	pub(crate) sends: Option<Sends>,
+	/// Variants of the consumed message type to queue on the high priority lane.
+	pub(crate) priority: Option<PriorityVariants>,
+	/// What happens when the subsystem fails.
+	pub(crate) supervision: Supervision,
 }
 
 impl Parse for SubSystemAttrItems {
@@ -243,10 +333,21 @@ impl Parse for SubSystemAttrItems {
 			))
 		}
 
+		let priority = extract_variant!(unique, Priority take);
+		if let (Some(priority), None) = (&priority, &consumes) {
+			return Err(Error::new(
+				priority.keyword_priority.span,
+				"`priority: [..]` names variants of the consumed message, but none is consumed.",
+			))
+		}
+
+		let supervision = extract_variant!(unique, Supervision take)
+			.map(|supervision| supervision.policy)
+			.unwrap_or_default();
 		let blocking = extract_variant!(unique, Blocking; default = false);
 		let wip = extract_variant!(unique, Wip; default = false);
 
-		Ok(Self { blocking, wip, sends, consumes })
+		Ok(Self { blocking, wip, sends, consumes, priority, supervision })
 	}
 }
 
@@ -460,7 +561,8 @@ impl OrchestraGuts {
 				}
 				unique_subsystem_idents.insert(generic.clone());
 
-				let SubSystemAttrItems { wip, blocking, consumes, sends, .. } = subsystem_attrs;
+				let SubSystemAttrItems { wip, blocking, consumes, sends, priority, supervision } =
+					subsystem_attrs;
 
 				// messages to be sent
 				let sends = if let Some(sends) = sends {
@@ -475,11 +577,20 @@ impl OrchestraGuts {
 					return Err(Error::new(span, "Must provide exactly one consuming message type"))
 				};
 
+				// variants of the consumed message for the high priority lane
+				let priority_variants = if let Some(priority) = priority {
+					Vec::from_iter(priority.variants.iter().cloned())
+				} else {
+					vec![]
+				};
+
 				subsystems.push(SubSysField {
 					name: ident,
 					generic,
 					message_to_consume: consumes,
 					messages_to_send: sends,
+					priority_variants,
+					supervision,
 					wip,
 					blocking,
 				});
@@ -124,6 +124,16 @@ mod strukt {
 		});
 	}
 
+	#[test]
+	fn parse_subsystem_attr_item_works_08_priority() {
+		assert_matches!(
+		syn::parse2::<SubSysAttrItem>(quote! {
+			priority: [A, B]
+		}), Ok(SubSysAttrItem::Priority(priority)) => {
+			assert_eq!(priority.variants.len(), 2);
+		});
+	}
+
 	#[test]
 	fn parse_subsystem_attributes_works_00() {
 		syn::parse2::<SubSystemAttrItems>(quote! {
@@ -246,6 +256,52 @@ mod strukt {
 		});
 	}
 
+	#[test]
+	fn parse_subsystem_attributes_works_13_priority() {
+		assert_matches!(
+		syn::parse2::<SubSystemAttrItems>(quote! {
+			(Foo, sends: [Bar], priority: [Urgent])
+		}), Ok(SubSystemAttrItems { priority: Some(priority), .. }) => {
+			assert_eq!(priority.variants.len(), 1);
+		});
+	}
+
+	#[test]
+	fn parse_subsystem_attributes_works_14_priority_without_consumes() {
+		assert_matches!(
+		syn::parse2::<SubSystemAttrItems>(quote! {
+			(sends: [Bar], priority: [Urgent])
+		}), Err(e) => {
+			dbg!(e)
+		});
+	}
+
+	#[test]
+	fn parse_subsystem_attributes_works_15_supervision() {
+		assert_matches!(
+			syn::parse2::<SubSystemAttrItems>(quote! {
+				(Foo, sends: [Bar], supervision: Restart)
+			}),
+			Ok(SubSystemAttrItems { supervision: Supervision::Restart, .. })
+		);
+		assert_matches!(
+			syn::parse2::<SubSystemAttrItems>(quote! {
+				(Foo, sends: [Bar])
+			}),
+			Ok(SubSystemAttrItems { supervision: Supervision::FailFast, .. })
+		);
+	}
+
+	#[test]
+	fn parse_subsystem_attributes_works_16_unknown_supervision() {
+		assert_matches!(
+		syn::parse2::<SubSystemAttrItems>(quote! {
+			(Foo, sends: [Bar], supervision: Retry)
+		}), Err(e) => {
+			dbg!(e)
+		});
+	}
+
 	#[test]
 	fn struct_parse_baggage() {
 		let item: OrchestraGuts = parse_quote! {
@@ -0,0 +1,408 @@
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The connection graph of an orchestra's subsystems, available at runtime.
+//!
+//! The `#[orchestra]` macro derives which subsystem sends which messages to which other
+//! subsystem at compile time. The generated orchestra exposes this as a
+//! [`ConnectionGraphTracker`], which counts the messages sent along each connection.
+//! A [`ConnectionGraph`] is a snapshot of it, which can be annotated with the meter readouts
+//! of the subsystems and rendered as graphviz DOT or JSON.
+
+use std::{
+	fmt::Write as _,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc,
+	},
+};
+
+use crate::SubsystemMeterReadouts;
+
+/// Description of the connections between subsystems, generated by `#[orchestra]`.
+///
+/// Subsystems are referred to by their index in `subsystems`.
+#[derive(Debug)]
+pub struct ConnectionGraphDescription {
+	/// The names of all subsystems.
+	pub subsystems: &'static [&'static str],
+	/// The connections as `(sender, receiver, message)`.
+	pub edges: &'static [(usize, usize, &'static str)],
+	/// Groups of subsystems which are connected in at least one cycle.
+	pub cycles: &'static [&'static [usize]],
+	/// Messages consumed by a subsystem, but not sent by any, as `(message, receiver)`.
+	pub unsent_messages: &'static [(&'static str, usize)],
+	/// Messages sent by a subsystem, but not consumed by any, as `(message, sender)`.
+	pub unconsumed_messages: &'static [(&'static str, usize)],
+}
+
+/// A message type which is sent along a single connection of the graph.
+pub trait CountedEdge {
+	/// The index of the connection in [`ConnectionGraphDescription::edges`], if any.
+	fn edge_index(&self) -> Option<usize>;
+}
+
+impl CountedEdge for () {
+	fn edge_index(&self) -> Option<usize> {
+		None
+	}
+}
+
+/// Counts of the messages sent along each connection.
+#[derive(Debug, Clone)]
+pub struct EdgeCounters(Arc<[AtomicU64]>);
+
+impl EdgeCounters {
+	/// Create counters for the connections of `description`.
+	pub fn new(description: &ConnectionGraphDescription) -> Self {
+		EdgeCounters(description.edges.iter().map(|_| AtomicU64::new(0)).collect())
+	}
+
+	/// Count a message about to be sent.
+	pub fn count<M: CountedEdge>(&self, message: &M) {
+		if let Some(counter) = message.edge_index().and_then(|idx| self.0.get(idx)) {
+			counter.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+
+	fn get(&self, idx: usize) -> u64 {
+		self.0.get(idx).map_or(0, |counter| counter.load(Ordering::Relaxed))
+	}
+}
+
+/// Tracks the messages sent between the subsystems of an orchestra.
+#[derive(Debug, Clone)]
+pub struct ConnectionGraphTracker {
+	description: &'static ConnectionGraphDescription,
+	counters: EdgeCounters,
+	instance_names: Arc<[Option<&'static str>]>,
+}
+
+impl ConnectionGraphTracker {
+	/// Create a tracker for `description`, based on the counters the subsystems' senders use.
+	///
+	/// `instance_names` are the names of the running subsystem instances, indexed like
+	/// [`ConnectionGraphDescription::subsystems`].
+	pub fn new(
+		description: &'static ConnectionGraphDescription,
+		counters: EdgeCounters,
+		instance_names: Vec<Option<&'static str>>,
+	) -> Self {
+		ConnectionGraphTracker { description, counters, instance_names: instance_names.into() }
+	}
+
+	/// Take a snapshot of the graph, with the number of messages sent so far.
+	pub fn snapshot(&self) -> ConnectionGraph {
+		let description = self.description;
+		let name = |idx: usize| description.subsystems[idx];
+
+		ConnectionGraph {
+			subsystems: description
+				.subsystems
+				.iter()
+				.enumerate()
+				.map(|(idx, name)| SubsystemNode {
+					name,
+					instance_name: self.instance_names.get(idx).copied().flatten(),
+					pending_messages: None,
+					received_messages: None,
+				})
+				.collect(),
+			edges: description
+				.edges
+				.iter()
+				.enumerate()
+				.map(|(idx, &(sender, receiver, message))| MessageEdge {
+					sender: name(sender),
+					receiver: name(receiver),
+					message,
+					sent: self.counters.get(idx),
+				})
+				.collect(),
+			cycles: description
+				.cycles
+				.iter()
+				.map(|cycle| cycle.iter().copied().map(name).collect())
+				.collect(),
+			unsent_messages: description
+				.unsent_messages
+				.iter()
+				.map(|&(message, receiver)| (message, name(receiver)))
+				.collect(),
+			unconsumed_messages: description
+				.unconsumed_messages
+				.iter()
+				.map(|&(message, sender)| (message, name(sender)))
+				.collect(),
+		}
+	}
+}
+
+/// A subsystem in a [`ConnectionGraph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubsystemNode {
+	/// The name of the subsystem in the orchestra.
+	pub name: &'static str,
+	/// The name of the running subsystem instance, if any.
+	pub instance_name: Option<&'static str>,
+	/// Messages sent to the subsystem, but not yet received, if annotated.
+	pub pending_messages: Option<usize>,
+	/// Messages received by the subsystem, if annotated.
+	pub received_messages: Option<usize>,
+}
+
+/// A connection between two subsystems in a [`ConnectionGraph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageEdge {
+	/// The sending subsystem.
+	pub sender: &'static str,
+	/// The receiving subsystem.
+	pub receiver: &'static str,
+	/// The type of message sent.
+	pub message: &'static str,
+	/// The number of messages sent so far.
+	pub sent: u64,
+}
+
+/// A snapshot of the connections between the subsystems of an orchestra.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionGraph {
+	/// All subsystems.
+	pub subsystems: Vec<SubsystemNode>,
+	/// All connections between subsystems.
+	pub edges: Vec<MessageEdge>,
+	/// Groups of subsystems which are connected in at least one cycle.
+	pub cycles: Vec<Vec<&'static str>>,
+	/// Messages consumed by a subsystem, but not sent by any, as `(message, receiver)`.
+	pub unsent_messages: Vec<(&'static str, &'static str)>,
+	/// Messages sent by a subsystem, but not consumed by any, as `(message, sender)`.
+	pub unconsumed_messages: Vec<(&'static str, &'static str)>,
+}
+
+impl ConnectionGraph {
+	/// Annotate the subsystems with the readouts of their meters, keyed by instance name.
+	pub fn with_meter_readouts(
+		mut self,
+		readouts: &[(&'static str, SubsystemMeterReadouts)],
+	) -> Self {
+		for node in &mut self.subsystems {
+			let readouts = readouts
+				.iter()
+				.find(|(name, _)| node.instance_name == Some(*name))
+				.map(|(_, readouts)| readouts);
+			if let Some(readouts) = readouts {
+				let received = readouts.bounded.received +
+					readouts.unbounded.received +
+					readouts.priority.received;
+				let sent = readouts.bounded.sent + readouts.unbounded.sent + readouts.priority.sent;
+				// Received may be slightly ahead of sent, see `metered::Readout`.
+				let pending = sent.saturating_sub(received);
+				node.received_messages = Some(received);
+				node.pending_messages = Some(pending);
+			}
+		}
+		self
+	}
+
+	/// Render the graph in the graphviz DOT language.
+	///
+	/// Edges are labeled with the number of messages sent, subsystems which are part of
+	/// a cycle are highlighted.
+	pub fn to_dot(&self) -> String {
+		let mut dot = String::from("digraph {\n\tnode [colorscheme=rdylgn10]\n");
+
+		for node in &self.subsystems {
+			let mut label = node.name.to_owned();
+			if let (Some(pending), Some(received)) = (node.pending_messages, node.received_messages)
+			{
+				let _ = write!(label, "\\npending={} received={}", pending, received);
+			}
+			let cycle = self.cycles.iter().position(|cycle| cycle.contains(&node.name));
+			match cycle {
+				// The color scheme is 1-indexed and limited to 10 colors.
+				Some(idx) => {
+					let _ = writeln!(
+						dot,
+						"\t\"{}\" [label=\"{}\",color=\"/rdylgn10/{}\"]",
+						node.name,
+						label,
+						idx % 10 + 1
+					);
+				},
+				None => {
+					let _ = writeln!(dot, "\t\"{}\" [label=\"{}\"]", node.name, label);
+				},
+			}
+		}
+
+		for edge in &self.edges {
+			let _ = writeln!(
+				dot,
+				"\t\"{}\" -> \"{}\" [label=\"{} ({})\"]",
+				edge.sender, edge.receiver, edge.message, edge.sent
+			);
+		}
+
+		if !self.unsent_messages.is_empty() {
+			dot.push_str("\tNEVER_SENT_ANYWHERE [label=\"✨\",shape=doublecircle]\n");
+		}
+		for (message, receiver) in &self.unsent_messages {
+			let _ = writeln!(
+				dot,
+				"\tNEVER_SENT_ANYWHERE -> \"{}\" [label=\"{}\",style=dashed]",
+				receiver, message
+			);
+		}
+
+		if !self.unconsumed_messages.is_empty() {
+			dot.push_str("\tSENT_TO_NONONE [label=\"💀\",shape=doublecircle]\n");
+		}
+		for (message, sender) in &self.unconsumed_messages {
+			let _ = writeln!(
+				dot,
+				"\t\"{}\" -> SENT_TO_NONONE [label=\"{}\",style=dashed]",
+				sender, message
+			);
+		}
+
+		dot.push_str("}\n");
+		dot
+	}
+
+	/// Render the graph as JSON.
+	pub fn to_json(&self) -> String {
+		fn optional(value: Option<impl ToString>) -> String {
+			value.map_or_else(|| "null".to_owned(), |value| value.to_string())
+		}
+
+		fn string(s: &str) -> String {
+			let mut escaped = String::with_capacity(s.len() + 2);
+			escaped.push('"');
+			for c in s.chars() {
+				match c {
+					'"' => escaped.push_str("\\\""),
+					'\\' => escaped.push_str("\\\\"),
+					c if c.is_control() => {
+						let _ = write!(escaped, "\\u{:04x}", c as u32);
+					},
+					c => escaped.push(c),
+				}
+			}
+			escaped.push('"');
+			escaped
+		}
+
+		fn list<T>(items: &[T], f: impl Fn(&T) -> String) -> String {
+			let items = items.iter().map(f).collect::<Vec<_>>();
+			format!("[{}]", items.join(","))
+		}
+
+		let subsystems = list(&self.subsystems, |node| {
+			format!(
+				r#"{{"name":{},"instance_name":{},"pending_messages":{},"received_messages":{}}}"#,
+				string(node.name),
+				optional(node.instance_name.map(string)),
+				optional(node.pending_messages),
+				optional(node.received_messages),
+			)
+		});
+		let edges = list(&self.edges, |edge| {
+			format!(
+				r#"{{"sender":{},"receiver":{},"message":{},"sent":{}}}"#,
+				string(edge.sender),
+				string(edge.receiver),
+				string(edge.message),
+				edge.sent,
+			)
+		});
+		let cycles = list(&self.cycles, |cycle| list(cycle, |name| string(name)));
+		let unsent_messages = list(&self.unsent_messages, |(message, receiver)| {
+			format!(r#"{{"message":{},"receiver":{}}}"#, string(message), string(receiver))
+		});
+		let unconsumed_messages = list(&self.unconsumed_messages, |(message, sender)| {
+			format!(r#"{{"message":{},"sender":{}}}"#, string(message), string(sender))
+		});
+
+		format!(
+			r#"{{"subsystems":{},"edges":{},"cycles":{},"unsent_messages":{},"unconsumed_messages":{}}}"#,
+			subsystems, edges, cycles, unsent_messages, unconsumed_messages,
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use metered::Readout;
+
+	static DESCRIPTION: ConnectionGraphDescription = ConnectionGraphDescription {
+		subsystems: &["Alpha", "Beta"],
+		edges: &[(0, 1, "BetaMessage"), (1, 0, "AlphaMessage")],
+		cycles: &[&[0, 1]],
+		unsent_messages: &[],
+		unconsumed_messages: &[("GammaMessage", 1)],
+	};
+
+	struct ToBeta;
+
+	impl CountedEdge for ToBeta {
+		fn edge_index(&self) -> Option<usize> {
+			Some(0)
+		}
+	}
+
+	#[test]
+	fn snapshot_counts_messages_per_edge() {
+		let counters = EdgeCounters::new(&DESCRIPTION);
+		let tracker =
+			ConnectionGraphTracker::new(&DESCRIPTION, counters.clone(), vec![Some("alpha"), None]);
+
+		counters.count(&ToBeta);
+		counters.count(&ToBeta);
+		counters.count(&());
+
+		let readouts = SubsystemMeterReadouts {
+			bounded: Readout { sent: 5, received: 3, ..Default::default() },
+			unbounded: Readout { sent: 1, received: 1, ..Default::default() },
+			signals: Readout::default(),
+			priority: Readout { sent: 2, received: 1, ..Default::default() },
+			supervision: Default::default(),
+			pending_kinds: Vec::new(),
+		};
+		let graph = tracker.snapshot().with_meter_readouts(&[("alpha", readouts)]);
+
+		assert_eq!(graph.edges[0].sent, 2);
+		assert_eq!(graph.edges[1].sent, 0);
+		assert_eq!(graph.cycles, vec![vec!["Alpha", "Beta"]]);
+		assert_eq!(graph.unconsumed_messages, vec![("GammaMessage", "Beta")]);
+		assert_eq!(graph.subsystems[0].pending_messages, Some(3));
+		assert_eq!(graph.subsystems[0].received_messages, Some(5));
+		assert_eq!(graph.subsystems[1].pending_messages, None);
+
+		assert!(graph.to_dot().contains("\"Alpha\" -> \"Beta\" [label=\"BetaMessage (2)\"]"));
+		assert_eq!(
+			graph.to_json(),
+			concat!(
+				r#"{"subsystems":["#,
+				r#"{"name":"Alpha","instance_name":"alpha","pending_messages":3,"received_messages":5},"#,
+				r#"{"name":"Beta","instance_name":null,"pending_messages":null,"received_messages":null}],"#,
+				r#""edges":[{"sender":"Alpha","receiver":"Beta","message":"BetaMessage","sent":2},"#,
+				r#"{"sender":"Beta","receiver":"Alpha","message":"AlphaMessage","sent":0}],"#,
+				r#""cycles":[["Alpha","Beta"]],"unsent_messages":[],"#,
+				r#""unconsumed_messages":[{"message":"GammaMessage","sender":"Beta"}]}"#,
+			),
+		);
+	}
+}
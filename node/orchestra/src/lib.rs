@@ -83,18 +83,25 @@ pub use futures::{
 #[doc(hidden)]
 pub use std::pin::Pin;
 
-use std::sync::{
-	atomic::{self, AtomicUsize},
-	Arc,
-};
 #[doc(hidden)]
 pub use std::time::Duration;
+use std::{
+	collections::HashMap,
+	sync::{
+		atomic::{self, AtomicUsize},
+		Arc, Mutex,
+	},
+};
 
 #[doc(hidden)]
 pub use futures_timer::Delay;
 
 use std::fmt;
 
+pub mod graph;
+pub mod record;
+pub mod supervision;
+
 #[cfg(test)]
 mod tests;
 
@@ -208,14 +215,187 @@ pub fn select_message_channel_strategy(_: &mut ()) -> PollNext {
 	PollNext::Right
 }
 
+/// The lane a message to a subsystem is queued on.
+///
+/// Variants of a consumed message listed in `#[subsystem(.., priority: [..])]` are
+/// queued on the high priority lane when sent with `send_message`, everything else on the
+/// regular channels. Unbounded sends always use the unbounded channel. Messages on different
+/// lanes are not ordered relative to each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+	/// The regular bounded channel.
+	Normal,
+	/// The high priority lane, received ahead of the regular channels.
+	High,
+}
+
+/// How many messages in a row are received from the high priority lane, before a message
+/// waiting on the regular channels gets its turn.
+pub const MAX_CONSECUTIVE_PRIORITY_MESSAGES: usize = 16;
+
 /// Incoming messages from both the bounded and unbounded channel.
-pub type SubsystemIncomingMessages<M> = self::stream::SelectWithStrategy<
+type RegularIncomingMessages<M> = self::stream::SelectWithStrategy<
 	self::metered::MeteredReceiver<MessagePacket<M>>,
 	self::metered::UnboundedMeteredReceiver<MessagePacket<M>>,
 	fn(&mut ()) -> self::stream::PollNext,
 	(),
 >;
 
+/// Incoming messages from the high priority lane and both the bounded and unbounded channel.
+///
+/// The high priority lane is polled first, then the unbounded and then the bounded channel.
+/// To not starve the regular channels, a waiting regular message is received after
+/// [`MAX_CONSECUTIVE_PRIORITY_MESSAGES`] high priority ones.
+#[derive(Debug)]
+pub struct SubsystemIncomingMessages<M> {
+	priority: self::stream::Fuse<self::metered::MeteredReceiver<MessagePacket<M>>>,
+	regular: RegularIncomingMessages<M>,
+	consecutive_priority: usize,
+	pending_kinds: PendingMessageKinds,
+	note_received: fn(&PendingMessageKinds, &M),
+}
+
+impl<M> SubsystemIncomingMessages<M> {
+	/// Combine the receiving ends of the channels to a subsystem.
+	pub fn new(
+		priority: self::metered::MeteredReceiver<MessagePacket<M>>,
+		bounded: self::metered::MeteredReceiver<MessagePacket<M>>,
+		unbounded: self::metered::UnboundedMeteredReceiver<MessagePacket<M>>,
+	) -> Self {
+		SubsystemIncomingMessages {
+			priority: priority.fuse(),
+			// Prefer unbounded channel when selecting
+			regular: select_with_strategy(
+				bounded,
+				unbounded,
+				select_message_channel_strategy as fn(&mut ()) -> PollNext,
+			),
+			consecutive_priority: 0,
+			pending_kinds: PendingMessageKinds::default(),
+			note_received: |_, _| {},
+		}
+	}
+
+	/// Count the received messages by kind in `pending_kinds`.
+	pub fn with_pending_kinds(mut self, pending_kinds: PendingMessageKinds) -> Self
+	where
+		M: fmt::Debug,
+	{
+		self.pending_kinds = pending_kinds;
+		self.note_received = |pending_kinds, message| pending_kinds.note_received(message);
+		self
+	}
+
+	fn poll_next_packet(&mut self, cx: &mut Context<'_>) -> Poll<Option<MessagePacket<M>>> {
+		let this = self;
+
+		if this.consecutive_priority >= MAX_CONSECUTIVE_PRIORITY_MESSAGES {
+			if let Poll::Ready(Some(packet)) = this.regular.poll_next_unpin(cx) {
+				this.consecutive_priority = 0;
+				return Poll::Ready(Some(packet))
+			}
+		}
+
+		if let Poll::Ready(Some(packet)) = this.priority.poll_next_unpin(cx) {
+			this.consecutive_priority += 1;
+			return Poll::Ready(Some(packet))
+		}
+		this.consecutive_priority = 0;
+
+		match this.regular.poll_next_unpin(cx) {
+			// Only terminated once the high priority lane is too.
+			Poll::Ready(None) if !this.priority.is_done() => Poll::Pending,
+			poll => poll,
+		}
+	}
+}
+
+impl<M> futures::Stream for SubsystemIncomingMessages<M> {
+	type Item = MessagePacket<M>;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = &mut *self;
+		let poll = this.poll_next_packet(cx);
+		if let Poll::Ready(Some(ref packet)) = poll {
+			(this.note_received)(&this.pending_kinds, &packet.message);
+		}
+		poll
+	}
+}
+
+/// Counts the messages waiting for a subsystem by their kind.
+///
+/// The kind of a message is the name of its variant, i.e. the start of its `Debug` output.
+/// Naming every message sent and received isn't free, so nothing is counted unless enabled.
+#[derive(Debug, Clone, Default)]
+pub struct PendingMessageKinds(Option<Arc<Mutex<HashMap<String, usize>>>>);
+
+impl PendingMessageKinds {
+	/// Create a new counter, which only counts if `enabled`.
+	pub fn new(enabled: bool) -> Self {
+		PendingMessageKinds(enabled.then(Default::default))
+	}
+
+	/// Count a message sent to the subsystem.
+	pub fn note_sent<M: fmt::Debug>(&self, message: &M) {
+		if let Some(ref counts) = self.0 {
+			let kind = message_kind(message);
+			let mut counts = counts.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+			*counts.entry(kind).or_default() += 1;
+		}
+	}
+
+	/// Count a message received by the subsystem.
+	pub fn note_received<M: fmt::Debug>(&self, message: &M) {
+		if let Some(ref counts) = self.0 {
+			let kind = message_kind(message);
+			let mut counts = counts.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+			// Messages sent before counting was enabled were never counted.
+			if let Some(count) = counts.get_mut(&kind) {
+				*count = count.saturating_sub(1);
+				if *count == 0 {
+					counts.remove(&kind);
+				}
+			}
+		}
+	}
+
+	/// The kinds of messages waiting, with their number, most frequent first.
+	pub fn read(&self) -> Vec<(String, usize)> {
+		let counts = match self.0 {
+			Some(ref counts) => counts.lock().unwrap_or_else(|poisoned| poisoned.into_inner()),
+			None => return Vec::new(),
+		};
+		let mut kinds =
+			counts.iter().map(|(kind, count)| (kind.clone(), *count)).collect::<Vec<_>>();
+		kinds.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+		kinds
+	}
+}
+
+/// The name of the variant of `message`, taken from its `Debug` output.
+fn message_kind<M: fmt::Debug>(message: &M) -> String {
+	/// Keeps the leading identifier written to it and aborts formatting after it.
+	struct LeadingIdent(String);
+
+	impl fmt::Write for LeadingIdent {
+		fn write_str(&mut self, s: &str) -> fmt::Result {
+			let end = s.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(s.len());
+			self.0.push_str(&s[..end]);
+			if end < s.len() || !self.0.is_empty() {
+				Err(fmt::Error)
+			} else {
+				Ok(())
+			}
+		}
+	}
+
+	let mut kind = LeadingIdent(String::new());
+	// Aborting the formatting is the point, so the error is expected.
+	let _ = fmt::write(&mut kind, format_args!("{:?}", message));
+	kind.0
+}
+
 /// Watermark to track the received signals.
 #[derive(Debug, Default, Clone)]
 pub struct SignalsReceived(Arc<AtomicUsize>);
@@ -312,6 +492,12 @@ pub struct SubsystemMeters {
 	pub unbounded: metered::Meter,
 	#[allow(missing_docs)]
 	pub signals: metered::Meter,
+	/// Meter of the high priority lane.
+	pub priority: metered::Meter,
+	/// Restarts and degradation of the subsystem.
+	pub supervision: supervision::SupervisionMeter,
+	/// The kinds of messages waiting for the subsystem, if counted.
+	pub pending_kinds: PendingMessageKinds,
 }
 
 impl SubsystemMeters {
@@ -321,6 +507,9 @@ impl SubsystemMeters {
 			bounded: self.bounded.read(),
 			unbounded: self.unbounded.read(),
 			signals: self.signals.read(),
+			priority: self.priority.read(),
+			supervision: self.supervision.read(),
+			pending_kinds: self.pending_kinds.read(),
 		}
 	}
 }
@@ -333,6 +522,12 @@ pub struct SubsystemMeterReadouts {
 	pub unbounded: metered::Readout,
 	#[allow(missing_docs)]
 	pub signals: metered::Readout,
+	/// Readout of the high priority lane.
+	pub priority: metered::Readout,
+	/// Readout of the supervision of the subsystem.
+	pub supervision: supervision::SupervisionReadout,
+	/// The kinds of messages waiting for the subsystem, most frequent first, if counted.
+	pub pending_kinds: Vec<(String, usize)>,
 }
 
 /// A running instance of some [`Subsystem`].
@@ -345,6 +540,8 @@ pub struct SubsystemInstance<Message, Signal> {
 	pub tx_signal: crate::metered::MeteredSender<Signal>,
 	/// Send sink for `Message`s to be sent to a subsystem.
 	pub tx_bounded: crate::metered::MeteredSender<MessagePacket<Message>>,
+	/// Send sink for `Message`s to be sent to a subsystem on the high priority lane.
+	pub tx_priority: crate::metered::MeteredSender<MessagePacket<Message>>,
 	/// All meters of the particular subsystem instance.
 	pub meters: SubsystemMeters,
 	/// The number of signals already received.
@@ -0,0 +1,874 @@
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recording of the signals and messages delivered to a single subsystem.
+//!
+//! Wrap a subsystem in a [`RecordingSubsystem`] before handing it to the orchestra builder,
+//! e.g. with `replace_<subsystem>`, and every [`FromOrchestra`] it receives is appended
+//! to the given writer, in order and with the time elapsed since the recording started.
+//! The responses to the requests the subsystem sends are recorded as well, so that a replay
+//! can answer the requests without the rest of the node.
+//!
+//! Each entry is laid out as
+//!
+//! ```text
+//! | elapsed micros: u64 LE | kind: u8 | payload length: u32 LE | payload |
+//! ```
+//!
+//! where the kind is `0` for signals, `1` for messages and `2` for responses. The payload of
+//! signals and messages is produced by a [`RecordCodec`], the one of responses is laid out as
+//!
+//! ```text
+//! | request index: u64 LE | response present: u8 | response |
+//! ```
+//!
+//! where requests are numbered in the order they were sent, and the response is produced by
+//! a [`ResponseCodec`]. Use [`read_recording`] to read the entries back.
+//!
+//! Entries are only encoded on the subsystem's task. Writing them is left to a dedicated
+//! thread, which buffers them and flushes whenever it caught up with the subsystem.
+
+use std::{
+	io::{self, BufWriter, Read, Write},
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		mpsc, Arc, Mutex,
+	},
+	thread,
+	time::{Duration, Instant},
+};
+
+use futures::{
+	channel::{mpsc as async_mpsc, oneshot},
+	future::{self, BoxFuture, Either},
+	stream::FuturesUnordered,
+	FutureExt as _, StreamExt as _,
+};
+
+use crate::{
+	FromOrchestra, OrchestraError, SpawnedSubsystem, Subsystem, SubsystemContext, SubsystemSender,
+};
+
+/// The largest payload of a single entry.
+///
+/// Larger entries are left out of recordings and rejected when reading one.
+pub const MAX_PAYLOAD_LEN: usize = 16 * 1024 * 1024;
+
+/// The number of entries waiting to be written, before the recording is stopped.
+const MAX_PENDING_ENTRIES: usize = 4096;
+
+/// Encoding and decoding of the signals and messages of a subsystem for recordings.
+///
+/// Signals or messages which can't be encoded, e.g. because they carry a response
+/// channel, return `None` and are left out of the recording.
+pub trait RecordCodec<Message, Signal>: Send + 'static {
+	/// Encode a signal.
+	fn encode_signal(&self, signal: &Signal) -> Option<Vec<u8>>;
+	/// Encode a message.
+	fn encode_message(&self, message: &Message) -> Option<Vec<u8>>;
+	/// Decode a signal encoded with `encode_signal`.
+	fn decode_signal(&self, payload: &[u8]) -> Option<Signal>;
+	/// Decode a message encoded with `encode_message`.
+	fn decode_message(&self, payload: &[u8]) -> Option<Message>;
+}
+
+/// Interception of the response channels of the requests a subsystem sends, to record the
+/// responses and to answer the requests when replaying a recording.
+///
+/// Messages which aren't requests, or whose responses can't be recorded, return `None`.
+pub trait ResponseCodec<OutgoingMessage>: Send + 'static {
+	/// Take over the response channel of `message`.
+	fn intercept_response(&self, message: &mut OutgoingMessage) -> Option<ResponseChannel>;
+}
+
+/// The response channel of a request, taken over by a [`ResponseCodec`].
+pub struct ResponseChannel(Box<dyn InterceptedResponse>);
+
+impl ResponseChannel {
+	/// Take over `tx`, leaving a fresh sender in its place.
+	///
+	/// Responses are encoded with `encode` for the recording, which returns `None` for
+	/// responses that are recorded as missing, and decoded with `decode` when replaying.
+	pub fn intercept<T: Send + 'static>(
+		tx: &mut oneshot::Sender<T>,
+		encode: fn(&T) -> Option<Vec<u8>>,
+		decode: fn(&[u8]) -> Option<T>,
+	) -> Self {
+		let (replacement, response) = oneshot::channel();
+		let original = std::mem::replace(tx, replacement);
+		ResponseChannel(Box::new(Intercepted { original, response, encode, decode }))
+	}
+
+	/// Wait for the response, forward it to the requester and return its encoding.
+	///
+	/// Resolves to `None` if the response is missing, i.e. the request was dropped or its
+	/// response can't be encoded.
+	pub fn record(self) -> BoxFuture<'static, Option<Vec<u8>>> {
+		self.0.record()
+	}
+
+	/// Answer the requester with a recorded response, dropping the request if it is missing.
+	///
+	/// Returns `false` if the response can't be decoded.
+	pub fn replay(self, response: Option<&[u8]>) -> bool {
+		self.0.replay(response)
+	}
+}
+
+trait InterceptedResponse: Send {
+	fn record(self: Box<Self>) -> BoxFuture<'static, Option<Vec<u8>>>;
+	fn replay(self: Box<Self>, response: Option<&[u8]>) -> bool;
+}
+
+struct Intercepted<T> {
+	original: oneshot::Sender<T>,
+	response: oneshot::Receiver<T>,
+	encode: fn(&T) -> Option<Vec<u8>>,
+	decode: fn(&[u8]) -> Option<T>,
+}
+
+impl<T: Send + 'static> InterceptedResponse for Intercepted<T> {
+	fn record(self: Box<Self>) -> BoxFuture<'static, Option<Vec<u8>>> {
+		let Intercepted { original, response, encode, .. } = *self;
+		async move {
+			let response = response.await.ok()?;
+			let encoded = encode(&response);
+			let _ = original.send(response);
+			encoded
+		}
+		.boxed()
+	}
+
+	fn replay(self: Box<Self>, response: Option<&[u8]>) -> bool {
+		let response = match response {
+			Some(response) => response,
+			// Dropping the original sender cancels the request, just like it was recorded.
+			None => return true,
+		};
+		match (self.decode)(response) {
+			Some(response) => {
+				let _ = self.original.send(response);
+				true
+			},
+			None => false,
+		}
+	}
+}
+
+/// The kind of a recorded entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordKind {
+	/// An orchestra signal.
+	Signal = 0,
+	/// A message from another subsystem.
+	Message = 1,
+	/// The response to a request sent by the subsystem.
+	Response = 2,
+}
+
+/// A single entry of a recording.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedEntry {
+	/// Time elapsed since the recording started.
+	pub elapsed: Duration,
+	/// Whether the entry is a signal, a message or a response.
+	pub kind: RecordKind,
+	/// The encoded signal, message or response.
+	pub payload: Vec<u8>,
+}
+
+impl RecordedEntry {
+	/// Decode a signal or message entry with the codec it was recorded with.
+	pub fn decode<M, S, C: RecordCodec<M, S>>(&self, codec: &C) -> Option<FromOrchestra<M, S>> {
+		match self.kind {
+			RecordKind::Signal => codec.decode_signal(&self.payload).map(FromOrchestra::Signal),
+			RecordKind::Message => codec
+				.decode_message(&self.payload)
+				.map(|msg| FromOrchestra::Communication { msg }),
+			RecordKind::Response => None,
+		}
+	}
+
+	/// The index of the request and its encoded response, or `None` if the response is
+	/// missing, of a response entry.
+	pub fn response(&self) -> Option<(u64, Option<&[u8]>)> {
+		if self.kind != RecordKind::Response {
+			return None
+		}
+		let request = u64::from_le_bytes(self.payload.get(..8)?.try_into().ok()?);
+		match self.payload.get(8)? {
+			0 if self.payload.len() == 9 => Some((request, None)),
+			1 => Some((request, Some(&self.payload[9..]))),
+			_ => None,
+		}
+	}
+}
+
+/// Appends the signals and messages delivered to a subsystem, and the responses to its
+/// requests, to a writer.
+///
+/// The writer is owned by a dedicated thread, so a slow writer never blocks the subsystem.
+/// If the thread falls too far behind, recording fails instead.
+pub struct Recorder<W, C> {
+	entries: Option<mpsc::SyncSender<Vec<u8>>>,
+	writer: Option<thread::JoinHandle<io::Result<W>>>,
+	codec: C,
+	started: Instant,
+}
+
+impl<W: Write + Send + 'static, C> Recorder<W, C> {
+	/// Start a new recording into `writer`.
+	pub fn new(writer: W, codec: C) -> io::Result<Self> {
+		let (entries, pending) = mpsc::sync_channel(MAX_PENDING_ENTRIES);
+		let writer = thread::Builder::new()
+			.name("orchestra-recorder".into())
+			.spawn(move || write_entries(writer, pending))?;
+
+		Ok(Recorder {
+			entries: Some(entries),
+			writer: Some(writer),
+			codec,
+			started: Instant::now(),
+		})
+	}
+
+	/// Stop recording, returning the writer once all entries have been written to it.
+	pub fn into_inner(mut self) -> io::Result<W> {
+		self.finish()
+	}
+
+	fn finish(&mut self) -> io::Result<W> {
+		// Disconnecting lets the thread write the remaining entries and exit.
+		self.entries = None;
+		match self.writer.take() {
+			Some(writer) => writer.join().map_err(|_| {
+				io::Error::new(io::ErrorKind::Other, "The recording writer thread panicked")
+			})?,
+			None => Err(io::Error::new(io::ErrorKind::BrokenPipe, "The recording was stopped")),
+		}
+	}
+
+	/// Record a signal or message delivered to the subsystem.
+	pub fn record<M, S>(&mut self, from_orchestra: &FromOrchestra<M, S>) -> io::Result<()>
+	where
+		C: RecordCodec<M, S>,
+	{
+		let (kind, payload) = match from_orchestra {
+			FromOrchestra::Signal(signal) => (RecordKind::Signal, self.codec.encode_signal(signal)),
+			FromOrchestra::Communication { msg } =>
+				(RecordKind::Message, self.codec.encode_message(msg)),
+		};
+
+		let payload = match payload {
+			Some(payload) => payload,
+			None => {
+				tracing::trace!(?kind, "Skipping entry which can't be encoded for the recording");
+				return Ok(())
+			},
+		};
+
+		self.record_entry(kind, &payload)
+	}
+
+	/// Record the response to the request with the given index, `None` if it is missing.
+	pub fn record_response(&mut self, request: u64, response: Option<&[u8]>) -> io::Result<()> {
+		// A skipped response would leave the request unanswered in a replay.
+		let response = response.filter(|response| {
+			let fits = 8 + 1 + response.len() <= MAX_PAYLOAD_LEN;
+			if !fits {
+				tracing::warn!(
+					request,
+					len = response.len(),
+					"Recording response which is too large as missing",
+				);
+			}
+			fits
+		});
+
+		let mut payload = Vec::with_capacity(8 + 1 + response.map_or(0, |r| r.len()));
+		payload.extend_from_slice(&request.to_le_bytes());
+		match response {
+			Some(response) => {
+				payload.push(1);
+				payload.extend_from_slice(response);
+			},
+			None => payload.push(0),
+		}
+
+		self.record_entry(RecordKind::Response, &payload)
+	}
+
+	fn record_entry(&mut self, kind: RecordKind, payload: &[u8]) -> io::Result<()> {
+		if payload.len() > MAX_PAYLOAD_LEN {
+			tracing::warn!(
+				?kind,
+				len = payload.len(),
+				"Skipping entry which is too large for the recording",
+			);
+			return Ok(())
+		}
+
+		let entries = match self.entries.as_ref() {
+			Some(entries) => entries,
+			None =>
+				return Err(io::Error::new(io::ErrorKind::BrokenPipe, "The recording was stopped")),
+		};
+
+		let elapsed = self.started.elapsed().as_micros() as u64;
+		let mut entry = Vec::with_capacity(8 + 1 + 4 + payload.len());
+		entry.extend_from_slice(&elapsed.to_le_bytes());
+		entry.push(kind as u8);
+		entry.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+		entry.extend_from_slice(payload);
+
+		match entries.try_send(entry) {
+			Ok(()) => Ok(()),
+			Err(mpsc::TrySendError::Full(_)) => {
+				self.entries = None;
+				Err(io::Error::new(
+					io::ErrorKind::Other,
+					"The recording writer can't keep up with the subsystem",
+				))
+			},
+			// The thread only exits early if writing failed.
+			Err(mpsc::TrySendError::Disconnected(_)) => match self.finish() {
+				Ok(_) =>
+					Err(io::Error::new(io::ErrorKind::BrokenPipe, "The recording was stopped")),
+				Err(err) => Err(err),
+			},
+		}
+	}
+}
+
+/// Write the recorded entries until the recorder is dropped, flushing whenever there are no
+/// more entries waiting.
+fn write_entries<W: Write>(writer: W, pending: mpsc::Receiver<Vec<u8>>) -> io::Result<W> {
+	let mut writer = BufWriter::new(writer);
+	while let Ok(entry) = pending.recv() {
+		writer.write_all(&entry)?;
+		while let Ok(entry) = pending.try_recv() {
+			writer.write_all(&entry)?;
+		}
+		// Recordings are mostly wanted after something went wrong, so don't hold on to
+		// entries for longer than necessary.
+		writer.flush()?;
+	}
+	writer.into_inner().map_err(|err| err.into_error())
+}
+
+/// Read all entries of a recording.
+pub fn read_recording(mut reader: impl Read) -> io::Result<Vec<RecordedEntry>> {
+	let mut entries = Vec::new();
+
+	loop {
+		let mut elapsed = [0u8; 8];
+		match reader.read_exact(&mut elapsed) {
+			Ok(()) => {},
+			Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(entries),
+			Err(e) => return Err(e),
+		}
+
+		let mut kind = [0u8; 1];
+		reader.read_exact(&mut kind)?;
+		let kind = match kind[0] {
+			0 => RecordKind::Signal,
+			1 => RecordKind::Message,
+			2 => RecordKind::Response,
+			other => {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidData,
+					format!("Unknown recorded entry kind {}", other),
+				))
+			},
+		};
+
+		let mut len = [0u8; 4];
+		reader.read_exact(&mut len)?;
+		let len = u32::from_le_bytes(len) as usize;
+		if len > MAX_PAYLOAD_LEN {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!("Recorded entry of {} bytes exceeds the limit of {}", len, MAX_PAYLOAD_LEN),
+			))
+		}
+		let mut payload = vec![0u8; len];
+		reader.read_exact(&mut payload)?;
+
+		entries.push(RecordedEntry {
+			elapsed: Duration::from_micros(u64::from_le_bytes(elapsed)),
+			kind,
+			payload,
+		});
+	}
+}
+
+type SharedRecorder<W, C> = Arc<Mutex<Option<Recorder<W, C>>>>;
+
+type PendingResponse = (u64, BoxFuture<'static, Option<Vec<u8>>>);
+
+/// Record with the shared recorder, stopping the recording if that fails.
+fn record_with<W, C>(
+	recorder: &Mutex<Option<Recorder<W, C>>>,
+	record: impl FnOnce(&mut Recorder<W, C>) -> io::Result<()>,
+) {
+	let mut recorder = match recorder.lock() {
+		Ok(recorder) => recorder,
+		Err(_) => return,
+	};
+	if let Some(r) = recorder.as_mut() {
+		if let Err(err) = record(r) {
+			tracing::warn!(?err, "Failed to write the recording, stopping it");
+			*recorder = None;
+		}
+	}
+}
+
+/// A sender taking over the response channels of the requests sent through it, so that
+/// their responses can be recorded.
+#[derive(Clone)]
+pub struct RecordingSender<S, C> {
+	inner: S,
+	requests: Option<(C, RequestRecorder)>,
+}
+
+#[derive(Clone)]
+struct RequestRecorder {
+	next_index: Arc<AtomicU64>,
+	responses: async_mpsc::UnboundedSender<PendingResponse>,
+}
+
+impl<S, C> RecordingSender<S, C> {
+	fn intercept<M>(&self, message: &mut M)
+	where
+		C: ResponseCodec<M>,
+	{
+		let (codec, requests) = match self.requests.as_ref() {
+			// Without anyone forwarding them, the responses would never arrive.
+			Some((codec, requests)) if !requests.responses.is_closed() => (codec, requests),
+			_ => return,
+		};
+		if let Some(channel) = codec.intercept_response(message) {
+			let index = requests.next_index.fetch_add(1, Ordering::Relaxed);
+			let _ = requests.responses.unbounded_send((index, channel.record()));
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl<S, C, M> SubsystemSender<M> for RecordingSender<S, C>
+where
+	S: SubsystemSender<M>,
+	C: ResponseCodec<M> + Clone,
+	M: Send + 'static,
+{
+	async fn send_message(&mut self, mut msg: M) {
+		self.intercept(&mut msg);
+		self.inner.send_message(msg).await
+	}
+
+	async fn send_messages<I>(&mut self, msgs: I)
+	where
+		I: IntoIterator<Item = M> + Send,
+		I::IntoIter: Send,
+	{
+		let msgs = msgs
+			.into_iter()
+			.map(|mut msg| {
+				self.intercept(&mut msg);
+				msg
+			})
+			.collect::<Vec<_>>();
+		self.inner.send_messages(msgs).await
+	}
+
+	fn send_unbounded_message(&mut self, mut msg: M) {
+		self.intercept(&mut msg);
+		self.inner.send_unbounded_message(msg)
+	}
+}
+
+/// A subsystem context recording everything received through it, and the responses to the
+/// requests sent through it.
+///
+/// Failing to write the recording stops the recording, but not the subsystem.
+pub struct RecordingContext<Ctx: SubsystemContext, W, C> {
+	inner: Ctx,
+	recorder: SharedRecorder<W, C>,
+	sender: RecordingSender<Ctx::Sender, C>,
+}
+
+impl<Ctx, W, C> RecordingContext<Ctx, W, C>
+where
+	Ctx: SubsystemContext,
+	W: Write + Send + 'static,
+	C: RecordCodec<Ctx::Message, Ctx::Signal>,
+{
+	fn record(&mut self, from_orchestra: &FromOrchestra<Ctx::Message, Ctx::Signal>) {
+		record_with(&self.recorder, |recorder| recorder.record(from_orchestra));
+	}
+}
+
+#[async_trait::async_trait]
+impl<Ctx, W, C> SubsystemContext for RecordingContext<Ctx, W, C>
+where
+	Ctx: SubsystemContext,
+	W: Write + Send + 'static,
+	C: RecordCodec<Ctx::Message, Ctx::Signal> + ResponseCodec<Ctx::OutgoingMessages> + Clone,
+{
+	type Message = Ctx::Message;
+	type Signal = Ctx::Signal;
+	type OutgoingMessages = Ctx::OutgoingMessages;
+	type Sender = RecordingSender<Ctx::Sender, C>;
+	type Error = Ctx::Error;
+
+	async fn try_recv(&mut self) -> Result<Option<FromOrchestra<Self::Message, Self::Signal>>, ()> {
+		let from_orchestra = self.inner.try_recv().await?;
+		if let Some(ref from_orchestra) = from_orchestra {
+			self.record(from_orchestra);
+		}
+		Ok(from_orchestra)
+	}
+
+	async fn recv(&mut self) -> Result<FromOrchestra<Self::Message, Self::Signal>, Self::Error> {
+		let from_orchestra = self.inner.recv().await?;
+		self.record(&from_orchestra);
+		Ok(from_orchestra)
+	}
+
+	fn spawn(
+		&mut self,
+		name: &'static str,
+		s: ::std::pin::Pin<Box<dyn crate::Future<Output = ()> + Send>>,
+	) -> Result<(), Self::Error> {
+		self.inner.spawn(name, s)
+	}
+
+	fn spawn_blocking(
+		&mut self,
+		name: &'static str,
+		s: ::std::pin::Pin<Box<dyn crate::Future<Output = ()> + Send>>,
+	) -> Result<(), Self::Error> {
+		self.inner.spawn_blocking(name, s)
+	}
+
+	fn sender(&mut self) -> &mut Self::Sender {
+		&mut self.sender
+	}
+}
+
+/// Forward the responses to the intercepted requests, recording them, until no more
+/// requests can be sent.
+async fn record_responses<W, C>(
+	mut requests: async_mpsc::UnboundedReceiver<PendingResponse>,
+	recorder: SharedRecorder<W, C>,
+) where
+	W: Write + Send + 'static,
+{
+	let mut responses = FuturesUnordered::new();
+	loop {
+		futures::select! {
+			request = requests.next() => match request {
+				Some((index, response)) =>
+					responses.push(response.map(move |response| (index, response))),
+				None => break,
+			},
+			(index, response) = responses.select_next_some() => {
+				record_with(&recorder, |r| r.record_response(index, response.as_deref()));
+			},
+		}
+	}
+	while let Some((index, response)) = responses.next().await {
+		record_with(&recorder, |r| r.record_response(index, response.as_deref()));
+	}
+}
+
+/// A subsystem whose received signals and messages, and the responses to its requests,
+/// are recorded.
+pub struct RecordingSubsystem<S, W, C> {
+	subsystem: S,
+	recorder: Option<Recorder<W, C>>,
+}
+
+impl<S, W: Write + Send + 'static, C> RecordingSubsystem<S, W, C> {
+	/// Record everything `subsystem` receives into `writer`, encoded with `codec`.
+	pub fn new(subsystem: S, writer: W, codec: C) -> io::Result<Self> {
+		Ok(RecordingSubsystem { subsystem, recorder: Some(Recorder::new(writer, codec)?) })
+	}
+
+	/// Run `subsystem` without recording anything.
+	///
+	/// Allows to decide at runtime whether to record, without changing the subsystem's type.
+	pub fn disabled(subsystem: S) -> Self {
+		RecordingSubsystem { subsystem, recorder: None }
+	}
+}
+
+impl<S, W, C, Ctx, E> Subsystem<Ctx, E> for RecordingSubsystem<S, W, C>
+where
+	Ctx: SubsystemContext,
+	W: Write + Send + 'static,
+	C: RecordCodec<Ctx::Message, Ctx::Signal> + ResponseCodec<Ctx::OutgoingMessages> + Clone,
+	S: Subsystem<RecordingContext<Ctx, W, C>, E>,
+	E: std::error::Error + Send + Sync + 'static + From<OrchestraError>,
+{
+	fn start(self, mut ctx: Ctx) -> SpawnedSubsystem<E> {
+		let RecordingSubsystem { subsystem, mut recorder } = self;
+		if let Some(recorder) = recorder.as_mut() {
+			// Time the recording from the start of the subsystem, rather than its construction.
+			recorder.started = Instant::now();
+		}
+
+		// Without a recorder, the sender of the pending responses is dropped right away and
+		// requests are passed through untouched.
+		let (responses, pending) = async_mpsc::unbounded();
+		let requests = recorder.as_ref().map(|recorder| {
+			let requests = RequestRecorder { next_index: Arc::new(AtomicU64::new(0)), responses };
+			(recorder.codec.clone(), requests)
+		});
+		let sender = RecordingSender { inner: ctx.sender().clone(), requests };
+		let recorder = Arc::new(Mutex::new(recorder));
+
+		let SpawnedSubsystem { name, future } =
+			subsystem.start(RecordingContext { inner: ctx, recorder: recorder.clone(), sender });
+		let future = async move {
+			match future::select(future, record_responses(pending, recorder).boxed()).await {
+				Either::Left((result, _)) => result,
+				Either::Right(((), future)) => future.await,
+			}
+		};
+		SpawnedSubsystem { name, future: future.boxed() }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Clone)]
+	struct BytesCodec;
+
+	impl RecordCodec<Vec<u8>, u8> for BytesCodec {
+		fn encode_signal(&self, signal: &u8) -> Option<Vec<u8>> {
+			Some(vec![*signal])
+		}
+
+		fn encode_message(&self, message: &Vec<u8>) -> Option<Vec<u8>> {
+			// Pretend empty messages carry a response channel.
+			if message.is_empty() {
+				None
+			} else {
+				Some(message.clone())
+			}
+		}
+
+		fn decode_signal(&self, payload: &[u8]) -> Option<u8> {
+			payload.first().copied()
+		}
+
+		fn decode_message(&self, payload: &[u8]) -> Option<Vec<u8>> {
+			Some(payload.to_vec())
+		}
+	}
+
+	#[test]
+	fn recording_round_trips() {
+		let mut recorder = Recorder::new(Vec::new(), BytesCodec).unwrap();
+
+		let delivered: Vec<FromOrchestra<Vec<u8>, u8>> = vec![
+			FromOrchestra::Signal(7),
+			FromOrchestra::Communication { msg: vec![1, 2, 3] },
+			FromOrchestra::Communication { msg: Vec::new() },
+			FromOrchestra::Signal(8),
+		];
+		for from_orchestra in &delivered {
+			recorder.record(from_orchestra).unwrap();
+		}
+
+		let recording = recorder.into_inner().unwrap();
+		let entries = read_recording(&recording[..]).unwrap();
+		let kinds = entries.iter().map(|entry| entry.kind).collect::<Vec<_>>();
+		assert_eq!(kinds, vec![RecordKind::Signal, RecordKind::Message, RecordKind::Signal]);
+		assert!(entries.windows(2).all(|w| w[0].elapsed <= w[1].elapsed));
+
+		let decoded =
+			entries.iter().map(|entry| entry.decode(&BytesCodec).unwrap()).collect::<Vec<_>>();
+		assert_matches(&decoded[0], &delivered[0]);
+		assert_matches(&decoded[1], &delivered[1]);
+		assert_matches(&decoded[2], &delivered[3]);
+	}
+
+	#[test]
+	fn truncated_recording_is_an_error() {
+		let mut recorder = Recorder::new(Vec::new(), BytesCodec).unwrap();
+		recorder.record(&FromOrchestra::<Vec<u8>, u8>::Signal(7)).unwrap();
+
+		let recording = recorder.into_inner().unwrap();
+		assert!(read_recording(&recording[..recording.len() - 1]).is_err());
+	}
+
+	#[test]
+	fn oversized_entries_are_skipped() {
+		let mut recorder = Recorder::new(Vec::new(), BytesCodec).unwrap();
+		recorder
+			.record(&FromOrchestra::<Vec<u8>, u8>::Communication {
+				msg: vec![0; MAX_PAYLOAD_LEN + 1],
+			})
+			.unwrap();
+		recorder.record(&FromOrchestra::<Vec<u8>, u8>::Signal(7)).unwrap();
+
+		let recording = recorder.into_inner().unwrap();
+		let entries = read_recording(&recording[..]).unwrap();
+		assert_eq!(entries.len(), 1);
+		assert_eq!(entries[0].kind, RecordKind::Signal);
+	}
+
+	#[test]
+	fn oversized_length_is_an_error() {
+		let mut recording = 0u64.to_le_bytes().to_vec();
+		recording.push(RecordKind::Message as u8);
+		recording.extend_from_slice(&u32::MAX.to_le_bytes());
+
+		let err = read_recording(&recording[..]).unwrap_err();
+		assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+	}
+
+	#[test]
+	fn responses_round_trip() {
+		let mut recorder = Recorder::new(Vec::new(), BytesCodec).unwrap();
+		recorder.record(&FromOrchestra::<Vec<u8>, u8>::Signal(7)).unwrap();
+		recorder.record_response(1, Some(&[4, 5])).unwrap();
+		recorder.record_response(0, None).unwrap();
+
+		let recording = recorder.into_inner().unwrap();
+		let entries = read_recording(&recording[..]).unwrap();
+		assert_eq!(entries.len(), 3);
+		assert_eq!(entries[0].response(), None);
+		assert_eq!(entries[1].response(), Some((1, Some(&[4u8, 5][..]))));
+		assert_eq!(entries[2].response(), Some((0, None)));
+		assert!(entries[1].decode(&BytesCodec).is_none());
+	}
+
+	fn encode(response: &u32) -> Option<Vec<u8>> {
+		Some(response.to_le_bytes().to_vec())
+	}
+
+	fn decode(payload: &[u8]) -> Option<u32> {
+		Some(u32::from_le_bytes(payload.try_into().ok()?))
+	}
+
+	// Requests are just their response channels.
+	impl ResponseCodec<oneshot::Sender<u32>> for BytesCodec {
+		fn intercept_response(
+			&self,
+			message: &mut oneshot::Sender<u32>,
+		) -> Option<ResponseChannel> {
+			Some(ResponseChannel::intercept(message, encode, decode))
+		}
+	}
+
+	#[derive(Clone, Default)]
+	struct CollectingSender(Arc<Mutex<Vec<oneshot::Sender<u32>>>>);
+
+	#[async_trait::async_trait]
+	impl SubsystemSender<oneshot::Sender<u32>> for CollectingSender {
+		async fn send_message(&mut self, msg: oneshot::Sender<u32>) {
+			self.0.lock().unwrap().push(msg);
+		}
+
+		async fn send_messages<I>(&mut self, msgs: I)
+		where
+			I: IntoIterator<Item = oneshot::Sender<u32>> + Send,
+			I::IntoIter: Send,
+		{
+			self.0.lock().unwrap().extend(msgs);
+		}
+
+		fn send_unbounded_message(&mut self, msg: oneshot::Sender<u32>) {
+			self.0.lock().unwrap().push(msg);
+		}
+	}
+
+	#[test]
+	fn recording_sender_records_responses() {
+		let recorder = Arc::new(Mutex::new(Some(Recorder::new(Vec::new(), BytesCodec).unwrap())));
+		let (responses, pending) = async_mpsc::unbounded();
+		let inner = CollectingSender::default();
+		let requests = RequestRecorder { next_index: Arc::new(AtomicU64::new(0)), responses };
+		let mut sender =
+			RecordingSender { inner: inner.clone(), requests: Some((BytesCodec, requests)) };
+
+		let (first, first_rx) = oneshot::channel();
+		let (second, second_rx) = oneshot::channel();
+		futures::executor::block_on(sender.send_messages(vec![first, second]));
+		drop(sender);
+
+		let mut sent = std::mem::take(&mut *inner.0.lock().unwrap());
+		sent.pop().unwrap().send(2).unwrap();
+		drop(sent);
+
+		futures::executor::block_on(record_responses(pending, recorder.clone()));
+		assert!(futures::executor::block_on(first_rx).is_err());
+		assert_eq!(futures::executor::block_on(second_rx), Ok(2));
+
+		let recording = recorder.lock().unwrap().take().unwrap().into_inner().unwrap();
+		let mut responses = read_recording(&recording[..])
+			.unwrap()
+			.iter()
+			.map(|entry| entry.response().map(|(i, r)| (i, r.map(|r| r.to_vec()))).unwrap())
+			.collect::<Vec<_>>();
+		responses.sort();
+		assert_eq!(responses, vec![(0, None), (1, encode(&2))]);
+	}
+
+	#[test]
+	fn response_channels_forward_and_replay() {
+		// Recording forwards the response to the requester.
+		let (mut tx, rx) = oneshot::channel();
+		let channel = ResponseChannel::intercept(&mut tx, encode, decode);
+		tx.send(42u32).unwrap();
+		assert_eq!(futures::executor::block_on(channel.record()), Some(encode(&42).unwrap()));
+		assert_eq!(futures::executor::block_on(rx), Ok(42));
+
+		// Dropped requests are recorded as missing.
+		let (mut tx, rx) = oneshot::channel::<u32>();
+		let channel = ResponseChannel::intercept(&mut tx, encode, decode);
+		drop(tx);
+		assert_eq!(futures::executor::block_on(channel.record()), None);
+		assert!(futures::executor::block_on(rx).is_err());
+
+		// Replaying answers with the recorded response.
+		let (mut tx, rx) = oneshot::channel();
+		assert!(ResponseChannel::intercept(&mut tx, encode, decode).replay(Some(&[7, 0, 0, 0])));
+		assert_eq!(futures::executor::block_on(rx), Ok(7));
+
+		let (mut tx, rx) = oneshot::channel::<u32>();
+		assert!(ResponseChannel::intercept(&mut tx, encode, decode).replay(None));
+		assert!(futures::executor::block_on(rx).is_err());
+
+		let (mut tx, _rx) = oneshot::channel::<u32>();
+		assert!(!ResponseChannel::intercept(&mut tx, encode, decode).replay(Some(&[7])));
+	}
+
+	fn assert_matches(a: &FromOrchestra<Vec<u8>, u8>, b: &FromOrchestra<Vec<u8>, u8>) {
+		match (a, b) {
+			(FromOrchestra::Signal(a), FromOrchestra::Signal(b)) => assert_eq!(a, b),
+			(
+				FromOrchestra::Communication { msg: a },
+				FromOrchestra::Communication { msg: b },
+			) => assert_eq!(a, b),
+			_ => panic!("{:?} != {:?}", a, b),
+		}
+	}
+}
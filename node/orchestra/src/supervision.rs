@@ -0,0 +1,407 @@
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Supervision of subsystems whose future returned an error.
+//!
+//! By default, a failing subsystem concludes the orchestra. A subsystem declared with
+//! `supervision: Restart` is instead started again from a fresh instance after a backoff, and
+//! one declared with `supervision: Degrade` stays down while the orchestra keeps running.
+//!
+//! The incoming channels of a subsystem are only lent to its context and are reclaimed once the
+//! context is dropped, so the senders of other subsystems never observe a closed channel. While
+//! the subsystem is down, messages sent to it are discarded and signals are only counted. To catch
+//! up with the discarded signals, a restarted subsystem first receives the signals produced by the
+//! builder's `restart_signals`, if set, and only then the signals sent after its restart.
+
+use std::{
+	collections::VecDeque,
+	fmt,
+	ops::{Deref, DerefMut},
+	sync::{
+		atomic::{AtomicBool, AtomicUsize, Ordering},
+		Arc,
+	},
+	time::{Duration, Instant},
+};
+
+use futures::{channel::oneshot, future::BoxFuture, FutureExt, StreamExt};
+use futures_timer::Delay;
+
+use crate::{
+	metered, OrchestraError, SignalsReceived, SpawnedSubsystem, SubsystemIncomingMessages,
+};
+
+/// What happens when the future of a subsystem returns an error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Supervision {
+	/// Conclude the orchestra.
+	#[default]
+	FailFast,
+	/// Start a fresh instance of the subsystem after a [`RestartBackoff`].
+	Restart,
+	/// Keep the orchestra running without the subsystem.
+	Degrade,
+}
+
+/// Delays between restarts of subsystems supervised with [`Supervision::Restart`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartBackoff {
+	/// Delay before the first restart, doubled with every consecutive one.
+	pub initial: Duration,
+	/// Upper bound of the delay before a restart.
+	pub max: Duration,
+	/// Consecutive restarts after which a failing subsystem concludes the orchestra.
+	pub max_restarts: u32,
+	/// A subsystem that ran for this long before failing is not considered to fail
+	/// consecutively, its backoff starts over.
+	pub reset_after: Duration,
+}
+
+impl Default for RestartBackoff {
+	fn default() -> Self {
+		RestartBackoff {
+			initial: Duration::from_secs(1),
+			max: Duration::from_secs(60),
+			max_restarts: 10,
+			reset_after: Duration::from_secs(600),
+		}
+	}
+}
+
+impl RestartBackoff {
+	/// The delay before restarting a subsystem that was restarted `restarts` times in a row.
+	pub fn delay(&self, restarts: u32) -> Duration {
+		self.initial.saturating_mul(2_u32.saturating_pow(restarts)).min(self.max)
+	}
+}
+
+/// Restarts and degradation of a subsystem, shared between its supervisor and the orchestra.
+#[derive(Debug, Clone, Default)]
+pub struct SupervisionMeter {
+	restarts: Arc<AtomicUsize>,
+	degraded: Arc<AtomicBool>,
+}
+
+/// A readout of a [`SupervisionMeter`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SupervisionReadout {
+	/// How many times the subsystem was restarted, in aggregate.
+	pub restarts: usize,
+	/// Whether the subsystem failed and the orchestra continues without it.
+	pub degraded: bool,
+}
+
+impl SupervisionMeter {
+	/// Read the current values.
+	pub fn read(&self) -> SupervisionReadout {
+		SupervisionReadout {
+			restarts: self.restarts.load(Ordering::Relaxed),
+			degraded: self.degraded.load(Ordering::Relaxed),
+		}
+	}
+
+	fn note_restart(&self) {
+		self.restarts.fetch_add(1, Ordering::Relaxed);
+	}
+
+	fn note_degraded(&self) {
+		self.degraded.store(true, Ordering::Relaxed);
+	}
+}
+
+/// A value lent to a subsystem context, given back once the context is dropped.
+pub struct Reclaimable<T> {
+	value: Option<T>,
+	reclaim: Option<oneshot::Sender<T>>,
+}
+
+impl<T> Reclaimable<T> {
+	fn lend(value: T) -> (Self, oneshot::Receiver<T>) {
+		let (tx, rx) = oneshot::channel();
+		(Reclaimable { value: Some(value), reclaim: Some(tx) }, rx)
+	}
+}
+
+impl<T> Deref for Reclaimable<T> {
+	type Target = T;
+	fn deref(&self) -> &T {
+		self.value.as_ref().expect("Only taken when dropped. qed")
+	}
+}
+
+impl<T> DerefMut for Reclaimable<T> {
+	fn deref_mut(&mut self) -> &mut T {
+		self.value.as_mut().expect("Only taken when dropped. qed")
+	}
+}
+
+impl<T> Drop for Reclaimable<T> {
+	fn drop(&mut self) {
+		if let (Some(value), Some(reclaim)) = (self.value.take(), self.reclaim.take()) {
+			let _ = reclaim.send(value);
+		}
+	}
+}
+
+impl<T: fmt::Debug> fmt::Debug for Reclaimable<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("Reclaimable").field(&self.value).finish()
+	}
+}
+
+/// The incoming channels of a subsystem.
+pub struct SubsystemIncoming<Signal, M> {
+	signals: metered::MeteredReceiver<Signal>,
+	messages: SubsystemIncomingMessages<M>,
+	signals_received: SignalsReceived,
+}
+
+impl<Signal, M> SubsystemIncoming<Signal, M> {
+	/// Bundle the receiving ends of the signal and message channels of a subsystem.
+	pub fn new(
+		signals: metered::MeteredReceiver<Signal>,
+		messages: SubsystemIncomingMessages<M>,
+	) -> Self {
+		SubsystemIncoming { signals, messages, signals_received: SignalsReceived::default() }
+	}
+
+	/// Lend the channels to a subsystem context.
+	pub fn lend(self) -> (IncomingLease<Signal, M>, Reclaim<Signal, M>) {
+		self.lend_with_signals(Vec::new())
+	}
+
+	/// Lend the channels to a subsystem context, which receives the given signals first.
+	fn lend_with_signals(
+		self,
+		restart_signals: Vec<Signal>,
+	) -> (IncomingLease<Signal, M>, Reclaim<Signal, M>) {
+		let (signals, signals_rx) = Reclaimable::lend(self.signals);
+		let (messages, messages_rx) = Reclaimable::lend(self.messages);
+		let lease = IncomingLease {
+			signals,
+			messages,
+			signals_received: self.signals_received.clone(),
+			restart_signals: restart_signals.into(),
+		};
+		let reclaim = Reclaim {
+			signals: signals_rx,
+			messages: messages_rx,
+			signals_received: self.signals_received,
+		};
+		(lease, reclaim)
+	}
+
+	/// Discard messages and count signals until the channels terminate or `until` resolves.
+	///
+	/// Returns `None` if the channels terminated.
+	async fn discard<F>(mut self, until: F) -> Option<Self>
+	where
+		F: std::future::Future<Output = ()> + Unpin,
+	{
+		let mut until = until.fuse();
+		loop {
+			let is_signal = futures::select! {
+				signal = self.signals.next().fuse() => match signal {
+					Some(_) => true,
+					None => return None,
+				},
+				message = self.messages.next().fuse() => match message {
+					Some(_) => false,
+					None => return None,
+				},
+				_ = until => break,
+			};
+			if is_signal {
+				self.signals_received.inc();
+			}
+		}
+		Some(self)
+	}
+
+	/// Count the signals which are ready to be received.
+	///
+	/// Returns `None` if the signal channel terminated.
+	fn discard_ready_signals(mut self) -> Option<Self> {
+		loop {
+			match self.signals.next().now_or_never() {
+				Some(Some(_)) => self.signals_received.inc(),
+				Some(None) => return None,
+				None => return Some(self),
+			}
+		}
+	}
+}
+
+/// The incoming channels of a subsystem as lent to its context.
+pub struct IncomingLease<Signal, M> {
+	signals: Reclaimable<metered::MeteredReceiver<Signal>>,
+	messages: Reclaimable<SubsystemIncomingMessages<M>>,
+	signals_received: SignalsReceived,
+	restart_signals: VecDeque<Signal>,
+}
+
+impl<Signal, M> IncomingLease<Signal, M> {
+	/// The lent channels, the counter of signals received through them and the signals to
+	/// receive before any of the channels after a restart.
+	pub fn into_parts(
+		self,
+	) -> (
+		Reclaimable<metered::MeteredReceiver<Signal>>,
+		Reclaimable<SubsystemIncomingMessages<M>>,
+		SignalsReceived,
+		VecDeque<Signal>,
+	) {
+		(self.signals, self.messages, self.signals_received, self.restart_signals)
+	}
+}
+
+/// Receives the incoming channels of a subsystem back from its dropped context.
+pub struct Reclaim<Signal, M> {
+	signals: oneshot::Receiver<metered::MeteredReceiver<Signal>>,
+	messages: oneshot::Receiver<SubsystemIncomingMessages<M>>,
+	signals_received: SignalsReceived,
+}
+
+impl<Signal, M> Reclaim<Signal, M> {
+	/// Take the channels back, if the context was dropped.
+	fn try_reclaim(mut self) -> Option<SubsystemIncoming<Signal, M>> {
+		let signals = self.signals.try_recv().ok().flatten()?;
+		let messages = self.messages.try_recv().ok().flatten()?;
+		Some(SubsystemIncoming { signals, messages, signals_received: self.signals_received })
+	}
+}
+
+/// Starts a fresh instance of a subsystem with the given channels.
+pub type RestartFn<Signal, M, E> =
+	Box<dyn FnMut(IncomingLease<Signal, M>) -> SpawnedSubsystem<E> + Send>;
+
+/// Produces the signals a restarted subsystem receives before any other, e.g. to announce
+/// the current state it would otherwise have learned from the signals it missed.
+pub type RestartSignalsFn<Signal> = Arc<dyn Fn() -> Vec<Signal> + Send + Sync>;
+
+/// Run the future of a subsystem and apply its [`Supervision`] whenever it returns an error.
+///
+/// Resolves once the subsystem exited without an error, failed with [`Supervision::FailFast`],
+/// exceeded its restarts, or its channels terminated.
+///
+/// A restarted subsystem receives the signals of `restart_signals` first. Those are produced
+/// after the signals already queued up were discarded, any signal sent later on is received
+/// after them.
+pub async fn supervise<Signal, M, E>(
+	name: &'static str,
+	future: BoxFuture<'static, Result<(), E>>,
+	reclaim: Reclaim<Signal, M>,
+	supervision: Supervision,
+	mut restart: Option<RestartFn<Signal, M, E>>,
+	restart_signals: Option<RestartSignalsFn<Signal>>,
+	backoff: RestartBackoff,
+	meter: SupervisionMeter,
+) where
+	E: std::error::Error + Send + Sync + 'static + From<OrchestraError>,
+{
+	let mut future = future;
+	let mut reclaim = reclaim;
+	let mut restarts = 0;
+
+	loop {
+		let started = Instant::now();
+		let err = match future.await {
+			Ok(()) => {
+				tracing::debug!(subsystem = name, "subsystem exited without an error");
+				return
+			},
+			Err(err) => err,
+		};
+
+		if supervision == Supervision::FailFast {
+			tracing::error!(subsystem = name, ?err, "subsystem exited with error");
+			return
+		}
+
+		let incoming = match reclaim.try_reclaim() {
+			Some(incoming) => incoming,
+			None => {
+				tracing::error!(
+					subsystem = name,
+					?err,
+					"subsystem exited with error, but its channels were not released",
+				);
+				return
+			},
+		};
+
+		if supervision == Supervision::Degrade {
+			tracing::error!(
+				subsystem = name,
+				?err,
+				"subsystem exited with error, continuing without it"
+			);
+			meter.note_degraded();
+			let _ = incoming.discard(futures::future::pending()).await;
+			return
+		}
+
+		let restart = match restart.as_mut() {
+			Some(restart) => restart,
+			None => {
+				tracing::error!(
+					subsystem = name,
+					?err,
+					"subsystem exited with error, but can not be restarted",
+				);
+				return
+			},
+		};
+
+		if started.elapsed() >= backoff.reset_after {
+			restarts = 0;
+		}
+		if restarts >= backoff.max_restarts {
+			tracing::error!(
+				subsystem = name,
+				?err,
+				restarts,
+				"subsystem exited with error, too many consecutive restarts",
+			);
+			return
+		}
+
+		let delay = backoff.delay(restarts);
+		tracing::warn!(
+			subsystem = name,
+			?err,
+			restarts,
+			delay_ms = delay.as_millis() as u64,
+			"subsystem exited with error, restarting",
+		);
+
+		let incoming = match incoming.discard(Delay::new(delay)).await {
+			Some(incoming) => incoming,
+			None => return,
+		};
+		let (lease, next_reclaim) = match restart_signals.as_ref() {
+			Some(restart_signals) => match incoming.discard_ready_signals() {
+				Some(incoming) => incoming.lend_with_signals(restart_signals()),
+				None => return,
+			},
+			None => incoming.lend(),
+		};
+		future = restart(lease).future;
+		reclaim = next_reclaim;
+		restarts += 1;
+		meter.note_restart();
+		tracing::info!(subsystem = name, restarts, "subsystem restarted");
+	}
+}
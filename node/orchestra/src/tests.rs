@@ -36,3 +36,200 @@ fn ui_pass() {
 	let t = trybuild::TestCases::new();
 	t.pass("tests/ui/ok-*.rs");
 }
+
+#[test]
+fn incoming_messages_prefer_priority_lane_without_starving_others() {
+	use super::*;
+	use futures::executor;
+
+	let (mut priority_tx, priority_rx) = metered::channel(64);
+	let (mut bounded_tx, bounded_rx) = metered::channel(64);
+	let (unbounded_tx, unbounded_rx) = metered::unbounded();
+	let mut messages = SubsystemIncomingMessages::new(priority_rx, bounded_rx, unbounded_rx);
+
+	executor::block_on(async {
+		bounded_tx.send(make_packet(0, 100)).await.unwrap();
+		unbounded_tx.unbounded_send(make_packet(0, 200)).unwrap();
+		for i in 0..MAX_CONSECUTIVE_PRIORITY_MESSAGES + 2 {
+			priority_tx.send(make_packet(0, i)).await.unwrap();
+		}
+	});
+	drop((priority_tx, bounded_tx, unbounded_tx));
+
+	let received =
+		executor::block_on(messages.by_ref().map(|packet| packet.message).collect::<Vec<_>>());
+
+	let mut expected = Vec::from_iter(0..MAX_CONSECUTIVE_PRIORITY_MESSAGES);
+	expected.extend([
+		200,
+		MAX_CONSECUTIVE_PRIORITY_MESSAGES,
+		MAX_CONSECUTIVE_PRIORITY_MESSAGES + 1,
+		100,
+	]);
+	assert_eq!(received, expected);
+}
+
+#[test]
+fn supervision_restarts_failed_subsystem_on_reclaimed_channels() {
+	use super::{supervision::*, *};
+	use futures::executor;
+	use std::time::Duration;
+
+	let (mut signal_tx, signal_rx) = metered::channel::<u32>(64);
+	let (_priority_tx, priority_rx) = metered::channel(64);
+	let (mut bounded_tx, bounded_rx) = metered::channel(64);
+	let (_unbounded_tx, unbounded_rx) = metered::unbounded();
+	let incoming = SubsystemIncoming::new(
+		signal_rx,
+		SubsystemIncomingMessages::<u32>::new(priority_rx, bounded_rx, unbounded_rx),
+	);
+	let (lease, reclaim) = incoming.lend();
+
+	// Fails right away, dropping its context.
+	let future = async move {
+		drop(lease);
+		Err(OrchestraError::Context("failed".to_owned()))
+	}
+	.boxed();
+
+	// Conclude on the first signal, after echoing the first message and the signals to catch up
+	// with back.
+	let (echo_tx, mut echo_rx) = metered::unbounded();
+	let restart: RestartFn<u32, u32, OrchestraError> = Box::new(move |lease| {
+		let echo_tx = echo_tx.clone();
+		let (mut signals, mut messages, signals_received, restart_signals) = lease.into_parts();
+		SpawnedSubsystem {
+			name: "restarted",
+			future: async move {
+				let packet = messages.next().await.unwrap();
+				echo_tx
+					.unbounded_send((
+						packet.message,
+						signals_received.load(),
+						Vec::from(restart_signals),
+					))
+					.unwrap();
+				signals.next().await.unwrap();
+				Ok(())
+			}
+			.boxed(),
+		}
+	});
+
+	let backoff = RestartBackoff { initial: Duration::from_millis(1), ..Default::default() };
+	let meter = SupervisionMeter::default();
+
+	executor::block_on(async {
+		let supervisor = supervise(
+			"test",
+			future,
+			reclaim,
+			Supervision::Restart,
+			Some(restart),
+			Some(Arc::new(|| vec![42])),
+			backoff,
+			meter.clone(),
+		);
+		let test = async {
+			signal_tx.send(1).await.unwrap();
+			loop {
+				bounded_tx.send(make_packet(0, 7)).await.unwrap();
+				if let Some(echo) = echo_rx.next().now_or_never().flatten() {
+					// the signal sent during the backoff was discarded, but counted, and the
+					// restarted subsystem catches up with the restart signals instead
+					assert_eq!(echo, (7, 1, vec![42]));
+					break
+				}
+				Delay::new(Duration::from_millis(5)).await;
+			}
+			signal_tx.send(2).await.unwrap();
+		};
+		futures::join!(supervisor, test);
+	});
+
+	assert_eq!(meter.read(), SupervisionReadout { restarts: 1, degraded: false });
+}
+
+#[test]
+fn supervision_degrades_and_keeps_channels_open() {
+	use super::{supervision::*, *};
+	use futures::executor;
+
+	let (signal_tx, signal_rx) = metered::channel::<u32>(1);
+	let (_priority_tx, priority_rx) = metered::channel(1);
+	let (bounded_tx, bounded_rx) = metered::channel(1);
+	let (unbounded_tx, unbounded_rx) = metered::unbounded();
+	let incoming = SubsystemIncoming::new(
+		signal_rx,
+		SubsystemIncomingMessages::<u32>::new(priority_rx, bounded_rx, unbounded_rx),
+	);
+	let (lease, reclaim) = incoming.lend();
+
+	let future = async move {
+		drop(lease);
+		Err(OrchestraError::Context("failed".to_owned()))
+	}
+	.boxed();
+	let meter = SupervisionMeter::default();
+
+	executor::block_on(async {
+		let supervisor = supervise(
+			"test",
+			future,
+			reclaim,
+			Supervision::Degrade,
+			None,
+			None,
+			RestartBackoff::default(),
+			meter.clone(),
+		);
+		let test = async move {
+			let (mut signal_tx, mut bounded_tx) = (signal_tx, bounded_tx);
+			// Way more than fit into the channels, so they must be drained.
+			for i in 0..10 {
+				signal_tx.send(i).await.unwrap();
+				bounded_tx.send(make_packet(0, i)).await.unwrap();
+			}
+			assert!(meter.read().degraded);
+			// Terminate the channels, so the supervisor resolves.
+			drop((signal_tx, bounded_tx, unbounded_tx));
+		};
+		futures::join!(supervisor, test);
+	});
+}
+
+#[test]
+fn pending_message_kinds_are_counted_until_received() {
+	use super::*;
+	use futures::executor;
+
+	#[derive(Debug)]
+	#[allow(dead_code)]
+	enum Message {
+		Import(u32),
+		Query { id: u32 },
+	}
+
+	let (_priority_tx, priority_rx) = metered::channel(64);
+	let (mut bounded_tx, bounded_rx) = metered::channel(64);
+	let (_unbounded_tx, unbounded_rx) = metered::unbounded();
+	let pending_kinds = PendingMessageKinds::new(true);
+	let mut messages = SubsystemIncomingMessages::new(priority_rx, bounded_rx, unbounded_rx)
+		.with_pending_kinds(pending_kinds.clone());
+
+	executor::block_on(async {
+		for message in [Message::Import(1), Message::Query { id: 2 }, Message::Import(3)] {
+			pending_kinds.note_sent(&message);
+			bounded_tx.send(make_packet(0, message)).await.unwrap();
+		}
+	});
+	assert_eq!(pending_kinds.read(), vec![("Import".to_owned(), 2), ("Query".to_owned(), 1)]);
+
+	executor::block_on(messages.next()).unwrap();
+	executor::block_on(messages.next()).unwrap();
+	assert_eq!(pending_kinds.read(), vec![("Import".to_owned(), 1)]);
+
+	let disabled = PendingMessageKinds::new(false);
+	disabled.note_sent(&Message::Import(4));
+	assert!(disabled.read().is_empty());
+}
@@ -0,0 +1,83 @@
+#![allow(dead_code)]
+
+use orchestra::*;
+
+#[derive(Default)]
+struct AwesomeSubSysA;
+
+
+impl ::orchestra::Subsystem<OrchestraSubsystemContext<MsgA>, OrchestraError> for AwesomeSubSysA {
+	fn start(self, _ctx: OrchestraSubsystemContext<MsgA>) -> SpawnedSubsystem<OrchestraError> {
+		SpawnedSubsystem { name: "sub A", future: Box::pin(async move { Ok(()) }) }
+	}
+}
+impl ::orchestra::Subsystem<OrchestraSubsystemContext<MsgB>, OrchestraError> for AwesomeSubSysB {
+	fn start(self, _ctx: OrchestraSubsystemContext<MsgB>) -> SpawnedSubsystem<OrchestraError> {
+		SpawnedSubsystem { name: "sub B", future: Box::pin(async move { Ok(()) }) }
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct DummySpawner;
+
+impl Spawner for DummySpawner {
+	fn spawn_blocking(
+		&self,
+		task_name: &'static str,
+		subsystem_name: Option<&'static str>,
+		_future: futures::future::BoxFuture<'static, ()>,
+	) {
+		println!("spawn blocking {} {}", task_name, subsystem_name.unwrap_or("default"))
+	}
+
+	fn spawn(
+		&self,
+		task_name: &'static str,
+		subsystem_name: Option<&'static str>,
+		_future: futures::future::BoxFuture<'static, ()>,
+	) {
+		println!("spawn {} {}", task_name, subsystem_name.unwrap_or("default"))
+	}
+}
+
+#[derive(Default)]
+struct AwesomeSubSysB;
+
+#[derive(Clone, Debug)]
+pub struct SigSigSig;
+
+pub struct Event;
+
+#[derive(Clone, Debug)]
+pub enum MsgA {
+	Urgent(u8),
+	Bulk { payload: u8 },
+	Tick,
+}
+
+#[derive(Clone, Debug)]
+pub struct MsgB(u8);
+
+#[orchestra(signal=SigSigSig, event=Event, gen=AllMessages, error=OrchestraError)]
+pub struct Orchestra {
+	#[subsystem(MsgA, priority: [Urgent, Tick])]
+	sub_a: AwesomeSubSysA,
+
+	#[subsystem(MsgB)]
+	sub_b: AwesomeSubSysB,
+}
+
+pub struct DummyCtx;
+
+fn main() {
+	assert_eq!(MsgA::Urgent(0).priority(), Priority::High);
+	assert_eq!(MsgA::Tick.priority(), Priority::High);
+	assert_eq!(MsgA::Bulk { payload: 0 }.priority(), Priority::Normal);
+	assert_eq!(MsgB(0).priority(), Priority::Normal);
+
+	let _orchestra_builder = Orchestra::builder()
+		.sub_a(AwesomeSubSysA::default())
+		.sub_b(AwesomeSubSysB::default())
+		.spawner(DummySpawner)
+		.build();
+}
@@ -16,7 +16,7 @@
 
 use crate::{
 	prometheus::Registry, HeadSupportsParachains, InitializedOverseerBuilder, MetricsTrait,
-	Overseer, OverseerMetrics, OverseerSignal, OverseerSubsystemContext, SpawnGlue,
+	NotifiedLeaves, Overseer, OverseerMetrics, OverseerSignal, OverseerSubsystemContext, SpawnGlue,
 	KNOWN_LEAVES_CACHE_SIZE,
 };
 use lru::LruCache;
@@ -133,6 +133,8 @@ where
 	SpawnGlue<Spawner>: orchestra::Spawner + 'static,
 	SupportsParachains: HeadSupportsParachains,
 	Sub: Clone
+		+ Send
+		+ 'static
 		+ Subsystem<OverseerSubsystemContext<AvailabilityDistributionMessage>, SubsystemError>
 		+ Subsystem<OverseerSubsystemContext<AvailabilityRecoveryMessage>, SubsystemError>
 		+ Subsystem<OverseerSubsystemContext<AvailabilityStoreMessage>, SubsystemError>
@@ -156,6 +158,7 @@ where
 		+ Subsystem<OverseerSubsystemContext<PvfCheckerMessage>, SubsystemError>,
 {
 	let metrics = <OverseerMetrics as MetricsTrait>::register(registry)?;
+	let notified_leaves = NotifiedLeaves::default();
 
 	let builder = Overseer::builder()
 		.availability_distribution(subsystem.clone())
@@ -182,10 +185,14 @@ where
 		.activation_external_listeners(Default::default())
 		.span_per_active_leaf(Default::default())
 		.active_leaves(Default::default())
+		.notified_leaves(notified_leaves.clone())
 		.known_leaves(LruCache::new(KNOWN_LEAVES_CACHE_SIZE))
 		.leaves(Default::default())
 		.spawner(SpawnGlue(spawner))
 		.metrics(metrics)
-		.supports_parachains(supports_parachains);
+		.stall_watchdog(Default::default())
+		.graph_export(None)
+		.supports_parachains(supports_parachains)
+		.restart_signals(move || notified_leaves.restart_signals());
 	Ok(builder)
 }
@@ -0,0 +1,106 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Periodic export of the subsystem connection graph to a file.
+//!
+//! The metrics metronome annotates a snapshot of the connection graph with the meter readouts
+//! of every subsystem and writes it out, at most once per configured interval. The file is
+//! replaced atomically, so readers never observe a partially written graph.
+
+use std::{
+	io,
+	path::{Path, PathBuf},
+	time::{Duration, Instant},
+};
+
+use crate::{gen::graph::ConnectionGraph, LOG_TARGET};
+
+/// Where and how often to export the subsystem connection graph.
+#[derive(Debug, Clone)]
+pub struct GraphExportConfig {
+	/// The file to write the graph to.
+	///
+	/// Files with a `.json` extension are written as JSON, all others in graphviz DOT format.
+	pub path: PathBuf,
+	/// The minimum time between two exports.
+	pub interval: Duration,
+}
+
+/// The format a graph is exported in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GraphFormat {
+	Dot,
+	Json,
+}
+
+impl GraphFormat {
+	pub(crate) fn from_path(path: &Path) -> Self {
+		match path.extension().and_then(|ext| ext.to_str()) {
+			Some(ext) if ext.eq_ignore_ascii_case("json") => GraphFormat::Json,
+			_ => GraphFormat::Dot,
+		}
+	}
+
+	pub(crate) fn render(&self, graph: &ConnectionGraph) -> String {
+		match self {
+			GraphFormat::Dot => graph.to_dot(),
+			GraphFormat::Json => graph.to_json(),
+		}
+	}
+}
+
+/// Writes the connection graph whenever the configured interval has elapsed.
+pub(crate) struct GraphExporter {
+	config: GraphExportConfig,
+	format: GraphFormat,
+	last_export: Option<Instant>,
+}
+
+impl GraphExporter {
+	pub(crate) fn new(config: GraphExportConfig) -> Self {
+		let format = GraphFormat::from_path(&config.path);
+		GraphExporter { config, format, last_export: None }
+	}
+
+	/// Whether an export is due at `now`.
+	pub(crate) fn is_due(&self, now: Instant) -> bool {
+		self.last_export
+			.map_or(true, |last| now.saturating_duration_since(last) >= self.config.interval)
+	}
+
+	/// Write the graph out, logging on failure.
+	pub(crate) fn export(&mut self, graph: &ConnectionGraph, now: Instant) {
+		self.last_export = Some(now);
+
+		if let Err(err) = write_atomically(&self.config.path, &self.format.render(graph)) {
+			gum::warn!(
+				target: LOG_TARGET,
+				path = ?self.config.path,
+				?err,
+				"Failed to export the subsystem connection graph",
+			);
+		}
+	}
+}
+
+fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+	let mut tmp = path.as_os_str().to_owned();
+	tmp.push(".tmp");
+	let tmp = PathBuf::from(tmp);
+
+	std::fs::write(&tmp, contents)?;
+	std::fs::rename(&tmp, path)
+}
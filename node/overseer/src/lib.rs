@@ -98,6 +98,16 @@ pub use self::metrics::Metrics as OverseerMetrics;
 pub mod dummy;
 pub use self::dummy::DummySubsystem;
 
+mod watchdog;
+use self::watchdog::StallWatchdog;
+pub use self::watchdog::StallWatchdogConfig;
+
+mod graph_export;
+pub use self::graph_export::GraphExportConfig;
+use self::graph_export::GraphExporter;
+
+pub mod record;
+
 pub use polkadot_node_metrics::{
 	metrics::{prometheus, Metrics as MetricsTrait},
 	Metronome,
@@ -107,10 +117,11 @@ use parity_util_mem::MemoryAllocationTracker;
 
 pub use orchestra as gen;
 pub use orchestra::{
-	contextbounds, orchestra, subsystem, FromOrchestra, MapSubsystem, MessagePacket,
-	SignalsReceived, Spawner, Subsystem, SubsystemContext, SubsystemIncomingMessages,
-	SubsystemInstance, SubsystemMeterReadouts, SubsystemMeters, SubsystemSender, TimeoutExt,
-	ToOrchestra,
+	contextbounds, orchestra, subsystem,
+	supervision::{RestartBackoff, Supervision},
+	FromOrchestra, MapSubsystem, MessagePacket, SignalsReceived, Spawner, Subsystem,
+	SubsystemContext, SubsystemIncomingMessages, SubsystemInstance, SubsystemMeterReadouts,
+	SubsystemMeters, SubsystemSender, TimeoutExt, ToOrchestra,
 };
 
 /// Store 2 days worth of blocks, not accounting for forks,
@@ -303,6 +314,36 @@ pub enum ExternalRequest {
 	},
 }
 
+/// The leaves subsystems were notified about as active, shared with the supervisors of
+/// subsystems declared with `supervision: Restart`.
+///
+/// A restarted subsystem missed the signals sent while it was down, so it is notified about
+/// the active leaves again before receiving any other signal.
+#[derive(Clone, Default)]
+pub struct NotifiedLeaves(Arc<parking_lot::Mutex<HashMap<Hash, ActivatedLeaf>>>);
+
+impl NotifiedLeaves {
+	fn note_update(&self, update: &ActiveLeavesUpdate) {
+		let mut leaves = self.0.lock();
+		for deactivated in &update.deactivated {
+			leaves.remove(deactivated);
+		}
+		if let Some(activated) = &update.activated {
+			leaves.insert(activated.hash, activated.clone());
+		}
+	}
+
+	/// The signals activating each active leaf, lowest block number first.
+	pub fn restart_signals(&self) -> Vec<OverseerSignal> {
+		let mut leaves = self.0.lock().values().cloned().collect::<Vec<_>>();
+		leaves.sort_by_key(|leaf| leaf.number);
+		leaves
+			.into_iter()
+			.map(|leaf| OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(leaf)))
+			.collect()
+	}
+}
+
 /// Glues together the [`Overseer`] and `BlockchainEvents` by forwarding
 /// import and finality notifications into the [`OverseerHandle`].
 pub async fn forward_events<P: BlockchainEvents<Block>>(client: Arc<P>, mut handle: Handle) {
@@ -461,7 +502,7 @@ pub struct Overseer<SupportsParachains> {
 	#[subsystem(PvfCheckerMessage, sends: [
 		CandidateValidationMessage,
 		RuntimeApiMessage,
-	])]
+	], supervision: Restart)]
 	pvf_checker: PvfChecker,
 
 	#[subsystem(CandidateBackingMessage, sends: [
@@ -503,7 +544,7 @@ pub struct Overseer<SupportsParachains> {
 		AvailabilityStoreMessage,
 		RuntimeApiMessage,
 		BitfieldDistributionMessage,
-	])]
+	], supervision: Restart)]
 	bitfield_signing: BitfieldSigning,
 
 	#[subsystem(BitfieldDistributionMessage, sends: [
@@ -571,6 +612,11 @@ pub struct Overseer<SupportsParachains> {
 		AvailabilityRecoveryMessage,
 		ApprovalDistributionMessage,
 		CandidateValidationMessage,
+	], priority: [
+		CheckAndImportAssignment,
+		CheckAndImportApproval,
+		CheckAndImportAssignments,
+		CheckAndImportApprovals,
 	])]
 	approval_voting: ApprovalVoting,
 
@@ -578,7 +624,7 @@ pub struct Overseer<SupportsParachains> {
 		NetworkBridgeMessage,
 		RuntimeApiMessage,
 		ChainSelectionMessage,
-	])]
+	], supervision: Restart)]
 	gossip_support: GossipSupport,
 
 	#[subsystem(blocking, DisputeCoordinatorMessage, sends: [
@@ -588,7 +634,7 @@ pub struct Overseer<SupportsParachains> {
 		CandidateValidationMessage,
 		AvailabilityStoreMessage,
 		AvailabilityRecoveryMessage,
-	])]
+	], priority: [ImportStatements])]
 	dispute_coordinator: DisputeCoordinator,
 
 	#[subsystem(DisputeDistributionMessage, sends: [
@@ -615,6 +661,9 @@ pub struct Overseer<SupportsParachains> {
 	/// The set of the "active leaves".
 	pub active_leaves: HashMap<Hash, BlockNumber>,
 
+	/// The active leaves subsystems were notified about, to notify restarted ones again.
+	pub notified_leaves: NotifiedLeaves,
+
 	/// An implementation for checking whether a header supports parachain consensus.
 	pub supports_parachains: SupportsParachains,
 
@@ -623,6 +672,12 @@ pub struct Overseer<SupportsParachains> {
 
 	/// Various Prometheus metrics.
 	pub metrics: OverseerMetrics,
+
+	/// Thresholds for detecting stalled subsystems.
+	pub stall_watchdog: StallWatchdogConfig,
+
+	/// Where to periodically export the subsystem connection graph to, if anywhere.
+	pub graph_export: Option<GraphExportConfig>,
 }
 
 /// Spawn the metrics metronome task.
@@ -647,6 +702,11 @@ where
 		}
 	}
 	let subsystem_meters = overseer.map_subsystems(ExtractNameAndMeters);
+	let mut stall_watchdog = StallWatchdog::new(overseer.stall_watchdog);
+	let mut graph_export = overseer
+		.graph_export
+		.clone()
+		.map(|config| (GraphExporter::new(config), overseer.connection_graph().clone()));
 
 	let collect_memory_stats: Box<dyn Fn(&OverseerMetrics) + Send> =
 		match MemoryAllocationTracker::new() {
@@ -676,16 +736,30 @@ where
 	let metronome = Metronome::new(std::time::Duration::from_millis(950)).for_each(move |_| {
 		collect_memory_stats(&metronome_metrics);
 
+		let readouts = subsystem_meters
+			.iter()
+			.cloned()
+			.filter_map(|x| x)
+			.map(|(name, ref meters)| (name, meters.read()))
+			.collect::<Vec<_>>();
+
+		let now = std::time::Instant::now();
+		for (name, readouts) in &readouts {
+			let observation = stall_watchdog.observe(*name, readouts, now);
+			metronome_metrics.on_watchdog_observation(*name, &observation);
+		}
+
+		if let Some((exporter, connection_graph)) = graph_export.as_mut() {
+			if exporter.is_due(now) {
+				let graph = connection_graph.snapshot().with_meter_readouts(&readouts);
+				exporter.export(&graph, now);
+			}
+		}
+
 		// We combine the amount of messages from subsystems to the overseer
 		// as well as the amount of messages from external sources to the overseer
 		// into one `to_overseer` value.
-		metronome_metrics.channel_metrics_snapshot(
-			subsystem_meters
-				.iter()
-				.cloned()
-				.filter_map(|x| x)
-				.map(|(name, ref meters)| (name, meters.read())),
-		);
+		metronome_metrics.channel_metrics_snapshot(readouts);
 
 		futures::future::ready(())
 	});
@@ -717,7 +791,7 @@ where
 			if let Some((span, status)) = self.on_head_activated(&hash, None) {
 				let update =
 					ActiveLeavesUpdate::start_work(ActivatedLeaf { hash, number, status, span });
-				self.broadcast_signal(OverseerSignal::ActiveLeaves(update)).await?;
+				self.broadcast_active_leaves(update).await?;
 			}
 		}
 
@@ -795,7 +869,7 @@ where
 		self.clean_up_external_listeners();
 
 		if !update.is_empty() {
-			self.broadcast_signal(OverseerSignal::ActiveLeaves(update)).await?;
+			self.broadcast_active_leaves(update).await?;
 		}
 		Ok(())
 	}
@@ -825,12 +899,20 @@ where
 		//
 		// Our peers will be informed about our finalized block the next time we activating/deactivating some leaf.
 		if !update.is_empty() {
-			self.broadcast_signal(OverseerSignal::ActiveLeaves(update)).await?;
+			self.broadcast_active_leaves(update).await?;
 		}
 
 		Ok(())
 	}
 
+	/// Broadcast an update of the active leaves to all subsystems.
+	async fn broadcast_active_leaves(&mut self, update: ActiveLeavesUpdate) -> SubsystemResult<()> {
+		// Noted before broadcasting, a subsystem restarting in the meantime may hence be notified
+		// about a leaf twice, but never misses one.
+		self.notified_leaves.note_update(&update);
+		self.broadcast_signal(OverseerSignal::ActiveLeaves(update)).await
+	}
+
 	/// Handles a header activation. If the header's state doesn't support the parachains API,
 	/// this returns `None`.
 	fn on_head_activated(
@@ -20,6 +20,14 @@ use super::*;
 pub use polkadot_node_metrics::metrics::{self, prometheus, Metrics as MetricsTrait};
 
 use parity_util_mem::MemoryAllocationSnapshot;
+use parking_lot::Mutex;
+use prometheus::prometheus::{
+	core::{Collector, Desc},
+	proto,
+};
+use std::collections::BTreeMap;
+
+use crate::{gen::metered::TofHistogram, watchdog::Observation};
 
 /// Overseer Prometheus metrics.
 #[derive(Clone)]
@@ -28,18 +36,30 @@ struct MetricsInner {
 	deactivated_heads_total: prometheus::Counter<prometheus::U64>,
 	messages_relayed_total: prometheus::Counter<prometheus::U64>,
 
-	to_subsystem_bounded_tof: prometheus::HistogramVec,
+	to_subsystem_bounded_tof: TofHistograms,
 	to_subsystem_bounded_sent: prometheus::GaugeVec<prometheus::U64>,
 	to_subsystem_bounded_received: prometheus::GaugeVec<prometheus::U64>,
 	to_subsystem_bounded_blocked: prometheus::GaugeVec<prometheus::U64>,
 
-	to_subsystem_unbounded_tof: prometheus::HistogramVec,
+	to_subsystem_unbounded_tof: TofHistograms,
 	to_subsystem_unbounded_sent: prometheus::GaugeVec<prometheus::U64>,
 	to_subsystem_unbounded_received: prometheus::GaugeVec<prometheus::U64>,
 
+	to_subsystem_priority_tof: TofHistograms,
+	to_subsystem_priority_sent: prometheus::GaugeVec<prometheus::U64>,
+	to_subsystem_priority_received: prometheus::GaugeVec<prometheus::U64>,
+	to_subsystem_priority_blocked: prometheus::GaugeVec<prometheus::U64>,
+
 	signals_sent: prometheus::GaugeVec<prometheus::U64>,
 	signals_received: prometheus::GaugeVec<prometheus::U64>,
 
+	subsystem_message_stall_seconds: prometheus::GaugeVec,
+	subsystem_signal_lag: prometheus::GaugeVec<prometheus::U64>,
+	subsystem_stalls_total: prometheus::CounterVec<prometheus::U64>,
+
+	subsystem_restarts: prometheus::GaugeVec<prometheus::U64>,
+	subsystem_degraded: prometheus::GaugeVec<prometheus::U64>,
+
 	memory_stats_resident: prometheus::Gauge<prometheus::U64>,
 	memory_stats_allocated: prometheus::Gauge<prometheus::U64>,
 }
@@ -74,6 +94,24 @@ impl Metrics {
 		}
 	}
 
+	pub(crate) fn on_watchdog_observation(&self, name: &'static str, observation: &Observation) {
+		if let Some(metrics) = &self.0 {
+			metrics
+				.subsystem_message_stall_seconds
+				.with_label_values(&[name])
+				.set(observation.since_message_progress.as_secs_f64());
+
+			metrics
+				.subsystem_signal_lag
+				.with_label_values(&[name])
+				.set(observation.signal_lag as u64);
+
+			for kind in &observation.new_stalls {
+				metrics.subsystem_stalls_total.with_label_values(&[name, kind.as_str()]).inc();
+			}
+		}
+	}
+
 	pub(crate) fn channel_metrics_snapshot(
 		&self,
 		collection: impl IntoIterator<Item = (&'static str, SubsystemMeterReadouts)>,
@@ -107,6 +145,21 @@ impl Metrics {
 						.with_label_values(&[name])
 						.set(readouts.unbounded.received as u64);
 
+					metrics
+						.to_subsystem_priority_sent
+						.with_label_values(&[name])
+						.set(readouts.priority.sent as u64);
+
+					metrics
+						.to_subsystem_priority_received
+						.with_label_values(&[name])
+						.set(readouts.priority.received as u64);
+
+					metrics
+						.to_subsystem_priority_blocked
+						.with_label_values(&[name])
+						.set(readouts.priority.blocked as u64);
+
 					metrics
 						.signals_sent
 						.with_label_values(&[name])
@@ -117,16 +170,19 @@ impl Metrics {
 						.with_label_values(&[name])
 						.set(readouts.signals.received as u64);
 
-					let hist_bounded = metrics.to_subsystem_bounded_tof.with_label_values(&[name]);
-					for tof in readouts.bounded.tof {
-						hist_bounded.observe(tof.as_f64());
-					}
+					metrics
+						.subsystem_restarts
+						.with_label_values(&[name])
+						.set(readouts.supervision.restarts as u64);
+
+					metrics
+						.subsystem_degraded
+						.with_label_values(&[name])
+						.set(readouts.supervision.degraded as u64);
 
-					let hist_unbounded =
-						metrics.to_subsystem_unbounded_tof.with_label_values(&[name]);
-					for tof in readouts.unbounded.tof {
-						hist_unbounded.observe(tof.as_f64());
-					}
+					metrics.to_subsystem_bounded_tof.set(name, readouts.bounded.tof_histogram);
+					metrics.to_subsystem_unbounded_tof.set(name, readouts.unbounded.tof_histogram);
+					metrics.to_subsystem_priority_tof.set(name, readouts.priority.tof_histogram);
 				});
 		}
 	}
@@ -157,12 +213,9 @@ impl MetricsTrait for Metrics {
 				registry,
 			)?,
 			to_subsystem_bounded_tof: prometheus::register(
-				prometheus::HistogramVec::new(
-					prometheus::HistogramOpts::new(
-						"polkadot_parachain_subsystem_bounded_tof",
-						"Duration spent in a particular channel from entrance to removal",
-					),
-					&["subsystem_name"],
+				TofHistograms::new(
+					"polkadot_parachain_subsystem_bounded_tof",
+					"Duration spent in a particular channel from entrance to removal",
 				)?,
 				registry,
 			)?,
@@ -197,12 +250,9 @@ impl MetricsTrait for Metrics {
 				registry,
 			)?,
 			to_subsystem_unbounded_tof: prometheus::register(
-				prometheus::HistogramVec::new(
-					prometheus::HistogramOpts::new(
-						"polkadot_parachain_subsystem_unbounded_tof",
-						"Duration spent in a particular channel from entrance to removal",
-					),
-					&["subsystem_name"],
+				TofHistograms::new(
+					"polkadot_parachain_subsystem_unbounded_tof",
+					"Duration spent in a particular channel from entrance to removal",
 				)?,
 				registry,
 			)?,
@@ -226,6 +276,43 @@ impl MetricsTrait for Metrics {
 				)?,
 				registry,
 			)?,
+			to_subsystem_priority_tof: prometheus::register(
+				TofHistograms::new(
+					"polkadot_parachain_subsystem_priority_tof",
+					"Duration spent in a particular channel from entrance to removal",
+				)?,
+				registry,
+			)?,
+			to_subsystem_priority_sent: prometheus::register(
+				prometheus::GaugeVec::<prometheus::U64>::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_priority_sent",
+						"Number of elements sent to subsystems' high priority queues",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
+			to_subsystem_priority_received: prometheus::register(
+				prometheus::GaugeVec::<prometheus::U64>::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_priority_received",
+						"Number of elements received by subsystems' high priority queues",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
+			to_subsystem_priority_blocked: prometheus::register(
+				prometheus::GaugeVec::<prometheus::U64>::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_priority_blocked",
+						"Number of times senders blocked while sending high priority messages to a subsystem",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
 			signals_sent: prometheus::register(
 				prometheus::GaugeVec::<prometheus::U64>::new(
 					prometheus::Opts::new(
@@ -246,6 +333,56 @@ impl MetricsTrait for Metrics {
 				)?,
 				registry,
 			)?,
+			subsystem_message_stall_seconds: prometheus::register(
+				prometheus::GaugeVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_message_stall_seconds",
+						"Time since a subsystem last received a message while it had messages waiting",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
+			subsystem_signal_lag: prometheus::register(
+				prometheus::GaugeVec::<prometheus::U64>::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_signal_lag",
+						"Number of signals sent to a subsystem, but not yet received by it",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
+			subsystem_stalls_total: prometheus::register(
+				prometheus::CounterVec::<prometheus::U64>::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_stalls_total",
+						"Number of times a subsystem stalled on its messages or signals",
+					),
+					&["subsystem_name", "kind"],
+				)?,
+				registry,
+			)?,
+			subsystem_restarts: prometheus::register(
+				prometheus::GaugeVec::<prometheus::U64>::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_restarts",
+						"Number of times a failed subsystem was restarted",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
+			subsystem_degraded: prometheus::register(
+				prometheus::GaugeVec::<prometheus::U64>::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_degraded",
+						"Whether a subsystem failed and the overseer continues without it",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
 
 			memory_stats_allocated: prometheus::register(
 				prometheus::Gauge::<prometheus::U64>::new(
@@ -271,3 +408,76 @@ impl fmt::Debug for Metrics {
 		f.write_str("Metrics {{...}}")
 	}
 }
+
+/// Exports the time of flight histograms of the channels' meters, per subsystem.
+///
+/// The meters account for every measured message, so the histograms are taken over
+/// as they are, instead of observing the sampled values of each snapshot.
+#[derive(Clone)]
+struct TofHistograms {
+	desc: Desc,
+	histograms: Arc<Mutex<BTreeMap<&'static str, TofHistogram>>>,
+}
+
+impl TofHistograms {
+	fn new(name: &str, help: &str) -> Result<Self, prometheus::PrometheusError> {
+		let desc = Desc::new(
+			name.to_owned(),
+			help.to_owned(),
+			vec!["subsystem_name".to_owned()],
+			HashMap::new(),
+		)?;
+		Ok(Self { desc, histograms: Default::default() })
+	}
+
+	fn set(&self, subsystem_name: &'static str, histogram: TofHistogram) {
+		self.histograms.lock().insert(subsystem_name, histogram);
+	}
+}
+
+impl Collector for TofHistograms {
+	fn desc(&self) -> Vec<&Desc> {
+		vec![&self.desc]
+	}
+
+	fn collect(&self) -> Vec<proto::MetricFamily> {
+		let metrics = self
+			.histograms
+			.lock()
+			.iter()
+			.map(|(subsystem_name, histogram)| {
+				let mut label = proto::LabelPair::default();
+				label.set_name("subsystem_name".to_owned());
+				label.set_value(subsystem_name.to_string());
+
+				let buckets = histogram
+					.buckets
+					.iter()
+					.map(|(upper_bound, cumulative_count)| {
+						let mut bucket = proto::Bucket::default();
+						bucket.set_upper_bound(*upper_bound);
+						bucket.set_cumulative_count(*cumulative_count);
+						bucket
+					})
+					.collect::<Vec<_>>();
+
+				let mut proto_histogram = proto::Histogram::default();
+				proto_histogram.set_sample_count(histogram.count);
+				proto_histogram.set_sample_sum(histogram.sum);
+				proto_histogram.set_bucket(buckets.into());
+
+				let mut metric = proto::Metric::default();
+				metric.set_label(vec![label].into());
+				metric.set_histogram(proto_histogram);
+				metric
+			})
+			.collect::<Vec<_>>();
+
+		let mut family = proto::MetricFamily::default();
+		family.set_name(self.desc.fq_name.clone());
+		family.set_help(self.desc.help.clone());
+		family.set_field_type(proto::MetricType::HISTOGRAM);
+		family.set_metric(metrics.into());
+		vec![family]
+	}
+}
@@ -0,0 +1,665 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Codecs for recording the inputs of the approval voting and dispute coordinator subsystems.
+//!
+//! Wrap the subsystems in a [`RecordingSubsystem`] with one of these codecs to record what
+//! they receive and the responses to the requests they send, and replay the recording with
+//! `replay_recording` of the subsystem test helpers.
+//!
+//! The response channels of received messages can't be recorded: decoded messages carry
+//! fresh channels, whose receivers are already dropped. The spans of activated leaves are
+//! decoded as disabled spans and dispute statements are trusted, as they have been checked
+//! when recorded. Errors of runtime API and chain API responses are replayed with their
+//! recorded description only.
+
+use std::{fmt, sync::Arc};
+
+use futures::channel::oneshot;
+use parity_scale_codec::{Decode, Encode};
+
+use polkadot_node_primitives::{
+	approval::{IndirectAssignmentCert, IndirectSignedApprovalVote},
+	SignedDisputeStatement,
+};
+use polkadot_node_subsystem_types::{
+	errors::{ChainApiError, RecoveryError, RuntimeApiError},
+	messages::{
+		ApprovalDistributionMessage, ApprovalVotingMessage, AvailabilityRecoveryMessage,
+		AvailabilityStoreMessage, BlockDescription, CandidateValidationMessage, ChainApiMessage,
+		ChainApiResponseChannel, ChainSelectionMessage, DisputeCoordinatorMessage,
+		DisputeDistributionMessage, ImportStatementsResult, PreCheckOutcome, RuntimeApiMessage,
+		RuntimeApiRequest, RuntimeApiSender, ValidationFailed,
+	},
+};
+use polkadot_primitives::v2::{
+	BlockNumber, CandidateHash, CandidateIndex, CandidateReceipt, DisputeStatement, Hash,
+	SessionIndex, ValidatorId, ValidatorIndex, ValidatorSignature,
+};
+
+use crate::{
+	jaeger, ActivatedLeaf, ActiveLeavesUpdate, AllMessages, ApprovalVotingOutgoingMessages,
+	DisputeCoordinatorOutgoingMessages, LeafStatus, OverseerSignal,
+};
+
+pub use orchestra::record::{
+	read_recording, RecordCodec, RecordKind, RecordedEntry, Recorder, RecordingSubsystem,
+	ResponseChannel, ResponseCodec, MAX_PAYLOAD_LEN,
+};
+
+#[derive(Encode, Decode)]
+enum RecordedSignal {
+	ActiveLeaves {
+		/// Hash, number and whether the leaf is fresh.
+		activated: Option<(Hash, BlockNumber, bool)>,
+		deactivated: Vec<Hash>,
+	},
+	BlockFinalized(Hash, BlockNumber),
+	Conclude,
+}
+
+fn encode_signal(signal: &OverseerSignal) -> Vec<u8> {
+	match signal {
+		OverseerSignal::ActiveLeaves(update) => RecordedSignal::ActiveLeaves {
+			activated: update
+				.activated
+				.as_ref()
+				.map(|leaf| (leaf.hash, leaf.number, leaf.status.is_fresh())),
+			deactivated: update.deactivated.to_vec(),
+		},
+		OverseerSignal::BlockFinalized(hash, number) =>
+			RecordedSignal::BlockFinalized(*hash, *number),
+		OverseerSignal::Conclude => RecordedSignal::Conclude,
+	}
+	.encode()
+}
+
+fn decode_signal(mut payload: &[u8]) -> Option<OverseerSignal> {
+	let signal = match RecordedSignal::decode(&mut payload).ok()? {
+		RecordedSignal::ActiveLeaves { activated, deactivated } =>
+			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
+				activated: activated.map(|(hash, number, fresh)| ActivatedLeaf {
+					hash,
+					number,
+					status: if fresh { LeafStatus::Fresh } else { LeafStatus::Stale },
+					span: Arc::new(jaeger::Span::Disabled),
+				}),
+				deactivated: deactivated.into_iter().collect(),
+			}),
+		RecordedSignal::BlockFinalized(hash, number) =>
+			OverseerSignal::BlockFinalized(hash, number),
+		RecordedSignal::Conclude => OverseerSignal::Conclude,
+	};
+	Some(signal)
+}
+
+fn dropped_sender<T>() -> oneshot::Sender<T> {
+	oneshot::channel().0
+}
+
+#[derive(Encode, Decode)]
+enum RecordedApprovalVotingMessage {
+	CheckAndImportAssignment(IndirectAssignmentCert, CandidateIndex),
+	CheckAndImportApproval(IndirectSignedApprovalVote),
+	CheckAndImportAssignments(Vec<(IndirectAssignmentCert, CandidateIndex)>),
+	CheckAndImportApprovals(Vec<IndirectSignedApprovalVote>),
+	ApprovedAncestor(Hash, BlockNumber),
+}
+
+/// Records the signals and messages of the approval voting subsystem, and the responses to
+/// its requests.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ApprovalVotingCodec;
+
+impl<M: RecordResponse> ResponseCodec<M> for ApprovalVotingCodec {
+	fn intercept_response(&self, message: &mut M) -> Option<ResponseChannel> {
+		message.intercept_response()
+	}
+}
+
+impl RecordCodec<ApprovalVotingMessage, OverseerSignal> for ApprovalVotingCodec {
+	fn encode_signal(&self, signal: &OverseerSignal) -> Option<Vec<u8>> {
+		Some(encode_signal(signal))
+	}
+
+	fn encode_message(&self, message: &ApprovalVotingMessage) -> Option<Vec<u8>> {
+		let recorded = match message {
+			ApprovalVotingMessage::CheckAndImportAssignment(cert, candidate_index, _) =>
+				RecordedApprovalVotingMessage::CheckAndImportAssignment(
+					cert.clone(),
+					*candidate_index,
+				),
+			ApprovalVotingMessage::CheckAndImportApproval(vote, _) =>
+				RecordedApprovalVotingMessage::CheckAndImportApproval(vote.clone()),
+			ApprovalVotingMessage::CheckAndImportAssignments(assignments, _) =>
+				RecordedApprovalVotingMessage::CheckAndImportAssignments(assignments.clone()),
+			ApprovalVotingMessage::CheckAndImportApprovals(votes, _) =>
+				RecordedApprovalVotingMessage::CheckAndImportApprovals(votes.clone()),
+			ApprovalVotingMessage::ApprovedAncestor(hash, number, _) =>
+				RecordedApprovalVotingMessage::ApprovedAncestor(*hash, *number),
+		};
+		Some(recorded.encode())
+	}
+
+	fn decode_signal(&self, payload: &[u8]) -> Option<OverseerSignal> {
+		decode_signal(payload)
+	}
+
+	fn decode_message(&self, mut payload: &[u8]) -> Option<ApprovalVotingMessage> {
+		let message = match RecordedApprovalVotingMessage::decode(&mut payload).ok()? {
+			RecordedApprovalVotingMessage::CheckAndImportAssignment(cert, candidate_index) =>
+				ApprovalVotingMessage::CheckAndImportAssignment(
+					cert,
+					candidate_index,
+					dropped_sender(),
+				),
+			RecordedApprovalVotingMessage::CheckAndImportApproval(vote) =>
+				ApprovalVotingMessage::CheckAndImportApproval(vote, dropped_sender()),
+			RecordedApprovalVotingMessage::CheckAndImportAssignments(assignments) =>
+				ApprovalVotingMessage::CheckAndImportAssignments(assignments, dropped_sender()),
+			RecordedApprovalVotingMessage::CheckAndImportApprovals(votes) =>
+				ApprovalVotingMessage::CheckAndImportApprovals(votes, dropped_sender()),
+			RecordedApprovalVotingMessage::ApprovedAncestor(hash, number) =>
+				ApprovalVotingMessage::ApprovedAncestor(hash, number, dropped_sender()),
+		};
+		Some(message)
+	}
+}
+
+#[derive(Encode, Decode)]
+struct RecordedDisputeStatement {
+	statement: DisputeStatement,
+	candidate_hash: CandidateHash,
+	validator_public: ValidatorId,
+	validator_signature: ValidatorSignature,
+	session_index: SessionIndex,
+}
+
+impl From<&SignedDisputeStatement> for RecordedDisputeStatement {
+	fn from(statement: &SignedDisputeStatement) -> Self {
+		RecordedDisputeStatement {
+			statement: statement.statement().clone(),
+			candidate_hash: *statement.candidate_hash(),
+			validator_public: statement.validator_public().clone(),
+			validator_signature: statement.validator_signature().clone(),
+			session_index: statement.session_index(),
+		}
+	}
+}
+
+impl From<RecordedDisputeStatement> for SignedDisputeStatement {
+	fn from(statement: RecordedDisputeStatement) -> Self {
+		SignedDisputeStatement::new_unchecked_from_trusted_source(
+			statement.statement,
+			statement.candidate_hash,
+			statement.session_index,
+			statement.validator_public,
+			statement.validator_signature,
+		)
+	}
+}
+
+#[derive(Encode, Decode)]
+enum RecordedDisputeCoordinatorMessage {
+	ImportStatements {
+		candidate_hash: CandidateHash,
+		candidate_receipt: CandidateReceipt,
+		session: SessionIndex,
+		statements: Vec<(RecordedDisputeStatement, ValidatorIndex)>,
+		/// Whether a confirmation was requested.
+		pending_confirmation: bool,
+	},
+	RecentDisputes,
+	ActiveDisputes,
+	QueryCandidateVotes(Vec<(SessionIndex, CandidateHash)>),
+	IssueLocalStatement(SessionIndex, CandidateHash, CandidateReceipt, bool),
+	DetermineUndisputedChain {
+		base: (BlockNumber, Hash),
+		/// Block hash, session and candidates of each block.
+		block_descriptions: Vec<(Hash, SessionIndex, Vec<CandidateHash>)>,
+	},
+}
+
+/// Records the signals and messages of the dispute coordinator subsystem, and the responses
+/// to its requests.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DisputeCoordinatorCodec;
+
+impl<M: RecordResponse> ResponseCodec<M> for DisputeCoordinatorCodec {
+	fn intercept_response(&self, message: &mut M) -> Option<ResponseChannel> {
+		message.intercept_response()
+	}
+}
+
+impl RecordCodec<DisputeCoordinatorMessage, OverseerSignal> for DisputeCoordinatorCodec {
+	fn encode_signal(&self, signal: &OverseerSignal) -> Option<Vec<u8>> {
+		Some(encode_signal(signal))
+	}
+
+	fn encode_message(&self, message: &DisputeCoordinatorMessage) -> Option<Vec<u8>> {
+		let recorded = match message {
+			DisputeCoordinatorMessage::ImportStatements {
+				candidate_hash,
+				candidate_receipt,
+				session,
+				statements,
+				pending_confirmation,
+			} => RecordedDisputeCoordinatorMessage::ImportStatements {
+				candidate_hash: *candidate_hash,
+				candidate_receipt: candidate_receipt.clone(),
+				session: *session,
+				statements: statements
+					.iter()
+					.map(|(statement, index)| (statement.into(), *index))
+					.collect(),
+				pending_confirmation: pending_confirmation.is_some(),
+			},
+			DisputeCoordinatorMessage::RecentDisputes(_) =>
+				RecordedDisputeCoordinatorMessage::RecentDisputes,
+			DisputeCoordinatorMessage::ActiveDisputes(_) =>
+				RecordedDisputeCoordinatorMessage::ActiveDisputes,
+			DisputeCoordinatorMessage::QueryCandidateVotes(query, _) =>
+				RecordedDisputeCoordinatorMessage::QueryCandidateVotes(query.clone()),
+			DisputeCoordinatorMessage::IssueLocalStatement(
+				session,
+				candidate_hash,
+				candidate_receipt,
+				valid,
+			) => RecordedDisputeCoordinatorMessage::IssueLocalStatement(
+				*session,
+				*candidate_hash,
+				candidate_receipt.clone(),
+				*valid,
+			),
+			DisputeCoordinatorMessage::DetermineUndisputedChain {
+				base,
+				block_descriptions,
+				tx: _,
+			} => RecordedDisputeCoordinatorMessage::DetermineUndisputedChain {
+				base: *base,
+				block_descriptions: block_descriptions
+					.iter()
+					.map(|block| (block.block_hash, block.session, block.candidates.clone()))
+					.collect(),
+			},
+		};
+		Some(recorded.encode())
+	}
+
+	fn decode_signal(&self, payload: &[u8]) -> Option<OverseerSignal> {
+		decode_signal(payload)
+	}
+
+	fn decode_message(&self, mut payload: &[u8]) -> Option<DisputeCoordinatorMessage> {
+		let message = match RecordedDisputeCoordinatorMessage::decode(&mut payload).ok()? {
+			RecordedDisputeCoordinatorMessage::ImportStatements {
+				candidate_hash,
+				candidate_receipt,
+				session,
+				statements,
+				pending_confirmation,
+			} => DisputeCoordinatorMessage::ImportStatements {
+				candidate_hash,
+				candidate_receipt,
+				session,
+				statements: statements
+					.into_iter()
+					.map(|(statement, index)| (statement.into(), index))
+					.collect(),
+				pending_confirmation: pending_confirmation.then(dropped_sender),
+			},
+			RecordedDisputeCoordinatorMessage::RecentDisputes =>
+				DisputeCoordinatorMessage::RecentDisputes(dropped_sender()),
+			RecordedDisputeCoordinatorMessage::ActiveDisputes =>
+				DisputeCoordinatorMessage::ActiveDisputes(dropped_sender()),
+			RecordedDisputeCoordinatorMessage::QueryCandidateVotes(query) =>
+				DisputeCoordinatorMessage::QueryCandidateVotes(query, dropped_sender()),
+			RecordedDisputeCoordinatorMessage::IssueLocalStatement(
+				session,
+				candidate_hash,
+				candidate_receipt,
+				valid,
+			) => DisputeCoordinatorMessage::IssueLocalStatement(
+				session,
+				candidate_hash,
+				candidate_receipt,
+				valid,
+			),
+			RecordedDisputeCoordinatorMessage::DetermineUndisputedChain {
+				base,
+				block_descriptions,
+			} => DisputeCoordinatorMessage::DetermineUndisputedChain {
+				base,
+				block_descriptions: block_descriptions
+					.into_iter()
+					.map(|(block_hash, session, candidates)| BlockDescription {
+						block_hash,
+						session,
+						candidates,
+					})
+					.collect(),
+				tx: dropped_sender(),
+			},
+		};
+		Some(message)
+	}
+}
+
+/// Requests whose responses can be recorded.
+pub trait RecordResponse: Send + 'static {
+	/// Take over the response channel, if the message is a request with a recordable response.
+	fn intercept_response(&mut self) -> Option<ResponseChannel>;
+}
+
+/// The name of the runtime API in errors of replayed responses.
+const REPLAYED_RUNTIME_API: &str = "replayed";
+
+/// The error of a replayed response, as described when recorded.
+#[derive(Debug)]
+struct ReplayedError(String);
+
+impl fmt::Display for ReplayedError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
+
+impl std::error::Error for ReplayedError {}
+
+#[derive(Encode, Decode)]
+enum RecordedRuntimeApiError {
+	Execution(String),
+	NotSupported,
+}
+
+fn encode_response<T: Encode>(response: &T) -> Option<Vec<u8>> {
+	Some(response.encode())
+}
+
+fn decode_response<T: Decode>(mut payload: &[u8]) -> Option<T> {
+	T::decode(&mut payload).ok()
+}
+
+fn intercept<T: Encode + Decode + Send + 'static>(
+	tx: &mut oneshot::Sender<T>,
+) -> Option<ResponseChannel> {
+	Some(ResponseChannel::intercept(tx, encode_response::<T>, decode_response::<T>))
+}
+
+fn intercept_runtime_api<T: Encode + Decode + Send + 'static>(
+	tx: &mut RuntimeApiSender<T>,
+) -> Option<ResponseChannel> {
+	Some(ResponseChannel::intercept(
+		tx,
+		|response| {
+			let response = response.as_ref().map_err(|err| match err {
+				RuntimeApiError::Execution { .. } =>
+					RecordedRuntimeApiError::Execution(err.to_string()),
+				RuntimeApiError::NotSupported { .. } => RecordedRuntimeApiError::NotSupported,
+			});
+			Some(response.encode())
+		},
+		|mut payload| {
+			let response = Result::<T, RecordedRuntimeApiError>::decode(&mut payload).ok()?;
+			Some(response.map_err(|err| match err {
+				RecordedRuntimeApiError::Execution(description) => RuntimeApiError::Execution {
+					runtime_api_name: REPLAYED_RUNTIME_API,
+					source: Arc::new(ReplayedError(description)),
+				},
+				RecordedRuntimeApiError::NotSupported =>
+					RuntimeApiError::NotSupported { runtime_api_name: REPLAYED_RUNTIME_API },
+			}))
+		},
+	))
+}
+
+fn intercept_chain_api<T: Encode + Decode + Send + 'static>(
+	tx: &mut ChainApiResponseChannel<T>,
+) -> Option<ResponseChannel> {
+	Some(ResponseChannel::intercept(
+		tx,
+		|response| Some(response.as_ref().map_err(|err| err.to_string()).encode()),
+		|mut payload| {
+			let response = Result::<T, String>::decode(&mut payload).ok()?;
+			Some(response.map_err(ChainApiError::from))
+		},
+	))
+}
+
+impl RecordResponse for () {
+	fn intercept_response(&mut self) -> Option<ResponseChannel> {
+		None
+	}
+}
+
+impl RecordResponse for RuntimeApiMessage {
+	fn intercept_response(&mut self) -> Option<ResponseChannel> {
+		let RuntimeApiMessage::Request(_, request) = self;
+		match request {
+			RuntimeApiRequest::Version(tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::Authorities(tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::Validators(tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::ValidatorGroups(tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::AvailabilityCores(tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::PersistedValidationData(_, _, tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::AssumedValidationData(_, _, tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::CheckValidationOutputs(_, _, tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::SessionIndexForChild(tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::ValidationCode(_, _, tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::ValidationCodeByHash(_, tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::CandidatePendingAvailability(_, tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::CandidateEvents(tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::SessionInfo(_, tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::DmqContents(_, tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::InboundHrmpChannelsContents(_, tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::CurrentBabeEpoch(tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::FetchOnChainVotes(tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::SubmitPvfCheckStatement(_, _, tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::PvfsRequirePrecheck(tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::ValidationCodeHash(_, _, tx) => intercept_runtime_api(tx),
+			RuntimeApiRequest::StagingDisputes(tx) => intercept_runtime_api(tx),
+		}
+	}
+}
+
+impl RecordResponse for ChainApiMessage {
+	fn intercept_response(&mut self) -> Option<ResponseChannel> {
+		match self {
+			ChainApiMessage::BlockNumber(_, tx) => intercept_chain_api(tx),
+			ChainApiMessage::BlockHeader(_, tx) => intercept_chain_api(tx),
+			ChainApiMessage::BlockWeight(_, tx) => intercept_chain_api(tx),
+			ChainApiMessage::FinalizedBlockHash(_, tx) => intercept_chain_api(tx),
+			ChainApiMessage::FinalizedBlockNumber(tx) => intercept_chain_api(tx),
+			ChainApiMessage::Ancestors { response_channel, .. } =>
+				intercept_chain_api(response_channel),
+		}
+	}
+}
+
+impl RecordResponse for ChainSelectionMessage {
+	fn intercept_response(&mut self) -> Option<ResponseChannel> {
+		match self {
+			ChainSelectionMessage::Approved(_) => None,
+			ChainSelectionMessage::Leaves(tx) => intercept(tx),
+			ChainSelectionMessage::BestLeafContaining(_, tx) => intercept(tx),
+		}
+	}
+}
+
+impl RecordResponse for AvailabilityStoreMessage {
+	fn intercept_response(&mut self) -> Option<ResponseChannel> {
+		match self {
+			AvailabilityStoreMessage::QueryAvailableData(_, tx) => intercept(tx),
+			AvailabilityStoreMessage::QueryArchivedAvailableData(_, tx) => intercept(tx),
+			AvailabilityStoreMessage::QueryDataAvailability(_, tx) => intercept(tx),
+			AvailabilityStoreMessage::QueryChunk(_, _, tx) => intercept(tx),
+			AvailabilityStoreMessage::QueryAllChunks(_, tx) => intercept(tx),
+			AvailabilityStoreMessage::QueryChunkAvailability(_, _, tx) => intercept(tx),
+			AvailabilityStoreMessage::StoreChunk { tx, .. } => intercept(tx),
+			AvailabilityStoreMessage::StoreAvailableData { tx, .. } => intercept(tx),
+		}
+	}
+}
+
+impl RecordResponse for CandidateValidationMessage {
+	fn intercept_response(&mut self) -> Option<ResponseChannel> {
+		let tx = match self {
+			CandidateValidationMessage::ValidateFromChainState(_, _, _, tx) => tx,
+			CandidateValidationMessage::ValidateFromExhaustive(_, _, _, _, _, tx) => tx,
+			CandidateValidationMessage::PreCheck(_, _, tx) =>
+				return Some(ResponseChannel::intercept(
+					tx,
+					|outcome| {
+						let outcome: u8 = match outcome {
+							PreCheckOutcome::Valid => 0,
+							PreCheckOutcome::Invalid => 1,
+							PreCheckOutcome::Failed => 2,
+						};
+						Some(vec![outcome])
+					},
+					|payload| match payload {
+						[0] => Some(PreCheckOutcome::Valid),
+						[1] => Some(PreCheckOutcome::Invalid),
+						[2] => Some(PreCheckOutcome::Failed),
+						_ => None,
+					},
+				)),
+		};
+		Some(ResponseChannel::intercept(
+			tx,
+			|response| Some(response.as_ref().map_err(|err| &err.0).encode()),
+			|mut payload| {
+				let response = Result::<_, String>::decode(&mut payload).ok()?;
+				Some(response.map_err(ValidationFailed))
+			},
+		))
+	}
+}
+
+impl RecordResponse for AvailabilityRecoveryMessage {
+	fn intercept_response(&mut self) -> Option<ResponseChannel> {
+		let AvailabilityRecoveryMessage::RecoverAvailableData(_, _, _, tx) = self;
+		Some(ResponseChannel::intercept(
+			tx,
+			|response| {
+				let response = response.as_ref().map_err(|err| match err {
+					RecoveryError::Invalid => 0u8,
+					RecoveryError::Unavailable => 1u8,
+				});
+				Some(response.encode())
+			},
+			|mut payload| match Result::<_, u8>::decode(&mut payload).ok()? {
+				Ok(data) => Some(Ok(data)),
+				Err(0) => Some(Err(RecoveryError::Invalid)),
+				Err(1) => Some(Err(RecoveryError::Unavailable)),
+				Err(_) => None,
+			},
+		))
+	}
+}
+
+impl RecordResponse for DisputeCoordinatorMessage {
+	fn intercept_response(&mut self) -> Option<ResponseChannel> {
+		match self {
+			DisputeCoordinatorMessage::ImportStatements { pending_confirmation, .. } =>
+				Some(ResponseChannel::intercept(
+					pending_confirmation.as_mut()?,
+					|result| {
+						let result: u8 = match result {
+							ImportStatementsResult::InvalidImport => 0,
+							ImportStatementsResult::ValidImport => 1,
+						};
+						Some(vec![result])
+					},
+					|payload| match payload {
+						[0] => Some(ImportStatementsResult::InvalidImport),
+						[1] => Some(ImportStatementsResult::ValidImport),
+						_ => None,
+					},
+				)),
+			DisputeCoordinatorMessage::RecentDisputes(tx) => intercept(tx),
+			DisputeCoordinatorMessage::ActiveDisputes(tx) => intercept(tx),
+			DisputeCoordinatorMessage::QueryCandidateVotes(_, tx) => intercept(tx),
+			DisputeCoordinatorMessage::IssueLocalStatement(..) => None,
+			DisputeCoordinatorMessage::DetermineUndisputedChain { tx, .. } => intercept(tx),
+		}
+	}
+}
+
+impl RecordResponse for ApprovalDistributionMessage {
+	fn intercept_response(&mut self) -> Option<ResponseChannel> {
+		None
+	}
+}
+
+impl RecordResponse for DisputeDistributionMessage {
+	fn intercept_response(&mut self) -> Option<ResponseChannel> {
+		None
+	}
+}
+
+impl RecordResponse for ApprovalVotingOutgoingMessages {
+	fn intercept_response(&mut self) -> Option<ResponseChannel> {
+		match self {
+			ApprovalVotingOutgoingMessages::RuntimeApiMessage(msg) => msg.intercept_response(),
+			ApprovalVotingOutgoingMessages::ChainApiMessage(msg) => msg.intercept_response(),
+			ApprovalVotingOutgoingMessages::ChainSelectionMessage(msg) => msg.intercept_response(),
+			ApprovalVotingOutgoingMessages::DisputeCoordinatorMessage(msg) =>
+				msg.intercept_response(),
+			ApprovalVotingOutgoingMessages::AvailabilityRecoveryMessage(msg) =>
+				msg.intercept_response(),
+			ApprovalVotingOutgoingMessages::ApprovalDistributionMessage(msg) =>
+				msg.intercept_response(),
+			ApprovalVotingOutgoingMessages::CandidateValidationMessage(msg) =>
+				msg.intercept_response(),
+			ApprovalVotingOutgoingMessages::Empty => None,
+		}
+	}
+}
+
+impl RecordResponse for DisputeCoordinatorOutgoingMessages {
+	fn intercept_response(&mut self) -> Option<ResponseChannel> {
+		match self {
+			DisputeCoordinatorOutgoingMessages::RuntimeApiMessage(msg) => msg.intercept_response(),
+			DisputeCoordinatorOutgoingMessages::ChainApiMessage(msg) => msg.intercept_response(),
+			DisputeCoordinatorOutgoingMessages::DisputeDistributionMessage(msg) =>
+				msg.intercept_response(),
+			DisputeCoordinatorOutgoingMessages::CandidateValidationMessage(msg) =>
+				msg.intercept_response(),
+			DisputeCoordinatorOutgoingMessages::AvailabilityStoreMessage(msg) =>
+				msg.intercept_response(),
+			DisputeCoordinatorOutgoingMessages::AvailabilityRecoveryMessage(msg) =>
+				msg.intercept_response(),
+			DisputeCoordinatorOutgoingMessages::Empty => None,
+		}
+	}
+}
+
+/// For replaying, where all messages sent by the subsystem are collected as `AllMessages`.
+impl RecordResponse for AllMessages {
+	fn intercept_response(&mut self) -> Option<ResponseChannel> {
+		match self {
+			AllMessages::RuntimeApi(msg) => msg.intercept_response(),
+			AllMessages::ChainApi(msg) => msg.intercept_response(),
+			AllMessages::ChainSelection(msg) => msg.intercept_response(),
+			AllMessages::AvailabilityStore(msg) => msg.intercept_response(),
+			AllMessages::CandidateValidation(msg) => msg.intercept_response(),
+			AllMessages::AvailabilityRecovery(msg) => msg.intercept_response(),
+			AllMessages::DisputeCoordinator(msg) => msg.intercept_response(),
+			_ => None,
+		}
+	}
+}
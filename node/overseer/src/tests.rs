@@ -14,14 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
-use futures::{executor, pending, pin_mut, poll, select, stream, FutureExt};
-use std::{collections::HashMap, sync::atomic, task::Poll};
+use futures::{executor, pending, pin_mut, poll, select, FutureExt};
+use std::{collections::HashMap, path::Path, sync::atomic, task::Poll, time::Instant};
 
 use ::test_helpers::{dummy_candidate_descriptor, dummy_candidate_receipt, dummy_hash};
 use polkadot_node_network_protocol::{PeerId, UnifiedReputationChange};
 use polkadot_node_primitives::{
 	BlockData, CollationGenerationConfig, CollationResult, DisputeMessage, InvalidDisputeVote, PoV,
-	UncheckedDisputeMessage, ValidDisputeVote,
+	SignedDisputeStatement, UncheckedDisputeMessage, ValidDisputeVote,
 };
 use polkadot_node_subsystem_types::{
 	jaeger,
@@ -29,14 +29,16 @@ use polkadot_node_subsystem_types::{
 	ActivatedLeaf, LeafStatus,
 };
 use polkadot_primitives::v2::{
-	CandidateHash, CandidateReceipt, CollatorPair, InvalidDisputeStatementKind,
+	CandidateHash, CandidateReceipt, CollatorPair, DisputeStatement, InvalidDisputeStatementKind,
 	ValidDisputeStatementKind, ValidatorIndex,
 };
 
 use crate::{
 	self as overseer,
 	dummy::{dummy_overseer_builder, one_for_all_overseer_builder},
-	gen::Delay,
+	gen::{supervision::SubsystemIncoming, Delay},
+	graph_export::{GraphExporter, GraphFormat},
+	watchdog::{StallKind, StallWatchdog},
 	HeadSupportsParachains,
 };
 use metered;
@@ -286,6 +288,67 @@ fn extract_metrics(registry: &prometheus::Registry) -> HashMap<&'static str, u64
 	result
 }
 
+#[test]
+fn channel_tof_histograms_are_exported() {
+	let registry = prometheus::Registry::new();
+	let metrics = OverseerMetrics::try_register(&registry).unwrap();
+
+	let mut readouts = watchdog_readouts((4, 4), (0, 0));
+	readouts.bounded.tof_histogram =
+		metered::TofHistogram { buckets: vec![(0.001, 1), (0.005, 3)], count: 4, sum: 0.5 };
+	metrics.channel_metrics_snapshot(vec![("candidate-validation", readouts)]);
+
+	let gather = registry.gather();
+	let family = gather
+		.iter()
+		.find(|mf| mf.get_name() == "polkadot_parachain_subsystem_bounded_tof")
+		.expect("Must contain the bounded time of flight histogram");
+	let metric = &family.get_metric()[0];
+	assert_eq!(metric.get_label()[0].get_value(), "candidate-validation");
+
+	let histogram = metric.get_histogram();
+	assert_eq!(histogram.get_sample_count(), 4);
+	assert_eq!(histogram.get_sample_sum(), 0.5);
+	assert_eq!(
+		histogram
+			.get_bucket()
+			.iter()
+			.map(|bucket| (bucket.get_upper_bound(), bucket.get_cumulative_count()))
+			.collect::<Vec<_>>(),
+		vec![(0.001, 1), (0.005, 3)],
+	);
+}
+
+#[test]
+fn notified_leaves_are_restart_signals() {
+	let leaf = |n: u8| ActivatedLeaf {
+		hash: [n; 32].into(),
+		number: n as BlockNumber,
+		status: LeafStatus::Fresh,
+		span: Arc::new(jaeger::Span::Disabled),
+	};
+
+	let notified_leaves = NotifiedLeaves::default();
+	notified_leaves.note_update(&ActiveLeavesUpdate::start_work(leaf(2)));
+	notified_leaves.note_update(&ActiveLeavesUpdate::start_work(leaf(1)));
+	let mut update = ActiveLeavesUpdate::start_work(leaf(3));
+	update.deactivated.push([2; 32].into());
+	notified_leaves.note_update(&update);
+
+	let activated = notified_leaves
+		.restart_signals()
+		.into_iter()
+		.map(|signal| match signal {
+			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
+				activated: Some(leaf),
+				deactivated,
+			}) if deactivated.is_empty() => leaf.hash,
+			other => panic!("Unexpected signal: {:?}", other),
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(activated, vec![Hash::from([1; 32]), Hash::from([3; 32])]);
+}
+
 // Spawn a subsystem that immediately exits.
 //
 // Should immediately conclude the overseer itself.
@@ -1075,6 +1138,27 @@ fn context_holds_onto_message_until_enough_signals_received() {
 	let (dispute_distribution_unbounded_tx, _) = metered::unbounded();
 	let (chain_selection_unbounded_tx, _) = metered::unbounded();
 	let (pvf_checker_unbounded_tx, _) = metered::unbounded();
+	let (candidate_validation_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (candidate_backing_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (statement_distribution_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (availability_distribution_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (availability_recovery_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (bitfield_signing_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (bitfield_distribution_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (provisioner_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (runtime_api_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (availability_store_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (network_bridge_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (chain_api_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (collator_protocol_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (collation_generation_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (approval_distribution_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (approval_voting_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (gossip_support_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (dispute_coordinator_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (dispute_distribution_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (chain_selection_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
+	let (pvf_checker_priority_tx, _) = metered::channel(CHANNEL_CAPACITY);
 
 	let channels_out = ChannelsOut {
 		candidate_validation: candidate_validation_bounded_tx.clone(),
@@ -1120,22 +1204,66 @@ fn context_holds_onto_message_until_enough_signals_received() {
 		dispute_distribution_unbounded: dispute_distribution_unbounded_tx.clone(),
 		chain_selection_unbounded: chain_selection_unbounded_tx.clone(),
 		pvf_checker_unbounded: pvf_checker_unbounded_tx.clone(),
+		candidate_validation_priority: candidate_validation_priority_tx.clone(),
+		candidate_backing_priority: candidate_backing_priority_tx.clone(),
+		statement_distribution_priority: statement_distribution_priority_tx.clone(),
+		availability_distribution_priority: availability_distribution_priority_tx.clone(),
+		availability_recovery_priority: availability_recovery_priority_tx.clone(),
+		bitfield_signing_priority: bitfield_signing_priority_tx.clone(),
+		bitfield_distribution_priority: bitfield_distribution_priority_tx.clone(),
+		provisioner_priority: provisioner_priority_tx.clone(),
+		runtime_api_priority: runtime_api_priority_tx.clone(),
+		availability_store_priority: availability_store_priority_tx.clone(),
+		network_bridge_priority: network_bridge_priority_tx.clone(),
+		chain_api_priority: chain_api_priority_tx.clone(),
+		collator_protocol_priority: collator_protocol_priority_tx.clone(),
+		collation_generation_priority: collation_generation_priority_tx.clone(),
+		approval_distribution_priority: approval_distribution_priority_tx.clone(),
+		approval_voting_priority: approval_voting_priority_tx.clone(),
+		gossip_support_priority: gossip_support_priority_tx.clone(),
+		dispute_coordinator_priority: dispute_coordinator_priority_tx.clone(),
+		dispute_distribution_priority: dispute_distribution_priority_tx.clone(),
+		chain_selection_priority: chain_selection_priority_tx.clone(),
+		pvf_checker_priority: pvf_checker_priority_tx.clone(),
+		candidate_validation_pending_kinds: Default::default(),
+		candidate_backing_pending_kinds: Default::default(),
+		statement_distribution_pending_kinds: Default::default(),
+		availability_distribution_pending_kinds: Default::default(),
+		availability_recovery_pending_kinds: Default::default(),
+		bitfield_signing_pending_kinds: Default::default(),
+		bitfield_distribution_pending_kinds: Default::default(),
+		provisioner_pending_kinds: Default::default(),
+		runtime_api_pending_kinds: Default::default(),
+		availability_store_pending_kinds: Default::default(),
+		network_bridge_pending_kinds: Default::default(),
+		chain_api_pending_kinds: Default::default(),
+		collator_protocol_pending_kinds: Default::default(),
+		collation_generation_pending_kinds: Default::default(),
+		approval_distribution_pending_kinds: Default::default(),
+		approval_voting_pending_kinds: Default::default(),
+		gossip_support_pending_kinds: Default::default(),
+		dispute_coordinator_pending_kinds: Default::default(),
+		dispute_distribution_pending_kinds: Default::default(),
+		chain_selection_pending_kinds: Default::default(),
+		pvf_checker_pending_kinds: Default::default(),
 	};
 
 	let (mut signal_tx, signal_rx) = metered::channel(CHANNEL_CAPACITY);
 	let (mut bounded_tx, bounded_rx) = metered::channel(CHANNEL_CAPACITY);
 	let (unbounded_tx, unbounded_rx) = metered::unbounded();
+	let (_priority_tx, priority_rx) = metered::channel(CHANNEL_CAPACITY);
 	let (to_overseer_tx, _to_overseer_rx) = metered::unbounded();
 
-	let mut ctx = OverseerSubsystemContext::new(
+	let (incoming, _reclaim) = SubsystemIncoming::new(
 		signal_rx,
-		stream::select_with_strategy(
-			bounded_rx,
-			unbounded_rx,
-			orchestra::select_message_channel_strategy,
-		),
+		SubsystemIncomingMessages::new(priority_rx, bounded_rx, unbounded_rx),
+	)
+	.lend();
+	let mut ctx = OverseerSubsystemContext::new(
+		incoming,
 		channels_out,
 		to_overseer_tx,
+		orchestra::graph::EdgeCounters::new(&CONNECTION_GRAPH),
 		"test",
 	);
 
@@ -1170,3 +1298,183 @@ fn context_holds_onto_message_until_enough_signals_received() {
 
 	futures::executor::block_on(test_fut);
 }
+
+fn watchdog_readouts(messages: (usize, usize), signals: (usize, usize)) -> SubsystemMeterReadouts {
+	SubsystemMeterReadouts {
+		bounded: metered::Readout { sent: messages.0, received: messages.1, ..Default::default() },
+		unbounded: Default::default(),
+		signals: metered::Readout { sent: signals.0, received: signals.1, ..Default::default() },
+		priority: Default::default(),
+		supervision: Default::default(),
+		pending_kinds: Vec::new(),
+	}
+}
+
+#[test]
+fn stall_watchdog_reports_stalls_once_and_recovery() {
+	let mut watchdog = StallWatchdog::new(StallWatchdogConfig {
+		message_threshold: Duration::from_secs(5),
+		signal_threshold: Duration::from_secs(2),
+		dump_pending: true,
+	});
+	let start = Instant::now();
+	let at = |secs| start + Duration::from_secs(secs);
+
+	// Nothing waiting, so no stall however long the subsystem doesn't receive.
+	let observation = watchdog.observe("subsystem", &watchdog_readouts((3, 3), (1, 1)), at(0));
+	assert_eq!(observation.pending_messages, 0);
+	assert!(observation.new_stalls.is_empty());
+
+	let observation = watchdog.observe("subsystem", &watchdog_readouts((5, 3), (1, 1)), at(3));
+	assert_eq!(observation.since_message_progress, Duration::from_secs(3));
+	assert!(observation.new_stalls.is_empty());
+
+	// Neither the waiting messages nor the new signal were received in time.
+	let observation = watchdog.observe("subsystem", &watchdog_readouts((5, 3), (2, 1)), at(9));
+	assert_eq!(observation.since_message_progress, Duration::from_secs(9));
+	assert_eq!(observation.signal_lag, 1);
+	assert_eq!(observation.since_signal_progress, Duration::from_secs(6));
+	assert_eq!(observation.new_stalls, vec![StallKind::Messages, StallKind::Signals]);
+
+	// Ongoing stalls are reported only once.
+	let observation = watchdog.observe("subsystem", &watchdog_readouts((5, 3), (2, 1)), at(10));
+	assert!(observation.new_stalls.is_empty());
+
+	// Receiving anything counts as progress.
+	let observation = watchdog.observe("subsystem", &watchdog_readouts((5, 4), (2, 2)), at(11));
+	assert_eq!(observation.since_message_progress, Duration::default());
+	assert_eq!(observation.signal_lag, 0);
+	assert!(observation.new_stalls.is_empty());
+
+	let observation = watchdog.observe("subsystem", &watchdog_readouts((5, 4), (2, 2)), at(16));
+	assert_eq!(observation.new_stalls, vec![StallKind::Messages]);
+}
+
+#[test]
+fn graph_export_picks_format_and_respects_interval() {
+	assert_eq!(GraphFormat::from_path(Path::new("graph.json")), GraphFormat::Json);
+	assert_eq!(GraphFormat::from_path(Path::new("graph.JSON")), GraphFormat::Json);
+	assert_eq!(GraphFormat::from_path(Path::new("graph.dot")), GraphFormat::Dot);
+	assert_eq!(GraphFormat::from_path(Path::new("graph")), GraphFormat::Dot);
+
+	let path = std::env::temp_dir()
+		.join(format!("overseer-graph-export-test-{}.json", std::process::id()));
+	let mut exporter = GraphExporter::new(GraphExportConfig {
+		path: path.clone(),
+		interval: Duration::from_secs(60),
+	});
+	let tracker = orchestra::graph::ConnectionGraphTracker::new(
+		&CONNECTION_GRAPH,
+		orchestra::graph::EdgeCounters::new(&CONNECTION_GRAPH),
+		Vec::new(),
+	);
+
+	let start = Instant::now();
+	assert!(exporter.is_due(start));
+	exporter.export(&tracker.snapshot(), start);
+	assert!(!exporter.is_due(start + Duration::from_secs(59)));
+	assert!(exporter.is_due(start + Duration::from_secs(60)));
+
+	let exported = std::fs::read_to_string(&path).unwrap();
+	let _ = std::fs::remove_file(&path);
+	assert!(exported.starts_with('{'));
+	assert!(exported.contains("\"CandidateBacking\""));
+}
+
+#[test]
+fn recording_codecs_round_trip() {
+	use crate::record::{DisputeCoordinatorCodec, RecordCodec};
+
+	let codec = DisputeCoordinatorCodec;
+
+	let update = ActiveLeavesUpdate {
+		activated: Some(ActivatedLeaf {
+			hash: Hash::repeat_byte(1),
+			number: 1,
+			status: LeafStatus::Stale,
+			span: Arc::new(jaeger::Span::Disabled),
+		}),
+		deactivated: vec![Hash::repeat_byte(2)].into(),
+	};
+	let signal = OverseerSignal::ActiveLeaves(update.clone());
+	let decoded = codec.decode_signal(&codec.encode_signal(&signal).unwrap()).unwrap();
+	assert_matches!(decoded, OverseerSignal::ActiveLeaves(decoded) => {
+		assert_eq!(decoded, update);
+		assert_eq!(decoded.activated.unwrap().status, LeafStatus::Stale);
+	});
+
+	let candidate_receipt = dummy_candidate_receipt(dummy_hash());
+	let statement = SignedDisputeStatement::new_unchecked_from_trusted_source(
+		DisputeStatement::Invalid(InvalidDisputeStatementKind::Explicit),
+		candidate_receipt.hash(),
+		3,
+		::test_helpers::dummy_validator(),
+		sp_core::sr25519::Signature([7; 64]).into(),
+	);
+	let message = DisputeCoordinatorMessage::ImportStatements {
+		candidate_hash: candidate_receipt.hash(),
+		candidate_receipt: candidate_receipt.clone(),
+		session: 3,
+		statements: vec![(statement.clone(), ValidatorIndex(4))],
+		pending_confirmation: None,
+	};
+	let decoded = codec.decode_message(&codec.encode_message(&message).unwrap()).unwrap();
+	assert_matches!(
+		decoded,
+		DisputeCoordinatorMessage::ImportStatements {
+			candidate_hash,
+			session: 3,
+			statements,
+			pending_confirmation: None,
+			..
+		} => {
+			assert_eq!(candidate_hash, candidate_receipt.hash());
+			assert_eq!(statements.len(), 1);
+			assert_eq!(statements[0].0.statement(), statement.statement());
+			assert_eq!(statements[0].0.validator_signature(), statement.validator_signature());
+			assert_eq!(statements[0].1, ValidatorIndex(4));
+		}
+	);
+
+	assert!(codec.decode_message(&[0xff]).is_none());
+}
+
+#[test]
+fn recording_codecs_round_trip_responses() {
+	use crate::record::{ApprovalVotingCodec, ResponseCodec};
+	use polkadot_node_subsystem_types::errors::RuntimeApiError;
+
+	let codec = ApprovalVotingCodec;
+
+	// Record the response to a request, and replay it to the same request sent again.
+	let (tx, rx) = oneshot::channel();
+	let mut message = AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+		Hash::repeat_byte(1),
+		RuntimeApiRequest::SessionIndexForChild(tx),
+	));
+	let recording = codec.intercept_response(&mut message).unwrap().record();
+	let tx = assert_matches!(
+		message,
+		AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+			_,
+			RuntimeApiRequest::SessionIndexForChild(tx),
+		)) => tx
+	);
+	tx.send(Err(RuntimeApiError::NotSupported { runtime_api_name: "session_index" }))
+		.unwrap();
+	let recorded = executor::block_on(recording).unwrap();
+	assert_matches!(executor::block_on(rx), Ok(Err(RuntimeApiError::NotSupported { .. })));
+
+	let (tx, rx) = oneshot::channel();
+	let mut message =
+		ApprovalVotingOutgoingMessages::RuntimeApiMessage(RuntimeApiMessage::Request(
+			Hash::repeat_byte(1),
+			RuntimeApiRequest::SessionIndexForChild(tx),
+		));
+	assert!(codec.intercept_response(&mut message).unwrap().replay(Some(&recorded)));
+	assert_matches!(executor::block_on(rx), Ok(Err(RuntimeApiError::NotSupported { .. })));
+
+	// Messages without a response channel aren't intercepted.
+	let mut message = AllMessages::ChainSelection(ChainSelectionMessage::Approved(dummy_hash()));
+	assert!(codec.intercept_response(&mut message).is_none());
+}
@@ -0,0 +1,202 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Detection of subsystems which stopped taking messages or signals off their channels.
+//!
+//! The watchdog is fed the meter readouts of every subsystem by the metrics metronome. A
+//! subsystem makes progress whenever it receives from a channel, or has nothing waiting on it.
+//! Once it has gone without progress for longer than the configured threshold, it is
+//! considered stalled until it makes progress again.
+
+use std::{
+	collections::HashMap,
+	time::{Duration, Instant},
+};
+
+use crate::{SubsystemMeterReadouts, LOG_TARGET};
+
+/// Thresholds of the stall watchdog.
+#[derive(Debug, Clone, Copy)]
+pub struct StallWatchdogConfig {
+	/// How long a subsystem may leave messages waiting without receiving any of them.
+	pub message_threshold: Duration,
+	/// How long a subsystem may leave signals waiting without receiving any of them.
+	pub signal_threshold: Duration,
+	/// Whether to log the pending inputs of a subsystem, per channel and by message kind,
+	/// once it stalls.
+	///
+	/// Message kinds are only known if the orchestra counts them, see
+	/// `count_pending_message_kinds` of the overseer builder.
+	pub dump_pending: bool,
+}
+
+impl Default for StallWatchdogConfig {
+	fn default() -> Self {
+		StallWatchdogConfig {
+			message_threshold: Duration::from_secs(30),
+			signal_threshold: Duration::from_secs(10),
+			dump_pending: false,
+		}
+	}
+}
+
+/// The kind of input a subsystem stalled on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StallKind {
+	Messages,
+	Signals,
+}
+
+impl StallKind {
+	pub(crate) fn as_str(&self) -> &'static str {
+		match self {
+			StallKind::Messages => "messages",
+			StallKind::Signals => "signals",
+		}
+	}
+}
+
+/// What the watchdog observed of a single subsystem.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Observation {
+	/// Messages sent to the subsystem, but not yet received by it.
+	pub pending_messages: usize,
+	/// Time since the subsystem last received a message while it had some waiting.
+	pub since_message_progress: Duration,
+	/// Signals sent to the subsystem, but not yet received by it.
+	pub signal_lag: usize,
+	/// Time since the subsystem last received a signal while it had some waiting.
+	pub since_signal_progress: Duration,
+	/// The kinds of input the subsystem stalled on since the previous observation.
+	pub new_stalls: Vec<StallKind>,
+}
+
+struct Progress {
+	messages_received: usize,
+	signals_received: usize,
+	last_message_progress: Instant,
+	last_signal_progress: Instant,
+	messages_stalled: bool,
+	signals_stalled: bool,
+}
+
+/// Tracks the progress of subsystems across meter readouts.
+pub(crate) struct StallWatchdog {
+	config: StallWatchdogConfig,
+	progress: HashMap<&'static str, Progress>,
+}
+
+impl StallWatchdog {
+	pub(crate) fn new(config: StallWatchdogConfig) -> Self {
+		StallWatchdog { config, progress: HashMap::new() }
+	}
+
+	/// Observe the latest readouts of a subsystem, logging when it stalls or recovers.
+	pub(crate) fn observe(
+		&mut self,
+		name: &'static str,
+		readouts: &SubsystemMeterReadouts,
+		now: Instant,
+	) -> Observation {
+		// Received may be slightly ahead of sent, see `metered::Readout`.
+		let pending_bounded = readouts.bounded.sent.saturating_sub(readouts.bounded.received);
+		let pending_unbounded = readouts.unbounded.sent.saturating_sub(readouts.unbounded.received);
+		let pending_priority = readouts.priority.sent.saturating_sub(readouts.priority.received);
+		let pending_messages = pending_bounded + pending_unbounded + pending_priority;
+		let messages_received =
+			readouts.bounded.received + readouts.unbounded.received + readouts.priority.received;
+		let signal_lag = readouts.signals.sent.saturating_sub(readouts.signals.received);
+		let signals_received = readouts.signals.received;
+
+		let progress = self.progress.entry(name).or_insert_with(|| Progress {
+			messages_received,
+			signals_received,
+			last_message_progress: now,
+			last_signal_progress: now,
+			messages_stalled: false,
+			signals_stalled: false,
+		});
+
+		if pending_messages == 0 || messages_received != progress.messages_received {
+			progress.messages_received = messages_received;
+			progress.last_message_progress = now;
+		}
+
+		if signal_lag == 0 || signals_received != progress.signals_received {
+			progress.signals_received = signals_received;
+			progress.last_signal_progress = now;
+		}
+
+		let since_message_progress = now.saturating_duration_since(progress.last_message_progress);
+		let since_signal_progress = now.saturating_duration_since(progress.last_signal_progress);
+
+		let mut new_stalls = Vec::new();
+
+		let messages_stalled =
+			pending_messages > 0 && since_message_progress >= self.config.message_threshold;
+		if messages_stalled && !progress.messages_stalled {
+			gum::error!(
+				target: LOG_TARGET,
+				subsystem = name,
+				pending_messages,
+				stalled_for = ?since_message_progress,
+				"Subsystem stopped receiving its messages",
+			);
+			new_stalls.push(StallKind::Messages);
+		} else if !messages_stalled && progress.messages_stalled {
+			gum::info!(target: LOG_TARGET, subsystem = name, "Subsystem receives messages again");
+		}
+		progress.messages_stalled = messages_stalled;
+
+		let signals_stalled =
+			signal_lag > 0 && since_signal_progress >= self.config.signal_threshold;
+		if signals_stalled && !progress.signals_stalled {
+			gum::error!(
+				target: LOG_TARGET,
+				subsystem = name,
+				signal_lag,
+				stalled_for = ?since_signal_progress,
+				"Subsystem stopped receiving its signals",
+			);
+			new_stalls.push(StallKind::Signals);
+		} else if !signals_stalled && progress.signals_stalled {
+			gum::info!(target: LOG_TARGET, subsystem = name, "Subsystem receives signals again");
+		}
+		progress.signals_stalled = signals_stalled;
+
+		if self.config.dump_pending && !new_stalls.is_empty() {
+			gum::error!(
+				target: LOG_TARGET,
+				subsystem = name,
+				pending_bounded,
+				pending_unbounded,
+				pending_priority,
+				pending_signals = signal_lag,
+				blocked_sends = readouts.bounded.blocked,
+				pending_kinds = ?readouts.pending_kinds,
+				"Pending inputs of stalled subsystem",
+			);
+		}
+
+		Observation {
+			pending_messages,
+			since_message_progress,
+			signal_lag,
+			since_signal_progress,
+			new_stalls,
+		}
+	}
+}
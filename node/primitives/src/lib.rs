@@ -206,7 +206,7 @@ pub type SignedFullStatement = Signed<Statement, CompactStatement>;
 pub type UncheckedSignedFullStatement = UncheckedSigned<Statement, CompactStatement>;
 
 /// Candidate invalidity details
-#[derive(Debug)]
+#[derive(Debug, Encode, Decode)]
 pub enum InvalidCandidate {
 	/// Failed to execute.`validate_block`. This includes function panicking.
 	ExecutionError(String),
@@ -239,7 +239,7 @@ pub enum InvalidCandidate {
 }
 
 /// Result of the validation of the candidate.
-#[derive(Debug)]
+#[derive(Debug, Encode, Decode)]
 pub enum ValidationResult {
 	/// Candidate is valid. The validation process yields these outputs and the persisted validation
 	/// data used to form inputs.
@@ -27,7 +27,7 @@ mod relay_chain_selection;
 pub mod overseer;
 
 #[cfg(feature = "full-node")]
-pub use self::overseer::{OverseerGen, OverseerGenArgs, RealOverseerGen};
+pub use self::overseer::{GossipSupportConfig, OverseerGen, OverseerGenArgs, RealOverseerGen};
 
 #[cfg(test)]
 mod tests;
@@ -57,7 +57,17 @@ use polkadot_node_subsystem_util::database::Database;
 
 #[cfg(feature = "full-node")]
 pub use {
-	polkadot_overseer::{Handle, Overseer, OverseerConnector, OverseerHandle},
+	polkadot_network_bridge::{
+		RateLimit as NetworkRateLimit, RateLimitConfig as NetworkRateLimitConfig,
+	},
+	polkadot_node_core_av_store::PruningConfig as AvailabilityPruningConfig,
+	polkadot_node_network_protocol::grid_topology::RandomK as RandomKTopology,
+	polkadot_node_subsystem::jaeger::JaegerConfigBuilder,
+	polkadot_overseer::{
+		gen::metered::{CoarseDuration, TofConfig as MessageTofConfig},
+		GraphExportConfig, Handle, Overseer, OverseerConnector, OverseerHandle,
+		StallWatchdogConfig,
+	},
 	polkadot_primitives::runtime_api::ParachainHost,
 	relay_chain_selection::SelectRelayChain,
 	sc_client_api::AuxStore,
@@ -67,7 +77,11 @@ pub use {
 };
 
 #[cfg(feature = "full-node")]
-use polkadot_node_subsystem::jaeger;
+use polkadot_node_subsystem::{jaeger, messages::PeerPolicy};
+#[cfg(feature = "full-node")]
+use polkadot_primitives::v2::AuthorityDiscoveryId;
+#[cfg(feature = "full-node")]
+use std::collections::HashMap;
 
 use std::{sync::Arc, time::Duration};
 
@@ -229,6 +243,14 @@ pub enum Error {
 	#[error(transparent)]
 	Availability(#[from] AvailabilityError),
 
+	#[cfg(feature = "full-node")]
+	#[error("Invalid availability archive: {0}")]
+	AvailabilityArchive(#[from] polkadot_availability_recovery::archive::ArchiveError),
+
+	#[cfg(feature = "full-node")]
+	#[error("Invalid authority discovery id: {0}")]
+	InvalidAuthorityId(String),
+
 	#[error("Authorities require the real overseer implementation")]
 	AuthoritiesRequireRealOverseer,
 
@@ -319,19 +341,43 @@ fn open_database(db_source: &DatabaseSource) -> Result<Arc<dyn Database>, Error>
 	Ok(parachains_db)
 }
 
-/// Initialize the `Jeager` collector. The destination must listen
-/// on the given address and port for `UDP` packets.
+/// Parse the authorities pinned and banned on the command line.
+///
+/// An authority that is both pinned and banned is banned.
+#[cfg(feature = "full-node")]
+fn peer_policies_from_cli(
+	pinned_authorities: Vec<String>,
+	banned_authorities: Vec<String>,
+) -> Result<HashMap<AuthorityDiscoveryId, PeerPolicy>, Error> {
+	use sp_core::crypto::Ss58Codec;
+
+	let parse = |id: String| {
+		sp_core::sr25519::Public::from_ss58check(&id)
+			.map(AuthorityDiscoveryId::from)
+			.map_err(|_| Error::InvalidAuthorityId(id))
+	};
+
+	let mut peer_policies = HashMap::new();
+	for id in pinned_authorities {
+		peer_policies.insert(parse(id)?, PeerPolicy::Pinned);
+	}
+	for id in banned_authorities {
+		peer_policies.insert(parse(id)?, PeerPolicy::Banned);
+	}
+	Ok(peer_policies)
+}
+
+/// Initialize the `Jeager` collector. The configured collector must accept
+/// OTLP over HTTP, or the configured agent thrift over UDP.
 #[cfg(any(test, feature = "full-node"))]
-fn jaeger_launch_collector_with_agent(
+fn jaeger_launch_collector(
 	spawner: impl SpawnNamed,
 	config: &Configuration,
-	agent: Option<std::net::SocketAddr>,
+	jaeger_config: Option<JaegerConfigBuilder>,
 ) -> Result<(), Error> {
-	if let Some(agent) = agent {
-		let cfg = jaeger::JaegerConfig::builder()
-			.agent(agent)
-			.named(&config.network.node_name)
-			.build();
+	if let Some(jaeger_config) = jaeger_config {
+		let role = if config.role.is_authority() { "authority" } else { "full" };
+		let cfg = jaeger_config.named(&config.network.node_name).role(role).build();
 
 		jaeger::Jaeger::new(cfg).launch(spawner)?;
 	}
@@ -370,7 +416,7 @@ where
 #[cfg(feature = "full-node")]
 fn new_partial_basics<RuntimeApi, ExecutorDispatch>(
 	config: &mut Configuration,
-	jaeger_agent: Option<std::net::SocketAddr>,
+	jaeger_config: Option<JaegerConfigBuilder>,
 	telemetry_worker_handle: Option<TelemetryWorkerHandle>,
 ) -> Result<Basics<RuntimeApi, ExecutorDispatch>, Error>
 where
@@ -425,7 +471,7 @@ where
 		telemetry
 	});
 
-	jaeger_launch_collector_with_agent(task_manager.spawn_handle(), &*config, jaeger_agent)?;
+	jaeger_launch_collector(task_manager.spawn_handle(), &*config, jaeger_config)?;
 
 	Ok(Basics { task_manager, client, backend, keystore_container, telemetry })
 }
@@ -438,6 +484,7 @@ fn new_partial<RuntimeApi, ExecutorDispatch, ChainSelection>(
 		ExecutorDispatch,
 	>,
 	select_chain: ChainSelection,
+	overseer_handle: Option<Handle>,
 ) -> Result<
 	service::PartialComponents<
 		FullClient<RuntimeApi, ExecutorDispatch>,
@@ -583,6 +630,7 @@ where
 					beefy_best_block_stream: beefy_best_block_stream.clone(),
 					subscription_executor,
 				},
+				overseer_handle: overseer_handle.clone(),
 			};
 
 			polkadot_rpc::create_full(deps, backend.clone()).map_err(Into::into)
@@ -705,6 +753,34 @@ where
 	Ok(leaves.into_iter().rev().take(MAX_ACTIVE_LEAVES).collect())
 }
 
+/// Options of a full node which configure its overseer and subsystems.
+#[cfg(feature = "full-node")]
+#[derive(Default)]
+pub struct FullNodeOptions {
+	/// How long the availability store keeps data, and how much of it.
+	pub availability_pruning_config: AvailabilityPruningConfig,
+	/// An archive to recover available data from if the network can't provide it.
+	pub availability_archive: Option<String>,
+	/// SS58 encoded authority discovery keys to always keep a validation connection to.
+	pub pinned_authorities: Vec<String>,
+	/// SS58 encoded authority discovery keys never to connect to.
+	pub banned_authorities: Vec<String>,
+	/// Where and how often to export the graph of subsystem communication, if at all.
+	pub graph_export_config: Option<GraphExportConfig>,
+	/// The maximum number of unfinalized block heights kept in the approval voting database.
+	pub approval_db_max_unfinalized_heights: Option<BlockNumber>,
+	/// Rate limits of the notifications received from peers.
+	pub network_rate_limits: NetworkRateLimitConfig,
+	/// The directory to record the inputs of the recorded subsystems into, if any.
+	pub subsystem_recording_dir: Option<std::path::PathBuf>,
+	/// When subsystems are reported as stalled.
+	pub stall_watchdog_config: StallWatchdogConfig,
+	/// The time of flight measurements of subsystem messages.
+	pub message_tof_config: MessageTofConfig,
+	/// How the gossip support subsystem arranges validators into the gossip topology.
+	pub gossip_support_config: GossipSupportConfig,
+}
+
 /// Create a new full node of arbitrary runtime and executor.
 ///
 /// This is an advanced feature and not recommended for general use. Generally, `build_full` is
@@ -719,12 +795,13 @@ pub fn new_full<RuntimeApi, ExecutorDispatch, OverseerGenerator>(
 	is_collator: IsCollator,
 	grandpa_pause: Option<(u32, u32)>,
 	enable_beefy: bool,
-	jaeger_agent: Option<std::net::SocketAddr>,
+	jaeger_config: Option<JaegerConfigBuilder>,
 	telemetry_worker_handle: Option<TelemetryWorkerHandle>,
 	program_path: Option<std::path::PathBuf>,
 	overseer_enable_anyways: bool,
 	overseer_gen: OverseerGenerator,
 	overseer_message_channel_capacity_override: Option<usize>,
+	options: FullNodeOptions,
 	hwbench: Option<sc_sysinfo::HwBench>,
 ) -> Result<NewFull<Arc<FullClient<RuntimeApi, ExecutorDispatch>>>, Error>
 where
@@ -739,6 +816,20 @@ where
 {
 	use polkadot_node_network_protocol::request_response::IncomingRequest;
 
+	let FullNodeOptions {
+		availability_pruning_config,
+		availability_archive,
+		pinned_authorities,
+		banned_authorities,
+		graph_export_config,
+		approval_db_max_unfinalized_heights,
+		network_rate_limits,
+		subsystem_recording_dir,
+		stall_watchdog_config,
+		message_tof_config,
+		gossip_support_config,
+	} = options;
+
 	let role = config.role.clone();
 	let force_authoring = config.force_authoring;
 	let backoff_authoring_blocks = {
@@ -771,7 +862,7 @@ where
 
 	let basics = new_partial_basics::<RuntimeApi, ExecutorDispatch>(
 		&mut config,
-		jaeger_agent,
+		jaeger_config,
 		telemetry_worker_handle,
 	)?;
 
@@ -814,6 +905,7 @@ where
 		&mut config,
 		basics,
 		select_chain,
+		Some(overseer_handle.clone()),
 	)?;
 
 	let shared_voter_state = rpc_setup;
@@ -848,14 +940,14 @@ where
 		config.network.extra_sets.extend(peer_sets_info(is_authority));
 	}
 
-	let (pov_req_receiver, cfg) = IncomingRequest::get_config_receiver();
-	config.network.request_response_protocols.push(cfg);
-	let (chunk_req_receiver, cfg) = IncomingRequest::get_config_receiver();
-	config.network.request_response_protocols.push(cfg);
-	let (collation_req_receiver, cfg) = IncomingRequest::get_config_receiver();
-	config.network.request_response_protocols.push(cfg);
-	let (available_data_req_receiver, cfg) = IncomingRequest::get_config_receiver();
-	config.network.request_response_protocols.push(cfg);
+	let (pov_req_receiver, cfgs) = IncomingRequest::get_config_receivers();
+	config.network.request_response_protocols.extend(cfgs);
+	let (chunk_req_receiver, cfgs) = IncomingRequest::get_config_receivers();
+	config.network.request_response_protocols.extend(cfgs);
+	let (collation_req_receiver, cfgs) = IncomingRequest::get_config_receivers();
+	config.network.request_response_protocols.extend(cfgs);
+	let (available_data_req_receiver, cfgs) = IncomingRequest::get_config_receivers();
+	config.network.request_response_protocols.extend(cfgs);
 	let (statement_req_receiver, cfg) = IncomingRequest::get_config_receiver();
 	config.network.request_response_protocols.push(cfg);
 	let (dispute_req_receiver, cfg) = IncomingRequest::get_config_receiver();
@@ -909,11 +1001,19 @@ where
 	let availability_config = AvailabilityConfig {
 		col_data: parachains_db::REAL_COLUMNS.col_availability_data,
 		col_meta: parachains_db::REAL_COLUMNS.col_availability_meta,
+		pruning: availability_pruning_config,
 	};
 
+	let availability_archive = availability_archive
+		.map(|endpoint| polkadot_availability_recovery::archive::from_endpoint(&endpoint))
+		.transpose()?;
+
+	let peer_policies = peer_policies_from_cli(pinned_authorities, banned_authorities)?;
+
 	let approval_voting_config = ApprovalVotingConfig {
 		col_data: parachains_db::REAL_COLUMNS.col_approval_data,
 		slot_duration_millis: slot_duration.as_millis() as u64,
+		max_unfinalized_heights: approval_db_max_unfinalized_heights,
 	};
 
 	let candidate_validation_config = CandidateValidationConfig {
@@ -1047,6 +1147,14 @@ where
 					dispute_coordinator_config,
 					pvf_checker_enabled,
 					overseer_message_channel_capacity_override,
+					availability_archive,
+					peer_policies,
+					network_rate_limits,
+					stall_watchdog_config,
+					graph_export_config,
+					subsystem_recording_dir,
+					message_tof_config,
+					gossip_support_config,
 				},
 			)
 			.map_err(|e| {
@@ -1262,13 +1370,13 @@ where
 
 #[cfg(feature = "full-node")]
 macro_rules! chain_ops {
-	($config:expr, $jaeger_agent:expr, $telemetry_worker_handle:expr; $scope:ident, $executor:ident, $variant:ident) => {{
+	($config:expr, $jaeger_config:expr, $telemetry_worker_handle:expr; $scope:ident, $executor:ident, $variant:ident) => {{
 		let telemetry_worker_handle = $telemetry_worker_handle;
-		let jaeger_agent = $jaeger_agent;
+		let jaeger_config = $jaeger_config;
 		let mut config = $config;
 		let basics = new_partial_basics::<$scope::RuntimeApi, $executor>(
 			config,
-			jaeger_agent,
+			jaeger_config,
 			telemetry_worker_handle,
 		)?;
 
@@ -1281,6 +1389,7 @@ macro_rules! chain_ops {
 				&mut config,
 				basics,
 				chain_selection,
+				None,
 			)?;
 		Ok((Arc::new(Client::$variant(client)), backend, import_queue, task_manager))
 	}};
@@ -1290,7 +1399,7 @@ macro_rules! chain_ops {
 #[cfg(feature = "full-node")]
 pub fn new_chain_ops(
 	mut config: &mut Configuration,
-	jaeger_agent: Option<std::net::SocketAddr>,
+	jaeger_config: Option<JaegerConfigBuilder>,
 ) -> Result<
 	(
 		Arc<Client>,
@@ -1309,22 +1418,22 @@ pub fn new_chain_ops(
 		config.chain_spec.is_wococo() ||
 		config.chain_spec.is_versi()
 	{
-		return chain_ops!(config, jaeger_agent, telemetry_worker_handle; rococo_runtime, RococoExecutorDispatch, Rococo)
+		return chain_ops!(config, jaeger_config, telemetry_worker_handle; rococo_runtime, RococoExecutorDispatch, Rococo)
 	}
 
 	#[cfg(feature = "kusama-native")]
 	if config.chain_spec.is_kusama() {
-		return chain_ops!(config, jaeger_agent, telemetry_worker_handle; kusama_runtime, KusamaExecutorDispatch, Kusama)
+		return chain_ops!(config, jaeger_config, telemetry_worker_handle; kusama_runtime, KusamaExecutorDispatch, Kusama)
 	}
 
 	#[cfg(feature = "westend-native")]
 	if config.chain_spec.is_westend() {
-		return chain_ops!(config, jaeger_agent, telemetry_worker_handle; westend_runtime, WestendExecutorDispatch, Westend)
+		return chain_ops!(config, jaeger_config, telemetry_worker_handle; westend_runtime, WestendExecutorDispatch, Westend)
 	}
 
 	#[cfg(feature = "polkadot-native")]
 	{
-		return chain_ops!(config, jaeger_agent, telemetry_worker_handle; polkadot_runtime, PolkadotExecutorDispatch, Polkadot)
+		return chain_ops!(config, jaeger_config, telemetry_worker_handle; polkadot_runtime, PolkadotExecutorDispatch, Polkadot)
 	}
 	#[cfg(not(feature = "polkadot-native"))]
 	Err(Error::NoRuntime)
@@ -1344,11 +1453,12 @@ pub fn build_full(
 	is_collator: IsCollator,
 	grandpa_pause: Option<(u32, u32)>,
 	enable_beefy: bool,
-	jaeger_agent: Option<std::net::SocketAddr>,
+	jaeger_config: Option<JaegerConfigBuilder>,
 	telemetry_worker_handle: Option<TelemetryWorkerHandle>,
 	overseer_enable_anyways: bool,
 	overseer_gen: impl OverseerGen,
 	overseer_message_channel_override: Option<usize>,
+	options: FullNodeOptions,
 	hwbench: Option<sc_sysinfo::HwBench>,
 ) -> Result<NewFull<Client>, Error> {
 	#[cfg(feature = "rococo-native")]
@@ -1361,12 +1471,13 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			enable_beefy,
-			jaeger_agent,
+			jaeger_config,
 			telemetry_worker_handle,
 			None,
 			overseer_enable_anyways,
 			overseer_gen,
 			overseer_message_channel_override,
+			options,
 			hwbench,
 		)
 		.map(|full| full.with_client(Client::Rococo))
@@ -1379,12 +1490,13 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			enable_beefy,
-			jaeger_agent,
+			jaeger_config,
 			telemetry_worker_handle,
 			None,
 			overseer_enable_anyways,
 			overseer_gen,
 			overseer_message_channel_override,
+			options,
 			hwbench,
 		)
 		.map(|full| full.with_client(Client::Kusama))
@@ -1397,12 +1509,13 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			enable_beefy,
-			jaeger_agent,
+			jaeger_config,
 			telemetry_worker_handle,
 			None,
 			overseer_enable_anyways,
 			overseer_gen,
 			overseer_message_channel_override,
+			options,
 			hwbench,
 		)
 		.map(|full| full.with_client(Client::Westend))
@@ -1415,7 +1528,7 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			enable_beefy,
-			jaeger_agent,
+			jaeger_config,
 			telemetry_worker_handle,
 			None,
 			overseer_enable_anyways,
@@ -1424,6 +1537,7 @@ pub fn build_full(
 				gum::warn!("Channel capacity should _never_ be tampered with on polkadot!");
 				capacity
 			}),
+			options,
 			hwbench,
 		)
 		.map(|full| full.with_client(Client::Polkadot))
@@ -1487,22 +1601,43 @@ fn revert_chain_selection(db: Arc<dyn Database>, hash: Hash) -> sp_blockchain::R
 }
 
 fn revert_approval_voting(db: Arc<dyn Database>, hash: Hash) -> sp_blockchain::Result<()> {
+	offline_approval_voting(db)
+		.revert_to(hash)
+		.map_err(|err| sp_blockchain::Error::Backend(err.to_string()))
+}
+
+/// Checks the consistency of the `ApprovalVotingSubsystem` data in the parachains-db.
+///
+/// Orphaned entries which were found are removed if `prune` is set.
+#[cfg(feature = "full-node")]
+pub fn check_approval_db(
+	db_source: &DatabaseSource,
+	prune: bool,
+) -> Result<approval_voting_subsystem::ConsistencyReport, Error> {
+	let parachains_db = open_database(db_source)?;
+
+	offline_approval_voting(parachains_db)
+		.check_db(prune)
+		.map_err(|err| sp_blockchain::Error::Backend(err.to_string()).into())
+}
+
+// An approval voting subsystem which is only used to access its DB.
+fn offline_approval_voting(
+	db: Arc<dyn Database>,
+) -> approval_voting_subsystem::ApprovalVotingSubsystem {
 	let config = approval_voting_subsystem::Config {
 		col_data: parachains_db::REAL_COLUMNS.col_approval_data,
 		slot_duration_millis: Default::default(),
+		max_unfinalized_heights: None,
 	};
 
-	let approval_voting = approval_voting_subsystem::ApprovalVotingSubsystem::with_config(
+	approval_voting_subsystem::ApprovalVotingSubsystem::with_config(
 		config,
 		db,
 		Arc::new(sc_keystore::LocalKeystore::in_memory()),
 		Box::new(consensus_common::NoNetwork),
 		approval_voting_subsystem::Metrics::default(),
-	);
-
-	approval_voting
-		.revert_to(hash)
-		.map_err(|err| sp_blockchain::Error::Backend(err.to_string()))
+	)
 }
 
 struct RevertConsensus {
@@ -31,27 +31,43 @@ pub use polkadot_overseer::{
 	HeadSupportsParachains,
 };
 use polkadot_overseer::{
-	metrics::Metrics as OverseerMetrics, BlockInfo, InitializedOverseerBuilder, MetricsTrait,
-	Overseer, OverseerConnector, OverseerHandle, SpawnGlue,
+	gen::metered::TofConfig as MessageTofConfig,
+	metrics::Metrics as OverseerMetrics,
+	record::{ApprovalVotingCodec, DisputeCoordinatorCodec, RecordingSubsystem},
+	BlockInfo, GraphExportConfig, InitializedOverseerBuilder, MetricsTrait, NotifiedLeaves,
+	Overseer, OverseerConnector, OverseerHandle, SpawnGlue, StallWatchdogConfig,
 };
 
-use polkadot_primitives::runtime_api::ParachainHost;
+use polkadot_node_subsystem_types::messages::PeerPolicy;
+use polkadot_primitives::{runtime_api::ParachainHost, v2::AuthorityDiscoveryId};
 use sc_authority_discovery::Service as AuthorityDiscoveryService;
 use sc_client_api::AuxStore;
 use sc_keystore::LocalKeystore;
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
 use sp_consensus_babe::BabeApi;
-use std::sync::Arc;
+use std::{
+	collections::HashMap,
+	fs::{File, OpenOptions},
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 pub use polkadot_approval_distribution::ApprovalDistribution as ApprovalDistributionSubsystem;
 pub use polkadot_availability_bitfield_distribution::BitfieldDistribution as BitfieldDistributionSubsystem;
 pub use polkadot_availability_distribution::AvailabilityDistributionSubsystem;
-pub use polkadot_availability_recovery::AvailabilityRecoverySubsystem;
+pub use polkadot_availability_recovery::{
+	archive::ArchiveSource as AvailabilityArchiveSource, AvailabilityRecoverySubsystem,
+};
 pub use polkadot_collator_protocol::{CollatorProtocolSubsystem, ProtocolSide};
 pub use polkadot_dispute_distribution::DisputeDistributionSubsystem;
-pub use polkadot_gossip_support::GossipSupport as GossipSupportSubsystem;
-pub use polkadot_network_bridge::NetworkBridge as NetworkBridgeSubsystem;
+pub use polkadot_gossip_support::{
+	Config as GossipSupportConfig, GossipSupport as GossipSupportSubsystem,
+};
+pub use polkadot_network_bridge::{
+	NetworkBridge as NetworkBridgeSubsystem, RateLimitConfig as NetworkRateLimitConfig,
+};
 pub use polkadot_node_collation_generation::CollationGenerationSubsystem;
 pub use polkadot_node_core_approval_voting::ApprovalVotingSubsystem;
 pub use polkadot_node_core_av_store::AvailabilityStoreSubsystem;
@@ -114,6 +130,23 @@ where
 	pub pvf_checker_enabled: bool,
 	/// Overseer channel capacity override.
 	pub overseer_message_channel_capacity_override: Option<usize>,
+	/// Archive to recover availability data from if the network fails to provide it.
+	pub availability_archive: Option<Arc<dyn AvailabilityArchiveSource>>,
+	/// Authorities the network bridge always connects to or never connects to.
+	pub peer_policies: HashMap<AuthorityDiscoveryId, PeerPolicy>,
+	/// Budgets of incoming notifications per peer in the network bridge.
+	pub network_rate_limits: NetworkRateLimitConfig,
+	/// Thresholds for detecting stalled subsystems.
+	pub stall_watchdog_config: StallWatchdogConfig,
+	/// Where to periodically export the subsystem connection graph to, if anywhere.
+	pub graph_export_config: Option<GraphExportConfig>,
+	/// Directory to record the inputs of the approval voting and dispute coordinator
+	/// subsystems into, if any. Every start of the node records into new files.
+	pub subsystem_recording_dir: Option<PathBuf>,
+	/// How the time of flight of messages between subsystems is measured.
+	pub message_tof_config: MessageTofConfig,
+	/// Configuration for the gossip support subsystem.
+	pub gossip_support_config: GossipSupportConfig,
 }
 
 /// Obtain a prepared `OverseerBuilder`, that is initialized
@@ -142,6 +175,14 @@ pub fn prepared_overseer_builder<'a, Spawner, RuntimeClient>(
 		dispute_coordinator_config,
 		pvf_checker_enabled,
 		overseer_message_channel_capacity_override,
+		availability_archive,
+		peer_policies,
+		network_rate_limits,
+		stall_watchdog_config,
+		graph_export_config,
+		subsystem_recording_dir,
+		message_tof_config,
+		gossip_support_config,
 	}: OverseerGenArgs<'a, Spawner, RuntimeClient>,
 ) -> Result<
 	InitializedOverseerBuilder<
@@ -166,9 +207,9 @@ pub fn prepared_overseer_builder<'a, Spawner, RuntimeClient>(
 		CollationGenerationSubsystem,
 		CollatorProtocolSubsystem,
 		ApprovalDistributionSubsystem,
-		ApprovalVotingSubsystem,
+		RecordingSubsystem<ApprovalVotingSubsystem, File, ApprovalVotingCodec>,
 		GossipSupportSubsystem<AuthorityDiscoveryService>,
-		DisputeCoordinatorSubsystem,
+		RecordingSubsystem<DisputeCoordinatorSubsystem, File, DisputeCoordinatorCodec>,
 		DisputeDistributionSubsystem<AuthorityDiscoveryService>,
 		ChainSelectionSubsystem,
 	>,
@@ -184,6 +225,9 @@ where
 	let metrics = <OverseerMetrics as MetricsTrait>::register(registry)?;
 
 	let spawner = SpawnGlue(spawner);
+	let notified_leaves = NotifiedLeaves::default();
+	// Keeps the recordings of earlier starts, e.g. of the run before a crash.
+	let recording_started = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
 
 	let builder = Overseer::builder()
 		.availability_distribution(AvailabilityDistributionSubsystem::new(
@@ -193,6 +237,7 @@ where
 		))
 		.availability_recovery(AvailabilityRecoverySubsystem::with_chunks_only(
 			available_data_req_receiver,
+			availability_archive,
 			Metrics::register(registry)?,
 		))
 		.availability_store(AvailabilityStoreSubsystem::new(
@@ -242,6 +287,8 @@ where
 			authority_discovery_service.clone(),
 			Box::new(network_service.clone()),
 			Metrics::register(registry)?,
+			peer_policies,
+			network_rate_limits,
 		))
 		.provisioner(ProvisionerSubsystem::new(Metrics::register(registry)?))
 		.runtime_api(RuntimeApiSubsystem::new(
@@ -256,24 +303,37 @@ where
 			rand::rngs::StdRng::from_entropy(),
 		))
 		.approval_distribution(ApprovalDistributionSubsystem::new(Metrics::register(registry)?))
-		.approval_voting(ApprovalVotingSubsystem::with_config(
-			approval_voting_config,
-			parachains_db.clone(),
-			keystore.clone(),
-			Box::new(network_service.clone()),
-			Metrics::register(registry)?,
-		))
+		.approval_voting(recording_subsystem(
+			ApprovalVotingSubsystem::with_config(
+				approval_voting_config,
+				parachains_db.clone(),
+				keystore.clone(),
+				Box::new(network_service.clone()),
+				Metrics::register(registry)?,
+			),
+			subsystem_recording_dir.as_deref(),
+			"approval-voting",
+			recording_started,
+			ApprovalVotingCodec,
+		)?)
 		.gossip_support(GossipSupportSubsystem::new(
 			keystore.clone(),
 			authority_discovery_service.clone(),
+			gossip_support_config,
 			Metrics::register(registry)?,
 		))
-		.dispute_coordinator(DisputeCoordinatorSubsystem::new(
-			parachains_db.clone(),
-			dispute_coordinator_config,
-			keystore.clone(),
-			Metrics::register(registry)?,
-		))
+		.dispute_coordinator(recording_subsystem(
+			DisputeCoordinatorSubsystem::new(
+				parachains_db.clone(),
+				dispute_coordinator_config,
+				keystore.clone(),
+				Metrics::register(registry)?,
+			),
+			subsystem_recording_dir.as_deref(),
+			"dispute-coordinator",
+			recording_started,
+			DisputeCoordinatorCodec,
+		)?)
 		.dispute_distribution(DisputeDistributionSubsystem::new(
 			keystore.clone(),
 			dispute_req_receiver,
@@ -289,10 +349,16 @@ where
 		.activation_external_listeners(Default::default())
 		.span_per_active_leaf(Default::default())
 		.active_leaves(Default::default())
+		.notified_leaves(notified_leaves.clone())
 		.supports_parachains(runtime_client)
 		.known_leaves(LruCache::new(KNOWN_LEAVES_CACHE_SIZE))
 		.metrics(metrics)
-		.spawner(spawner);
+		.stall_watchdog(stall_watchdog_config)
+		.graph_export(graph_export_config)
+		.spawner(spawner)
+		.count_pending_message_kinds(stall_watchdog_config.dump_pending)
+		.message_tof_config(message_tof_config)
+		.restart_signals(move || notified_leaves.restart_signals());
 
 	if let Some(capacity) = overseer_message_channel_capacity_override {
 		Ok(builder.message_channel_capacity(capacity))
@@ -301,6 +367,29 @@ where
 	}
 }
 
+/// Record the inputs of `subsystem` into `<dir>/<name>-<started>.rec`, if a directory is
+/// given, where `started` are the milliseconds since the unix epoch at the start of the node.
+fn recording_subsystem<S, C>(
+	subsystem: S,
+	dir: Option<&Path>,
+	name: &str,
+	started: Duration,
+	codec: C,
+) -> Result<RecordingSubsystem<S, File, C>, Error> {
+	let dir = match dir {
+		Some(dir) => dir,
+		None => return Ok(RecordingSubsystem::disabled(subsystem)),
+	};
+
+	std::fs::create_dir_all(dir)?;
+	let path = dir.join(format!("{}-{}.rec", name, started.as_millis()));
+	// Never overwrite an existing recording.
+	let file = OpenOptions::new().write(true).create_new(true).open(&path)?;
+	let recording = RecordingSubsystem::new(subsystem, file, codec)?;
+	gum::info!(path = %path.display(), "Recording subsystem inputs");
+	Ok(recording)
+}
+
 /// Trait for the `fn` generating the overseer.
 ///
 /// Default behavior is to create an unmodified overseer, as `RealOverseerGen`
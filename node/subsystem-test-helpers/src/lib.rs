@@ -33,7 +33,7 @@ use std::{
 	pin::Pin,
 	sync::Arc,
 	task::{Context, Poll, Waker},
-	time::Duration,
+	time::{Duration, Instant},
 };
 
 /// Generally useful mock data providers for unit tests.
@@ -308,6 +308,144 @@ pub fn subsystem_test_harness<M, OverseerFactory, Overseer, TestFactory, Test>(
 	});
 }
 
+/// Errors of [`replay_recording`].
+#[derive(Debug)]
+pub enum ReplayError {
+	/// Reading the recording failed.
+	Io(std::io::Error),
+	/// The recorded entry at the given index could not be decoded, or answers a request
+	/// which was answered already.
+	Undecodable(usize),
+	/// The subsystem exited with an error.
+	Subsystem(SubsystemError),
+	/// The subsystem did not conclude in time.
+	TimedOut,
+}
+
+/// Replay a recording made with [`overseer::gen::record::RecordingSubsystem`] into a fresh
+/// instance of a subsystem.
+///
+/// The recorded signals and messages are delivered in order and at the time they were
+/// recorded at, followed by `OverseerSignal::Conclude` unless the recording already ends with
+/// it. The requests the subsystem sends are answered with the recorded responses, in the order
+/// they were sent; requests whose response is missing are dropped, as are all other sent
+/// messages. The `Debug` representations of the sent messages are returned in order, for
+/// diffing against what the subsystem did when it was recorded.
+///
+/// The replay takes as long as the recording, `timeout` is the time the subsystem is given
+/// to conclude on top of that.
+pub fn replay_recording<M, Sub, C>(
+	subsystem: Sub,
+	recording: impl std::io::Read,
+	codec: &C,
+	timeout: Duration,
+) -> Result<Vec<String>, ReplayError>
+where
+	TestSubsystemContext<M, SpawnGlue<TaskExecutor>>:
+		overseer::SubsystemContext<Message = M, Signal = OverseerSignal, Error = SubsystemError>,
+	Sub: overseer::Subsystem<TestSubsystemContext<M, SpawnGlue<TaskExecutor>>, SubsystemError>,
+	C: overseer::gen::record::RecordCodec<M, OverseerSignal>
+		+ overseer::gen::record::ResponseCodec<AllMessages>,
+{
+	use overseer::gen::record::{read_recording, RecordKind, ResponseChannel};
+
+	enum Replayed<'a, M> {
+		Input(FromOrchestra<M, OverseerSignal>),
+		Response { entry: usize, request: u64, response: Option<&'a [u8]> },
+	}
+
+	let entries = read_recording(recording).map_err(ReplayError::Io)?;
+	let mut timeline = Vec::with_capacity(entries.len() + 1);
+	for (i, entry) in entries.iter().enumerate() {
+		let replayed = match entry.kind {
+			RecordKind::Response => {
+				let (request, response) = entry.response().ok_or(ReplayError::Undecodable(i))?;
+				Replayed::Response { entry: i, request, response }
+			},
+			RecordKind::Signal | RecordKind::Message =>
+				Replayed::Input(entry.decode(codec).ok_or(ReplayError::Undecodable(i))?),
+		};
+		timeline.push((entry.elapsed, replayed));
+	}
+	let concluded = timeline.iter().rev().find_map(|(_, replayed)| match replayed {
+		Replayed::Input(input) => Some(input),
+		Replayed::Response { .. } => None,
+	});
+	if !matches!(concluded, Some(FromOrchestra::Signal(OverseerSignal::Conclude))) {
+		let elapsed = timeline.last().map_or(Duration::ZERO, |(elapsed, _)| *elapsed);
+		timeline.push((elapsed, Replayed::Input(FromOrchestra::Signal(OverseerSignal::Conclude))));
+	}
+	let duration = timeline.last().map_or(Duration::ZERO, |(elapsed, _)| *elapsed);
+
+	let (context, TestSubsystemContextHandle { mut tx, mut rx }) =
+		make_subsystem_context(TaskExecutor::new());
+	let (requests_tx, mut requests_rx) = mpsc::unbounded::<ResponseChannel>();
+	let subsystem = subsystem.start(context).future.fuse();
+	let started = Instant::now();
+
+	let feed = async move {
+		let mut requests = Vec::new();
+		for (elapsed, replayed) in timeline {
+			overseer::gen::Delay::new(elapsed.saturating_sub(started.elapsed())).await;
+			match replayed {
+				Replayed::Input(input) => {
+					let _ = tx.send(input).await;
+				},
+				Replayed::Response { entry, request, response } => {
+					// Wait for the subsystem to send the request, just like it was recorded.
+					while requests.len() as u64 <= request {
+						match requests_rx.next().await {
+							Some(channel) => requests.push(Some(channel)),
+							None => return Ok(()),
+						}
+					}
+					let channel =
+						requests[request as usize].take().ok_or(ReplayError::Undecodable(entry))?;
+					if !channel.replay(response) {
+						return Err(ReplayError::Undecodable(entry))
+					}
+				},
+			}
+		}
+		Ok(())
+	}
+	.fuse();
+
+	let mut sent = Vec::new();
+	let replay = async {
+		let collect = async {
+			while let Some(mut msg) = rx.next().await {
+				sent.push(format!("{:?}", msg));
+				if let Some(channel) = codec.intercept_response(&mut msg) {
+					let _ = requests_tx.unbounded_send(channel);
+				}
+			}
+		}
+		.fuse();
+		futures::pin_mut!(subsystem, feed, collect);
+		loop {
+			futures::select! {
+				result = subsystem => break result.map_err(ReplayError::Subsystem),
+				result = feed => result?,
+				() = collect => {},
+			}
+		}
+	};
+
+	match futures::executor::block_on(replay.timeout(duration + timeout)) {
+		None => return Err(ReplayError::TimedOut),
+		Some(Err(err)) => return Err(err),
+		Some(Ok(())) => {},
+	}
+
+	// Messages sent right before concluding.
+	while let Ok(Some(msg)) = rx.try_next() {
+		sent.push(format!("{:?}", msg));
+	}
+
+	Ok(sent)
+}
+
 /// A forward subsystem that implements [`Subsystem`].
 ///
 /// It forwards all communication from the overseer to the internal message
@@ -377,10 +515,15 @@ macro_rules! arbitrary_order {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use futures::executor::block_on;
-	use polkadot_node_subsystem::messages::CollatorProtocolMessage;
-	use polkadot_overseer::{dummy::dummy_overseer_builder, Handle, HeadSupportsParachains};
-	use polkadot_primitives::v2::Hash;
+	use futures::{channel::oneshot, executor::block_on};
+	use polkadot_node_subsystem::{
+		messages::{ChainApiMessage, CollatorProtocolMessage},
+		overseer::gen::record::{RecordCodec, Recorder, ResponseChannel, ResponseCodec},
+	};
+	use polkadot_overseer::{
+		dummy::dummy_overseer_builder, record::RecordResponse, Handle, HeadSupportsParachains,
+	};
+	use polkadot_primitives::v2::{BlockNumber, Hash};
 	use sp_core::traits::SpawnNamed;
 
 	struct AlwaysSupportsParachains;
@@ -413,6 +556,214 @@ mod tests {
 		));
 	}
 
+	// Records `CollateOn` messages and the conclude signal only, and the responses to all
+	// requests.
+	struct CollateOnCodec;
+
+	impl ResponseCodec<AllMessages> for CollateOnCodec {
+		fn intercept_response(&self, message: &mut AllMessages) -> Option<ResponseChannel> {
+			message.intercept_response()
+		}
+	}
+
+	impl RecordCodec<CollatorProtocolMessage, OverseerSignal> for CollateOnCodec {
+		fn encode_signal(&self, signal: &OverseerSignal) -> Option<Vec<u8>> {
+			match signal {
+				OverseerSignal::Conclude => Some(Vec::new()),
+				_ => None,
+			}
+		}
+
+		fn encode_message(&self, message: &CollatorProtocolMessage) -> Option<Vec<u8>> {
+			match message {
+				CollatorProtocolMessage::CollateOn(para_id) =>
+					Some(u32::from(*para_id).to_le_bytes().to_vec()),
+				_ => None,
+			}
+		}
+
+		fn decode_signal(&self, payload: &[u8]) -> Option<OverseerSignal> {
+			if payload.is_empty() {
+				Some(OverseerSignal::Conclude)
+			} else {
+				None
+			}
+		}
+
+		fn decode_message(&self, payload: &[u8]) -> Option<CollatorProtocolMessage> {
+			let para_id = u32::from_le_bytes(payload.try_into().ok()?);
+			Some(CollatorProtocolMessage::CollateOn(para_id.into()))
+		}
+	}
+
+	#[test]
+	fn replay_recording_delivers_in_order() {
+		let mut recorder = Recorder::new(Vec::new(), CollateOnCodec).unwrap();
+		for para_id in [1u32, 2, 3] {
+			recorder
+				.record(&FromOrchestra::Communication {
+					msg: CollatorProtocolMessage::CollateOn(para_id.into()),
+				})
+				.unwrap();
+		}
+		let recording = recorder.into_inner().unwrap();
+
+		let (tx, rx) = mpsc::channel(8);
+		let sent = replay_recording(
+			ForwardSubsystem(tx),
+			&recording[..],
+			&CollateOnCodec,
+			Duration::from_secs(5),
+		)
+		.unwrap();
+		assert!(sent.is_empty());
+
+		let delivered = block_on(rx.collect::<Vec<_>>())
+			.into_iter()
+			.map(|msg| match msg {
+				CollatorProtocolMessage::CollateOn(para_id) => u32::from(para_id),
+				other => panic!("Unexpected message {:?}", other),
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(delivered, vec![1, 2, 3]);
+	}
+
+	#[test]
+	fn replay_recording_rejects_undecodable_entries() {
+		// A signal entry the codec doesn't know.
+		let mut recording = 0u64.to_le_bytes().to_vec();
+		recording.push(0);
+		recording.extend_from_slice(&1u32.to_le_bytes());
+		recording.push(1);
+
+		let (tx, _rx) = mpsc::channel(8);
+		let result = replay_recording(
+			ForwardSubsystem(tx),
+			&recording[..],
+			&CollateOnCodec,
+			Duration::from_secs(5),
+		);
+		assert!(matches!(result, Err(ReplayError::Undecodable(0))));
+
+		// A response entry without the request index.
+		let mut recording = 0u64.to_le_bytes().to_vec();
+		recording.push(2);
+		recording.extend_from_slice(&1u32.to_le_bytes());
+		recording.push(1);
+
+		let (tx, _rx) = mpsc::channel(8);
+		let result = replay_recording(
+			ForwardSubsystem(tx),
+			&recording[..],
+			&CollateOnCodec,
+			Duration::from_secs(5),
+		);
+		assert!(matches!(result, Err(ReplayError::Undecodable(0))));
+	}
+
+	// Requests the finalized block number on every `CollateOn` and passes on the response.
+	struct FinalizedNumberSubsystem(mpsc::UnboundedSender<Option<BlockNumber>>);
+
+	impl<Context> overseer::Subsystem<Context, SubsystemError> for FinalizedNumberSubsystem
+	where
+		Context: overseer::SubsystemContext<
+			Message = CollatorProtocolMessage,
+			Signal = OverseerSignal,
+			Error = SubsystemError,
+		>,
+		Context::Sender: overseer::SubsystemSender<ChainApiMessage>,
+	{
+		fn start(self, mut ctx: Context) -> SpawnedSubsystem {
+			let future = Box::pin(async move {
+				loop {
+					match ctx.recv().await? {
+						FromOrchestra::Signal(OverseerSignal::Conclude) => return Ok(()),
+						FromOrchestra::Communication {
+							msg: CollatorProtocolMessage::CollateOn(_),
+						} => {
+							let (tx, rx) = oneshot::channel();
+							ctx.sender()
+								.send_message(ChainApiMessage::FinalizedBlockNumber(tx))
+								.await;
+							let _ = self.0.unbounded_send(rx.await.ok().and_then(Result::ok));
+						},
+						_ => {},
+					}
+				}
+			});
+
+			SpawnedSubsystem { name: "finalized-number-subsystem", future }
+		}
+	}
+
+	// The response to a `FinalizedBlockNumber` request, as recorded.
+	fn recorded_finalized_number(number: BlockNumber) -> Vec<u8> {
+		let (tx, _rx) = oneshot::channel();
+		let mut request = AllMessages::ChainApi(ChainApiMessage::FinalizedBlockNumber(tx));
+		let response = CollateOnCodec.intercept_response(&mut request).unwrap().record();
+		match request {
+			AllMessages::ChainApi(ChainApiMessage::FinalizedBlockNumber(tx)) =>
+				tx.send(Ok(number)).unwrap(),
+			_ => unreachable!(),
+		}
+		block_on(response).unwrap()
+	}
+
+	#[test]
+	fn replay_recording_answers_requests() {
+		let mut recorder = Recorder::new(Vec::new(), CollateOnCodec).unwrap();
+		recorder
+			.record(&FromOrchestra::Communication {
+				msg: CollatorProtocolMessage::CollateOn(1u32.into()),
+			})
+			.unwrap();
+		recorder.record_response(0, Some(&recorded_finalized_number(5))).unwrap();
+		recorder
+			.record(&FromOrchestra::Communication {
+				msg: CollatorProtocolMessage::CollateOn(2u32.into()),
+			})
+			.unwrap();
+		// The second request was dropped.
+		recorder.record_response(1, None).unwrap();
+		let recording = recorder.into_inner().unwrap();
+
+		let (tx, rx) = mpsc::unbounded();
+		let sent = replay_recording(
+			FinalizedNumberSubsystem(tx),
+			&recording[..],
+			&CollateOnCodec,
+			Duration::from_secs(5),
+		)
+		.unwrap();
+		assert_eq!(sent.len(), 2);
+		assert!(sent.iter().all(|msg| msg.contains("FinalizedBlockNumber")));
+		assert_eq!(block_on(rx.collect::<Vec<_>>()), vec![Some(5), None]);
+	}
+
+	#[test]
+	fn replay_recording_respects_timestamps() {
+		let elapsed = Duration::from_millis(200);
+		let payload = CollateOnCodec
+			.encode_message(&CollatorProtocolMessage::CollateOn(1u32.into()))
+			.unwrap();
+		let mut recording = (elapsed.as_micros() as u64).to_le_bytes().to_vec();
+		recording.push(1);
+		recording.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+		recording.extend_from_slice(&payload);
+
+		let (tx, rx) = mpsc::channel(8);
+		let started = Instant::now();
+		replay_recording(
+			ForwardSubsystem(tx),
+			&recording[..],
+			&CollateOnCodec,
+			Duration::from_secs(5),
+		)
+		.unwrap();
+		assert!(started.elapsed() >= elapsed);
+		assert_eq!(block_on(rx.collect::<Vec<_>>()).len(), 1);
+	}
+
 	#[test]
 	fn macro_arbitrary_order() {
 		let mut vals = vec![Some(15_usize), None];
@@ -29,8 +29,8 @@ use thiserror::Error;
 pub use sc_network::IfDisconnected;
 
 use polkadot_node_network_protocol::{
-	self as net_protocol, peer_set::PeerSet, request_response::Requests, PeerId,
-	UnifiedReputationChange,
+	self as net_protocol, peer_set::PeerSet, request_response::Requests, PeerId, ProtocolVersion,
+	UnifiedReputationChange, View,
 };
 use polkadot_node_primitives::{
 	approval::{BlockApprovalMeta, IndirectAssignmentCert, IndirectSignedApprovalVote},
@@ -51,7 +51,7 @@ use polkadot_statement_table::v2::Misbehavior;
 use std::{
 	collections::{BTreeMap, HashMap, HashSet},
 	sync::Arc,
-	time::Duration,
+	time::{Duration, SystemTime},
 };
 
 /// Network events as transmitted to other subsystems, wrapped in their message types.
@@ -181,6 +181,39 @@ impl CandidateValidationMessage {
 	}
 }
 
+/// How far the distribution of a collation has progressed, as seen by the collator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollationDistributionStatus {
+	/// The collation was not advertised to any validator of the backing group yet. Usually this
+	/// means we are not connected to any of them.
+	Created,
+	/// The collation was advertised, but no validator requested it.
+	Advertised,
+	/// The collation was requested by at least one validator, but not seconded (yet).
+	Requested,
+	/// The collation was seconded by at least one validator.
+	Seconded,
+}
+
+/// Information about a collation currently being distributed by the collator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollationDistributionInfo {
+	/// The relay parent of the collation.
+	pub relay_parent: Hash,
+	/// The hash of the candidate.
+	pub candidate_hash: CandidateHash,
+	/// The current status of the collation.
+	pub status: CollationDistributionStatus,
+	/// Number of validators in the backing group assigned to our para.
+	pub group_size: usize,
+	/// Number of validators of that group we are connected to.
+	pub connected_validators: usize,
+	/// Number of validators we advertised the collation to.
+	pub advertised_to: usize,
+	/// Number of validators that requested the collation.
+	pub requested_by: usize,
+}
+
 /// Messages received by the Collator Protocol subsystem.
 #[derive(Debug, derive_more::From)]
 pub enum CollatorProtocolMessage {
@@ -210,6 +243,11 @@ pub enum CollatorProtocolMessage {
 	///
 	/// The hash is the relay parent.
 	Seconded(Hash, SignedFullStatement),
+	/// Get the distribution status of all collations we are currently distributing as a collator.
+	///
+	/// Collators can expose this, to help operators figure out why their collations are not being
+	/// backed.
+	QueryCollationStatus(oneshot::Sender<Vec<CollationDistributionInfo>>),
 }
 
 impl Default for CollatorProtocolMessage {
@@ -375,6 +413,10 @@ pub enum NetworkBridgeMessage {
 		/// The peer set we want the connection on.
 		peer_set: PeerSet,
 	},
+	/// Get information about all connected peers and the validators we fail to reach.
+	GetPeerReport(oneshot::Sender<PeerReport>),
+	/// Set the connection policy for the given authority, or clear it with `None`.
+	SetPeerPolicy(AuthorityDiscoveryId, Option<PeerPolicy>),
 	/// Inform the distribution subsystems about the new
 	/// gossip network topology formed.
 	NewGossipTopology {
@@ -408,11 +450,48 @@ impl NetworkBridgeMessage {
 			Self::ConnectToValidators { .. } => None,
 			Self::ConnectToResolvedValidators { .. } => None,
 			Self::SendRequests { .. } => None,
+			Self::GetPeerReport(_) => None,
+			Self::SetPeerPolicy(_, _) => None,
 			Self::NewGossipTopology { .. } => None,
 		}
 	}
 }
 
+/// Connection policy for an authority, as configured by the node operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerPolicy {
+	/// Always try to stay connected to the authority on the validation peer-set.
+	Pinned,
+	/// Never connect to the authority and disconnect it on all peer-sets.
+	Banned,
+}
+
+/// Information about a peer connected on one of our peer-sets.
+#[derive(Debug, Clone)]
+pub struct PeerInfo {
+	/// The peer.
+	pub peer_id: PeerId,
+	/// The peer-set the peer is connected on.
+	pub peer_set: PeerSet,
+	/// The negotiated protocol version.
+	pub version: ProtocolVersion,
+	/// The latest view sent by the peer.
+	pub view: View,
+	/// The authority ids of the peer, if it is an authority.
+	pub authority_ids: Option<HashSet<AuthorityDiscoveryId>>,
+	/// Reputation changes reported for the peer since it connected, accumulated by reason.
+	pub reputation_changes: HashMap<&'static str, i64>,
+}
+
+/// A snapshot of the peers known to the network bridge.
+#[derive(Debug, Clone, Default)]
+pub struct PeerReport {
+	/// All connected peers, once per peer-set they are connected on.
+	pub peers: Vec<PeerInfo>,
+	/// Validators we were asked to connect to, but are not connected to.
+	pub unreachable_validators: Vec<(PeerSet, AuthorityDiscoveryId)>,
+}
+
 /// Availability Distribution Message.
 #[derive(Debug)]
 pub enum AvailabilityDistributionMessage {
@@ -486,6 +565,12 @@ pub enum AvailabilityStoreMessage {
 	/// Query a `AvailableData` from the AV store.
 	QueryAvailableData(CandidateHash, oneshot::Sender<Option<AvailableData>>),
 
+	/// Query the `AvailableData` of a historic candidate from the AV store archive.
+	///
+	/// Only candidates finalized within the configured number of archived sessions are served,
+	/// and only if the archive is enabled. Returns `None` otherwise.
+	QueryArchivedAvailableData(CandidateHash, oneshot::Sender<Option<AvailableData>>),
+
 	/// Query whether a `AvailableData` exists within the AV Store.
 	///
 	/// This is useful in cases when existence
@@ -892,6 +977,26 @@ pub enum ApprovalVotingMessage {
 	///
 	/// Should not be sent unless the block hash within the indirect vote is known.
 	CheckAndImportApproval(IndirectSignedApprovalVote, oneshot::Sender<ApprovalCheckResult>),
+	/// Check a batch of assignments and import the valid ones, like
+	/// [`ApprovalVotingMessage::CheckAndImportAssignment`] would for each of them.
+	///
+	/// The gossiped VRF proofs can't be batch-verified like approval signatures, so the
+	/// certificates of a large batch are instead checked in parallel.
+	/// The results are returned in the same order as the assignments.
+	CheckAndImportAssignments(
+		Vec<(IndirectAssignmentCert, CandidateIndex)>,
+		oneshot::Sender<Vec<AssignmentCheckResult>>,
+	),
+	/// Check a batch of approval votes and import the valid ones, like
+	/// [`ApprovalVotingMessage::CheckAndImportApproval`] would for each of them.
+	///
+	/// The signatures are verified together and only checked individually
+	/// if the batch fails to verify. The results are returned in the same order
+	/// as the approval votes.
+	CheckAndImportApprovals(
+		Vec<IndirectSignedApprovalVote>,
+		oneshot::Sender<Vec<ApprovalCheckResult>>,
+	),
 	/// Returns the highest possible ancestor hash of the provided block hash which is
 	/// acceptable to vote on finality for.
 	/// The `BlockNumber` provided is the number of the block's ancestor which is the
@@ -926,6 +1031,32 @@ pub enum GossipSupportMessage {
 	/// Dummy constructor, so we can receive networking events.
 	#[from]
 	NetworkBridgeUpdate(NetworkBridgeEvent<net_protocol::GossipSupportNetworkMessage>),
+	/// Get a report on our connectivity to the authorities we want to be connected to.
+	GetConnectivityReport(oneshot::Sender<ConnectivityReport>),
+}
+
+/// Connectivity to the authorities of the past, present and future sessions.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectivityReport {
+	/// The latest session we issued a connection request for.
+	pub session: Option<SessionIndex>,
+	/// All authorities we want to be connected to.
+	pub authorities: Vec<AuthorityConnectivity>,
+}
+
+/// Connectivity to a single authority.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorityConnectivity {
+	/// The authority.
+	pub authority: AuthorityDiscoveryId,
+	/// The addresses authority discovery resolved for the authority, empty if it failed.
+	pub addresses: HashSet<Multiaddr>,
+	/// Whether we are currently connected to the authority.
+	pub connected: bool,
+	/// The last time we were connected to the authority, if ever.
+	pub last_connected: Option<SystemTime>,
+	/// How often we failed to resolve or connect to the authority since it was last connected.
+	pub failures: u32,
 }
 
 /// PVF checker message.
@@ -101,6 +101,7 @@ pub fn new_full(
 		false,
 		polkadot_service::RealOverseerGen,
 		None,
+		Default::default(),
 		None,
 	)
 }
@@ -151,6 +152,11 @@ pub fn node_config(
 		"network/test/0.1",
 		Default::default(),
 		None,
+		Default::default(),
+		None,
+		Default::default(),
+		Default::default(),
+		None,
 	);
 
 	network_config.boot_nodes = boot_nodes;
@@ -71,6 +71,7 @@ fn main() -> Result<()> {
 							false,
 							polkadot_service::RealOverseerGen,
 							None,
+							Default::default(),
 							None,
 						)
 						.map_err(|e| e.to_string())?;
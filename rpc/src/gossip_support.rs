@@ -0,0 +1,120 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC interface to the connectivity report of the gossip support subsystem.
+
+use std::time::UNIX_EPOCH;
+
+use futures::channel::oneshot;
+use jsonrpsee::{
+	core::{async_trait, Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+};
+use polkadot_node_subsystem_types::messages::{self, GossipSupportMessage};
+use polkadot_overseer::Handle;
+use polkadot_primitives::v2::{AuthorityDiscoveryId, SessionIndex};
+use sc_rpc::DenyUnsafe;
+use serde::{Deserialize, Serialize};
+
+/// Connectivity to the authorities of the past, present and future sessions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectivityReport {
+	/// The latest session a connection request was issued for.
+	pub session: Option<SessionIndex>,
+	/// All authorities we want to be connected to.
+	pub authorities: Vec<AuthorityConnectivity>,
+}
+
+/// Connectivity to a single authority.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorityConnectivity {
+	/// The authority.
+	pub authority: AuthorityDiscoveryId,
+	/// The addresses authority discovery resolved for the authority.
+	pub addresses: Vec<String>,
+	/// Whether we are currently connected to the authority.
+	pub connected: bool,
+	/// The last time we were connected to the authority, in milliseconds since the UNIX epoch.
+	pub last_connected: Option<u64>,
+	/// How often we failed to resolve or connect to the authority since it was last connected.
+	pub failures: u32,
+}
+
+impl From<messages::ConnectivityReport> for ConnectivityReport {
+	fn from(report: messages::ConnectivityReport) -> Self {
+		let authorities = report
+			.authorities
+			.into_iter()
+			.map(|a| AuthorityConnectivity {
+				authority: a.authority,
+				addresses: a.addresses.iter().map(ToString::to_string).collect(),
+				connected: a.connected,
+				last_connected: a
+					.last_connected
+					.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+					.map(|d| d.as_millis() as u64),
+				failures: a.failures,
+			})
+			.collect();
+
+		ConnectivityReport { session: report.session, authorities }
+	}
+}
+
+/// Gossip support RPC methods.
+#[rpc(server)]
+pub trait GossipSupportApi {
+	/// Report on our connectivity to the authorities of the past, present and future sessions.
+	///
+	/// Lists every authority with the addresses resolved via authority discovery, the last time
+	/// we were connected to it and the number of failed attempts since.
+	#[method(name = "parachain_connectivityReport")]
+	async fn connectivity_report(&self) -> RpcResult<ConnectivityReport>;
+}
+
+/// Implements the [`GossipSupportApiServer`] RPC trait.
+pub struct GossipSupport {
+	overseer_handle: Handle,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl GossipSupport {
+	/// Create a new instance of the gossip support RPC.
+	pub fn new(overseer_handle: Handle, deny_unsafe: DenyUnsafe) -> Self {
+		Self { overseer_handle, deny_unsafe }
+	}
+}
+
+#[async_trait]
+impl GossipSupportApiServer for GossipSupport {
+	async fn connectivity_report(&self) -> RpcResult<ConnectivityReport> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let (tx, rx) = oneshot::channel();
+		self.overseer_handle
+			.clone()
+			.send_msg(GossipSupportMessage::GetConnectivityReport(tx), "rpc")
+			.await;
+
+		let report = rx
+			.await
+			.map_err(|_| JsonRpseeError::Custom("Gossip support is not running".into()))?;
+
+		Ok(report.into())
+	}
+}
@@ -34,6 +34,10 @@ use sp_consensus_babe::BabeApi;
 use sp_keystore::SyncCryptoStorePtr;
 use txpool_api::TransactionPool;
 
+mod gossip_support;
+
+pub use gossip_support::{AuthorityConnectivity, ConnectivityReport};
+
 /// A type representing all RPC extensions.
 pub type RpcExtension = RpcModule<()>;
 
@@ -90,6 +94,8 @@ pub struct FullDeps<C, P, SC, B> {
 	pub grandpa: GrandpaDeps<B>,
 	/// BEEFY specific dependencies.
 	pub beefy: BeefyDeps,
+	/// Handle to the overseer, if the node runs one.
+	pub overseer_handle: Option<polkadot_overseer::Handle>,
 }
 
 /// Instantiate all RPC extensions.
@@ -115,6 +121,7 @@ where
 	B: sc_client_api::Backend<Block> + Send + Sync + 'static,
 	B::State: sc_client_api::StateBackend<sp_runtime::traits::HashFor<Block>>,
 {
+	use crate::gossip_support::{GossipSupport, GossipSupportApiServer};
 	use beefy_gadget_rpc::{Beefy, BeefyApiServer};
 	use frame_rpc_system::{System, SystemApiServer};
 	use pallet_mmr_rpc::{Mmr, MmrApiServer};
@@ -125,8 +132,17 @@ where
 	use substrate_state_trie_migration_rpc::{StateMigration, StateMigrationApiServer};
 
 	let mut io = RpcModule::new(());
-	let FullDeps { client, pool, select_chain, chain_spec, deny_unsafe, babe, grandpa, beefy } =
-		deps;
+	let FullDeps {
+		client,
+		pool,
+		select_chain,
+		chain_spec,
+		deny_unsafe,
+		babe,
+		grandpa,
+		beefy,
+		overseer_handle,
+	} = deps;
 	let BabeDeps { keystore, babe_config, shared_epoch_changes } = babe;
 	let GrandpaDeps {
 		shared_voter_state,
@@ -174,5 +190,9 @@ where
 		.into_rpc(),
 	)?;
 
+	if let Some(overseer_handle) = overseer_handle {
+		io.merge(GossipSupport::new(overseer_handle, deny_unsafe).into_rpc())?;
+	}
+
 	Ok(io)
 }
@@ -39,6 +39,8 @@ use sp_std::{cmp::Ordering, prelude::*};
 #[allow(unused_imports)]
 pub(crate) use self::tests::run_to_block;
 
+pub mod slashing;
+
 #[cfg(test)]
 mod tests;
 
@@ -78,6 +80,7 @@ pub trait PunishValidators {
 	/// punishment.
 	fn punish_for_invalid(
 		session: SessionIndex,
+		candidate_hash: CandidateHash,
 		validators: impl IntoIterator<Item = ValidatorIndex>,
 	);
 
@@ -85,6 +88,7 @@ pub trait PunishValidators {
 	/// punishment.
 	fn punish_against_valid(
 		session: SessionIndex,
+		candidate_hash: CandidateHash,
 		validators: impl IntoIterator<Item = ValidatorIndex>,
 	);
 
@@ -92,16 +96,32 @@ pub trait PunishValidators {
 	/// to be a minor punishment.
 	fn punish_inconclusive(
 		session: SessionIndex,
+		candidate_hash: CandidateHash,
 		validators: impl IntoIterator<Item = ValidatorIndex>,
 	);
 }
 
 impl PunishValidators for () {
-	fn punish_for_invalid(_: SessionIndex, _: impl IntoIterator<Item = ValidatorIndex>) {}
+	fn punish_for_invalid(
+		_: SessionIndex,
+		_: CandidateHash,
+		_: impl IntoIterator<Item = ValidatorIndex>,
+	) {
+	}
 
-	fn punish_against_valid(_: SessionIndex, _: impl IntoIterator<Item = ValidatorIndex>) {}
+	fn punish_against_valid(
+		_: SessionIndex,
+		_: CandidateHash,
+		_: impl IntoIterator<Item = ValidatorIndex>,
+	) {
+	}
 
-	fn punish_inconclusive(_: SessionIndex, _: impl IntoIterator<Item = ValidatorIndex>) {}
+	fn punish_inconclusive(
+		_: SessionIndex,
+		_: CandidateHash,
+		_: impl IntoIterator<Item = ValidatorIndex>,
+	) {
+	}
 }
 
 /// Binary discriminator to determine if the expensive signature
@@ -817,6 +837,7 @@ impl<T: Config> Pallet<T> {
 					// others in a timely manner.
 					T::PunishValidators::punish_inconclusive(
 						session_index,
+						candidate_hash,
 						participating.iter_ones().map(|i| ValidatorIndex(i as _)),
 					);
 				});
@@ -1166,10 +1187,14 @@ impl<T: Config> Pallet<T> {
 		// Slash participants on a losing side.
 		{
 			// a valid candidate, according to 2/3. Punish those on the 'against' side.
-			T::PunishValidators::punish_against_valid(session, summary.slash_against);
+			T::PunishValidators::punish_against_valid(
+				session,
+				candidate_hash,
+				summary.slash_against,
+			);
 
 			// an invalid candidate, according to 2/3. Punish those on the 'for' side.
-			T::PunishValidators::punish_for_invalid(session, summary.slash_for);
+			T::PunishValidators::punish_for_invalid(session, candidate_hash, summary.slash_for);
 		}
 
 		<Disputes<T>>::insert(&session, &candidate_hash, &summary.state);
@@ -0,0 +1,487 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Dispute slashing pallet.
+//!
+//! Implements [`PunishValidators`] by reporting offences for the validators on the losing side
+//! of a concluded dispute, usually to `pallet-offences` and from there to `pallet-staking`:
+//!
+//! * Validators who voted for an invalid candidate commit a [`ForInvalidOffence`], which is
+//!   slashed with [`SLASH_FOR_INVALID`].
+//! * Validators who voted against a valid candidate commit an [`AgainstValidOffence`], which is
+//!   slashed with [`SLASH_AGAINST_VALID`] and doesn't disable them.
+//! * Validators in an inconclusive dispute are not slashed, they already lose spam slots.
+//!
+//! The offenders are identified right away, while the accounts of the dispute's session are
+//! still known, together with their exposure in the era of that session rather than the active
+//! one. Reporting the offence is deferred by `SlashDeferSessions` sessions, during which
+//! `CancelOrigin` can cancel it. Offences are reported for the session the disputed candidate
+//! was backed in, so that `pallet-staking` slashes in the matching era, possibly deferring the
+//! slash further by its own `SlashDeferDuration`. Reports which don't fit in the
+//! `MaxReportingWeight` of a block are left for the following blocks.
+
+use crate::{disputes::PunishValidators, session_info, shared};
+use frame_support::{ensure, traits::Get, weights::Weight};
+use frame_system::pallet_prelude::*;
+use pallet_session::historical::IdentificationTuple;
+use parity_scale_codec::{Decode, Encode};
+use primitives::v2::{CandidateHash, SessionIndex, ValidatorIndex};
+use scale_info::TypeInfo;
+use sp_runtime::{traits::Convert, Perbill, RuntimeDebug};
+use sp_staking::offence::{DisableStrategy, Kind, Offence, OffenceError, ReportOffence};
+use sp_std::{marker::PhantomData, prelude::*};
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+const LOG_TARGET: &str = "runtime::disputes::slashing";
+
+/// The fraction slashed from validators who voted for an invalid candidate.
+pub const SLASH_FOR_INVALID: Perbill = Perbill::from_percent(100);
+
+/// The fraction slashed from validators who voted against a valid candidate.
+pub const SLASH_AGAINST_VALID: Perbill = Perbill::from_percent(1);
+
+/// The kinds of offences in disputes.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum SlashingOffenceKind {
+	/// Voted for an invalid candidate.
+	ForInvalid,
+	/// Voted against a valid candidate.
+	AgainstValid,
+}
+
+/// The time slot of a dispute offence: the disputed candidate.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug, TypeInfo)]
+pub struct DisputesTimeSlot {
+	/// The session the candidate was backed in.
+	pub session_index: SessionIndex,
+	/// The disputed candidate.
+	pub candidate_hash: CandidateHash,
+}
+
+macro_rules! dispute_offence {
+	(
+		$(#[$attr:meta])*
+		$name:ident, $id:expr, $slash_fraction:expr, $disable_strategy:expr
+	) => {
+		$(#[$attr])*
+		#[derive(RuntimeDebug, Clone, PartialEq, Eq)]
+		pub struct $name<KeyOwnerIdentification> {
+			/// The disputed candidate.
+			pub time_slot: DisputesTimeSlot,
+			/// The number of validators in the session of the dispute.
+			pub validator_set_count: u32,
+			/// The validators on the losing side of the dispute.
+			pub offenders: Vec<KeyOwnerIdentification>,
+		}
+
+		impl<KeyOwnerIdentification: Clone> Offence<KeyOwnerIdentification>
+			for $name<KeyOwnerIdentification>
+		{
+			const ID: Kind = *$id;
+			type TimeSlot = DisputesTimeSlot;
+
+			fn offenders(&self) -> Vec<KeyOwnerIdentification> {
+				self.offenders.clone()
+			}
+
+			fn session_index(&self) -> SessionIndex {
+				self.time_slot.session_index
+			}
+
+			fn validator_set_count(&self) -> u32 {
+				self.validator_set_count
+			}
+
+			fn time_slot(&self) -> Self::TimeSlot {
+				self.time_slot.clone()
+			}
+
+			fn disable_strategy(&self) -> DisableStrategy {
+				$disable_strategy
+			}
+
+			fn slash_fraction(_offenders_count: u32, _validator_set_count: u32) -> Perbill {
+				$slash_fraction
+			}
+		}
+	};
+}
+
+dispute_offence!(
+	/// Voting for an invalid candidate.
+	ForInvalidOffence,
+	b"disputes:invalid",
+	SLASH_FOR_INVALID,
+	DisableStrategy::WhenSlashed
+);
+
+dispute_offence!(
+	/// Voting against a valid candidate.
+	AgainstValidOffence,
+	b"disputes:against",
+	SLASH_AGAINST_VALID,
+	DisableStrategy::Never
+);
+
+/// The offenders of a dispute, waiting to be reported.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct PendingSlashes<KeyOwnerIdentification> {
+	/// The kind of offence.
+	pub kind: SlashingOffenceKind,
+	/// The number of validators in the session of the dispute.
+	pub validator_set_count: u32,
+	/// The validators on the losing side of the dispute.
+	pub offenders: Vec<KeyOwnerIdentification>,
+}
+
+/// Identifies the validators of a session by their account and their exposure in the era of
+/// that session, which is what `pallet-offences` expects for `pallet-staking`.
+///
+/// Unlike `FullIdentificationOf` of `pallet_session::historical`, which looks up the exposure
+/// of the active era, this resolves the era the session belongs to. Sessions of eras which
+/// `pallet-staking` doesn't keep the history of anymore can't be identified.
+pub struct HistoricalIdentification<T>(PhantomData<T>);
+
+impl<T> Convert<(SessionIndex, T::AccountId), Option<IdentificationTuple<T>>>
+	for HistoricalIdentification<T>
+where
+	T: pallet_staking::Config
+		+ pallet_session::historical::Config<
+			FullIdentification = pallet_staking::Exposure<
+				<T as frame_system::Config>::AccountId,
+				pallet_staking::BalanceOf<T>,
+			>,
+		> + pallet_session::Config<ValidatorId = <T as frame_system::Config>::AccountId>,
+{
+	fn convert(
+		(session_index, account): (SessionIndex, T::AccountId),
+	) -> Option<IdentificationTuple<T>> {
+		let active_era = pallet_staking::Pallet::<T>::active_era()?.index;
+		// The era of a session is the latest era starting at or before it.
+		let (era, _) = (0..=active_era)
+			.rev()
+			.map_while(|era| {
+				Some((era, pallet_staking::Pallet::<T>::eras_start_session_index(era)?))
+			})
+			.find(|(_, start_session_index)| *start_session_index <= session_index)?;
+
+		let exposure = pallet_staking::Pallet::<T>::eras_stakers(era, &account);
+		Some((account, exposure))
+	}
+}
+
+pub trait WeightInfo {
+	fn cancel_slash() -> Weight;
+	fn report_offence(n: u32) -> Weight;
+}
+
+pub struct TestWeightInfo;
+impl WeightInfo for TestWeightInfo {
+	fn cancel_slash() -> Weight {
+		0
+	}
+	// One unit per offender, so that tests can bound the reports per block.
+	fn report_offence(n: u32) -> Weight {
+		n as Weight
+	}
+}
+
+pub use pallet::*;
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + session_info::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The identification of an offender, as expected by `HandleReports`.
+		type KeyOwnerIdentification: Parameter;
+
+		/// Identifies a validator of the given session by the account it has in `session_info`.
+		type IdentifyValidator: Convert<
+			(SessionIndex, session_info::AccountId<Self>),
+			Option<Self::KeyOwnerIdentification>,
+		>;
+
+		/// The handler of offence reports, usually `pallet-offences`.
+		type HandleReports: ReportOffence<
+				Self::AccountId,
+				Self::KeyOwnerIdentification,
+				ForInvalidOffence<Self::KeyOwnerIdentification>,
+			> + ReportOffence<
+				Self::AccountId,
+				Self::KeyOwnerIdentification,
+				AgainstValidOffence<Self::KeyOwnerIdentification>,
+			>;
+
+		/// The number of sessions a slash is deferred by before its offence is reported.
+		///
+		/// Together with the age of disputes and the `SlashDeferDuration` of `pallet-staking`,
+		/// this needs to stay within the bonding duration, or the slash is lost.
+		#[pallet::constant]
+		type SlashDeferSessions: Get<SessionIndex>;
+
+		/// The maximum weight spent on reporting offences at the start of a block.
+		///
+		/// The offences of at least one dispute are reported per block, even if they exceed it.
+		#[pallet::constant]
+		type MaxReportingWeight: Get<Weight>;
+
+		/// The origin which can cancel a slash before its offence is reported.
+		type CancelOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::pallet]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	/// The slashes of disputes which are not reported yet, by the session of the candidate.
+	#[pallet::storage]
+	pub(super) type UnappliedSlashes<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		SessionIndex,
+		Blake2_128Concat,
+		CandidateHash,
+		Vec<PendingSlashes<T::KeyOwnerIdentification>>,
+	>;
+
+	/// The disputes whose slashes are to be reported at the start of a session.
+	#[pallet::storage]
+	pub(super) type SlashesDue<T> =
+		StorageMap<_, Twox64Concat, SessionIndex, Vec<(SessionIndex, CandidateHash)>, ValueQuery>;
+
+	/// The disputes whose slashes are due but not reported yet, oldest first.
+	#[pallet::storage]
+	pub(super) type SlashesToReport<T> =
+		StorageValue<_, Vec<(SessionIndex, CandidateHash)>, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Validators on the losing side of a dispute are going to be slashed.
+		/// `\[session index, candidate hash, offence kind\]`
+		SlashDeferred(SessionIndex, CandidateHash, SlashingOffenceKind),
+		/// The offence of validators on the losing side of a dispute has been reported.
+		/// `\[session index, candidate hash, offence kind\]`
+		SlashReported(SessionIndex, CandidateHash, SlashingOffenceKind),
+		/// The slashes of a dispute have been cancelled.
+		/// `\[session index, candidate hash\]`
+		SlashCancelled(SessionIndex, CandidateHash),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// There are no unapplied slashes for the dispute.
+		NoUnappliedSlash,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_now: T::BlockNumber) -> Weight {
+			let session_index = shared::Pallet::<T>::session_index();
+			let mut weight = T::DbWeight::get().reads(3);
+
+			let mut to_report = SlashesToReport::<T>::get();
+			if SlashesDue::<T>::contains_key(session_index) {
+				to_report.extend(SlashesDue::<T>::take(session_index));
+				weight = weight.saturating_add(T::DbWeight::get().writes(1));
+			}
+			if to_report.is_empty() {
+				return weight
+			}
+
+			let max_weight = T::MaxReportingWeight::get();
+			let mut reported = 0;
+			for &(session_index, candidate_hash) in &to_report {
+				weight = weight.saturating_add(T::DbWeight::get().reads(1));
+				// Cancelled if missing.
+				let slashes =
+					UnappliedSlashes::<T>::get(session_index, candidate_hash).unwrap_or_default();
+				let report_weight = Self::report_weight(&slashes);
+				if reported > 0 && weight.saturating_add(report_weight) > max_weight {
+					break
+				}
+
+				UnappliedSlashes::<T>::remove(session_index, candidate_hash);
+				Self::report_slashes(session_index, candidate_hash, slashes);
+				weight = weight
+					.saturating_add(report_weight)
+					.saturating_add(T::DbWeight::get().writes(1));
+				reported += 1;
+			}
+
+			to_report.drain(..reported);
+			if to_report.is_empty() {
+				SlashesToReport::<T>::kill();
+			} else {
+				SlashesToReport::<T>::put(to_report);
+			}
+
+			weight.saturating_add(T::DbWeight::get().writes(1))
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Cancel all slashes of a dispute whose offences are not reported yet.
+		#[pallet::weight(<T as Config>::WeightInfo::cancel_slash())]
+		pub fn cancel_slash(
+			origin: OriginFor<T>,
+			session_index: SessionIndex,
+			candidate_hash: CandidateHash,
+		) -> DispatchResult {
+			T::CancelOrigin::ensure_origin(origin)?;
+
+			ensure!(
+				UnappliedSlashes::<T>::contains_key(session_index, candidate_hash),
+				Error::<T>::NoUnappliedSlash,
+			);
+			UnappliedSlashes::<T>::remove(session_index, candidate_hash);
+
+			Self::deposit_event(Event::SlashCancelled(session_index, candidate_hash));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	fn defer_slash(
+		session_index: SessionIndex,
+		candidate_hash: CandidateHash,
+		kind: SlashingOffenceKind,
+		validators: impl IntoIterator<Item = ValidatorIndex>,
+	) {
+		let account_keys = match session_info::Pallet::<T>::account_keys(session_index) {
+			Some(account_keys) => account_keys,
+			None => {
+				log::warn!(
+					target: LOG_TARGET,
+					"Missing account keys of session {}, can't slash for dispute of {:?}",
+					session_index,
+					candidate_hash,
+				);
+				return
+			},
+		};
+
+		let offenders: Vec<_> = validators
+			.into_iter()
+			.filter_map(|i| account_keys.get(i.0 as usize).cloned())
+			.filter_map(|account| T::IdentifyValidator::convert((session_index, account)))
+			.collect();
+		if offenders.is_empty() {
+			return
+		}
+
+		let pending =
+			PendingSlashes { kind, validator_set_count: account_keys.len() as u32, offenders };
+		let due = shared::Pallet::<T>::session_index().saturating_add(T::SlashDeferSessions::get());
+
+		UnappliedSlashes::<T>::mutate(session_index, candidate_hash, |slashes| {
+			if slashes.is_none() {
+				SlashesDue::<T>::append(due, (session_index, candidate_hash));
+			}
+			slashes.get_or_insert_with(Vec::new).push(pending);
+		});
+
+		Self::deposit_event(Event::SlashDeferred(session_index, candidate_hash, kind));
+	}
+
+	fn report_weight(slashes: &[PendingSlashes<T::KeyOwnerIdentification>]) -> Weight {
+		slashes.iter().fold(0, |weight: Weight, pending| {
+			weight.saturating_add(T::WeightInfo::report_offence(pending.offenders.len() as u32))
+		})
+	}
+
+	fn report_slashes(
+		session_index: SessionIndex,
+		candidate_hash: CandidateHash,
+		slashes: Vec<PendingSlashes<T::KeyOwnerIdentification>>,
+	) {
+		for PendingSlashes { kind, validator_set_count, offenders } in slashes {
+			let time_slot = DisputesTimeSlot { session_index, candidate_hash };
+			let result = match kind {
+				SlashingOffenceKind::ForInvalid => Self::report_offence(ForInvalidOffence {
+					time_slot,
+					validator_set_count,
+					offenders,
+				}),
+				SlashingOffenceKind::AgainstValid => Self::report_offence(AgainstValidOffence {
+					time_slot,
+					validator_set_count,
+					offenders,
+				}),
+			};
+
+			match result {
+				Ok(()) =>
+					Self::deposit_event(Event::SlashReported(session_index, candidate_hash, kind)),
+				Err(OffenceError::DuplicateReport) => {},
+				Err(e) => log::warn!(
+					target: LOG_TARGET,
+					"Failed to report {:?} offence for dispute of {:?}: {:?}",
+					kind,
+					candidate_hash,
+					e,
+				),
+			}
+		}
+	}
+
+	fn report_offence<O>(offence: O) -> Result<(), OffenceError>
+	where
+		O: Offence<T::KeyOwnerIdentification>,
+		T::HandleReports: ReportOffence<T::AccountId, T::KeyOwnerIdentification, O>,
+	{
+		<T::HandleReports as ReportOffence<_, _, O>>::report_offence(Vec::new(), offence)
+	}
+}
+
+impl<T: Config> PunishValidators for Pallet<T> {
+	fn punish_for_invalid(
+		session: SessionIndex,
+		candidate_hash: CandidateHash,
+		validators: impl IntoIterator<Item = ValidatorIndex>,
+	) {
+		Self::defer_slash(session, candidate_hash, SlashingOffenceKind::ForInvalid, validators)
+	}
+
+	fn punish_against_valid(
+		session: SessionIndex,
+		candidate_hash: CandidateHash,
+		validators: impl IntoIterator<Item = ValidatorIndex>,
+	) {
+		Self::defer_slash(session, candidate_hash, SlashingOffenceKind::AgainstValid, validators)
+	}
+
+	fn punish_inconclusive(
+		_session: SessionIndex,
+		_candidate_hash: CandidateHash,
+		_validators: impl IntoIterator<Item = ValidatorIndex>,
+	) {
+	}
+}
@@ -0,0 +1,68 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+
+use frame_benchmarking::{account, benchmarks};
+use frame_support::traits::EnsureOrigin;
+
+// The most validators a dispute can slash.
+const MAX_OFFENDERS: u32 = 1_000;
+
+benchmarks! {
+	where_clause {
+		where session_info::AccountId<T>: From<T::AccountId>,
+	}
+
+	cancel_slash {
+		let session_index = 1;
+		let candidate_hash = CandidateHash(Default::default());
+		let pending = PendingSlashes {
+			kind: SlashingOffenceKind::ForInvalid,
+			validator_set_count: 0,
+			offenders: Vec::new(),
+		};
+		UnappliedSlashes::<T>::insert(session_index, candidate_hash, vec![pending]);
+		let origin = T::CancelOrigin::successful_origin();
+	}: _<T::Origin>(origin, session_index, candidate_hash)
+	verify {
+		assert!(UnappliedSlashes::<T>::get(session_index, candidate_hash).is_none())
+	}
+
+	report_offence {
+		let n in 1 .. MAX_OFFENDERS;
+
+		let session_index = shared::Pallet::<T>::session_index();
+		let candidate_hash = CandidateHash(Default::default());
+		let offenders = (0..n)
+			.map(|i| account::<T::AccountId>("offender", i, 0).into())
+			.filter_map(|account| T::IdentifyValidator::convert((session_index, account)))
+			.collect();
+		let pending = PendingSlashes {
+			kind: SlashingOffenceKind::ForInvalid,
+			validator_set_count: n,
+			offenders,
+		};
+	}: {
+		Pallet::<T>::report_slashes(session_index, candidate_hash, vec![pending]);
+	}
+
+	impl_benchmark_test_suite!(
+		Pallet,
+		crate::mock::new_test_ext(Default::default()),
+		crate::mock::Test
+	);
+}
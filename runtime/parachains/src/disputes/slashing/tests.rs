@@ -0,0 +1,173 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::mock::{
+	assert_last_event, new_test_ext, MockGenesisConfig, Origin, ParasSlashing, SlashDeferSessions,
+	Test, REPORTED_OFFENCES, SESSIONS_PER_ERA,
+};
+use frame_support::{assert_noop, assert_ok, traits::OnInitialize};
+use sp_runtime::DispatchError;
+use sp_staking::EraIndex;
+
+const DISPUTE_SESSION: SessionIndex = 2;
+
+fn candidate_hash() -> CandidateHash {
+	CandidateHash([1; 32].into())
+}
+
+fn reported_offences() -> Vec<(Kind, SessionIndex, Vec<(u64, EraIndex)>, Perbill)> {
+	REPORTED_OFFENCES.with(|r| r.borrow().clone())
+}
+
+// Start the given session with the dispute session's validators being the accounts 10 to 14.
+fn start_session(session_index: SessionIndex) {
+	session_info::AccountKeys::<Test>::insert(DISPUTE_SESSION, vec![10, 11, 12, 13, 14]);
+	shared::Pallet::<Test>::set_session_index(session_index);
+	ParasSlashing::on_initialize(0);
+}
+
+fn indices(indices: &[u32]) -> Vec<ValidatorIndex> {
+	indices.iter().copied().map(ValidatorIndex).collect()
+}
+
+#[test]
+fn slashes_are_reported_after_deferral() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		start_session(3);
+
+		ParasSlashing::punish_for_invalid(DISPUTE_SESSION, candidate_hash(), indices(&[0, 2]));
+		ParasSlashing::punish_against_valid(DISPUTE_SESSION, candidate_hash(), indices(&[4]));
+		assert_last_event(
+			Event::<Test>::SlashDeferred(
+				DISPUTE_SESSION,
+				candidate_hash(),
+				SlashingOffenceKind::AgainstValid,
+			)
+			.into(),
+		);
+
+		start_session(4);
+		assert!(reported_offences().is_empty());
+
+		start_session(5);
+		assert_eq!(
+			reported_offences(),
+			vec![
+				(*b"disputes:invalid", DISPUTE_SESSION, vec![(10, 0), (12, 0)], SLASH_FOR_INVALID),
+				(*b"disputes:against", DISPUTE_SESSION, vec![(14, 0)], SLASH_AGAINST_VALID),
+			],
+		);
+		assert!(UnappliedSlashes::<Test>::get(DISPUTE_SESSION, candidate_hash()).is_none());
+
+		// Nothing is reported twice.
+		start_session(6);
+		assert_eq!(reported_offences().len(), 2);
+	});
+}
+
+#[test]
+fn reports_exceeding_the_block_weight_are_left_for_later_blocks() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		start_session(3);
+
+		// Every dispute weighs 3 with the test weights, so only one fits in a block.
+		let candidates: Vec<_> = (1..=3).map(|i| CandidateHash([i; 32].into())).collect();
+		for candidate_hash in &candidates {
+			ParasSlashing::punish_for_invalid(
+				DISPUTE_SESSION,
+				*candidate_hash,
+				indices(&[0, 1, 2]),
+			);
+		}
+
+		start_session(5);
+		assert_eq!(reported_offences().len(), 1);
+		assert_eq!(
+			SlashesToReport::<Test>::get(),
+			vec![(DISPUTE_SESSION, candidates[1]), (DISPUTE_SESSION, candidates[2])],
+		);
+
+		ParasSlashing::on_initialize(1);
+		assert_eq!(reported_offences().len(), 2);
+
+		// Leftovers are still reported in a later session.
+		start_session(6);
+		assert_eq!(reported_offences().len(), 3);
+		assert!(SlashesToReport::<Test>::get().is_empty());
+		assert!(UnappliedSlashes::<Test>::iter().next().is_none());
+	});
+}
+
+#[test]
+fn cancelled_slashes_are_not_reported() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		start_session(3);
+
+		ParasSlashing::punish_for_invalid(DISPUTE_SESSION, candidate_hash(), indices(&[1]));
+
+		assert_noop!(
+			ParasSlashing::cancel_slash(Origin::signed(1), DISPUTE_SESSION, candidate_hash()),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(ParasSlashing::cancel_slash(Origin::root(), DISPUTE_SESSION, candidate_hash()));
+		assert_last_event(Event::<Test>::SlashCancelled(DISPUTE_SESSION, candidate_hash()).into());
+		assert_noop!(
+			ParasSlashing::cancel_slash(Origin::root(), DISPUTE_SESSION, candidate_hash()),
+			Error::<Test>::NoUnappliedSlash,
+		);
+
+		start_session(5);
+		assert!(reported_offences().is_empty());
+	});
+}
+
+#[test]
+fn only_known_validators_on_the_losing_side_are_slashed() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		start_session(3);
+
+		ParasSlashing::punish_inconclusive(DISPUTE_SESSION, candidate_hash(), indices(&[0]));
+		ParasSlashing::punish_for_invalid(DISPUTE_SESSION, candidate_hash(), indices(&[5]));
+		ParasSlashing::punish_for_invalid(DISPUTE_SESSION + 1, candidate_hash(), indices(&[0]));
+		assert!(UnappliedSlashes::<Test>::iter().next().is_none());
+
+		start_session(5);
+		assert!(reported_offences().is_empty());
+	});
+}
+
+#[test]
+fn offenders_are_identified_in_the_era_of_the_dispute_session() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		let active_session = 2 * SESSIONS_PER_ERA;
+		assert_ne!(DISPUTE_SESSION / SESSIONS_PER_ERA, active_session / SESSIONS_PER_ERA);
+		start_session(active_session);
+
+		ParasSlashing::punish_for_invalid(DISPUTE_SESSION, candidate_hash(), indices(&[1, 3]));
+
+		start_session(active_session + SlashDeferSessions::get());
+		assert_eq!(
+			reported_offences(),
+			vec![(
+				*b"disputes:invalid",
+				DISPUTE_SESSION,
+				vec![(11, 0), (13, 0)],
+				SLASH_FOR_INVALID
+			)],
+		);
+	});
+}
@@ -17,8 +17,10 @@
 //! Mocks for all the traits.
 
 use crate::{
-	configuration, disputes, dmp, hrmp, inclusion, initializer, origin, paras, paras_inherent,
-	scheduler, session_info, shared,
+	configuration,
+	disputes::{self, slashing},
+	dmp, hrmp, inclusion, initializer, origin, paras, paras_inherent, scheduler, session_info,
+	shared,
 	ump::{self, MessageId, UmpSink},
 	ParaId,
 };
@@ -31,15 +33,19 @@ use frame_support::{
 use frame_support_test::TestRandomness;
 use parity_scale_codec::Decode;
 use primitives::v2::{
-	AuthorityDiscoveryId, Balance, BlockNumber, Header, Moment, SessionIndex, UpwardMessage,
-	ValidatorIndex,
+	AuthorityDiscoveryId, Balance, BlockNumber, CandidateHash, Header, Moment, SessionIndex,
+	UpwardMessage, ValidatorIndex,
 };
 use sp_core::H256;
 use sp_io::TestExternalities;
 use sp_runtime::{
 	traits::{BlakeTwo256, IdentityLookup},
 	transaction_validity::TransactionPriority,
-	KeyTypeId, Permill,
+	KeyTypeId, Perbill, Permill,
+};
+use sp_staking::{
+	offence::{Kind, Offence, OffenceError, ReportOffence},
+	EraIndex,
 };
 use std::{cell::RefCell, collections::HashMap};
 
@@ -67,6 +73,7 @@ frame_support::construct_runtime!(
 		ParachainsOrigin: origin,
 		SessionInfo: session_info,
 		Disputes: disputes,
+		ParasSlashing: slashing,
 		Babe: pallet_babe,
 	}
 );
@@ -266,6 +273,7 @@ impl crate::disputes::RewardValidators for Test {
 impl crate::disputes::PunishValidators for Test {
 	fn punish_for_invalid(
 		session: SessionIndex,
+		_: CandidateHash,
 		validators: impl IntoIterator<Item = ValidatorIndex>,
 	) {
 		PUNISH_VALIDATORS_FOR
@@ -274,6 +282,7 @@ impl crate::disputes::PunishValidators for Test {
 
 	fn punish_against_valid(
 		session: SessionIndex,
+		_: CandidateHash,
 		validators: impl IntoIterator<Item = ValidatorIndex>,
 	) {
 		PUNISH_VALIDATORS_AGAINST
@@ -282,6 +291,7 @@ impl crate::disputes::PunishValidators for Test {
 
 	fn punish_inconclusive(
 		session: SessionIndex,
+		_: CandidateHash,
 		validators: impl IntoIterator<Item = ValidatorIndex>,
 	) {
 		PUNISH_VALIDATORS_INCONCLUSIVE
@@ -289,6 +299,58 @@ impl crate::disputes::PunishValidators for Test {
 	}
 }
 
+parameter_types! {
+	pub const SlashDeferSessions: SessionIndex = 2;
+	pub const MaxSlashReportingWeight: Weight = 4;
+}
+
+/// The number of sessions in an era, as far as `TestIdentifyValidator` is concerned.
+pub const SESSIONS_PER_ERA: SessionIndex = 3;
+
+impl crate::disputes::slashing::Config for Test {
+	type Event = Event;
+	type KeyOwnerIdentification = (AccountId, EraIndex);
+	type IdentifyValidator = TestIdentifyValidator;
+	type HandleReports = TestReportOffences;
+	type SlashDeferSessions = SlashDeferSessions;
+	type MaxReportingWeight = MaxSlashReportingWeight;
+	type CancelOrigin = frame_system::EnsureRoot<AccountId>;
+	type WeightInfo = crate::disputes::slashing::TestWeightInfo;
+}
+
+thread_local! {
+	pub static REPORTED_OFFENCES: RefCell<Vec<(Kind, SessionIndex, Vec<(AccountId, EraIndex)>, Perbill)>> = RefCell::new(Vec::new());
+}
+
+/// Identifies validators with the era of the given session.
+pub struct TestIdentifyValidator;
+impl sp_runtime::traits::Convert<(SessionIndex, AccountId), Option<(AccountId, EraIndex)>>
+	for TestIdentifyValidator
+{
+	fn convert((session_index, a): (SessionIndex, AccountId)) -> Option<(AccountId, EraIndex)> {
+		Some((a, session_index / SESSIONS_PER_ERA))
+	}
+}
+
+pub struct TestReportOffences;
+impl<O: Offence<(AccountId, EraIndex)>> ReportOffence<AccountId, (AccountId, EraIndex), O>
+	for TestReportOffences
+{
+	fn report_offence(_reporters: Vec<AccountId>, offence: O) -> Result<(), OffenceError> {
+		let offenders = offence.offenders();
+		let slash_fraction =
+			O::slash_fraction(offenders.len() as u32, offence.validator_set_count());
+		REPORTED_OFFENCES.with(|r| {
+			r.borrow_mut().push((O::ID, offence.session_index(), offenders, slash_fraction))
+		});
+		Ok(())
+	}
+
+	fn is_known_offence(_offenders: &[(AccountId, EraIndex)], _time_slot: &O::TimeSlot) -> bool {
+		false
+	}
+}
+
 impl crate::scheduler::Config for Test {}
 
 impl crate::inclusion::Config for Test {
@@ -437,6 +499,7 @@ pub fn new_test_ext(state: MockGenesisConfig) -> TestExternalities {
 
 	BACKING_REWARDS.with(|r| r.borrow_mut().clear());
 	AVAILABILITY_REWARDS.with(|r| r.borrow_mut().clear());
+	REPORTED_OFFENCES.with(|r| r.borrow_mut().clear());
 
 	let mut t = state.system.build_storage::<Test>().unwrap();
 	state.configuration.assimilate_storage(&mut t).unwrap();
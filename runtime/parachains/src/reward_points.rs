@@ -14,8 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
-//! An implementation of the `RewardValidators` trait used by `inclusion` that employs
-//! `pallet-staking` to compute the rewards.
+//! An implementation of the `RewardValidators` traits used by `inclusion` and `disputes` that
+//! employs `pallet-staking` to compute the rewards.
 //!
 //! Based on <https://research.web3.foundation/en/latest/polkadot/overview/2-token-economics.html>
 //! which doesn't currently mention availability bitfields. As such, we don't reward them
@@ -23,11 +23,14 @@
 
 use crate::{session_info, shared};
 use frame_support::traits::ValidatorSet;
-use primitives::v2::ValidatorIndex;
+use primitives::v2::{SessionIndex, ValidatorIndex};
 
 /// The amount of era points given by backing a candidate that is included.
 pub const BACKING_POINTS: u32 = 20;
 
+/// The amount of era points given by participating in a dispute.
+pub const DISPUTE_STATEMENT_POINTS: u32 = 20;
+
 /// Rewards validators for participating in parachains with era points in pallet-staking.
 pub struct RewardValidatorsWithEraPoints<C>(sp_std::marker::PhantomData<C>);
 
@@ -61,3 +64,27 @@ where
 
 	fn reward_bitfields(_validators: impl IntoIterator<Item = ValidatorIndex>) {}
 }
+
+impl<C> crate::disputes::RewardValidators for RewardValidatorsWithEraPoints<C>
+where
+	C: pallet_staking::Config + session_info::Config,
+	C::ValidatorSet: ValidatorSet<C::AccountId, ValidatorId = C::AccountId>,
+{
+	fn reward_dispute_statement(
+		session: SessionIndex,
+		validators: impl IntoIterator<Item = ValidatorIndex>,
+	) {
+		// The validators are indexed in the session of the dispute, which might be a past one.
+		let account_keys = match session_info::Pallet::<C>::account_keys(&session) {
+			Some(account_keys) => account_keys,
+			None => return,
+		};
+
+		let rewards = validators
+			.into_iter()
+			.filter_map(|i| account_keys.get(i.0 as usize).cloned())
+			.map(|v| (v, DISPUTE_STATEMENT_POINTS));
+
+		<pallet_staking::Pallet<C>>::reward_by_ids(rewards);
+	}
+}
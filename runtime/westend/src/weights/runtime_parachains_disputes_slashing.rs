@@ -0,0 +1,69 @@
+// Copyright 2017-2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+//! Weights for `runtime_parachains::disputes::slashing`
+//!
+//! Not generated yet: only the storage accesses are accounted for, estimated from the reads
+//! and writes of `pallet-offences` and `pallet-staking` for `report_offence`, until this file
+//! is replaced by the output of
+//!
+//! ./target/production/polkadot
+//! benchmark
+//! pallet
+//! --chain=westend-dev
+//! --steps=50
+//! --repeat=20
+//! --pallet=runtime_parachains::disputes::slashing
+//! --extrinsic=*
+//! --execution=wasm
+//! --wasm-execution=compiled
+//! --heap-pages=4096
+//! --header=./file_header.txt
+//! --output=./runtime/westend/src/weights/runtime_parachains_disputes_slashing.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for `runtime_parachains::disputes::slashing`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> runtime_parachains::disputes::slashing::WeightInfo for WeightInfo<T> {
+	// Storage: ParasSlashing UnappliedSlashes (r:1 w:1)
+	fn cancel_slash() -> Weight {
+		(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Offences ConcurrentReportsIndex (r:1 w:1)
+	// Storage: Offences Reports (r:1 w:1)
+	// Storage: Staking SlashRewardFraction (r:1 w:0)
+	// Storage: Staking ActiveEra (r:1 w:0)
+	// Storage: Staking ErasStartSessionIndex (r:1 w:0)
+	// Storage: Staking Invulnerables (r:1 w:0)
+	// Storage: Staking ValidatorSlashInEra (r:1 w:1)
+	// Storage: Staking SlashingSpans (r:1 w:1)
+	// Storage: Staking SpanSlash (r:1 w:1)
+	// Storage: Staking Validators (r:1 w:1)
+	// Storage: Staking UnappliedSlashes (r:1 w:1)
+	// Storage: Session DisabledValidators (r:1 w:1)
+	fn report_offence(n: u32, ) -> Weight {
+		(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().reads((9 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((8 as Weight).saturating_mul(n as Weight)))
+	}
+}